@@ -10,6 +10,8 @@ statements=(
     "cargo test --no-default-features"
 
     "cargo doc --no-deps --no-default-features"
+
+    "cargo build --package timelock_wasm --target wasm32-unknown-unknown"
 )
 
 # loop echo and executing statements
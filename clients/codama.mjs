@@ -0,0 +1,55 @@
+// Renders the clients of the program from its shank IDL, run by
+// `cargo xtask codegen` with the IDL and the instruction discriminators.
+import { readFileSync } from 'node:fs';
+import path from 'node:path';
+import { fileURLToPath } from 'node:url';
+
+import {
+  bytesTypeNode,
+  bytesValueNode,
+  camelCase,
+  createFromRoot,
+  deleteNodesVisitor,
+  fixedSizeTypeNode,
+  updateInstructionsVisitor,
+} from 'codama';
+import { rootNodeFromAnchor } from '@codama/nodes-from-anchor';
+import { renderVisitor as renderJs } from '@codama/renderers-js';
+import { renderVisitor as renderRust } from '@codama/renderers-rust';
+
+const [idlPath, discriminatorsPath] = process.argv.slice(2);
+const idl = JSON.parse(readFileSync(idlPath, 'utf8'));
+const discriminators = JSON.parse(readFileSync(discriminatorsPath, 'utf8'));
+const clients = path.dirname(fileURLToPath(import.meta.url));
+
+const codama = createFromRoot(rootNodeFromAnchor(idl));
+
+// emitted by the program to itself, not for clients
+codama.update(deleteNodesVisitor(['[instructionNode]emitEvent']));
+
+// shank describes one byte tags, the program expects 8-byte discriminators
+codama.update(
+  updateInstructionsVisitor(
+    Object.fromEntries(
+      Object.entries(discriminators).map(([name, hex]) => [
+        camelCase(name),
+        {
+          arguments: {
+            discriminator: {
+              type: fixedSizeTypeNode(bytesTypeNode(), 8),
+              defaultValue: bytesValueNode('base16', hex),
+            },
+          },
+        },
+      ]),
+    ),
+  ),
+);
+
+codama.accept(renderJs(path.join(clients, 'js', 'src', 'generated')));
+codama.accept(
+  renderRust(path.join(clients, 'rust', 'src', 'generated'), {
+    crateFolder: path.join(clients, 'rust'),
+    formatCode: true,
+  }),
+);
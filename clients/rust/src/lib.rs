@@ -0,0 +1,6 @@
+//! Rust thin client of the time lock program, without the program and its
+//! dependencies. Generated by `cargo xtask codegen` into [`generated`].
+
+mod generated;
+
+pub use generated::*;
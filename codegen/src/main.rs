@@ -0,0 +1,98 @@
+//! IDL and client code generation of the time lock program.
+//!
+//! Usage: `cargo xtask idl` or `cargo xtask codegen`
+//!
+//! `idl` parses `timelock_interface`, where the accounts and instructions of
+//! the program are declared, into its shank IDL at
+//! `target/idl/timelock_program.json`. The program build never writes it, so
+//! building stays free of the parser and of writes outside `OUT_DIR`.
+//!
+//! `codegen` generates the IDL first. Shank describes one byte instruction
+//! tags, so the 8-byte discriminators are computed here and handed to
+//! `clients/codama.mjs` along with the IDL. It renders the TypeScript client
+//! into `clients/js/src/generated` and the Rust thin client into
+//! `clients/rust/src/generated`, and needs `node` with the dependencies of
+//! `clients/package.json` installed.
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde_json::{Map, Value};
+use shank_idl::{extract_idl, ParseIdlOpts};
+use timelock_interface::schema::instruction_prefix;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+fn main() -> Result<()> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("codegen is in the workspace");
+    match std::env::args().nth(1).as_deref() {
+        Some("idl") => idl(root).map(|path| println!("{}", path.display())),
+        Some("codegen") => codegen(root),
+        _ => Err("usage: cargo xtask idl|codegen".into()),
+    }
+}
+
+/// Write the shank IDL of the program, returning its path
+fn idl(root: &Path) -> Result<PathBuf> {
+    let lib = root.join("timelock_interface/src/lib.rs");
+    let mut idl = extract_idl(
+        lib.to_str().ok_or("non utf8 crate path")?,
+        ParseIdlOpts {
+            require_program_address: false,
+            ..Default::default()
+        },
+    )?
+    .ok_or("the program has no IDL items")?;
+    idl.name = "timelock_program".to_string();
+    let dir = root.join("target/idl");
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join("timelock_program.json");
+    std::fs::write(&path, idl.try_into_json()?)?;
+    Ok(path)
+}
+
+fn codegen(root: &Path) -> Result<()> {
+    let idl_path = idl(root)?;
+    let idl: Value = serde_json::from_str(&std::fs::read_to_string(&idl_path)?)?;
+    let mut discriminators = Map::new();
+    for ix in idl["instructions"]
+        .as_array()
+        .ok_or("IDL without instructions")?
+    {
+        let name = ix["name"].as_str().ok_or("instruction without a name")?;
+        // emitted by the program to itself, dropped from the clients
+        if name == "EmitEvent" {
+            continue;
+        }
+        let hex: String = instruction_prefix(name)
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        discriminators.insert(name.to_string(), hex.into());
+    }
+    let out = root.join("target/codegen");
+    std::fs::create_dir_all(&out)?;
+    let discriminators_path = out.join("discriminators.json");
+    std::fs::write(
+        &discriminators_path,
+        serde_json::to_string_pretty(&discriminators)?,
+    )?;
+
+    let clients = root.join("clients");
+    run(Command::new("node")
+        .arg(clients.join("codama.mjs"))
+        .arg(&idl_path)
+        .arg(&discriminators_path)
+        .current_dir(&clients))
+}
+
+fn run(command: &mut Command) -> Result<()> {
+    let status = command.status()?;
+    if !status.success() {
+        return Err(format!("{command:?} failed with {status}").into());
+    }
+    Ok(())
+}
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use timelock_interface::TimeLockInstruction;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(ix) = TimeLockInstruction::unpack(data) {
+        // whatever decodes must survive a round trip
+        assert_eq!(TimeLockInstruction::unpack(&ix.pack()).unwrap(), ix);
+    }
+});
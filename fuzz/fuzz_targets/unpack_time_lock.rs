@@ -0,0 +1,14 @@
+#![no_main]
+
+use borsh::BorshDeserialize as _;
+use libfuzzer_sys::fuzz_target;
+use timelock_interface::TimeLockAccount;
+
+fuzz_target!(|data: &[u8]| {
+    let account = TimeLockAccount::try_from_slice(data);
+    assert_eq!(account.is_ok(), TimeLockAccount::unpack(data).is_ok());
+    if let Ok(account) = account {
+        assert_eq!(data.len(), TimeLockAccount::LEN);
+        assert_eq!(account.pack(), data);
+    }
+});
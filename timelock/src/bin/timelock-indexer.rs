@@ -0,0 +1,32 @@
+//! Postgres indexer of time locks.
+//!
+//! Usage: `timelock-indexer [--backfill] <indexer.toml>`
+//!
+//! With `--backfill`, the historical transactions are indexed and the
+//! indexer exits instead of following new ones.
+//!
+//! ```toml
+//! rpc_url = "https://api.mainnet-beta.solana.com"
+//! ws_url = "wss://api.mainnet-beta.solana.com"
+//! program_id = "..."
+//! database_url = "host=localhost user=postgres"
+//! ```
+
+use timelock::indexer::{self, IndexerConfig};
+
+#[tokio::main]
+async fn main() -> timelock::Result<()> {
+    tracing_subscriber::fmt::init();
+    let mut args = std::env::args().skip(1).peekable();
+    let backfill_only = args.next_if(|a| a == "--backfill").is_some();
+    let path = args.next().unwrap_or_else(|| "indexer.toml".to_string());
+    let config = IndexerConfig::load(path)?;
+    if backfill_only {
+        let db = indexer::connect(&config.database_url).await?;
+        let count = indexer::backfill(&config, &db).await?;
+        println!("backfilled {count} transaction(s)");
+        Ok(())
+    } else {
+        indexer::run(config).await
+    }
+}
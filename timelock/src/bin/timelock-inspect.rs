@@ -0,0 +1,39 @@
+//! Show the time lock instructions of a transaction before signing it.
+//!
+//! Usage: `timelock-inspect <rpc_url> <program_id> <signature | base64 transaction>`
+//!
+//! A signature is fetched from the cluster, anything else is decoded as a
+//! serialized transaction, as multisig tools export them for approval.
+
+use std::str::FromStr as _;
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use timelock::inspect;
+use timelock::Error;
+
+#[tokio::main]
+async fn main() -> timelock::Result<()> {
+    tracing_subscriber::fmt::init();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [rpc_url, program_id, input] = args.as_slice() else {
+        return Err(Error::Config(
+            "usage: timelock-inspect <rpc_url> <program_id> <signature | base64 transaction>"
+                .into(),
+        ));
+    };
+    let program_id = Pubkey::from_str(program_id)
+        .map_err(|e| Error::Config(format!("invalid program id: {e}")))?;
+    let rpc = RpcClient::new(rpc_url.to_string());
+    let tx = inspect::load_transaction(&rpc, input).await?;
+    let instructions = inspect::inspect(&program_id, &tx.message);
+    println!(
+        "fee payer: {}",
+        tx.message.static_account_keys().first().copied().unwrap_or_default()
+    );
+    println!("{} time lock instruction(s)", instructions.len());
+    for instruction in instructions {
+        print!("{instruction}");
+    }
+    Ok(())
+}
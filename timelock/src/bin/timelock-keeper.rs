@@ -0,0 +1,39 @@
+//! Keeper daemon unlocking matured time locks.
+//!
+//! Usage: `timelock-keeper [--dry-run <hours>] <config.toml>`
+//!
+//! With `--dry-run`, nothing is sent: the unlocks due within the next
+//! `hours`, their expected fees and payouts are printed instead.
+
+use std::time::Duration;
+
+use timelock::config::Config;
+use timelock::Error;
+
+#[tokio::main]
+async fn main() -> timelock::Result<()> {
+    tracing_subscriber::fmt::init();
+    let mut args = std::env::args().skip(1).peekable();
+    let dry_run = match args.peek().map(String::as_str) {
+        Some("--dry-run") => {
+            args.next();
+            let hours: u64 = args
+                .next()
+                .and_then(|h| h.parse().ok())
+                .ok_or_else(|| Error::Config("--dry-run expects a number of hours".into()))?;
+            Some(Duration::from_secs(hours * 3600))
+        }
+        _ => None,
+    };
+    let path = args.next().unwrap_or_else(|| "keeper.toml".to_string());
+    let config = Config::load(path)?;
+    match dry_run {
+        Some(horizon) => {
+            for plan in timelock::dry_run::dry_run(&config, horizon).await? {
+                println!("{plan}");
+            }
+            Ok(())
+        }
+        None => timelock::keeper::run(config).await,
+    }
+}
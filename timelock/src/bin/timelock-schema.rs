@@ -0,0 +1,90 @@
+//! Dumps the borsh schemas of the time lock accounts, instructions and events
+//! to JSON, for client generators in other languages.
+//!
+//! Usage: `timelock-schema [schema.json]`, printing to stdout without a path.
+//!
+//! `roots` lists the declarations of the instruction, the accounts and the
+//! event, `definitions` every type they reach. `instruction_prefixes` maps
+//! each instruction variant to the bytes replacing its one byte tag in the
+//! instruction data.
+
+use borsh::schema::{Definition, Fields};
+use borsh::schema_container_of;
+use serde_json::{json, Map, Value};
+use timelock_program::{schema, TimeLockEvent};
+
+fn main() -> timelock::Result<()> {
+    let mut containers = schema::containers();
+    containers.push(schema_container_of::<TimeLockEvent>());
+    let mut roots = vec![];
+    let mut definitions = Map::new();
+    let mut prefixes = Map::new();
+    for container in &containers {
+        roots.push(json!(container.declaration()));
+        for (declaration, definition) in container.definitions() {
+            definitions.insert(declaration.clone(), definition_json(definition));
+        }
+    }
+    if let Some(Definition::Enum { variants, .. }) =
+        containers[0].get_definition(containers[0].declaration())
+    {
+        for (_, name, _) in variants {
+            prefixes.insert(name.clone(), json!(schema::instruction_prefix(name)));
+        }
+    }
+    let dump = json!({
+        "roots": roots,
+        "definitions": definitions,
+        "instruction_prefixes": prefixes,
+    });
+    let text = serde_json::to_string_pretty(&dump).expect("json values always serialize");
+    match std::env::args().nth(1) {
+        Some(path) => std::fs::write(path, text)?,
+        None => println!("{text}"),
+    }
+    Ok(())
+}
+
+fn definition_json(definition: &Definition) -> Value {
+    match definition {
+        Definition::Primitive(width) => json!({ "primitive": width }),
+        Definition::Sequence {
+            length_width,
+            length_range,
+            elements,
+        } => json!({
+            "sequence": {
+                "length_width": length_width,
+                "length_range": [length_range.start(), length_range.end()],
+                "elements": elements,
+            }
+        }),
+        Definition::Tuple { elements } => json!({ "tuple": elements }),
+        Definition::Enum {
+            tag_width,
+            variants,
+        } => json!({
+            "enum": {
+                "tag_width": tag_width,
+                "variants": variants
+                    .iter()
+                    .map(|(tag, name, declaration)| json!([tag, name, declaration]))
+                    .collect::<Vec<_>>(),
+            }
+        }),
+        Definition::Struct { fields } => json!({ "struct": fields_json(fields) }),
+    }
+}
+
+fn fields_json(fields: &Fields) -> Value {
+    match fields {
+        Fields::NamedFields(fields) => json!({
+            "named": fields
+                .iter()
+                .map(|(name, declaration)| json!([name, declaration]))
+                .collect::<Vec<_>>(),
+        }),
+        Fields::UnnamedFields(fields) => json!({ "unnamed": fields }),
+        Fields::Empty => json!("empty"),
+    }
+}
@@ -0,0 +1,50 @@
+//! Export the accounts of a time lock deployment, or import them into a
+//! localnet.
+//!
+//! Usage:
+//! - `timelock-snapshot export <rpc_url> <program_id> <snapshot.json.gz>`
+//! - `timelock-snapshot import <snapshot.json.gz> <account_dir>`
+//!
+//! The account directory is loaded with
+//! `solana-test-validator --account-dir <account_dir>`.
+
+use std::str::FromStr as _;
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use timelock::snapshot::{self, Snapshot};
+use timelock::Error;
+
+#[tokio::main]
+async fn main() -> timelock::Result<()> {
+    tracing_subscriber::fmt::init();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["export", rpc_url, program_id, path] => {
+            let program_id = Pubkey::from_str(program_id)
+                .map_err(|e| Error::Config(format!("invalid program id: {e}")))?;
+            let rpc = RpcClient::new(rpc_url.to_string());
+            let snapshot = snapshot::export(&rpc, &program_id).await?;
+            snapshot.save(path)?;
+            println!(
+                "exported {} account(s) at slot {}",
+                snapshot.accounts.len(),
+                snapshot.slot
+            );
+            Ok(())
+        }
+        ["import", path, dir] => {
+            let snapshot = Snapshot::load(path)?;
+            snapshot.write_account_dir(dir)?;
+            println!(
+                "wrote {} account file(s), start the validator with --account-dir {dir}",
+                snapshot.accounts.len()
+            );
+            Ok(())
+        }
+        _ => Err(Error::Config(
+            "usage: timelock-snapshot export <rpc_url> <program_id> <archive> | import <archive> <account_dir>"
+                .into(),
+        )),
+    }
+}
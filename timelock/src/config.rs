@@ -0,0 +1,133 @@
+//! Keeper configuration file.
+//!
+//! ```toml
+//! metrics_addr = "0.0.0.0:9100"
+//!
+//! [notifiers]
+//! discord_webhook = "https://discord.com/api/webhooks/..."
+//! telegram = { bot_token = "...", chat_id = "..." }
+//!
+//! [[targets]]
+//! name = "mainnet"
+//! rpc_url = "https://api.mainnet-beta.solana.com"
+//! program_id = "..."
+//! wallets = ["keeper-1.json", "keeper-2.json"]
+//! max_concurrency = 4
+//! poll_interval_secs = 30
+//! grace_period_secs = 3600
+//! # optional, submit unlocks as jito bundles
+//! jito = { block_engine_url = "https://mainnet.block-engine.jito.wtf", tip_lamports = 1000 }
+//! ```
+
+use std::net::SocketAddr;
+use std::path::Path;
+use std::str::FromStr as _;
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::keeper::KeeperConfig;
+use crate::notifier::{Discord, Notifiers, Telegram};
+use crate::{Error, Result};
+
+/// Settings of a whole keeper process.
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    /// Address to serve prometheus metrics on, disabled when absent.
+    pub metrics_addr: Option<SocketAddr>,
+    /// Where alerts and unlock notifications are sent.
+    #[serde(default)]
+    pub notifiers: NotifierConfig,
+    /// Clusters and program deployments to watch.
+    pub targets: Vec<KeeperConfig>,
+}
+
+impl Config {
+    /// Read the TOML config file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)?;
+        toml::from_str(&raw).map_err(|e| Error::Config(e.to_string()))
+    }
+}
+
+/// Notification backends to enable.
+#[derive(Debug, Default, Deserialize)]
+pub struct NotifierConfig {
+    /// Discord webhook url.
+    pub discord_webhook: Option<String>,
+    /// Telegram bot credentials.
+    pub telegram: Option<TelegramConfig>,
+}
+
+/// Telegram bot credentials.
+#[derive(Debug, Deserialize)]
+pub struct TelegramConfig {
+    /// Token of the bot sending messages.
+    pub bot_token: String,
+    /// Chat the messages are sent to.
+    pub chat_id: String,
+}
+
+impl NotifierConfig {
+    /// Build the configured backends.
+    pub fn build(&self) -> Notifiers {
+        let mut notifiers = Notifiers::new();
+        if let Some(url) = &self.discord_webhook {
+            notifiers = notifiers.with(Discord::new(url));
+        }
+        if let Some(telegram) = &self.telegram {
+            notifiers = notifiers.with(Telegram::new(&telegram.bot_token, &telegram.chat_id));
+        }
+        notifiers
+    }
+}
+
+pub(crate) fn pubkey<'de, D: Deserializer<'de>>(d: D) -> std::result::Result<Pubkey, D::Error> {
+    let s = String::deserialize(d)?;
+    Pubkey::from_str(&s).map_err(serde::de::Error::custom)
+}
+
+pub(crate) fn secs<'de, D: Deserializer<'de>>(d: D) -> std::result::Result<Duration, D::Error> {
+    u64::deserialize(d).map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let program_id = Pubkey::new_unique();
+        let raw = format!(
+            r#"
+            metrics_addr = "127.0.0.1:9100"
+
+            [[targets]]
+            name = "devnet"
+            rpc_url = "https://api.devnet.solana.com"
+            program_id = "{program_id}"
+            wallets = ["a.json", "b.json"]
+            max_concurrency = 2
+            poll_interval_secs = 10
+            grace_period_secs = 60
+
+            [[targets]]
+            name = "localnet"
+            rpc_url = "http://127.0.0.1:8899"
+            program_id = "{program_id}"
+            wallets = ["c.json"]
+            "#
+        );
+        let config: Config = toml::from_str(&raw).unwrap();
+        assert_eq!(config.targets.len(), 2);
+        let devnet = &config.targets[0];
+        assert_eq!(devnet.program_id, program_id);
+        assert_eq!(devnet.wallets.len(), 2);
+        assert_eq!(devnet.max_concurrency, 2);
+        assert_eq!(devnet.poll_interval, Duration::from_secs(10));
+        let localnet = &config.targets[1];
+        assert_eq!(localnet.max_concurrency, 4);
+        assert!(config.notifiers.discord_webhook.is_none());
+    }
+}
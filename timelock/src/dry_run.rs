@@ -0,0 +1,161 @@
+//! Keeper dry-run: report what the keeper would do without sending anything.
+
+use std::fmt;
+use std::time::Duration;
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::message::Message;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::config::Config;
+use crate::jito::tip_instruction;
+use crate::keeper::{try_unlock_instruction, unix_now, KeeperConfig};
+use crate::rpc::fetch_locks;
+use crate::{Error, Result};
+
+/// An unlock the keeper would submit within the horizon.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedUnlock {
+    /// Address of the time lock account.
+    pub lock: Pubkey,
+    /// Unix timestamp the lock matures at.
+    pub timestamp: i64,
+    /// Bounty the keeper would earn.
+    pub bounty: u64,
+}
+
+/// Actions planned for one target.
+#[derive(Debug, Clone)]
+pub struct Plan {
+    /// Name of the target.
+    pub target: String,
+    /// How far ahead the plan looks.
+    pub horizon: Duration,
+    /// Unlocks due within the horizon, earliest first.
+    pub unlocks: Vec<PlannedUnlock>,
+    /// Estimated lamports paid per unlock, transaction fee and jito tip.
+    pub cost_per_unlock: u64,
+}
+
+impl Plan {
+    /// Lamports expected to be spent on fees and tips.
+    pub fn expected_fees(&self) -> u64 {
+        self.cost_per_unlock * self.unlocks.len() as u64
+    }
+
+    /// Lamports expected to be earned in bounties.
+    pub fn expected_payouts(&self) -> u64 {
+        self.unlocks.iter().map(|u| u.bounty).sum()
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "[{}] {} unlock(s) due in the next {}h",
+            self.target,
+            self.unlocks.len(),
+            self.horizon.as_secs() / 3600
+        )?;
+        for unlock in &self.unlocks {
+            writeln!(
+                f,
+                "  {} at {} (bounty {} lamports)",
+                unlock.lock, unlock.timestamp, unlock.bounty
+            )?;
+        }
+        writeln!(f, "  expected fees:    {} lamports", self.expected_fees())?;
+        write!(
+            f,
+            "  expected payouts: {} lamports",
+            self.expected_payouts()
+        )
+    }
+}
+
+/// Plan every target of `config` for the next `horizon`.
+pub async fn dry_run(config: &Config, horizon: Duration) -> Result<Vec<Plan>> {
+    let mut plans = vec![];
+    for target in &config.targets {
+        plans.push(plan(target, horizon).await?);
+    }
+    Ok(plans)
+}
+
+/// Plan the unlocks of `target` for the next `horizon`.
+pub async fn plan(target: &KeeperConfig, horizon: Duration) -> Result<Plan> {
+    let rpc = RpcClient::new_with_commitment(target.rpc_url.clone(), CommitmentConfig::confirmed());
+    let until = unix_now().saturating_add(horizon.as_secs() as i64);
+    let mut unlocks: Vec<_> = target
+        .backoff
+        .retry(|| fetch_locks(&rpc, &target.program_id))
+        .await?
+        .into_iter()
+        .filter(|(_, data)| data.timestamp <= until && data.bounty > 0)
+        .map(|(lock, data)| PlannedUnlock {
+            lock,
+            timestamp: data.timestamp,
+            bounty: data.bounty,
+        })
+        .collect();
+    unlocks.sort_by_key(|u| u.timestamp);
+
+    // the fee does not depend on which wallet pays, any key gives the same estimate
+    let payer = Pubkey::new_unique();
+    let mut ixs = vec![try_unlock_instruction(
+        &target.program_id,
+        &Pubkey::new_unique(),
+        &payer,
+    )];
+    let tip = target.jito.as_ref().map_or(0, |jito| jito.tip_lamports);
+    if target.jito.is_some() {
+        ixs.push(tip_instruction(&payer, tip));
+    }
+    let blockhash = target
+        .backoff
+        .retry(|| async { rpc.get_latest_blockhash().await.map_err(Error::from) })
+        .await?;
+    let message = Message::new_with_blockhash(&ixs, Some(&payer), &blockhash);
+    let fee = target
+        .backoff
+        .retry(|| async { rpc.get_fee_for_message(&message).await.map_err(Error::from) })
+        .await?;
+
+    Ok(Plan {
+        target: target.name.clone(),
+        horizon,
+        unlocks,
+        cost_per_unlock: fee + tip,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_totals() {
+        let plan = Plan {
+            target: "localnet".to_string(),
+            horizon: Duration::from_secs(3600),
+            unlocks: vec![
+                PlannedUnlock {
+                    lock: Pubkey::new_unique(),
+                    timestamp: 1,
+                    bounty: 10_000,
+                },
+                PlannedUnlock {
+                    lock: Pubkey::new_unique(),
+                    timestamp: 2,
+                    bounty: 20_000,
+                },
+            ],
+            cost_per_unlock: 5000,
+        };
+        assert_eq!(plan.expected_fees(), 10_000);
+        assert_eq!(plan.expected_payouts(), 30_000);
+        assert!(plan.to_string().contains("2 unlock(s) due in the next 1h"));
+    }
+}
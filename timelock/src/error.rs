@@ -0,0 +1,74 @@
+use solana_client::client_error::ClientError;
+use solana_pubsub_client::nonblocking::pubsub_client::PubsubClientError;
+use solana_sdk::transaction::TransactionError;
+
+/// Errors raised by the off-chain timelock tooling.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// An HTTP request to an external service failed.
+    #[error("http request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    /// An external service answered, but rejected the request.
+    #[error("{service} rejected the request: {reason}")]
+    Rejected {
+        /// Name of the service, e.g. `discord`.
+        service: &'static str,
+        /// Reason reported by the service.
+        reason: String,
+    },
+    /// A solana RPC request failed.
+    #[error("rpc request failed: {0}")]
+    Rpc(Box<ClientError>),
+    /// A solana websocket subscription failed.
+    #[error("websocket subscription failed: {0}")]
+    Pubsub(Box<PubsubClientError>),
+    /// A postgres query failed.
+    #[error("postgres query failed: {0}")]
+    Postgres(#[from] tokio_postgres::Error),
+    /// The configuration is invalid.
+    #[error("invalid config: {0}")]
+    Config(String),
+    /// A signer failed or is missing.
+    #[error("signing failed: {0}")]
+    Signer(String),
+    /// An account snapshot archive is not supported.
+    #[error("invalid snapshot: {0}")]
+    Snapshot(String),
+    /// An I/O operation, including borsh and JSON decoding, failed.
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl From<ClientError> for Error {
+    fn from(e: ClientError) -> Self {
+        Self::Rpc(Box::new(e))
+    }
+}
+
+impl From<PubsubClientError> for Error {
+    fn from(e: PubsubClientError) -> Self {
+        Self::Pubsub(Box::new(e))
+    }
+}
+
+impl Error {
+    /// Whether retrying the failed operation may succeed.
+    pub(crate) fn is_transient(&self) -> bool {
+        match self {
+            Error::Http(_) | Error::Pubsub(_) => true,
+            Error::Rpc(e) => matches!(
+                e.get_transaction_error(),
+                None | Some(TransactionError::BlockhashNotFound)
+            ),
+            Error::Rejected { .. }
+            | Error::Postgres(_)
+            | Error::Config(_)
+            | Error::Signer(_)
+            | Error::Snapshot(_)
+            | Error::Io(_) => false,
+        }
+    }
+}
+
+/// Result alias using the crate [`Error`].
+pub type Result<T> = std::result::Result<T, Error>;
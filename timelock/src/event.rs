@@ -0,0 +1,72 @@
+//! Decoding of the events the program logs.
+
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use borsh::BorshDeserialize as _;
+pub use timelock_program::TimeLockEvent;
+use timelock_program::EMIT_EVENT_TAG;
+
+/// Prefix of the log lines written by `sol_log_data`.
+const DATA_PREFIX: &str = "Program data: ";
+
+/// Decode one log line, `None` if it is not a timelock event.
+pub fn parse_log(line: &str) -> Option<TimeLockEvent> {
+    let data = line.strip_prefix(DATA_PREFIX)?;
+    let bytes = BASE64_STANDARD.decode(data).ok()?;
+    TimeLockEvent::try_from_slice(&bytes).ok()
+}
+
+/// Decode every timelock event in the logs of a transaction.
+///
+/// Callers should only pass logs of transactions invoking the timelock
+/// program, other programs may log data that happens to decode.
+pub fn parse_logs<S: AsRef<str>>(logs: &[S]) -> Vec<TimeLockEvent> {
+    logs.iter().filter_map(|l| parse_log(l.as_ref())).collect()
+}
+
+/// Decode the data of a self-CPI inner instruction, `None` if it does not carry an event.
+///
+/// Unlike logs, inner instructions are never truncated. Callers should only
+/// pass inner instructions invoking the timelock program.
+pub fn parse_inner_instruction(data: &[u8]) -> Option<TimeLockEvent> {
+    match data.split_first()? {
+        (&EMIT_EVENT_TAG, event) => TimeLockEvent::try_from_slice(event).ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+
+    #[test]
+    fn test_parse_logs() {
+        let event = TimeLockEvent::LockCreated {
+            lock: Pubkey::new_unique(),
+            payer: Pubkey::new_unique(),
+            timestamp: 42,
+            bounty: 5000,
+        };
+        let logs = vec![
+            "Program log: Instruction: InitializeTimeLock".to_string(),
+            format!(
+                "{DATA_PREFIX}{}",
+                BASE64_STANDARD.encode(borsh::to_vec(&event).unwrap())
+            ),
+        ];
+        assert_eq!(parse_logs(&logs), vec![event]);
+    }
+
+    #[test]
+    fn test_parse_inner_instruction() {
+        let event = TimeLockEvent::BountyPaid {
+            lock: Pubkey::new_unique(),
+            cranker: Pubkey::new_unique(),
+            amount: 5000,
+        };
+        let mut data = vec![EMIT_EVENT_TAG];
+        data.extend(borsh::to_vec(&event).unwrap());
+        assert_eq!(parse_inner_instruction(&data), Some(event));
+        assert_eq!(parse_inner_instruction(&[1]), None);
+    }
+}
@@ -0,0 +1,104 @@
+//! `getProgramAccounts` filters finding accounts of the program by their
+//! fields, at the offsets `timelock_program` publishes for its layouts.
+//!
+//! Combine a filter selecting the account type with field filters and pass
+//! them to [`fetch_accounts`](crate::rpc::fetch_accounts):
+//!
+//! ```no_run
+//! # async fn example(
+//! #     rpc: &solana_client::nonblocking::rpc_client::RpcClient,
+//! #     program_id: &solana_sdk::pubkey::Pubkey,
+//! #     wallet: &solana_sdk::pubkey::Pubkey,
+//! # ) -> timelock::Result<()> {
+//! use timelock::filter;
+//! use timelock::rpc::fetch_accounts;
+//! use timelock_program::EscrowAccount;
+//!
+//! let filters = vec![filter::escrows(), filter::escrow_beneficiary(wallet)];
+//! let escrows = fetch_accounts::<EscrowAccount>(rpc, program_id, filters).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::pubkey::Pubkey;
+use timelock_program::{EscrowAccount, TimeLockAccount, VaultLockAccount};
+
+fn memcmp(offset: usize, bytes: &[u8]) -> RpcFilterType {
+    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(offset, bytes))
+}
+
+/// Time locks, told apart from the other accounts by their size.
+pub fn time_locks() -> RpcFilterType {
+    RpcFilterType::DataSize(TimeLockAccount::LEN as u64)
+}
+
+/// Time locks of `authority`, their payer unless it changed.
+pub fn time_lock_authority(authority: &Pubkey) -> RpcFilterType {
+    memcmp(TimeLockAccount::AUTHORITY_OFFSET, authority.as_ref())
+}
+
+/// Time locks whose secret was never revealed.
+pub fn unrevealed() -> RpcFilterType {
+    memcmp(TimeLockAccount::REVEALED_AT_OFFSET, &0i64.to_le_bytes())
+}
+
+/// Escrows, told apart from the other accounts by their size. Escrows are
+/// closed once claimed or refunded.
+pub fn escrows() -> RpcFilterType {
+    RpcFilterType::DataSize(EscrowAccount::LEN as u64)
+}
+
+/// Escrows funded by `depositor`.
+pub fn escrow_depositor(depositor: &Pubkey) -> RpcFilterType {
+    memcmp(EscrowAccount::DEPOSITOR_OFFSET, depositor.as_ref())
+}
+
+/// Escrows `beneficiary` may claim.
+pub fn escrow_beneficiary(beneficiary: &Pubkey) -> RpcFilterType {
+    memcmp(EscrowAccount::BENEFICIARY_OFFSET, beneficiary.as_ref())
+}
+
+/// Vault locks, told apart from the other accounts by their size. Vault
+/// locks are closed once claimed.
+pub fn vault_locks() -> RpcFilterType {
+    RpcFilterType::DataSize(VaultLockAccount::LEN as u64)
+}
+
+/// Vault locks drawing on `vault`.
+pub fn vault_lock_vault(vault: &Pubkey) -> RpcFilterType {
+    memcmp(VaultLockAccount::VAULT_OFFSET, vault.as_ref())
+}
+
+/// Vault locks `beneficiary` may claim.
+pub fn vault_lock_beneficiary(beneficiary: &Pubkey) -> RpcFilterType {
+    memcmp(VaultLockAccount::BENEFICIARY_OFFSET, beneficiary.as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allows(filter: RpcFilterType, data: &[u8]) -> bool {
+        match filter {
+            RpcFilterType::DataSize(size) => data.len() as u64 == size,
+            RpcFilterType::Memcmp(memcmp) => memcmp.bytes_match(data),
+            _ => unreachable!("only sizes and memcmps are built"),
+        }
+    }
+
+    #[test]
+    fn test_filters_match_accounts() {
+        let authority = Pubkey::new_unique();
+        let mut lock = TimeLockAccount::unpack(&[0; TimeLockAccount::LEN]).unwrap();
+        lock.authority = authority;
+        let data = lock.pack();
+        assert!(allows(time_locks(), &data));
+        assert!(allows(time_lock_authority(&authority), &data));
+        assert!(!allows(time_lock_authority(&Pubkey::new_unique()), &data));
+        assert!(allows(unrevealed(), &data));
+        lock.revealed_at = 1;
+        assert!(!allows(unrevealed(), &lock.pack()));
+        assert!(!allows(escrows(), &data));
+    }
+}
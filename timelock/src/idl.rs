@@ -0,0 +1,124 @@
+//! Publishing the program IDL on chain, where explorers look it up.
+//!
+//! The IDL account follows the Anchor convention: it lives at
+//! [`idl_address`] and holds the zlib compressed IDL json after a header.
+//! Only the upgrade authority of the program can create it.
+
+use std::io::Write as _;
+
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::bpf_loader_upgradeable;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer as _};
+use solana_sdk::system_program;
+use solana_sdk::transaction::Transaction;
+pub use timelock_program::idl_address;
+use timelock_program::{IdlInstruction, IDL_IX_TAG};
+
+use crate::Result;
+
+/// Bytes of compressed IDL written per transaction.
+pub const WRITE_CHUNK_LEN: usize = 900;
+
+fn idl_instruction(
+    program_id: &Pubkey,
+    ix: &IdlInstruction,
+    accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let mut data = IDL_IX_TAG.to_vec();
+    data.extend(borsh::to_vec(ix).expect("serializing an instruction never fails"));
+    Instruction::new_with_bytes(*program_id, &data, accounts)
+}
+
+/// Create the IDL account with room for `data_len` bytes, signed by the
+/// upgrade authority of the program.
+pub fn create_idl_instruction(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    data_len: u64,
+) -> Instruction {
+    let base = Pubkey::find_program_address(&[], program_id).0;
+    let programdata =
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id()).0;
+    idl_instruction(
+        program_id,
+        &IdlInstruction::Create { data_len },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(idl_address(program_id), false),
+            AccountMeta::new_readonly(base, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(*program_id, false),
+            AccountMeta::new_readonly(programdata, false),
+        ],
+    )
+}
+
+/// Append `chunk` to the IDL account.
+pub fn write_idl_instruction(program_id: &Pubkey, authority: &Pubkey, chunk: &[u8]) -> Instruction {
+    idl_instruction(
+        program_id,
+        &IdlInstruction::Write {
+            data: chunk.to_vec(),
+        },
+        vec![
+            AccountMeta::new(idl_address(program_id), false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Compress `idl_json` and upload it to the IDL account of `program_id`,
+/// returning the account address.
+pub async fn publish_idl(
+    rpc: &RpcClient,
+    authority: &Keypair,
+    program_id: &Pubkey,
+    idl_json: &[u8],
+) -> Result<Pubkey> {
+    let mut encoder = ZlibEncoder::new(vec![], Compression::best());
+    encoder.write_all(idl_json)?;
+    let compressed = encoder.finish()?;
+
+    let mut ixs = vec![create_idl_instruction(
+        program_id,
+        &authority.pubkey(),
+        compressed.len() as u64,
+    )];
+    ixs.extend(
+        compressed
+            .chunks(WRITE_CHUNK_LEN)
+            .map(|chunk| write_idl_instruction(program_id, &authority.pubkey(), chunk)),
+    );
+    for ix in ixs {
+        let blockhash = rpc.get_latest_blockhash().await?;
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&authority.pubkey()),
+            &[authority],
+            blockhash,
+        );
+        rpc.send_and_confirm_transaction(&tx).await?;
+    }
+    Ok(idl_address(program_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_instruction_fits_a_transaction() {
+        let program_id = Pubkey::new_unique();
+        let authority = Keypair::new();
+        let ix = write_idl_instruction(&program_id, &authority.pubkey(), &[0; WRITE_CHUNK_LEN]);
+        assert_eq!(ix.data[..8], IDL_IX_TAG);
+        let tx = Transaction::new_with_payer(&[ix], Some(&authority.pubkey()));
+        // the unsigned transaction already holds a placeholder signature
+        let size = bincode::serialized_size(&tx).unwrap() as usize;
+        assert!(size <= solana_sdk::packet::PACKET_DATA_SIZE);
+    }
+}
@@ -0,0 +1,421 @@
+//! Postgres indexer of time locks and their events.
+//!
+//! Every [`TimeLockEvent`] the program logs is stored in `timelock_events`,
+//! and the state it implies is folded into `timelock_locks`. Events are keyed
+//! by transaction signature and position, so replaying a transaction twice
+//! (a backfill overlapping the live stream, a restart) changes nothing.
+//!
+//! [`backfill`] walks the historical signatures of the program back to the
+//! last indexed one, [`run`] backfills then follows new transactions over
+//! websocket.
+
+use std::path::Path;
+use std::str::FromStr as _;
+
+use borsh::BorshSerialize as _;
+use futures_util::StreamExt as _;
+use serde::Deserialize;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_client::rpc_config::{
+    RpcTransactionConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter,
+};
+use solana_pubsub_client::nonblocking::pubsub_client::PubsubClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::UiTransactionEncoding;
+use tokio_postgres::{Client, NoTls};
+
+use crate::event::{parse_logs, TimeLockEvent};
+use crate::{Error, Result};
+
+/// Tables maintained by the indexer.
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS timelock_locks (
+    pubkey        TEXT PRIMARY KEY,
+    payer         TEXT NOT NULL,
+    timestamp     BIGINT NOT NULL,
+    bounty        BIGINT NOT NULL,
+    created_slot  BIGINT NOT NULL,
+    revealed_slot BIGINT,
+    cranker       TEXT
+);
+CREATE TABLE IF NOT EXISTS timelock_events (
+    signature  TEXT NOT NULL,
+    idx        INT NOT NULL,
+    slot       BIGINT NOT NULL,
+    block_time BIGINT,
+    kind       TEXT NOT NULL,
+    lock       TEXT NOT NULL,
+    data       BYTEA NOT NULL,
+    PRIMARY KEY (signature, idx)
+);
+CREATE INDEX IF NOT EXISTS timelock_locks_payer ON timelock_locks (payer);
+CREATE INDEX IF NOT EXISTS timelock_events_lock ON timelock_events (lock);
+CREATE TABLE IF NOT EXISTS timelock_cursor (
+    program_id TEXT PRIMARY KEY,
+    signature  TEXT NOT NULL
+);
+";
+
+/// Indexer settings.
+#[derive(Debug, Clone, Deserialize)]
+pub struct IndexerConfig {
+    /// RPC endpoint of the cluster.
+    pub rpc_url: String,
+    /// Websocket endpoint of the cluster.
+    pub ws_url: String,
+    /// Address of the deployed timelock program.
+    #[serde(deserialize_with = "crate::config::pubkey")]
+    pub program_id: Pubkey,
+    /// Postgres connection string.
+    pub database_url: String,
+}
+
+impl IndexerConfig {
+    /// Read the TOML config file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)?;
+        toml::from_str(&raw).map_err(|e| Error::Config(e.to_string()))
+    }
+}
+
+/// Connect to postgres and create the tables if needed.
+pub async fn connect(database_url: &str) -> Result<Client> {
+    let (client, connection) = tokio_postgres::connect(database_url, NoTls).await?;
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            tracing::error!("postgres connection closed: {e}");
+        }
+    });
+    client.batch_execute(SCHEMA).await?;
+    Ok(client)
+}
+
+/// Index the transactions of the program newer than the cursor, oldest first.
+pub async fn backfill(config: &IndexerConfig, db: &Client) -> Result<usize> {
+    let rpc = RpcClient::new_with_commitment(config.rpc_url.clone(), CommitmentConfig::confirmed());
+    let program_id = config.program_id.to_string();
+    let until = db
+        .query_opt(
+            "SELECT signature FROM timelock_cursor WHERE program_id = $1",
+            &[&program_id],
+        )
+        .await?
+        .map(|row| row.get::<_, String>(0))
+        .map(|s| Signature::from_str(&s))
+        .transpose()
+        .map_err(|e| Error::Config(format!("corrupted cursor: {e}")))?;
+
+    // signatures come newest first, collect them all before replaying
+    let mut signatures = vec![];
+    let mut before = None;
+    loop {
+        let page = rpc
+            .get_signatures_for_address_with_config(
+                &config.program_id,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until,
+                    limit: None,
+                    commitment: Some(CommitmentConfig::confirmed()),
+                },
+            )
+            .await?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        before =
+            Some(Signature::from_str(&last.signature).map_err(|e| Error::Config(e.to_string()))?);
+        signatures.extend(page.into_iter().filter(|s| s.err.is_none()));
+    }
+
+    let count = signatures.len();
+    for status in signatures.into_iter().rev() {
+        let signature =
+            Signature::from_str(&status.signature).map_err(|e| Error::Config(e.to_string()))?;
+        let tx_config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Json),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        };
+        let tx = rpc
+            .get_transaction_with_config(&signature, tx_config)
+            .await?;
+        let logs: Option<Vec<String>> = tx
+            .transaction
+            .meta
+            .and_then(|meta| meta.log_messages.into());
+        index_transaction(
+            db,
+            &status.signature,
+            tx.slot,
+            tx.block_time,
+            &parse_logs(&logs.unwrap_or_default()),
+        )
+        .await?;
+        set_cursor(db, &program_id, &status.signature).await?;
+    }
+    Ok(count)
+}
+
+/// Remember `signature` as the newest indexed transaction of the program.
+async fn set_cursor(db: &Client, program_id: &str, signature: &str) -> Result<()> {
+    db.execute(
+        "INSERT INTO timelock_cursor (program_id, signature) VALUES ($1, $2)
+         ON CONFLICT (program_id) DO UPDATE SET signature = EXCLUDED.signature",
+        &[&program_id, &signature],
+    )
+    .await?;
+    Ok(())
+}
+
+/// Backfill, then index new transactions as they are confirmed.
+pub async fn run(config: IndexerConfig) -> Result<()> {
+    let db = connect(&config.database_url).await?;
+    let pubsub = PubsubClient::new(&config.ws_url).await?;
+    // subscribe before backfilling so no transaction falls in between
+    let (mut stream, _unsubscribe) = pubsub
+        .logs_subscribe(
+            RpcTransactionLogsFilter::Mentions(vec![config.program_id.to_string()]),
+            RpcTransactionLogsConfig {
+                commitment: Some(CommitmentConfig::confirmed()),
+            },
+        )
+        .await?;
+
+    let count = backfill(&config, &db).await?;
+    let program_id = config.program_id.to_string();
+    tracing::info!("backfilled {count} transaction(s)");
+
+    while let Some(resp) = stream.next().await {
+        if resp.value.err.is_some() {
+            continue;
+        }
+        let events = parse_logs(&resp.value.logs);
+        index_transaction(&db, &resp.value.signature, resp.context.slot, None, &events).await?;
+        set_cursor(&db, &program_id, &resp.value.signature).await?;
+    }
+    Err(Error::Rejected {
+        service: "websocket",
+        reason: "log subscription closed".into(),
+    })
+}
+
+/// Store the events of one transaction and fold them into the lock state.
+pub async fn index_transaction(
+    db: &Client,
+    signature: &str,
+    slot: u64,
+    block_time: Option<i64>,
+    events: &[TimeLockEvent],
+) -> Result<()> {
+    for (idx, event) in events.iter().enumerate() {
+        let (kind, lock) = describe(event);
+        let mut data = vec![];
+        event.serialize(&mut data)?;
+        let inserted = db
+            .execute(
+                "INSERT INTO timelock_events (signature, idx, slot, block_time, kind, lock, data)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT DO NOTHING",
+                &[
+                    &signature,
+                    &(idx as i32),
+                    &(slot as i64),
+                    &block_time,
+                    &kind,
+                    &lock.to_string(),
+                    &data,
+                ],
+            )
+            .await?;
+        // already indexed: the state change was applied back then
+        if inserted == 0 {
+            continue;
+        }
+        apply(db, slot, event).await?;
+    }
+    Ok(())
+}
+
+async fn apply(db: &Client, slot: u64, event: &TimeLockEvent) -> Result<()> {
+    match event {
+        TimeLockEvent::LockCreated {
+            lock,
+            payer,
+            timestamp,
+            bounty,
+        } => {
+            db.execute(
+                "INSERT INTO timelock_locks (pubkey, payer, timestamp, bounty, created_slot)
+                 VALUES ($1, $2, $3, $4, $5) ON CONFLICT DO NOTHING",
+                &[
+                    &lock.to_string(),
+                    &payer.to_string(),
+                    timestamp,
+                    &(*bounty as i64),
+                    &(slot as i64),
+                ],
+            )
+            .await?;
+        }
+        TimeLockEvent::SecretRevealed { lock, .. } => {
+            db.execute(
+                "UPDATE timelock_locks SET revealed_slot = COALESCE(revealed_slot, $2)
+                 WHERE pubkey = $1",
+                &[&lock.to_string(), &(slot as i64)],
+            )
+            .await?;
+        }
+        TimeLockEvent::BountyPaid { lock, cranker, .. } => {
+            db.execute(
+                "UPDATE timelock_locks SET bounty = 0, cranker = $2 WHERE pubkey = $1",
+                &[&lock.to_string(), &cranker.to_string()],
+            )
+            .await?;
+        }
+        TimeLockEvent::Heartbeat { lock, timestamp } => {
+            db.execute(
+                "UPDATE timelock_locks SET timestamp = $2 WHERE pubkey = $1",
+                &[&lock.to_string(), timestamp],
+            )
+            .await?;
+        }
+        TimeLockEvent::UnlockTooEarly { .. }
+        | TimeLockEvent::StakeLocked { .. }
+        | TimeLockEvent::StakeReleased { .. }
+        | TimeLockEvent::ToppedUp { .. }
+        | TimeLockEvent::ExcessWithdrawn { .. }
+        | TimeLockEvent::EscrowCreated { .. }
+        | TimeLockEvent::EscrowClaimed { .. }
+        | TimeLockEvent::EscrowRefunded { .. }
+        | TimeLockEvent::EscrowBeneficiaryChanged { .. }
+        | TimeLockEvent::EscrowDelegateSet { .. }
+        | TimeLockEvent::EscrowDelegateRevoked { .. }
+        | TimeLockEvent::EscrowClaimedBy { .. }
+        | TimeLockEvent::UnlockerAdded { .. }
+        | TimeLockEvent::UnlockerRemoved { .. }
+        | TimeLockEvent::EscrowClaimedEarly { .. }
+        | TimeLockEvent::LockExpirySet { .. }
+        | TimeLockEvent::LockExpired { .. }
+        | TimeLockEvent::HeartbeatSet { .. }
+        | TimeLockEvent::RecurrenceSet { .. }
+        | TimeLockEvent::CallbackSet { .. }
+        | TimeLockEvent::CallbackInvoked { .. }
+        | TimeLockEvent::ConditionSet { .. }
+        | TimeLockEvent::PredecessorSet { .. }
+        | TimeLockEvent::DistributorCreated { .. }
+        | TimeLockEvent::LeafClaimed { .. }
+        | TimeLockEvent::VaultCreated { .. }
+        | TimeLockEvent::VaultLockCreated { .. }
+        | TimeLockEvent::VaultLockClaimed { .. }
+        | TimeLockEvent::VaultWithdrawn { .. }
+        | TimeLockEvent::VaultLockSplit { .. }
+        | TimeLockEvent::VaultLocksMerged { .. }
+        | TimeLockEvent::VaultLockTransferred { .. }
+        | TimeLockEvent::VaultLockSold { .. }
+        | TimeLockEvent::ConfigUpdated { .. }
+        | TimeLockEvent::FeesCollected { .. }
+        | TimeLockEvent::ClaimFeeCharged { .. }
+        | TimeLockEvent::FeesWithdrawn { .. }
+        | TimeLockEvent::FeeExemptionAdded { .. }
+        | TimeLockEvent::FeeExemptionRemoved { .. }
+        | TimeLockEvent::AdminProposed { .. }
+        | TimeLockEvent::ConfigUpdateQueued { .. }
+        | TimeLockEvent::ConfigUpdateCancelled { .. }
+        | TimeLockEvent::ImmutabilityRecorded { .. }
+        | TimeLockEvent::VerifiedBuildRecorded { .. }
+        | TimeLockEvent::AuditTrailEnabled { .. }
+        | TimeLockEvent::ClaimCommitRequired { .. }
+        | TimeLockEvent::ClaimCommitted { .. }
+        | TimeLockEvent::PayrollCreated { .. }
+        | TimeLockEvent::PayrollEntriesAdded { .. }
+        | TimeLockEvent::PayrollClaimed { .. }
+        | TimeLockEvent::ScheduleTemplateCreated { .. }
+        | TimeLockEvent::CallbackCleared { .. }
+        | TimeLockEvent::PayrollClosed { .. }
+        | TimeLockEvent::AccountMigrated { .. }
+        | TimeLockEvent::GrantRecipientSet { .. }
+        | TimeLockEvent::GrantReleased { .. } => {}
+    }
+    Ok(())
+}
+
+/// Name of the event and the lock (time lock, stake lock or escrow) it is about.
+fn describe(event: &TimeLockEvent) -> (&'static str, &Pubkey) {
+    match event {
+        TimeLockEvent::LockCreated { lock, .. } => ("lock_created", lock),
+        TimeLockEvent::UnlockTooEarly { lock, .. } => ("unlock_too_early", lock),
+        TimeLockEvent::SecretRevealed { lock, .. } => ("secret_revealed", lock),
+        TimeLockEvent::BountyPaid { lock, .. } => ("bounty_paid", lock),
+        TimeLockEvent::StakeLocked { stake_lock, .. } => ("stake_locked", stake_lock),
+        TimeLockEvent::StakeReleased { stake_lock, .. } => ("stake_released", stake_lock),
+        TimeLockEvent::ToppedUp { lock, .. } => ("topped_up", lock),
+        TimeLockEvent::ExcessWithdrawn { lock, .. } => ("excess_withdrawn", lock),
+        TimeLockEvent::EscrowCreated { escrow, .. } => ("escrow_created", escrow),
+        TimeLockEvent::EscrowClaimed { escrow, .. } => ("escrow_claimed", escrow),
+        TimeLockEvent::EscrowRefunded { escrow, .. } => ("escrow_refunded", escrow),
+        TimeLockEvent::EscrowBeneficiaryChanged { escrow, .. } => {
+            ("escrow_beneficiary_changed", escrow)
+        }
+        TimeLockEvent::EscrowDelegateSet { escrow, .. } => ("escrow_delegate_set", escrow),
+        TimeLockEvent::EscrowDelegateRevoked { escrow, .. } => ("escrow_delegate_revoked", escrow),
+        TimeLockEvent::EscrowClaimedBy { escrow, .. } => ("escrow_claimed_by", escrow),
+        TimeLockEvent::UnlockerAdded { lock, .. } => ("unlocker_added", lock),
+        TimeLockEvent::UnlockerRemoved { lock, .. } => ("unlocker_removed", lock),
+        TimeLockEvent::EscrowClaimedEarly { escrow, .. } => ("escrow_claimed_early", escrow),
+        TimeLockEvent::LockExpirySet { lock, .. } => ("lock_expiry_set", lock),
+        TimeLockEvent::LockExpired { lock, .. } => ("lock_expired", lock),
+        TimeLockEvent::HeartbeatSet { lock, .. } => ("heartbeat_set", lock),
+        TimeLockEvent::Heartbeat { lock, .. } => ("heartbeat", lock),
+        TimeLockEvent::RecurrenceSet { lock, .. } => ("recurrence_set", lock),
+        TimeLockEvent::CallbackSet { lock, .. } => ("callback_set", lock),
+        TimeLockEvent::CallbackInvoked { lock, .. } => ("callback_invoked", lock),
+        TimeLockEvent::ConditionSet { lock, .. } => ("condition_set", lock),
+        TimeLockEvent::PredecessorSet { lock, .. } => ("predecessor_set", lock),
+        TimeLockEvent::DistributorCreated { distributor, .. } => {
+            ("distributor_created", distributor)
+        }
+        TimeLockEvent::LeafClaimed { distributor, .. } => ("leaf_claimed", distributor),
+        TimeLockEvent::VaultCreated { vault, .. } => ("vault_created", vault),
+        TimeLockEvent::VaultLockCreated { vault_lock, .. } => ("vault_lock_created", vault_lock),
+        TimeLockEvent::VaultLockClaimed { vault_lock, .. } => ("vault_lock_claimed", vault_lock),
+        TimeLockEvent::VaultWithdrawn { vault, .. } => ("vault_withdrawn", vault),
+        TimeLockEvent::VaultLockSplit { vault_lock, .. } => ("vault_lock_split", vault_lock),
+        TimeLockEvent::VaultLocksMerged { vault_lock, .. } => ("vault_locks_merged", vault_lock),
+        TimeLockEvent::VaultLockTransferred { vault_lock, .. } => {
+            ("vault_lock_transferred", vault_lock)
+        }
+        TimeLockEvent::VaultLockSold { vault_lock, .. } => ("vault_lock_sold", vault_lock),
+        TimeLockEvent::ConfigUpdated { config, .. } => ("config_updated", config),
+        TimeLockEvent::FeesCollected { treasury, .. } => ("fees_collected", treasury),
+        TimeLockEvent::ClaimFeeCharged { source, .. } => ("claim_fee_charged", source),
+        TimeLockEvent::FeesWithdrawn { recipient, .. } => ("fees_withdrawn", recipient),
+        TimeLockEvent::FeeExemptionAdded { key, .. } => ("fee_exemption_added", key),
+        TimeLockEvent::FeeExemptionRemoved { key, .. } => ("fee_exemption_removed", key),
+        TimeLockEvent::AdminProposed { config, .. } => ("admin_proposed", config),
+        TimeLockEvent::ConfigUpdateQueued { config_update, .. } => {
+            ("config_update_queued", config_update)
+        }
+        TimeLockEvent::ConfigUpdateCancelled { config_update, .. } => {
+            ("config_update_cancelled", config_update)
+        }
+        TimeLockEvent::ImmutabilityRecorded { config, .. } => ("immutability_recorded", config),
+        TimeLockEvent::VerifiedBuildRecorded { config, .. } => ("verified_build_recorded", config),
+        TimeLockEvent::AuditTrailEnabled { lock, .. } => ("audit_trail_enabled", lock),
+        TimeLockEvent::ClaimCommitRequired { lock, .. } => ("claim_commit_required", lock),
+        TimeLockEvent::ClaimCommitted { lock, .. } => ("claim_committed", lock),
+        TimeLockEvent::PayrollCreated { payroll, .. } => ("payroll_created", payroll),
+        TimeLockEvent::PayrollEntriesAdded { payroll, .. } => ("payroll_entries_added", payroll),
+        TimeLockEvent::PayrollClaimed { payroll, .. } => ("payroll_claimed", payroll),
+        TimeLockEvent::ScheduleTemplateCreated { template, .. } => {
+            ("schedule_template_created", template)
+        }
+        TimeLockEvent::CallbackCleared { lock, .. } => ("callback_cleared", lock),
+        TimeLockEvent::PayrollClosed { payroll, .. } => ("payroll_closed", payroll),
+        TimeLockEvent::AccountMigrated { account, .. } => ("account_migrated", account),
+        TimeLockEvent::GrantRecipientSet { lock, .. } => ("grant_recipient_set", lock),
+        TimeLockEvent::GrantReleased { lock, .. } => ("grant_released", lock),
+    }
+}
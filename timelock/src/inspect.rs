@@ -0,0 +1,344 @@
+//! Decoding the time lock instructions of a transaction for review, so that
+//! multisig signers see what they approve rather than opaque bytes.
+//!
+//! [`inspect`] picks the instructions of the program out of a message and
+//! decodes them; their [`Display`](fmt::Display) shows times as UTC dates,
+//! durations in days and hours, lamports in SOL and keys in base58.
+
+use std::fmt;
+use std::str::FromStr as _;
+
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use serde_json::Value;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::message::VersionedMessage;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::VersionedTransaction;
+use solana_transaction_status::UiTransactionEncoding;
+use timelock_program::instruction::TimeLockInstruction;
+
+use crate::{Error, Result};
+
+/// Fields holding unix timestamps.
+const TIME_FIELDS: &[&str] = &[
+    "timestamp",
+    "expires_at",
+    "release_time",
+    "refund_time",
+    "unlock_time",
+];
+/// Fields holding durations in seconds.
+const DURATION_FIELDS: &[&str] = &[
+    "interval",
+    "window",
+    "min_duration",
+    "max_duration",
+    "update_delay",
+    "clock_drift_tolerance",
+];
+/// Fields holding lamports.
+const LAMPORT_FIELDS: &[&str] = &["amount", "bounty", "price", "creation_fee"];
+/// Fields holding raw bytes, shown in hex rather than as keys.
+const BYTES_FIELDS: &[&str] = &["data", "expected", "build_hash", "source_commit"];
+
+/// Instruction of the program in a transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InspectedInstruction {
+    /// Position in the transaction.
+    pub index: usize,
+    /// Decoded instruction, or why its data is not one.
+    pub instruction: std::result::Result<TimeLockInstruction, String>,
+    /// Accounts passed, in order.
+    pub accounts: Vec<InspectedAccount>,
+}
+
+/// Account passed to an [`InspectedInstruction`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InspectedAccount {
+    /// Address, `None` when loaded from an address lookup table.
+    pub pubkey: Option<Pubkey>,
+    /// Whether the transaction is signed by it.
+    pub is_signer: bool,
+    /// Whether the transaction may write it, always for looked up accounts.
+    pub is_writable: bool,
+}
+
+/// Decode a transaction serialized in base64, as wallets and multisig
+/// tools export it.
+pub fn decode_transaction(encoded: &str) -> Result<VersionedTransaction> {
+    let bytes = BASE64_STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| Error::Config(format!("invalid base64 transaction: {e}")))?;
+    bincode::deserialize(&bytes).map_err(|e| Error::Config(format!("invalid transaction: {e}")))
+}
+
+/// Fetch the transaction of `signature`.
+pub async fn fetch_transaction(
+    rpc: &RpcClient,
+    signature: &Signature,
+) -> Result<VersionedTransaction> {
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Base64),
+        commitment: Some(CommitmentConfig::confirmed()),
+        max_supported_transaction_version: Some(0),
+    };
+    rpc.get_transaction_with_config(signature, config)
+        .await?
+        .transaction
+        .transaction
+        .decode()
+        .ok_or_else(|| Error::Config(format!("undecodable transaction {signature}")))
+}
+
+/// Read `input` as a signature to fetch, or else as a base64 transaction.
+pub async fn load_transaction(rpc: &RpcClient, input: &str) -> Result<VersionedTransaction> {
+    match Signature::from_str(input.trim()) {
+        Ok(signature) => fetch_transaction(rpc, &signature).await,
+        Err(_) => decode_transaction(input),
+    }
+}
+
+/// Instructions of `message` calling `program_id`, decoded.
+pub fn inspect(program_id: &Pubkey, message: &VersionedMessage) -> Vec<InspectedInstruction> {
+    let keys = message.static_account_keys();
+    message
+        .instructions()
+        .iter()
+        .enumerate()
+        .filter(|(_, ix)| keys.get(usize::from(ix.program_id_index)) == Some(program_id))
+        .map(|(index, ix)| InspectedInstruction {
+            index,
+            instruction: TimeLockInstruction::unpack(&ix.data).map_err(|e| e.to_string()),
+            accounts: ix
+                .accounts
+                .iter()
+                .map(|&i| account(message, usize::from(i)))
+                .collect(),
+        })
+        .collect()
+}
+
+fn account(message: &VersionedMessage, index: usize) -> InspectedAccount {
+    let keys = message.static_account_keys();
+    let Some(pubkey) = keys.get(index) else {
+        return InspectedAccount {
+            pubkey: None,
+            is_signer: false,
+            is_writable: true,
+        };
+    };
+    let header = message.header();
+    let signers = usize::from(header.num_required_signatures);
+    let is_writable = if index < signers {
+        index < signers - usize::from(header.num_readonly_signed_accounts)
+    } else {
+        index < keys.len() - usize::from(header.num_readonly_unsigned_accounts)
+    };
+    InspectedAccount {
+        pubkey: Some(*pubkey),
+        is_signer: index < signers,
+        is_writable,
+    }
+}
+
+impl fmt::Display for InspectedInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.instruction {
+            Ok(instruction) => {
+                let value = serde_json::to_value(instruction).map_err(|_| fmt::Error)?;
+                match value {
+                    Value::String(name) => writeln!(f, "#{} {name}", self.index)?,
+                    Value::Object(variant) => {
+                        for (name, fields) in variant {
+                            writeln!(f, "#{} {name}", self.index)?;
+                            for (field, value) in fields.as_object().into_iter().flatten() {
+                                writeln!(f, "  {field}: {}", render(field, value))?;
+                            }
+                        }
+                    }
+                    _ => writeln!(f, "#{} {value}", self.index)?,
+                }
+            }
+            Err(e) => writeln!(f, "#{} undecodable: {e}", self.index)?,
+        }
+        writeln!(f, "  accounts:")?;
+        for (i, account) in self.accounts.iter().enumerate() {
+            match account.pubkey {
+                Some(pubkey) => write!(f, "    {i}: {pubkey}")?,
+                None => write!(f, "    {i}: (lookup table)")?,
+            }
+            if account.is_writable {
+                write!(f, " writable")?;
+            }
+            if account.is_signer {
+                write!(f, " signer")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// `value` of `field` in human form.
+fn render(field: &str, value: &Value) -> String {
+    match value {
+        Value::Number(n) if TIME_FIELDS.contains(&field) => match n.as_i64() {
+            Some(secs) => format!("{} ({secs})", utc(secs)),
+            None => n.to_string(),
+        },
+        Value::Number(n) if DURATION_FIELDS.contains(&field) => match n.as_i64() {
+            Some(secs) => format!("{} ({secs}s)", duration(secs)),
+            None => n.to_string(),
+        },
+        Value::Number(n) if LAMPORT_FIELDS.contains(&field) => match n.as_u64() {
+            Some(lamports) => format!("{} ({lamports} lamports)", sol(lamports)),
+            None => n.to_string(),
+        },
+        Value::Number(n) if field.ends_with("_bps") => match n.as_u64() {
+            Some(bps) => format!("{}.{:02}% ({bps} bps)", bps / 100, bps % 100),
+            None => n.to_string(),
+        },
+        Value::Array(items) => match bytes(items) {
+            Some(bytes) if field == "secret" => {
+                format!("{:?}", String::from_utf8_lossy(&bytes).trim_end())
+            }
+            Some(bytes) if BYTES_FIELDS.contains(&field) => hex(&bytes),
+            Some(bytes) => match <[u8; 32]>::try_from(bytes.as_slice()) {
+                Ok(key) => Pubkey::new_from_array(key).to_string(),
+                Err(_) => hex(&bytes),
+            },
+            None => {
+                let items: Vec<_> = items.iter().map(|item| render(field, item)).collect();
+                format!("[{}]", items.join(", "))
+            }
+        },
+        _ => value.to_string(),
+    }
+}
+
+/// `items` as bytes, if they all are.
+fn bytes(items: &[Value]) -> Option<Vec<u8>> {
+    items
+        .iter()
+        .map(|item| item.as_u64().and_then(|b| u8::try_from(b).ok()))
+        .collect()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let digits: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    format!("0x{digits}")
+}
+
+/// `lamports` in SOL, without trailing zeros.
+fn sol(lamports: u64) -> String {
+    let whole = lamports / LAMPORTS_PER_SOL;
+    let fraction = format!("{:09}", lamports % LAMPORTS_PER_SOL);
+    match fraction.trim_end_matches('0') {
+        "" => format!("{whole} SOL"),
+        fraction => format!("{whole}.{fraction} SOL"),
+    }
+}
+
+/// `secs` as days, hours, minutes and seconds, omitting the zero ones.
+fn duration(secs: i64) -> String {
+    let sign = if secs < 0 { "-" } else { "" };
+    let secs = secs.unsigned_abs();
+    let parts: Vec<_> = [
+        (secs / 86_400, "d"),
+        (secs / 3_600 % 24, "h"),
+        (secs / 60 % 60, "m"),
+        (secs % 60, "s"),
+    ]
+    .into_iter()
+    .filter(|(n, _)| *n > 0)
+    .map(|(n, unit)| format!("{n}{unit}"))
+    .collect();
+    match parts.is_empty() {
+        true => "0s".into(),
+        false => format!("{sign}{}", parts.join(" ")),
+    }
+}
+
+/// `secs` since the unix epoch as a UTC date and time.
+fn utc(secs: i64) -> String {
+    let (days, rem) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+    // civil from days, proleptic Gregorian
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        rem / 3_600,
+        rem / 60 % 60,
+        rem % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::instruction::{AccountMeta, Instruction};
+    use solana_sdk::message::Message;
+    use timelock_program::instruction::initialize_time_lock;
+    use timelock_program::SECRET_LENGTH;
+
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        assert_eq!(utc(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(utc(1_709_210_096), "2024-02-29 12:34:56 UTC");
+        assert_eq!(duration(90_061), "1d 1h 1m 1s");
+        assert_eq!(duration(0), "0s");
+        assert_eq!(sol(1_500_000_000), "1.5 SOL");
+        assert_eq!(sol(5_000), "0.000005 SOL");
+        assert_eq!(render("penalty_bps", &Value::from(250)), "2.50% (250 bps)");
+    }
+
+    #[test]
+    fn test_inspect() {
+        let program_id = Pubkey::new_unique();
+        let (lock, payer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut secret = [b' '; SECRET_LENGTH];
+        secret[..5].copy_from_slice(b"hello");
+        let other = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[1],
+            vec![AccountMeta::new(payer, true)],
+        );
+        let ix = initialize_time_lock(
+            &program_id,
+            &lock,
+            &payer,
+            1_709_210_096,
+            secret,
+            5_000,
+            false,
+            false,
+        );
+        let message = VersionedMessage::Legacy(Message::new(&[other, ix], Some(&payer)));
+
+        let inspected = inspect(&program_id, &message);
+        assert_eq!(inspected.len(), 1);
+        assert_eq!(inspected[0].index, 1);
+        let lock_account = inspected[0].accounts[0];
+        assert_eq!(lock_account.pubkey, Some(lock));
+        assert!(lock_account.is_signer && lock_account.is_writable);
+
+        let shown = inspected[0].to_string();
+        assert!(shown.starts_with("#1 InitializeTimeLock\n"));
+        assert!(shown.contains("timestamp: 2024-02-29 12:34:56 UTC (1709210096)"));
+        assert!(shown.contains("secret: \"hello\""));
+        assert!(shown.contains("bounty: 0.000005 SOL (5000 lamports)"));
+        assert!(shown.contains(&format!("0: {lock} writable signer")));
+    }
+}
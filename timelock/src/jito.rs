@@ -0,0 +1,130 @@
+//! Jito bundle submission.
+//!
+//! Transactions in a bundle land atomically and in order, so an unlock and
+//! whatever the caller does with the revealed secret or released lamports
+//! cannot be sandwiched or front-run. Every bundle must pay a tip to one of
+//! the [`TIP_ACCOUNTS`], see [`tip_instruction`].
+
+use std::str::FromStr as _;
+
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use serde::Deserialize;
+use serde_json::json;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+
+use crate::{Error, Result};
+
+/// Transactions a bundle may hold at most.
+pub const MAX_BUNDLE_LEN: usize = 5;
+
+/// Accounts the block engine accepts tips on.
+pub const TIP_ACCOUNTS: [&str; 8] = [
+    "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5",
+    "HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe",
+    "Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY",
+    "ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49",
+    "DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh",
+    "ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt",
+    "DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL",
+    "3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT",
+];
+
+/// Jito settings of a keeper target.
+#[derive(Debug, Clone, Deserialize)]
+pub struct JitoConfig {
+    /// Block engine url, e.g. `https://mainnet.block-engine.jito.wtf`.
+    pub block_engine_url: String,
+    /// Lamports tipped per bundle.
+    pub tip_lamports: u64,
+}
+
+/// Transfer `lamports` from `payer` to a tip account.
+///
+/// The tip account is picked from `payer`, so tips of several wallets are
+/// spread over the accounts and don't all write-lock the same one.
+pub fn tip_instruction(payer: &Pubkey, lamports: u64) -> Instruction {
+    let tip_account = TIP_ACCOUNTS[payer.to_bytes()[0] as usize % TIP_ACCOUNTS.len()];
+    let tip_account = Pubkey::from_str(tip_account).expect("valid tip account");
+    system_instruction::transfer(payer, &tip_account, lamports)
+}
+
+/// Client of the block engine bundle API.
+#[derive(Debug, Clone)]
+pub struct JitoClient {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl JitoClient {
+    /// Create a client of the block engine at `block_engine_url`.
+    pub fn new(block_engine_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: format!("{}/api/v1/bundles", block_engine_url.trim_end_matches('/')),
+        }
+    }
+
+    /// Submit signed `txs` as one bundle, returning the bundle id.
+    ///
+    /// One of the transactions must carry a [`tip_instruction`].
+    pub async fn send_bundle(&self, txs: &[Transaction]) -> Result<String> {
+        if txs.is_empty() || txs.len() > MAX_BUNDLE_LEN {
+            return Err(Error::Rejected {
+                service: "jito",
+                reason: format!("a bundle holds 1 to {MAX_BUNDLE_LEN} transactions"),
+            });
+        }
+        let encoded = txs
+            .iter()
+            .map(|tx| {
+                bincode::serialize(tx)
+                    .map(|bytes| BASE64_STANDARD.encode(bytes))
+                    .map_err(|e| Error::Rejected {
+                        service: "jito",
+                        reason: e.to_string(),
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let resp: serde_json::Value = self
+            .client
+            .post(&self.url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "sendBundle",
+                "params": [encoded, { "encoding": "base64" }],
+            }))
+            .send()
+            .await?
+            .json()
+            .await?;
+        match resp["result"].as_str() {
+            Some(bundle_id) => Ok(bundle_id.to_string()),
+            None => Err(Error::Rejected {
+                service: "jito",
+                reason: resp["error"]["message"]
+                    .as_str()
+                    .unwrap_or("unknown error")
+                    .to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tip_instruction() {
+        let payer = Pubkey::new_unique();
+        let ix = tip_instruction(&payer, 1000);
+        assert_eq!(ix.accounts[0].pubkey, payer);
+        assert!(TIP_ACCOUNTS
+            .iter()
+            .any(|a| Pubkey::from_str(a).unwrap() == ix.accounts[1].pubkey));
+    }
+}
@@ -0,0 +1,473 @@
+//! Off-chain keeper cranking matured time locks.
+//!
+//! The keeper polls every time lock owned by the program and submits
+//! `TryUnlock` for locks that matured with a bounty still set aside, earning
+//! the bounty for its fee payer. Failed RPC calls are retried with
+//! exponential backoff, an unlock is never submitted twice concurrently, and
+//! locks staying unclaimed past a grace period are reported to a [`Notifier`].
+//! Activity is exported as prometheus [`Metrics`].
+//!
+//! One process can watch several clusters and program deployments, each
+//! [target](KeeperConfig) rotating between its own fee payer wallets with a
+//! bounded number of concurrent unlocks.
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Deserialize;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signature, Signer as _};
+use solana_sdk::transaction::Transaction;
+use timelock_program::instruction::{memo, try_unlock_with_callback};
+use timelock_program::{audit_address, TimeLockAccount};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::config::Config;
+use crate::jito::{tip_instruction, JitoClient, JitoConfig};
+use crate::metrics::{self, Metrics};
+use crate::notifier::{Notification, Notifier};
+use crate::rpc::{fetch_callback, fetch_locks};
+use crate::{Error, Result};
+
+/// Exponential backoff policy for transient failures.
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    /// Delay before the first retry.
+    pub initial: Duration,
+    /// Upper bound of the delay between two retries.
+    pub max: Duration,
+    /// Retries attempted before giving up.
+    pub max_retries: u32,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_millis(500),
+            max: Duration::from_secs(30),
+            max_retries: 5,
+        }
+    }
+}
+
+impl Backoff {
+    /// Delay before the `attempt`-th retry, counting from 0.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.initial
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max)
+    }
+
+    /// Run `op` until it succeeds, fails permanently, or retries are exhausted.
+    pub async fn retry<T, F, Fut>(&self, mut op: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match op().await {
+                Err(e) if e.is_transient() && attempt < self.max_retries => {
+                    let delay = self.delay(attempt);
+                    tracing::warn!("transient failure, retrying in {delay:?}: {e}");
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+}
+
+/// Settings of one watched target: a program deployment on a cluster.
+#[derive(Debug, Clone, Deserialize)]
+pub struct KeeperConfig {
+    /// Name of the target, used in logs.
+    pub name: String,
+    /// RPC endpoint of the cluster.
+    pub rpc_url: String,
+    /// Address of the deployed timelock program.
+    #[serde(deserialize_with = "crate::config::pubkey")]
+    pub program_id: Pubkey,
+    /// Keypair files of the fee payers, rotated between transactions.
+    pub wallets: Vec<PathBuf>,
+    /// Unlock transactions submitted concurrently at most.
+    #[serde(default = "default_max_concurrency")]
+    pub max_concurrency: usize,
+    /// Interval between two scans of the program accounts.
+    #[serde(
+        rename = "poll_interval_secs",
+        deserialize_with = "crate::config::secs",
+        default = "default_poll_interval"
+    )]
+    pub poll_interval: Duration,
+    /// How long a matured lock may stay unclaimed before an alert is raised.
+    #[serde(
+        rename = "grace_period_secs",
+        deserialize_with = "crate::config::secs",
+        default = "default_grace_period"
+    )]
+    pub grace_period: Duration,
+    /// Retry policy for RPC calls.
+    #[serde(skip)]
+    pub backoff: Backoff,
+    /// Submit unlocks as jito bundles instead of plain transactions.
+    pub jito: Option<JitoConfig>,
+}
+
+fn default_max_concurrency() -> usize {
+    4
+}
+
+fn default_poll_interval() -> Duration {
+    Duration::from_secs(30)
+}
+
+fn default_grace_period() -> Duration {
+    Duration::from_secs(3600)
+}
+
+/// Run a keeper for every target in `config` until interrupted with ctrl-c.
+pub async fn run(config: Config) -> Result<()> {
+    let metrics = Arc::new(Metrics::new());
+    if let Some(addr) = config.metrics_addr {
+        let metrics = metrics.clone();
+        tokio::spawn(async move {
+            if let Err(e) = metrics::serve(metrics, addr).await {
+                tracing::error!("metrics endpoint stopped: {e}");
+            }
+        });
+    }
+    let notifier: Arc<dyn Notifier> = Arc::new(config.notifiers.build());
+    let mut keepers = JoinSet::new();
+    for target in config.targets {
+        let keeper = Keeper::new(target, notifier.clone(), metrics.clone())?;
+        keepers.spawn(keeper.run());
+    }
+    while let Some(res) = keepers.join_next().await {
+        res.expect("keeper panicked")?;
+    }
+    Ok(())
+}
+
+/// State shared between the keeper and its unlock tasks.
+struct Shared {
+    rpc: RpcClient,
+    payers: Vec<Keypair>,
+    /// index of the next fee payer to use
+    next_payer: AtomicUsize,
+    /// bounds the unlock transactions submitted concurrently
+    permits: Semaphore,
+    notifier: Arc<dyn Notifier>,
+    metrics: Arc<Metrics>,
+    config: KeeperConfig,
+    /// locks with an unlock transaction currently being submitted
+    in_flight: Mutex<HashSet<Pubkey>>,
+    jito: Option<JitoClient>,
+}
+
+/// Watches time locks and unlocks them once they mature.
+pub struct Keeper {
+    shared: Arc<Shared>,
+    /// locks already reported as overdue
+    alerted: HashSet<Pubkey>,
+    tasks: JoinSet<()>,
+}
+
+impl Keeper {
+    /// Create a keeper for `config`, loading its fee payer wallets.
+    pub fn new(
+        config: KeeperConfig,
+        notifier: Arc<dyn Notifier>,
+        metrics: Arc<Metrics>,
+    ) -> Result<Self> {
+        let payers = config
+            .wallets
+            .iter()
+            .map(|path| {
+                read_keypair_file(path).map_err(|e| {
+                    Error::Config(format!("failed to read wallet {}: {e}", path.display()))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if payers.is_empty() {
+            return Err(Error::Config(format!(
+                "target {} has no wallet",
+                config.name
+            )));
+        }
+        let rpc =
+            RpcClient::new_with_commitment(config.rpc_url.clone(), CommitmentConfig::confirmed());
+        let jito = config
+            .jito
+            .as_ref()
+            .map(|jito| JitoClient::new(&jito.block_engine_url));
+        Ok(Self {
+            shared: Arc::new(Shared {
+                rpc,
+                payers,
+                next_payer: AtomicUsize::new(0),
+                permits: Semaphore::new(config.max_concurrency.max(1)),
+                notifier,
+                metrics,
+                config,
+                in_flight: Default::default(),
+                jito,
+            }),
+            alerted: HashSet::new(),
+            tasks: JoinSet::new(),
+        })
+    }
+
+    /// Poll until interrupted with ctrl-c, then wait for in-flight unlocks.
+    pub async fn run(mut self) -> Result<()> {
+        let mut interval = tokio::time::interval(self.shared.config.poll_interval);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    if let Err(e) = self.tick().await {
+                        self.shared.metrics.record_failure(&e);
+                        tracing::error!("[{}] failed to scan time locks: {e}", self.shared.config.name);
+                    }
+                }
+                _ = tokio::signal::ctrl_c() => break,
+            }
+        }
+        while self.tasks.join_next().await.is_some() {}
+        Ok(())
+    }
+
+    /// Scan the program accounts once and crank every matured lock.
+    pub async fn tick(&mut self) -> Result<()> {
+        // reap finished unlocks
+        while self.tasks.try_join_next().is_some() {}
+
+        let shared = &self.shared;
+        let locks = shared.config.backoff.retry(|| shared.fetch_locks()).await?;
+        let now = unix_now();
+        let grace = shared.config.grace_period.as_secs() as i64;
+
+        // locks waiting on a predecessor still locked would fail to reveal
+        let revealed: HashSet<Pubkey> = locks
+            .iter()
+            .filter(|(_, data)| data.revealed_at != 0)
+            .map(|(lock, _)| *lock)
+            .collect();
+        let pending: Vec<_> = locks
+            .into_iter()
+            .filter(|(_, data)| data.is_open(now) && data.bounty > 0)
+            .filter(|(_, data)| !data.has_predecessor() || revealed.contains(&data.predecessor))
+            // locks keeping their bounty for committed claims would not pay a crank
+            .filter(|(_, data)| !data.claim_commit_required)
+            // locks restricting their unlockers to other keys are not ours to crank
+            .filter(|(_, data)| {
+                shared
+                    .payers
+                    .iter()
+                    .any(|p| data.allows_unlocker(&p.pubkey()))
+            })
+            .collect();
+        shared.metrics.locks_tracked.set(pending.len() as i64);
+
+        for (lock, data) in pending {
+            if now.saturating_sub(data.timestamp) > grace && self.alerted.insert(lock) {
+                let notification = Notification::Overdue {
+                    lock,
+                    timestamp: data.timestamp,
+                };
+                if let Err(e) = shared.notifier.notify(&notification).await {
+                    shared.metrics.record_failure(&e);
+                    tracing::error!(
+                        "[{}] failed to send alert for {lock}: {e}",
+                        shared.config.name
+                    );
+                }
+            }
+            // dedupe: never submit two unlocks for the same lock at once
+            if !shared.in_flight.lock().unwrap().insert(lock) {
+                continue;
+            }
+            self.tasks.spawn(shared.clone().unlock(lock, data));
+        }
+        Ok(())
+    }
+}
+
+impl Shared {
+    async fn unlock(self: Arc<Self>, lock: Pubkey, data: TimeLockAccount) {
+        let timestamp = data.timestamp;
+        let permit = self
+            .permits
+            .acquire()
+            .await
+            .expect("semaphore is never closed");
+        let res = self
+            .config
+            .backoff
+            .retry(|| self.submit_unlock(&lock, &data))
+            .await;
+        drop(permit);
+        match res {
+            Ok(signature) => {
+                self.metrics.unlocks_submitted.inc();
+                tracing::info!("[{}] unlocked {lock}: {signature}", self.config.name);
+                if let Err(e) = self
+                    .notifier
+                    .notify(&Notification::Unlocked { lock, timestamp })
+                    .await
+                {
+                    self.metrics.record_failure(&e);
+                    tracing::error!(
+                        "[{}] failed to notify unlock of {lock}: {e}",
+                        self.config.name
+                    );
+                }
+            }
+            Err(e) => {
+                self.metrics.record_failure(&e);
+                tracing::error!("[{}] failed to unlock {lock}: {e}", self.config.name);
+            }
+        }
+        self.in_flight.lock().unwrap().remove(&lock);
+    }
+
+    /// Round-robin between the fee payers allowed to unlock `data`, to spread
+    /// rate limits.
+    fn next_payer(&self, data: &TimeLockAccount) -> &Keypair {
+        let i = self.next_payer.fetch_add(1, Ordering::Relaxed);
+        let n = self.payers.len();
+        (0..n)
+            .map(|j| &self.payers[(i + j) % n])
+            .find(|p| data.allows_unlocker(&p.pubkey()))
+            .unwrap_or(&self.payers[i % n])
+    }
+
+    /// Sign with a fresh blockhash on every attempt, so retries never reuse an expired one.
+    async fn submit_unlock(&self, lock: &Pubkey, data: &TimeLockAccount) -> Result<Signature> {
+        let payer = self.next_payer(data);
+        let blockhash = {
+            let _timer = self.metrics.time_rpc("getLatestBlockhash");
+            self.rpc.get_latest_blockhash().await?
+        };
+        let mut ixs = vec![try_unlock_instruction(
+            &self.config.program_id,
+            lock,
+            &payer.pubkey(),
+        )];
+        // the callback is called with accounts the transaction must carry
+        if data.has_callback {
+            let callback = {
+                let _timer = self.metrics.time_rpc("getAccountInfo");
+                fetch_callback(&self.rpc, &self.config.program_id, lock).await?
+            };
+            if let Some(callback) = callback {
+                ixs[0] = try_unlock_with_callback(
+                    &self.config.program_id,
+                    lock,
+                    Some(&payer.pubkey()),
+                    &callback,
+                );
+            }
+        }
+        // the condition and the predecessor are read from accounts the
+        // transaction must carry
+        if data.has_condition() {
+            ixs[0]
+                .accounts
+                .push(AccountMeta::new_readonly(data.condition_account, false));
+        }
+        if data.has_predecessor() {
+            ixs[0]
+                .accounts
+                .push(AccountMeta::new_readonly(data.predecessor, false));
+        }
+        // the reveal is appended to the audit trail the lock keeps
+        if data.has_audit_trail {
+            let audit_trail = audit_address(&self.config.program_id, lock).0;
+            ixs[0].accounts.push(AccountMeta::new(audit_trail, false));
+        }
+        // locks requiring a memo only pay the bounty to annotated claims
+        if data.require_memo {
+            ixs.push(memo(&format!("timelock keeper claim of {lock}")));
+        }
+        let tip = self
+            .config
+            .jito
+            .as_ref()
+            .map_or(0, |jito| jito.tip_lamports);
+        if self.jito.is_some() {
+            ixs.push(tip_instruction(&payer.pubkey(), tip));
+        }
+        let tx =
+            Transaction::new_signed_with_payer(&ixs, Some(&payer.pubkey()), &[payer], blockhash);
+        let fee = {
+            let _timer = self.metrics.time_rpc("getFeeForMessage");
+            self.rpc.get_fee_for_message(&tx.message).await?
+        };
+        let signature = match &self.jito {
+            Some(jito) => {
+                let bundle_id = {
+                    let _timer = self.metrics.time_rpc("sendBundle");
+                    jito.send_bundle(std::slice::from_ref(&tx)).await?
+                };
+                tracing::debug!("[{}] sent bundle {bundle_id}", self.config.name);
+                let _timer = self.metrics.time_rpc("confirmTransaction");
+                self.rpc.poll_for_signature(&tx.signatures[0]).await?;
+                tx.signatures[0]
+            }
+            None => {
+                let _timer = self.metrics.time_rpc("sendTransaction");
+                self.rpc.send_and_confirm_transaction(&tx).await?
+            }
+        };
+        self.metrics.fees_lamports.inc_by(fee + tip);
+        Ok(signature)
+    }
+
+    async fn fetch_locks(&self) -> Result<Vec<(Pubkey, TimeLockAccount)>> {
+        let _timer = self.metrics.time_rpc("getProgramAccounts");
+        fetch_locks(&self.rpc, &self.config.program_id).await
+    }
+}
+
+pub(crate) fn try_unlock_instruction(
+    program_id: &Pubkey,
+    lock: &Pubkey,
+    cranker: &Pubkey,
+) -> Instruction {
+    timelock_program::instruction::try_unlock(program_id, lock, Some(cranker))
+}
+
+pub(crate) fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock before unix epoch")
+        .as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_delay() {
+        let backoff = Backoff {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(10),
+            max_retries: 5,
+        };
+        assert_eq!(backoff.delay(0), Duration::from_secs(1));
+        assert_eq!(backoff.delay(1), Duration::from_secs(2));
+        assert_eq!(backoff.delay(3), Duration::from_secs(8));
+        assert_eq!(backoff.delay(4), Duration::from_secs(10));
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_secs(10));
+    }
+}
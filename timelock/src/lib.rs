@@ -5,3 +5,23 @@
     elided_lifetimes_in_paths
 )]
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
+
+pub mod config;
+pub mod dry_run;
+mod error;
+pub mod event;
+pub mod filter;
+pub mod idl;
+pub mod indexer;
+pub mod inspect;
+pub mod jito;
+pub mod keeper;
+pub mod metrics;
+pub mod notifier;
+pub mod provenance;
+pub mod rpc;
+pub mod signer;
+pub mod snapshot;
+pub mod subscribe;
+
+pub use error::{Error, Result};
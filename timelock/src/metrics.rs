@@ -0,0 +1,164 @@
+//! Prometheus metrics exposed by the keeper on `/metrics`.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Instant;
+
+use axum::routing::get;
+use axum::Router;
+use prometheus::{
+    Encoder as _, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts, Registry,
+    TextEncoder,
+};
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::transaction::TransactionError;
+
+use crate::{Error, Result};
+
+/// Counters and gauges describing the keeper activity.
+pub struct Metrics {
+    registry: Registry,
+    /// Matured or pending time locks seen in the last scan.
+    pub locks_tracked: IntGauge,
+    /// Unlock transactions confirmed.
+    pub unlocks_submitted: IntCounter,
+    /// Failed operations, labelled by error code.
+    pub failures: IntCounterVec,
+    /// RPC latency in seconds, labelled by method.
+    pub rpc_latency: HistogramVec,
+    /// Lamports paid in transaction fees.
+    pub fees_lamports: IntCounter,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    /// Create and register every keeper metric.
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some("timelock_keeper".into()), None)
+            .expect("valid registry prefix");
+        let locks_tracked =
+            IntGauge::new("locks_tracked", "Time locks seen in the last scan").unwrap();
+        let unlocks_submitted =
+            IntCounter::new("unlocks_submitted_total", "Unlock transactions confirmed").unwrap();
+        let failures = IntCounterVec::new(
+            Opts::new("failures_total", "Failed operations by error code"),
+            &["code"],
+        )
+        .unwrap();
+        let rpc_latency = HistogramVec::new(
+            HistogramOpts::new("rpc_latency_seconds", "RPC latency by method"),
+            &["method"],
+        )
+        .unwrap();
+        let fees_lamports =
+            IntCounter::new("fees_lamports_total", "Lamports paid in transaction fees").unwrap();
+        registry.register(Box::new(locks_tracked.clone())).unwrap();
+        registry
+            .register(Box::new(unlocks_submitted.clone()))
+            .unwrap();
+        registry.register(Box::new(failures.clone())).unwrap();
+        registry.register(Box::new(rpc_latency.clone())).unwrap();
+        registry.register(Box::new(fees_lamports.clone())).unwrap();
+        Self {
+            registry,
+            locks_tracked,
+            unlocks_submitted,
+            failures,
+            rpc_latency,
+            fees_lamports,
+        }
+    }
+
+    /// Start timing an RPC call, the latency is recorded when the guard drops.
+    pub fn time_rpc(&self, method: &str) -> RpcTimer {
+        RpcTimer {
+            histogram: self.rpc_latency.with_label_values(&[method]),
+            start: Instant::now(),
+        }
+    }
+
+    /// Count a failure under its error code.
+    pub fn record_failure(&self, error: &Error) {
+        self.failures
+            .with_label_values(&[error_code(error).as_str()])
+            .inc();
+    }
+
+    /// Render every metric in the prometheus text format.
+    pub fn encode(&self) -> String {
+        let mut buf = vec![];
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buf)
+            .expect("encoding to a vec never fails");
+        String::from_utf8(buf).expect("prometheus text format is utf8")
+    }
+}
+
+/// Records the elapsed time of an RPC call on drop.
+pub struct RpcTimer {
+    histogram: prometheus::Histogram,
+    start: Instant,
+}
+
+impl Drop for RpcTimer {
+    fn drop(&mut self) {
+        self.histogram.observe(self.start.elapsed().as_secs_f64());
+    }
+}
+
+/// Serve `/metrics` on `addr` until the task is dropped.
+pub async fn serve(metrics: Arc<Metrics>, addr: SocketAddr) -> Result<()> {
+    let app = Router::new().route(
+        "/metrics",
+        get(move || {
+            let metrics = metrics.clone();
+            async move { metrics.encode() }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+/// Stable label for an error: the custom program error code when there is
+/// one, the kind of failure otherwise.
+fn error_code(error: &Error) -> String {
+    match error {
+        Error::Rpc(e) => match e.get_transaction_error() {
+            Some(TransactionError::InstructionError(_, InstructionError::Custom(code))) => {
+                code.to_string()
+            }
+            Some(TransactionError::InstructionError(_, e)) => format!("{e:?}"),
+            Some(e) => format!("{e:?}"),
+            None => "rpc".to_string(),
+        },
+        Error::Http(_) => "http".to_string(),
+        Error::Pubsub(_) => "pubsub".to_string(),
+        Error::Postgres(_) => "postgres".to_string(),
+        Error::Rejected { service, .. } => format!("{service}_rejected"),
+        Error::Config(_) => "config".to_string(),
+        Error::Io(_) => "io".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let metrics = Metrics::new();
+        metrics.unlocks_submitted.inc();
+        metrics.fees_lamports.inc_by(5000);
+        drop(metrics.time_rpc("getProgramAccounts"));
+        let text = metrics.encode();
+        assert!(text.contains("timelock_keeper_unlocks_submitted_total 1"));
+        assert!(text.contains("timelock_keeper_fees_lamports_total 5000"));
+        assert!(text.contains("method=\"getProgramAccounts\""));
+    }
+}
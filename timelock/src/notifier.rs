@@ -0,0 +1,201 @@
+//! Notification backends used to ping teams when their time locks unlock.
+//!
+//! Implement [`Notifier`] to plug in a custom channel, or use the bundled
+//! [`Discord`] and [`Telegram`] backends. Several backends can be combined
+//! with [`Notifiers`].
+
+use std::fmt;
+
+use async_trait::async_trait;
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{Error, Result};
+
+/// An event worth telling a human about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Notification {
+    /// A time lock reached its unlock timestamp and was unlocked.
+    Unlocked {
+        /// Address of the time lock account.
+        lock: Pubkey,
+        /// Unix timestamp the lock was set to.
+        timestamp: i64,
+    },
+    /// A time lock matured but stayed unclaimed past the grace period.
+    Overdue {
+        /// Address of the time lock account.
+        lock: Pubkey,
+        /// Unix timestamp the lock was set to.
+        timestamp: i64,
+    },
+}
+
+impl fmt::Display for Notification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Notification::Unlocked { lock, timestamp } => {
+                write!(
+                    f,
+                    "🔓 TimeLock {lock} unlocked (unix timestamp {timestamp})"
+                )
+            }
+            Notification::Overdue { lock, timestamp } => {
+                write!(
+                    f,
+                    "⚠️ TimeLock {lock} matured at unix timestamp {timestamp} but is still unclaimed"
+                )
+            }
+        }
+    }
+}
+
+/// A channel notifications can be delivered to.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// Deliver `notification`.
+    async fn notify(&self, notification: &Notification) -> Result<()>;
+}
+
+/// Posts notifications to a Discord channel through a webhook.
+#[derive(Debug, Clone)]
+pub struct Discord {
+    client: reqwest::Client,
+    webhook_url: String,
+}
+
+impl Discord {
+    /// Create a backend posting to `webhook_url`.
+    pub fn new(webhook_url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            webhook_url: webhook_url.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for Discord {
+    async fn notify(&self, notification: &Notification) -> Result<()> {
+        self.client
+            .post(&self.webhook_url)
+            .json(&json!({ "content": notification.to_string() }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Sends notifications to a Telegram chat through the bot API.
+#[derive(Debug, Clone)]
+pub struct Telegram {
+    client: reqwest::Client,
+    bot_token: String,
+    chat_id: String,
+}
+
+impl Telegram {
+    /// Create a backend sending as the bot `bot_token` to `chat_id`.
+    pub fn new(bot_token: impl Into<String>, chat_id: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            bot_token: bot_token.into(),
+            chat_id: chat_id.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for Telegram {
+    async fn notify(&self, notification: &Notification) -> Result<()> {
+        let resp: serde_json::Value = self
+            .client
+            .post(format!(
+                "https://api.telegram.org/bot{}/sendMessage",
+                self.bot_token
+            ))
+            .json(&json!({ "chat_id": self.chat_id, "text": notification.to_string() }))
+            .send()
+            .await?
+            .json()
+            .await?;
+        // telegram answers 200 with `ok: false` for some failures
+        match resp["ok"].as_bool() {
+            Some(true) => Ok(()),
+            _ => Err(Error::Rejected {
+                service: "telegram",
+                reason: resp["description"]
+                    .as_str()
+                    .unwrap_or("unknown error")
+                    .to_string(),
+            }),
+        }
+    }
+}
+
+/// Fans a notification out to every registered backend.
+#[derive(Default)]
+pub struct Notifiers {
+    backends: Vec<Box<dyn Notifier>>,
+}
+
+impl Notifiers {
+    /// Create an empty set of backends.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register another backend.
+    pub fn with(mut self, backend: impl Notifier + 'static) -> Self {
+        self.backends.push(Box::new(backend));
+        self
+    }
+}
+
+#[async_trait]
+impl Notifier for Notifiers {
+    /// Every backend is tried; the first error, if any, is returned.
+    async fn notify(&self, notification: &Notification) -> Result<()> {
+        let mut res = Ok(());
+        for backend in &self.backends {
+            if let Err(e) = backend.notify(notification).await {
+                if res.is_ok() {
+                    res = Err(e);
+                }
+            }
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    struct Counter(Arc<AtomicUsize>);
+
+    #[async_trait]
+    impl Notifier for Counter {
+        async fn notify(&self, _: &Notification) -> Result<()> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_notifiers_fan_out() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let notifiers = Notifiers::new()
+            .with(Counter(count.clone()))
+            .with(Counter(count.clone()));
+        let notification = Notification::Unlocked {
+            lock: Pubkey::new_unique(),
+            timestamp: 0,
+        };
+        notifiers.notify(&notification).await.unwrap();
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,112 @@
+//! Provenance of the deployed program, for integrators to check it runs the
+//! source they reviewed.
+//!
+//! The admin records the hash `solana-verify` computes for the verified build,
+//! and the commit it was built from, in the program config with
+//! `RecordVerifiedBuild`. [`verify_build`] hashes the executable actually
+//! deployed the same way and compares both in a [`BuildVerification`].
+
+use borsh::BorshDeserialize;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+use solana_sdk::hash::hash;
+use solana_sdk::pubkey::Pubkey;
+use timelock_program::{config_address, ConfigAccount};
+
+use crate::{Error, Result};
+
+/// Verified build recorded in the program config against the deployed one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuildVerification {
+    /// Hash of the verified build recorded by the admin, `None` until
+    /// recorded.
+    pub recorded_hash: Option<[u8; 32]>,
+    /// Git commit of the source of the recorded build.
+    pub source_commit: [u8; 20],
+    /// Hash of the executable deployed.
+    pub deployed_hash: [u8; 32],
+}
+
+impl BuildVerification {
+    /// Whether the deployed program is the recorded verified build.
+    pub fn is_verified(&self) -> bool {
+        self.recorded_hash == Some(self.deployed_hash)
+    }
+
+    /// Source commit in hex, as git shows it.
+    pub fn source_commit_hex(&self) -> String {
+        hex(&self.source_commit)
+    }
+
+    /// Deployed hash in hex, as `solana-verify get-program-hash` shows it.
+    pub fn deployed_hash_hex(&self) -> String {
+        hex(&self.deployed_hash)
+    }
+}
+
+/// Hash of the executable in the program data account `data`, as
+/// `solana-verify` computes it: sha256 of the executable with its trailing
+/// zeros trimmed, as deployments pad it.
+pub fn program_hash(data: &[u8]) -> Result<[u8; 32]> {
+    let executable = data
+        .get(UpgradeableLoaderState::size_of_programdata_metadata()..)
+        .ok_or_else(|| Error::Config("not a program data account".to_string()))?;
+    let len = executable
+        .iter()
+        .rposition(|byte| *byte != 0)
+        .map_or(0, |last| last + 1);
+    Ok(hash(&executable[..len]).to_bytes())
+}
+
+/// Compare the verified build recorded in the config of `program_id` with
+/// the executable deployed in its program data account.
+pub async fn verify_build(rpc: &RpcClient, program_id: &Pubkey) -> Result<BuildVerification> {
+    let config = rpc.get_account_data(&config_address(program_id).0).await?;
+    let config = ConfigAccount::try_from_slice(&config)?;
+    let programdata = bpf_loader_upgradeable::get_program_data_address(program_id);
+    let deployed_hash = program_hash(&rpc.get_account_data(&programdata).await?)?;
+    let recorded = config.verified_build_hash != [0; 32];
+    Ok(BuildVerification {
+        recorded_hash: recorded.then_some(config.verified_build_hash),
+        source_commit: config.verified_source_commit,
+        deployed_hash,
+    })
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_program_hash() {
+        let metadata = UpgradeableLoaderState::size_of_programdata_metadata();
+        let mut data = vec![1; metadata];
+        data.extend_from_slice(b"\x7fELF program");
+        let unpadded = program_hash(&data).unwrap();
+        assert_eq!(unpadded, hash(b"\x7fELF program").to_bytes());
+
+        // the padding of the deployment leaves the hash as is
+        data.resize(data.len() + 100, 0);
+        assert_eq!(program_hash(&data).unwrap(), unpadded);
+        assert!(program_hash(&data[..metadata - 1]).is_err());
+    }
+
+    #[test]
+    fn test_is_verified() {
+        let mut verification = BuildVerification {
+            recorded_hash: None,
+            source_commit: [0xab; 20],
+            deployed_hash: [7; 32],
+        };
+        assert!(!verification.is_verified());
+        verification.recorded_hash = Some([7; 32]);
+        assert!(verification.is_verified());
+        verification.recorded_hash = Some([8; 32]);
+        assert!(!verification.is_verified());
+        assert_eq!(verification.source_commit_hex(), "ab".repeat(20));
+    }
+}
@@ -0,0 +1,157 @@
+//! Reading time lock accounts over RPC, and sending transactions with
+//! estimated compute budgets.
+
+use borsh::BorshDeserialize;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::client_error::ClientError;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{
+    RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSimulateTransactionConfig,
+};
+use solana_client::rpc_filter::RpcFilterType;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::Message;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::Transaction;
+use timelock_program::{callback_address, CallbackAccount, TimeLockAccount};
+
+use crate::signer::{sign_transaction, TimelockSigner};
+use crate::{filter, Result};
+
+/// Fetch and decode every time lock of the program.
+pub async fn fetch_locks(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+) -> Result<Vec<(Pubkey, TimeLockAccount)>> {
+    fetch_accounts(rpc, program_id, vec![filter::time_locks()]).await
+}
+
+/// Fetch and decode the accounts of the program matching `filters`, see
+/// [`filter`]. The filters must select accounts of type `T` only.
+pub async fn fetch_accounts<T: BorshDeserialize>(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    filters: Vec<RpcFilterType>,
+) -> Result<Vec<(Pubkey, T)>> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    rpc.get_program_accounts_with_config(program_id, config)
+        .await?
+        .into_iter()
+        .map(|(pubkey, account)| Ok((pubkey, T::try_from_slice(&account.data)?)))
+        .collect()
+}
+
+/// Fetch and decode the time lock at `lock`, `None` if it is not a time lock of the program.
+pub async fn fetch_lock(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    lock: &Pubkey,
+) -> Result<Option<TimeLockAccount>> {
+    let account = rpc
+        .get_account_with_commitment(lock, rpc.commitment())
+        .await?
+        .value;
+    match account {
+        Some(account)
+            if account.owner == *program_id && account.data.len() == TimeLockAccount::LEN =>
+        {
+            Ok(Some(TimeLockAccount::try_from_slice(&account.data)?))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Fetch and decode the callback of `lock`, `None` if it has none.
+pub async fn fetch_callback(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    lock: &Pubkey,
+) -> Result<Option<CallbackAccount>> {
+    let address = callback_address(program_id, lock).0;
+    let account = rpc
+        .get_account_with_commitment(&address, rpc.commitment())
+        .await?
+        .value;
+    match account {
+        Some(account) if account.owner == *program_id => {
+            Ok(Some(CallbackAccount::try_from_slice(&account.data)?))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Most compute units a transaction may request.
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Compute units requested on top of the simulated consumption, in percent.
+pub const COMPUTE_UNIT_MARGIN_PERCENT: u64 = 20;
+
+/// Compute unit limit covering `units_consumed` with the margin.
+pub fn compute_unit_limit(units_consumed: u64) -> u32 {
+    let limit = units_consumed.saturating_mul(100 + COMPUTE_UNIT_MARGIN_PERCENT) / 100;
+    u32::try_from(limit).map_or(MAX_COMPUTE_UNIT_LIMIT, |l| l.min(MAX_COMPUTE_UNIT_LIMIT))
+}
+
+/// Simulate `ixs` paid by `payer`, then send them with a compute unit limit
+/// of the units the simulation consumed plus the margin, waiting for
+/// confirmation. A failing simulation is returned instead of sending. The
+/// simulation needs no signature, so each signer signs once.
+pub async fn send_with_estimation(
+    rpc: &RpcClient,
+    ixs: &[Instruction],
+    payer: &dyn TimelockSigner,
+    signers: &[&dyn TimelockSigner],
+) -> Result<Signature> {
+    // simulated with the budget instruction, so it is accounted for
+    let mut budgeted = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+        MAX_COMPUTE_UNIT_LIMIT,
+    )];
+    budgeted.extend_from_slice(ixs);
+    let blockhash = rpc.get_latest_blockhash().await?;
+    let message = Message::new_with_blockhash(&budgeted, Some(&payer.pubkey()), &blockhash);
+    let config = RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        commitment: Some(rpc.commitment()),
+        ..Default::default()
+    };
+    let simulation = rpc
+        .simulate_transaction_with_config(&Transaction::new_unsigned(message), config)
+        .await?
+        .value;
+    if let Some(err) = simulation.err {
+        return Err(ClientError::from(err).into());
+    }
+    let units = simulation
+        .units_consumed
+        .unwrap_or(MAX_COMPUTE_UNIT_LIMIT as u64);
+    budgeted[0] = ComputeBudgetInstruction::set_compute_unit_limit(compute_unit_limit(units));
+    let message = Message::new_with_blockhash(&budgeted, Some(&payer.pubkey()), &blockhash);
+    let mut tx = Transaction::new_unsigned(message);
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    sign_transaction(&mut tx, &all_signers)?;
+    Ok(rpc.send_and_confirm_transaction(&tx).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_unit_limit() {
+        assert_eq!(compute_unit_limit(0), 0);
+        assert_eq!(compute_unit_limit(10_000), 12_000);
+        assert_eq!(compute_unit_limit(1_200_000), MAX_COMPUTE_UNIT_LIMIT);
+        assert_eq!(compute_unit_limit(u64::MAX), MAX_COMPUTE_UNIT_LIMIT);
+    }
+}
@@ -0,0 +1,106 @@
+//! Signers of the transactions the crate builds, see [`TimelockSigner`].
+
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::Transaction;
+
+use crate::{Error, Result};
+
+/// Key signing transactions, with as little as wallets offer: a pubkey and
+/// message signing.
+///
+/// Every [`Signer`] is one, so keypairs, presigners and the remote wallets of
+/// `solana-remote-wallet` work as is. Other wallets, e.g. a browser wallet
+/// reached from wasm, plug in through [`CallbackSigner`].
+pub trait TimelockSigner {
+    /// Key the signatures verify against.
+    fn pubkey(&self) -> Pubkey;
+
+    /// Sign the serialized message of a transaction.
+    fn sign_message(&self, message: &[u8]) -> Result<Signature>;
+}
+
+impl<T: Signer + ?Sized> TimelockSigner for T {
+    fn pubkey(&self) -> Pubkey {
+        Signer::pubkey(self)
+    }
+
+    fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        self.try_sign_message(message)
+            .map_err(|e| Error::Signer(e.to_string()))
+    }
+}
+
+/// Signer of `pubkey` calling `sign` on each message.
+pub struct CallbackSigner<F> {
+    pubkey: Pubkey,
+    sign: F,
+}
+
+impl<F: Fn(&[u8]) -> Result<Signature>> CallbackSigner<F> {
+    /// Signer of `pubkey` whose signatures `sign` makes.
+    pub fn new(pubkey: Pubkey, sign: F) -> Self {
+        Self { pubkey, sign }
+    }
+}
+
+impl<F: Fn(&[u8]) -> Result<Signature>> TimelockSigner for CallbackSigner<F> {
+    fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        (self.sign)(message)
+    }
+}
+
+/// Sign `tx` with `signers`, which must cover every signature it requires.
+/// Each signer signs once, whatever the number of signers.
+pub fn sign_transaction(tx: &mut Transaction, signers: &[&dyn TimelockSigner]) -> Result<()> {
+    let message = tx.message_data();
+    let required = usize::from(tx.message.header.num_required_signatures);
+    tx.signatures = vec![Signature::default(); required];
+    for (key, signature) in tx.message.account_keys[..required]
+        .iter()
+        .zip(&mut tx.signatures)
+    {
+        let signer = signers
+            .iter()
+            .find(|s| s.pubkey() == *key)
+            .ok_or_else(|| Error::Signer(format!("missing signature of {key}")))?;
+        *signature = signer.sign_message(&message)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::message::Message;
+    use solana_sdk::signature::Keypair;
+    use solana_sdk::system_instruction;
+
+    use super::*;
+
+    #[test]
+    fn test_sign_transaction() {
+        let payer = Keypair::new();
+        let sender = Keypair::new();
+        let ix = system_instruction::transfer(&Signer::pubkey(&sender), &Pubkey::new_unique(), 1);
+        let message = Message::new(&[ix], Some(&Signer::pubkey(&payer)));
+
+        // the callback signer stands for a wallet holding the sender key
+        let wallet = CallbackSigner::new(Signer::pubkey(&sender), |message: &[u8]| {
+            Ok(Signer::sign_message(&sender, message))
+        });
+        let mut tx = Transaction::new_unsigned(message.clone());
+        sign_transaction(&mut tx, &[&payer, &wallet]).unwrap();
+        tx.verify().unwrap();
+
+        let mut tx = Transaction::new_unsigned(message);
+        assert!(matches!(
+            sign_transaction(&mut tx, &[&payer]),
+            Err(Error::Signer(_))
+        ));
+    }
+}
@@ -0,0 +1,223 @@
+//! Snapshots of every account of the program, for migration rehearsals and
+//! load tests against mainnet-shaped data.
+//!
+//! [`export`] reads the program-owned accounts over RPC into a [`Snapshot`],
+//! archived as gzipped JSON. [`Snapshot::write_account_dir`] writes them back
+//! as the account files `solana-test-validator --account-dir` loads into the
+//! genesis of a localnet.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::config::pubkey;
+use crate::{Error, Result};
+
+/// Version of the archive layout, bumped on incompatible changes.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Accounts of a program at a slot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Layout version, [`SNAPSHOT_VERSION`] when written by this crate.
+    pub version: u32,
+    /// Program owning the accounts.
+    #[serde(serialize_with = "display", deserialize_with = "pubkey")]
+    pub program_id: Pubkey,
+    /// Slot the export started at.
+    pub slot: u64,
+    /// Accounts, sorted by address.
+    pub accounts: Vec<SnapshotAccount>,
+}
+
+/// One account of a [`Snapshot`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotAccount {
+    /// Address of the account.
+    #[serde(serialize_with = "display", deserialize_with = "pubkey")]
+    pub address: Pubkey,
+    /// Balance of the account.
+    pub lamports: u64,
+    /// Program owning the account.
+    #[serde(serialize_with = "display", deserialize_with = "pubkey")]
+    pub owner: Pubkey,
+    /// Whether the account is an executable program.
+    pub executable: bool,
+    /// Epoch the account next owes rent at.
+    pub rent_epoch: u64,
+    /// Data of the account, base64 in the archive.
+    #[serde(serialize_with = "base64", deserialize_with = "from_base64")]
+    pub data: Vec<u8>,
+}
+
+/// Read every account owned by `program_id` into a snapshot.
+pub async fn export(rpc: &RpcClient, program_id: &Pubkey) -> Result<Snapshot> {
+    let slot = rpc.get_slot().await?;
+    let config = RpcProgramAccountsConfig {
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut accounts: Vec<_> = rpc
+        .get_program_accounts_with_config(program_id, config)
+        .await?
+        .into_iter()
+        .map(|(address, account)| SnapshotAccount {
+            address,
+            lamports: account.lamports,
+            owner: account.owner,
+            executable: account.executable,
+            rent_epoch: account.rent_epoch,
+            data: account.data,
+        })
+        .collect();
+    accounts.sort_by_key(|a| a.address);
+    Ok(Snapshot {
+        version: SNAPSHOT_VERSION,
+        program_id: *program_id,
+        slot,
+        accounts,
+    })
+}
+
+impl Snapshot {
+    /// Write the snapshot as gzipped JSON.
+    pub fn write_to(&self, writer: impl Write) -> Result<()> {
+        let mut encoder = GzEncoder::new(writer, Compression::default());
+        serde_json::to_writer(&mut encoder, self).map_err(std::io::Error::from)?;
+        encoder.finish()?;
+        Ok(())
+    }
+
+    /// Read a snapshot written by [`Snapshot::write_to`], rejecting other
+    /// layout versions.
+    pub fn read_from(reader: impl Read) -> Result<Self> {
+        let snapshot: Self =
+            serde_json::from_reader(GzDecoder::new(reader)).map_err(std::io::Error::from)?;
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(Error::Snapshot(format!(
+                "unsupported version {}, expected {SNAPSHOT_VERSION}",
+                snapshot.version
+            )));
+        }
+        Ok(snapshot)
+    }
+
+    /// Write the snapshot to the archive at `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        self.write_to(BufWriter::new(File::create(path)?))
+    }
+
+    /// Read the archive at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+
+    /// Write one `<address>.json` account file per account into `dir`, in
+    /// the format of `solana account --output json` that
+    /// `solana-test-validator --account-dir` loads.
+    pub fn write_account_dir(&self, dir: impl AsRef<Path>) -> Result<()> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        for account in &self.accounts {
+            let path = dir.join(format!("{}.json", account.address));
+            std::fs::write(path, account.to_account_file().to_string())?;
+        }
+        Ok(())
+    }
+}
+
+impl SnapshotAccount {
+    fn to_account_file(&self) -> serde_json::Value {
+        serde_json::json!({
+            "pubkey": self.address.to_string(),
+            "account": {
+                "lamports": self.lamports,
+                "data": [BASE64_STANDARD.encode(&self.data), "base64"],
+                "owner": self.owner.to_string(),
+                "executable": self.executable,
+                "rentEpoch": self.rent_epoch,
+                "space": self.data.len(),
+            },
+        })
+    }
+}
+
+fn display<S: Serializer>(pubkey: &Pubkey, s: S) -> std::result::Result<S::Ok, S::Error> {
+    s.collect_str(pubkey)
+}
+
+fn base64<S: Serializer>(data: &[u8], s: S) -> std::result::Result<S::Ok, S::Error> {
+    s.serialize_str(&BASE64_STANDARD.encode(data))
+}
+
+fn from_base64<'de, D: Deserializer<'de>>(d: D) -> std::result::Result<Vec<u8>, D::Error> {
+    let s = String::deserialize(d)?;
+    BASE64_STANDARD.decode(s).map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> Snapshot {
+        let program_id = Pubkey::new_unique();
+        Snapshot {
+            version: SNAPSHOT_VERSION,
+            program_id,
+            slot: 42,
+            accounts: vec![SnapshotAccount {
+                address: Pubkey::new_unique(),
+                lamports: 1_000_000,
+                owner: program_id,
+                executable: false,
+                rent_epoch: u64::MAX,
+                data: vec![1, 2, 3, 255],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let snapshot = snapshot();
+        let mut archive = vec![];
+        snapshot.write_to(&mut archive).unwrap();
+        assert_eq!(Snapshot::read_from(archive.as_slice()).unwrap(), snapshot);
+    }
+
+    #[test]
+    fn test_reject_other_version() {
+        let mut snapshot = snapshot();
+        snapshot.version = SNAPSHOT_VERSION + 1;
+        let mut archive = vec![];
+        snapshot.write_to(&mut archive).unwrap();
+        assert!(matches!(
+            Snapshot::read_from(archive.as_slice()),
+            Err(Error::Snapshot(_))
+        ));
+    }
+
+    #[test]
+    fn test_account_file() {
+        let snapshot = snapshot();
+        let account = &snapshot.accounts[0];
+        let file = account.to_account_file();
+        assert_eq!(file["pubkey"], account.address.to_string());
+        assert_eq!(file["account"]["data"][0], "AQID/w==");
+        assert_eq!(file["account"]["data"][1], "base64");
+        assert_eq!(file["account"]["space"], 4);
+        assert_eq!(file["account"]["owner"], snapshot.program_id.to_string());
+    }
+}
@@ -0,0 +1,258 @@
+//! Websocket subscriptions to time locks.
+//!
+//! [`subscribe_lock`] and [`subscribe_program_locks`] combine an account (or
+//! program) subscription with a logs subscription, decode both into
+//! [`LockUpdate`]s and keep them alive: when the websocket drops, they
+//! reconnect with [`Backoff`] and subscribe again. Updates happening while
+//! disconnected are not replayed; use [`crate::rpc`] to resync if needed.
+//!
+//! The subscription runs until the returned receiver is dropped.
+
+use std::pin::Pin;
+use std::str::FromStr as _;
+
+use borsh::BorshDeserialize as _;
+use futures_util::{Stream, StreamExt as _};
+use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use solana_client::rpc_config::{
+    RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionLogsConfig,
+    RpcTransactionLogsFilter,
+};
+use solana_client::rpc_filter::RpcFilterType;
+use solana_pubsub_client::nonblocking::pubsub_client::PubsubClient;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use timelock_program::TimeLockAccount;
+use tokio::sync::mpsc;
+
+use crate::event::{parse_logs, TimeLockEvent};
+use crate::keeper::Backoff;
+use crate::{Error, Result};
+
+/// A change seen on a subscription.
+#[derive(Debug)]
+pub enum LockUpdate {
+    /// New state of a time lock account.
+    Account {
+        /// Address of the lock.
+        lock: Pubkey,
+        /// Slot of the change.
+        slot: u64,
+        /// Decoded account.
+        account: TimeLockAccount,
+    },
+    /// Events of a successful transaction touching the lock or program.
+    Events {
+        /// Transaction signature.
+        signature: String,
+        /// Slot of the transaction.
+        slot: u64,
+        /// Events in emission order.
+        events: Vec<TimeLockEvent>,
+    },
+}
+
+/// Follow the time lock at `lock` and the transactions mentioning it.
+pub fn subscribe_lock(
+    ws_url: &str,
+    program_id: Pubkey,
+    lock: Pubkey,
+) -> mpsc::UnboundedReceiver<LockUpdate> {
+    spawn(ws_url, program_id, Some(lock))
+}
+
+/// Follow every time lock of the program and the transactions calling it.
+pub fn subscribe_program_locks(
+    ws_url: &str,
+    program_id: Pubkey,
+) -> mpsc::UnboundedReceiver<LockUpdate> {
+    spawn(ws_url, program_id, None)
+}
+
+fn spawn(
+    ws_url: &str,
+    program_id: Pubkey,
+    lock: Option<Pubkey>,
+) -> mpsc::UnboundedReceiver<LockUpdate> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let ws_url = ws_url.to_string();
+    tokio::spawn(async move {
+        let backoff = Backoff::default();
+        let mut attempt = 0;
+        loop {
+            match session(&ws_url, &program_id, lock, &tx, &mut attempt).await {
+                // the receiver was dropped
+                Ok(()) => return,
+                Err(e) => {
+                    let delay = backoff.delay(attempt);
+                    tracing::warn!("subscription lost, reconnecting in {delay:?}: {e}");
+                    tokio::time::sleep(delay).await;
+                    attempt = attempt.saturating_add(1);
+                }
+            }
+            if tx.is_closed() {
+                return;
+            }
+        }
+    });
+    rx
+}
+
+type AccountStream<'a> =
+    Pin<Box<dyn Stream<Item = Option<(Pubkey, u64, TimeLockAccount)>> + Send + 'a>>;
+
+/// Subscribe once and forward updates until the connection fails.
+async fn session(
+    ws_url: &str,
+    program_id: &Pubkey,
+    lock: Option<Pubkey>,
+    tx: &mpsc::UnboundedSender<LockUpdate>,
+    attempt: &mut u32,
+) -> Result<()> {
+    let pubsub = PubsubClient::new(ws_url).await?;
+    let commitment = Some(CommitmentConfig::confirmed());
+    let account_config = RpcAccountInfoConfig {
+        encoding: Some(UiAccountEncoding::Base64),
+        commitment,
+        ..Default::default()
+    };
+    let mentions = lock.unwrap_or(*program_id);
+    let (mut logs, _logs_unsubscribe) = pubsub
+        .logs_subscribe(
+            RpcTransactionLogsFilter::Mentions(vec![mentions.to_string()]),
+            RpcTransactionLogsConfig { commitment },
+        )
+        .await?;
+    let program_id = *program_id;
+    let mut accounts: AccountStream<'_> = match lock {
+        Some(lock) => {
+            let (stream, _unsubscribe) = pubsub
+                .account_subscribe(&lock, Some(account_config))
+                .await?;
+            Box::pin(stream.map(move |resp| {
+                decode(&resp.value, &program_id).map(|account| (lock, resp.context.slot, account))
+            }))
+        }
+        None => {
+            let config = RpcProgramAccountsConfig {
+                filters: Some(vec![RpcFilterType::DataSize(TimeLockAccount::LEN as u64)]),
+                account_config,
+                ..Default::default()
+            };
+            let (stream, _unsubscribe) =
+                pubsub.program_subscribe(&program_id, Some(config)).await?;
+            Box::pin(stream.map(move |resp| {
+                let lock = Pubkey::from_str(&resp.value.pubkey).ok()?;
+                decode(&resp.value.account, &program_id)
+                    .map(|account| (lock, resp.context.slot, account))
+            }))
+        }
+    };
+    *attempt = 0;
+
+    loop {
+        let update = tokio::select! {
+            Some(resp) = logs.next() => {
+                if resp.value.err.is_some() {
+                    continue;
+                }
+                let events = parse_logs(&resp.value.logs);
+                if events.is_empty() {
+                    continue;
+                }
+                LockUpdate::Events {
+                    signature: resp.value.signature,
+                    slot: resp.context.slot,
+                    events,
+                }
+            }
+            Some(decoded) = accounts.next() => {
+                let Some((lock, slot, account)) = decoded else {
+                    continue;
+                };
+                LockUpdate::Account { lock, slot, account }
+            }
+            else => {
+                return Err(Error::Rejected {
+                    service: "websocket",
+                    reason: "subscription closed".into(),
+                })
+            }
+        };
+        if tx.send(update).is_err() {
+            return Ok(());
+        }
+    }
+}
+
+/// Decode `account` if it is a time lock of the program.
+fn decode(account: &UiAccount, program_id: &Pubkey) -> Option<TimeLockAccount> {
+    let account: Account = account.decode()?;
+    if account.owner != *program_id || account.data.len() != TimeLockAccount::LEN {
+        return None;
+    }
+    TimeLockAccount::try_from_slice(&account.data).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize as _;
+    use timelock_program::{MAX_UNLOCKERS, SECRET_LENGTH};
+
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        let program_id = Pubkey::new_unique();
+        let lock = TimeLockAccount {
+            timestamp: 42,
+            secret: [b'a'; SECRET_LENGTH],
+            bounty: 5000,
+            attempts: 0,
+            premature_attempts: 0,
+            last_attempt: 0,
+            revealed_at: 0,
+            require_memo: false,
+            claim_memo_hash: [0; 32],
+            authority: Pubkey::new_unique(),
+            top_up_authority_only: false,
+            unlockers: [Pubkey::default(); MAX_UNLOCKERS],
+            expires_at: 0,
+            expiry_recipient: Pubkey::default(),
+            heartbeat_interval: 0,
+            recurrence_unit: TimeLockAccount::RECUR_NONE,
+            recurrence_every: 0,
+            recurrence_window: 0,
+            has_callback: false,
+            condition_account: Pubkey::default(),
+            condition_offset: 0,
+            condition_len: 0,
+            condition_bytes: [0; MAX_CONDITION_BYTES],
+            predecessor: Pubkey::default(),
+            has_audit_trail: false,
+            claim_commit_required: false,
+            in_progress: false,
+            funded: 0,
+            grant_recipient: Pubkey::default(),
+        };
+        let mut data = vec![];
+        lock.serialize(&mut data).unwrap();
+        let account = Account {
+            lamports: 1,
+            data,
+            owner: program_id,
+            executable: false,
+            rent_epoch: 0,
+        };
+        let ui = UiAccount::encode(
+            &Pubkey::new_unique(),
+            &account,
+            UiAccountEncoding::Base64,
+            None,
+            None,
+        );
+        assert_eq!(decode(&ui, &program_id).unwrap().bounty, 5000);
+        assert!(decode(&ui, &Pubkey::new_unique()).is_none());
+    }
+}
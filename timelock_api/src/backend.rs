@@ -0,0 +1,154 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use timelock::rpc::{fetch_lock, fetch_locks};
+use timelock_program::TimeLockAccount;
+use tokio_postgres::{Client, Row};
+
+use crate::ApiError;
+
+#[derive(Debug, Serialize)]
+pub struct LockView {
+    pub pubkey: String,
+    pub timestamp: i64,
+    pub bounty: u64,
+    pub matured: bool,
+    /// The encrypted secret, once matured and when read from chain
+    pub secret: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Stats {
+    pub total_locks: u64,
+    pub matured_locks: u64,
+    pub pending_bounties: u64,
+}
+
+/// Where lock data is read from
+pub enum Backend {
+    Rpc { rpc: RpcClient, program_id: Pubkey },
+    Indexer { db: Arc<Client> },
+}
+
+impl Backend {
+    pub fn rpc(rpc_url: String, program_id: Pubkey) -> Self {
+        Self::Rpc {
+            rpc: RpcClient::new_with_commitment(rpc_url, CommitmentConfig::confirmed()),
+            program_id,
+        }
+    }
+
+    pub async fn indexer(database_url: &str) -> timelock::Result<Self> {
+        Ok(Self::Indexer {
+            db: Arc::new(timelock::indexer::connect(database_url).await?),
+        })
+    }
+
+    /// The indexer database, if this backend reads from it.
+    pub fn db(&self) -> Option<Arc<Client>> {
+        match self {
+            Backend::Rpc { .. } => None,
+            Backend::Indexer { db } => Some(db.clone()),
+        }
+    }
+
+    pub async fn lock(&self, pubkey: &Pubkey) -> Result<Option<LockView>, ApiError> {
+        match self {
+            Backend::Rpc { rpc, program_id } => Ok(fetch_lock(rpc, program_id, pubkey)
+                .await?
+                .map(|lock| view(pubkey, &lock, unix_now()))),
+            Backend::Indexer { db } => {
+                let row = db
+                    .query_opt(
+                        "SELECT pubkey, timestamp, bounty FROM timelock_locks WHERE pubkey = $1",
+                        &[&pubkey.to_string()],
+                    )
+                    .await?;
+                Ok(row.map(|row| row_view(&row, unix_now())))
+            }
+        }
+    }
+
+    pub async fn wallet_locks(&self, wallet: &Pubkey) -> Result<Vec<LockView>, ApiError> {
+        match self {
+            // the creator of a lock is not stored on chain, only in its creation event
+            Backend::Rpc { .. } => Err(ApiError::Unsupported(
+                "wallet lookups need the indexer backend",
+            )),
+            Backend::Indexer { db } => {
+                let now = unix_now();
+                let rows = db
+                    .query(
+                        "SELECT pubkey, timestamp, bounty FROM timelock_locks
+                         WHERE payer = $1 ORDER BY timestamp",
+                        &[&wallet.to_string()],
+                    )
+                    .await?;
+                Ok(rows.iter().map(|row| row_view(row, now)).collect())
+            }
+        }
+    }
+
+    pub async fn stats(&self) -> Result<Stats, ApiError> {
+        let now = unix_now();
+        match self {
+            Backend::Rpc { rpc, program_id } => {
+                let locks = fetch_locks(rpc, program_id).await?;
+                Ok(Stats {
+                    total_locks: locks.len() as u64,
+                    matured_locks: locks.iter().filter(|(_, l)| l.timestamp <= now).count() as u64,
+                    pending_bounties: locks.iter().map(|(_, l)| l.bounty).sum(),
+                })
+            }
+            Backend::Indexer { db } => {
+                let row = db
+                    .query_one(
+                        "SELECT COUNT(*),
+                                COUNT(*) FILTER (WHERE timestamp <= $1),
+                                COALESCE(SUM(bounty), 0)::BIGINT
+                         FROM timelock_locks",
+                        &[&now],
+                    )
+                    .await?;
+                Ok(Stats {
+                    total_locks: row.get::<_, i64>(0) as u64,
+                    matured_locks: row.get::<_, i64>(1) as u64,
+                    pending_bounties: row.get::<_, i64>(2) as u64,
+                })
+            }
+        }
+    }
+}
+
+fn view(pubkey: &Pubkey, lock: &TimeLockAccount, now: i64) -> LockView {
+    let matured = lock.timestamp <= now;
+    LockView {
+        pubkey: pubkey.to_string(),
+        timestamp: lock.timestamp,
+        bounty: lock.bounty,
+        matured,
+        secret: matured.then(|| String::from_utf8_lossy(&lock.secret).into_owned()),
+    }
+}
+
+fn row_view(row: &Row, now: i64) -> LockView {
+    let timestamp: i64 = row.get(1);
+    LockView {
+        pubkey: row.get(0),
+        timestamp,
+        bounty: row.get::<_, i64>(2) as u64,
+        matured: timestamp <= now,
+        secret: None,
+    }
+}
+
+pub(crate) fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock before unix epoch")
+        .as_secs() as i64
+}
@@ -0,0 +1,263 @@
+//! GraphQL schema over the indexer tables, served on `/graphql`.
+//!
+//! ```graphql
+//! {
+//!   locks(filter: { payer: "...", state: MATURED, unlockBefore: 1735689600 }) {
+//!     pubkey timestamp bounty state
+//!     events { kind slot signature }
+//!   }
+//!   stats { totalLocks pendingBounties }
+//! }
+//! ```
+
+use std::sync::Arc;
+
+use async_graphql::{
+    ComplexObject, Context, EmptyMutation, EmptySubscription, Enum, InputObject, Object, Result,
+    Schema, SimpleObject,
+};
+use tokio_postgres::types::ToSql;
+use tokio_postgres::{Client, Row};
+
+use crate::backend::unix_now;
+
+/// Schema served on `/graphql`.
+pub type TimeLockSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Build the schema reading from the indexer database.
+pub fn schema(db: Arc<Client>) -> TimeLockSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(db)
+        .limit_depth(8)
+        .finish()
+}
+
+/// Where a lock is in its life.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+pub enum LockState {
+    /// The unlock time is in the future.
+    Pending,
+    /// The unlock time passed but nobody revealed the secret yet.
+    Matured,
+    /// The secret was revealed on chain.
+    Revealed,
+}
+
+/// Criteria locks must all match.
+#[derive(Debug, Default, InputObject)]
+pub struct LockFilter {
+    /// Wallet that created the locks.
+    pub payer: Option<String>,
+    /// Lower bound (inclusive) of the unlock time.
+    pub unlock_after: Option<i64>,
+    /// Upper bound (inclusive) of the unlock time.
+    pub unlock_before: Option<i64>,
+    /// State of the locks.
+    pub state: Option<LockState>,
+}
+
+#[derive(Debug, SimpleObject)]
+#[graphql(complex)]
+struct Lock {
+    pubkey: String,
+    payer: String,
+    timestamp: i64,
+    bounty: u64,
+    state: LockState,
+    created_slot: u64,
+    revealed_slot: Option<u64>,
+    cranker: Option<String>,
+}
+
+#[ComplexObject]
+impl Lock {
+    /// Events about this lock, oldest first.
+    async fn events(&self, ctx: &Context<'_>) -> Result<Vec<Event>> {
+        let db = ctx.data::<Arc<Client>>()?;
+        let rows = db
+            .query(
+                "SELECT signature, idx, slot, block_time, kind, lock FROM timelock_events
+                 WHERE lock = $1 ORDER BY slot, signature, idx",
+                &[&self.pubkey],
+            )
+            .await?;
+        Ok(rows.iter().map(Event::from_row).collect())
+    }
+}
+
+#[derive(Debug, SimpleObject)]
+struct Event {
+    signature: String,
+    idx: i32,
+    slot: u64,
+    block_time: Option<i64>,
+    kind: String,
+    lock: String,
+}
+
+impl Event {
+    fn from_row(row: &Row) -> Self {
+        Self {
+            signature: row.get(0),
+            idx: row.get(1),
+            slot: row.get::<_, i64>(2) as u64,
+            block_time: row.get(3),
+            kind: row.get(4),
+            lock: row.get(5),
+        }
+    }
+}
+
+#[derive(Debug, SimpleObject)]
+struct Stats {
+    total_locks: u64,
+    pending_locks: u64,
+    matured_locks: u64,
+    revealed_locks: u64,
+    pending_bounties: u64,
+}
+
+const LOCK_COLUMNS: &str =
+    "pubkey, payer, timestamp, bounty, created_slot, revealed_slot, cranker FROM timelock_locks";
+
+impl Lock {
+    fn from_row(row: &Row, now: i64) -> Self {
+        let timestamp: i64 = row.get(2);
+        let revealed_slot: Option<i64> = row.get(5);
+        let state = match revealed_slot {
+            Some(_) => LockState::Revealed,
+            None if timestamp <= now => LockState::Matured,
+            None => LockState::Pending,
+        };
+        Self {
+            pubkey: row.get(0),
+            payer: row.get(1),
+            timestamp,
+            bounty: row.get::<_, i64>(3) as u64,
+            state,
+            created_slot: row.get::<_, i64>(4) as u64,
+            revealed_slot: revealed_slot.map(|s| s as u64),
+            cranker: row.get(6),
+        }
+    }
+}
+
+/// Add a query parameter, returning its placeholder.
+fn bind<T: ToSql + Sync + Send + 'static>(
+    params: &mut Vec<Box<dyn ToSql + Sync + Send>>,
+    value: T,
+) -> String {
+    params.push(Box::new(value));
+    format!("${}", params.len())
+}
+
+/// Root of every query.
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// One lock by address.
+    async fn lock(&self, ctx: &Context<'_>, pubkey: String) -> Result<Option<Lock>> {
+        let db = ctx.data::<Arc<Client>>()?;
+        let row = db
+            .query_opt(
+                &format!("SELECT {LOCK_COLUMNS} WHERE pubkey = $1"),
+                &[&pubkey],
+            )
+            .await?;
+        Ok(row.map(|row| Lock::from_row(&row, unix_now())))
+    }
+
+    /// Locks matching `filter`, by unlock time.
+    async fn locks(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] filter: LockFilter,
+        #[graphql(default = 100, validator(minimum = 1, maximum = 1000))] limit: i64,
+        #[graphql(default = 0, validator(minimum = 0))] offset: i64,
+    ) -> Result<Vec<Lock>> {
+        let db = ctx.data::<Arc<Client>>()?;
+        let now = unix_now();
+        let mut clauses = vec![];
+        let mut params: Vec<Box<dyn ToSql + Sync + Send>> = vec![];
+        if let Some(payer) = filter.payer {
+            clauses.push(format!("payer = {}", bind(&mut params, payer)));
+        }
+        if let Some(after) = filter.unlock_after {
+            clauses.push(format!("timestamp >= {}", bind(&mut params, after)));
+        }
+        if let Some(before) = filter.unlock_before {
+            clauses.push(format!("timestamp <= {}", bind(&mut params, before)));
+        }
+        match filter.state {
+            Some(LockState::Pending) => clauses.push(format!(
+                "revealed_slot IS NULL AND timestamp > {}",
+                bind(&mut params, now)
+            )),
+            Some(LockState::Matured) => clauses.push(format!(
+                "revealed_slot IS NULL AND timestamp <= {}",
+                bind(&mut params, now)
+            )),
+            Some(LockState::Revealed) => clauses.push("revealed_slot IS NOT NULL".to_string()),
+            None => {}
+        }
+        let query = format!(
+            "SELECT {LOCK_COLUMNS} {} ORDER BY timestamp, pubkey LIMIT {} OFFSET {}",
+            if clauses.is_empty() {
+                String::new()
+            } else {
+                format!("WHERE {}", clauses.join(" AND "))
+            },
+            bind(&mut params, limit),
+            bind(&mut params, offset),
+        );
+        let params: Vec<&(dyn ToSql + Sync)> = params
+            .iter()
+            .map(|p| p.as_ref() as &(dyn ToSql + Sync))
+            .collect();
+        let rows = db.query(&query, &params).await?;
+        Ok(rows.iter().map(|row| Lock::from_row(row, now)).collect())
+    }
+
+    /// Most recent events, optionally of one kind only.
+    async fn events(
+        &self,
+        ctx: &Context<'_>,
+        kind: Option<String>,
+        #[graphql(default = 100, validator(minimum = 1, maximum = 1000))] limit: i64,
+    ) -> Result<Vec<Event>> {
+        let db = ctx.data::<Arc<Client>>()?;
+        let rows = db
+            .query(
+                "SELECT signature, idx, slot, block_time, kind, lock FROM timelock_events
+                 WHERE $1::TEXT IS NULL OR kind = $1
+                 ORDER BY slot DESC, signature, idx LIMIT $2",
+                &[&kind, &limit],
+            )
+            .await?;
+        Ok(rows.iter().map(Event::from_row).collect())
+    }
+
+    /// Aggregates over every indexed lock.
+    async fn stats(&self, ctx: &Context<'_>) -> Result<Stats> {
+        let db = ctx.data::<Arc<Client>>()?;
+        let row = db
+            .query_one(
+                "SELECT COUNT(*),
+                        COUNT(*) FILTER (WHERE revealed_slot IS NULL AND timestamp > $1),
+                        COUNT(*) FILTER (WHERE revealed_slot IS NULL AND timestamp <= $1),
+                        COUNT(*) FILTER (WHERE revealed_slot IS NOT NULL),
+                        COALESCE(SUM(bounty), 0)::BIGINT
+                 FROM timelock_locks",
+                &[&unix_now()],
+            )
+            .await?;
+        Ok(Stats {
+            total_locks: row.get::<_, i64>(0) as u64,
+            pending_locks: row.get::<_, i64>(1) as u64,
+            matured_locks: row.get::<_, i64>(2) as u64,
+            revealed_locks: row.get::<_, i64>(3) as u64,
+            pending_bounties: row.get::<_, i64>(4) as u64,
+        })
+    }
+}
@@ -0,0 +1,236 @@
+//! gRPC `SubscribeLocks` stream of lock state transitions, see
+//! `proto/timelock.proto`.
+//!
+//! One websocket log subscription feeds every gRPC subscriber through a
+//! broadcast channel; a subscriber too slow to keep up gets its stream ended
+//! with `RESOURCE_EXHAUSTED` rather than silently missing transitions.
+
+use std::collections::HashSet;
+use std::pin::Pin;
+
+use futures_util::{Stream, StreamExt as _};
+use solana_client::rpc_config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter};
+use solana_pubsub_client::nonblocking::pubsub_client::PubsubClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use timelock::event::{parse_logs, TimeLockEvent};
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tonic::{Request, Response, Status};
+
+use proto::time_lock_stream_server::TimeLockStream;
+pub use proto::time_lock_stream_server::TimeLockStreamServer;
+use proto::{LockState, LockStateChange, SubscribeLocksRequest};
+
+pub mod proto {
+    tonic::include_proto!("timelock.v1");
+}
+
+/// Transitions buffered per subscriber before it is considered lagging.
+const CAPACITY: usize = 1024;
+
+/// Fans the transitions seen on chain out to gRPC subscribers.
+#[derive(Clone)]
+pub struct LockStream {
+    tx: broadcast::Sender<LockStateChange>,
+}
+
+impl Default for LockStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LockStream {
+    pub fn new() -> Self {
+        Self {
+            tx: broadcast::channel(CAPACITY).0,
+        }
+    }
+
+    /// Publish the transitions of the program's transactions until the
+    /// websocket closes.
+    pub async fn follow(&self, ws_url: &str, program_id: &Pubkey) -> timelock::Result<()> {
+        let pubsub = PubsubClient::new(ws_url).await?;
+        let (mut stream, _unsubscribe) = pubsub
+            .logs_subscribe(
+                RpcTransactionLogsFilter::Mentions(vec![program_id.to_string()]),
+                RpcTransactionLogsConfig {
+                    commitment: Some(CommitmentConfig::confirmed()),
+                },
+            )
+            .await?;
+        while let Some(resp) = stream.next().await {
+            if resp.value.err.is_some() {
+                continue;
+            }
+            for event in parse_logs(&resp.value.logs) {
+                if let Some(change) = state_change(&event, &resp.value.signature, resp.context.slot)
+                {
+                    // no subscriber is not an error
+                    let _ = self.tx.send(change);
+                }
+            }
+        }
+        Err(timelock::Error::Rejected {
+            service: "websocket",
+            reason: "log subscription closed".into(),
+        })
+    }
+}
+
+#[tonic::async_trait]
+impl TimeLockStream for LockStream {
+    type SubscribeLocksStream =
+        Pin<Box<dyn Stream<Item = Result<LockStateChange, Status>> + Send + 'static>>;
+
+    async fn subscribe_locks(
+        &self,
+        request: Request<SubscribeLocksRequest>,
+    ) -> Result<Response<Self::SubscribeLocksStream>, Status> {
+        let locks: HashSet<String> = request.into_inner().locks.into_iter().collect();
+        let stream = BroadcastStream::new(self.tx.subscribe())
+            .filter_map(move |item| {
+                let item = match item {
+                    Ok(change) if locks.is_empty() || locks.contains(&change.lock) => {
+                        Some(Ok(change))
+                    }
+                    Ok(_) => None,
+                    Err(BroadcastStreamRecvError::Lagged(n)) => Some(Err(
+                        Status::resource_exhausted(format!("subscriber lagged by {n} changes")),
+                    )),
+                };
+                futures_util::future::ready(item)
+            })
+            // end the stream after the lag error, the subscriber must resync
+            .scan(false, |failed, item| {
+                let done = *failed;
+                *failed = item.is_err();
+                futures_util::future::ready((!done).then_some(item))
+            });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+/// The transition `event` stands for, if any.
+fn state_change(event: &TimeLockEvent, signature: &str, slot: u64) -> Option<LockStateChange> {
+    let mut change = LockStateChange {
+        signature: signature.to_string(),
+        slot,
+        ..Default::default()
+    };
+    match event {
+        TimeLockEvent::LockCreated {
+            lock,
+            timestamp,
+            bounty,
+            ..
+        } => {
+            change.lock = lock.to_string();
+            change.set_state(LockState::Created);
+            change.timestamp = *timestamp;
+            change.bounty = *bounty;
+        }
+        TimeLockEvent::SecretRevealed { lock, .. } => {
+            change.lock = lock.to_string();
+            change.set_state(LockState::Unlocked);
+        }
+        TimeLockEvent::BountyPaid {
+            lock,
+            cranker,
+            amount,
+        } => {
+            change.lock = lock.to_string();
+            change.set_state(LockState::Claimed);
+            change.bounty = *amount;
+            change.cranker = Some(cranker.to_string());
+        }
+        TimeLockEvent::UnlockTooEarly { .. }
+        | TimeLockEvent::StakeLocked { .. }
+        | TimeLockEvent::StakeReleased { .. }
+        | TimeLockEvent::ToppedUp { .. }
+        | TimeLockEvent::ExcessWithdrawn { .. }
+        | TimeLockEvent::EscrowCreated { .. }
+        | TimeLockEvent::EscrowClaimed { .. }
+        | TimeLockEvent::EscrowRefunded { .. }
+        | TimeLockEvent::EscrowBeneficiaryChanged { .. }
+        | TimeLockEvent::EscrowDelegateSet { .. }
+        | TimeLockEvent::EscrowDelegateRevoked { .. }
+        | TimeLockEvent::EscrowClaimedBy { .. }
+        | TimeLockEvent::UnlockerAdded { .. }
+        | TimeLockEvent::UnlockerRemoved { .. }
+        | TimeLockEvent::EscrowClaimedEarly { .. }
+        | TimeLockEvent::LockExpirySet { .. }
+        | TimeLockEvent::LockExpired { .. }
+        | TimeLockEvent::HeartbeatSet { .. }
+        | TimeLockEvent::Heartbeat { .. }
+        | TimeLockEvent::RecurrenceSet { .. }
+        | TimeLockEvent::CallbackSet { .. }
+        | TimeLockEvent::CallbackInvoked { .. }
+        | TimeLockEvent::ConditionSet { .. }
+        | TimeLockEvent::PredecessorSet { .. }
+        | TimeLockEvent::DistributorCreated { .. }
+        | TimeLockEvent::LeafClaimed { .. }
+        | TimeLockEvent::VaultCreated { .. }
+        | TimeLockEvent::VaultLockCreated { .. }
+        | TimeLockEvent::VaultLockClaimed { .. }
+        | TimeLockEvent::VaultWithdrawn { .. }
+        | TimeLockEvent::VaultLockSplit { .. }
+        | TimeLockEvent::VaultLocksMerged { .. }
+        | TimeLockEvent::VaultLockTransferred { .. }
+        | TimeLockEvent::VaultLockSold { .. }
+        | TimeLockEvent::ConfigUpdated { .. }
+        | TimeLockEvent::FeesCollected { .. }
+        | TimeLockEvent::ClaimFeeCharged { .. }
+        | TimeLockEvent::FeesWithdrawn { .. }
+        | TimeLockEvent::FeeExemptionAdded { .. }
+        | TimeLockEvent::FeeExemptionRemoved { .. }
+        | TimeLockEvent::AdminProposed { .. }
+        | TimeLockEvent::ConfigUpdateQueued { .. }
+        | TimeLockEvent::ConfigUpdateCancelled { .. }
+        | TimeLockEvent::ImmutabilityRecorded { .. }
+        | TimeLockEvent::VerifiedBuildRecorded { .. }
+        | TimeLockEvent::AuditTrailEnabled { .. }
+        | TimeLockEvent::ClaimCommitRequired { .. }
+        | TimeLockEvent::ClaimCommitted { .. }
+        | TimeLockEvent::PayrollCreated { .. }
+        | TimeLockEvent::PayrollEntriesAdded { .. }
+        | TimeLockEvent::PayrollClaimed { .. }
+        | TimeLockEvent::ScheduleTemplateCreated { .. }
+        | TimeLockEvent::CallbackCleared { .. }
+        | TimeLockEvent::PayrollClosed { .. }
+        | TimeLockEvent::AccountMigrated { .. }
+        | TimeLockEvent::GrantRecipientSet { .. }
+        | TimeLockEvent::GrantReleased { .. } => return None,
+    }
+    Some(change)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_change() {
+        let lock = Pubkey::new_unique();
+        let cranker = Pubkey::new_unique();
+        let paid = TimeLockEvent::BountyPaid {
+            lock,
+            cranker,
+            amount: 5000,
+        };
+        let change = state_change(&paid, "sig", 42).unwrap();
+        assert_eq!(change.state(), LockState::Claimed);
+        assert_eq!(change.lock, lock.to_string());
+        assert_eq!(change.cranker, Some(cranker.to_string()));
+        assert_eq!(change.bounty, 5000);
+
+        let early = TimeLockEvent::UnlockTooEarly {
+            lock,
+            timestamp: 10,
+            now: 5,
+        };
+        assert!(state_change(&early, "sig", 42).is_none());
+    }
+}
@@ -0,0 +1,168 @@
+//! REST API exposing time lock status, so frontends don't decode borsh.
+//!
+//! Usage: `timelock-api <api.toml>`
+//!
+//! ```toml
+//! listen = "0.0.0.0:8080"
+//! rpc_url = "https://api.mainnet-beta.solana.com"
+//! program_id = "..."
+//! # optional, serve from the `timelock-indexer` database instead of RPC
+//! database_url = "host=localhost user=postgres"
+//! # optional, stream lock state changes over gRPC
+//! grpc_listen = "0.0.0.0:50051"
+//! ws_url = "wss://api.mainnet-beta.solana.com"
+//! ```
+//!
+//! - `GET /locks/{pubkey}`: one lock
+//! - `GET /wallets/{pubkey}/locks`: locks created by a wallet, indexer only
+//! - `GET /stats`: aggregates over every lock
+//! - `POST /graphql`: see [`graphql`], indexer only
+//! - gRPC `SubscribeLocks` on `grpc_listen`: see [`grpc`]
+
+use std::net::SocketAddr;
+use std::str::FromStr as _;
+use std::sync::Arc;
+
+use async_graphql_axum::GraphQL;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+
+mod backend;
+mod graphql;
+mod grpc;
+
+use backend::Backend;
+use grpc::{LockStream, TimeLockStreamServer};
+
+#[derive(Debug, Deserialize)]
+struct Config {
+    listen: SocketAddr,
+    rpc_url: String,
+    program_id: String,
+    database_url: Option<String>,
+    grpc_listen: Option<SocketAddr>,
+    ws_url: Option<String>,
+}
+
+#[tokio::main]
+async fn main() -> timelock::Result<()> {
+    tracing_subscriber::fmt::init();
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "api.toml".to_string());
+    let raw = std::fs::read_to_string(path)?;
+    let config: Config =
+        toml::from_str(&raw).map_err(|e| timelock::Error::Config(e.to_string()))?;
+    let program_id = Pubkey::from_str(&config.program_id)
+        .map_err(|e| timelock::Error::Config(format!("invalid program_id: {e}")))?;
+    let backend = match &config.database_url {
+        Some(url) => Backend::indexer(url).await?,
+        None => Backend::rpc(config.rpc_url.clone(), program_id),
+    };
+
+    if let Some(addr) = config.grpc_listen {
+        let ws_url = config.ws_url.clone().ok_or_else(|| {
+            timelock::Error::Config("grpc_listen needs ws_url to follow the chain".into())
+        })?;
+        let stream = LockStream::new();
+        let follower = stream.clone();
+        tokio::spawn(async move {
+            if let Err(e) = follower.follow(&ws_url, &program_id).await {
+                tracing::error!("lock stream stopped: {e}");
+            }
+        });
+        tokio::spawn(async move {
+            let server = tonic::transport::Server::builder()
+                .add_service(TimeLockStreamServer::new(stream))
+                .serve(addr);
+            if let Err(e) = server.await {
+                tracing::error!("grpc server stopped: {e}");
+            }
+        });
+        tracing::info!("grpc listening on {addr}");
+    }
+
+    let mut app = Router::new()
+        .route("/locks/{pubkey}", get(get_lock))
+        .route("/wallets/{pubkey}/locks", get(get_wallet_locks))
+        .route("/stats", get(get_stats));
+    if let Some(db) = backend.db() {
+        app = app.route_service("/graphql", GraphQL::new(graphql::schema(db)));
+    }
+    let app = app.with_state(Arc::new(backend));
+    let listener = tokio::net::TcpListener::bind(config.listen).await?;
+    tracing::info!("listening on {}", config.listen);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+async fn get_lock(
+    State(backend): State<Arc<Backend>>,
+    Path(pubkey): Path<String>,
+) -> Result<Response, ApiError> {
+    let lock = backend.lock(&parse_pubkey(&pubkey)?).await?;
+    match lock {
+        Some(lock) => Ok(Json(lock).into_response()),
+        None => Err(ApiError::NotFound),
+    }
+}
+
+async fn get_wallet_locks(
+    State(backend): State<Arc<Backend>>,
+    Path(pubkey): Path<String>,
+) -> Result<Response, ApiError> {
+    let locks = backend.wallet_locks(&parse_pubkey(&pubkey)?).await?;
+    Ok(Json(locks).into_response())
+}
+
+async fn get_stats(State(backend): State<Arc<Backend>>) -> Result<Response, ApiError> {
+    Ok(Json(backend.stats().await?).into_response())
+}
+
+fn parse_pubkey(s: &str) -> Result<Pubkey, ApiError> {
+    Pubkey::from_str(s).map_err(|e| ApiError::BadRequest(format!("invalid pubkey {s}: {e}")))
+}
+
+#[derive(Debug)]
+enum ApiError {
+    NotFound,
+    BadRequest(String),
+    /// The endpoint needs a backend that is not configured
+    Unsupported(&'static str),
+    Internal(timelock::Error),
+}
+
+impl From<timelock::Error> for ApiError {
+    fn from(e: timelock::Error) -> Self {
+        Self::Internal(e)
+    }
+}
+
+impl From<tokio_postgres::Error> for ApiError {
+    fn from(e: tokio_postgres::Error) -> Self {
+        Self::Internal(e.into())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            ApiError::NotFound => (StatusCode::NOT_FOUND, "lock not found".to_string()),
+            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            ApiError::Unsupported(msg) => (StatusCode::NOT_IMPLEMENTED, msg.to_string()),
+            ApiError::Internal(e) => {
+                tracing::error!("{e}");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "internal error".to_string(),
+                )
+            }
+        }
+        .into_response()
+    }
+}
@@ -0,0 +1,153 @@
+//! Geyser plugin indexing time lock accounts in real time.
+//!
+//! The plugin keeps the account updates owned by the timelock program,
+//! decodes them into [`LockUpdate`]s and forwards them to the configured
+//! [`Sink`] from a background thread, so the validator is never blocked by
+//! the sink. Load it with a config file like:
+//!
+//! ```json
+//! {
+//!     "libpath": "/path/to/libtimelock_geyser.so",
+//!     "program_id": "<timelock program id>",
+//!     "sink": { "type": "postgres", "url": "host=localhost user=postgres" }
+//! }
+//! ```
+//!
+//! `sink` may also be `{ "type": "file", "path": "/var/log/timelock.jsonl" }`.
+
+use std::str::FromStr as _;
+use std::sync::mpsc::{self, Sender};
+use std::thread::JoinHandle;
+
+use agave_geyser_plugin_interface::geyser_plugin_interface::{
+    GeyserPlugin, GeyserPluginError, ReplicaAccountInfoVersions, Result,
+};
+use borsh::BorshDeserialize as _;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use timelock_program::TimeLockAccount;
+
+mod sink;
+
+pub use sink::{FileSink, PostgresSink, Sink, SinkConfig};
+
+/// Decoded state of a time lock account at some slot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LockUpdate {
+    pub pubkey: String,
+    pub slot: u64,
+    pub write_version: u64,
+    pub lamports: u64,
+    pub timestamp: i64,
+    pub bounty: u64,
+    /// Whether the update was sent while the validator replays its snapshot
+    pub is_startup: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Config {
+    program_id: String,
+    sink: SinkConfig,
+}
+
+#[derive(Debug, Default)]
+pub struct TimeLockGeyserPlugin {
+    program_id: Pubkey,
+    updates: Option<Sender<LockUpdate>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl GeyserPlugin for TimeLockGeyserPlugin {
+    fn name(&self) -> &'static str {
+        "timelock_geyser"
+    }
+
+    fn on_load(&mut self, config_file: &str, _is_reload: bool) -> Result<()> {
+        let raw =
+            std::fs::read_to_string(config_file).map_err(GeyserPluginError::ConfigFileOpenError)?;
+        let config: Config = serde_json::from_str(&raw)
+            .map_err(|e| GeyserPluginError::ConfigFileReadError { msg: e.to_string() })?;
+        self.program_id = Pubkey::from_str(&config.program_id).map_err(|e| {
+            GeyserPluginError::ConfigFileReadError {
+                msg: format!("invalid program_id: {e}"),
+            }
+        })?;
+        let mut sink = config.sink.connect().map_err(GeyserPluginError::Custom)?;
+
+        let (tx, rx) = mpsc::channel::<LockUpdate>();
+        self.updates = Some(tx);
+        self.worker = Some(std::thread::spawn(move || {
+            for update in rx {
+                if let Err(e) = sink.send(&update) {
+                    log::error!("failed to forward update of {}: {e}", update.pubkey);
+                }
+            }
+        }));
+        Ok(())
+    }
+
+    fn on_unload(&mut self) {
+        // closing the channel stops the worker once it drained the updates
+        self.updates.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+
+    fn update_account(
+        &self,
+        account: ReplicaAccountInfoVersions<'_>,
+        slot: u64,
+        is_startup: bool,
+    ) -> Result<()> {
+        let (pubkey, owner, data, lamports, write_version) = match account {
+            ReplicaAccountInfoVersions::V0_0_1(a) => {
+                (a.pubkey, a.owner, a.data, a.lamports, a.write_version)
+            }
+            ReplicaAccountInfoVersions::V0_0_2(a) => {
+                (a.pubkey, a.owner, a.data, a.lamports, a.write_version)
+            }
+            ReplicaAccountInfoVersions::V0_0_3(a) => {
+                (a.pubkey, a.owner, a.data, a.lamports, a.write_version)
+            }
+        };
+        if owner != self.program_id.as_ref() || data.len() != TimeLockAccount::LEN {
+            return Ok(());
+        }
+        let Some(updates) = &self.updates else {
+            return Ok(());
+        };
+        let lock = TimeLockAccount::try_from_slice(data)
+            .map_err(|e| GeyserPluginError::AccountsUpdateError { msg: e.to_string() })?;
+        let pubkey = Pubkey::try_from(pubkey)
+            .map_err(|e| GeyserPluginError::AccountsUpdateError { msg: e.to_string() })?;
+        let _ = updates.send(LockUpdate {
+            pubkey: pubkey.to_string(),
+            slot,
+            write_version,
+            lamports,
+            timestamp: lock.timestamp,
+            bounty: lock.bounty,
+            is_startup,
+        });
+        Ok(())
+    }
+
+    fn account_data_notifications_enabled(&self) -> bool {
+        true
+    }
+
+    fn transaction_notifications_enabled(&self) -> bool {
+        false
+    }
+}
+
+/// # Safety
+///
+/// Called by the validator when loading the plugin, the returned pointer is
+/// owned by the validator.
+#[no_mangle]
+#[allow(improper_ctypes_definitions)]
+pub unsafe extern "C" fn _create_plugin() -> *mut dyn GeyserPlugin {
+    Box::into_raw(Box::<TimeLockGeyserPlugin>::default())
+}
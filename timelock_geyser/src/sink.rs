@@ -0,0 +1,103 @@
+use std::error::Error;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write as _};
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use crate::LockUpdate;
+
+pub type BoxError = Box<dyn Error + Send + Sync>;
+
+/// Destination of the decoded lock updates
+pub trait Sink: Send {
+    fn send(&mut self, update: &LockUpdate) -> Result<(), BoxError>;
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SinkConfig {
+    /// Append updates as JSON lines to a file
+    File { path: PathBuf },
+    /// Upsert updates into the `timelock_locks` table
+    Postgres { url: String },
+}
+
+impl SinkConfig {
+    pub fn connect(&self) -> Result<Box<dyn Sink>, BoxError> {
+        Ok(match self {
+            SinkConfig::File { path } => Box::new(FileSink::open(path)?),
+            SinkConfig::Postgres { url } => Box::new(PostgresSink::connect(url)?),
+        })
+    }
+}
+
+pub struct FileSink {
+    writer: BufWriter<File>,
+}
+
+impl FileSink {
+    pub fn open(path: &PathBuf) -> Result<Self, BoxError> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+        })
+    }
+}
+
+impl Sink for FileSink {
+    fn send(&mut self, update: &LockUpdate) -> Result<(), BoxError> {
+        serde_json::to_writer(&mut self.writer, update)?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+pub struct PostgresSink {
+    client: postgres::Client,
+}
+
+impl PostgresSink {
+    pub fn connect(url: &str) -> Result<Self, BoxError> {
+        let mut client = postgres::Client::connect(url, postgres::NoTls)?;
+        client.batch_execute(
+            "CREATE TABLE IF NOT EXISTS timelock_locks (
+                pubkey        TEXT PRIMARY KEY,
+                slot          BIGINT NOT NULL,
+                write_version BIGINT NOT NULL,
+                lamports      BIGINT NOT NULL,
+                timestamp     BIGINT NOT NULL,
+                bounty        BIGINT NOT NULL
+            )",
+        )?;
+        Ok(Self { client })
+    }
+}
+
+impl Sink for PostgresSink {
+    fn send(&mut self, update: &LockUpdate) -> Result<(), BoxError> {
+        // only move forward: updates may arrive out of order around startup
+        self.client.execute(
+            "INSERT INTO timelock_locks (pubkey, slot, write_version, lamports, timestamp, bounty)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT (pubkey) DO UPDATE SET
+                slot = EXCLUDED.slot,
+                write_version = EXCLUDED.write_version,
+                lamports = EXCLUDED.lamports,
+                timestamp = EXCLUDED.timestamp,
+                bounty = EXCLUDED.bounty
+             WHERE (timelock_locks.slot, timelock_locks.write_version)
+                 < (EXCLUDED.slot, EXCLUDED.write_version)",
+            &[
+                &update.pubkey,
+                &(update.slot as i64),
+                &(update.write_version as i64),
+                &(update.lamports as i64),
+                &update.timestamp,
+                &(update.bounty as i64),
+            ],
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,211 @@
+//! High level creation of time locks, see [`TimeLockBuilder`].
+
+use std::time::Duration;
+
+use solana_program::instruction::Instruction;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+
+use crate::error::TimeLockError;
+use crate::instruction::{
+    add_unlocker, initialize_time_lock, set_condition, set_expiry, set_heartbeat, set_predecessor,
+    set_recurrence,
+};
+use crate::state::{
+    FixedSizeAccount, TimeLockAccount, MAX_CONDITION_BYTES, MAX_UNLOCKERS, SECRET_LENGTH,
+};
+use crate::timestamp::UnixTimestamp;
+
+/// Time lock to create, turned into the instructions creating it and applying
+/// its settings, to send in one transaction signed by the lock and the payer.
+///
+/// Times are [`UnixTimestamp`]s, taken from seconds, a
+/// [`SystemTime`](std::time::SystemTime) or a date time of `chrono` or `time`
+/// with their features, and checked not to be milliseconds.
+///
+/// ```
+/// # use std::time::{Duration, SystemTime};
+/// # use solana_program::pubkey::Pubkey;
+/// # use timelock_interface::builder::TimeLockBuilder;
+/// # use timelock_interface::SECRET_LENGTH;
+/// # let (program_id, lock, payer) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+/// let unlock_at = SystemTime::now() + Duration::from_secs(3_600);
+/// let instructions = TimeLockBuilder::new(&program_id, &lock, &payer)
+///     .unlock_at(unlock_at)
+///     .secret([b'a'; SECRET_LENGTH])
+///     .bounty(5_000)
+///     .expires_at(unlock_at + Duration::from_secs(86_400), &payer)
+///     .build_instructions()
+///     .unwrap();
+/// assert_eq!(instructions.len(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TimeLockBuilder {
+    program_id: Pubkey,
+    lock: Pubkey,
+    payer: Pubkey,
+    timestamp: Result<i64, TimeLockError>,
+    secret: [u8; SECRET_LENGTH],
+    bounty: u64,
+    require_memo: bool,
+    top_up_authority_only: bool,
+    unlockers: Vec<Pubkey>,
+    expiry: Option<(Result<i64, TimeLockError>, Pubkey)>,
+    heartbeat_interval: Option<Duration>,
+    recurrence: Option<(u8, u32, u32)>,
+    condition: Option<(Pubkey, u32, Vec<u8>)>,
+    predecessor: Option<Pubkey>,
+}
+
+impl TimeLockBuilder {
+    /// Time lock at `lock` paid by `payer`, which becomes its authority.
+    /// It matures at the unix epoch with a blank secret until set otherwise.
+    pub fn new(program_id: &Pubkey, lock: &Pubkey, payer: &Pubkey) -> Self {
+        Self {
+            program_id: *program_id,
+            lock: *lock,
+            payer: *payer,
+            timestamp: Ok(0),
+            secret: [b' '; SECRET_LENGTH],
+            bounty: 0,
+            require_memo: false,
+            top_up_authority_only: false,
+            unlockers: vec![],
+            expiry: None,
+            heartbeat_interval: None,
+            recurrence: None,
+            condition: None,
+            predecessor: None,
+        }
+    }
+
+    /// Time the lock matures at
+    pub fn unlock_at(mut self, at: impl TryInto<UnixTimestamp, Error = TimeLockError>) -> Self {
+        self.timestamp = at.try_into().map(UnixTimestamp::secs);
+        self
+    }
+
+    /// Encrypted encoded secret revealed at maturity, valid UTF-8
+    pub fn secret(mut self, secret: [u8; SECRET_LENGTH]) -> Self {
+        self.secret = secret;
+        self
+    }
+
+    /// Lamports set aside for the first caller of `TryUnlock` after maturity
+    pub fn bounty(mut self, bounty: u64) -> Self {
+        self.bounty = bounty;
+        self
+    }
+
+    /// Require the transaction claiming the bounty to carry an SPL memo
+    pub fn require_memo(mut self, require_memo: bool) -> Self {
+        self.require_memo = require_memo;
+        self
+    }
+
+    /// Only let the payer top up the bounty
+    pub fn top_up_authority_only(mut self, top_up_authority_only: bool) -> Self {
+        self.top_up_authority_only = top_up_authority_only;
+        self
+    }
+
+    /// Restrict `TryUnlock` to `unlocker`, and the other unlockers added
+    pub fn unlocker(mut self, unlocker: &Pubkey) -> Self {
+        self.unlockers.push(*unlocker);
+        self
+    }
+
+    /// Let anyone `Expire` the lock after `expires_at`, sending its lamports to `recipient`
+    pub fn expires_at(
+        mut self,
+        expires_at: impl TryInto<UnixTimestamp, Error = TimeLockError>,
+        recipient: &Pubkey,
+    ) -> Self {
+        let expires_at = expires_at.try_into().map(UnixTimestamp::secs);
+        self.expiry = Some((expires_at, *recipient));
+        self
+    }
+
+    /// Push maturity `interval` past each `Heartbeat` of the payer
+    pub fn heartbeat(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = Some(interval);
+        self
+    }
+
+    /// Open the lock `window` seconds every `every` `unit`s, see
+    /// [`TimeLockAccount::RECUR_NONE`]
+    pub fn recurrence(mut self, unit: u8, every: u32, window: u32) -> Self {
+        self.recurrence = Some((unit, every, window));
+        self
+    }
+
+    /// Only reveal while the data of `account` holds `expected` at `offset`
+    pub fn condition(mut self, account: &Pubkey, offset: u32, expected: &[u8]) -> Self {
+        self.condition = Some((*account, offset, expected.to_vec()));
+        self
+    }
+
+    /// Only reveal after `predecessor` did
+    pub fn predecessor(mut self, predecessor: &Pubkey) -> Self {
+        self.predecessor = Some(*predecessor);
+        self
+    }
+
+    /// Lamports the payer needs besides the fee of the config and the
+    /// transaction fee: the rent exemption of the lock and its bounty
+    pub fn lamports(&self, rent: &Rent) -> u64 {
+        TimeLockAccount::rent_exempt_lamports(rent).saturating_add(self.bounty)
+    }
+
+    /// Instructions creating the lock and applying its settings, rejecting
+    /// what the program would and times in milliseconds
+    pub fn build_instructions(&self) -> Result<Vec<Instruction>, TimeLockError> {
+        let timestamp = self.timestamp?;
+        core::str::from_utf8(&self.secret).map_err(|_| TimeLockError::SecretNotUtf8)?;
+        if self.unlockers.len() > MAX_UNLOCKERS {
+            return Err(TimeLockError::UnlockersFull);
+        }
+        let (program_id, lock, payer) = (&self.program_id, &self.lock, &self.payer);
+        let mut instructions = vec![initialize_time_lock(
+            program_id,
+            lock,
+            payer,
+            timestamp,
+            self.secret,
+            self.bounty,
+            self.require_memo,
+            self.top_up_authority_only,
+        )];
+        for unlocker in &self.unlockers {
+            instructions.push(add_unlocker(program_id, lock, payer, unlocker));
+        }
+        if let Some((expires_at, recipient)) = &self.expiry {
+            instructions.push(set_expiry(
+                program_id,
+                lock,
+                payer,
+                (*expires_at)?,
+                recipient,
+            ));
+        }
+        if let Some(interval) = self.heartbeat_interval {
+            let interval = i64::try_from(interval.as_secs()).unwrap_or(i64::MAX);
+            instructions.push(set_heartbeat(program_id, lock, payer, interval));
+        }
+        if let Some((unit, every, window)) = self.recurrence {
+            instructions.push(set_recurrence(program_id, lock, payer, unit, every, window));
+        }
+        if let Some((account, offset, expected)) = &self.condition {
+            if expected.len() > MAX_CONDITION_BYTES {
+                return Err(TimeLockError::ConditionTooLong);
+            }
+            instructions.push(set_condition(
+                program_id, lock, payer, account, *offset, expected,
+            ));
+        }
+        if let Some(predecessor) = &self.predecessor {
+            instructions.push(set_predecessor(program_id, lock, payer, predecessor));
+        }
+        Ok(instructions)
+    }
+}
@@ -0,0 +1,142 @@
+//! Proleptic Gregorian calendar arithmetic on unix timestamps, for locks
+//! recurring every few months and schedules releasing monthly.
+
+const SECS_PER_DAY: i64 = 86_400;
+
+/// Year, month (1 to 12) and day (1 to 31) of the `days` since 1970-01-01
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Days since 1970-01-01 of a date, the inverse of [`civil_from_days`],
+/// `None` past the range of an `i64`
+fn days_from_civil(year: i64, month: i64, day: i64) -> Option<i64> {
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era.checked_mul(146_097)?.checked_add(doe - 719_468)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Months since year 0 of the month `timestamp` falls in
+fn month_index(timestamp: i64) -> i64 {
+    let (year, month, _) = civil_from_days(timestamp.div_euclid(SECS_PER_DAY));
+    year * 12 + month - 1
+}
+
+/// `timestamp` moved `months` calendar months, the day clamped to the length
+/// of the month it lands in: a month after January 31 is the last of February.
+/// `None` when that is past the range of a timestamp.
+pub(crate) fn checked_add_months(timestamp: i64, months: i64) -> Option<i64> {
+    let days = timestamp.div_euclid(SECS_PER_DAY);
+    let secs = timestamp.rem_euclid(SECS_PER_DAY);
+    let (year, month, day) = civil_from_days(days);
+    let index = (year * 12 + month - 1).checked_add(months)?;
+    let (year, month) = (index.div_euclid(12), index.rem_euclid(12) + 1);
+    days_from_civil(year, month, day.min(days_in_month(year, month)))?
+        .checked_mul(SECS_PER_DAY)?
+        .checked_add(secs)
+}
+
+/// [`checked_add_months`], saturating at the bounds of a timestamp
+pub(crate) fn add_months(timestamp: i64, months: i64) -> i64 {
+    checked_add_months(timestamp, months).unwrap_or(match months < 0 {
+        true => i64::MIN,
+        false => i64::MAX,
+    })
+}
+
+/// Start of the last period of `every` months from `start` that began at or
+/// before `now`, which is not before `start`.
+pub(crate) fn period_start(start: i64, every: u32, now: i64) -> i64 {
+    let every = i64::from(every.max(1));
+    let mut months = (month_index(now) - month_index(start)) / every * every;
+    let mut period = add_months(start, months);
+    // a period starting later in the month than `now` has not begun yet
+    while period > now && months > 0 {
+        months -= every;
+        period = add_months(start, months);
+    }
+    period
+}
+
+/// Whole calendar months from `start` to `now`, which is not before `start`:
+/// the most months `start` can be moved without passing `now`.
+pub(crate) fn months_between(start: i64, now: i64) -> i64 {
+    let months = month_index(now) - month_index(start);
+    // a month starting later in the month than `now` is not whole yet
+    match add_months(start, months) > now {
+        true => months - 1,
+        false => months,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-01-31T12:00:00Z
+    const JAN_31: i64 = 1_706_702_400;
+
+    #[test]
+    fn test_add_months() {
+        assert_eq!(days_from_civil(1970, 1, 1), Some(0));
+        assert_eq!(civil_from_days(19_753), (2024, 1, 31));
+        // leap February, then back to the 31st
+        assert_eq!(add_months(JAN_31, 1), JAN_31 + 29 * SECS_PER_DAY);
+        assert_eq!(add_months(JAN_31, 2), JAN_31 + 60 * SECS_PER_DAY);
+        assert_eq!(add_months(JAN_31, 12), JAN_31 + 366 * SECS_PER_DAY);
+        assert_eq!(add_months(JAN_31, -1), JAN_31 - 31 * SECS_PER_DAY);
+        assert_eq!(add_months(-1, 1), 31 * SECS_PER_DAY - 1);
+        // past the range of a timestamp
+        assert_eq!(checked_add_months(JAN_31, i64::MAX), None);
+        assert_eq!(checked_add_months(i64::MAX - SECS_PER_DAY, 1), None);
+        assert_eq!(add_months(JAN_31, i64::MAX), i64::MAX);
+        assert_eq!(add_months(JAN_31, i64::MIN), i64::MIN);
+    }
+
+    #[test]
+    fn test_period_start() {
+        assert_eq!(period_start(JAN_31, 1, JAN_31), JAN_31);
+        let feb_29 = add_months(JAN_31, 1);
+        assert_eq!(period_start(JAN_31, 1, feb_29 - 1), JAN_31);
+        assert_eq!(period_start(JAN_31, 1, feb_29), feb_29);
+        // quarterly periods skip the months in between
+        assert_eq!(period_start(JAN_31, 3, add_months(JAN_31, 2)), JAN_31);
+        assert_eq!(
+            period_start(JAN_31, 3, add_months(JAN_31, 4)),
+            add_months(JAN_31, 3)
+        );
+    }
+
+    #[test]
+    fn test_months_between() {
+        assert_eq!(months_between(JAN_31, JAN_31), 0);
+        let feb_29 = add_months(JAN_31, 1);
+        assert_eq!(months_between(JAN_31, feb_29 - 1), 0);
+        assert_eq!(months_between(JAN_31, feb_29), 1);
+        // March 30 is still short of the clamped March 31
+        let mar_30 = feb_29 + 30 * SECS_PER_DAY;
+        assert_eq!(months_between(JAN_31, mar_30), 1);
+        assert_eq!(months_between(JAN_31, add_months(JAN_31, 14)), 14);
+    }
+}
@@ -0,0 +1,1377 @@
+use borsh::BorshDeserialize as _;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program::{get_return_data, invoke, invoke_signed};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+use crate::instruction;
+use crate::state::{ImmutabilityReport, LockPosition, PayrollShare, RevenueReport, SECRET_LENGTH};
+
+/// Create a time lock from another program.
+///
+/// `lock` and `payer` must sign, through `signer_seeds` when they are PDAs of
+/// the calling program.
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_time_lock<'info>(
+    program: &AccountInfo<'info>,
+    lock: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    stats: &AccountInfo<'info>,
+    config: &AccountInfo<'info>,
+    timestamp: i64,
+    secret: [u8; SECRET_LENGTH],
+    bounty: u64,
+    require_memo: bool,
+    top_up_authority_only: bool,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::initialize_time_lock(
+            program.key,
+            lock.key,
+            payer.key,
+            timestamp,
+            secret,
+            bounty,
+            require_memo,
+            top_up_authority_only,
+        ),
+        &[
+            lock.clone(),
+            payer.clone(),
+            system_program.clone(),
+            stats.clone(),
+            config.clone(),
+            program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Accounts claiming the bounty in [`try_unlock`]
+pub struct Claim<'a, 'info> {
+    /// Receives the bounty, signs
+    pub cranker: &'a AccountInfo<'info>,
+    /// Global statistics PDA
+    pub stats: &'a AccountInfo<'info>,
+    /// Config PDA
+    pub config: &'a AccountInfo<'info>,
+    /// Instructions sysvar
+    pub instructions: &'a AccountInfo<'info>,
+}
+
+/// Unlock a time lock from another program, claiming its bounty when
+/// `claim` is given.
+pub fn try_unlock<'info>(
+    program: &AccountInfo<'info>,
+    lock: &AccountInfo<'info>,
+    claim: Option<Claim<'_, 'info>>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut accounts = vec![lock.clone()];
+    if let Some(claim) = &claim {
+        accounts.push(claim.cranker.clone());
+        accounts.push(claim.stats.clone());
+        accounts.push(claim.config.clone());
+        accounts.push(claim.instructions.clone());
+    }
+    accounts.push(program.clone());
+    invoke_signed(
+        &instruction::try_unlock(program.key, lock.key, claim.map(|c| c.cranker.key)),
+        &accounts,
+        signer_seeds,
+    )
+}
+
+/// Top up a time lock from another program, `depositor` signing through
+/// `signer_seeds` when it is a PDA of the calling program.
+pub fn top_up<'info>(
+    program: &AccountInfo<'info>,
+    lock: &AccountInfo<'info>,
+    depositor: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    stats: &AccountInfo<'info>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::top_up(program.key, lock.key, depositor.key, amount),
+        &[
+            lock.clone(),
+            depositor.clone(),
+            system_program.clone(),
+            stats.clone(),
+            program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Withdraw the surplus of a time lock from another program, `authority`
+/// signing through `signer_seeds` when it is a PDA of the calling program.
+pub fn withdraw_excess<'info>(
+    program: &AccountInfo<'info>,
+    lock: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::withdraw_excess(program.key, lock.key, authority.key),
+        &[lock.clone(), authority.clone(), program.clone()],
+        signer_seeds,
+    )
+}
+
+/// Escrow lamports for `beneficiary` from another program. `escrow` and
+/// `depositor` sign, through `signer_seeds` when they are PDAs of the calling
+/// program.
+#[allow(clippy::too_many_arguments)]
+pub fn create_escrow<'info>(
+    program: &AccountInfo<'info>,
+    escrow: &AccountInfo<'info>,
+    depositor: &AccountInfo<'info>,
+    beneficiary: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    config: &AccountInfo<'info>,
+    amount: u64,
+    release_time: i64,
+    refund_time: i64,
+    penalty_bps: u16,
+    penalty_decays: bool,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::create_escrow(
+            program.key,
+            escrow.key,
+            depositor.key,
+            beneficiary.key,
+            amount,
+            release_time,
+            refund_time,
+            penalty_bps,
+            penalty_decays,
+        ),
+        &[
+            escrow.clone(),
+            depositor.clone(),
+            beneficiary.clone(),
+            system_program.clone(),
+            config.clone(),
+            program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Claim an escrow from another program, `beneficiary`, or `delegate` when
+/// given, signing through `signer_seeds` when it is a PDA of the calling
+/// program.
+pub fn claim_escrow<'info>(
+    program: &AccountInfo<'info>,
+    escrow: &AccountInfo<'info>,
+    beneficiary: &AccountInfo<'info>,
+    depositor: &AccountInfo<'info>,
+    config: &AccountInfo<'info>,
+    delegate: Option<&AccountInfo<'info>>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut accounts = vec![
+        escrow.clone(),
+        beneficiary.clone(),
+        depositor.clone(),
+        config.clone(),
+    ];
+    accounts.extend(delegate.cloned());
+    accounts.push(program.clone());
+    invoke_signed(
+        &instruction::claim_escrow(
+            program.key,
+            escrow.key,
+            beneficiary.key,
+            depositor.key,
+            delegate.map(|d| d.key),
+        ),
+        &accounts,
+        signer_seeds,
+    )
+}
+
+/// Refund an escrow from another program, `depositor` signing through
+/// `signer_seeds` when it is a PDA of the calling program.
+pub fn refund_escrow<'info>(
+    program: &AccountInfo<'info>,
+    escrow: &AccountInfo<'info>,
+    depositor: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::refund_escrow(program.key, escrow.key, depositor.key),
+        &[escrow.clone(), depositor.clone(), program.clone()],
+        signer_seeds,
+    )
+}
+
+/// Redirect an escrow from another program, `depositor` signing through
+/// `signer_seeds` when it is a PDA of the calling program.
+pub fn change_escrow_beneficiary<'info>(
+    program: &AccountInfo<'info>,
+    escrow: &AccountInfo<'info>,
+    depositor: &AccountInfo<'info>,
+    beneficiary: &Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::change_escrow_beneficiary(
+            program.key,
+            escrow.key,
+            depositor.key,
+            beneficiary,
+        ),
+        &[escrow.clone(), depositor.clone(), program.clone()],
+        signer_seeds,
+    )
+}
+
+/// Set the delegate of an escrow from another program, `depositor` signing
+/// through `signer_seeds` when it is a PDA of the calling program.
+pub fn set_escrow_delegate<'info>(
+    program: &AccountInfo<'info>,
+    escrow: &AccountInfo<'info>,
+    depositor: &AccountInfo<'info>,
+    delegate: &Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::set_escrow_delegate(program.key, escrow.key, depositor.key, delegate),
+        &[escrow.clone(), depositor.clone(), program.clone()],
+        signer_seeds,
+    )
+}
+
+/// Revoke the delegate of an escrow from another program, `depositor`
+/// signing through `signer_seeds` when it is a PDA of the calling program.
+pub fn revoke_escrow_delegate<'info>(
+    program: &AccountInfo<'info>,
+    escrow: &AccountInfo<'info>,
+    depositor: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::revoke_escrow_delegate(program.key, escrow.key, depositor.key),
+        &[escrow.clone(), depositor.clone(), program.clone()],
+        signer_seeds,
+    )
+}
+
+/// Allow an unlocker of a time lock from another program, `authority`
+/// signing through `signer_seeds` when it is a PDA of the calling program.
+pub fn add_unlocker<'info>(
+    program: &AccountInfo<'info>,
+    lock: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    unlocker: &Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::add_unlocker(program.key, lock.key, authority.key, unlocker),
+        &[lock.clone(), authority.clone(), program.clone()],
+        signer_seeds,
+    )
+}
+
+/// Disallow an unlocker of a time lock from another program, `authority`
+/// signing through `signer_seeds` when it is a PDA of the calling program.
+pub fn remove_unlocker<'info>(
+    program: &AccountInfo<'info>,
+    lock: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    unlocker: &Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::remove_unlocker(program.key, lock.key, authority.key, unlocker),
+        &[lock.clone(), authority.clone(), program.clone()],
+        signer_seeds,
+    )
+}
+
+/// Claim an escrow early from another program, `beneficiary` signing
+/// through `signer_seeds` when it is a PDA of the calling program.
+pub fn claim_escrow_early<'info>(
+    program: &AccountInfo<'info>,
+    escrow: &AccountInfo<'info>,
+    beneficiary: &AccountInfo<'info>,
+    depositor: &AccountInfo<'info>,
+    config: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::claim_escrow_early(program.key, escrow.key, beneficiary.key, depositor.key),
+        &[
+            escrow.clone(),
+            beneficiary.clone(),
+            depositor.clone(),
+            config.clone(),
+            program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Set the expiry of a time lock from another program, `authority` signing
+/// through `signer_seeds` when it is a PDA of the calling program.
+pub fn set_expiry<'info>(
+    program: &AccountInfo<'info>,
+    lock: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    expires_at: i64,
+    recipient: &Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::set_expiry(program.key, lock.key, authority.key, expires_at, recipient),
+        &[lock.clone(), authority.clone(), program.clone()],
+        signer_seeds,
+    )
+}
+
+/// Close an expired time lock from another program.
+pub fn expire<'info>(
+    program: &AccountInfo<'info>,
+    lock: &AccountInfo<'info>,
+    recipient: &AccountInfo<'info>,
+    stats: &AccountInfo<'info>,
+) -> ProgramResult {
+    invoke(
+        &instruction::expire(program.key, lock.key, recipient.key),
+        &[
+            lock.clone(),
+            recipient.clone(),
+            stats.clone(),
+            program.clone(),
+        ],
+    )
+}
+
+/// Set the heartbeat interval of a time lock from another program,
+/// `authority` signing through `signer_seeds` when it is a PDA of the calling
+/// program.
+pub fn set_heartbeat<'info>(
+    program: &AccountInfo<'info>,
+    lock: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    interval: i64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::set_heartbeat(program.key, lock.key, authority.key, interval),
+        &[lock.clone(), authority.clone(), program.clone()],
+        signer_seeds,
+    )
+}
+
+/// Send the heartbeat of a time lock from another program, `authority`
+/// signing through `signer_seeds` when it is a PDA of the calling program.
+pub fn heartbeat<'info>(
+    program: &AccountInfo<'info>,
+    lock: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::heartbeat(program.key, lock.key, authority.key),
+        &[lock.clone(), authority.clone(), program.clone()],
+        signer_seeds,
+    )
+}
+
+/// Set the recurrence of a time lock from another program, `authority`
+/// signing through `signer_seeds` when it is a PDA of the calling program.
+pub fn set_recurrence<'info>(
+    program: &AccountInfo<'info>,
+    lock: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    unit: u8,
+    every: u32,
+    window: u32,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::set_recurrence(program.key, lock.key, authority.key, unit, every, window),
+        &[lock.clone(), authority.clone(), program.clone()],
+        signer_seeds,
+    )
+}
+
+/// Set the callback of a time lock from another program, `authority` signing
+/// through `signer_seeds` when it is a PDA of the calling program. `accounts`
+/// are the accounts of the call.
+#[allow(clippy::too_many_arguments)]
+pub fn set_callback<'info>(
+    program: &AccountInfo<'info>,
+    lock: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    callback: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    target: &AccountInfo<'info>,
+    accounts: &[AccountInfo<'info>],
+    data: &[u8],
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let metas: Vec<_> = accounts
+        .iter()
+        .map(|a| match a.is_writable {
+            true => AccountMeta::new(*a.key, false),
+            false => AccountMeta::new_readonly(*a.key, false),
+        })
+        .collect();
+    let mut infos = vec![
+        lock.clone(),
+        authority.clone(),
+        callback.clone(),
+        system_program.clone(),
+        target.clone(),
+    ];
+    infos.extend(accounts.iter().cloned());
+    infos.push(program.clone());
+    invoke_signed(
+        &instruction::set_callback(
+            program.key,
+            lock.key,
+            authority.key,
+            target.key,
+            &metas,
+            data,
+        ),
+        &infos,
+        signer_seeds,
+    )
+}
+
+/// Set the instruction a time lock executes on its first reveal from another
+/// program, `authority` signing through `signer_seeds` when it is a PDA of the
+/// calling program. `accounts` are the accounts of `ix`.
+#[allow(clippy::too_many_arguments)]
+pub fn set_unlock_instruction<'info>(
+    program: &AccountInfo<'info>,
+    lock: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    callback: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    target: &AccountInfo<'info>,
+    accounts: &[AccountInfo<'info>],
+    ix: &Instruction,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut infos = vec![
+        lock.clone(),
+        authority.clone(),
+        callback.clone(),
+        system_program.clone(),
+        target.clone(),
+    ];
+    infos.extend(accounts.iter().cloned());
+    infos.push(program.clone());
+    invoke_signed(
+        &instruction::set_unlock_instruction(program.key, lock.key, authority.key, ix),
+        &infos,
+        signer_seeds,
+    )
+}
+
+/// Set the unlock condition of a time lock from another program, `authority`
+/// signing through `signer_seeds` when it is a PDA of the calling program.
+pub fn set_condition<'info>(
+    program: &AccountInfo<'info>,
+    lock: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    account: &Pubkey,
+    offset: u32,
+    expected: &[u8],
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::set_condition(
+            program.key,
+            lock.key,
+            authority.key,
+            account,
+            offset,
+            expected,
+        ),
+        &[lock.clone(), authority.clone(), program.clone()],
+        signer_seeds,
+    )
+}
+
+/// Set the predecessor of a time lock from another program, `authority`
+/// signing through `signer_seeds` when it is a PDA of the calling program.
+pub fn set_predecessor<'info>(
+    program: &AccountInfo<'info>,
+    lock: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    predecessor: &Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::set_predecessor(program.key, lock.key, authority.key, predecessor),
+        &[lock.clone(), authority.clone(), program.clone()],
+        signer_seeds,
+    )
+}
+
+/// Create a distributor of timelocked claims from another program.
+/// `distributor` and `authority` sign, through `signer_seeds` when they are
+/// PDAs of the calling program.
+#[allow(clippy::too_many_arguments)]
+pub fn create_distributor<'info>(
+    program: &AccountInfo<'info>,
+    distributor: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    root: [u8; 32],
+    num_leaves: u32,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::create_distributor(
+            program.key,
+            distributor.key,
+            authority.key,
+            root,
+            num_leaves,
+            amount,
+        ),
+        &[
+            distributor.clone(),
+            authority.clone(),
+            system_program.clone(),
+            program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Claim a leaf of a distributor from another program.
+#[allow(clippy::too_many_arguments)]
+pub fn claim_with_proof<'info>(
+    program: &AccountInfo<'info>,
+    distributor: &AccountInfo<'info>,
+    recipient: &AccountInfo<'info>,
+    config: &AccountInfo<'info>,
+    index: u32,
+    amount: u64,
+    unlock_time: i64,
+    proof: &[[u8; 32]],
+) -> ProgramResult {
+    invoke(
+        &instruction::claim_with_proof(
+            program.key,
+            distributor.key,
+            recipient.key,
+            index,
+            amount,
+            unlock_time,
+            proof,
+        ),
+        &[
+            distributor.clone(),
+            recipient.clone(),
+            config.clone(),
+            program.clone(),
+        ],
+    )
+}
+
+/// Create a payroll from another program. `payroll` and `authority` sign,
+/// through `signer_seeds` when they are PDAs of the calling program.
+#[allow(clippy::too_many_arguments)]
+pub fn create_payroll<'info>(
+    program: &AccountInfo<'info>,
+    payroll: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    amount: u64,
+    start_time: i64,
+    end_time: i64,
+    num_entries: u32,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::create_payroll(
+            program.key,
+            payroll.key,
+            authority.key,
+            amount,
+            start_time,
+            end_time,
+            num_entries,
+        ),
+        &[
+            payroll.clone(),
+            authority.clone(),
+            system_program.clone(),
+            program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Add entries to a payroll from another program. `authority` signs,
+/// through `signer_seeds` when it is a PDA of the calling program.
+pub fn add_payroll_entries<'info>(
+    program: &AccountInfo<'info>,
+    payroll: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    entries: &[PayrollShare],
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::add_payroll_entries(program.key, payroll.key, authority.key, entries),
+        &[payroll.clone(), authority.clone(), program.clone()],
+        signer_seeds,
+    )
+}
+
+/// Claim the share of an entry of a payroll from another program, passing
+/// the schedule template of the payroll if it has one.
+pub fn claim_payroll<'info>(
+    program: &AccountInfo<'info>,
+    payroll: &AccountInfo<'info>,
+    beneficiary: &AccountInfo<'info>,
+    config: &AccountInfo<'info>,
+    template: Option<&AccountInfo<'info>>,
+    index: u32,
+) -> ProgramResult {
+    let mut infos = vec![payroll.clone(), beneficiary.clone(), config.clone()];
+    infos.extend(template.cloned());
+    infos.push(program.clone());
+    invoke(
+        &instruction::claim_payroll(
+            program.key,
+            payroll.key,
+            beneficiary.key,
+            index,
+            template.map(|template| template.key),
+        ),
+        &infos,
+    )
+}
+
+/// Create a schedule template from another program. `template` and
+/// `authority` sign, through `signer_seeds` when they are PDAs of the
+/// calling program.
+#[allow(clippy::too_many_arguments)]
+pub fn create_schedule_template<'info>(
+    program: &AccountInfo<'info>,
+    template: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    tge_bps: u16,
+    cliff_months: u32,
+    period_months: u32,
+    periods: u32,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::create_schedule_template(
+            program.key,
+            template.key,
+            authority.key,
+            tge_bps,
+            cliff_months,
+            period_months,
+            periods,
+        ),
+        &[
+            template.clone(),
+            authority.clone(),
+            system_program.clone(),
+            program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Create a payroll vesting on a schedule template from another program.
+/// `payroll` and `authority` sign, through `signer_seeds` when they are PDAs
+/// of the calling program.
+#[allow(clippy::too_many_arguments)]
+pub fn create_templated_payroll<'info>(
+    program: &AccountInfo<'info>,
+    payroll: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    template: &AccountInfo<'info>,
+    amount: u64,
+    start_time: i64,
+    num_entries: u32,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::create_templated_payroll(
+            program.key,
+            payroll.key,
+            authority.key,
+            template.key,
+            amount,
+            start_time,
+            num_entries,
+        ),
+        &[
+            payroll.clone(),
+            authority.clone(),
+            system_program.clone(),
+            template.clone(),
+            program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Create a vault from another program. `vault` and `authority` sign, through
+/// `signer_seeds` when they are PDAs of the calling program.
+pub fn create_vault<'info>(
+    program: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::create_vault(program.key, vault.key, authority.key, amount),
+        &[
+            vault.clone(),
+            authority.clone(),
+            system_program.clone(),
+            program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Create a vault lock from another program. `vault_lock` and the vault
+/// `authority` sign, through `signer_seeds` when they are PDAs of the calling
+/// program.
+#[allow(clippy::too_many_arguments)]
+pub fn create_vault_lock<'info>(
+    program: &AccountInfo<'info>,
+    vault_lock: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    beneficiary: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    vault_stats: &AccountInfo<'info>,
+    amount: u64,
+    release_time: i64,
+    transferable: bool,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::create_vault_lock(
+            program.key,
+            vault_lock.key,
+            vault.key,
+            authority.key,
+            beneficiary.key,
+            amount,
+            release_time,
+            transferable,
+        ),
+        &[
+            vault_lock.clone(),
+            vault.clone(),
+            authority.clone(),
+            beneficiary.clone(),
+            system_program.clone(),
+            vault_stats.clone(),
+            program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Claim a vault lock from another program, `beneficiary` signing through
+/// `signer_seeds` when it is a PDA of the calling program.
+pub fn claim_vault_lock<'info>(
+    program: &AccountInfo<'info>,
+    vault_lock: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    beneficiary: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    config: &AccountInfo<'info>,
+    vault_stats: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::claim_vault_lock(
+            program.key,
+            vault_lock.key,
+            vault.key,
+            beneficiary.key,
+            payer.key,
+        ),
+        &[
+            vault_lock.clone(),
+            vault.clone(),
+            beneficiary.clone(),
+            payer.clone(),
+            config.clone(),
+            vault_stats.clone(),
+            program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Withdraw unallocated lamports of a vault from another program, `authority`
+/// signing through `signer_seeds` when it is a PDA of the calling program.
+pub fn withdraw_unallocated<'info>(
+    program: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::withdraw_unallocated(program.key, vault.key, authority.key, amount),
+        &[vault.clone(), authority.clone(), program.clone()],
+        signer_seeds,
+    )
+}
+
+/// Split a vault lock from another program. `new_vault_lock` and
+/// `beneficiary` sign, through `signer_seeds` when they are PDAs of the
+/// calling program.
+#[allow(clippy::too_many_arguments)]
+pub fn split_lock<'info>(
+    program: &AccountInfo<'info>,
+    vault_lock: &AccountInfo<'info>,
+    new_vault_lock: &AccountInfo<'info>,
+    beneficiary: &AccountInfo<'info>,
+    new_beneficiary: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::split_lock(
+            program.key,
+            vault_lock.key,
+            new_vault_lock.key,
+            beneficiary.key,
+            new_beneficiary.key,
+            amount,
+        ),
+        &[
+            vault_lock.clone(),
+            new_vault_lock.clone(),
+            beneficiary.clone(),
+            new_beneficiary.clone(),
+            system_program.clone(),
+            program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Merge two vault locks from another program. `beneficiary` signs, through
+/// `signer_seeds` when it is a PDA of the calling program.
+pub fn merge_locks<'info>(
+    program: &AccountInfo<'info>,
+    vault_lock: &AccountInfo<'info>,
+    source: &AccountInfo<'info>,
+    beneficiary: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::merge_locks(
+            program.key,
+            vault_lock.key,
+            source.key,
+            beneficiary.key,
+            payer.key,
+        ),
+        &[
+            vault_lock.clone(),
+            source.clone(),
+            beneficiary.clone(),
+            payer.clone(),
+            program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Transfer a vault lock from another program. `beneficiary` signs, through
+/// `signer_seeds` when it is a PDA of the calling program.
+pub fn transfer_vault_lock<'info>(
+    program: &AccountInfo<'info>,
+    vault_lock: &AccountInfo<'info>,
+    beneficiary: &AccountInfo<'info>,
+    new_beneficiary: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::transfer_vault_lock(
+            program.key,
+            vault_lock.key,
+            beneficiary.key,
+            new_beneficiary.key,
+        ),
+        &[
+            vault_lock.clone(),
+            beneficiary.clone(),
+            new_beneficiary.clone(),
+            program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Sell a vault lock from another program. `beneficiary` and `buyer` sign,
+/// through `signer_seeds` when they are PDAs of the calling program.
+pub fn transfer_with_payment<'info>(
+    program: &AccountInfo<'info>,
+    vault_lock: &AccountInfo<'info>,
+    beneficiary: &AccountInfo<'info>,
+    buyer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    price: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::transfer_with_payment(
+            program.key,
+            vault_lock.key,
+            beneficiary.key,
+            buyer.key,
+            price,
+        ),
+        &[
+            vault_lock.clone(),
+            beneficiary.clone(),
+            buyer.clone(),
+            system_program.clone(),
+            program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Create the program config from another program. `admin` signs, through
+/// `signer_seeds` when it is a PDA of the calling program.
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_config<'info>(
+    program: &AccountInfo<'info>,
+    config: &AccountInfo<'info>,
+    admin: &AccountInfo<'info>,
+    programdata: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    treasury: &Pubkey,
+    creation_fee: u64,
+    min_duration: i64,
+    max_duration: i64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::initialize_config(
+            program.key,
+            admin.key,
+            treasury,
+            creation_fee,
+            min_duration,
+            max_duration,
+        ),
+        &[
+            config.clone(),
+            admin.clone(),
+            programdata.clone(),
+            system_program.clone(),
+            program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Queue an update of the program config from another program. `admin` signs,
+/// through `signer_seeds` when it is a PDA of the calling program.
+#[allow(clippy::too_many_arguments)]
+pub fn update_config<'info>(
+    program: &AccountInfo<'info>,
+    config: &AccountInfo<'info>,
+    admin: &AccountInfo<'info>,
+    guardian: &AccountInfo<'info>,
+    config_update: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    treasury: &Pubkey,
+    creation_fee: u64,
+    min_duration: i64,
+    max_duration: i64,
+    paused: bool,
+    claim_fee_bps: u16,
+    update_delay: i64,
+    clock_drift_tolerance: i64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::update_config(
+            program.key,
+            admin.key,
+            guardian.key,
+            treasury,
+            creation_fee,
+            min_duration,
+            max_duration,
+            paused,
+            claim_fee_bps,
+            update_delay,
+            clock_drift_tolerance,
+        ),
+        &[
+            config.clone(),
+            admin.clone(),
+            guardian.clone(),
+            config_update.clone(),
+            system_program.clone(),
+            program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Collect the fees of the program config from another program.
+pub fn collect_fees<'info>(
+    program: &AccountInfo<'info>,
+    config: &AccountInfo<'info>,
+    treasury: &AccountInfo<'info>,
+) -> ProgramResult {
+    invoke(
+        &instruction::collect_fees(program.key, treasury.key),
+        &[config.clone(), treasury.clone(), program.clone()],
+    )
+}
+
+/// Pause or unpause the program from another program. `authority` signs,
+/// through `signer_seeds` when it is a PDA of the calling program.
+pub fn set_paused<'info>(
+    program: &AccountInfo<'info>,
+    config: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    paused: bool,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::set_paused(program.key, authority.key, paused),
+        &[config.clone(), authority.clone(), program.clone()],
+        signer_seeds,
+    )
+}
+
+/// Withdraw fees of the program config from another program. `admin` signs,
+/// through `signer_seeds` when it is a PDA of the calling program.
+pub fn withdraw_fees<'info>(
+    program: &AccountInfo<'info>,
+    config: &AccountInfo<'info>,
+    admin: &AccountInfo<'info>,
+    recipient: &AccountInfo<'info>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::withdraw_fees(program.key, admin.key, recipient.key, amount),
+        &[
+            config.clone(),
+            admin.clone(),
+            recipient.clone(),
+            program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Exempt `key` from the fees of the program config from another program.
+/// `admin` signs, through `signer_seeds` when it is a PDA of the calling
+/// program.
+pub fn add_fee_exemption<'info>(
+    program: &AccountInfo<'info>,
+    config: &AccountInfo<'info>,
+    admin: &AccountInfo<'info>,
+    fee_exemption: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    key: &Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::add_fee_exemption(program.key, admin.key, key),
+        &[
+            config.clone(),
+            admin.clone(),
+            fee_exemption.clone(),
+            system_program.clone(),
+            program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Charge `key` the fees of the program config again from another program.
+/// `admin` signs, through `signer_seeds` when it is a PDA of the calling
+/// program.
+pub fn remove_fee_exemption<'info>(
+    program: &AccountInfo<'info>,
+    config: &AccountInfo<'info>,
+    admin: &AccountInfo<'info>,
+    fee_exemption: &AccountInfo<'info>,
+    key: &Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::remove_fee_exemption(program.key, admin.key, key),
+        &[
+            config.clone(),
+            admin.clone(),
+            fee_exemption.clone(),
+            program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Propose a new admin of the program config from another program. `admin`
+/// signs, through `signer_seeds` when it is a PDA of the calling program.
+pub fn propose_admin<'info>(
+    program: &AccountInfo<'info>,
+    config: &AccountInfo<'info>,
+    admin: &AccountInfo<'info>,
+    pending_admin: &AccountInfo<'info>,
+    expires_at: i64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::propose_admin(program.key, admin.key, pending_admin.key, expires_at),
+        &[
+            config.clone(),
+            admin.clone(),
+            pending_admin.clone(),
+            program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Take over the program config from another program. `pending_admin` signs,
+/// through `signer_seeds` when it is a PDA of the calling program.
+pub fn accept_admin<'info>(
+    program: &AccountInfo<'info>,
+    config: &AccountInfo<'info>,
+    pending_admin: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::accept_admin(program.key, pending_admin.key),
+        &[config.clone(), pending_admin.clone(), program.clone()],
+        signer_seeds,
+    )
+}
+
+/// Apply the queued update of the program config from another program.
+/// `admin` signs, through `signer_seeds` when it is a PDA of the calling
+/// program.
+pub fn apply_config_update<'info>(
+    program: &AccountInfo<'info>,
+    config: &AccountInfo<'info>,
+    config_update: &AccountInfo<'info>,
+    admin: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::apply_config_update(program.key, admin.key),
+        &[
+            config.clone(),
+            config_update.clone(),
+            admin.clone(),
+            program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Drop the queued update of the program config from another program.
+/// `authority` signs, through `signer_seeds` when it is a PDA of the calling
+/// program.
+pub fn cancel_config_update<'info>(
+    program: &AccountInfo<'info>,
+    config: &AccountInfo<'info>,
+    config_update: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::cancel_config_update(program.key, authority.key, payer.key),
+        &[
+            config.clone(),
+            config_update.clone(),
+            authority.clone(),
+            payer.clone(),
+            program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Audit the revenue of the program config from another program, see
+/// [`RevenueReport`].
+pub fn get_revenue<'info>(
+    program: &AccountInfo<'info>,
+    config: &AccountInfo<'info>,
+) -> Result<RevenueReport, ProgramError> {
+    invoke(
+        &instruction::get_revenue(program.key),
+        &[config.clone(), program.clone()],
+    )?;
+    match get_return_data() {
+        Some((program_id, data)) if program_id == *program.key => {
+            // later versions may append fields
+            RevenueReport::deserialize(&mut data.as_slice()).map_err(Into::into)
+        }
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+/// Check whether the program was recorded immutable from another program, see
+/// [`ImmutabilityReport`].
+pub fn get_immutability<'info>(
+    program: &AccountInfo<'info>,
+    config: &AccountInfo<'info>,
+) -> Result<ImmutabilityReport, ProgramError> {
+    invoke(
+        &instruction::get_immutability(program.key),
+        &[config.clone(), program.clone()],
+    )?;
+    match get_return_data() {
+        Some((program_id, data)) if program_id == *program.key => {
+            // later versions may append fields
+            ImmutabilityReport::deserialize(&mut data.as_slice()).map_err(Into::into)
+        }
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+/// Drop the callback of a matured time lock from another program,
+/// `authority` signing through `signer_seeds` when it is a PDA of the calling
+/// program.
+pub fn clear_callback<'info>(
+    program: &AccountInfo<'info>,
+    lock: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    callback: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::clear_callback(program.key, lock.key, authority.key),
+        &[
+            lock.clone(),
+            authority.clone(),
+            callback.clone(),
+            program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Close a settled payroll from another program, `authority` signing through
+/// `signer_seeds` when it is a PDA of the calling program.
+pub fn close_payroll<'info>(
+    program: &AccountInfo<'info>,
+    payroll: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::close_payroll(program.key, payroll.key, authority.key),
+        &[payroll.clone(), authority.clone(), program.clone()],
+        signer_seeds,
+    )
+}
+
+/// Migrate an account of the program to its current layout from another
+/// program, `payer` signing through `signer_seeds` when it is a PDA of the
+/// calling program.
+pub fn migrate_account<'info>(
+    program: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::migrate_account(program.key, account.key, payer.key),
+        &[
+            account.clone(),
+            payer.clone(),
+            system_program.clone(),
+            program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Set the grant recipient of a time lock from another program, `authority`
+/// signing through `signer_seeds` when it is a PDA of the calling program.
+pub fn set_grant_recipient<'info>(
+    program: &AccountInfo<'info>,
+    lock: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    recipient: &Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &instruction::set_grant_recipient(program.key, lock.key, authority.key, recipient),
+        &[lock.clone(), authority.clone(), program.clone()],
+        signer_seeds,
+    )
+}
+
+/// Release the installments of a revealed time lock from another program.
+pub fn release_grant<'info>(
+    program: &AccountInfo<'info>,
+    lock: &AccountInfo<'info>,
+    recipient: &AccountInfo<'info>,
+    stats: &AccountInfo<'info>,
+) -> ProgramResult {
+    invoke(
+        &instruction::release_grant(program.key, lock.key, recipient.key),
+        &[
+            lock.clone(),
+            recipient.clone(),
+            stats.clone(),
+            program.clone(),
+        ],
+    )
+}
+
+/// Value a time lock from another program, see [`LockPosition`].
+pub fn get_position<'info>(
+    program: &AccountInfo<'info>,
+    lock: &AccountInfo<'info>,
+) -> Result<LockPosition, ProgramError> {
+    invoke(
+        &instruction::get_position(program.key, lock.key),
+        &[lock.clone(), program.clone()],
+    )?;
+    match get_return_data() {
+        Some((program_id, data)) if program_id == *program.key => {
+            // later versions may append fields
+            LockPosition::deserialize(&mut data.as_slice()).map_err(Into::into)
+        }
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
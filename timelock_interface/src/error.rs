@@ -0,0 +1,139 @@
+use solana_program::program_error::ProgramError;
+
+/// Errors specific to the time lock program, surfaced as
+/// `ProgramError::Custom(code)`. Codes are stable: variants are append only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum TimeLockError {
+    /// The lock requires the claiming transaction to carry an SPL memo
+    MemoRequired = 0,
+    /// The stake lock has not reached its release time
+    StakeStillLocked = 1,
+    /// The stake account has a native lockup outlasting the stake lock
+    StakeLockupMisaligned = 2,
+    /// The instruction data starts with no known discriminator or tag
+    UnknownInstruction = 3,
+    /// The instruction data ends before the fields of the instruction do
+    InstructionTooShort = 4,
+    /// Bytes follow the fields of the instruction
+    InstructionTooLong = 5,
+    /// The secret of a new time lock is not valid UTF-8
+    SecretNotUtf8 = 6,
+    /// The memo requirement flag of a new time lock is neither 0 nor 1
+    InvalidMemoFlag = 7,
+    /// The top up restriction flag of a new time lock is neither 0 nor 1
+    InvalidTopUpFlag = 8,
+    /// Only the lock authority may do this
+    AuthorityRequired = 9,
+    /// The lock already matured
+    LockMatured = 10,
+    /// The escrow refund time is not after its release time
+    InvalidEscrowDeadlines = 11,
+    /// The escrow has not reached its release time
+    EscrowNotReleased = 12,
+    /// The escrow has not reached its refund time
+    EscrowNotRefundable = 13,
+    /// The escrow already reached its release time
+    EscrowReleased = 14,
+    /// The lock restricts `TryUnlock` to keys the signer is not among
+    UnlockerNotAllowed = 15,
+    /// Every unlocker slot of the lock is taken
+    UnlockersFull = 16,
+    /// The penalty decay flag of an escrow is neither 0 nor 1
+    InvalidPenaltyFlag = 17,
+    /// The escrow penalty exceeds 10000 basis points
+    InvalidEscrowPenalty = 18,
+    /// The escrow cannot be claimed before its release time
+    EarlyClaimDisabled = 19,
+    /// The lock expiry is not after its timestamp
+    InvalidExpiry = 20,
+    /// The lock has no expiry or has not reached it
+    LockNotExpired = 21,
+    /// The lock is not in dead man's switch mode
+    HeartbeatDisabled = 22,
+    /// The recurrence unit is unknown, or its period or window is 0
+    InvalidRecurrence = 23,
+    /// An account the lock callback calls with was not passed
+    CallbackAccountMissing = 24,
+    /// The lock callback passes more than `MAX_CALLBACK_ACCOUNTS` accounts
+    TooManyCallbackAccounts = 25,
+    /// The condition account of the lock does not hold the expected bytes
+    ConditionNotMet = 26,
+    /// The predecessor of the lock has not revealed its secret
+    PredecessorLocked = 27,
+    /// The merkle proof does not link the leaf to the distributor root
+    InvalidProof = 28,
+    /// The distributor leaf was already claimed
+    LeafClaimed = 29,
+    /// The distributor leaf has not reached its unlock time
+    LeafLocked = 30,
+    /// The unallocated lamports of the vault do not cover the amount
+    VaultUnderfunded = 31,
+    /// The vault lock has not reached its release time
+    VaultLockNotReleased = 32,
+    /// The transferable flag of a vault lock is neither 0 nor 1
+    InvalidTransferableFlag = 33,
+    /// The vault lock was not created transferable
+    VaultLockNotTransferable = 34,
+    /// The paused flag of the config is neither 0 nor 1
+    InvalidPauseFlag = 35,
+    /// The program is paused, nothing is created nor claimed
+    ProgramPaused = 36,
+    /// The lock matures too soon or too late for the config
+    DurationOutOfBounds = 37,
+    /// The proposal to hand the config to a new admin expired
+    AdminProposalExpired = 38,
+    /// The queued config update has not reached its eta
+    ConfigUpdateNotReady = 39,
+    /// The program still has an upgrade authority
+    ProgramUpgradeable = 40,
+    /// A timestamp is too far from the epoch to be in seconds, likely
+    /// milliseconds, see [`UnixTimestamp`](crate::timestamp::UnixTimestamp)
+    TimestampNotSeconds = 41,
+    /// The stake lock exists with another owner, release time or beneficiary
+    StakeLockMismatch = 42,
+    /// The escrow, vault lock or stake lock already paid out
+    AlreadyReleased = 43,
+    /// The lock keeps an audit trail and its audit trail PDA is missing
+    AuditTrailMissing = 44,
+    /// The revealed destination and salt do not hash to the claim commitment
+    ClaimCommitmentMismatch = 45,
+    /// The claim commitment was made in the current slot, it is revealed in a later one
+    ClaimCommitmentTooRecent = 46,
+    /// The lock has not revealed its secret, see `TryUnlock`
+    SecretNotRevealed = 47,
+    /// The nonce is not the next one of the payer, see `LockNonceAccount`
+    NonceMismatch = 48,
+    /// The instruction data exceeds `MAX_INSTRUCTION_DATA` bytes
+    InstructionDataTooLarge = 49,
+    /// The merkle proof has more than `MAX_PROOF_LEN` hashes
+    ProofTooLong = 50,
+    /// The condition expects more than `MAX_CONDITION_BYTES` bytes
+    ConditionTooLong = 51,
+    /// The instruction is given more than `MAX_ACCOUNTS` accounts
+    TooManyAccounts = 52,
+    /// The lock is calling its callback, which may not call back to mutate it
+    ReentrantCall = 53,
+    /// Entries of the payroll are still to be added, see `AddPayrollEntries`
+    PayrollIncomplete = 54,
+    /// The entries exceed those the payroll was created for
+    PayrollFull = 55,
+    /// The payroll share vested nothing more since its last claim
+    PayrollNothingVested = 56,
+    /// The schedule template releases over 10000 basis points at TGE, has
+    /// periods of 0 months, or lasts over 1200 months
+    InvalidScheduleTemplate = 57,
+    /// The lock has yet to mature
+    LockNotMatured = 58,
+    /// Entries of the payroll have yet to claim their whole share
+    PayrollNotSettled = 59,
+    /// The account is in the current layout already, or in no layout it
+    /// grew from
+    NotMigratable = 60,
+}
+
+impl From<TimeLockError> for ProgramError {
+    fn from(e: TimeLockError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
@@ -0,0 +1,4503 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use shank::ShankInstruction;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::{bpf_loader_upgradeable, pubkey, stake, system_program, sysvar};
+use spl_discriminator::SplDiscriminate;
+
+use crate::error::TimeLockError;
+use crate::pda::{
+    audit_address, callback_address, claim_commitment_address, config_address,
+    config_update_address, fee_exemption_address, lock_address, lock_nonce_address,
+    stake_lock_address, stats_address, vault_stats_address,
+};
+use crate::state::{CallbackAccount, ClaimCommitmentAccount, PayrollShare, SECRET_LENGTH};
+
+/// Instruction tag of the self-CPI carrying an event, followed by the borsh event
+pub const EMIT_EVENT_TAG: u8 = 0xff;
+
+/// Bytes of instruction data the program decodes at most, above any
+/// instruction or event it packs
+pub const MAX_INSTRUCTION_DATA: usize = 1024;
+
+/// Accounts an instruction is given at most. The program searches them for
+/// optional ones, this bounds the search.
+pub const MAX_ACCOUNTS: usize = 32;
+
+/// Instructions of the program, described for the shank IDL.
+///
+/// The data of each instruction is its 8-byte discriminator, the first bytes
+/// of `sha256("timelock:<snake_case_name>")`, followed by the borsh encoded
+/// fields, see [`TimeLockInstruction::pack`]. The legacy one byte tags (the
+/// variant index) are still accepted.
+#[allow(clippy::large_enum_variant)]
+#[derive(BorshSerialize, BorshDeserialize, ShankInstruction, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(borsh::BorshSchema))]
+pub enum TimeLockInstruction {
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "lock",
+        desc = "Time lock account to create"
+    )]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "payer",
+        desc = "Pays the rent and the bounty"
+    )]
+    #[account(2, name = "system_program", desc = "System program")]
+    #[account(
+        3,
+        optional,
+        writable,
+        name = "stats",
+        desc = "Global statistics PDA, counts the lock"
+    )]
+    #[account(
+        4,
+        optional,
+        writable,
+        name = "config",
+        desc = "Config PDA, with the stats, receives the fee"
+    )]
+    #[account(
+        5,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        6,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    InitializeTimeLock {
+        timestamp: i64,
+        /// encrypted encoded secret
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_array"))]
+        #[borsh(deserialize_with = "wire::secret")]
+        secret: [u8; SECRET_LENGTH],
+        /// lamports set aside for the first caller of `TryUnlock` after maturity,
+        /// absent (0) in the first legacy instruction data
+        #[borsh(deserialize_with = "wire::legacy_bounty")]
+        bounty: u64,
+        /// require the transaction claiming the bounty to carry an SPL memo,
+        /// absent (false) in legacy instruction data
+        #[borsh(deserialize_with = "wire::legacy_memo_flag")]
+        require_memo: bool,
+        /// only the payer, the lock authority, may top up the lock; absent
+        /// (false) in legacy instruction data
+        #[borsh(deserialize_with = "wire::legacy_top_up_flag")]
+        top_up_authority_only: bool,
+    },
+    #[account(0, writable, name = "lock", desc = "Time lock account to unlock")]
+    #[account(
+        1,
+        optional,
+        writable,
+        signer,
+        name = "cranker",
+        desc = "Receives the bounty"
+    )]
+    #[account(
+        2,
+        optional,
+        writable,
+        name = "stats",
+        desc = "Global statistics PDA, with a cranker"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "config",
+        desc = "Config PDA, with a cranker, no bounty is paid while paused"
+    )]
+    #[account(
+        4,
+        optional,
+        name = "instructions",
+        desc = "Instructions sysvar, with a cranker, to find the claim memo"
+    )]
+    #[account(
+        5,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        6,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    TryUnlock,
+    /// Self-CPI carrying an event, only the program itself can sign it.
+    ///
+    /// Its tag is [`EMIT_EVENT_TAG`], not the position in this enum.
+    #[account(0, signer, name = "event_authority", desc = "Event authority PDA")]
+    EmitEvent,
+    /// Read-only view of a lock for other programs, returned as a borsh
+    /// [`LockPosition`](crate::state::LockPosition) in the return data.
+    #[account(0, name = "lock", desc = "Time lock account to value")]
+    GetPosition,
+    /// Escrow a native stake account: its withdraw authority moves to the
+    /// stake lock PDA until `timestamp`, delegation stays with the staker.
+    #[account(0, writable, name = "stake_lock", desc = "Stake lock PDA to create")]
+    #[account(1, writable, name = "stake", desc = "Stake account to escrow")]
+    #[account(
+        2,
+        writable,
+        signer,
+        name = "owner",
+        desc = "Current withdraw authority, pays the rent"
+    )]
+    #[account(3, name = "clock", desc = "Clock sysvar")]
+    #[account(4, name = "stake_program", desc = "Stake program")]
+    #[account(5, name = "system_program", desc = "System program")]
+    #[account(
+        6,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        7,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    LockStake {
+        /// unix timestamp the stake is released at
+        timestamp: i64,
+        /// withdraw authority after release
+        beneficiary: Pubkey,
+    },
+    /// Hand the withdraw authority of a matured stake lock to its
+    /// beneficiary and close the stake lock, anyone can call it.
+    #[account(0, writable, name = "stake_lock", desc = "Stake lock PDA to close")]
+    #[account(1, writable, name = "stake", desc = "Escrowed stake account")]
+    #[account(2, writable, name = "owner", desc = "Receives the stake lock rent")]
+    #[account(3, name = "clock", desc = "Clock sysvar")]
+    #[account(4, name = "stake_program", desc = "Stake program")]
+    #[account(
+        5,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        6,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    ReleaseStake,
+    /// Deposit `amount` lamports in a lock before it matures, an installment
+    /// of the grant released to its recipient once revealed, see
+    /// `ReleaseGrant`. Restricted to the lock authority if the lock says so.
+    #[account(0, writable, name = "lock", desc = "Time lock account to top up")]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "depositor",
+        desc = "Pays the amount, the authority for restricted locks"
+    )]
+    #[account(2, name = "system_program", desc = "System program")]
+    #[account(3, writable, name = "stats", desc = "Global statistics PDA")]
+    #[account(
+        4,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        5,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    TopUp {
+        /// lamports added to the funded total, not 0
+        amount: u64,
+    },
+    /// Send the lamports of a lock above its rent exemption, bounty and
+    /// installments, sent to it by mistake, to the lock authority.
+    #[account(
+        0,
+        writable,
+        name = "lock",
+        desc = "Time lock account holding the surplus"
+    )]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "authority",
+        desc = "Lock authority, receives the surplus"
+    )]
+    #[account(
+        2,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    WithdrawExcess,
+    /// Escrow `amount` lamports of the depositor for a beneficiary, who can
+    /// claim them from `release_time`, refundable from `refund_time`.
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "escrow",
+        desc = "Escrow account to create"
+    )]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "depositor",
+        desc = "Pays the amount and the rent"
+    )]
+    #[account(2, name = "beneficiary", desc = "May claim the amount")]
+    #[account(3, name = "system_program", desc = "System program")]
+    #[account(4, writable, name = "config", desc = "Config PDA, receives the fee")]
+    #[account(
+        5,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        6,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    CreateEscrow {
+        /// lamports escrowed
+        amount: u64,
+        /// unix timestamp the beneficiary may claim from
+        release_time: i64,
+        /// unix timestamp the depositor may reclaim from, after `release_time`
+        refund_time: i64,
+        /// share of the amount, in basis points, forfeited to the depositor by
+        /// a claim before `release_time`; 0 disallows early claims
+        penalty_bps: u16,
+        /// whether the penalty decreases linearly to 0 at `release_time`
+        #[borsh(deserialize_with = "wire::penalty_flag")]
+        penalty_decays: bool,
+    },
+    /// Pay an escrow to its beneficiary once released, closing it. Signed
+    /// by the beneficiary or, when given, by the delegate of the escrow.
+    #[account(0, writable, name = "escrow", desc = "Escrow account to close")]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "beneficiary",
+        desc = "Receives the amount, signs unless the delegate does"
+    )]
+    #[account(2, writable, name = "depositor", desc = "Receives the rent")]
+    #[account(
+        3,
+        writable,
+        name = "config",
+        desc = "Config PDA, keeps the claim fee, no claim goes through while paused"
+    )]
+    #[account(
+        4,
+        signer,
+        optional,
+        name = "delegate",
+        desc = "Delegate claiming on behalf of the beneficiary"
+    )]
+    #[account(
+        5,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        6,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    ClaimEscrow,
+    /// Return an unclaimed escrow to its depositor once refundable, closing it.
+    #[account(0, writable, name = "escrow", desc = "Escrow account to close")]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "depositor",
+        desc = "Receives the amount and the rent"
+    )]
+    #[account(
+        2,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    RefundEscrow,
+    /// Redirect an escrow to another beneficiary, before its release time.
+    #[account(0, writable, name = "escrow", desc = "Escrow account")]
+    #[account(1, signer, name = "depositor", desc = "Depositor of the escrow")]
+    #[account(
+        2,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    ChangeEscrowBeneficiary {
+        /// the new beneficiary
+        beneficiary: Pubkey,
+    },
+    /// Allow `delegate` to claim an escrow on behalf of its beneficiary,
+    /// replacing any previous delegate.
+    #[account(0, writable, name = "escrow", desc = "Escrow account")]
+    #[account(1, signer, name = "depositor", desc = "Depositor of the escrow")]
+    #[account(
+        2,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    SetEscrowDelegate {
+        /// key allowed to trigger the claim
+        delegate: Pubkey,
+    },
+    /// Remove the delegate of an escrow.
+    #[account(0, writable, name = "escrow", desc = "Escrow account")]
+    #[account(1, signer, name = "depositor", desc = "Depositor of the escrow")]
+    #[account(
+        2,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    RevokeEscrowDelegate,
+    /// Restrict `TryUnlock` of a lock to `unlocker` and the other keys added.
+    /// A lock without unlockers can be unlocked by anyone.
+    #[account(0, writable, name = "lock", desc = "Time lock account")]
+    #[account(1, signer, name = "authority", desc = "Authority of the lock")]
+    #[account(
+        2,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    AddUnlocker {
+        /// key allowed to call `TryUnlock`
+        unlocker: Pubkey,
+    },
+    /// Remove `unlocker` from the keys allowed to call `TryUnlock` of a lock,
+    /// which anyone may call again once the last one is removed.
+    #[account(0, writable, name = "lock", desc = "Time lock account")]
+    #[account(1, signer, name = "authority", desc = "Authority of the lock")]
+    #[account(
+        2,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    RemoveUnlocker {
+        /// key no longer allowed to call `TryUnlock`
+        unlocker: Pubkey,
+    },
+    /// Pay an escrow allowing early claims to its beneficiary before its
+    /// release time, minus the penalty returned to the depositor, closing it.
+    #[account(0, writable, name = "escrow", desc = "Escrow account to close")]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "beneficiary",
+        desc = "Receives the amount net of the penalty"
+    )]
+    #[account(
+        2,
+        writable,
+        name = "depositor",
+        desc = "Receives the penalty and the rent"
+    )]
+    #[account(
+        3,
+        writable,
+        name = "config",
+        desc = "Config PDA, keeps the claim fee, no claim goes through while paused"
+    )]
+    #[account(
+        4,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        5,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    ClaimEscrowEarly,
+    /// Let anyone `Expire` a lock not unlocked by `expires_at`, before it matures.
+    /// A zero `expires_at` removes the expiry.
+    #[account(0, writable, name = "lock", desc = "Time lock account")]
+    #[account(1, signer, name = "authority", desc = "Authority of the lock")]
+    #[account(
+        2,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    SetExpiry {
+        /// unix timestamp after the lock timestamp, 0 to never expire
+        expires_at: i64,
+        /// receives the lamports of the expired lock
+        recipient: Pubkey,
+    },
+    /// Close a lock past its expiry, wiping the secret and paying its lamports
+    /// to the expiry recipient. Anyone may call it.
+    #[account(0, writable, name = "lock", desc = "Time lock account to close")]
+    #[account(1, writable, name = "recipient", desc = "Expiry recipient of the lock")]
+    #[account(2, writable, name = "stats", desc = "Global statistics PDA")]
+    #[account(
+        3,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        4,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    Expire,
+    /// Put a lock in dead man's switch mode before it matures: each `Heartbeat`
+    /// defers maturity to `interval` seconds after it. A zero `interval` ends the
+    /// mode, the lock keeping its current timestamp.
+    #[account(0, writable, name = "lock", desc = "Time lock account")]
+    #[account(1, signer, name = "authority", desc = "Authority of the lock")]
+    #[account(
+        2,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    SetHeartbeat {
+        /// seconds a heartbeat defers maturity by, 0 to end the mode
+        interval: i64,
+    },
+    /// Defer the maturity of a lock in dead man's switch mode to its heartbeat
+    /// interval from now. Maturity never moves earlier, and once reached the
+    /// lock unlocks as usual.
+    #[account(0, writable, name = "lock", desc = "Time lock account")]
+    #[account(1, signer, name = "authority", desc = "Authority of the lock")]
+    #[account(
+        2,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    Heartbeat,
+    /// Make a lock open during recurring windows rather than once, before it
+    /// matures: `TryUnlock` only reveals the secret within `window` seconds of
+    /// the start of each period. `unit` `RECUR_NONE` makes it open once again.
+    #[account(0, writable, name = "lock", desc = "Time lock account")]
+    #[account(1, signer, name = "authority", desc = "Authority of the lock")]
+    #[account(
+        2,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    SetRecurrence {
+        /// one of the `TimeLockAccount::RECUR_*` units
+        unit: u8,
+        /// length of the period in `unit`, from the lock timestamp
+        every: u32,
+        /// seconds the lock stays open from the start of each period
+        window: u32,
+    },
+    /// Make a lock call another program when it first reveals its secret,
+    /// before it matures. The callback PDA signs the call as its first
+    /// account, followed by the accounts after `target`, at most
+    /// `MAX_CALLBACK_ACCOUNTS`, with their writability; the secret is appended
+    /// to `data`. Setting it again replaces the call.
+    #[account(0, writable, name = "lock", desc = "Time lock account")]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "authority",
+        desc = "Authority of the lock, pays the callback rent"
+    )]
+    #[account(2, writable, name = "callback", desc = "Callback PDA of the lock")]
+    #[account(3, name = "system_program", desc = "System program")]
+    #[account(
+        4,
+        name = "target",
+        desc = "Program called, the accounts of the call follow"
+    )]
+    #[account(
+        5,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        6,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    SetCallback {
+        /// instruction data of the call preceding the secret, at most
+        /// `MAX_CALLBACK_DATA` bytes
+        #[borsh(
+            deserialize_with = "wire::callback_data",
+            serialize_with = "wire::trailing"
+        )]
+        data: Vec<u8>,
+    },
+    /// Make a lock execute an instruction once, as is, when it first reveals
+    /// its secret, before it matures: the call of `SetCallback` without the
+    /// callback PDA prepended nor the secret appended. The PDA signs wherever
+    /// the accounts after `target` list it, those being the accounts of the
+    /// instruction. Setting it again replaces the call.
+    #[account(0, writable, name = "lock", desc = "Time lock account")]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "authority",
+        desc = "Authority of the lock, pays the callback rent"
+    )]
+    #[account(2, writable, name = "callback", desc = "Callback PDA of the lock")]
+    #[account(3, name = "system_program", desc = "System program")]
+    #[account(
+        4,
+        name = "target",
+        desc = "Program called, the accounts of the instruction follow"
+    )]
+    #[account(
+        5,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        6,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    SetUnlockInstruction {
+        /// instruction data, at most `MAX_CALLBACK_DATA` bytes
+        #[borsh(
+            deserialize_with = "wire::callback_data",
+            serialize_with = "wire::trailing"
+        )]
+        data: Vec<u8>,
+    },
+    /// Make a lock reveal its secret only while the data of another account
+    /// holds the expected bytes at an offset, a flag or a counter of another
+    /// program, checked by `TryUnlock` against the account passed after the
+    /// others. Only allowed before maturity; the default pubkey as the account
+    /// lifts the condition.
+    #[account(0, writable, name = "lock", desc = "Time lock account")]
+    #[account(1, signer, name = "authority", desc = "Authority of the lock")]
+    #[account(
+        2,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    SetCondition {
+        /// account whose data is checked
+        account: Pubkey,
+        /// offset of the bytes in the data of `account`
+        offset: u32,
+        /// bytes expected at `offset`, at most `MAX_CONDITION_BYTES`
+        #[borsh(
+            deserialize_with = "wire::condition_bytes",
+            serialize_with = "wire::trailing"
+        )]
+        expected: Vec<u8>,
+    },
+    /// Make a lock reveal its secret only once another lock revealed its own,
+    /// chaining milestones, checked by `TryUnlock` against the predecessor
+    /// passed after the others. Only allowed before maturity; the default pubkey
+    /// ends the dependency.
+    #[account(0, writable, name = "lock", desc = "Time lock account")]
+    #[account(1, signer, name = "authority", desc = "Authority of the lock")]
+    #[account(
+        2,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    SetPredecessor {
+        /// lock that must reveal first
+        predecessor: Pubkey,
+    },
+    /// Fund a distributor of timelocked claims: `amount` lamports shared among
+    /// the `num_leaves` leaves of a merkle tree of (recipient, amount, unlock
+    /// time), each claimable once with `ClaimWithProof`.
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "distributor",
+        desc = "Distributor account to create"
+    )]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "authority",
+        desc = "Pays the amount and the rent"
+    )]
+    #[account(2, name = "system_program", desc = "System program")]
+    #[account(
+        3,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        4,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    CreateDistributor {
+        /// root of the merkle tree of the claims
+        root: [u8; 32],
+        /// number of leaves of the tree
+        num_leaves: u32,
+        /// lamports distributed
+        amount: u64,
+    },
+    /// Pay the amount of a leaf of a distributor to its recipient, from its
+    /// unlock time on, once. The leaf is checked against the root of the
+    /// distributor with the proof, see `merkle::verify`.
+    #[account(0, writable, name = "distributor", desc = "Distributor account")]
+    #[account(1, writable, name = "recipient", desc = "Recipient of the leaf")]
+    #[account(
+        2,
+        writable,
+        name = "config",
+        desc = "Config PDA, keeps the claim fee, no claim goes through while paused"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        4,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    ClaimWithProof {
+        /// index of the leaf
+        index: u32,
+        /// lamports of the leaf
+        amount: u64,
+        /// unix timestamp the leaf is claimable from
+        unlock_time: i64,
+        /// siblings of the leaf up to the root, at most `MAX_PROOF_LEN`
+        #[borsh(deserialize_with = "wire::proof", serialize_with = "wire::trailing")]
+        proof: Vec<[u8; 32]>,
+    },
+    /// Fund a vault with `amount` lamports of its authority, for many vault
+    /// locks to draw from at claim time instead of each holding its amount.
+    #[account(0, writable, signer, name = "vault", desc = "Vault account to create")]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "authority",
+        desc = "Pays the amount and the rent"
+    )]
+    #[account(2, name = "system_program", desc = "System program")]
+    #[account(
+        3,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        4,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    CreateVault {
+        /// lamports put in the vault
+        amount: u64,
+    },
+    /// Allocate lamports of a vault to a beneficiary, who can claim them from
+    /// `release_time`. Only the unallocated lamports of the vault can be. The
+    /// vault stats count it, see `VaultStatsAccount`.
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "vault_lock",
+        desc = "Vault lock account to create"
+    )]
+    #[account(1, writable, name = "vault", desc = "Vault the amount is drawn from")]
+    #[account(
+        2,
+        writable,
+        signer,
+        name = "authority",
+        desc = "Authority of the vault, pays the rent"
+    )]
+    #[account(3, name = "beneficiary", desc = "May claim the amount")]
+    #[account(4, name = "system_program", desc = "System program")]
+    #[account(
+        5,
+        writable,
+        name = "vault_stats",
+        desc = "Stats PDA of the vault, created with its first vault lock"
+    )]
+    #[account(
+        6,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        7,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    CreateVaultLock {
+        /// lamports allocated
+        amount: u64,
+        /// unix timestamp the beneficiary may claim from
+        release_time: i64,
+        /// whether the beneficiary may hand the vault lock to another one
+        #[borsh(deserialize_with = "wire::transferable_flag")]
+        transferable: bool,
+    },
+    /// Pay the amount of a vault lock out of its vault to its beneficiary,
+    /// closing the vault lock.
+    #[account(0, writable, name = "vault_lock", desc = "Vault lock account")]
+    #[account(1, writable, name = "vault", desc = "Vault paying the amount")]
+    #[account(
+        2,
+        writable,
+        signer,
+        name = "beneficiary",
+        desc = "Beneficiary of the vault lock"
+    )]
+    #[account(
+        3,
+        writable,
+        name = "payer",
+        desc = "Paid the rent of the vault lock, refunded it"
+    )]
+    #[account(
+        4,
+        writable,
+        name = "config",
+        desc = "Config PDA, keeps the claim fee, no claim goes through while paused"
+    )]
+    #[account(
+        5,
+        writable,
+        name = "vault_stats",
+        desc = "Stats PDA of the vault, updated once created"
+    )]
+    #[account(
+        6,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        7,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    ClaimVaultLock,
+    /// Take lamports of a vault no vault lock was allocated back to its
+    /// authority.
+    #[account(0, writable, name = "vault", desc = "Vault account")]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "authority",
+        desc = "Authority of the vault"
+    )]
+    #[account(
+        2,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    WithdrawUnallocated {
+        /// lamports withdrawn
+        amount: u64,
+    },
+    /// Move part of the amount of a vault lock into a new vault lock with the
+    /// same vault and release time, for another beneficiary or the same one.
+    /// Signed by the beneficiary, who pays the rent of the new vault lock.
+    #[account(0, writable, name = "vault_lock", desc = "Vault lock account to split")]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "new_vault_lock",
+        desc = "Vault lock account to create"
+    )]
+    #[account(
+        2,
+        writable,
+        signer,
+        name = "beneficiary",
+        desc = "Beneficiary of the vault lock, pays the rent"
+    )]
+    #[account(3, name = "new_beneficiary", desc = "May claim the new vault lock")]
+    #[account(4, name = "system_program", desc = "System program")]
+    #[account(
+        5,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        6,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    SplitLock {
+        /// lamports moved to the new vault lock
+        amount: u64,
+    },
+    /// Move the whole amount of a vault lock into another one with the same vault,
+    /// beneficiary and release time, closing it. Signed by the beneficiary.
+    #[account(
+        0,
+        writable,
+        name = "vault_lock",
+        desc = "Vault lock account to merge into"
+    )]
+    #[account(1, writable, name = "source", desc = "Vault lock account to close")]
+    #[account(
+        2,
+        signer,
+        name = "beneficiary",
+        desc = "Beneficiary of both vault locks"
+    )]
+    #[account(
+        3,
+        writable,
+        name = "payer",
+        desc = "Paid the rent of the source, refunded it"
+    )]
+    #[account(
+        4,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        5,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    MergeLocks,
+    /// Hand a transferable vault lock to another beneficiary, who may claim it from
+    /// then on. Signed by the current beneficiary.
+    #[account(
+        0,
+        writable,
+        name = "vault_lock",
+        desc = "Vault lock account to transfer"
+    )]
+    #[account(
+        1,
+        signer,
+        name = "beneficiary",
+        desc = "Current beneficiary of the vault lock"
+    )]
+    #[account(
+        2,
+        name = "new_beneficiary",
+        desc = "May claim the vault lock from now on"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        4,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    TransferVaultLock,
+    /// Sell a transferable vault lock: the buyer pays the price to the beneficiary
+    /// and becomes the beneficiary in the same instruction, so neither side can
+    /// back out halfway. Signed by both.
+    #[account(0, writable, name = "vault_lock", desc = "Vault lock account to sell")]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "beneficiary",
+        desc = "Current beneficiary of the vault lock, paid the price"
+    )]
+    #[account(
+        2,
+        writable,
+        signer,
+        name = "buyer",
+        desc = "Pays the price, may claim the vault lock from now on"
+    )]
+    #[account(3, name = "system_program", desc = "System program")]
+    #[account(
+        4,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        5,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    TransferWithPayment {
+        /// lamports paid by the buyer to the beneficiary
+        price: u64,
+    },
+    /// Create the program config, once. Signed by the upgrade authority of the
+    /// program, who becomes its admin.
+    #[account(0, writable, name = "config", desc = "Config PDA to create")]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "admin",
+        desc = "Upgrade authority of the program, pays the rent"
+    )]
+    #[account(2, name = "programdata", desc = "Program data account of the program")]
+    #[account(3, name = "system_program", desc = "System program")]
+    #[account(
+        4,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        5,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    InitializeConfig {
+        /// receives the collected fees
+        treasury: Pubkey,
+        /// lamports charged for creating a time lock or an escrow
+        creation_fee: u64,
+        /// shortest duration, in seconds, from creation to maturity
+        min_duration: i64,
+        /// longest duration, in seconds, from creation to maturity, 0 for no bound
+        max_duration: i64,
+    },
+    /// Queue new parameters of the program config, applied with
+    /// `ApplyConfigUpdate` once the update delay of the config has passed
+    /// unless the admin or the guardian cancels them with `CancelConfigUpdate`.
+    /// One update is queued at a time. Signed by the admin, who hands the
+    /// config over with `ProposeAdmin` instead.
+    #[account(0, name = "config", desc = "Config PDA")]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "admin",
+        desc = "Admin of the config, pays the rent of the queued update"
+    )]
+    #[account(
+        2,
+        name = "guardian",
+        desc = "May pause and unpause the program once applied"
+    )]
+    #[account(
+        3,
+        writable,
+        name = "config_update",
+        desc = "Queued config update PDA, uninitialized"
+    )]
+    #[account(4, name = "system_program", desc = "System program")]
+    #[account(
+        5,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        6,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    UpdateConfig {
+        /// receives the collected fees
+        treasury: Pubkey,
+        /// lamports charged for creating a time lock or an escrow
+        creation_fee: u64,
+        /// shortest duration, in seconds, from creation to maturity
+        min_duration: i64,
+        /// longest duration, in seconds, from creation to maturity, 0 for no bound
+        max_duration: i64,
+        /// pauses the program when applied; applying never lifts a pause, which
+        /// only `SetPaused` does
+        #[borsh(deserialize_with = "wire::pause_flag")]
+        paused: bool,
+        /// basis points of every claimed amount kept as a fee
+        claim_fee_bps: u16,
+        /// seconds between queueing an update and applying it
+        update_delay: i64,
+        /// seconds a time lock may be initialized past its unlock timestamp
+        clock_drift_tolerance: i64,
+    },
+    /// Move the fees held by the program config to its treasury. Anyone may send
+    /// it, the treasury being fixed by the config.
+    #[account(0, writable, name = "config", desc = "Config PDA")]
+    #[account(1, writable, name = "treasury", desc = "Treasury of the config")]
+    #[account(
+        2,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    CollectFees,
+    /// Pause or unpause the program: while paused, no time lock nor escrow is
+    /// created and no claim goes through, refunds and closes still do. Signed by
+    /// the admin or the guardian of the config.
+    #[account(0, writable, name = "config", desc = "Config PDA")]
+    #[account(
+        1,
+        signer,
+        name = "authority",
+        desc = "Admin or guardian of the config"
+    )]
+    #[account(
+        2,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    SetPaused {
+        /// whether the program is paused
+        #[borsh(deserialize_with = "wire::pause_flag")]
+        paused: bool,
+    },
+    /// Move `amount` lamports of the fees held by the program config to any
+    /// recipient. Signed by the admin of the config.
+    #[account(0, writable, name = "config", desc = "Config PDA")]
+    #[account(1, signer, name = "admin", desc = "Admin of the config")]
+    #[account(2, writable, name = "recipient", desc = "Receives the fees")]
+    #[account(
+        3,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        4,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    WithdrawFees {
+        /// lamports withdrawn, at most the fees held
+        amount: u64,
+    },
+    /// Exempt `key` from the creation and claim fees of the program config, see
+    /// `FeeExemptionAccount`. Signed by the admin of the config.
+    #[account(0, name = "config", desc = "Config PDA")]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "admin",
+        desc = "Admin of the config, pays the rent"
+    )]
+    #[account(
+        2,
+        writable,
+        name = "fee_exemption",
+        desc = "Fee exemption PDA of the key, uninitialized"
+    )]
+    #[account(3, name = "system_program", desc = "System program")]
+    #[account(
+        4,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        5,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    AddFeeExemption {
+        /// key charged no fee
+        key: Pubkey,
+    },
+    /// Charge `key` the fees of the program config again, closing its fee
+    /// exemption PDA. Signed by the admin of the config.
+    #[account(0, name = "config", desc = "Config PDA")]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "admin",
+        desc = "Admin of the config, receives the rent"
+    )]
+    #[account(
+        2,
+        writable,
+        name = "fee_exemption",
+        desc = "Fee exemption PDA of the key"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        4,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    RemoveFeeExemption {
+        /// key charged fees again
+        key: Pubkey,
+    },
+    /// Propose `pending_admin` to take over the program config, until it accepts
+    /// with `AcceptAdmin` or `expires_at`. A new proposal replaces the pending one,
+    /// the default pubkey withdraws it. Signed by the admin of the config.
+    #[account(0, writable, name = "config", desc = "Config PDA")]
+    #[account(1, signer, name = "admin", desc = "Admin of the config")]
+    #[account(2, name = "pending_admin", desc = "Proposed admin")]
+    #[account(
+        3,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        4,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    ProposeAdmin {
+        /// unix timestamp the proposal may be accepted until
+        expires_at: i64,
+    },
+    /// Take over the program config as its pending admin, before the proposal
+    /// expires. Signed by the pending admin.
+    #[account(0, writable, name = "config", desc = "Config PDA")]
+    #[account(
+        1,
+        signer,
+        name = "pending_admin",
+        desc = "Pending admin of the config"
+    )]
+    #[account(
+        2,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    AcceptAdmin,
+    /// Apply the queued update of the program config once its eta has passed,
+    /// refunding the rent of the update. Signed by the admin who queued it.
+    #[account(0, writable, name = "config", desc = "Config PDA")]
+    #[account(1, writable, name = "config_update", desc = "Queued config update PDA")]
+    #[account(
+        2,
+        writable,
+        signer,
+        name = "admin",
+        desc = "Admin of the config, paid the rent of the update"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        4,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    ApplyConfigUpdate,
+    /// Drop the queued update of the program config, refunding its rent. Signed by
+    /// the admin or the guardian of the config.
+    #[account(0, name = "config", desc = "Config PDA")]
+    #[account(1, writable, name = "config_update", desc = "Queued config update PDA")]
+    #[account(
+        2,
+        signer,
+        name = "authority",
+        desc = "Admin or guardian of the config"
+    )]
+    #[account(3, writable, name = "payer", desc = "Paid the rent of the update")]
+    #[account(
+        4,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        5,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    CancelConfigUpdate,
+    /// Read-only view of the lifetime revenue of the program config, returned as a
+    /// borsh [`RevenueReport`](crate::state::RevenueReport) in the return data.
+    #[account(0, name = "config", desc = "Config PDA")]
+    GetRevenue,
+    /// Record in the program config that the program has no upgrade authority
+    /// anymore, with the slot it was last deployed at, so integrators can check it
+    /// with `GetImmutability`. Recorded once, anyone may send it.
+    #[account(0, writable, name = "config", desc = "Config PDA")]
+    #[account(1, name = "programdata", desc = "Program data account of the program")]
+    #[account(
+        2,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    RecordImmutability,
+    /// Read-only view of whether the program was recorded immutable, returned as a
+    /// borsh [`ImmutabilityReport`](crate::state::ImmutabilityReport) in the return
+    /// data.
+    #[account(0, name = "config", desc = "Config PDA")]
+    GetImmutability,
+    /// [`TimeLockInstruction::LockStake`] succeeding without effect when the
+    /// stake lock already exists with the same owner, release time and
+    /// beneficiary, so that batch jobs can be re-run.
+    #[account(
+        0,
+        writable,
+        name = "stake_lock",
+        desc = "Stake lock PDA to create, or already holding the same lock"
+    )]
+    #[account(1, writable, name = "stake", desc = "Stake account to escrow")]
+    #[account(
+        2,
+        writable,
+        signer,
+        name = "owner",
+        desc = "Current withdraw authority, pays the rent"
+    )]
+    #[account(3, name = "clock", desc = "Clock sysvar")]
+    #[account(4, name = "stake_program", desc = "Stake program")]
+    #[account(5, name = "system_program", desc = "System program")]
+    #[account(
+        6,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        7,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    LockStakeIdempotent {
+        /// unix timestamp the stake is released at
+        timestamp: i64,
+        /// withdraw authority after release
+        beneficiary: Pubkey,
+    },
+    /// Record in the program config the hash of the verified build of the program and
+    /// the commit it was built from, as `solana-verify` reports them, so integrators
+    /// can compare it with the deployed program. Signed by the admin, who may record
+    /// again after each upgrade.
+    #[account(0, writable, name = "config", desc = "Config PDA")]
+    #[account(1, signer, name = "admin", desc = "Admin of the config")]
+    #[account(
+        2,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    RecordVerifiedBuild {
+        /// sha256 of the executable of the verified build, trailing zeros trimmed
+        build_hash: [u8; 32],
+        /// git commit of the source the build was made from
+        source_commit: [u8; 20],
+    },
+    /// Keep an audit trail of the lock in its audit trail PDA, signed by its authority,
+    /// who pays the rent. Every later mutation of the lock appends its instruction, first
+    /// signer, slot and timestamp, and must pass the PDA writable among its accounts, see
+    /// `with_audit_trail`. Tries to unlock that neither reveal nor pay the bounty are not
+    /// recorded, as anyone may send them. The trail cannot be disabled.
+    #[account(0, writable, name = "lock", desc = "Time lock")]
+    #[account(
+        1,
+        signer,
+        writable,
+        name = "authority",
+        desc = "Authority of the lock, pays the audit trail rent"
+    )]
+    #[account(
+        2,
+        writable,
+        name = "audit_trail",
+        desc = "Audit trail PDA of the lock"
+    )]
+    #[account(3, name = "system_program", desc = "System program")]
+    #[account(
+        4,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        5,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    EnableAuditTrail,
+    /// Pay the bounty of the lock only to claims committed with `CommitClaim` and revealed
+    /// with `RevealClaim` in a later slot, signed by its authority before the lock matures.
+    /// `TryUnlock` still reveals the secret but no longer pays a cranker. Cannot be undone.
+    #[account(0, writable, name = "lock", desc = "Time lock")]
+    #[account(1, signer, name = "authority", desc = "Authority of the lock")]
+    #[account(
+        2,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    RequireClaimCommit,
+    /// Commit to claiming the bounty of the lock for a destination kept secret until
+    /// `RevealClaim`: `commitment` is the sha256 of the destination address followed by a
+    /// salt, see `ClaimCommitmentAccount::commitment`. The committer pays the rent of its
+    /// claim commitment PDA, and may commit again, replacing its commitment.
+    #[account(0, name = "lock", desc = "Time lock")]
+    #[account(
+        1,
+        signer,
+        writable,
+        name = "committer",
+        desc = "Committer, pays the commitment rent"
+    )]
+    #[account(
+        2,
+        writable,
+        name = "claim_commitment",
+        desc = "Claim commitment PDA of the lock and the committer"
+    )]
+    #[account(3, name = "system_program", desc = "System program")]
+    #[account(
+        4,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        5,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    CommitClaim {
+        /// sha256 of the destination followed by the salt
+        commitment: [u8; 32],
+    },
+    /// Reveal the destination and the salt of the claim committed by the committer in an
+    /// earlier slot, paying the bounty of the lock to the destination once the secret is
+    /// revealed, see `TryUnlock`. The commitment is closed, its rent refunded to the
+    /// committer, even once the bounty was paid to another claim. Locks created with
+    /// `require_memo` only pay when the transaction also carries a memo.
+    #[account(0, writable, name = "lock", desc = "Time lock")]
+    #[account(
+        1,
+        writable,
+        name = "claim_commitment",
+        desc = "Claim commitment PDA of the lock and the committer"
+    )]
+    #[account(
+        2,
+        writable,
+        name = "committer",
+        desc = "Committer, refunded the commitment rent"
+    )]
+    #[account(
+        3,
+        writable,
+        name = "destination",
+        desc = "Destination committed to, paid the bounty"
+    )]
+    #[account(4, writable, name = "stats", desc = "Stats PDA")]
+    #[account(
+        5,
+        name = "config",
+        desc = "Config PDA, no claim goes through while paused"
+    )]
+    #[account(6, name = "instructions", desc = "Instructions sysvar, for the memo")]
+    #[account(
+        7,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        8,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    RevealClaim {
+        /// salt of the commitment
+        salt: [u8; 32],
+    },
+    /// Create a time lock at the PDA of the payer and `nonce`, the next nonce of the payer
+    /// kept in its nonce PDA, see [`lock_address`]. Otherwise as `InitializeTimeLock`: the
+    /// lock address is deterministic and enumerable from the payer, and a captured
+    /// instruction cannot be replayed, its nonce being spent.
+    #[account(
+        0,
+        writable,
+        name = "lock",
+        desc = "Lock PDA of the payer and the nonce, created"
+    )]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "payer",
+        desc = "Pays the rent and the bounty"
+    )]
+    #[account(2, name = "system_program", desc = "System program")]
+    #[account(3, writable, name = "stats", desc = "Global statistics PDA")]
+    #[account(4, writable, name = "config", desc = "Config PDA, receives the fee")]
+    #[account(
+        5,
+        writable,
+        name = "lock_nonce",
+        desc = "Nonce PDA of the payer, created on its first lock"
+    )]
+    #[account(
+        6,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        7,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    InitializeNoncedTimeLock {
+        /// next nonce of the payer, 0 for its first lock
+        nonce: u64,
+        /// unix timestamp the lock matures at
+        timestamp: i64,
+        /// encrypted encoded secret
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_array"))]
+        #[borsh(deserialize_with = "wire::secret")]
+        secret: [u8; SECRET_LENGTH],
+        /// lamports set aside for the first caller of `TryUnlock` after maturity
+        bounty: u64,
+        /// require the transaction claiming the bounty to carry an SPL memo
+        #[borsh(deserialize_with = "wire::memo_flag")]
+        require_memo: bool,
+        /// only the payer, the lock authority, may top up the lock
+        #[borsh(deserialize_with = "wire::top_up_flag")]
+        top_up_authority_only: bool,
+    },
+    /// Fund a payroll with `amount` lamports of its authority, shared among
+    /// `num_entries` beneficiaries vesting on the same schedule: linearly from
+    /// `start_time` to `end_time`, or all at once at `start_time` when equal.
+    /// The authority then adds the entries with `AddPayrollEntries`.
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "payroll",
+        desc = "Payroll account to create"
+    )]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "authority",
+        desc = "Pays the amount and the rent, adds the entries"
+    )]
+    #[account(2, name = "system_program", desc = "System program")]
+    #[account(
+        3,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        4,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    CreatePayroll {
+        /// lamports shared
+        amount: u64,
+        /// unix timestamp the amount starts vesting at
+        start_time: i64,
+        /// unix timestamp the whole amount vested at, not before `start_time`
+        end_time: i64,
+        /// beneficiaries of the payroll, at most `MAX_PAYROLL_ENTRIES`
+        num_entries: u32,
+    },
+    /// Add the next entries of a payroll, as many as fit an instruction. The
+    /// shares are only claimable once every entry is added, their weights
+    /// being final.
+    #[account(0, writable, name = "payroll", desc = "Payroll account")]
+    #[account(1, signer, name = "authority", desc = "Authority of the payroll")]
+    #[account(
+        2,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    AddPayrollEntries {
+        /// beneficiaries and weights of the entries, taking the rest of the
+        /// instruction
+        #[borsh(
+            deserialize_with = "wire::payroll_shares",
+            serialize_with = "wire::trailing"
+        )]
+        entries: Vec<PayrollShare>,
+    },
+    /// Pay the `index`th entry of a payroll its share of what vested since its
+    /// last claim, pro rata to its weight. Anyone may send it, the entry
+    /// decides who is paid.
+    #[account(0, writable, name = "payroll", desc = "Payroll account")]
+    #[account(1, writable, name = "beneficiary", desc = "Beneficiary of the entry")]
+    #[account(
+        2,
+        writable,
+        name = "config",
+        desc = "Config PDA, keeps the claim fee, no claim goes through while paused"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "schedule_template",
+        desc = "Schedule template of the payroll, only when it has one"
+    )]
+    #[account(
+        4,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        5,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    ClaimPayroll {
+        /// index of the entry
+        index: u32,
+    },
+    /// Create a schedule template: `tge_bps` basis points at the start, then
+    /// after `cliff_months` calendar months, `periods` equal releases every
+    /// `period_months`. Templates cannot be modified, locks reference them by
+    /// address, see `CreateTemplatedPayroll`.
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "schedule_template",
+        desc = "Schedule template account to create"
+    )]
+    #[account(1, writable, signer, name = "authority", desc = "Pays the rent")]
+    #[account(2, name = "system_program", desc = "System program")]
+    #[account(
+        3,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        4,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    CreateScheduleTemplate {
+        /// basis points of the amount released at the start
+        tge_bps: u16,
+        /// calendar months from the start before the first period
+        cliff_months: u32,
+        /// calendar months of each period
+        period_months: u32,
+        /// equal releases of the rest after the cliff
+        periods: u32,
+    },
+    /// `CreatePayroll` vesting on a schedule template from `start_time`, the
+    /// token generation event, instead of linearly. The template must be
+    /// passed to `ClaimPayroll`.
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "payroll",
+        desc = "Payroll account to create"
+    )]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "authority",
+        desc = "Pays the amount and the rent, adds the entries"
+    )]
+    #[account(2, name = "system_program", desc = "System program")]
+    #[account(
+        3,
+        name = "schedule_template",
+        desc = "Schedule template the payroll vests on"
+    )]
+    #[account(
+        4,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        5,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    CreateTemplatedPayroll {
+        /// lamports shared
+        amount: u64,
+        /// unix timestamp the schedule starts at
+        start_time: i64,
+        /// beneficiaries of the payroll, at most `MAX_PAYROLL_ENTRIES`
+        num_entries: u32,
+    },
+    /// Drop the callback of a lock once it matured, closing the callback PDA:
+    /// a call that keeps failing would otherwise keep the secret locked. Only
+    /// the authority can clear it.
+    #[account(0, writable, name = "lock", desc = "Time lock account")]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "authority",
+        desc = "Authority of the lock, gets the callback rent"
+    )]
+    #[account(2, writable, name = "callback", desc = "Callback PDA of the lock")]
+    #[account(
+        3,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        4,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    ClearCallback,
+    /// Close a payroll once every entry claimed its whole share, returning the
+    /// rent and what rounding left over to the authority.
+    #[account(0, writable, name = "payroll", desc = "Payroll account to close")]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "authority",
+        desc = "Authority of the payroll, gets its lamports"
+    )]
+    #[account(
+        2,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    ClosePayroll,
+    /// Grow an account created before its layout grew to the current layout,
+    /// appending the defaulted fields. Anyone may migrate any account.
+    #[account(
+        0,
+        writable,
+        name = "account",
+        desc = "Account of the program to migrate"
+    )]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "payer",
+        desc = "Pays the rent of the added bytes"
+    )]
+    #[account(2, name = "system_program", desc = "System program")]
+    #[account(
+        3,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        4,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    MigrateAccount,
+    /// Name the key the installments of a lock are released to once it reveals
+    /// its secret, see `ReleaseGrant`. The authority receives them until then.
+    #[account(
+        0,
+        writable,
+        name = "lock",
+        desc = "Time lock account to set the grant recipient of"
+    )]
+    #[account(1, signer, name = "authority", desc = "Lock authority")]
+    #[account(
+        2,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        3,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    SetGrantRecipient {
+        /// receives the funded total, the authority when the default pubkey
+        recipient: Pubkey,
+    },
+    /// Pay the lamports deposited by `TopUp` to the grant recipient of a lock
+    /// once it revealed its secret. Anyone may send it.
+    #[account(
+        0,
+        writable,
+        name = "lock",
+        desc = "Revealed time lock holding the installments"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "recipient",
+        desc = "Grant recipient of the lock, or its authority when unset"
+    )]
+    #[account(2, writable, name = "stats", desc = "Global statistics PDA")]
+    #[account(
+        3,
+        optional,
+        name = "event_authority",
+        desc = "Event authority PDA, for self-CPI events"
+    )]
+    #[account(
+        4,
+        optional,
+        name = "program",
+        desc = "This program, for self-CPI events"
+    )]
+    ReleaseGrant,
+}
+
+/// Discriminator of [`TimeLockInstruction::InitializeTimeLock`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:initialize_time_lock")]
+pub struct InitializeTimeLockDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::TryUnlock`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:try_unlock")]
+pub struct TryUnlockDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::GetPosition`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:get_position")]
+pub struct GetPositionDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::LockStake`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:lock_stake")]
+pub struct LockStakeDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::ReleaseStake`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:release_stake")]
+pub struct ReleaseStakeDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::TopUp`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:top_up")]
+pub struct TopUpDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::WithdrawExcess`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:withdraw_excess")]
+pub struct WithdrawExcessDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::CreateEscrow`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:create_escrow")]
+pub struct CreateEscrowDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::ClaimEscrow`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:claim_escrow")]
+pub struct ClaimEscrowDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::RefundEscrow`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:refund_escrow")]
+pub struct RefundEscrowDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::ChangeEscrowBeneficiary`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:change_escrow_beneficiary")]
+pub struct ChangeEscrowBeneficiaryDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::SetEscrowDelegate`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:set_escrow_delegate")]
+pub struct SetEscrowDelegateDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::RevokeEscrowDelegate`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:revoke_escrow_delegate")]
+pub struct RevokeEscrowDelegateDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::AddUnlocker`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:add_unlocker")]
+pub struct AddUnlockerDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::RemoveUnlocker`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:remove_unlocker")]
+pub struct RemoveUnlockerDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::ClaimEscrowEarly`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:claim_escrow_early")]
+pub struct ClaimEscrowEarlyDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::SetExpiry`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:set_expiry")]
+pub struct SetExpiryDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::Expire`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:expire")]
+pub struct ExpireDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::SetHeartbeat`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:set_heartbeat")]
+pub struct SetHeartbeatDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::Heartbeat`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:heartbeat")]
+pub struct HeartbeatDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::SetRecurrence`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:set_recurrence")]
+pub struct SetRecurrenceDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::SetCallback`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:set_callback")]
+pub struct SetCallbackDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::SetUnlockInstruction`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:set_unlock_instruction")]
+pub struct SetUnlockInstructionDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::SetCondition`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:set_condition")]
+pub struct SetConditionDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::SetPredecessor`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:set_predecessor")]
+pub struct SetPredecessorDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::CreateDistributor`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:create_distributor")]
+pub struct CreateDistributorDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::ClaimWithProof`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:claim_with_proof")]
+pub struct ClaimWithProofDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::CreateVault`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:create_vault")]
+pub struct CreateVaultDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::CreateVaultLock`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:create_vault_lock")]
+pub struct CreateVaultLockDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::ClaimVaultLock`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:claim_vault_lock")]
+pub struct ClaimVaultLockDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::WithdrawUnallocated`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:withdraw_unallocated")]
+pub struct WithdrawUnallocatedDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::SplitLock`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:split_lock")]
+pub struct SplitLockDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::MergeLocks`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:merge_locks")]
+pub struct MergeLocksDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::TransferVaultLock`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:transfer_vault_lock")]
+pub struct TransferVaultLockDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::TransferWithPayment`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:transfer_with_payment")]
+pub struct TransferWithPaymentDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::InitializeConfig`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:initialize_config")]
+pub struct InitializeConfigDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::UpdateConfig`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:update_config")]
+pub struct UpdateConfigDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::CollectFees`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:collect_fees")]
+pub struct CollectFeesDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::SetPaused`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:set_paused")]
+pub struct SetPausedDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::WithdrawFees`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:withdraw_fees")]
+pub struct WithdrawFeesDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::AddFeeExemption`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:add_fee_exemption")]
+pub struct AddFeeExemptionDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::RemoveFeeExemption`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:remove_fee_exemption")]
+pub struct RemoveFeeExemptionDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::ProposeAdmin`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:propose_admin")]
+pub struct ProposeAdminDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::AcceptAdmin`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:accept_admin")]
+pub struct AcceptAdminDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::ApplyConfigUpdate`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:apply_config_update")]
+pub struct ApplyConfigUpdateDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::CancelConfigUpdate`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:cancel_config_update")]
+pub struct CancelConfigUpdateDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::GetRevenue`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:get_revenue")]
+pub struct GetRevenueDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::RecordImmutability`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:record_immutability")]
+pub struct RecordImmutabilityDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::GetImmutability`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:get_immutability")]
+pub struct GetImmutabilityDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::LockStakeIdempotent`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:lock_stake_idempotent")]
+pub struct LockStakeIdempotentDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::RecordVerifiedBuild`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:record_verified_build")]
+pub struct RecordVerifiedBuildDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::EnableAuditTrail`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:enable_audit_trail")]
+pub struct EnableAuditTrailDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::RequireClaimCommit`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:require_claim_commit")]
+pub struct RequireClaimCommitDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::CommitClaim`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:commit_claim")]
+pub struct CommitClaimDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::RevealClaim`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:reveal_claim")]
+pub struct RevealClaimDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::InitializeNoncedTimeLock`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:initialize_nonced_time_lock")]
+pub struct InitializeNoncedTimeLockDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::CreatePayroll`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:create_payroll")]
+pub struct CreatePayrollDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::AddPayrollEntries`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:add_payroll_entries")]
+pub struct AddPayrollEntriesDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::ClaimPayroll`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:claim_payroll")]
+pub struct ClaimPayrollDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::CreateScheduleTemplate`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:create_schedule_template")]
+pub struct CreateScheduleTemplateDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::CreateTemplatedPayroll`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:create_templated_payroll")]
+pub struct CreateTemplatedPayrollDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::ClearCallback`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:clear_callback")]
+pub struct ClearCallbackDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::ClosePayroll`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:close_payroll")]
+pub struct ClosePayrollDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::MigrateAccount`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:migrate_account")]
+pub struct MigrateAccountDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::SetGrantRecipient`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:set_grant_recipient")]
+pub struct SetGrantRecipientDiscriminator;
+
+/// Discriminator of [`TimeLockInstruction::ReleaseGrant`]
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("timelock:release_grant")]
+pub struct ReleaseGrantDiscriminator;
+
+/// Prefix of the data of each variant, in declaration order: the position of
+/// a prefix is the borsh variant index it replaces
+const PREFIXES: &[&[u8]] = &[
+    InitializeTimeLockDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    TryUnlockDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    &[EMIT_EVENT_TAG],
+    GetPositionDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    LockStakeDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    ReleaseStakeDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    TopUpDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    WithdrawExcessDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    CreateEscrowDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    ClaimEscrowDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    RefundEscrowDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    ChangeEscrowBeneficiaryDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    SetEscrowDelegateDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    RevokeEscrowDelegateDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    AddUnlockerDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    RemoveUnlockerDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    ClaimEscrowEarlyDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    SetExpiryDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    ExpireDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    SetHeartbeatDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    HeartbeatDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    SetRecurrenceDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    SetCallbackDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    SetUnlockInstructionDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    SetConditionDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    SetPredecessorDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    CreateDistributorDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    ClaimWithProofDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    CreateVaultDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    CreateVaultLockDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    ClaimVaultLockDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    WithdrawUnallocatedDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    SplitLockDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    MergeLocksDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    TransferVaultLockDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    TransferWithPaymentDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    InitializeConfigDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    UpdateConfigDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    CollectFeesDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    SetPausedDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    WithdrawFeesDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    AddFeeExemptionDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    RemoveFeeExemptionDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    ProposeAdminDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    AcceptAdminDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    ApplyConfigUpdateDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    CancelConfigUpdateDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    GetRevenueDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    RecordImmutabilityDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    GetImmutabilityDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    LockStakeIdempotentDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    RecordVerifiedBuildDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    EnableAuditTrailDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    RequireClaimCommitDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    CommitClaimDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    RevealClaimDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    InitializeNoncedTimeLockDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    CreatePayrollDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    AddPayrollEntriesDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    ClaimPayrollDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    CreateScheduleTemplateDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    CreateTemplatedPayrollDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    ClearCallbackDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    ClosePayrollDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    MigrateAccountDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    SetGrantRecipientDiscriminator::SPL_DISCRIMINATOR_SLICE,
+    ReleaseGrantDiscriminator::SPL_DISCRIMINATOR_SLICE,
+];
+
+impl TimeLockInstruction {
+    /// Decode instruction data: an 8-byte discriminator followed by the
+    /// fields, or the legacy one byte tag (0, 1) followed by the same fields.
+    ///
+    /// Inverse of [`TimeLockInstruction::pack`]. Truncated data and trailing
+    /// bytes are rejected, except after [`EMIT_EVENT_TAG`] which is followed
+    /// by the event.
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() > MAX_INSTRUCTION_DATA {
+            return Err(TimeLockError::InstructionDataTooLarge.into());
+        }
+        if let Some((discriminator, rest)) = input.split_first_chunk::<8>() {
+            if let Some(index) = PREFIXES.iter().position(|p| *p == discriminator) {
+                return Self::unpack_fields(index as u8, rest);
+            }
+        }
+        let (tag, rest) = input
+            .split_first()
+            .ok_or(TimeLockError::InstructionTooShort)?;
+        match *tag {
+            // the legacy tags are the variant indices of the first two variants
+            0 | 1 => Self::unpack_fields(*tag, rest),
+            EMIT_EVENT_TAG => Ok(Self::EmitEvent),
+            _ => Err(TimeLockError::UnknownInstruction.into()),
+        }
+    }
+
+    /// The one byte tag of `input` if it has the legacy layout, which
+    /// [`TimeLockInstruction::unpack`] falls back to. No discriminator starts
+    /// with a legacy tag.
+    pub fn legacy_tag(input: &[u8]) -> Option<u8> {
+        input.first().copied().filter(|tag| *tag <= 1)
+    }
+
+    /// Borsh decode the variant at `index` from its `fields`, all of them
+    fn unpack_fields(index: u8, fields: &[u8]) -> Result<Self, ProgramError> {
+        let mut data = Vec::with_capacity(1 + fields.len());
+        data.push(index);
+        data.extend_from_slice(fields);
+        let mut rest = data.as_slice();
+        let ix = Self::deserialize(&mut rest).map_err(wire::error)?;
+        match rest.is_empty() {
+            true => Ok(ix),
+            false => Err(TimeLockError::InstructionTooLong.into()),
+        }
+    }
+
+    /// Encode the instruction with its 8-byte discriminator, see
+    /// [`TimeLockInstruction::unpack`].
+    pub fn pack(&self) -> Vec<u8> {
+        let derived = borsh::to_vec(self).expect("writing to a vec never fails");
+        let (index, fields) = derived
+            .split_first()
+            .expect("the variant index leads the encoding");
+        let mut data = PREFIXES[*index as usize].to_vec();
+        data.extend_from_slice(fields);
+        data
+    }
+}
+
+/// Fields whose encoding is not borsh's own: flags failing with their own
+/// errors, secrets checked as utf8, and trailing data taking the rest of the
+/// instruction without a length prefix.
+mod wire {
+    use core::{fmt, str};
+
+    use borsh::io::{Error, ErrorKind, Read, Result, Write};
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use solana_program::program_error::ProgramError;
+
+    use crate::error::TimeLockError;
+    use crate::merkle::MAX_PROOF_LEN;
+    use crate::state::{PayrollShare, MAX_CALLBACK_DATA, MAX_CONDITION_BYTES, SECRET_LENGTH};
+
+    /// A [`TimeLockError`] of a field, carried through borsh
+    #[derive(Debug)]
+    struct FieldError(TimeLockError);
+
+    impl fmt::Display for FieldError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{:?}", self.0)
+        }
+    }
+
+    impl std::error::Error for FieldError {}
+
+    fn invalid(error: TimeLockError) -> Error {
+        Error::new(ErrorKind::InvalidData, FieldError(error))
+    }
+
+    /// The error of a field, else truncated fields, all borsh rejects
+    /// otherwise
+    pub(super) fn error(error: Error) -> ProgramError {
+        match error.get_ref().and_then(|e| e.downcast_ref::<FieldError>()) {
+            Some(FieldError(error)) => (*error).into(),
+            None => TimeLockError::InstructionTooShort.into(),
+        }
+    }
+
+    fn flag<R: Read>(reader: &mut R, error: TimeLockError) -> Result<bool> {
+        match u8::deserialize_reader(reader)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(invalid(error)),
+        }
+    }
+
+    /// A flag absent from older data, false then
+    fn legacy_flag<R: Read>(reader: &mut R, error: TimeLockError) -> Result<bool> {
+        let mut byte = [0];
+        match reader.read(&mut byte)? {
+            0 => Ok(false),
+            _ => flag(&mut byte.as_slice(), error),
+        }
+    }
+
+    pub(super) fn memo_flag<R: Read>(reader: &mut R) -> Result<bool> {
+        flag(reader, TimeLockError::InvalidMemoFlag)
+    }
+
+    pub(super) fn top_up_flag<R: Read>(reader: &mut R) -> Result<bool> {
+        flag(reader, TimeLockError::InvalidTopUpFlag)
+    }
+
+    pub(super) fn legacy_memo_flag<R: Read>(reader: &mut R) -> Result<bool> {
+        legacy_flag(reader, TimeLockError::InvalidMemoFlag)
+    }
+
+    /// Absent from older data too, the top up flag being newer
+    pub(super) fn legacy_top_up_flag<R: Read>(reader: &mut R) -> Result<bool> {
+        legacy_flag(reader, TimeLockError::InvalidTopUpFlag)
+    }
+
+    /// Absent from the first data, which had no bounty, 0 then
+    pub(super) fn legacy_bounty<R: Read>(reader: &mut R) -> Result<u64> {
+        let mut bytes = [0; 8];
+        match reader.read(&mut bytes[..1])? {
+            0 => Ok(0),
+            _ => {
+                reader.read_exact(&mut bytes[1..])?;
+                Ok(u64::from_le_bytes(bytes))
+            }
+        }
+    }
+
+    pub(super) fn penalty_flag<R: Read>(reader: &mut R) -> Result<bool> {
+        flag(reader, TimeLockError::InvalidPenaltyFlag)
+    }
+
+    pub(super) fn transferable_flag<R: Read>(reader: &mut R) -> Result<bool> {
+        flag(reader, TimeLockError::InvalidTransferableFlag)
+    }
+
+    pub(super) fn pause_flag<R: Read>(reader: &mut R) -> Result<bool> {
+        flag(reader, TimeLockError::InvalidPauseFlag)
+    }
+
+    pub(super) fn secret<R: Read>(reader: &mut R) -> Result<[u8; SECRET_LENGTH]> {
+        let secret = <[u8; SECRET_LENGTH]>::deserialize_reader(reader)?;
+        str::from_utf8(&secret).map_err(|_| invalid(TimeLockError::SecretNotUtf8))?;
+        Ok(secret)
+    }
+
+    fn rest<R: Read>(reader: &mut R) -> Result<Vec<u8>> {
+        let mut rest = vec![];
+        reader.read_to_end(&mut rest)?;
+        Ok(rest)
+    }
+
+    /// The rest of the data as items of `size` bytes
+    fn items<R: Read, T: BorshDeserialize>(reader: &mut R, size: usize) -> Result<Vec<T>> {
+        let rest = rest(reader)?;
+        let chunks = rest.chunks_exact(size);
+        if !chunks.remainder().is_empty() {
+            return Err(invalid(TimeLockError::InstructionTooLong));
+        }
+        chunks.map(|mut chunk| T::deserialize(&mut chunk)).collect()
+    }
+
+    pub(super) fn callback_data<R: Read>(reader: &mut R) -> Result<Vec<u8>> {
+        let data = rest(reader)?;
+        if data.len() > MAX_CALLBACK_DATA {
+            return Err(invalid(TimeLockError::InstructionTooLong));
+        }
+        Ok(data)
+    }
+
+    pub(super) fn condition_bytes<R: Read>(reader: &mut R) -> Result<Vec<u8>> {
+        let expected = rest(reader)?;
+        if expected.len() > MAX_CONDITION_BYTES {
+            return Err(invalid(TimeLockError::ConditionTooLong));
+        }
+        Ok(expected)
+    }
+
+    pub(super) fn proof<R: Read>(reader: &mut R) -> Result<Vec<[u8; 32]>> {
+        let proof = items(reader, 32)?;
+        if proof.len() > MAX_PROOF_LEN {
+            return Err(invalid(TimeLockError::ProofTooLong));
+        }
+        Ok(proof)
+    }
+
+    pub(super) fn payroll_shares<R: Read>(reader: &mut R) -> Result<Vec<PayrollShare>> {
+        items(reader, PayrollShare::LEN)
+    }
+
+    /// `items` one after the other, up to the end of the data
+    pub(super) fn trailing<T: BorshSerialize, W: Write>(items: &[T], writer: &mut W) -> Result<()> {
+        items.iter().try_for_each(|item| item.serialize(writer))
+    }
+}
+
+/// Create a time lock at `lock` maturing at `timestamp`, with `bounty`
+/// lamports set aside for its first unlocker. `lock` and `payer` sign,
+/// `payer` becomes the lock authority.
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_time_lock(
+    program_id: &Pubkey,
+    lock: &Pubkey,
+    payer: &Pubkey,
+    timestamp: i64,
+    secret: [u8; SECRET_LENGTH],
+    bounty: u64,
+    require_memo: bool,
+    top_up_authority_only: bool,
+) -> Instruction {
+    let data = TimeLockInstruction::InitializeTimeLock {
+        timestamp,
+        secret,
+        bounty,
+        require_memo,
+        top_up_authority_only,
+    }
+    .pack();
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new(*lock, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(stats_address(program_id).0, false),
+            AccountMeta::new(config_address(program_id).0, false),
+        ],
+    )
+}
+
+/// Reveal the secret of `lock` if it matured, paying its bounty to `cranker`
+/// when one signs.
+///
+/// Locks created with `require_memo` only pay when the transaction also
+/// carries a [`memo`] instruction.
+pub fn try_unlock(program_id: &Pubkey, lock: &Pubkey, cranker: Option<&Pubkey>) -> Instruction {
+    let mut accounts = vec![AccountMeta::new(*lock, false)];
+    if let Some(cranker) = cranker {
+        accounts.push(AccountMeta::new(*cranker, true));
+        accounts.push(AccountMeta::new(stats_address(program_id).0, false));
+        accounts.push(AccountMeta::new_readonly(
+            config_address(program_id).0,
+            false,
+        ));
+        accounts.push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+    }
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::TryUnlock.pack(),
+        accounts,
+    )
+}
+
+/// SPL Memo program
+pub const MEMO_PROGRAM_ID: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+/// Legacy SPL Memo program, accepted as well
+pub const MEMO_V1_PROGRAM_ID: Pubkey = pubkey!("Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo");
+
+/// SPL memo carrying `text`, annotating a claim.
+pub fn memo(text: &str) -> Instruction {
+    Instruction::new_with_bytes(MEMO_PROGRAM_ID, text.as_bytes(), vec![])
+}
+
+/// Value `lock` for collateral, see [`LockPosition`](crate::state::LockPosition).
+pub fn get_position(program_id: &Pubkey, lock: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::GetPosition.pack(),
+        vec![AccountMeta::new_readonly(*lock, false)],
+    )
+}
+
+/// Deposit `amount` lamports paid by `depositor` in `lock`, an installment
+/// released to its grant recipient once revealed.
+pub fn top_up(program_id: &Pubkey, lock: &Pubkey, depositor: &Pubkey, amount: u64) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::TopUp { amount }.pack(),
+        vec![
+            AccountMeta::new(*lock, false),
+            AccountMeta::new(*depositor, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(stats_address(program_id).0, false),
+        ],
+    )
+}
+
+/// Send the surplus lamports of `lock` to its `authority`, who signs.
+pub fn withdraw_excess(program_id: &Pubkey, lock: &Pubkey, authority: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::WithdrawExcess.pack(),
+        vec![
+            AccountMeta::new(*lock, false),
+            AccountMeta::new(*authority, true),
+        ],
+    )
+}
+
+/// Escrow `amount` lamports of `depositor` for `beneficiary` at `escrow`.
+/// `escrow` and `depositor` sign. A non zero `penalty_bps` lets the
+/// beneficiary claim before `release_time`, forfeiting that share of the
+/// amount, see [`EscrowAccount::early_claim_penalty`](crate::state::EscrowAccount::early_claim_penalty).
+#[allow(clippy::too_many_arguments)]
+pub fn create_escrow(
+    program_id: &Pubkey,
+    escrow: &Pubkey,
+    depositor: &Pubkey,
+    beneficiary: &Pubkey,
+    amount: u64,
+    release_time: i64,
+    refund_time: i64,
+    penalty_bps: u16,
+    penalty_decays: bool,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::CreateEscrow {
+            amount,
+            release_time,
+            refund_time,
+            penalty_bps,
+            penalty_decays,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(*escrow, true),
+            AccountMeta::new(*depositor, true),
+            AccountMeta::new_readonly(*beneficiary, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(config_address(program_id).0, false),
+        ],
+    )
+}
+
+/// Claim `escrow` for its `beneficiary`, refunding the rent to `depositor`.
+/// The beneficiary signs, or `delegate` when given.
+pub fn claim_escrow(
+    program_id: &Pubkey,
+    escrow: &Pubkey,
+    beneficiary: &Pubkey,
+    depositor: &Pubkey,
+    delegate: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*escrow, false),
+        AccountMeta::new(*beneficiary, delegate.is_none()),
+        AccountMeta::new(*depositor, false),
+        AccountMeta::new(config_address(program_id).0, false),
+    ];
+    if let Some(delegate) = delegate {
+        accounts.push(AccountMeta::new_readonly(*delegate, true));
+    }
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::ClaimEscrow.pack(),
+        accounts,
+    )
+}
+
+/// Take an unclaimed `escrow` back for its `depositor`, who signs.
+pub fn refund_escrow(program_id: &Pubkey, escrow: &Pubkey, depositor: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::RefundEscrow.pack(),
+        vec![
+            AccountMeta::new(*escrow, false),
+            AccountMeta::new(*depositor, true),
+        ],
+    )
+}
+
+/// Redirect `escrow` to `beneficiary`, signed by its `depositor`.
+pub fn change_escrow_beneficiary(
+    program_id: &Pubkey,
+    escrow: &Pubkey,
+    depositor: &Pubkey,
+    beneficiary: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::ChangeEscrowBeneficiary {
+            beneficiary: *beneficiary,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(*escrow, false),
+            AccountMeta::new_readonly(*depositor, true),
+        ],
+    )
+}
+
+/// Let `delegate` claim `escrow`, signed by its `depositor`.
+pub fn set_escrow_delegate(
+    program_id: &Pubkey,
+    escrow: &Pubkey,
+    depositor: &Pubkey,
+    delegate: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::SetEscrowDelegate {
+            delegate: *delegate,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(*escrow, false),
+            AccountMeta::new_readonly(*depositor, true),
+        ],
+    )
+}
+
+/// Remove the delegate of `escrow`, signed by its `depositor`.
+pub fn revoke_escrow_delegate(
+    program_id: &Pubkey,
+    escrow: &Pubkey,
+    depositor: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::RevokeEscrowDelegate.pack(),
+        vec![
+            AccountMeta::new(*escrow, false),
+            AccountMeta::new_readonly(*depositor, true),
+        ],
+    )
+}
+
+/// Allow `unlocker` to call `TryUnlock` on `lock`, signed by its `authority`.
+pub fn add_unlocker(
+    program_id: &Pubkey,
+    lock: &Pubkey,
+    authority: &Pubkey,
+    unlocker: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::AddUnlocker {
+            unlocker: *unlocker,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(*lock, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Disallow `unlocker` to call `TryUnlock` on `lock`, signed by its `authority`.
+pub fn remove_unlocker(
+    program_id: &Pubkey,
+    lock: &Pubkey,
+    authority: &Pubkey,
+    unlocker: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::RemoveUnlocker {
+            unlocker: *unlocker,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(*lock, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Claim `escrow` before its release time for its `beneficiary`, who signs,
+/// forfeiting the penalty to `depositor` along with the rent.
+pub fn claim_escrow_early(
+    program_id: &Pubkey,
+    escrow: &Pubkey,
+    beneficiary: &Pubkey,
+    depositor: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::ClaimEscrowEarly.pack(),
+        vec![
+            AccountMeta::new(*escrow, false),
+            AccountMeta::new(*beneficiary, true),
+            AccountMeta::new(*depositor, false),
+            AccountMeta::new(config_address(program_id).0, false),
+        ],
+    )
+}
+
+/// Let anyone close `lock` once `expires_at` passes, paying its lamports to
+/// `recipient`, signed by its `authority`.
+pub fn set_expiry(
+    program_id: &Pubkey,
+    lock: &Pubkey,
+    authority: &Pubkey,
+    expires_at: i64,
+    recipient: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::SetExpiry {
+            expires_at,
+            recipient: *recipient,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(*lock, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Close `lock` past its expiry, wiping the secret and paying its lamports to
+/// its expiry `recipient`. Anyone may send it.
+pub fn expire(program_id: &Pubkey, lock: &Pubkey, recipient: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::Expire.pack(),
+        vec![
+            AccountMeta::new(*lock, false),
+            AccountMeta::new(*recipient, false),
+            AccountMeta::new(stats_address(program_id).0, false),
+        ],
+    )
+}
+
+/// Put `lock` in dead man's switch mode, each heartbeat of its `authority`
+/// deferring maturity to `interval` seconds later. A zero `interval` ends it.
+pub fn set_heartbeat(
+    program_id: &Pubkey,
+    lock: &Pubkey,
+    authority: &Pubkey,
+    interval: i64,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::SetHeartbeat { interval }.pack(),
+        vec![
+            AccountMeta::new(*lock, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Defer the maturity of `lock`, in dead man's switch mode, to its heartbeat
+/// interval from now, signed by its `authority`.
+pub fn heartbeat(program_id: &Pubkey, lock: &Pubkey, authority: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::Heartbeat.pack(),
+        vec![
+            AccountMeta::new(*lock, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Make `lock` open during recurring windows of `window` seconds, every
+/// `every` seconds or months from its timestamp as `unit` says, signed by its
+/// `authority`. See [`TimeLockAccount::is_open`](crate::state::TimeLockAccount::is_open).
+pub fn set_recurrence(
+    program_id: &Pubkey,
+    lock: &Pubkey,
+    authority: &Pubkey,
+    unit: u8,
+    every: u32,
+    window: u32,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::SetRecurrence {
+            unit,
+            every,
+            window,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(*lock, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Make revealing the secret of `lock` call `target` with `accounts` and
+/// `data` followed by the secret, signed by its `authority`, who pays the rent
+/// of the callback PDA.
+pub fn set_callback(
+    program_id: &Pubkey,
+    lock: &Pubkey,
+    authority: &Pubkey,
+    target: &Pubkey,
+    accounts: &[AccountMeta],
+    data: &[u8],
+) -> Instruction {
+    let mut metas = vec![
+        AccountMeta::new(*lock, false),
+        AccountMeta::new(*authority, true),
+        AccountMeta::new(callback_address(program_id, lock).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(*target, false),
+    ];
+    metas.extend(accounts.iter().map(|meta| AccountMeta {
+        is_signer: false,
+        ..meta.clone()
+    }));
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::SetCallback {
+            data: data.to_vec(),
+        }
+        .pack(),
+        metas,
+    )
+}
+
+/// [`try_unlock`] of a lock with a `callback`, passing the accounts of the
+/// call.
+pub fn try_unlock_with_callback(
+    program_id: &Pubkey,
+    lock: &Pubkey,
+    cranker: Option<&Pubkey>,
+    callback: &CallbackAccount,
+) -> Instruction {
+    let mut ix = try_unlock(program_id, lock, cranker);
+    ix.accounts.push(AccountMeta::new_readonly(
+        callback_address(program_id, lock).0,
+        false,
+    ));
+    ix.accounts
+        .push(AccountMeta::new_readonly(callback.program, false));
+    ix.accounts.extend(callback.account_metas());
+    ix
+}
+
+/// Make revealing the secret of `lock` execute `ix` once, as is, signed by its
+/// `authority`, who pays the rent of the callback PDA. The PDA signs wherever
+/// `ix` lists it, see [`callback_address`].
+pub fn set_unlock_instruction(
+    program_id: &Pubkey,
+    lock: &Pubkey,
+    authority: &Pubkey,
+    ix: &Instruction,
+) -> Instruction {
+    let mut set = set_callback(
+        program_id,
+        lock,
+        authority,
+        &ix.program_id,
+        &ix.accounts,
+        &ix.data,
+    );
+    set.data = TimeLockInstruction::SetUnlockInstruction {
+        data: ix.data.clone(),
+    }
+    .pack();
+    set
+}
+
+/// Make `lock` reveal only while the data of `account` holds `expected` at
+/// `offset`, signed by its `authority`. The default pubkey as `account` lifts
+/// the condition.
+pub fn set_condition(
+    program_id: &Pubkey,
+    lock: &Pubkey,
+    authority: &Pubkey,
+    account: &Pubkey,
+    offset: u32,
+    expected: &[u8],
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::SetCondition {
+            account: *account,
+            offset,
+            expected: expected.to_vec(),
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(*lock, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// [`try_unlock`] of a lock with a condition, passing its `condition` account.
+pub fn try_unlock_with_condition(
+    program_id: &Pubkey,
+    lock: &Pubkey,
+    cranker: Option<&Pubkey>,
+    condition: &Pubkey,
+) -> Instruction {
+    let mut ix = try_unlock(program_id, lock, cranker);
+    ix.accounts
+        .push(AccountMeta::new_readonly(*condition, false));
+    ix
+}
+
+/// Make `lock` reveal only after `predecessor` did, signed by its `authority`.
+/// The default pubkey as `predecessor` ends the dependency.
+pub fn set_predecessor(
+    program_id: &Pubkey,
+    lock: &Pubkey,
+    authority: &Pubkey,
+    predecessor: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::SetPredecessor {
+            predecessor: *predecessor,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(*lock, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// [`try_unlock`] of a lock with a `predecessor`, passing it.
+pub fn try_unlock_with_predecessor(
+    program_id: &Pubkey,
+    lock: &Pubkey,
+    cranker: Option<&Pubkey>,
+    predecessor: &Pubkey,
+) -> Instruction {
+    let mut ix = try_unlock(program_id, lock, cranker);
+    ix.accounts
+        .push(AccountMeta::new_readonly(*predecessor, false));
+    ix
+}
+
+/// Create `distributor`, funded with `amount` lamports of `authority`, for the
+/// `num_leaves` claims of the merkle tree of `root`, see [`crate::merkle`].
+pub fn create_distributor(
+    program_id: &Pubkey,
+    distributor: &Pubkey,
+    authority: &Pubkey,
+    root: [u8; 32],
+    num_leaves: u32,
+    amount: u64,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::CreateDistributor {
+            root,
+            num_leaves,
+            amount,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(*distributor, true),
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Claim the `index`th leaf of `distributor`, paying `amount` to `recipient`
+/// from `unlock_time` on. Anyone may send it, the leaf decides who is paid.
+pub fn claim_with_proof(
+    program_id: &Pubkey,
+    distributor: &Pubkey,
+    recipient: &Pubkey,
+    index: u32,
+    amount: u64,
+    unlock_time: i64,
+    proof: &[[u8; 32]],
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::ClaimWithProof {
+            index,
+            amount,
+            unlock_time,
+            proof: proof.to_vec(),
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(*distributor, false),
+            AccountMeta::new(*recipient, false),
+            AccountMeta::new(config_address(program_id).0, false),
+        ],
+    )
+}
+
+/// Create `vault`, funded with `amount` lamports of its `authority`, to draw
+/// vault locks from.
+pub fn create_vault(
+    program_id: &Pubkey,
+    vault: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::CreateVault { amount }.pack(),
+        vec![
+            AccountMeta::new(*vault, true),
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create `vault_lock`, allocating `amount` lamports of `vault` to
+/// `beneficiary` from `release_time` on, signed by the vault `authority`, who
+/// pays the rent. A `transferable` vault lock may be handed to another
+/// beneficiary by the current one.
+#[allow(clippy::too_many_arguments)]
+pub fn create_vault_lock(
+    program_id: &Pubkey,
+    vault_lock: &Pubkey,
+    vault: &Pubkey,
+    authority: &Pubkey,
+    beneficiary: &Pubkey,
+    amount: u64,
+    release_time: i64,
+    transferable: bool,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::CreateVaultLock {
+            amount,
+            release_time,
+            transferable,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(*vault_lock, true),
+            AccountMeta::new(*vault, false),
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(*beneficiary, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(vault_stats_address(program_id, vault).0, false),
+        ],
+    )
+}
+
+/// Claim `vault_lock` for its `beneficiary`, paid by `vault`, refunding the
+/// rent to its `payer`.
+pub fn claim_vault_lock(
+    program_id: &Pubkey,
+    vault_lock: &Pubkey,
+    vault: &Pubkey,
+    beneficiary: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::ClaimVaultLock.pack(),
+        vec![
+            AccountMeta::new(*vault_lock, false),
+            AccountMeta::new(*vault, false),
+            AccountMeta::new(*beneficiary, true),
+            AccountMeta::new(*payer, false),
+            AccountMeta::new(config_address(program_id).0, false),
+            AccountMeta::new(vault_stats_address(program_id, vault).0, false),
+        ],
+    )
+}
+
+/// Take `amount` unallocated lamports out of `vault`, signed by its
+/// `authority`.
+pub fn withdraw_unallocated(
+    program_id: &Pubkey,
+    vault: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::WithdrawUnallocated { amount }.pack(),
+        vec![
+            AccountMeta::new(*vault, false),
+            AccountMeta::new(*authority, true),
+        ],
+    )
+}
+
+/// Split `amount` lamports off `vault_lock` into `new_vault_lock` for
+/// `new_beneficiary`, signed by its `beneficiary`, who pays the rent.
+pub fn split_lock(
+    program_id: &Pubkey,
+    vault_lock: &Pubkey,
+    new_vault_lock: &Pubkey,
+    beneficiary: &Pubkey,
+    new_beneficiary: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::SplitLock { amount }.pack(),
+        vec![
+            AccountMeta::new(*vault_lock, false),
+            AccountMeta::new(*new_vault_lock, true),
+            AccountMeta::new(*beneficiary, true),
+            AccountMeta::new_readonly(*new_beneficiary, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Merge `source` into `vault_lock`, both held by `beneficiary` with the same
+/// vault and release time, refunding the rent of `source` to its `payer`.
+pub fn merge_locks(
+    program_id: &Pubkey,
+    vault_lock: &Pubkey,
+    source: &Pubkey,
+    beneficiary: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::MergeLocks.pack(),
+        vec![
+            AccountMeta::new(*vault_lock, false),
+            AccountMeta::new(*source, false),
+            AccountMeta::new_readonly(*beneficiary, true),
+            AccountMeta::new(*payer, false),
+        ],
+    )
+}
+
+/// Hand `vault_lock` from its `beneficiary` to `new_beneficiary`, if it was
+/// created transferable.
+pub fn transfer_vault_lock(
+    program_id: &Pubkey,
+    vault_lock: &Pubkey,
+    beneficiary: &Pubkey,
+    new_beneficiary: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::TransferVaultLock.pack(),
+        vec![
+            AccountMeta::new(*vault_lock, false),
+            AccountMeta::new_readonly(*beneficiary, true),
+            AccountMeta::new_readonly(*new_beneficiary, false),
+        ],
+    )
+}
+
+/// Sell the transferable `vault_lock` of `beneficiary` to `buyer` for `price`
+/// lamports, signed by both.
+pub fn transfer_with_payment(
+    program_id: &Pubkey,
+    vault_lock: &Pubkey,
+    beneficiary: &Pubkey,
+    buyer: &Pubkey,
+    price: u64,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::TransferWithPayment { price }.pack(),
+        vec![
+            AccountMeta::new(*vault_lock, false),
+            AccountMeta::new(*beneficiary, true),
+            AccountMeta::new(*buyer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create the program config with `admin`, the upgrade authority of the
+/// program, who pays the rent.
+pub fn initialize_config(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    treasury: &Pubkey,
+    creation_fee: u64,
+    min_duration: i64,
+    max_duration: i64,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::InitializeConfig {
+            treasury: *treasury,
+            creation_fee,
+            min_duration,
+            max_duration,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(config_address(program_id).0, false),
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(
+                bpf_loader_upgradeable::get_program_data_address(program_id),
+                false,
+            ),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Queue new parameters of the program config, signed by its `admin`, with
+/// `guardian` allowed to pause it and `claim_fee_bps` of every claim kept as a
+/// fee. See [`apply_config_update`].
+#[allow(clippy::too_many_arguments)]
+pub fn update_config(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    guardian: &Pubkey,
+    treasury: &Pubkey,
+    creation_fee: u64,
+    min_duration: i64,
+    max_duration: i64,
+    paused: bool,
+    claim_fee_bps: u16,
+    update_delay: i64,
+    clock_drift_tolerance: i64,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::UpdateConfig {
+            treasury: *treasury,
+            creation_fee,
+            min_duration,
+            max_duration,
+            paused,
+            claim_fee_bps,
+            update_delay,
+            clock_drift_tolerance,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(*guardian, false),
+            AccountMeta::new(config_update_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Move the fees held by the program config to its `treasury`.
+pub fn collect_fees(program_id: &Pubkey, treasury: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::CollectFees.pack(),
+        vec![
+            AccountMeta::new(config_address(program_id).0, false),
+            AccountMeta::new(*treasury, false),
+        ],
+    )
+}
+
+/// Pause or unpause the program, signed by the admin or the guardian of the
+/// config as `authority`.
+pub fn set_paused(program_id: &Pubkey, authority: &Pubkey, paused: bool) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::SetPaused { paused }.pack(),
+        vec![
+            AccountMeta::new(config_address(program_id).0, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Move `amount` lamports of the fees held by the program config to
+/// `recipient`, signed by its `admin`.
+pub fn withdraw_fees(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    recipient: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::WithdrawFees { amount }.pack(),
+        vec![
+            AccountMeta::new(config_address(program_id).0, false),
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(*recipient, false),
+        ],
+    )
+}
+
+/// Exempt `key` from the fees of the program config, signed by its `admin`.
+pub fn add_fee_exemption(program_id: &Pubkey, admin: &Pubkey, key: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::AddFeeExemption { key: *key }.pack(),
+        vec![
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(fee_exemption_address(program_id, key).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Charge `key` the fees of the program config again, signed by its `admin`.
+pub fn remove_fee_exemption(program_id: &Pubkey, admin: &Pubkey, key: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::RemoveFeeExemption { key: *key }.pack(),
+        vec![
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(fee_exemption_address(program_id, key).0, false),
+        ],
+    )
+}
+
+/// Append the fee exemption PDA of `key` to `ix`, so that a creation paid by
+/// or a claim for `key` is charged no fee.
+pub fn with_fee_exemption(program_id: &Pubkey, mut ix: Instruction, key: &Pubkey) -> Instruction {
+    ix.accounts.push(AccountMeta::new_readonly(
+        fee_exemption_address(program_id, key).0,
+        false,
+    ));
+    ix
+}
+
+/// Append the audit trail PDA of `lock` to `ix`, an instruction mutating a
+/// lock that keeps an audit trail, see [`enable_audit_trail`].
+pub fn with_audit_trail(program_id: &Pubkey, mut ix: Instruction, lock: &Pubkey) -> Instruction {
+    ix.accounts
+        .push(AccountMeta::new(audit_address(program_id, lock).0, false));
+    ix
+}
+
+/// Propose `pending_admin` to take over the program config until
+/// `expires_at`, signed by its `admin`.
+pub fn propose_admin(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    pending_admin: &Pubkey,
+    expires_at: i64,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::ProposeAdmin { expires_at }.pack(),
+        vec![
+            AccountMeta::new(config_address(program_id).0, false),
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(*pending_admin, false),
+        ],
+    )
+}
+
+/// Take over the program config as its `pending_admin`.
+pub fn accept_admin(program_id: &Pubkey, pending_admin: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::AcceptAdmin.pack(),
+        vec![
+            AccountMeta::new(config_address(program_id).0, false),
+            AccountMeta::new_readonly(*pending_admin, true),
+        ],
+    )
+}
+
+/// Apply the queued update of the program config, signed by the `admin` who
+/// queued it, refunding its rent to them.
+pub fn apply_config_update(program_id: &Pubkey, admin: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::ApplyConfigUpdate.pack(),
+        vec![
+            AccountMeta::new(config_address(program_id).0, false),
+            AccountMeta::new(config_update_address(program_id).0, false),
+            AccountMeta::new(*admin, true),
+        ],
+    )
+}
+
+/// Drop the queued update of the program config, signed by the admin or the
+/// guardian of the config as `authority`, refunding its rent to `payer`.
+pub fn cancel_config_update(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::CancelConfigUpdate.pack(),
+        vec![
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+            AccountMeta::new(config_update_address(program_id).0, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*payer, false),
+        ],
+    )
+}
+
+/// Report the lifetime revenue of the program config in the return data.
+pub fn get_revenue(program_id: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::GetRevenue.pack(),
+        vec![AccountMeta::new_readonly(
+            config_address(program_id).0,
+            false,
+        )],
+    )
+}
+
+/// Record in the program config that the program has no upgrade authority
+/// anymore.
+pub fn record_immutability(program_id: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::RecordImmutability.pack(),
+        vec![
+            AccountMeta::new(config_address(program_id).0, false),
+            AccountMeta::new_readonly(
+                bpf_loader_upgradeable::get_program_data_address(program_id),
+                false,
+            ),
+        ],
+    )
+}
+
+/// Report whether the program was recorded immutable in the return data.
+pub fn get_immutability(program_id: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::GetImmutability.pack(),
+        vec![AccountMeta::new_readonly(
+            config_address(program_id).0,
+            false,
+        )],
+    )
+}
+
+/// Record in the program config the hash of the verified build of the
+/// program, as `solana-verify` reports it, and the `source_commit` it was
+/// built from, signed by its `admin`.
+pub fn record_verified_build(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    build_hash: [u8; 32],
+    source_commit: [u8; 20],
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::RecordVerifiedBuild {
+            build_hash,
+            source_commit,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(config_address(program_id).0, false),
+            AccountMeta::new_readonly(*admin, true),
+        ],
+    )
+}
+
+/// Keep an audit trail of `lock`, signed by its `authority`, which pays the
+/// rent of the audit trail PDA.
+pub fn enable_audit_trail(program_id: &Pubkey, lock: &Pubkey, authority: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::EnableAuditTrail.pack(),
+        vec![
+            AccountMeta::new(*lock, false),
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(audit_address(program_id, lock).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Pay the bounty of `lock` only to committed claims, see [`commit_claim`],
+/// signed by its `authority`.
+pub fn require_claim_commit(program_id: &Pubkey, lock: &Pubkey, authority: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::RequireClaimCommit.pack(),
+        vec![
+            AccountMeta::new(*lock, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Commit `committer` to claiming the bounty of `lock` for `destination`,
+/// hidden by `salt` until [`reveal_claim`] in a later slot.
+pub fn commit_claim(
+    program_id: &Pubkey,
+    lock: &Pubkey,
+    committer: &Pubkey,
+    destination: &Pubkey,
+    salt: &[u8; 32],
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::CommitClaim {
+            commitment: ClaimCommitmentAccount::commitment(destination, salt),
+        }
+        .pack(),
+        vec![
+            AccountMeta::new_readonly(*lock, false),
+            AccountMeta::new(*committer, true),
+            AccountMeta::new(
+                claim_commitment_address(program_id, lock, committer).0,
+                false,
+            ),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Reveal the claim of the bounty of `lock` committed by `committer` with
+/// [`commit_claim`], paying it to `destination`. Sent in a later slot than
+/// the commitment, after a [`try_unlock`] in the same transaction when the
+/// secret is not revealed yet.
+pub fn reveal_claim(
+    program_id: &Pubkey,
+    lock: &Pubkey,
+    committer: &Pubkey,
+    destination: &Pubkey,
+    salt: [u8; 32],
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::RevealClaim { salt }.pack(),
+        vec![
+            AccountMeta::new(*lock, false),
+            AccountMeta::new(
+                claim_commitment_address(program_id, lock, committer).0,
+                false,
+            ),
+            AccountMeta::new(*committer, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new(stats_address(program_id).0, false),
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),
+        ],
+    )
+}
+
+/// Create a time lock at [`lock_address`] of `payer` and `nonce`, the next
+/// nonce of `payer`, see [`LockNonceAccount`](crate::state::LockNonceAccount).
+/// Only `payer` signs, it becomes the lock authority.
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_nonced_time_lock(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    nonce: u64,
+    timestamp: i64,
+    secret: [u8; SECRET_LENGTH],
+    bounty: u64,
+    require_memo: bool,
+    top_up_authority_only: bool,
+) -> Instruction {
+    let data = TimeLockInstruction::InitializeNoncedTimeLock {
+        nonce,
+        timestamp,
+        secret,
+        bounty,
+        require_memo,
+        top_up_authority_only,
+    }
+    .pack();
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new(lock_address(program_id, payer, nonce).0, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(stats_address(program_id).0, false),
+            AccountMeta::new(config_address(program_id).0, false),
+            AccountMeta::new(lock_nonce_address(program_id, payer).0, false),
+        ],
+    )
+}
+
+/// Create `payroll`, funded with `amount` lamports of its `authority`,
+/// shared among `num_entries` beneficiaries vesting from `start_time` to
+/// `end_time`.
+pub fn create_payroll(
+    program_id: &Pubkey,
+    payroll: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+    start_time: i64,
+    end_time: i64,
+    num_entries: u32,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::CreatePayroll {
+            amount,
+            start_time,
+            end_time,
+            num_entries,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(*payroll, true),
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Add `entries` after those already added to `payroll`, signed by its
+/// `authority`. At most 28 fit an instruction.
+pub fn add_payroll_entries(
+    program_id: &Pubkey,
+    payroll: &Pubkey,
+    authority: &Pubkey,
+    entries: &[PayrollShare],
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::AddPayrollEntries {
+            entries: entries.to_vec(),
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(*payroll, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Pay `beneficiary`, that of the `index`th entry of `payroll`, its share
+/// vested since its last claim. Anyone may send it. `template` is the
+/// schedule template of the payroll, if it has one.
+pub fn claim_payroll(
+    program_id: &Pubkey,
+    payroll: &Pubkey,
+    beneficiary: &Pubkey,
+    index: u32,
+    template: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*payroll, false),
+        AccountMeta::new(*beneficiary, false),
+        AccountMeta::new(config_address(program_id).0, false),
+    ];
+    accounts.extend(template.map(|template| AccountMeta::new_readonly(*template, false)));
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::ClaimPayroll { index }.pack(),
+        accounts,
+    )
+}
+
+/// Create `template`, paid by `authority`, releasing `tge_bps` basis points
+/// at the start, then `periods` equal releases every `period_months` after
+/// a cliff of `cliff_months`.
+pub fn create_schedule_template(
+    program_id: &Pubkey,
+    template: &Pubkey,
+    authority: &Pubkey,
+    tge_bps: u16,
+    cliff_months: u32,
+    period_months: u32,
+    periods: u32,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::CreateScheduleTemplate {
+            tge_bps,
+            cliff_months,
+            period_months,
+            periods,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(*template, true),
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create `payroll`, funded with `amount` lamports of its `authority`,
+/// shared among `num_entries` beneficiaries vesting on `template` from
+/// `start_time`.
+pub fn create_templated_payroll(
+    program_id: &Pubkey,
+    payroll: &Pubkey,
+    authority: &Pubkey,
+    template: &Pubkey,
+    amount: u64,
+    start_time: i64,
+    num_entries: u32,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::CreateTemplatedPayroll {
+            amount,
+            start_time,
+            num_entries,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(*payroll, true),
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(*template, false),
+        ],
+    )
+}
+
+/// Drop the callback of the matured `lock`, a call that keeps failing would
+/// otherwise keep its secret locked, `authority` getting the callback rent
+pub fn clear_callback(program_id: &Pubkey, lock: &Pubkey, authority: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::ClearCallback.pack(),
+        vec![
+            AccountMeta::new(*lock, false),
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(callback_address(program_id, lock).0, false),
+        ],
+    )
+}
+
+/// Close `payroll` once every entry claimed its whole share, `authority`
+/// getting the rent and the rounding leftovers
+pub fn close_payroll(program_id: &Pubkey, payroll: &Pubkey, authority: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::ClosePayroll.pack(),
+        vec![
+            AccountMeta::new(*payroll, false),
+            AccountMeta::new(*authority, true),
+        ],
+    )
+}
+
+/// Migrate `account`, created before its layout grew, to the current layout,
+/// `payer` topping up the rent of the added bytes
+pub fn migrate_account(program_id: &Pubkey, account: &Pubkey, payer: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::MigrateAccount.pack(),
+        vec![
+            AccountMeta::new(*account, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Release the installments deposited in `lock` to `recipient` once it
+/// reveals its secret, signed by its `authority`.
+pub fn set_grant_recipient(
+    program_id: &Pubkey,
+    lock: &Pubkey,
+    authority: &Pubkey,
+    recipient: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::SetGrantRecipient {
+            recipient: *recipient,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(*lock, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Pay the installments deposited in the revealed `lock` to its grant
+/// `recipient`. Anyone may send it.
+pub fn release_grant(program_id: &Pubkey, lock: &Pubkey, recipient: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::ReleaseGrant.pack(),
+        vec![
+            AccountMeta::new(*lock, false),
+            AccountMeta::new(*recipient, false),
+            AccountMeta::new(stats_address(program_id).0, false),
+        ],
+    )
+}
+
+/// Escrow the stake account `stake` until `timestamp`, `owner` being its
+/// current withdraw authority.
+pub fn lock_stake(
+    program_id: &Pubkey,
+    stake: &Pubkey,
+    owner: &Pubkey,
+    timestamp: i64,
+    beneficiary: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::LockStake {
+            timestamp,
+            beneficiary: *beneficiary,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(stake_lock_address(program_id, stake).0, false),
+            AccountMeta::new(*stake, false),
+            AccountMeta::new(*owner, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(stake::program::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// [`lock_stake`] succeeding without effect if the same stake lock exists,
+/// for jobs that may be re-run.
+pub fn lock_stake_idempotent(
+    program_id: &Pubkey,
+    stake: &Pubkey,
+    owner: &Pubkey,
+    timestamp: i64,
+    beneficiary: &Pubkey,
+) -> Instruction {
+    let mut ix = lock_stake(program_id, stake, owner, timestamp, beneficiary);
+    ix.data = TimeLockInstruction::LockStakeIdempotent {
+        timestamp,
+        beneficiary: *beneficiary,
+    }
+    .pack();
+    ix
+}
+
+/// Release the matured stake lock of `stake` to its beneficiary, refunding
+/// the stake lock rent to `owner`.
+pub fn release_stake(program_id: &Pubkey, stake: &Pubkey, owner: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &TimeLockInstruction::ReleaseStake.pack(),
+        vec![
+            AccountMeta::new(stake_lock_address(program_id, stake).0, false),
+            AccountMeta::new(*stake, false),
+            AccountMeta::new(*owner, false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(stake::program::id(), false),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merkle::MAX_PROOF_LEN;
+    use crate::state::{MAX_CALLBACK_DATA, MAX_CONDITION_BYTES};
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_pack_unpack() {
+        let ix = TimeLockInstruction::InitializeTimeLock {
+            timestamp: 42,
+            secret: [b'a'; SECRET_LENGTH],
+            bounty: 5000,
+            require_memo: true,
+            top_up_authority_only: true,
+        };
+        let data = ix.pack();
+        assert_eq!(data.len(), 8 + 8 + SECRET_LENGTH + 8 + 2);
+        assert!(matches!(
+            TimeLockInstruction::unpack(&data).unwrap(),
+            TimeLockInstruction::InitializeTimeLock {
+                timestamp: 42,
+                bounty: 5000,
+                require_memo: true,
+                top_up_authority_only: true,
+                ..
+            }
+        ));
+        // the legacy tag without the flags decodes to the same lock
+        let mut legacy = vec![0];
+        legacy.extend_from_slice(&data[8..data.len() - 2]);
+        assert!(matches!(
+            TimeLockInstruction::unpack(&legacy).unwrap(),
+            TimeLockInstruction::InitializeTimeLock {
+                timestamp: 42,
+                require_memo: false,
+                top_up_authority_only: false,
+                ..
+            }
+        ));
+        // nor did the first ones have a bounty
+        legacy.truncate(legacy.len() - 8);
+        assert!(matches!(
+            TimeLockInstruction::unpack(&legacy).unwrap(),
+            TimeLockInstruction::InitializeTimeLock {
+                timestamp: 42,
+                bounty: 0,
+                require_memo: false,
+                top_up_authority_only: false,
+                ..
+            }
+        ));
+        assert!(matches!(
+            TimeLockInstruction::unpack(&TimeLockInstruction::TryUnlock.pack()).unwrap(),
+            TimeLockInstruction::TryUnlock
+        ));
+        assert!(matches!(
+            TimeLockInstruction::unpack(&[1]).unwrap(),
+            TimeLockInstruction::TryUnlock
+        ));
+        assert!(matches!(
+            TimeLockInstruction::unpack(&TimeLockInstruction::GetPosition.pack()).unwrap(),
+            TimeLockInstruction::GetPosition
+        ));
+        let beneficiary = Pubkey::new_unique();
+        let data = TimeLockInstruction::LockStake {
+            timestamp: 42,
+            beneficiary,
+        }
+        .pack();
+        assert!(matches!(
+            TimeLockInstruction::unpack(&data).unwrap(),
+            TimeLockInstruction::LockStake { timestamp: 42, beneficiary: b } if b == beneficiary
+        ));
+        assert!(TimeLockInstruction::unpack(&data[..data.len() - 1]).is_err());
+        assert_eq!(
+            TimeLockInstruction::unpack(&TimeLockInstruction::TopUp { amount: 7 }.pack()).unwrap(),
+            TimeLockInstruction::TopUp { amount: 7 }
+        );
+        let escrow = TimeLockInstruction::CreateEscrow {
+            amount: 7,
+            release_time: 42,
+            refund_time: 43,
+            penalty_bps: 500,
+            penalty_decays: true,
+        };
+        let data = escrow.pack();
+        assert_eq!(TimeLockInstruction::unpack(&data).unwrap(), escrow);
+        assert!(TimeLockInstruction::unpack(&data[..data.len() - 1]).is_err());
+    }
+
+    fn instruction() -> impl Strategy<Value = TimeLockInstruction> {
+        prop_oneof![
+            (
+                any::<i64>(),
+                // the secret must be utf8
+                vec(0u8..0x80, SECRET_LENGTH),
+                any::<u64>(),
+                any::<bool>(),
+                any::<bool>(),
+            )
+                .prop_map(
+                    |(timestamp, secret, bounty, require_memo, top_up_authority_only)| {
+                        TimeLockInstruction::InitializeTimeLock {
+                            timestamp,
+                            secret: secret.try_into().unwrap(),
+                            bounty,
+                            require_memo,
+                            top_up_authority_only,
+                        }
+                    }
+                ),
+            Just(TimeLockInstruction::TryUnlock),
+            Just(TimeLockInstruction::GetPosition),
+            (any::<i64>(), any::<[u8; 32]>()).prop_map(|(timestamp, beneficiary)| {
+                TimeLockInstruction::LockStake {
+                    timestamp,
+                    beneficiary: Pubkey::new_from_array(beneficiary),
+                }
+            }),
+            Just(TimeLockInstruction::ReleaseStake),
+            any::<u64>().prop_map(|amount| TimeLockInstruction::TopUp { amount }),
+            Just(TimeLockInstruction::WithdrawExcess),
+            (
+                any::<u64>(),
+                any::<i64>(),
+                any::<i64>(),
+                any::<u16>(),
+                any::<bool>()
+            )
+                .prop_map(
+                    |(amount, release_time, refund_time, penalty_bps, penalty_decays)| {
+                        TimeLockInstruction::CreateEscrow {
+                            amount,
+                            release_time,
+                            refund_time,
+                            penalty_bps,
+                            penalty_decays,
+                        }
+                    }
+                ),
+            Just(TimeLockInstruction::ClaimEscrow),
+            Just(TimeLockInstruction::RefundEscrow),
+            any::<[u8; 32]>().prop_map(|beneficiary| {
+                TimeLockInstruction::ChangeEscrowBeneficiary {
+                    beneficiary: Pubkey::new_from_array(beneficiary),
+                }
+            }),
+            any::<[u8; 32]>().prop_map(|delegate| TimeLockInstruction::SetEscrowDelegate {
+                delegate: Pubkey::new_from_array(delegate),
+            }),
+            Just(TimeLockInstruction::RevokeEscrowDelegate),
+            any::<[u8; 32]>().prop_map(|unlocker| TimeLockInstruction::AddUnlocker {
+                unlocker: Pubkey::new_from_array(unlocker),
+            }),
+            any::<[u8; 32]>().prop_map(|unlocker| TimeLockInstruction::RemoveUnlocker {
+                unlocker: Pubkey::new_from_array(unlocker),
+            }),
+            Just(TimeLockInstruction::ClaimEscrowEarly),
+            (any::<i64>(), any::<[u8; 32]>()).prop_map(|(expires_at, recipient)| {
+                TimeLockInstruction::SetExpiry {
+                    expires_at,
+                    recipient: Pubkey::new_from_array(recipient),
+                }
+            }),
+            Just(TimeLockInstruction::Expire),
+            any::<i64>()
+                .prop_map(|interval| TimeLockInstruction::SetHeartbeat { interval: interval }),
+            Just(TimeLockInstruction::Heartbeat),
+            (any::<u8>(), any::<u32>(), any::<u32>()).prop_map(|(unit, every, window)| {
+                TimeLockInstruction::SetRecurrence {
+                    unit,
+                    every,
+                    window,
+                }
+            }),
+            vec(any::<u8>(), 0..=MAX_CALLBACK_DATA)
+                .prop_map(|data| TimeLockInstruction::SetCallback { data }),
+            vec(any::<u8>(), 0..=MAX_CALLBACK_DATA)
+                .prop_map(|data| TimeLockInstruction::SetUnlockInstruction { data }),
+            (
+                any::<[u8; 32]>(),
+                any::<u32>(),
+                vec(any::<u8>(), 0..=MAX_CONDITION_BYTES)
+            )
+                .prop_map(|(account, offset, expected)| {
+                    TimeLockInstruction::SetCondition {
+                        account: Pubkey::new_from_array(account),
+                        offset,
+                        expected,
+                    }
+                }),
+            any::<[u8; 32]>().prop_map(|predecessor| TimeLockInstruction::SetPredecessor {
+                predecessor: Pubkey::new_from_array(predecessor),
+            }),
+            (any::<[u8; 32]>(), any::<u32>(), any::<u64>()).prop_map(
+                |(root, num_leaves, amount)| {
+                    TimeLockInstruction::CreateDistributor {
+                        root,
+                        num_leaves,
+                        amount,
+                    }
+                }
+            ),
+            (
+                any::<u32>(),
+                any::<u64>(),
+                any::<i64>(),
+                vec(any::<[u8; 32]>(), 0..=MAX_PROOF_LEN)
+            )
+                .prop_map(|(index, amount, unlock_time, proof)| {
+                    TimeLockInstruction::ClaimWithProof {
+                        index,
+                        amount,
+                        unlock_time,
+                        proof,
+                    }
+                }),
+            any::<u64>().prop_map(|amount| TimeLockInstruction::CreateVault { amount: amount }),
+            (any::<u64>(), any::<i64>(), any::<bool>()).prop_map(
+                |(amount, release_time, transferable)| TimeLockInstruction::CreateVaultLock {
+                    amount,
+                    release_time,
+                    transferable,
+                }
+            ),
+            Just(TimeLockInstruction::ClaimVaultLock),
+            any::<u64>()
+                .prop_map(|amount| TimeLockInstruction::WithdrawUnallocated { amount: amount }),
+            any::<u64>().prop_map(|amount| TimeLockInstruction::SplitLock { amount: amount }),
+            Just(TimeLockInstruction::MergeLocks),
+            Just(TimeLockInstruction::TransferVaultLock),
+            any::<u64>()
+                .prop_map(|price| TimeLockInstruction::TransferWithPayment { price: price }),
+            (any::<[u8; 32]>(), any::<u64>(), any::<i64>(), any::<i64>()).prop_map(
+                |(treasury, creation_fee, min_duration, max_duration)| {
+                    TimeLockInstruction::InitializeConfig {
+                        treasury: Pubkey::new_from_array(treasury),
+                        creation_fee,
+                        min_duration,
+                        max_duration,
+                    }
+                }
+            ),
+            (
+                any::<[u8; 32]>(),
+                any::<u64>(),
+                any::<i64>(),
+                any::<i64>(),
+                any::<bool>(),
+                any::<u16>(),
+                any::<i64>(),
+                any::<i64>()
+            )
+                .prop_map(
+                    |(
+                        treasury,
+                        creation_fee,
+                        min_duration,
+                        max_duration,
+                        paused,
+                        claim_fee_bps,
+                        update_delay,
+                        clock_drift_tolerance,
+                    )| {
+                        TimeLockInstruction::UpdateConfig {
+                            treasury: Pubkey::new_from_array(treasury),
+                            creation_fee,
+                            min_duration,
+                            max_duration,
+                            paused,
+                            claim_fee_bps,
+                            update_delay,
+                            clock_drift_tolerance,
+                        }
+                    }
+                ),
+            Just(TimeLockInstruction::CollectFees),
+            any::<bool>().prop_map(|paused| TimeLockInstruction::SetPaused { paused: paused }),
+            any::<u64>().prop_map(|amount| TimeLockInstruction::WithdrawFees { amount: amount }),
+            any::<[u8; 32]>().prop_map(|key| TimeLockInstruction::AddFeeExemption {
+                key: Pubkey::new_from_array(key),
+            }),
+            any::<[u8; 32]>().prop_map(|key| TimeLockInstruction::RemoveFeeExemption {
+                key: Pubkey::new_from_array(key),
+            }),
+            any::<i64>().prop_map(|expires_at| TimeLockInstruction::ProposeAdmin {
+                expires_at: expires_at,
+            }),
+            Just(TimeLockInstruction::AcceptAdmin),
+            Just(TimeLockInstruction::ApplyConfigUpdate),
+            Just(TimeLockInstruction::CancelConfigUpdate),
+            Just(TimeLockInstruction::GetRevenue),
+            Just(TimeLockInstruction::RecordImmutability),
+            Just(TimeLockInstruction::GetImmutability),
+            (any::<i64>(), any::<[u8; 32]>()).prop_map(|(timestamp, beneficiary)| {
+                TimeLockInstruction::LockStakeIdempotent {
+                    timestamp,
+                    beneficiary: Pubkey::new_from_array(beneficiary),
+                }
+            }),
+            (any::<[u8; 32]>(), any::<[u8; 20]>()).prop_map(|(build_hash, source_commit)| {
+                TimeLockInstruction::RecordVerifiedBuild {
+                    build_hash,
+                    source_commit,
+                }
+            }),
+            Just(TimeLockInstruction::EnableAuditTrail),
+            Just(TimeLockInstruction::RequireClaimCommit),
+            any::<[u8; 32]>()
+                .prop_map(|commitment| TimeLockInstruction::CommitClaim { commitment }),
+            any::<[u8; 32]>().prop_map(|salt| TimeLockInstruction::RevealClaim { salt }),
+            (
+                any::<u64>(),
+                any::<i64>(),
+                vec(0u8..0x80, SECRET_LENGTH),
+                any::<u64>(),
+                any::<bool>(),
+                any::<bool>(),
+            )
+                .prop_map(
+                    |(nonce, timestamp, secret, bounty, require_memo, top_up_authority_only)| {
+                        TimeLockInstruction::InitializeNoncedTimeLock {
+                            nonce,
+                            timestamp,
+                            secret: secret.try_into().unwrap(),
+                            bounty,
+                            require_memo,
+                            top_up_authority_only,
+                        }
+                    }
+                ),
+            (any::<u64>(), any::<i64>(), any::<i64>(), any::<u32>()).prop_map(
+                |(amount, start_time, end_time, num_entries)| TimeLockInstruction::CreatePayroll {
+                    amount,
+                    start_time,
+                    end_time,
+                    num_entries,
+                }
+            ),
+            vec(
+                (any::<[u8; 32]>(), any::<u32>()),
+                0..=MAX_INSTRUCTION_DATA / PayrollShare::LEN
+            )
+            .prop_map(|entries| TimeLockInstruction::AddPayrollEntries {
+                entries: entries
+                    .into_iter()
+                    .map(|(beneficiary, weight)| PayrollShare {
+                        beneficiary: Pubkey::new_from_array(beneficiary),
+                        weight,
+                    })
+                    .collect(),
+            }),
+            any::<u32>().prop_map(|index| TimeLockInstruction::ClaimPayroll { index }),
+            (any::<u16>(), any::<u32>(), any::<u32>(), any::<u32>()).prop_map(
+                |(tge_bps, cliff_months, period_months, periods)| {
+                    TimeLockInstruction::CreateScheduleTemplate {
+                        tge_bps,
+                        cliff_months,
+                        period_months,
+                        periods,
+                    }
+                }
+            ),
+            (any::<u64>(), any::<i64>(), any::<u32>()).prop_map(
+                |(amount, start_time, num_entries)| TimeLockInstruction::CreateTemplatedPayroll {
+                    amount,
+                    start_time,
+                    num_entries,
+                }
+            ),
+            Just(TimeLockInstruction::ClearCallback),
+            Just(TimeLockInstruction::ClosePayroll),
+            Just(TimeLockInstruction::MigrateAccount),
+            any::<[u8; 32]>().prop_map(|recipient| TimeLockInstruction::SetGrantRecipient {
+                recipient: Pubkey::new_from_array(recipient),
+            }),
+            Just(TimeLockInstruction::ReleaseGrant),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn prop_round_trip(ix in instruction()) {
+            let data = ix.pack();
+            prop_assert_eq!(TimeLockInstruction::unpack(&data)?, ix);
+        }
+
+        #[test]
+        fn prop_reject_truncated(ix in instruction(), cut in any::<usize>()) {
+            let data = ix.pack();
+            let cut = cut % data.len() + 1;
+            let truncated = TimeLockInstruction::unpack(&data[..data.len() - cut]);
+            match (ix, truncated) {
+                (_, Err(_)) => {}
+                // dropping flags leaves a lock without those restrictions
+                (
+                    TimeLockInstruction::InitializeTimeLock {
+                        timestamp, secret, bounty, require_memo, ..
+                    },
+                    Ok(decoded),
+                ) if cut <= 2 => prop_assert_eq!(
+                    decoded,
+                    TimeLockInstruction::InitializeTimeLock {
+                        timestamp,
+                        secret,
+                        bounty,
+                        require_memo: require_memo && cut == 1,
+                        top_up_authority_only: false,
+                    }
+                ),
+                // so does dropping the bounty with them, as the first layout did
+                (
+                    TimeLockInstruction::InitializeTimeLock { timestamp, secret, .. },
+                    Ok(decoded),
+                ) if cut == 10 => prop_assert_eq!(
+                    decoded,
+                    TimeLockInstruction::InitializeTimeLock {
+                        timestamp,
+                        secret,
+                        bounty: 0,
+                        require_memo: false,
+                        top_up_authority_only: false,
+                    }
+                ),
+                (ix, Ok(decoded)) => {
+                    prop_assert!(false, "{ix:?} cut by {cut} decoded to {decoded:?}")
+                }
+            }
+        }
+
+        #[test]
+        fn prop_reject_oversized(ix in instruction(), extra in vec(any::<u8>(), 1..64)) {
+            let mut data = ix.pack();
+            data.extend_from_slice(&extra);
+            prop_assert!(TimeLockInstruction::unpack(&data).is_err());
+            let mut legacy = vec![1];
+            legacy.extend_from_slice(&extra);
+            prop_assert!(TimeLockInstruction::unpack(&legacy).is_err());
+        }
+
+        #[test]
+        fn prop_discriminated_not_legacy(ix in instruction()) {
+            prop_assert_eq!(TimeLockInstruction::legacy_tag(&ix.pack()), None);
+        }
+
+        #[test]
+        fn prop_fields_are_borsh(ix in instruction()) {
+            // the derived encoding has the same fields after its one byte
+            // variant index, which the prefix of the variant replaces
+            prop_assume!(ix != TimeLockInstruction::EmitEvent);
+            let derived = borsh::to_vec(&ix).unwrap();
+            prop_assert_eq!(&ix.pack()[8..], &derived[1..]);
+            prop_assert_eq!(TimeLockInstruction::try_from_slice(&derived).unwrap(), ix);
+        }
+
+        #[test]
+        fn prop_unpack_never_panics(data in vec(any::<u8>(), 0..512)) {
+            let _ = TimeLockInstruction::unpack(&data);
+        }
+    }
+}
@@ -0,0 +1,34 @@
+//! Interface of the time lock program: account layouts, instructions, PDAs
+//! and CPI helpers, without the program itself.
+//!
+//! On-chain programs CPI into the time lock through [`cpi`], off-chain
+//! clients build instructions with [`instruction`]. The program id is not
+//! fixed, every builder takes it as an argument.
+
+pub mod builder;
+mod calendar;
+pub mod cpi;
+pub mod error;
+pub mod instruction;
+pub mod merkle;
+pub mod pda;
+#[cfg(feature = "schema")]
+pub mod schema;
+#[cfg(feature = "serde")]
+pub mod serde_array;
+pub mod state;
+pub mod timestamp;
+
+pub use builder::TimeLockBuilder;
+pub use error::TimeLockError;
+pub use instruction::TimeLockInstruction;
+pub use state::{
+    AuditEntry, AuditTrailAccount, CallbackAccount, ClaimCommitmentAccount, ConfigAccount,
+    ConfigUpdateAccount, DistributorAccount, EscrowAccount, FeeExemptionAccount, FixedSizeAccount,
+    ImmutabilityReport, LockNonceAccount, LockPosition, PayrollAccount, PayrollEntry, PayrollShare,
+    RevenueReport, ScheduleTemplateAccount, StakeLockAccount, TimeLockAccount, TimeLockStats,
+    VaultAccount, VaultLockAccount, VaultStatsAccount, MAX_AUDIT_ENTRIES, MAX_CALLBACK_ACCOUNTS,
+    MAX_CALLBACK_DATA, MAX_CONDITION_BYTES, MAX_PAYROLL_ENTRIES, MAX_UNLOCKERS, SECRET_LENGTH,
+    VAULT_SCHEDULE_WEEKS,
+};
+pub use timestamp::UnixTimestamp;
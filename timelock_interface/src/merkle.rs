@@ -0,0 +1,127 @@
+//! Merkle trees of timelocked claims, see `CreateDistributor`.
+//!
+//! Leaves and nodes are sha256 hashes with distinct prefixes, so a node can
+//! never pass for a leaf. The children of a node are sorted before hashing,
+//! proofs are then plain lists of siblings without directions.
+
+use solana_program::hash::hashv;
+use solana_program::pubkey::Pubkey;
+
+/// Deepest proof `ClaimWithProof` accepts, enough for 2^16 leaves
+pub const MAX_PROOF_LEN: usize = 16;
+
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+/// Hash of the claim of `amount` lamports by `recipient` from `unlock_time`,
+/// the `index`th leaf of its tree
+pub fn leaf(index: u32, recipient: &Pubkey, amount: u64, unlock_time: i64) -> [u8; 32] {
+    hashv(&[
+        LEAF_PREFIX,
+        &index.to_le_bytes(),
+        recipient.as_ref(),
+        &amount.to_le_bytes(),
+        &unlock_time.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+fn node(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[NODE_PREFIX, left, right]).to_bytes()
+}
+
+/// Whether `proof` links `leaf` to `root`
+pub fn verify(proof: &[[u8; 32]], root: &[u8; 32], leaf: &[u8; 32]) -> bool {
+    proof
+        .iter()
+        .fold(*leaf, |hash, sibling| node(&hash, sibling))
+        == *root
+}
+
+/// The levels of the tree over `leaves`, from the leaves up to the root. A
+/// node without a sibling moves up as is.
+fn levels(leaves: &[[u8; 32]]) -> Vec<Vec<[u8; 32]>> {
+    let mut levels = vec![leaves.to_vec()];
+    while levels.last().is_some_and(|level| level.len() > 1) {
+        let next = levels
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => node(a, b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+/// Root of the tree over `leaves`, zeroed when there are none
+pub fn root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    levels(leaves)
+        .last()
+        .and_then(|level| level.first().copied())
+        .unwrap_or_default()
+}
+
+/// Proof of the `index`th of `leaves`, `None` if out of range
+pub fn proof(leaves: &[[u8; 32]], index: usize) -> Option<Vec<[u8; 32]>> {
+    if index >= leaves.len() {
+        return None;
+    }
+    let mut index = index;
+    let mut proof = vec![];
+    for level in levels(leaves).iter().take_while(|level| level.len() > 1) {
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        index /= 2;
+    }
+    Some(proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(n: u32) -> Vec<[u8; 32]> {
+        (0..n)
+            .map(|i| {
+                leaf(
+                    i,
+                    &Pubkey::new_from_array([i as u8; 32]),
+                    1000,
+                    1_700_000_000,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_proofs() {
+        for n in 1..=9 {
+            let leaves = leaves(n);
+            let root = root(&leaves);
+            for (i, leaf) in leaves.iter().enumerate() {
+                let proof = proof(&leaves, i).unwrap();
+                assert!(verify(&proof, &root, leaf), "leaf {i} of {n}");
+                // another leaf does not pass with the same proof
+                let other = leaves[(i + 1) % leaves.len()];
+                assert!(n == 1 || !verify(&proof, &root, &other), "leaf {i} of {n}");
+            }
+            assert_eq!(proof(&leaves, n as usize), None);
+        }
+    }
+
+    #[test]
+    fn test_leaf_fields() {
+        let recipient = Pubkey::new_from_array([7; 32]);
+        let leaf = leaf(0, &recipient, 1000, 1_700_000_000);
+        assert_ne!(leaf, super::leaf(1, &recipient, 1000, 1_700_000_000));
+        assert_ne!(leaf, super::leaf(0, &recipient, 1001, 1_700_000_000));
+        assert_ne!(leaf, super::leaf(0, &recipient, 1000, 1_700_000_001));
+    }
+}
@@ -0,0 +1,113 @@
+use solana_program::pubkey::Pubkey;
+
+/// Seed of the PDA holding the global statistics
+pub const STATS_SEED: &[u8] = b"stats";
+
+/// Seed prefix of stake lock PDAs, followed by the stake account address
+pub const STAKE_LOCK_SEED: &[u8] = b"stake_lock";
+
+/// Seed prefix of callback PDAs, followed by the time lock address
+pub const CALLBACK_SEED: &[u8] = b"callback";
+
+/// Seed of the PDA holding the program config
+pub const CONFIG_SEED: &[u8] = b"config";
+
+/// Seed of the PDA holding the queued update of the program config
+pub const CONFIG_UPDATE_SEED: &[u8] = b"config_update";
+
+/// Seed prefix of fee exemption PDAs, followed by the exempted key
+pub const FEE_EXEMPTION_SEED: &[u8] = b"fee_exemption";
+
+/// Seed prefix of audit trail PDAs, followed by the time lock address
+pub const AUDIT_SEED: &[u8] = b"audit";
+
+/// Seed prefix of claim commitment PDAs, followed by the time lock and the
+/// committer addresses
+pub const CLAIM_COMMITMENT_SEED: &[u8] = b"claim_commitment";
+
+/// Seed prefix of nonced time lock PDAs, followed by the payer address and
+/// the little endian nonce
+pub const LOCK_SEED: &[u8] = b"lock";
+
+/// Seed prefix of lock nonce PDAs, followed by the payer address
+pub const LOCK_NONCE_SEED: &[u8] = b"lock_nonce";
+
+/// Seed prefix of vault stats PDAs, followed by the vault address
+pub const VAULT_STATS_SEED: &[u8] = b"vault_stats";
+
+/// Seed of the PDA signing self-CPI events
+pub const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+/// Address and bump of the global statistics PDA
+pub fn stats_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STATS_SEED], program_id)
+}
+
+/// Address and bump of the program config PDA
+pub fn config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], program_id)
+}
+
+/// Address and bump of the PDA holding the queued update of the program config
+pub fn config_update_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_UPDATE_SEED], program_id)
+}
+
+/// Address and bump of the PDA signing self-CPI events
+pub fn event_authority(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EVENT_AUTHORITY_SEED], program_id)
+}
+
+/// Address and bump of the stake lock PDA escrowing `stake`, which is also
+/// its withdraw authority while locked
+pub fn stake_lock_address(program_id: &Pubkey, stake: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_LOCK_SEED, stake.as_ref()], program_id)
+}
+
+/// Address and bump of the PDA holding the callback of `lock`, which signs
+/// the call
+pub fn callback_address(program_id: &Pubkey, lock: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CALLBACK_SEED, lock.as_ref()], program_id)
+}
+
+/// Address and bump of the fee exemption PDA of `key`
+pub fn fee_exemption_address(program_id: &Pubkey, key: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FEE_EXEMPTION_SEED, key.as_ref()], program_id)
+}
+
+/// Address and bump of the PDA holding the audit trail of `lock`
+pub fn audit_address(program_id: &Pubkey, lock: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AUDIT_SEED, lock.as_ref()], program_id)
+}
+
+/// Address and bump of the PDA holding the claim of the bounty of `lock`
+/// committed by `committer`
+pub fn claim_commitment_address(
+    program_id: &Pubkey,
+    lock: &Pubkey,
+    committer: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[CLAIM_COMMITMENT_SEED, lock.as_ref(), committer.as_ref()],
+        program_id,
+    )
+}
+
+/// Address and bump of the time lock PDA `payer` creates with `nonce`, its
+/// locks enumerable from 0 up to the next nonce of its nonce PDA
+pub fn lock_address(program_id: &Pubkey, payer: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[LOCK_SEED, payer.as_ref(), &nonce.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// Address and bump of the PDA holding the next lock nonce of `payer`
+pub fn lock_nonce_address(program_id: &Pubkey, payer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LOCK_NONCE_SEED, payer.as_ref()], program_id)
+}
+
+/// Address and bump of the PDA aggregating the vault locks of `vault`
+pub fn vault_stats_address(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_STATS_SEED, vault.as_ref()], program_id)
+}
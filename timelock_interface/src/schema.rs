@@ -0,0 +1,81 @@
+//! Borsh schemas of the accounts and instructions, for client generators in
+//! other languages, dumped to JSON by the `timelock-schema` binary.
+//!
+//! The schema of [`TimeLockInstruction`] describes the legacy layout with a
+//! one byte tag, the instruction data replaces the tag with the prefix of the
+//! variant, see [`instruction_prefix`].
+
+use borsh::schema::BorshSchemaContainer;
+use borsh::schema_container_of;
+use solana_program::hash::hash;
+
+use crate::instruction::{TimeLockInstruction, EMIT_EVENT_TAG};
+use crate::state::{
+    AuditTrailAccount, CallbackAccount, ClaimCommitmentAccount, ConfigAccount, ConfigUpdateAccount,
+    DistributorAccount, EscrowAccount, FeeExemptionAccount, ImmutabilityReport, LockNonceAccount,
+    LockPosition, PayrollAccount, RevenueReport, ScheduleTemplateAccount, StakeLockAccount,
+    TimeLockAccount, TimeLockStats, VaultAccount, VaultLockAccount, VaultStatsAccount,
+};
+
+/// Schemas of the instruction, the accounts and the view return data
+pub fn containers() -> Vec<BorshSchemaContainer> {
+    vec![
+        schema_container_of::<TimeLockInstruction>(),
+        schema_container_of::<TimeLockAccount>(),
+        schema_container_of::<CallbackAccount>(),
+        schema_container_of::<TimeLockStats>(),
+        schema_container_of::<StakeLockAccount>(),
+        schema_container_of::<EscrowAccount>(),
+        schema_container_of::<DistributorAccount>(),
+        schema_container_of::<VaultAccount>(),
+        schema_container_of::<VaultLockAccount>(),
+        schema_container_of::<ConfigAccount>(),
+        schema_container_of::<ConfigUpdateAccount>(),
+        schema_container_of::<FeeExemptionAccount>(),
+        schema_container_of::<AuditTrailAccount>(),
+        schema_container_of::<ClaimCommitmentAccount>(),
+        schema_container_of::<LockNonceAccount>(),
+        schema_container_of::<VaultStatsAccount>(),
+        schema_container_of::<PayrollAccount>(),
+        schema_container_of::<ScheduleTemplateAccount>(),
+        schema_container_of::<LockPosition>(),
+        schema_container_of::<RevenueReport>(),
+        schema_container_of::<ImmutabilityReport>(),
+    ]
+}
+
+/// Bytes the data of the instruction variant named `variant` in the schema
+/// starts with: [`EMIT_EVENT_TAG`] for `EmitEvent`, else its 8-byte
+/// discriminator, the first bytes of `sha256("timelock:<snake_case_name>")`
+pub fn instruction_prefix(variant: &str) -> Vec<u8> {
+    if variant == "EmitEvent" {
+        return vec![EMIT_EVENT_TAG];
+    }
+    let mut name = String::with_capacity(variant.len() + 4);
+    for (i, c) in variant.chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            name.push('_');
+        }
+        name.push(c.to_ascii_lowercase());
+    }
+    let hash = hash(format!("timelock:{name}").as_bytes());
+    hash.to_bytes()[..8].to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_matches_pack() {
+        for ix in [
+            TimeLockInstruction::TryUnlock,
+            TimeLockInstruction::EmitEvent,
+            TimeLockInstruction::GetPosition,
+            TimeLockInstruction::ApplyConfigUpdate,
+            TimeLockInstruction::GetImmutability,
+        ] {
+            assert_eq!(instruction_prefix(&format!("{ix:?}")), ix.pack());
+        }
+    }
+}
@@ -0,0 +1,21 @@
+//! Serde of arrays longer than serde supports, as sequences, see
+//! `#[serde(with = "serde_array")]` on the secrets.
+
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+pub fn serialize<S: Serializer, T: Serialize, const N: usize>(
+    array: &[T; N],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    array.as_slice().serialize(serializer)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>, T: Deserialize<'de>, const N: usize>(
+    deserializer: D,
+) -> Result<[T; N], D::Error> {
+    let items = Vec::<T>::deserialize(deserializer)?;
+    items
+        .try_into()
+        .map_err(|items: Vec<T>| D::Error::invalid_length(items.len(), &"an array of N items"))
+}
@@ -0,0 +1,1869 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use shank::ShankAccount;
+use solana_program::hash::hashv;
+use solana_program::instruction::AccountMeta;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+
+use crate::calendar;
+
+/// Length in bytes of the encrypted encoded secret stored in a time lock
+pub const SECRET_LENGTH: usize = 256;
+
+/// Number of keys a time lock can restrict `TryUnlock` to
+pub const MAX_UNLOCKERS: usize = 4;
+
+/// Number of accounts a lock callback can pass besides its PDA
+pub const MAX_CALLBACK_ACCOUNTS: usize = 4;
+
+/// Length in bytes of the instruction data a lock callback can pass before the secret
+pub const MAX_CALLBACK_DATA: usize = 64;
+
+/// Length in bytes of the account data an unlock condition can expect
+pub const MAX_CONDITION_BYTES: usize = 32;
+
+// Define struct representing our time lock account's data
+//
+// The layout is stable: fields are only ever appended, so the offsets below
+// stay valid for programs reading the account directly.
+//   0 timestamp, 8 secret, 264 bounty, 272 attempts, 276 premature_attempts,
+//   280 last_attempt, 288 revealed_at, 296 require_memo, 297 claim_memo_hash,
+//   329 authority, 361 top_up_authority_only, 362 unlockers, 490 expires_at,
+//   498 expiry_recipient, 530 heartbeat_interval, 538 recurrence_unit,
+//   539 recurrence_every, 543 recurrence_window, 547 has_callback,
+//   548 condition_account, 580 condition_offset, 584 condition_len,
+//   585 condition_bytes, 617 predecessor, 649 has_audit_trail,
+//   650 claim_commit_required, 651 in_progress, 652 funded,
+//   660 grant_recipient
+#[derive(BorshSerialize, BorshDeserialize, ShankAccount, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(borsh::BorshSchema))]
+pub struct TimeLockAccount {
+    pub timestamp: i64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_array"))]
+    pub secret: [u8; SECRET_LENGTH],
+    /// lamports paid to whoever unlocks the time lock after maturity
+    pub bounty: u64,
+    /// number of `TryUnlock` calls, premature ones included
+    pub attempts: u32,
+    /// number of `TryUnlock` calls made before maturity
+    pub premature_attempts: u32,
+    /// unix timestamp of the last `TryUnlock` call, 0 if never called
+    pub last_attempt: i64,
+    /// unix timestamp of the first successful `TryUnlock`, 0 until revealed
+    pub revealed_at: i64,
+    /// whether the transaction claiming the bounty must carry an SPL memo
+    pub require_memo: bool,
+    /// sha256 of the memo of the claiming transaction, zeroed until claimed with one
+    pub claim_memo_hash: [u8; 32],
+    /// payer of the lock, allowed to top it up and to manage it
+    pub authority: Pubkey,
+    /// whether only `authority` may top up the bounty
+    pub top_up_authority_only: bool,
+    /// keys allowed to call `TryUnlock`, unused slots hold the default pubkey;
+    /// anyone may when every slot is unused
+    pub unlockers: [Pubkey; MAX_UNLOCKERS],
+    /// unix timestamp after which anyone may `Expire` the lock, 0 if it never expires
+    pub expires_at: i64,
+    /// receives the lamports of the lock when it expires
+    pub expiry_recipient: Pubkey,
+    /// seconds each `Heartbeat` pushes maturity past its call, 0 unless in dead man's switch mode
+    pub heartbeat_interval: i64,
+    /// unit of the recurrence of the unlock windows, see [`TimeLockAccount::RECUR_NONE`]
+    pub recurrence_unit: u8,
+    /// length of the recurrence period, in `recurrence_unit`
+    pub recurrence_every: u32,
+    /// seconds the lock stays open from the start of each period
+    pub recurrence_window: u32,
+    /// whether revealing the secret calls the program of the [`CallbackAccount`] of the lock
+    pub has_callback: bool,
+    /// account whose data must hold `condition_bytes` at `condition_offset` for the
+    /// lock to reveal, the default pubkey when the lock is unconditional
+    pub condition_account: Pubkey,
+    /// offset in the data of `condition_account` of the expected bytes
+    pub condition_offset: u32,
+    /// length of the used prefix of `condition_bytes`
+    pub condition_len: u8,
+    /// bytes expected in the data of `condition_account`
+    pub condition_bytes: [u8; MAX_CONDITION_BYTES],
+    /// lock that must have revealed its secret before this one may, the default pubkey when none
+    pub predecessor: Pubkey,
+    /// Whether every mutation is appended to the audit trail of the lock, see `EnableAuditTrail`
+    pub has_audit_trail: bool,
+    /// Whether the bounty is only paid to a claim committed in an earlier slot, see `RequireClaimCommit`
+    pub claim_commit_required: bool,
+    /// Whether the lock is calling its callback, no instruction may mutate it meanwhile
+    pub in_progress: bool,
+    /// lamports deposited by `TopUp`, released to `grant_recipient` once revealed, see `ReleaseGrant`
+    pub funded: u64,
+    /// receives the `funded` lamports, the authority when the default pubkey
+    pub grant_recipient: Pubkey,
+}
+
+impl TimeLockAccount {
+    /// Size of the serialized account: i64 timestamp + SECRET_LENGTH byte secret + u64 bounty
+    /// + u32 attempts + u32 premature attempts + i64 last attempt + i64 reveal time
+    /// + bool memo requirement + 32 byte memo hash + authority pubkey + bool top up restriction
+    /// + MAX_UNLOCKERS unlocker pubkeys
+    /// + i64 expiry + expiry recipient pubkey
+    /// + i64 heartbeat interval
+    /// + u8 recurrence unit + u32 period + u32 window
+    /// + bool callback flag
+    /// + condition account pubkey + u32 offset + u8 length + MAX_CONDITION_BYTES expected bytes
+    /// + predecessor pubkey
+    /// + bool audit trail flag
+    /// + bool claim commit flag
+    /// + bool in progress flag
+    /// + u64 funded total + grant recipient pubkey
+    pub const LEN: usize = 8
+        + SECRET_LENGTH
+        + 8
+        + 4
+        + 4
+        + 8
+        + 8
+        + 1
+        + 32
+        + 32
+        + 1
+        + 32 * MAX_UNLOCKERS
+        + 8
+        + 32
+        + 8
+        + 1
+        + 4
+        + 4
+        + 1
+        + 32
+        + 4
+        + 1
+        + MAX_CONDITION_BYTES
+        + 32
+        + 1
+        + 1
+        + 1
+        + 8
+        + 32;
+
+    /// Offset of `revealed_at` in the account data, for `getProgramAccounts` filters
+    pub const REVEALED_AT_OFFSET: usize = 288;
+
+    /// Offset of `authority` in the account data, for `getProgramAccounts` filters
+    pub const AUTHORITY_OFFSET: usize = 329;
+
+    /// Offset of `in_progress` in the account data, for `getProgramAccounts` filters
+    pub const IN_PROGRESS_OFFSET: usize = 651;
+
+    /// Decode account data of exactly [`TimeLockAccount::LEN`] bytes.
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        Self::try_from_slice(data).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    /// Encode the account, [`TimeLockAccount::LEN`] bytes.
+    pub fn pack(&self) -> Vec<u8> {
+        borsh::to_vec(self).expect("serializing to a vec never fails")
+    }
+
+    /// The lock opens once, at its timestamp, and stays open
+    pub const RECUR_NONE: u8 = 0;
+    /// The lock opens every `recurrence_every` seconds from its timestamp
+    pub const RECUR_SECONDS: u8 = 1;
+    /// The lock opens every `recurrence_every` calendar months from its
+    /// timestamp, on the same day or the last of shorter months
+    pub const RECUR_MONTHS: u8 = 2;
+
+    /// Whether `TryUnlock` reveals the secret at `now`: from the timestamp on,
+    /// and for a recurring lock only within a window.
+    pub fn is_open(&self, now: i64) -> bool {
+        if now < self.timestamp {
+            return false;
+        }
+        if self.recurrence_every == 0 {
+            return true;
+        }
+        let elapsed = now as i128 - self.timestamp as i128;
+        let since_period = match self.recurrence_unit {
+            Self::RECUR_SECONDS => elapsed % self.recurrence_every as i128,
+            Self::RECUR_MONTHS => {
+                now as i128
+                    - calendar::period_start(self.timestamp, self.recurrence_every, now) as i128
+            }
+            _ => return true,
+        };
+        since_period < self.recurrence_window as i128
+    }
+
+    /// Whether revealing waits on [`TimeLockAccount::predecessor`].
+    pub fn has_predecessor(&self) -> bool {
+        self.predecessor != Pubkey::default()
+    }
+
+    /// Whether revealing waits on [`TimeLockAccount::condition_account`].
+    pub fn has_condition(&self) -> bool {
+        self.condition_account != Pubkey::default()
+    }
+
+    /// Whether `data`, of the condition account, holds the expected bytes at
+    /// the condition offset. Always true for an unconditional lock.
+    pub fn condition_met(&self, data: &[u8]) -> bool {
+        if !self.has_condition() {
+            return true;
+        }
+        let expected =
+            &self.condition_bytes[..(self.condition_len as usize).min(MAX_CONDITION_BYTES)];
+        let start = self.condition_offset as usize;
+        data.get(start..start + expected.len()) == Some(expected)
+    }
+
+    /// Whether `TryUnlock` is restricted to [`TimeLockAccount::unlockers`].
+    pub fn has_unlockers(&self) -> bool {
+        self.unlockers.iter().any(|k| *k != Pubkey::default())
+    }
+
+    /// Whether `key` may call `TryUnlock` on the lock.
+    pub fn allows_unlocker(&self, key: &Pubkey) -> bool {
+        !self.has_unlockers() || (*key != Pubkey::default() && self.unlockers.contains(key))
+    }
+
+    /// Key the `funded` lamports are released to, the authority unless
+    /// [`TimeLockAccount::grant_recipient`] is set.
+    pub fn grant_payee(&self) -> Pubkey {
+        match self.grant_recipient {
+            recipient if recipient == Pubkey::default() => self.authority,
+            recipient => recipient,
+        }
+    }
+}
+
+/// Call a time lock makes into another program when it first reveals its
+/// secret, held by the PDA at
+/// [`callback_address`](crate::pda::callback_address), which signs the call as
+/// its first account. The secret is appended to the instruction data, unless
+/// the call is `verbatim`.
+#[derive(BorshSerialize, BorshDeserialize, ShankAccount, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(borsh::BorshSchema))]
+pub struct CallbackAccount {
+    /// Time lock making the call
+    pub lock: Pubkey,
+    /// Program called
+    pub program: Pubkey,
+    /// Accounts of the call after the callback PDA, unused slots hold the
+    /// default pubkey
+    pub accounts: [Pubkey; MAX_CALLBACK_ACCOUNTS],
+    /// Bit `i` is set when `accounts[i]` is writable
+    pub writable: u8,
+    /// Length of the used prefix of `data`
+    pub data_len: u8,
+    /// Instruction data preceding the secret
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_array"))]
+    pub data: [u8; MAX_CALLBACK_DATA],
+    /// Whether the call is executed as stored: the PDA signs wherever
+    /// `accounts` lists it, not as the first account, and no secret is appended
+    pub verbatim: bool,
+    /// Canonical bump of the PDA, stored at creation to rederive the address
+    pub bump: u8,
+}
+
+impl CallbackAccount {
+    /// Size of the serialized account: two pubkeys + MAX_CALLBACK_ACCOUNTS
+    /// pubkeys + u8 writable bits + u8 data length + MAX_CALLBACK_DATA bytes
+    /// + bool verbatim flag + u8 bump
+    pub const LEN: usize = 32 * 2 + 32 * MAX_CALLBACK_ACCOUNTS + 1 + 1 + MAX_CALLBACK_DATA + 1 + 1;
+
+    /// Accounts of the call after the callback PDA, in order, or all of them
+    /// when `verbatim`.
+    pub fn account_metas(&self) -> Vec<AccountMeta> {
+        self.accounts
+            .iter()
+            .enumerate()
+            .filter(|(_, key)| **key != Pubkey::default())
+            .map(|(i, key)| match self.writable & (1 << i) != 0 {
+                true => AccountMeta::new(*key, false),
+                false => AccountMeta::new_readonly(*key, false),
+            })
+            .collect()
+    }
+
+    /// Instruction data, preceding the secret unless `verbatim`.
+    pub fn data(&self) -> &[u8] {
+        &self.data[..(self.data_len as usize).min(MAX_CALLBACK_DATA)]
+    }
+}
+
+/// Aggregates over every time lock, kept up to date by the instructions
+/// changing them so explorers don't have to scan every account.
+///
+/// Locks created before the stats account existed are not counted.
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Debug, Default, Clone, Copy, PartialEq, Eq,
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(borsh::BorshSchema))]
+pub struct TimeLockStats {
+    /// Time lock accounts currently open
+    pub active_locks: u64,
+    /// Time locks ever created
+    pub total_locks: u64,
+    /// Lamports held by open time lock accounts, rent and bounties included
+    pub locked_lamports: u64,
+    /// Canonical bump of the PDA, stored at creation to rederive the address
+    pub bump: u8,
+}
+
+impl TimeLockStats {
+    /// Size of the serialized account: three u64 + u8 bump
+    pub const LEN: usize = 8 * 3 + 1;
+}
+
+/// Escrow of a native stake account, see `LockStake`.
+///
+/// The PDA is the withdraw authority of `stake` until `timestamp`; the funds,
+/// rent-exempt reserve and delegation never leave the stake account.
+#[derive(BorshSerialize, BorshDeserialize, ShankAccount, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(borsh::BorshSchema))]
+pub struct StakeLockAccount {
+    /// Escrowed stake account
+    pub stake: Pubkey,
+    /// Withdraw authority before the lock, refunded the rent on release
+    pub owner: Pubkey,
+    /// Withdraw authority after the lock
+    pub beneficiary: Pubkey,
+    /// Unix timestamp the stake is released at
+    pub timestamp: i64,
+    /// Canonical bump of the PDA, stored at creation to rederive the address
+    pub bump: u8,
+}
+
+impl StakeLockAccount {
+    /// Size of the serialized account: three pubkeys + i64 timestamp + u8
+    /// bump
+    pub const LEN: usize = 32 * 3 + 8 + 1;
+}
+
+/// Two-party escrow, see `CreateEscrow`.
+///
+/// `depositor` locks `amount` lamports for `beneficiary`, who can claim them
+/// from `release_time` on. From `refund_time` on, `depositor` can take them
+/// back if they were never claimed.
+#[derive(BorshSerialize, BorshDeserialize, ShankAccount, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(borsh::BorshSchema))]
+pub struct EscrowAccount {
+    /// Funded the escrow, refunded its rent and, after `refund_time`, the amount
+    pub depositor: Pubkey,
+    /// May claim the amount after `release_time`
+    pub beneficiary: Pubkey,
+    /// Lamports escrowed on top of the rent exemption
+    pub amount: u64,
+    /// Unix timestamp the beneficiary may claim from
+    pub release_time: i64,
+    /// Unix timestamp the depositor may reclaim from, after `release_time`
+    pub refund_time: i64,
+    /// May claim on behalf of the beneficiary, the default pubkey when unset
+    pub delegate: Pubkey,
+    /// Share of the amount, in basis points, an early claim forfeits; 0 when
+    /// the beneficiary must wait for `release_time`
+    pub penalty_bps: u16,
+    /// Whether the penalty decreases linearly from `created_at` to `release_time`
+    pub penalty_decays: bool,
+    /// Unix timestamp the escrow was created at
+    pub created_at: i64,
+}
+
+impl EscrowAccount {
+    /// Size of the serialized account: two pubkeys + u64 amount + two i64
+    /// deadlines + delegate pubkey + u16 penalty + bool decay + i64 creation time
+    pub const LEN: usize = 32 * 2 + 8 + 8 + 8 + 32 + 2 + 1 + 8;
+
+    /// Offset of `depositor` in the account data, for `getProgramAccounts` filters
+    pub const DEPOSITOR_OFFSET: usize = 0;
+
+    /// Offset of `beneficiary` in the account data, for `getProgramAccounts` filters
+    pub const BENEFICIARY_OFFSET: usize = 32;
+
+    /// Basis points in a whole amount
+    pub const MAX_PENALTY_BPS: u16 = 10_000;
+
+    /// Lamports of the amount forfeited by a claim at `now`, before the
+    /// release time. `None` if the escrow does not allow early claims.
+    pub fn early_claim_penalty(&self, now: i64) -> Option<u64> {
+        if self.penalty_bps == 0 {
+            return None;
+        }
+        let full = self.amount as u128 * self.penalty_bps.min(Self::MAX_PENALTY_BPS) as u128
+            / Self::MAX_PENALTY_BPS as u128;
+        if !self.penalty_decays || now <= self.created_at {
+            return Some(full as u64);
+        }
+        let remaining = self.release_time.saturating_sub(now).max(0) as u128;
+        let window = self.release_time.saturating_sub(self.created_at).max(1) as u128;
+        Some((full * remaining.min(window) / window) as u64)
+    }
+}
+
+/// Distributor of timelocked claims, see `CreateDistributor`.
+///
+/// `authority` funds `amount` lamports shared among the leaves of a merkle
+/// tree, each claimable once by its recipient from its own unlock time with
+/// `ClaimWithProof`, see [`crate::merkle`].
+#[derive(BorshSerialize, BorshDeserialize, ShankAccount, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(borsh::BorshSchema))]
+pub struct DistributorAccount {
+    /// Funded the distributor
+    pub authority: Pubkey,
+    /// Root of the merkle tree of the claims
+    pub root: [u8; 32],
+    /// Number of leaves of the tree
+    pub num_leaves: u32,
+    /// Lamports distributed on top of the rent exemption
+    pub amount: u64,
+    /// Lamports claimed so far
+    pub claimed_amount: u64,
+    /// Bit `i` is set once the `i`th leaf is claimed
+    pub claimed: Vec<u8>,
+}
+
+impl DistributorAccount {
+    /// Size of the serialized account for `num_leaves` leaves: authority pubkey
+    /// + 32 byte root + u32 leaf count + u64 amount + u64 claimed amount
+    /// + u32 length prefixed claim bitmap
+    pub fn space(num_leaves: u32) -> usize {
+        32 + 32 + 4 + 8 + 8 + 4 + (num_leaves as usize).div_ceil(8)
+    }
+
+    /// Lamports keeping a distributor of `num_leaves` leaves rent exempt
+    pub fn rent_exempt_lamports(rent: &Rent, num_leaves: u32) -> u64 {
+        rent.minimum_balance(Self::space(num_leaves))
+    }
+
+    /// Whether the `index`th leaf was claimed
+    pub fn is_claimed(&self, index: u32) -> bool {
+        let index = index as usize;
+        self.claimed
+            .get(index / 8)
+            .is_some_and(|byte| byte & (1 << (index % 8)) != 0)
+    }
+
+    /// Mark the `index`th leaf claimed
+    pub fn set_claimed(&mut self, index: u32) {
+        let index = index as usize;
+        if let Some(byte) = self.claimed.get_mut(index / 8) {
+            *byte |= 1 << (index % 8);
+        }
+    }
+}
+
+/// Vault funding many vault locks, see `CreateVault`.
+///
+/// Of the `amount` lamports `authority` put in, `allocated` are promised to
+/// open vault locks and paid out as they are claimed; the rest may be
+/// allocated to new ones or withdrawn.
+#[derive(BorshSerialize, BorshDeserialize, ShankAccount, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(borsh::BorshSchema))]
+pub struct VaultAccount {
+    /// Funded the vault, creates its locks and withdraws from it
+    pub authority: Pubkey,
+    /// Lamports held on top of the rent exemption
+    pub amount: u64,
+    /// Part of `amount` promised to unclaimed vault locks
+    pub allocated: u64,
+}
+
+impl VaultAccount {
+    /// Size of the serialized account: authority pubkey + u64 amount + u64
+    /// allocated amount
+    pub const LEN: usize = 32 + 8 + 8;
+
+    /// Lamports neither promised to a vault lock nor withdrawn
+    pub fn unallocated(&self) -> u64 {
+        self.amount.saturating_sub(self.allocated)
+    }
+}
+
+/// Claim of a beneficiary on lamports of a [`VaultAccount`], see
+/// `CreateVaultLock`. The vault pays when the beneficiary claims, from
+/// `release_time` on.
+#[derive(BorshSerialize, BorshDeserialize, ShankAccount, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(borsh::BorshSchema))]
+pub struct VaultLockAccount {
+    /// Vault the amount is drawn from
+    pub vault: Pubkey,
+    /// May claim the amount after `release_time`
+    pub beneficiary: Pubkey,
+    /// Lamports allocated in the vault
+    pub amount: u64,
+    /// Unix timestamp the beneficiary may claim from
+    pub release_time: i64,
+    /// Paid the rent, refunded on claim: the vault authority, or the
+    /// beneficiary who split the vault lock off another
+    pub payer: Pubkey,
+    /// Whether the beneficiary may hand the vault lock to another one
+    pub transferable: bool,
+}
+
+impl VaultLockAccount {
+    /// Size of the serialized account: two pubkeys + u64 amount + i64 release
+    /// time + payer pubkey + transferable flag
+    pub const LEN: usize = 32 * 2 + 8 + 8 + 32 + 1;
+
+    /// Offset of `vault` in the account data, for `getProgramAccounts` filters
+    pub const VAULT_OFFSET: usize = 0;
+
+    /// Offset of `beneficiary` in the account data, for `getProgramAccounts` filters
+    pub const BENEFICIARY_OFFSET: usize = 32;
+}
+
+/// Weeks of vault lock releases a [`VaultStatsAccount`] schedules ahead, four
+/// years
+pub const VAULT_SCHEDULE_WEEKS: usize = 208;
+
+/// Aggregate of the vault locks of a vault, the grants of the organization
+/// funding it, so a treasury dashboard reads them in one account. Created
+/// with the first vault lock of the vault, see `CreateVaultLock`, and updated
+/// as vault locks are created and claimed.
+///
+/// Releases are scheduled per week from `schedule_week` on, and added to
+/// `vested` once their week passes. The account only moves on with the
+/// vault locks: [`VaultStatsAccount::advance`] a fetched copy to the current
+/// time before reading it.
+#[derive(BorshSerialize, BorshDeserialize, ShankAccount, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(borsh::BorshSchema))]
+pub struct VaultStatsAccount {
+    /// Vault the vault locks are drawn from
+    pub vault: Pubkey,
+    /// Vault locks created
+    pub grants: u64,
+    /// Lamports allocated to vault locks
+    pub granted: u64,
+    /// Lamports of vault locks whose release week passed, claimed or not
+    pub vested: u64,
+    /// Lamports of vault locks claimed, claim fees included
+    pub claimed: u64,
+    /// Lamports of vault locks releasing past the schedule when created,
+    /// counted as vested once claimed
+    pub unscheduled: u64,
+    /// Week since the epoch of the oldest release in `schedule`
+    pub schedule_week: i64,
+    /// Lamports released per week, that of week `w` at `w` modulo
+    /// `VAULT_SCHEDULE_WEEKS`
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_array"))]
+    pub schedule: [u64; VAULT_SCHEDULE_WEEKS],
+    /// Canonical bump of the PDA, stored at creation to rederive the address
+    pub bump: u8,
+}
+
+impl VaultStatsAccount {
+    /// Size of the serialized account: vault pubkey + five u64 amounts + i64
+    /// week + `VAULT_SCHEDULE_WEEKS` u64 amounts + u8 bump
+    pub const LEN: usize = 32 + 8 * 5 + 8 + 8 * VAULT_SCHEDULE_WEEKS + 1;
+
+    /// Seconds of a week of the schedule
+    pub const WEEK: i64 = 7 * 86_400;
+
+    /// Weeks of the releases a dashboard shows upcoming, the next 90 days
+    /// rounded up to whole weeks
+    pub const UPCOMING_WEEKS: usize = 13;
+
+    /// No vault locks yet, scheduled from the week of `now`
+    pub fn new(vault: Pubkey, now: i64, bump: u8) -> Self {
+        Self {
+            vault,
+            grants: 0,
+            granted: 0,
+            vested: 0,
+            claimed: 0,
+            unscheduled: 0,
+            schedule_week: Self::week(now),
+            schedule: [0; VAULT_SCHEDULE_WEEKS],
+            bump,
+        }
+    }
+
+    fn week(timestamp: i64) -> i64 {
+        timestamp.div_euclid(Self::WEEK)
+    }
+
+    fn slot(week: i64) -> usize {
+        week.rem_euclid(VAULT_SCHEDULE_WEEKS as i64) as usize
+    }
+
+    /// Vest the releases of the weeks before that of `now`
+    pub fn advance(&mut self, now: i64) {
+        let week = Self::week(now);
+        // past a whole schedule, every scheduled release vested
+        let from = self
+            .schedule_week
+            .max(week.saturating_sub(VAULT_SCHEDULE_WEEKS as i64));
+        for past in from..week {
+            let released = core::mem::take(&mut self.schedule[Self::slot(past)]);
+            self.vested = self.vested.saturating_add(released);
+        }
+        self.schedule_week = self.schedule_week.max(week);
+    }
+
+    /// Count a vault lock of `amount` lamports released at `release_time`
+    pub fn add_grant(&mut self, amount: u64, release_time: i64, now: i64) {
+        self.advance(now);
+        self.grants = self.grants.saturating_add(1);
+        self.granted = self.granted.saturating_add(amount);
+        let week = Self::week(release_time);
+        let bucket = if week < self.schedule_week {
+            &mut self.vested
+        } else if week - self.schedule_week < VAULT_SCHEDULE_WEEKS as i64 {
+            &mut self.schedule[Self::slot(week)]
+        } else {
+            &mut self.unscheduled
+        };
+        *bucket = bucket.saturating_add(amount);
+    }
+
+    /// Count the claim of a vault lock of `amount` lamports
+    pub fn add_claim(&mut self, amount: u64, now: i64) {
+        self.advance(now);
+        self.claimed = self.claimed.saturating_add(amount);
+        // claims beyond whatever released so far were unscheduled
+        let released = self
+            .vested
+            .saturating_add(self.schedule[Self::slot(self.schedule_week)]);
+        let vesting = self.claimed.saturating_sub(released).min(self.unscheduled);
+        self.unscheduled -= vesting;
+        self.vested = self.vested.saturating_add(vesting);
+    }
+
+    /// Lamports of vault locks released in the `weeks` weeks from that of the
+    /// last update, that week included. Releases past the schedule are not.
+    pub fn upcoming(&self, weeks: usize) -> u64 {
+        let weeks = weeks.min(VAULT_SCHEDULE_WEEKS) as i64;
+        (self.schedule_week..self.schedule_week + weeks)
+            .map(|week| self.schedule[Self::slot(week)])
+            .fold(0, u64::saturating_add)
+    }
+}
+
+/// Unlock schedule shared by reference, see `CreateScheduleTemplate`.
+///
+/// `tge_bps` of an amount release at its start, the token generation event,
+/// the rest in `periods` equal releases every `period_months` calendar
+/// months once `cliff_months` passed, or at once at the end of the cliff
+/// without periods. Templates are never modified, so locks created with one
+/// keep the terms it had when audited.
+#[derive(BorshSerialize, BorshDeserialize, ShankAccount, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(borsh::BorshSchema))]
+pub struct ScheduleTemplateAccount {
+    /// Created the template
+    pub authority: Pubkey,
+    /// Share of the amount, in basis points, released at the start
+    pub tge_bps: u16,
+    /// Calendar months from the start before the first period
+    pub cliff_months: u32,
+    /// Calendar months of each period, not 0 with periods
+    pub period_months: u32,
+    /// Equal releases of the rest of the amount after the cliff
+    pub periods: u32,
+}
+
+impl ScheduleTemplateAccount {
+    /// Size of the serialized account: authority pubkey + u16 basis points +
+    /// three u32 month counts
+    pub const LEN: usize = 32 + 2 + 4 * 3;
+
+    /// Basis points in a whole amount
+    pub const MAX_TGE_BPS: u16 = 10_000;
+
+    /// Calendar months a schedule lasts at most, cliff included: a century
+    pub const MAX_MONTHS: u64 = 1200;
+
+    /// Whether the terms make a schedule
+    pub fn is_valid(&self) -> bool {
+        self.tge_bps <= Self::MAX_TGE_BPS
+            && (self.periods == 0 || self.period_months > 0)
+            && self.months() <= Self::MAX_MONTHS
+    }
+
+    /// Calendar months from the start to the last release
+    fn months(&self) -> u64 {
+        self.cliff_months as u64 + self.period_months as u64 * self.periods as u64
+    }
+
+    /// Unix timestamp of the last release of a schedule starting at `start`,
+    /// `None` past the range of a timestamp
+    pub fn end_time(&self, start: i64) -> Option<i64> {
+        calendar::checked_add_months(start, i64::try_from(self.months()).ok()?)
+    }
+
+    /// Lamports of `amount` released at `now` by a schedule starting at `start`
+    pub fn vested(&self, amount: u64, start: i64, now: i64) -> u64 {
+        if now < start {
+            return 0;
+        }
+        let tge = (amount as u128 * self.tge_bps.min(Self::MAX_TGE_BPS) as u128
+            / Self::MAX_TGE_BPS as u128) as u64;
+        let rest = amount - tge;
+        let months = calendar::months_between(start, now) - self.cliff_months as i64;
+        if months < 0 {
+            return tge;
+        }
+        if self.periods == 0 {
+            return amount;
+        }
+        let released = (months / self.period_months.max(1) as i64).min(self.periods as i64);
+        tge + (rest as u128 * released as u128 / self.periods as u128) as u64
+    }
+}
+
+/// Entries a payroll is created for at most
+pub const MAX_PAYROLL_ENTRIES: u32 = 1024;
+
+/// Share of a payroll, as given to `AddPayrollEntries`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(borsh::BorshSchema))]
+pub struct PayrollShare {
+    /// Paid the share
+    pub beneficiary: Pubkey,
+    /// Weight of the share among those of the payroll, not 0
+    pub weight: u32,
+}
+
+impl PayrollShare {
+    /// Size of the serialized share: beneficiary pubkey + u32 weight
+    pub const LEN: usize = 32 + 4;
+}
+
+/// Share of a payroll and what its beneficiary claimed of it
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(borsh::BorshSchema))]
+pub struct PayrollEntry {
+    /// Paid the share
+    pub beneficiary: Pubkey,
+    /// Weight of the share among those of the payroll
+    pub weight: u32,
+    /// Lamports of the share claimed so far, claim fees included
+    pub claimed: u64,
+}
+
+impl PayrollEntry {
+    /// Size of the serialized entry: beneficiary pubkey + u32 weight + u64
+    /// claimed amount
+    pub const LEN: usize = 32 + 4 + 8;
+}
+
+/// Payroll of many beneficiaries on one schedule, see `CreatePayroll`.
+///
+/// `authority` funds `amount` lamports vesting linearly from `start_time` to
+/// `end_time`, all at once when they are equal, or on a
+/// [`ScheduleTemplateAccount`] from `start_time`. Each entry claims its share
+/// of what vested, pro rata to its weight, independently of the others with
+/// `ClaimPayroll`, once `AddPayrollEntries` filled every entry.
+#[derive(BorshSerialize, BorshDeserialize, ShankAccount, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(borsh::BorshSchema))]
+pub struct PayrollAccount {
+    /// Funded the payroll and adds its entries
+    pub authority: Pubkey,
+    /// Lamports shared on top of the rent exemption
+    pub amount: u64,
+    /// Unix timestamp the amount starts vesting at
+    pub start_time: i64,
+    /// Unix timestamp the whole amount vested at
+    pub end_time: i64,
+    /// Schedule template the amount vests on from `start_time` instead, the
+    /// default pubkey when it vests linearly
+    pub template: Pubkey,
+    /// Sum of the weights of the entries added
+    pub total_weight: u64,
+    /// Entries added so far, the first ones of `entries`
+    pub added: u32,
+    /// Every entry of the payroll, the default entry until added
+    pub entries: Vec<PayrollEntry>,
+}
+
+impl PayrollAccount {
+    /// Size of the serialized account for `num_entries` entries: authority
+    /// pubkey + u64 amount + two i64 times + template pubkey + u64 total
+    /// weight + u32 added count + u32 length prefixed entries
+    pub fn space(num_entries: u32) -> usize {
+        32 + 8 + 8 + 8 + 32 + 8 + 4 + 4 + PayrollEntry::LEN * num_entries as usize
+    }
+
+    /// Lamports keeping a payroll of `num_entries` entries rent exempt
+    pub fn rent_exempt_lamports(rent: &Rent, num_entries: u32) -> u64 {
+        rent.minimum_balance(Self::space(num_entries))
+    }
+
+    /// Whether every entry was added, so the shares are final
+    pub fn is_complete(&self) -> bool {
+        self.added as usize == self.entries.len()
+    }
+
+    /// Whether the amount vests on a schedule template
+    pub fn has_template(&self) -> bool {
+        self.template != Pubkey::default()
+    }
+
+    /// Lamports of the amount vested at `now`, on `template` if the payroll
+    /// has one
+    pub fn vested(&self, template: Option<&ScheduleTemplateAccount>, now: i64) -> u64 {
+        if let Some(template) = template {
+            return template.vested(self.amount, self.start_time, now);
+        }
+        if now < self.start_time {
+            return 0;
+        }
+        if now >= self.end_time {
+            return self.amount;
+        }
+        let elapsed = (now - self.start_time) as u128;
+        let duration = (self.end_time - self.start_time) as u128;
+        (self.amount as u128 * elapsed / duration) as u64
+    }
+
+    /// Whether every entry was added and claimed its share of the whole
+    /// amount, leaving at most what rounding down the shares left over
+    pub fn is_settled(&self) -> bool {
+        self.is_complete()
+            && self.total_weight > 0
+            && self.entries.iter().all(|entry| {
+                let share = self.amount as u128 * entry.weight as u128 / self.total_weight as u128;
+                entry.claimed >= share as u64
+            })
+    }
+
+    /// Lamports the `index`th entry may claim at `now`: its share of what
+    /// vested, rounded down, less what it claimed
+    pub fn claimable(
+        &self,
+        template: Option<&ScheduleTemplateAccount>,
+        index: u32,
+        now: i64,
+    ) -> u64 {
+        let Some(entry) = self.entries.get(index as usize) else {
+            return 0;
+        };
+        if self.total_weight == 0 {
+            return 0;
+        }
+        let share =
+            self.vested(template, now) as u128 * entry.weight as u128 / self.total_weight as u128;
+        (share as u64).saturating_sub(entry.claimed)
+    }
+}
+
+/// Parameters of the program set by its admin, see `InitializeConfig`.
+///
+/// Until the config is initialized, locks are created without a fee nor
+/// duration bounds.
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Debug, Default, Clone, Copy, PartialEq, Eq,
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(borsh::BorshSchema))]
+pub struct ConfigAccount {
+    /// May update the config
+    pub admin: Pubkey,
+    /// Receives the collected fees
+    pub treasury: Pubkey,
+    /// Lamports charged for creating a time lock or an escrow, held by the
+    /// config until collected
+    pub creation_fee: u64,
+    /// Shortest duration, in seconds, from creation to maturity
+    pub min_duration: i64,
+    /// Longest duration, in seconds, from creation to maturity, 0 for no bound
+    pub max_duration: i64,
+    /// Whether the program is paused: no time lock nor escrow is created and
+    /// no claim goes through, refunds and closes still do
+    pub paused: bool,
+    /// May pause and unpause the program besides the admin, default for none
+    pub guardian: Pubkey,
+    /// Share of every claimed amount, in basis points, held by the config as
+    /// a fee until collected
+    pub claim_fee_bps: u16,
+    /// Proposed by the admin to take over the config, default for none
+    pub pending_admin: Pubkey,
+    /// Unix timestamp the pending admin may accept until
+    pub pending_admin_expires_at: i64,
+    /// Seconds between queueing an update with `UpdateConfig` and applying
+    /// it with `ApplyConfigUpdate`
+    pub update_delay: i64,
+    /// Lifetime lamports of creation fees received
+    pub total_creation_fees: u64,
+    /// Lifetime lamports of claim fees received
+    pub total_claim_fees: u64,
+    /// Lifetime lamports forfeited by early escrow claims, which go back to
+    /// the depositors rather than to the config
+    pub total_penalties: u64,
+    /// Lifetime lamports of fees collected or withdrawn
+    pub total_withdrawn: u64,
+    /// Seconds a time lock may be initialized past its unlock timestamp, for
+    /// transactions landing later than built. Never applies to unlocking.
+    pub clock_drift_tolerance: i64,
+    /// Slot `RecordImmutability` saw the program without upgrade authority
+    /// at, 0 until then
+    pub immutable_slot: u64,
+    /// Slot the immutable program was last deployed at, from its program data
+    pub immutable_deploy_slot: u64,
+    /// sha256 of the executable of the verified build, as `solana-verify`
+    /// reports it, recorded by the admin with `RecordVerifiedBuild`. Zeroed
+    /// until recorded.
+    pub verified_build_hash: [u8; 32],
+    /// Git commit of the source of the verified build, zeroed until recorded
+    pub verified_source_commit: [u8; 20],
+    /// Canonical bump of the PDA, stored at creation to rederive the address
+    pub bump: u8,
+}
+
+impl ConfigAccount {
+    /// Size of the serialized account: two pubkeys + u64 fee + two i64
+    /// durations + paused flag + guardian pubkey + u16 claim fee + pending
+    /// admin pubkey + i64 expiry + i64 update delay + four u64 totals + i64
+    /// drift tolerance + two u64 immutability slots + 32 byte build hash +
+    /// 20 byte source commit + u8 bump
+    pub const LEN: usize =
+        32 * 2 + 8 + 8 * 2 + 1 + 32 + 2 + 32 + 8 + 8 + 8 * 4 + 8 + 8 * 2 + 32 + 20 + 1;
+
+    /// Basis points in a whole amount
+    pub const MAX_CLAIM_FEE_BPS: u16 = 10_000;
+
+    /// Largest clock drift tolerance, in seconds
+    pub const MAX_CLOCK_DRIFT_TOLERANCE: i64 = 300;
+
+    /// Whether a lock maturing `duration` seconds after its creation is allowed
+    pub fn allows_duration(&self, duration: i64) -> bool {
+        duration >= self.min_duration && (self.max_duration == 0 || duration <= self.max_duration)
+    }
+
+    /// Lamports of a claim of `amount` kept as a fee
+    pub fn claim_fee(&self, amount: u64) -> u64 {
+        (amount as u128 * self.claim_fee_bps.min(Self::MAX_CLAIM_FEE_BPS) as u128
+            / Self::MAX_CLAIM_FEE_BPS as u128) as u64
+    }
+}
+
+/// Update of the program config queued by its admin, see `UpdateConfig`.
+///
+/// Anyone applies it from `eta`, the admin or the guardian may cancel it
+/// until then.
+#[derive(BorshSerialize, BorshDeserialize, ShankAccount, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(borsh::BorshSchema))]
+pub struct ConfigUpdateAccount {
+    /// Receives the collected fees
+    pub treasury: Pubkey,
+    /// Lamports charged for creating a time lock or an escrow
+    pub creation_fee: u64,
+    /// Shortest duration, in seconds, from creation to maturity
+    pub min_duration: i64,
+    /// Longest duration, in seconds, from creation to maturity, 0 for no bound
+    pub max_duration: i64,
+    /// Pauses the program when applied, never lifting a pause
+    pub paused: bool,
+    /// May pause and unpause the program besides the admin
+    pub guardian: Pubkey,
+    /// Share of every claimed amount, in basis points, kept as a fee
+    pub claim_fee_bps: u16,
+    /// Seconds between queueing an update and applying it
+    pub update_delay: i64,
+    /// Seconds a time lock may be initialized past its unlock timestamp
+    pub clock_drift_tolerance: i64,
+    /// Unix timestamp the update may be applied from
+    pub eta: i64,
+    /// Paid the rent, refunded when the update is applied or cancelled
+    pub payer: Pubkey,
+    /// Canonical bump of the PDA, stored at creation to rederive the address
+    pub bump: u8,
+}
+
+impl ConfigUpdateAccount {
+    /// Size of the serialized account: pubkey + u64 fee + two i64 durations +
+    /// paused flag + guardian pubkey + u16 claim fee + i64 delay + i64
+    /// tolerance + i64 eta + payer pubkey + u8 bump
+    pub const LEN: usize = 32 + 8 + 8 * 2 + 1 + 32 + 2 + 8 + 8 + 8 + 32 + 1;
+}
+
+/// Fee exemption of a key, see `AddFeeExemption`.
+///
+/// A creation or a claim is free when the fee exemption PDA of its payer or
+/// claimant is among the accounts of the instruction.
+#[derive(BorshSerialize, BorshDeserialize, ShankAccount, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(borsh::BorshSchema))]
+pub struct FeeExemptionAccount {
+    /// Key charged no fee
+    pub key: Pubkey,
+    /// Canonical bump of the PDA, stored at creation to rederive the address
+    pub bump: u8,
+}
+
+impl FeeExemptionAccount {
+    /// Size of the serialized account: one pubkey + u8 bump
+    pub const LEN: usize = 32 + 1;
+}
+
+/// Entries an audit trail stores
+pub const MAX_AUDIT_ENTRIES: usize = 32;
+
+/// Mutation of a time lock recorded in its audit trail
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(borsh::BorshSchema))]
+pub struct AuditEntry {
+    /// Discriminator of the instruction mutating the lock
+    pub instruction: [u8; 8],
+    /// First signer of the instruction, the default pubkey when none signed
+    pub actor: Pubkey,
+    /// Slot of the mutation
+    pub slot: u64,
+    /// Unix timestamp of the mutation
+    pub unix_timestamp: i64,
+}
+
+impl AuditEntry {
+    /// Size of the serialized entry: 8 byte discriminator + actor pubkey +
+    /// u64 slot + i64 timestamp
+    pub const LEN: usize = 8 + 32 + 8 + 8;
+}
+
+/// Audit trail of a time lock, see `EnableAuditTrail`.
+///
+/// Every mutation of the lock by its authority from then on appends an entry,
+/// and so do its reveals and payouts, an on-chain record independent of how
+/// long RPC nodes keep the transaction history. What others may send for the
+/// price of a transaction, premature attempts and deposits, is not recorded.
+/// Entries are never overwritten: past [`MAX_AUDIT_ENTRIES`] mutations are
+/// still counted in `total` but no longer stored, rather than failing them.
+#[derive(BorshSerialize, BorshDeserialize, ShankAccount, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(borsh::BorshSchema))]
+pub struct AuditTrailAccount {
+    /// Time lock audited
+    pub lock: Pubkey,
+    /// Mutations recorded, stored or not
+    pub total: u32,
+    /// The first `total` mutations, up to [`MAX_AUDIT_ENTRIES`]
+    pub entries: [AuditEntry; MAX_AUDIT_ENTRIES],
+    /// Canonical bump of the PDA, stored at creation to rederive the address
+    pub bump: u8,
+}
+
+impl AuditTrailAccount {
+    /// Size of the serialized account: pubkey + u32 total + MAX_AUDIT_ENTRIES
+    /// entries + u8 bump
+    pub const LEN: usize = 32 + 4 + AuditEntry::LEN * MAX_AUDIT_ENTRIES + 1;
+
+    /// The entries stored, oldest first
+    pub fn stored(&self) -> &[AuditEntry] {
+        &self.entries[..(self.total as usize).min(MAX_AUDIT_ENTRIES)]
+    }
+
+    /// Append `entry`, only counted once the trail is full
+    pub fn append(&mut self, entry: AuditEntry) {
+        if let Some(slot) = self.entries.get_mut(self.total as usize) {
+            *slot = entry;
+        }
+        self.total = self.total.saturating_add(1);
+    }
+}
+
+/// Claim of the bounty of a time lock committed ahead of its reveal, see
+/// `CommitClaim`.
+///
+/// Only the hash of the destination of the bounty and a salt is public until
+/// `RevealClaim`, in a later slot, so a claim seen in flight cannot be copied
+/// with another destination.
+#[derive(BorshSerialize, BorshDeserialize, ShankAccount, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(borsh::BorshSchema))]
+pub struct ClaimCommitmentAccount {
+    /// Time lock whose bounty is claimed
+    pub lock: Pubkey,
+    /// Signed the commitment and paid its rent, refunded at the reveal
+    pub committer: Pubkey,
+    /// Hash of the destination and the salt, see [`ClaimCommitmentAccount::commitment`]
+    pub commitment: [u8; 32],
+    /// Slot of the commitment, the reveal comes in a later one
+    pub slot: u64,
+    /// Canonical bump of the PDA, stored at creation to rederive the address
+    pub bump: u8,
+}
+
+impl ClaimCommitmentAccount {
+    /// Size of the serialized account: two pubkeys + 32 byte hash + u64 slot
+    /// + u8 bump
+    pub const LEN: usize = 32 * 2 + 32 + 8 + 1;
+
+    /// Commitment to pay the bounty to `destination`: sha256 of its address
+    /// followed by `salt`, a secret of the committer
+    pub fn commitment(destination: &Pubkey, salt: &[u8; 32]) -> [u8; 32] {
+        hashv(&[destination.as_ref(), salt]).to_bytes()
+    }
+}
+
+/// Next nonce of the time locks a payer creates at PDAs, see
+/// `InitializeNoncedTimeLock`.
+///
+/// Each nonce creates a single lock, so a captured creation cannot be
+/// replayed, and the locks of a payer are at the PDAs of the nonces below
+/// `next`.
+#[derive(BorshSerialize, BorshDeserialize, ShankAccount, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(borsh::BorshSchema))]
+pub struct LockNonceAccount {
+    /// Payer the nonces are of, the authority of its locks
+    pub payer: Pubkey,
+    /// Nonce the next lock of the payer is created with
+    pub next: u64,
+    /// Canonical bump of the PDA, stored at creation to rederive the address
+    pub bump: u8,
+}
+
+impl LockNonceAccount {
+    /// Size of the serialized account: pubkey + u64 nonce + u8 bump
+    pub const LEN: usize = 32 + 8 + 1;
+}
+
+/// Accounts of the program of a fixed size. The program creates them with
+/// this size and balance, and clients funding them use the same.
+pub trait FixedSizeAccount {
+    /// Size of the serialized account
+    const SPACE: usize;
+
+    /// Lamports keeping the account rent exempt
+    fn rent_exempt_lamports(rent: &Rent) -> u64 {
+        rent.minimum_balance(Self::SPACE)
+    }
+}
+
+impl FixedSizeAccount for TimeLockAccount {
+    const SPACE: usize = Self::LEN;
+}
+
+impl FixedSizeAccount for CallbackAccount {
+    const SPACE: usize = Self::LEN;
+}
+
+impl FixedSizeAccount for TimeLockStats {
+    const SPACE: usize = Self::LEN;
+}
+
+impl FixedSizeAccount for StakeLockAccount {
+    const SPACE: usize = Self::LEN;
+}
+
+impl FixedSizeAccount for ScheduleTemplateAccount {
+    const SPACE: usize = Self::LEN;
+}
+
+impl FixedSizeAccount for EscrowAccount {
+    const SPACE: usize = Self::LEN;
+}
+
+impl FixedSizeAccount for VaultAccount {
+    const SPACE: usize = Self::LEN;
+}
+
+impl FixedSizeAccount for VaultLockAccount {
+    const SPACE: usize = Self::LEN;
+}
+
+impl FixedSizeAccount for ConfigAccount {
+    const SPACE: usize = Self::LEN;
+}
+
+impl FixedSizeAccount for ConfigUpdateAccount {
+    const SPACE: usize = Self::LEN;
+}
+
+impl FixedSizeAccount for FeeExemptionAccount {
+    const SPACE: usize = Self::LEN;
+}
+
+impl FixedSizeAccount for AuditTrailAccount {
+    const SPACE: usize = Self::LEN;
+}
+
+impl FixedSizeAccount for ClaimCommitmentAccount {
+    const SPACE: usize = Self::LEN;
+}
+
+impl FixedSizeAccount for LockNonceAccount {
+    const SPACE: usize = Self::LEN;
+}
+
+impl FixedSizeAccount for VaultStatsAccount {
+    const SPACE: usize = Self::LEN;
+}
+
+// Deployed accounts keep their layout: a change to the size of an account
+// must fail the build rather than corrupt the accounts on chain. Only update
+// these along with a migration of the existing accounts, see `MigrateAccount`.
+const _: () = assert!(
+    TimeLockAccount::LEN == 692,
+    "TimeLockAccount layout changed"
+);
+const _: () = assert!(
+    CallbackAccount::LEN == 260,
+    "CallbackAccount layout changed"
+);
+const _: () = assert!(TimeLockStats::LEN == 25, "TimeLockStats layout changed");
+const _: () = assert!(
+    StakeLockAccount::LEN == 105,
+    "StakeLockAccount layout changed"
+);
+const _: () = assert!(EscrowAccount::LEN == 131, "EscrowAccount layout changed");
+const _: () = assert!(VaultAccount::LEN == 48, "VaultAccount layout changed");
+const _: () = assert!(
+    VaultLockAccount::LEN == 113,
+    "VaultLockAccount layout changed"
+);
+const _: () = assert!(ConfigAccount::LEN == 280, "ConfigAccount layout changed");
+const _: () = assert!(
+    ConfigUpdateAccount::LEN == 148,
+    "ConfigUpdateAccount layout changed"
+);
+const _: () = assert!(
+    FeeExemptionAccount::LEN == 33,
+    "FeeExemptionAccount layout changed"
+);
+const _: () = assert!(
+    AuditTrailAccount::LEN == 1829,
+    "AuditTrailAccount layout changed"
+);
+const _: () = assert!(
+    ClaimCommitmentAccount::LEN == 105,
+    "ClaimCommitmentAccount layout changed"
+);
+const _: () = assert!(
+    LockNonceAccount::LEN == 41,
+    "LockNonceAccount layout changed"
+);
+const _: () = assert!(
+    VaultStatsAccount::LEN == 1745,
+    "VaultStatsAccount layout changed"
+);
+
+/// Current version of [`LockPosition`]
+pub const POSITION_VERSION: u8 = 1;
+
+/// Valuation of a time lock, returned by `GetPosition` so lending and LP
+/// protocols can CPI into the program to price it as collateral.
+///
+/// Fields are only ever appended, callers should check `version` and may
+/// ignore trailing bytes they don't know.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(borsh::BorshSchema))]
+pub struct LockPosition {
+    /// Layout version, [`POSITION_VERSION`] when produced by this interface
+    pub version: u8,
+    /// Address of the lock
+    pub lock: Pubkey,
+    /// Unix timestamp the lock matures at
+    pub unlock_timestamp: i64,
+    /// Unix timestamp the position was valued at
+    pub now: i64,
+    /// Lamports escrowed by the lock, the bounty
+    pub total_lamports: u64,
+    /// Part of `total_lamports` claimable now
+    pub vested_lamports: u64,
+    /// Part of `total_lamports` claimable only after maturity
+    pub unvested_lamports: u64,
+}
+
+impl LockPosition {
+    /// Value `lock` at `now`: everything vests at once when it matures
+    pub fn new(lock: Pubkey, account: &TimeLockAccount, now: i64) -> Self {
+        let matured = now >= account.timestamp;
+        Self {
+            version: POSITION_VERSION,
+            lock,
+            unlock_timestamp: account.timestamp,
+            now,
+            total_lamports: account.bounty,
+            vested_lamports: if matured { account.bounty } else { 0 },
+            unvested_lamports: if matured { 0 } else { account.bounty },
+        }
+    }
+}
+
+/// Current version of [`RevenueReport`]
+pub const REVENUE_VERSION: u8 = 1;
+
+/// Lifetime revenue of the program config, returned by `GetRevenue` so
+/// anyone can audit it without an indexer.
+///
+/// Fields are only ever appended, callers should check `version` and may
+/// ignore trailing bytes they don't know.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(borsh::BorshSchema))]
+pub struct RevenueReport {
+    /// Layout version, [`REVENUE_VERSION`] when produced by this interface
+    pub version: u8,
+    /// Lamports of fees held by the config now
+    pub fees_held: u64,
+    /// Lifetime lamports of creation fees received
+    pub total_creation_fees: u64,
+    /// Lifetime lamports of claim fees received
+    pub total_claim_fees: u64,
+    /// Lifetime lamports of fees collected or withdrawn
+    pub total_withdrawn: u64,
+}
+
+impl RevenueReport {
+    /// Report of `config` holding `fees_held` lamports of fees
+    pub fn new(config: &ConfigAccount, fees_held: u64) -> Self {
+        Self {
+            version: REVENUE_VERSION,
+            fees_held,
+            total_creation_fees: config.total_creation_fees,
+            total_claim_fees: config.total_claim_fees,
+            total_withdrawn: config.total_withdrawn,
+        }
+    }
+}
+
+/// Current version of [`ImmutabilityReport`]
+pub const IMMUTABILITY_VERSION: u8 = 1;
+
+/// Whether the program was recorded immutable, returned by `GetImmutability`
+/// so integrators can check it without parsing the program data.
+///
+/// Fields are only ever appended, callers should check `version` and may
+/// ignore trailing bytes they don't know.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(borsh::BorshSchema))]
+pub struct ImmutabilityReport {
+    /// Layout version, [`IMMUTABILITY_VERSION`] when produced by this interface
+    pub version: u8,
+    /// Whether `RecordImmutability` saw the program without upgrade authority
+    pub immutable: bool,
+    /// Slot it was recorded at, 0 if not
+    pub recorded_slot: u64,
+    /// Slot the immutable program was last deployed at, 0 if not recorded
+    pub deploy_slot: u64,
+}
+
+impl ImmutabilityReport {
+    /// Report of what `config` recorded
+    pub fn new(config: &ConfigAccount) -> Self {
+        Self {
+            version: IMMUTABILITY_VERSION,
+            immutable: config.immutable_slot != 0,
+            recorded_slot: config.immutable_slot,
+            deploy_slot: config.immutable_deploy_slot,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    prop_compose! {
+        fn time_lock()(
+            timestamp in any::<i64>(),
+            secret in vec(any::<u8>(), SECRET_LENGTH),
+            bounty in any::<u64>(),
+            attempts in any::<u32>(),
+            premature_attempts in any::<u32>(),
+            last_attempt in any::<i64>(),
+            revealed_at in any::<i64>(),
+            require_memo in any::<bool>(),
+            claim_memo_hash in any::<[u8; 32]>(),
+            authority in any::<[u8; 32]>(),
+            top_up_authority_only in any::<bool>(),
+            unlockers in any::<[[u8; 32]; MAX_UNLOCKERS]>(),
+            expires_at in any::<i64>(),
+            expiry_recipient in any::<[u8; 32]>(),
+            heartbeat_interval in any::<i64>(),
+            recurrence_unit in any::<u8>(),
+            recurrence_every in any::<u32>(),
+            recurrence_window in any::<u32>(),
+            has_callback in any::<bool>(),
+            condition_account in any::<[u8; 32]>(),
+            condition_offset in any::<u32>(),
+            condition_len in any::<u8>(),
+            condition_bytes in any::<[u8; MAX_CONDITION_BYTES]>(),
+            predecessor in any::<[u8; 32]>(),
+            has_audit_trail in any::<bool>(),
+            claim_commit_required in any::<bool>(),
+            in_progress in any::<bool>(),
+            funded in any::<u64>(),
+            grant_recipient in any::<[u8; 32]>(),
+        ) -> TimeLockAccount {
+            TimeLockAccount {
+                timestamp,
+                secret: secret.try_into().unwrap(),
+                bounty,
+                attempts,
+                premature_attempts,
+                last_attempt,
+                revealed_at,
+                require_memo,
+                claim_memo_hash,
+                authority: Pubkey::new_from_array(authority),
+                top_up_authority_only,
+                unlockers: unlockers.map(Pubkey::new_from_array),
+                expires_at,
+                expiry_recipient: Pubkey::new_from_array(expiry_recipient),
+                heartbeat_interval,
+                recurrence_unit,
+                recurrence_every,
+                recurrence_window,
+                has_callback,
+                condition_account: Pubkey::new_from_array(condition_account),
+                condition_offset,
+                condition_len,
+                condition_bytes,
+                predecessor: Pubkey::new_from_array(predecessor),
+                has_audit_trail,
+                claim_commit_required,
+                in_progress,
+                funded,
+                grant_recipient: Pubkey::new_from_array(grant_recipient),
+            }
+        }
+    }
+
+    /// Whether `field` is serialized at `offset` in `data`
+    fn field_at(data: &[u8], offset: usize, field: &impl BorshSerialize) -> bool {
+        let bytes = borsh::to_vec(field).unwrap();
+        data.get(offset..offset + bytes.len()) == Some(bytes.as_slice())
+    }
+
+    #[test]
+    fn test_filter_offsets() {
+        let depositor = Pubkey::new_unique();
+        let beneficiary = Pubkey::new_unique();
+        let escrow = EscrowAccount {
+            depositor,
+            beneficiary,
+            amount: 0,
+            release_time: 0,
+            refund_time: 0,
+            delegate: Pubkey::default(),
+            penalty_bps: 0,
+            penalty_decays: false,
+            created_at: 0,
+        };
+        let data = borsh::to_vec(&escrow).unwrap();
+        assert!(field_at(&data, EscrowAccount::DEPOSITOR_OFFSET, &depositor));
+        assert!(field_at(
+            &data,
+            EscrowAccount::BENEFICIARY_OFFSET,
+            &beneficiary
+        ));
+
+        let vault = Pubkey::new_unique();
+        let vault_lock = VaultLockAccount {
+            vault,
+            beneficiary,
+            amount: 0,
+            release_time: 0,
+            payer: Pubkey::default(),
+            transferable: false,
+        };
+        let data = borsh::to_vec(&vault_lock).unwrap();
+        assert!(field_at(&data, VaultLockAccount::VAULT_OFFSET, &vault));
+        assert!(field_at(
+            &data,
+            VaultLockAccount::BENEFICIARY_OFFSET,
+            &beneficiary
+        ));
+    }
+
+    #[test]
+    fn test_early_claim_penalty() {
+        let mut escrow = EscrowAccount {
+            depositor: Pubkey::default(),
+            beneficiary: Pubkey::default(),
+            amount: 10_000,
+            release_time: 200,
+            refund_time: 300,
+            delegate: Pubkey::default(),
+            penalty_bps: 0,
+            penalty_decays: false,
+            created_at: 100,
+        };
+        assert_eq!(escrow.early_claim_penalty(150), None);
+
+        escrow.penalty_bps = 2_500;
+        assert_eq!(escrow.early_claim_penalty(100), Some(2_500));
+        assert_eq!(escrow.early_claim_penalty(199), Some(2_500));
+
+        escrow.penalty_decays = true;
+        assert_eq!(escrow.early_claim_penalty(100), Some(2_500));
+        assert_eq!(escrow.early_claim_penalty(150), Some(1_250));
+        assert_eq!(escrow.early_claim_penalty(200), Some(0));
+    }
+
+    #[test]
+    fn test_distributor_bitmap() {
+        let mut distributor = DistributorAccount {
+            authority: Pubkey::new_unique(),
+            root: [0; 32],
+            num_leaves: 10,
+            amount: 0,
+            claimed_amount: 0,
+            claimed: vec![0; 2],
+        };
+        assert_eq!(
+            borsh::to_vec(&distributor).unwrap().len(),
+            DistributorAccount::space(10)
+        );
+        distributor.set_claimed(9);
+        assert!(distributor.is_claimed(9));
+        assert!(!distributor.is_claimed(8));
+        assert!(!distributor.is_claimed(1));
+        // out of range leaves are never claimed
+        distributor.set_claimed(100);
+        assert!(!distributor.is_claimed(100));
+    }
+
+    #[test]
+    fn test_allows_duration() {
+        let mut config = ConfigAccount::default();
+        assert!(config.allows_duration(0));
+        assert!(config.allows_duration(i64::MAX));
+
+        config.min_duration = 60;
+        config.max_duration = 3_600;
+        assert!(!config.allows_duration(59));
+        assert!(config.allows_duration(60));
+        assert!(config.allows_duration(3_600));
+        assert!(!config.allows_duration(3_601));
+    }
+
+    #[test]
+    fn test_claim_fee() {
+        let mut config = ConfigAccount::default();
+        assert_eq!(config.claim_fee(1_000), 0);
+
+        config.claim_fee_bps = 250;
+        assert_eq!(config.claim_fee(1_000), 25);
+        assert_eq!(config.claim_fee(39), 0);
+        assert_eq!(config.claim_fee(u64::MAX), u64::MAX / 40);
+        config.claim_fee_bps = u16::MAX;
+        assert_eq!(config.claim_fee(1_000), 1_000);
+    }
+
+    #[test]
+    fn test_condition_met() {
+        let mut lock = TimeLockAccount::unpack(&[0; TimeLockAccount::LEN]).unwrap();
+        assert!(lock.condition_met(&[]));
+
+        lock.condition_account = Pubkey::new_unique();
+        lock.condition_offset = 2;
+        lock.condition_len = 2;
+        lock.condition_bytes[..2].copy_from_slice(&[7, 8]);
+        assert!(lock.condition_met(&[0, 0, 7, 8]));
+        assert!(lock.condition_met(&[0, 0, 7, 8, 9]));
+        assert!(!lock.condition_met(&[0, 0, 7, 9]));
+        // data too short to hold the bytes
+        assert!(!lock.condition_met(&[0, 0, 7]));
+    }
+
+    #[test]
+    fn test_is_open() {
+        let mut lock = TimeLockAccount::unpack(&[0; TimeLockAccount::LEN]).unwrap();
+        lock.timestamp = 100;
+        assert!(!lock.is_open(99));
+        assert!(lock.is_open(100));
+        assert!(lock.is_open(i64::MAX));
+
+        // open 10 seconds every minute
+        lock.recurrence_unit = TimeLockAccount::RECUR_SECONDS;
+        lock.recurrence_every = 60;
+        lock.recurrence_window = 10;
+        assert!(!lock.is_open(99));
+        assert!(lock.is_open(109));
+        assert!(!lock.is_open(110));
+        assert!(lock.is_open(160));
+
+        // open the first day of every month, from 2024-01-01
+        lock.timestamp = 1_704_067_200;
+        lock.recurrence_unit = TimeLockAccount::RECUR_MONTHS;
+        lock.recurrence_every = 1;
+        lock.recurrence_window = 86_400;
+        assert!(lock.is_open(1_704_067_200 + 86_399));
+        assert!(!lock.is_open(1_704_067_200 + 86_400));
+        // 2024-02-01, 2024-03-01
+        assert!(lock.is_open(1_706_745_600));
+        assert!(!lock.is_open(1_706_745_600 - 1));
+        assert!(lock.is_open(1_709_251_200 + 3_600));
+    }
+
+    #[test]
+    fn test_audit_append() {
+        let mut trail = AuditTrailAccount {
+            lock: Pubkey::new_unique(),
+            total: 0,
+            entries: [AuditEntry::default(); MAX_AUDIT_ENTRIES],
+            bump: 255,
+        };
+        for slot in 0..MAX_AUDIT_ENTRIES as u64 + 2 {
+            trail.append(AuditEntry {
+                slot,
+                ..AuditEntry::default()
+            });
+        }
+        // a full trail keeps its entries and only counts the later ones
+        assert_eq!(trail.total, MAX_AUDIT_ENTRIES as u32 + 2);
+        assert_eq!(trail.stored().len(), MAX_AUDIT_ENTRIES);
+        assert_eq!(trail.stored()[0].slot, 0);
+        assert_eq!(trail.stored()[MAX_AUDIT_ENTRIES - 1].slot, 31);
+        assert_eq!(borsh::to_vec(&trail).unwrap().len(), AuditTrailAccount::LEN);
+    }
+
+    #[test]
+    fn test_payroll_claimable() {
+        let share = |weight| PayrollEntry {
+            beneficiary: Pubkey::new_unique(),
+            weight,
+            claimed: 0,
+        };
+        let mut payroll = PayrollAccount {
+            authority: Pubkey::new_unique(),
+            amount: 1000,
+            start_time: 100,
+            end_time: 200,
+            template: Pubkey::default(),
+            total_weight: 4,
+            added: 3,
+            entries: vec![share(1), share(1), share(2)],
+        };
+        assert!(payroll.is_complete());
+        assert_eq!(
+            borsh::to_vec(&payroll).unwrap().len(),
+            PayrollAccount::space(3)
+        );
+        assert_eq!(payroll.claimable(None, 0, 99), 0);
+        assert_eq!(payroll.vested(None, 150), 500);
+        assert_eq!(payroll.claimable(None, 2, 150), 250);
+        payroll.entries[2].claimed = 250;
+        // claims only take what vested since
+        assert_eq!(payroll.claimable(None, 2, 150), 0);
+        assert_eq!(payroll.claimable(None, 2, 200), 250);
+        assert_eq!(payroll.claimable(None, 0, 1_000), 250);
+        assert_eq!(payroll.claimable(None, 3, 1_000), 0);
+
+        // the shares round down, never over the amount
+        payroll.total_weight = 3;
+        payroll.entries[2].weight = 1;
+        payroll.entries[2].claimed = 0;
+        assert_eq!(payroll.claimable(None, 0, 200), 333);
+        // a cliff vests at once
+        payroll.end_time = payroll.start_time;
+        assert_eq!(payroll.vested(None, 99), 0);
+        assert_eq!(payroll.vested(None, 100), 1000);
+    }
+
+    #[test]
+    fn test_templated_payroll_claimable() {
+        // 2024-01-31T12:00:00Z
+        let tge = 1_706_702_400;
+        let month = |months| calendar::add_months(tge, months);
+        // 25% at TGE, a 3 month cliff, then 3 monthly releases
+        let template = ScheduleTemplateAccount {
+            authority: Pubkey::new_unique(),
+            tge_bps: 2500,
+            cliff_months: 3,
+            period_months: 1,
+            periods: 3,
+        };
+        let share = |weight| PayrollEntry {
+            beneficiary: Pubkey::new_unique(),
+            weight,
+            claimed: 0,
+        };
+        let mut payroll = PayrollAccount {
+            authority: Pubkey::new_unique(),
+            amount: 4000,
+            start_time: tge,
+            end_time: template.end_time(tge).unwrap(),
+            template: Pubkey::new_unique(),
+            total_weight: 4,
+            added: 2,
+            entries: vec![share(1), share(3)],
+        };
+        assert!(payroll.has_template());
+        assert_eq!(payroll.claimable(Some(&template), 0, tge - 1), 0);
+        // only the TGE share is claimable until the first release after the cliff
+        assert_eq!(payroll.claimable(Some(&template), 0, tge), 250);
+        assert_eq!(payroll.claimable(Some(&template), 1, month(3)), 750);
+        assert_eq!(payroll.claimable(Some(&template), 1, month(4)), 1500);
+        payroll.entries[1].claimed = 1500;
+        assert_eq!(payroll.claimable(Some(&template), 1, month(5)), 750);
+        assert_eq!(payroll.claimable(Some(&template), 0, month(6)), 1000);
+        // the template, not the linear schedule, decides the vesting
+        assert_eq!(payroll.claimable(None, 0, tge), 0);
+    }
+
+    #[test]
+    fn test_schedule_template() {
+        // 2024-01-31T12:00:00Z
+        let tge = 1_706_702_400;
+        let month = |months| calendar::add_months(tge, months);
+        // 10% at TGE, a 6 month cliff, then 18 monthly releases
+        let template = ScheduleTemplateAccount {
+            authority: Pubkey::new_unique(),
+            tge_bps: 1000,
+            cliff_months: 6,
+            period_months: 1,
+            periods: 18,
+        };
+        assert!(template.is_valid());
+        assert_eq!(
+            borsh::to_vec(&template).unwrap().len(),
+            ScheduleTemplateAccount::LEN
+        );
+        assert_eq!(template.vested(1800, tge, tge - 1), 0);
+        assert_eq!(template.vested(1800, tge, tge), 180);
+        assert_eq!(template.vested(1800, tge, month(7) - 1), 180);
+        assert_eq!(template.vested(1800, tge, month(7)), 270);
+        assert_eq!(template.vested(1800, tge, month(15)), 990);
+        assert_eq!(template.end_time(tge), Some(month(24)));
+        assert_eq!(template.end_time(i64::MAX), None);
+        assert_eq!(template.vested(1800, tge, month(24)), 1800);
+        assert_eq!(template.vested(1800, tge, month(100)), 1800);
+
+        // without periods the rest releases at the end of the cliff
+        let cliff = ScheduleTemplateAccount {
+            periods: 0,
+            period_months: 0,
+            ..template
+        };
+        assert!(cliff.is_valid());
+        assert_eq!(cliff.vested(1800, tge, month(6) - 1), 180);
+        assert_eq!(cliff.vested(1800, tge, month(6)), 1800);
+        assert!(!ScheduleTemplateAccount {
+            period_months: 0,
+            ..template
+        }
+        .is_valid());
+        assert!(!ScheduleTemplateAccount {
+            tge_bps: 10_001,
+            ..template
+        }
+        .is_valid());
+        // a century at most, the periods and the cliff together
+        assert!(ScheduleTemplateAccount {
+            period_months: 12,
+            periods: 99,
+            ..template
+        }
+        .is_valid());
+        let endless = ScheduleTemplateAccount {
+            period_months: u32::MAX,
+            periods: u32::MAX,
+            ..template
+        };
+        assert!(!endless.is_valid());
+        assert_eq!(endless.end_time(tge), None);
+    }
+
+    #[test]
+    fn test_vault_stats() {
+        let week = VaultStatsAccount::WEEK;
+        let now = 1_700_000_000;
+        let mut stats = VaultStatsAccount::new(Pubkey::new_unique(), now, 255);
+        assert_eq!(borsh::to_vec(&stats).unwrap().len(), VaultStatsAccount::LEN);
+
+        stats.add_grant(100, now - week, now);
+        stats.add_grant(200, now + 2 * week, now);
+        stats.add_grant(300, now + 10 * week, now);
+        let far = now + VAULT_SCHEDULE_WEEKS as i64 * week;
+        stats.add_grant(400, far, now);
+        assert_eq!((stats.grants, stats.granted), (4, 1000));
+        // released before the stats, vested at once
+        assert_eq!(stats.vested, 100);
+        assert_eq!(stats.upcoming(VaultStatsAccount::UPCOMING_WEEKS), 500);
+        assert_eq!(stats.upcoming(3), 200);
+        assert_eq!(stats.unscheduled, 400);
+
+        stats.advance(now + 3 * week);
+        assert_eq!(stats.vested, 300);
+        assert_eq!(stats.upcoming(VaultStatsAccount::UPCOMING_WEEKS), 300);
+        stats.add_claim(200, now + 3 * week);
+        assert_eq!((stats.claimed, stats.vested), (200, 300));
+
+        // a whole schedule later, everything scheduled vested
+        stats.advance(far + week);
+        assert_eq!(stats.vested, 600);
+        assert_eq!(stats.upcoming(VAULT_SCHEDULE_WEEKS), 0);
+        stats.add_claim(100, far + week);
+        stats.add_claim(300, far + week);
+        stats.add_claim(400, far + week);
+        assert_eq!((stats.claimed, stats.vested), (1000, 1000));
+        assert_eq!(stats.unscheduled, 0);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_round_trip(account in time_lock()) {
+            let data = account.pack();
+            prop_assert_eq!(data.len(), TimeLockAccount::LEN);
+            prop_assert_eq!(TimeLockAccount::unpack(&data)?, account);
+        }
+
+        #[test]
+        fn prop_documented_offsets(account in time_lock()) {
+            // the offsets listed above `TimeLockAccount`
+            let data = account.pack();
+            prop_assert!(field_at(&data, 0, &account.timestamp));
+            prop_assert!(field_at(&data, 8, &account.secret));
+            prop_assert!(field_at(&data, 264, &account.bounty));
+            prop_assert!(field_at(&data, 272, &account.attempts));
+            prop_assert!(field_at(&data, 276, &account.premature_attempts));
+            prop_assert!(field_at(&data, 280, &account.last_attempt));
+            prop_assert!(field_at(&data, TimeLockAccount::REVEALED_AT_OFFSET, &account.revealed_at));
+            prop_assert!(field_at(&data, 296, &account.require_memo));
+            prop_assert!(field_at(&data, 297, &account.claim_memo_hash));
+            prop_assert!(field_at(&data, TimeLockAccount::AUTHORITY_OFFSET, &account.authority));
+            prop_assert!(field_at(&data, 361, &account.top_up_authority_only));
+            prop_assert!(field_at(&data, 362, &account.unlockers));
+            prop_assert!(field_at(&data, 490, &account.expires_at));
+            prop_assert!(field_at(&data, 498, &account.expiry_recipient));
+            prop_assert!(field_at(&data, 530, &account.heartbeat_interval));
+            prop_assert!(field_at(&data, 538, &account.recurrence_unit));
+            prop_assert!(field_at(&data, 539, &account.recurrence_every));
+            prop_assert!(field_at(&data, 543, &account.recurrence_window));
+            prop_assert!(field_at(&data, 547, &account.has_callback));
+            prop_assert!(field_at(&data, 548, &account.condition_account));
+            prop_assert!(field_at(&data, 580, &account.condition_offset));
+            prop_assert!(field_at(&data, 584, &account.condition_len));
+            prop_assert!(field_at(&data, 585, &account.condition_bytes));
+            prop_assert!(field_at(&data, 617, &account.predecessor));
+            prop_assert!(field_at(&data, 649, &account.has_audit_trail));
+            prop_assert!(field_at(&data, 650, &account.claim_commit_required));
+            prop_assert!(field_at(&data, TimeLockAccount::IN_PROGRESS_OFFSET, &account.in_progress));
+            prop_assert!(field_at(&data, 652, &account.funded));
+            prop_assert!(field_at(&data, 660, &account.grant_recipient));
+        }
+
+        #[test]
+        fn prop_reject_truncated(account in time_lock(), cut in 1..=TimeLockAccount::LEN) {
+            let data = account.pack();
+            prop_assert!(TimeLockAccount::unpack(&data[..data.len() - cut]).is_err());
+        }
+
+        #[test]
+        fn prop_reject_oversized(account in time_lock(), extra in vec(any::<u8>(), 1..64)) {
+            let mut data = account.pack();
+            data.extend_from_slice(&extra);
+            prop_assert!(TimeLockAccount::unpack(&data).is_err());
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! Unix timestamps in seconds, the unit of every time the program stores,
+//! see [`UnixTimestamp`].
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::error::TimeLockError;
+
+/// Latest timestamp taken for seconds, in the year 5138. Later ones are
+/// milliseconds passed by mistake, the usual error with unix timestamps.
+pub const MAX_TIMESTAMP_SECS: i64 = 99_999_999_999;
+
+/// Seconds since the unix epoch, checked not to be milliseconds.
+///
+/// Built from an `i64` of seconds, a [`SystemTime`], a `chrono::DateTime<Utc>`
+/// with the `chrono` feature or a `time::OffsetDateTime` with the `time`
+/// feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UnixTimestamp(i64);
+
+impl UnixTimestamp {
+    /// `secs` seconds since the unix epoch, rejected past
+    /// [`MAX_TIMESTAMP_SECS`] either way
+    pub fn from_secs(secs: i64) -> Result<Self, TimeLockError> {
+        if secs.unsigned_abs() > MAX_TIMESTAMP_SECS as u64 {
+            return Err(TimeLockError::TimestampNotSeconds);
+        }
+        Ok(Self(secs))
+    }
+
+    /// `duration` after `self`
+    pub fn after(self, duration: Duration) -> Result<Self, TimeLockError> {
+        let secs =
+            i64::try_from(duration.as_secs()).map_err(|_| TimeLockError::TimestampNotSeconds)?;
+        Self::from_secs(self.0.saturating_add(secs))
+    }
+
+    /// Seconds since the unix epoch, as stored on chain
+    pub fn secs(self) -> i64 {
+        self.0
+    }
+}
+
+impl TryFrom<i64> for UnixTimestamp {
+    type Error = TimeLockError;
+
+    fn try_from(secs: i64) -> Result<Self, Self::Error> {
+        Self::from_secs(secs)
+    }
+}
+
+impl TryFrom<SystemTime> for UnixTimestamp {
+    type Error = TimeLockError;
+
+    fn try_from(time: SystemTime) -> Result<Self, Self::Error> {
+        let secs = match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => i64::try_from(since.as_secs()),
+            Err(e) => i64::try_from(e.duration().as_secs()).map(|secs| -secs),
+        };
+        Self::from_secs(secs.map_err(|_| TimeLockError::TimestampNotSeconds)?)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<chrono::DateTime<chrono::Utc>> for UnixTimestamp {
+    type Error = TimeLockError;
+
+    fn try_from(time: chrono::DateTime<chrono::Utc>) -> Result<Self, Self::Error> {
+        Self::from_secs(time.timestamp())
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<time::OffsetDateTime> for UnixTimestamp {
+    type Error = TimeLockError;
+
+    fn try_from(time: time::OffsetDateTime) -> Result<Self, Self::Error> {
+        Self::from_secs(time.unix_timestamp())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reject_milliseconds() {
+        let secs = 1_700_000_000;
+        assert_eq!(UnixTimestamp::from_secs(secs).unwrap().secs(), secs);
+        assert_eq!(
+            UnixTimestamp::from_secs(secs * 1000),
+            Err(TimeLockError::TimestampNotSeconds)
+        );
+        assert_eq!(
+            UnixTimestamp::from_secs(i64::MIN),
+            Err(TimeLockError::TimestampNotSeconds)
+        );
+    }
+
+    #[test]
+    fn test_conversions() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let timestamp = UnixTimestamp::try_from(time).unwrap();
+        assert_eq!(timestamp.secs(), 1_700_000_000);
+        assert_eq!(
+            timestamp.after(Duration::from_secs(60)).unwrap().secs(),
+            1_700_000_060
+        );
+        let before = UNIX_EPOCH - Duration::from_secs(60);
+        assert_eq!(UnixTimestamp::try_from(before).unwrap().secs(), -60);
+        assert!(timestamp.after(Duration::MAX).is_err());
+    }
+}
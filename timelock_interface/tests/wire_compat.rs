@@ -0,0 +1,603 @@
+//! Byte-exact instruction payloads of every wire format the program has
+//! shipped, decoded by the current decoder.
+
+use solana_program::pubkey::Pubkey;
+use timelock_interface::instruction::EMIT_EVENT_TAG;
+use timelock_interface::{PayrollShare, TimeLockInstruction, SECRET_LENGTH};
+
+const TIMESTAMP: i64 = 1_700_000_000;
+const BOUNTY: u64 = 5000;
+const SECRET: [u8; SECRET_LENGTH] = [b's'; SECRET_LENGTH];
+
+/// The first payloads, as deployed: a one byte tag, then the little endian
+/// timestamp and the secret.
+fn legacy_initialize() -> Vec<u8> {
+    let mut data = vec![0];
+    data.extend_from_slice(&TIMESTAMP.to_le_bytes());
+    data.extend_from_slice(&SECRET);
+    data
+}
+
+/// The legacy payloads once they carried a bounty.
+fn legacy_initialize_with_bounty() -> Vec<u8> {
+    let mut data = legacy_initialize();
+    data.extend_from_slice(&BOUNTY.to_le_bytes());
+    data
+}
+
+fn discriminator(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn initialize(require_memo: bool, top_up_authority_only: bool) -> TimeLockInstruction {
+    TimeLockInstruction::InitializeTimeLock {
+        timestamp: TIMESTAMP,
+        secret: SECRET,
+        bounty: BOUNTY,
+        require_memo,
+        top_up_authority_only,
+    }
+}
+
+#[test]
+fn test_legacy_payloads() {
+    assert_eq!(legacy_initialize().len(), 1 + 8 + SECRET_LENGTH);
+    assert_eq!(
+        TimeLockInstruction::unpack(&legacy_initialize()).unwrap(),
+        TimeLockInstruction::InitializeTimeLock {
+            timestamp: TIMESTAMP,
+            secret: SECRET,
+            bounty: 0,
+            require_memo: false,
+            top_up_authority_only: false,
+        }
+    );
+    assert_eq!(
+        TimeLockInstruction::unpack(&legacy_initialize_with_bounty()).unwrap(),
+        initialize(false, false)
+    );
+    assert_eq!(
+        TimeLockInstruction::unpack(&[1]).unwrap(),
+        TimeLockInstruction::TryUnlock
+    );
+    assert_eq!(
+        TimeLockInstruction::unpack(&[EMIT_EVENT_TAG]).unwrap(),
+        TimeLockInstruction::EmitEvent
+    );
+    // the flags may follow the legacy fields too, the memo one came first
+    let mut data = legacy_initialize_with_bounty();
+    data.push(1);
+    assert_eq!(
+        TimeLockInstruction::unpack(&data).unwrap(),
+        initialize(true, false)
+    );
+    data.push(1);
+    assert_eq!(
+        TimeLockInstruction::unpack(&data).unwrap(),
+        initialize(true, true)
+    );
+    // discriminated data from before the top up flag
+    let mut data = discriminator("d5b488b5d57b600d");
+    data.extend_from_slice(&legacy_initialize_with_bounty()[1..]);
+    data.push(1);
+    assert_eq!(
+        TimeLockInstruction::unpack(&data).unwrap(),
+        initialize(true, false)
+    );
+    assert_eq!(TimeLockInstruction::legacy_tag(&data), None);
+
+    // the program logs which of them still come in
+    assert_eq!(
+        TimeLockInstruction::legacy_tag(&legacy_initialize()),
+        Some(0)
+    );
+    assert_eq!(TimeLockInstruction::legacy_tag(&[1]), Some(1));
+    assert_eq!(TimeLockInstruction::legacy_tag(&[EMIT_EVENT_TAG]), None);
+}
+
+#[test]
+fn test_discriminated_payloads() {
+    let beneficiary = Pubkey::new_from_array([7; 32]);
+    let mut initialize_data = discriminator("d5b488b5d57b600d");
+    initialize_data.extend_from_slice(&legacy_initialize_with_bounty()[1..]);
+    initialize_data.extend_from_slice(&[1, 0]);
+    let mut top_up_data = discriminator("57ee34ef9e55f788");
+    top_up_data.extend_from_slice(&BOUNTY.to_le_bytes());
+    let mut create_escrow_data = discriminator("95171fe382554eff");
+    create_escrow_data.extend_from_slice(&BOUNTY.to_le_bytes());
+    create_escrow_data.extend_from_slice(&TIMESTAMP.to_le_bytes());
+    create_escrow_data.extend_from_slice(&(TIMESTAMP + 1).to_le_bytes());
+    create_escrow_data.extend_from_slice(&500u16.to_le_bytes());
+    create_escrow_data.push(1);
+    let mut change_beneficiary_data = discriminator("1e7c9ebf7f7b5813");
+    change_beneficiary_data.extend_from_slice(beneficiary.as_ref());
+    let mut set_delegate_data = discriminator("fae4f1515514438e");
+    set_delegate_data.extend_from_slice(beneficiary.as_ref());
+    let mut lock_stake_data = discriminator("d7943f36647d2c97");
+    lock_stake_data.extend_from_slice(&TIMESTAMP.to_le_bytes());
+    lock_stake_data.extend_from_slice(beneficiary.as_ref());
+    let mut add_unlocker_data = discriminator("07308ce57ad58b09");
+    add_unlocker_data.extend_from_slice(beneficiary.as_ref());
+    let mut remove_unlocker_data = discriminator("d747c933e7c177d3");
+    remove_unlocker_data.extend_from_slice(beneficiary.as_ref());
+    let mut set_expiry_data = discriminator("de95dd3ec4f0e3a3");
+    set_expiry_data.extend_from_slice(&TIMESTAMP.to_le_bytes());
+    set_expiry_data.extend_from_slice(beneficiary.as_ref());
+    let mut set_heartbeat_data = discriminator("1967b893343702d7");
+    set_heartbeat_data.extend_from_slice(&TIMESTAMP.to_le_bytes());
+    let mut set_recurrence_data = discriminator("e195ead2917307b5");
+    set_recurrence_data.push(2);
+    set_recurrence_data.extend_from_slice(&1u32.to_le_bytes());
+    set_recurrence_data.extend_from_slice(&86_400u32.to_le_bytes());
+    let mut set_callback_data = discriminator("d57ff81b2091a51f");
+    set_callback_data.extend_from_slice(&[1, 2, 3]);
+    let mut set_unlock_instruction_data = discriminator("b52dd55a32fd6fe5");
+    set_unlock_instruction_data.extend_from_slice(&[1, 2, 3]);
+    let mut set_condition_data = discriminator("70e2f3f3b47b7244");
+    set_condition_data.extend_from_slice(beneficiary.as_ref());
+    set_condition_data.extend_from_slice(&7u32.to_le_bytes());
+    set_condition_data.push(1);
+    let mut set_predecessor_data = discriminator("0a554219f5d2a283");
+    set_predecessor_data.extend_from_slice(beneficiary.as_ref());
+    let mut create_distributor_data = discriminator("c9262ae294b8e00e");
+    create_distributor_data.extend_from_slice(&[9; 32]);
+    create_distributor_data.extend_from_slice(&7u32.to_le_bytes());
+    create_distributor_data.extend_from_slice(&BOUNTY.to_le_bytes());
+    let mut claim_with_proof_data = discriminator("632c18d4ed196fcf");
+    claim_with_proof_data.extend_from_slice(&7u32.to_le_bytes());
+    claim_with_proof_data.extend_from_slice(&BOUNTY.to_le_bytes());
+    claim_with_proof_data.extend_from_slice(&TIMESTAMP.to_le_bytes());
+    claim_with_proof_data.extend_from_slice(&[1; 32]);
+    claim_with_proof_data.extend_from_slice(&[2; 32]);
+    let mut create_vault_data = discriminator("26fdcbe006cd8d0f");
+    create_vault_data.extend_from_slice(&BOUNTY.to_le_bytes());
+    let mut create_vault_lock_data = discriminator("e9838eef9a20b23d");
+    create_vault_lock_data.extend_from_slice(&BOUNTY.to_le_bytes());
+    create_vault_lock_data.extend_from_slice(&TIMESTAMP.to_le_bytes());
+    create_vault_lock_data.push(1);
+    let mut withdraw_unallocated_data = discriminator("ef06a530806235c2");
+    withdraw_unallocated_data.extend_from_slice(&BOUNTY.to_le_bytes());
+    let mut split_lock_data = discriminator("a33cacdf6f464a29");
+    split_lock_data.extend_from_slice(&BOUNTY.to_le_bytes());
+    let mut transfer_with_payment_data = discriminator("2336cf9f55ad81d3");
+    transfer_with_payment_data.extend_from_slice(&BOUNTY.to_le_bytes());
+    let mut initialize_config_data = discriminator("02d8b7c919b6d72c");
+    initialize_config_data.extend_from_slice(beneficiary.as_ref());
+    initialize_config_data.extend_from_slice(&BOUNTY.to_le_bytes());
+    initialize_config_data.extend_from_slice(&TIMESTAMP.to_le_bytes());
+    initialize_config_data.extend_from_slice(&TIMESTAMP.to_le_bytes());
+    let mut update_config_data = discriminator("1ba045f1471ede3f");
+    update_config_data.extend_from_slice(beneficiary.as_ref());
+    update_config_data.extend_from_slice(&BOUNTY.to_le_bytes());
+    update_config_data.extend_from_slice(&TIMESTAMP.to_le_bytes());
+    update_config_data.extend_from_slice(&TIMESTAMP.to_le_bytes());
+    update_config_data.push(1);
+    update_config_data.extend_from_slice(&7u16.to_le_bytes());
+    update_config_data.extend_from_slice(&TIMESTAMP.to_le_bytes());
+    update_config_data.extend_from_slice(&30i64.to_le_bytes());
+    let mut set_paused_data = discriminator("70ab260417be8129");
+    set_paused_data.push(1);
+    let mut withdraw_fees_data = discriminator("7325fc372d20adc5");
+    withdraw_fees_data.extend_from_slice(&BOUNTY.to_le_bytes());
+    let mut add_fee_exemption_data = discriminator("0d4493d7dba3742d");
+    add_fee_exemption_data.extend_from_slice(beneficiary.as_ref());
+    let mut remove_fee_exemption_data = discriminator("81e04bfa869b3010");
+    remove_fee_exemption_data.extend_from_slice(beneficiary.as_ref());
+    let mut propose_admin_data = discriminator("9759a8a4ef88f0a0");
+    propose_admin_data.extend_from_slice(&TIMESTAMP.to_le_bytes());
+    let mut lock_stake_idempotent_data = discriminator("59f87c93606b24bc");
+    lock_stake_idempotent_data.extend_from_slice(&TIMESTAMP.to_le_bytes());
+    lock_stake_idempotent_data.extend_from_slice(beneficiary.as_ref());
+    let mut record_verified_build_data = discriminator("03d2281339047019");
+    record_verified_build_data.extend_from_slice(&[9; 32]);
+    record_verified_build_data.extend_from_slice(&[5; 20]);
+    let mut commit_claim_data = discriminator("ab17ae16fc907d49");
+    commit_claim_data.extend_from_slice(&[9; 32]);
+    let mut reveal_claim_data = discriminator("613b9e0ddb8efcb3");
+    reveal_claim_data.extend_from_slice(&[9; 32]);
+    let mut initialize_nonced_time_lock_data = discriminator("f76e7b52ed641f63");
+    initialize_nonced_time_lock_data.extend_from_slice(&BOUNTY.to_le_bytes());
+    initialize_nonced_time_lock_data.extend_from_slice(&TIMESTAMP.to_le_bytes());
+    initialize_nonced_time_lock_data.extend_from_slice(&SECRET);
+    initialize_nonced_time_lock_data.extend_from_slice(&BOUNTY.to_le_bytes());
+    initialize_nonced_time_lock_data.push(1);
+    initialize_nonced_time_lock_data.push(1);
+    let mut create_payroll_data = discriminator("4fe81eea37d45264");
+    create_payroll_data.extend_from_slice(&BOUNTY.to_le_bytes());
+    create_payroll_data.extend_from_slice(&TIMESTAMP.to_le_bytes());
+    create_payroll_data.extend_from_slice(&(TIMESTAMP + 1).to_le_bytes());
+    create_payroll_data.extend_from_slice(&7u32.to_le_bytes());
+    let mut add_payroll_entries_data = discriminator("5ed293ce02144ed9");
+    add_payroll_entries_data.extend_from_slice(&[9; 32]);
+    add_payroll_entries_data.extend_from_slice(&7u32.to_le_bytes());
+    add_payroll_entries_data.extend_from_slice(&[8; 32]);
+    add_payroll_entries_data.extend_from_slice(&1u32.to_le_bytes());
+    let mut claim_payroll_data = discriminator("a42279f4aa834d8b");
+    claim_payroll_data.extend_from_slice(&7u32.to_le_bytes());
+    let mut create_schedule_template_data = discriminator("b451faae6f2e795c");
+    create_schedule_template_data.extend_from_slice(&7u16.to_le_bytes());
+    create_schedule_template_data.extend_from_slice(&6u32.to_le_bytes());
+    create_schedule_template_data.extend_from_slice(&1u32.to_le_bytes());
+    create_schedule_template_data.extend_from_slice(&18u32.to_le_bytes());
+    let mut create_templated_payroll_data = discriminator("69b682028cbd29bb");
+    create_templated_payroll_data.extend_from_slice(&BOUNTY.to_le_bytes());
+    create_templated_payroll_data.extend_from_slice(&TIMESTAMP.to_le_bytes());
+    create_templated_payroll_data.extend_from_slice(&7u32.to_le_bytes());
+    let mut set_grant_recipient_data = discriminator("e35c5c014c2992f5");
+    set_grant_recipient_data.extend_from_slice(beneficiary.as_ref());
+    let cases = [
+        (initialize(true, false), initialize_data),
+        (
+            TimeLockInstruction::TryUnlock,
+            discriminator("6fa29244f089be24"),
+        ),
+        (
+            TimeLockInstruction::GetPosition,
+            discriminator("9cf609cbd76ceaf8"),
+        ),
+        (
+            TimeLockInstruction::LockStake {
+                timestamp: TIMESTAMP,
+                beneficiary,
+            },
+            lock_stake_data,
+        ),
+        (
+            TimeLockInstruction::ReleaseStake,
+            discriminator("7c3a56e3af1dfef1"),
+        ),
+        (TimeLockInstruction::TopUp { amount: BOUNTY }, top_up_data),
+        (
+            TimeLockInstruction::WithdrawExcess,
+            discriminator("d50c6857d42429fd"),
+        ),
+        (
+            TimeLockInstruction::CreateEscrow {
+                amount: BOUNTY,
+                release_time: TIMESTAMP,
+                refund_time: TIMESTAMP + 1,
+                penalty_bps: 500,
+                penalty_decays: true,
+            },
+            create_escrow_data,
+        ),
+        (
+            TimeLockInstruction::ClaimEscrow,
+            discriminator("14a43cce247fc967"),
+        ),
+        (
+            TimeLockInstruction::RefundEscrow,
+            discriminator("362c3cb501183cd7"),
+        ),
+        (
+            TimeLockInstruction::ChangeEscrowBeneficiary { beneficiary },
+            change_beneficiary_data,
+        ),
+        (
+            TimeLockInstruction::SetEscrowDelegate {
+                delegate: beneficiary,
+            },
+            set_delegate_data,
+        ),
+        (
+            TimeLockInstruction::RevokeEscrowDelegate,
+            discriminator("fd78922e8ecc98c7"),
+        ),
+        (
+            TimeLockInstruction::AddUnlocker {
+                unlocker: beneficiary,
+            },
+            add_unlocker_data,
+        ),
+        (
+            TimeLockInstruction::RemoveUnlocker {
+                unlocker: beneficiary,
+            },
+            remove_unlocker_data,
+        ),
+        (
+            TimeLockInstruction::ClaimEscrowEarly,
+            discriminator("4beb63ae9c5d0440"),
+        ),
+        (
+            TimeLockInstruction::SetExpiry {
+                expires_at: TIMESTAMP,
+                recipient: beneficiary,
+            },
+            set_expiry_data,
+        ),
+        (
+            TimeLockInstruction::Expire,
+            discriminator("3cbc769b6252bbaa"),
+        ),
+        (
+            TimeLockInstruction::SetHeartbeat {
+                interval: TIMESTAMP,
+            },
+            set_heartbeat_data,
+        ),
+        (
+            TimeLockInstruction::Heartbeat,
+            discriminator("ce2ff969da274ecc"),
+        ),
+        (
+            TimeLockInstruction::SetRecurrence {
+                unit: 2,
+                every: 1,
+                window: 86_400,
+            },
+            set_recurrence_data,
+        ),
+        (
+            TimeLockInstruction::SetCallback {
+                data: vec![1, 2, 3],
+            },
+            set_callback_data,
+        ),
+        (
+            TimeLockInstruction::SetUnlockInstruction {
+                data: vec![1, 2, 3],
+            },
+            set_unlock_instruction_data,
+        ),
+        (
+            TimeLockInstruction::SetCondition {
+                account: beneficiary,
+                offset: 7,
+                expected: vec![1],
+            },
+            set_condition_data,
+        ),
+        (
+            TimeLockInstruction::SetPredecessor {
+                predecessor: beneficiary,
+            },
+            set_predecessor_data,
+        ),
+        (
+            TimeLockInstruction::CreateDistributor {
+                root: [9; 32],
+                num_leaves: 7,
+                amount: BOUNTY,
+            },
+            create_distributor_data,
+        ),
+        (
+            TimeLockInstruction::ClaimWithProof {
+                index: 7,
+                amount: BOUNTY,
+                unlock_time: TIMESTAMP,
+                proof: vec![[1; 32], [2; 32]],
+            },
+            claim_with_proof_data,
+        ),
+        (
+            TimeLockInstruction::CreateVault { amount: BOUNTY },
+            create_vault_data,
+        ),
+        (
+            TimeLockInstruction::CreateVaultLock {
+                amount: BOUNTY,
+                release_time: TIMESTAMP,
+                transferable: true,
+            },
+            create_vault_lock_data,
+        ),
+        (
+            TimeLockInstruction::ClaimVaultLock,
+            discriminator("087cefcaf2ac531a"),
+        ),
+        (
+            TimeLockInstruction::WithdrawUnallocated { amount: BOUNTY },
+            withdraw_unallocated_data,
+        ),
+        (
+            TimeLockInstruction::SplitLock { amount: BOUNTY },
+            split_lock_data,
+        ),
+        (
+            TimeLockInstruction::MergeLocks,
+            discriminator("463e2fe2b177c6fa"),
+        ),
+        (
+            TimeLockInstruction::TransferVaultLock,
+            discriminator("362cfc0b1a0bc8e0"),
+        ),
+        (
+            TimeLockInstruction::TransferWithPayment { price: BOUNTY },
+            transfer_with_payment_data,
+        ),
+        (
+            TimeLockInstruction::InitializeConfig {
+                treasury: beneficiary,
+                creation_fee: BOUNTY,
+                min_duration: TIMESTAMP,
+                max_duration: TIMESTAMP,
+            },
+            initialize_config_data,
+        ),
+        (
+            TimeLockInstruction::UpdateConfig {
+                treasury: beneficiary,
+                creation_fee: BOUNTY,
+                min_duration: TIMESTAMP,
+                max_duration: TIMESTAMP,
+                paused: true,
+                claim_fee_bps: 7u16,
+                update_delay: TIMESTAMP,
+                clock_drift_tolerance: 30,
+            },
+            update_config_data,
+        ),
+        (
+            TimeLockInstruction::CollectFees,
+            discriminator("805214f232482b66"),
+        ),
+        (
+            TimeLockInstruction::SetPaused { paused: true },
+            set_paused_data,
+        ),
+        (
+            TimeLockInstruction::WithdrawFees { amount: BOUNTY },
+            withdraw_fees_data,
+        ),
+        (
+            TimeLockInstruction::AddFeeExemption { key: beneficiary },
+            add_fee_exemption_data,
+        ),
+        (
+            TimeLockInstruction::RemoveFeeExemption { key: beneficiary },
+            remove_fee_exemption_data,
+        ),
+        (
+            TimeLockInstruction::ProposeAdmin {
+                expires_at: TIMESTAMP,
+            },
+            propose_admin_data,
+        ),
+        (
+            TimeLockInstruction::AcceptAdmin,
+            discriminator("ef7a9473d75573e9"),
+        ),
+        (
+            TimeLockInstruction::ApplyConfigUpdate,
+            discriminator("29d5af1d1fb5fb1b"),
+        ),
+        (
+            TimeLockInstruction::CancelConfigUpdate,
+            discriminator("f0fcc744cd92a01d"),
+        ),
+        (
+            TimeLockInstruction::GetRevenue,
+            discriminator("f768eab61ce58d88"),
+        ),
+        (
+            TimeLockInstruction::RecordImmutability,
+            discriminator("2435221b172256fd"),
+        ),
+        (
+            TimeLockInstruction::GetImmutability,
+            discriminator("2a9284e87669ff8a"),
+        ),
+        (
+            TimeLockInstruction::LockStakeIdempotent {
+                timestamp: TIMESTAMP,
+                beneficiary,
+            },
+            lock_stake_idempotent_data,
+        ),
+        (
+            TimeLockInstruction::RecordVerifiedBuild {
+                build_hash: [9; 32],
+                source_commit: [5; 20],
+            },
+            record_verified_build_data,
+        ),
+        (
+            TimeLockInstruction::EnableAuditTrail,
+            discriminator("e1706335f1df4a95"),
+        ),
+        (
+            TimeLockInstruction::RequireClaimCommit,
+            discriminator("e8f2ddc8eae4b5c1"),
+        ),
+        (
+            TimeLockInstruction::CommitClaim {
+                commitment: [9; 32],
+            },
+            commit_claim_data,
+        ),
+        (
+            TimeLockInstruction::RevealClaim { salt: [9; 32] },
+            reveal_claim_data,
+        ),
+        (
+            TimeLockInstruction::InitializeNoncedTimeLock {
+                nonce: BOUNTY,
+                timestamp: TIMESTAMP,
+                secret: SECRET,
+                bounty: BOUNTY,
+                require_memo: true,
+                top_up_authority_only: true,
+            },
+            initialize_nonced_time_lock_data,
+        ),
+        (
+            TimeLockInstruction::CreatePayroll {
+                amount: BOUNTY,
+                start_time: TIMESTAMP,
+                end_time: TIMESTAMP + 1,
+                num_entries: 7,
+            },
+            create_payroll_data,
+        ),
+        (
+            TimeLockInstruction::AddPayrollEntries {
+                entries: vec![
+                    PayrollShare {
+                        beneficiary: Pubkey::new_from_array([9; 32]),
+                        weight: 7,
+                    },
+                    PayrollShare {
+                        beneficiary: Pubkey::new_from_array([8; 32]),
+                        weight: 1,
+                    },
+                ],
+            },
+            add_payroll_entries_data,
+        ),
+        (
+            TimeLockInstruction::ClaimPayroll { index: 7 },
+            claim_payroll_data,
+        ),
+        (
+            TimeLockInstruction::CreateScheduleTemplate {
+                tge_bps: 7,
+                cliff_months: 6,
+                period_months: 1,
+                periods: 18,
+            },
+            create_schedule_template_data,
+        ),
+        (
+            TimeLockInstruction::CreateTemplatedPayroll {
+                amount: BOUNTY,
+                start_time: TIMESTAMP,
+                num_entries: 7,
+            },
+            create_templated_payroll_data,
+        ),
+        (
+            TimeLockInstruction::ClearCallback,
+            discriminator("01505cb4b967067c"),
+        ),
+        (
+            TimeLockInstruction::ClosePayroll,
+            discriminator("c936d06b9b8da881"),
+        ),
+        (
+            TimeLockInstruction::MigrateAccount,
+            discriminator("9487e135a11ab407"),
+        ),
+        (
+            TimeLockInstruction::SetGrantRecipient {
+                recipient: beneficiary,
+            },
+            set_grant_recipient_data,
+        ),
+        (
+            TimeLockInstruction::ReleaseGrant,
+            discriminator("b55eaaa6785207b4"),
+        ),
+    ];
+    for (ix, data) in cases {
+        assert_eq!(ix.pack(), data, "encoding of {ix:?} changed");
+        assert_eq!(TimeLockInstruction::unpack(&data).unwrap(), ix);
+        // deployments predating discriminators only know tags 0 and 1, they
+        // must reject a new client rather than misread it
+        assert!(data[0] > 1 && data[0] != EMIT_EVENT_TAG, "{ix:?}");
+    }
+}
@@ -0,0 +1,319 @@
+use borsh::BorshDeserialize;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use timelock_interface::error::TimeLockError;
+use timelock_interface::state::TimeLockAccount;
+
+/// What an account of an instruction must be, shown by the account list
+/// alone. Checks against the data, like a signer matching a stored
+/// authority, stay with the processor.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Constraints {
+    signer: bool,
+    writable: bool,
+    program_owned: bool,
+    program: Option<Pubkey>,
+    pda: Option<Pubkey>,
+}
+
+impl Constraints {
+    /// Any account
+    pub(crate) const ANY: Self = Self {
+        signer: false,
+        writable: false,
+        program_owned: false,
+        program: None,
+        pda: None,
+    };
+
+    /// Signs the transaction
+    pub(crate) const fn signer(self) -> Self {
+        Self {
+            signer: true,
+            ..self
+        }
+    }
+
+    /// Is marked writable
+    pub(crate) const fn writable(self) -> Self {
+        Self {
+            writable: true,
+            ..self
+        }
+    }
+
+    /// Is owned by this program, holding its data
+    pub(crate) const fn program_owned(self) -> Self {
+        Self {
+            program_owned: true,
+            ..self
+        }
+    }
+
+    /// Is the program `id`, to invoke
+    pub(crate) const fn program(self, id: Pubkey) -> Self {
+        Self {
+            program: Some(id),
+            ..self
+        }
+    }
+
+    /// Is the program address `address`, derived by the caller
+    pub(crate) const fn pda(self, address: Pubkey) -> Self {
+        Self {
+            pda: Some(address),
+            ..self
+        }
+    }
+
+    fn check(&self, program_id: &Pubkey, account: &AccountInfo) -> Result<(), ProgramError> {
+        if self.signer && !account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if self.writable && !account.is_writable {
+            return Err(ProgramError::Immutable);
+        }
+        if self.program_owned && account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if self.program.is_some_and(|id| id != *account.key) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if self.pda.is_some_and(|address| address != *account.key) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        Ok(())
+    }
+}
+
+/// Accounts of an instruction, taken in order and checked as they are
+pub(crate) struct AccountsIter<'a, 'info> {
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'info>],
+}
+
+impl<'a, 'info> AccountsIter<'a, 'info> {
+    /// Next account, checked against `constraints`
+    pub(crate) fn next(
+        &mut self,
+        constraints: Constraints,
+    ) -> Result<&'a AccountInfo<'info>, ProgramError> {
+        let (account, rest) = self
+            .accounts
+            .split_first()
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        constraints.check(self.program_id, account)?;
+        self.accounts = rest;
+        Ok(account)
+    }
+
+    /// Next account if it is `address`, checked against `constraints`: an
+    /// optional account the client may leave out
+    pub(crate) fn next_if(
+        &mut self,
+        address: &Pubkey,
+        constraints: Constraints,
+    ) -> Result<Option<&'a AccountInfo<'info>>, ProgramError> {
+        match self.accounts.first() {
+            Some(account) if account.key == address => self.next(constraints).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// The program the accounts are checked for
+    pub(crate) fn program_id(&self) -> &'a Pubkey {
+        self.program_id
+    }
+
+    /// Address of the account `offset` places after the next one, to derive
+    /// the PDA of the next one from
+    pub(crate) fn peek_key(&self, offset: usize) -> Result<&'a Pubkey, ProgramError> {
+        self.accounts
+            .get(offset)
+            .map(|account| account.key)
+            .ok_or(ProgramError::NotEnoughAccountKeys)
+    }
+
+    /// Accounts not taken: the optional ones and the event accounts
+    pub(crate) fn remaining(&self) -> &'a [AccountInfo<'info>] {
+        self.accounts
+    }
+}
+
+/// Accounts of one instruction as a struct of their infos, each declared
+/// with its [`Constraints`] in [`Accounts::take`]. [`Accounts::validate`]
+/// enforces them all before the processor logic runs.
+pub(crate) trait Accounts<'a, 'info>: Sized {
+    /// Take the accounts of the instruction off `iter`
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError>;
+
+    /// The accounts of the instruction, checked
+    fn validate(
+        program_id: &'a Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError> {
+        Self::take(&mut AccountsIter {
+            program_id,
+            accounts,
+        })
+    }
+}
+
+/// Check `account` is the PDA of `seeds` and `bump`, the canonical bump
+/// stored in it at creation. Rederiving the address once costs a fraction of
+/// searching for the bump again.
+pub(crate) fn check_pda(
+    program_id: &Pubkey,
+    account: &AccountInfo,
+    seeds: &[&[u8]],
+    bump: u8,
+) -> ProgramResult {
+    let bump = [bump];
+    let mut seeds = seeds.to_vec();
+    seeds.push(&bump);
+    match Pubkey::create_program_address(&seeds, program_id) {
+        Ok(address) if address == *account.key => Ok(()),
+        _ => Err(ProgramError::InvalidSeeds),
+    }
+}
+
+/// Fail with `ReentrantCall` unless `lock`, a time lock back from calling
+/// its callback, still holds `expected` and `lamports`: the program called
+/// called back into this one to mutate the lock mid-call otherwise.
+pub(crate) fn check_not_reentered(
+    lock: &AccountInfo,
+    expected: &TimeLockAccount,
+    lamports: u64,
+) -> ProgramResult {
+    let untouched = lock.lamports() == lamports
+        && TimeLockAccount::try_from_slice(&lock.data.borrow()).is_ok_and(|lock| lock == *expected);
+    match untouched {
+        true => Ok(()),
+        false => Err(TimeLockError::ReentrantCall.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constraints() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = [];
+        let account = AccountInfo::new(
+            &key,
+            true,
+            false,
+            &mut lamports,
+            &mut data,
+            &program_id,
+            false,
+            0,
+        );
+        let check = |c: Constraints| c.check(&program_id, &account);
+
+        assert_eq!(check(Constraints::ANY.signer().program_owned()), Ok(()));
+        assert_eq!(
+            check(Constraints::ANY.writable()),
+            Err(ProgramError::Immutable)
+        );
+        assert_eq!(check(Constraints::ANY.pda(key)), Ok(()));
+        assert_eq!(
+            check(Constraints::ANY.pda(other)),
+            Err(ProgramError::InvalidSeeds)
+        );
+        assert_eq!(
+            check(Constraints::ANY.program(other)),
+            Err(ProgramError::IncorrectProgramId)
+        );
+
+        let accounts = [account.clone()];
+        let mut iter = AccountsIter {
+            program_id: &program_id,
+            accounts: &accounts,
+        };
+        assert_eq!(iter.peek_key(0), Ok(&key));
+        assert_eq!(
+            iter.next_if(&other, Constraints::ANY).map(|a| a.is_some()),
+            Ok(false)
+        );
+        assert_eq!(
+            iter.next_if(&key, Constraints::ANY.writable()).map(|_| ()),
+            Err(ProgramError::Immutable)
+        );
+        assert!(iter.next(Constraints::ANY.signer()).is_ok());
+        assert!(iter.remaining().is_empty());
+        assert_eq!(
+            iter.next(Constraints::ANY).map(|_| ()),
+            Err(ProgramError::NotEnoughAccountKeys)
+        );
+    }
+
+    #[test]
+    fn test_check_pda() {
+        let program_id = Pubkey::new_unique();
+        let seeds: &[&[u8]] = &[b"seed"];
+        let (key, bump) = Pubkey::find_program_address(seeds, &program_id);
+        let mut lamports = 0;
+        let mut data = [];
+        let account = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &program_id,
+            false,
+            0,
+        );
+
+        assert_eq!(check_pda(&program_id, &account, seeds, bump), Ok(()));
+        assert_eq!(
+            check_pda(&program_id, &account, seeds, bump.wrapping_sub(1)),
+            Err(ProgramError::InvalidSeeds)
+        );
+        assert_eq!(
+            check_pda(&program_id, &account, &[b"other"], bump),
+            Err(ProgramError::InvalidSeeds)
+        );
+    }
+
+    #[test]
+    fn test_check_not_reentered() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let mut lamports = 5000;
+        let mut data = vec![0; TimeLockAccount::LEN];
+        data[TimeLockAccount::IN_PROGRESS_OFFSET] = 1;
+        let expected = TimeLockAccount::try_from_slice(&data).unwrap();
+        let account = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &program_id,
+            false,
+            0,
+        );
+
+        assert_eq!(check_not_reentered(&account, &expected, 5000), Ok(()));
+        // lamports taken out of the lock meanwhile
+        assert_eq!(
+            check_not_reentered(&account, &expected, 6000),
+            Err(TimeLockError::ReentrantCall.into())
+        );
+        // the lock written to meanwhile
+        account.data.borrow_mut()[TimeLockAccount::REVEALED_AT_OFFSET] = 1;
+        assert_eq!(
+            check_not_reentered(&account, &expected, 5000),
+            Err(TimeLockError::ReentrantCall.into())
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program::invoke_signed;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::sysvar::Sysvar as _;
+use solana_program::{msg, system_instruction, system_program};
+use timelock_interface::error::TimeLockError;
+use timelock_interface::instruction::TimeLockInstruction;
+use timelock_interface::pda::{audit_address, AUDIT_SEED};
+use timelock_interface::state::{
+    AuditEntry, AuditTrailAccount, FixedSizeAccount, TimeLockAccount, MAX_AUDIT_ENTRIES,
+};
+
+use crate::accounts::{check_pda, Accounts, AccountsIter, Constraints};
+use crate::event::EventEmitter;
+use crate::TimeLockEvent;
+
+/// Accounts of `EnableAuditTrail`
+struct EnableAuditTrailAccounts<'a, 'info> {
+    lock: &'a AccountInfo<'info>,
+    authority: &'a AccountInfo<'info>,
+    audit: &'a AccountInfo<'info>,
+    bump: u8,
+    system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for EnableAuditTrailAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        let (address, bump) = audit_address(iter.program_id(), iter.peek_key(0)?);
+        Ok(Self {
+            lock: iter.next(Constraints::ANY.writable().program_owned())?,
+            authority: iter.next(Constraints::ANY.signer().writable())?,
+            audit: iter.next(Constraints::ANY.writable().pda(address))?,
+            bump,
+            system_program: iter.next(Constraints::ANY.program(system_program::id()))?,
+        })
+    }
+}
+
+pub(crate) fn enable_audit_trail(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = EnableAuditTrailAccounts::validate(program_id, accounts)?;
+
+    let mut timelock_data = TimeLockAccount::try_from_slice(&ctx.lock.data.borrow())?;
+    if *ctx.authority.key != timelock_data.authority {
+        return Err(TimeLockError::AuthorityRequired.into());
+    }
+    if timelock_data.has_audit_trail {
+        msg!("TimeLock already keeps an audit trail");
+        return Ok(());
+    }
+
+    let space = AuditTrailAccount::LEN;
+    invoke_signed(
+        &system_instruction::create_account(
+            ctx.authority.key,
+            ctx.audit.key,
+            AuditTrailAccount::rent_exempt_lamports(&Rent::get()?),
+            space as u64,
+            program_id,
+        ),
+        &[
+            ctx.authority.clone(),
+            ctx.audit.clone(),
+            ctx.system_program.clone(),
+        ],
+        &[&[AUDIT_SEED, ctx.lock.key.as_ref(), &[ctx.bump]]],
+    )?;
+    let trail = AuditTrailAccount {
+        lock: *ctx.lock.key,
+        total: 0,
+        entries: [AuditEntry::default(); MAX_AUDIT_ENTRIES],
+        bump: ctx.bump,
+    };
+    trail.serialize(&mut &mut ctx.audit.data.borrow_mut()[..])?;
+
+    timelock_data.has_audit_trail = true;
+    timelock_data.serialize(&mut &mut ctx.lock.data.borrow_mut()[..])?;
+
+    msg!("TimeLock keeps an audit trail at {}", ctx.audit.key);
+    emitter.emit(&TimeLockEvent::AuditTrailEnabled {
+        lock: *ctx.lock.key,
+        authority: *ctx.authority.key,
+    })
+}
+
+/// Time lock an instruction mutates, as it was before, when it keeps an audit
+/// trail. Read ahead of the instruction, which may close the lock.
+pub(crate) struct Audited<'a, 'info> {
+    lock: &'a AccountInfo<'info>,
+    before: TimeLockAccount,
+    lamports: u64,
+    instruction: [u8; 8],
+}
+
+/// The lock `instruction` mutates, its first account, when it keeps an audit
+/// trail. Anything else about the lock is left for the processor to reject.
+pub(crate) fn audited<'a, 'info>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'info>],
+    instruction: &TimeLockInstruction,
+) -> Option<Audited<'a, 'info>> {
+    if !matches!(
+        instruction,
+        TimeLockInstruction::TryUnlock
+            | TimeLockInstruction::TopUp { .. }
+            | TimeLockInstruction::SetGrantRecipient { .. }
+            | TimeLockInstruction::ReleaseGrant
+            | TimeLockInstruction::WithdrawExcess
+            | TimeLockInstruction::AddUnlocker { .. }
+            | TimeLockInstruction::RemoveUnlocker { .. }
+            | TimeLockInstruction::SetExpiry { .. }
+            | TimeLockInstruction::Expire
+            | TimeLockInstruction::SetHeartbeat { .. }
+            | TimeLockInstruction::Heartbeat
+            | TimeLockInstruction::SetRecurrence { .. }
+            | TimeLockInstruction::SetCallback { .. }
+            | TimeLockInstruction::SetUnlockInstruction { .. }
+            | TimeLockInstruction::ClearCallback
+            | TimeLockInstruction::SetCondition { .. }
+            | TimeLockInstruction::SetPredecessor { .. }
+            | TimeLockInstruction::RequireClaimCommit
+            | TimeLockInstruction::RevealClaim { .. }
+    ) {
+        return None;
+    }
+    let lock = accounts.first().filter(|a| a.owner == program_id)?;
+    let before = TimeLockAccount::try_from_slice(&lock.data.borrow()).ok()?;
+    if !before.has_audit_trail {
+        return None;
+    }
+    Some(Audited {
+        lock,
+        before,
+        lamports: lock.lamports(),
+        instruction: instruction.pack().first_chunk::<8>().copied()?,
+    })
+}
+
+/// Whether `account` is an audit trail, which instructions taking a variable
+/// number of accounts must not count among theirs
+pub(crate) fn is_audit_trail(program_id: &Pubkey, account: &AccountInfo) -> bool {
+    account.owner == program_id && account.data_len() == AuditTrailAccount::LEN
+}
+
+impl Audited<'_, '_> {
+    /// Append the instruction, once it succeeded, to the audit trail of the
+    /// lock found among `accounts`
+    pub(crate) fn record(self, program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        // anyone may try to unlock or top up: for the price of a transaction
+        // they would fill the trail, so besides the mutations of the authority
+        // only reveals and payouts, lamports leaving the lock, are recorded
+        let by_authority = accounts
+            .iter()
+            .any(|a| a.is_signer && *a.key == self.before.authority);
+        if !by_authority {
+            let revealed = TimeLockAccount::try_from_slice(&self.lock.data.borrow())
+                .is_ok_and(|after| after.revealed_at != self.before.revealed_at);
+            if !revealed && self.lock.lamports() >= self.lamports {
+                return Ok(());
+            }
+        }
+        // the audit trail PDA is the program owned trail of the lock, its
+        // stored bump spares searching for the address
+        let (audit_account, mut trail) = accounts
+            .iter()
+            .filter(|a| is_audit_trail(program_id, a))
+            .find_map(|a| {
+                let trail = AuditTrailAccount::try_from_slice(&a.data.borrow()).ok()?;
+                (trail.lock == *self.lock.key).then_some((a, trail))
+            })
+            .ok_or(TimeLockError::AuditTrailMissing)?;
+        check_pda(
+            program_id,
+            audit_account,
+            &[AUDIT_SEED, self.lock.key.as_ref()],
+            trail.bump,
+        )?;
+
+        let clock = Clock::get()?;
+        trail.append(AuditEntry {
+            instruction: self.instruction,
+            actor: accounts
+                .iter()
+                .find(|a| a.is_signer)
+                .map_or_else(Pubkey::default, |a| *a.key),
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        trail.serialize(&mut &mut audit_account.data.borrow_mut()[..])?;
+        Ok(())
+    }
+}
@@ -0,0 +1,252 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::program::invoke_signed;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::sysvar::Sysvar as _;
+use solana_program::{msg, system_instruction, system_program};
+use timelock_interface::error::TimeLockError;
+use timelock_interface::pda::{callback_address, CALLBACK_SEED};
+use timelock_interface::state::{
+    CallbackAccount, FixedSizeAccount, TimeLockAccount, MAX_CALLBACK_ACCOUNTS, MAX_CALLBACK_DATA,
+};
+
+use crate::accounts::{check_pda, Accounts, AccountsIter, Constraints};
+use crate::audit;
+use crate::event::EventEmitter;
+use crate::TimeLockEvent;
+
+/// Accounts of `SetCallback` and `SetUnlockInstruction`
+struct SetCallbackAccounts<'a, 'info> {
+    lock: &'a AccountInfo<'info>,
+    authority: &'a AccountInfo<'info>,
+    callback: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+    target_program: &'a AccountInfo<'info>,
+    /// The accounts of the call, then the event accounts or the audit trail
+    remaining: &'a [AccountInfo<'info>],
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for SetCallbackAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            lock: iter.next(Constraints::ANY.writable().program_owned())?,
+            authority: iter.next(Constraints::ANY.signer().writable())?,
+            callback: iter.next(Constraints::ANY.writable())?,
+            system_program: iter.next(Constraints::ANY.program(system_program::id()))?,
+            target_program: iter.next(Constraints::ANY)?,
+            remaining: iter.remaining(),
+        })
+    }
+}
+
+pub(crate) fn set_callback(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+    verbatim: bool,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = SetCallbackAccounts::validate(program_id, accounts)?;
+    // the accounts of the call run up to the event accounts or the audit trail
+    let call_accounts: Vec<_> = ctx
+        .remaining
+        .iter()
+        .take_while(|a| !emitter.is_event_authority(a.key) && !audit::is_audit_trail(program_id, a))
+        .collect();
+
+    let mut timelock_data = TimeLockAccount::try_from_slice(&ctx.lock.data.borrow())?;
+    if *ctx.authority.key != timelock_data.authority {
+        return Err(TimeLockError::AuthorityRequired.into());
+    }
+    if Clock::get()?.unix_timestamp >= timelock_data.timestamp {
+        return Err(TimeLockError::LockMatured.into());
+    }
+    // a call that cannot succeed would keep the secret locked for good
+    if !ctx.target_program.executable || ctx.target_program.key == program_id {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if call_accounts.len() > MAX_CALLBACK_ACCOUNTS {
+        return Err(TimeLockError::TooManyCallbackAccounts.into());
+    }
+    if data.len() > MAX_CALLBACK_DATA {
+        return Err(TimeLockError::InstructionTooLong.into());
+    }
+
+    let seeds: &[&[u8]] = &[CALLBACK_SEED, ctx.lock.key.as_ref()];
+    let bump = if ctx.callback.owner == program_id {
+        // replaced in place, the bump stored at creation vouches for the address
+        let bump = CallbackAccount::try_from_slice(&ctx.callback.data.borrow())?.bump;
+        check_pda(program_id, ctx.callback, seeds, bump)?;
+        bump
+    } else {
+        let (address, bump) = callback_address(program_id, ctx.lock.key);
+        if *ctx.callback.key != address {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let space = CallbackAccount::LEN;
+        invoke_signed(
+            &system_instruction::create_account(
+                ctx.authority.key,
+                ctx.callback.key,
+                CallbackAccount::rent_exempt_lamports(&Rent::get()?),
+                space as u64,
+                program_id,
+            ),
+            &[
+                ctx.authority.clone(),
+                ctx.callback.clone(),
+                ctx.system_program.clone(),
+            ],
+            &[&[CALLBACK_SEED, ctx.lock.key.as_ref(), &[bump]]],
+        )?;
+        bump
+    };
+    let mut callback = CallbackAccount {
+        lock: *ctx.lock.key,
+        program: *ctx.target_program.key,
+        accounts: [Pubkey::default(); MAX_CALLBACK_ACCOUNTS],
+        writable: 0,
+        data_len: data.len() as u8,
+        data: [0; MAX_CALLBACK_DATA],
+        verbatim,
+        bump,
+    };
+    for (i, account) in call_accounts.iter().enumerate() {
+        callback.accounts[i] = *account.key;
+        if account.is_writable {
+            callback.writable |= 1 << i;
+        }
+    }
+    callback.data[..data.len()].copy_from_slice(data);
+    callback.serialize(&mut &mut ctx.callback.data.borrow_mut()[..])?;
+
+    timelock_data.has_callback = true;
+    timelock_data.serialize(&mut &mut ctx.lock.data.borrow_mut()[..])?;
+
+    msg!("TimeLock calls {} when revealed", ctx.target_program.key);
+    emitter.emit(&TimeLockEvent::CallbackSet {
+        lock: *ctx.lock.key,
+        program: *ctx.target_program.key,
+    })
+}
+
+/// Accounts of `ClearCallback`
+struct ClearCallbackAccounts<'a, 'info> {
+    lock: &'a AccountInfo<'info>,
+    authority: &'a AccountInfo<'info>,
+    callback: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for ClearCallbackAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            lock: iter.next(Constraints::ANY.writable().program_owned())?,
+            authority: iter.next(Constraints::ANY.signer().writable())?,
+            callback: iter.next(Constraints::ANY.writable())?,
+        })
+    }
+}
+
+pub(crate) fn clear_callback(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = ClearCallbackAccounts::validate(program_id, accounts)?;
+
+    let mut timelock_data = TimeLockAccount::try_from_slice(&ctx.lock.data.borrow())?;
+    if *ctx.authority.key != timelock_data.authority {
+        return Err(TimeLockError::AuthorityRequired.into());
+    }
+    // until then the call is part of what the lock promises on reveal
+    if Clock::get()?.unix_timestamp < timelock_data.timestamp {
+        return Err(TimeLockError::LockNotMatured.into());
+    }
+    if ctx.callback.owner != program_id {
+        return Err(TimeLockError::CallbackAccountMissing.into());
+    }
+    let callback = CallbackAccount::try_from_slice(&ctx.callback.data.borrow())?;
+    check_pda(
+        program_id,
+        ctx.callback,
+        &[CALLBACK_SEED, ctx.lock.key.as_ref()],
+        callback.bump,
+    )?;
+
+    let rent = ctx.callback.lamports();
+    **ctx.callback.try_borrow_mut_lamports()? = 0;
+    **ctx.authority.try_borrow_mut_lamports()? += rent;
+    ctx.callback.data.borrow_mut().fill(0);
+
+    timelock_data.has_callback = false;
+    timelock_data.serialize(&mut &mut ctx.lock.data.borrow_mut()[..])?;
+
+    msg!("TimeLock no longer calls {}", callback.program);
+    emitter.emit(&TimeLockEvent::CallbackCleared {
+        lock: *ctx.lock.key,
+        program: callback.program,
+    })
+}
+
+/// Call the callback of `lock`, with `secret` unless verbatim, finding its accounts among
+/// `accounts` by key, returning the program called.
+pub(crate) fn invoke_callback(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    lock: &Pubkey,
+    secret: &[u8],
+) -> Result<Pubkey, ProgramError> {
+    let find = |key: &Pubkey| {
+        accounts
+            .iter()
+            .find(|a| a.key == key)
+            .ok_or(TimeLockError::CallbackAccountMissing)
+    };
+    // the callback PDA is the program owned callback of `lock`, its stored
+    // bump spares searching for the address
+    let (callback_account, callback) = accounts
+        .iter()
+        .filter(|a| a.owner == program_id && a.data_len() == CallbackAccount::LEN)
+        .find_map(|a| {
+            let callback = CallbackAccount::try_from_slice(&a.data.borrow()).ok()?;
+            (callback.lock == *lock).then_some((a, callback))
+        })
+        .ok_or(TimeLockError::CallbackAccountMissing)?;
+    check_pda(
+        program_id,
+        callback_account,
+        &[CALLBACK_SEED, lock.as_ref()],
+        callback.bump,
+    )?;
+    let (address, bump) = (*callback_account.key, callback.bump);
+
+    let mut metas = match callback.verbatim {
+        true => vec![],
+        false => vec![AccountMeta::new_readonly(address, true)],
+    };
+    metas.extend(callback.account_metas());
+    for meta in metas.iter_mut().filter(|meta| meta.pubkey == address) {
+        meta.is_signer = true;
+    }
+    let mut infos = metas
+        .iter()
+        .map(|meta| find(&meta.pubkey).cloned())
+        .collect::<Result<Vec<_>, _>>()?;
+    infos.push(find(&callback.program)?.clone());
+    let mut data = callback.data().to_vec();
+    if !callback.verbatim {
+        data.extend_from_slice(secret);
+    }
+    invoke_signed(
+        &Instruction {
+            program_id: callback.program,
+            accounts: metas,
+            data,
+        },
+        &infos,
+        &[&[CALLBACK_SEED, lock.as_ref(), &[bump]]],
+    )?;
+    Ok(callback.program)
+}
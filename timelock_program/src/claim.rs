@@ -0,0 +1,198 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program::invoke_signed;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::sysvar::Sysvar as _;
+use solana_program::{msg, system_instruction, system_program};
+use timelock_interface::error::TimeLockError;
+use timelock_interface::pda::{claim_commitment_address, CLAIM_COMMITMENT_SEED};
+use timelock_interface::state::{ClaimCommitmentAccount, FixedSizeAccount, TimeLockAccount};
+
+use crate::accounts::{check_pda, Accounts, AccountsIter, Constraints};
+use crate::event::EventEmitter;
+use crate::{pay_bounty, LockAuthorityAccounts, TimeLockEvent};
+
+pub(crate) fn require_claim_commit(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = LockAuthorityAccounts::validate(program_id, accounts)?;
+
+    let mut timelock_data = TimeLockAccount::try_from_slice(&ctx.lock.data.borrow())?;
+    if *ctx.authority.key != timelock_data.authority {
+        return Err(TimeLockError::AuthorityRequired.into());
+    }
+    // crankers count on the bounty they were offered
+    if Clock::get()?.unix_timestamp >= timelock_data.timestamp {
+        return Err(TimeLockError::LockMatured.into());
+    }
+    timelock_data.claim_commit_required = true;
+    timelock_data.serialize(&mut &mut ctx.lock.data.borrow_mut()[..])?;
+
+    msg!("TimeLock bounty only paid to committed claims");
+    emitter.emit(&TimeLockEvent::ClaimCommitRequired {
+        lock: *ctx.lock.key,
+    })
+}
+
+/// Accounts of `CommitClaim`
+struct CommitClaimAccounts<'a, 'info> {
+    lock: &'a AccountInfo<'info>,
+    committer: &'a AccountInfo<'info>,
+    commitment: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for CommitClaimAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            lock: iter.next(Constraints::ANY.program_owned())?,
+            committer: iter.next(Constraints::ANY.signer().writable())?,
+            commitment: iter.next(Constraints::ANY.writable())?,
+            system_program: iter.next(Constraints::ANY.program(system_program::id()))?,
+        })
+    }
+}
+
+pub(crate) fn commit_claim(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    commitment: [u8; 32],
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = CommitClaimAccounts::validate(program_id, accounts)?;
+
+    let seeds: &[&[u8]] = &[
+        CLAIM_COMMITMENT_SEED,
+        ctx.lock.key.as_ref(),
+        ctx.committer.key.as_ref(),
+    ];
+    let bump = if ctx.commitment.owner == program_id {
+        // replaced in place, the bump stored at creation vouches for the address
+        let bump = ClaimCommitmentAccount::try_from_slice(&ctx.commitment.data.borrow())?.bump;
+        check_pda(program_id, ctx.commitment, seeds, bump)?;
+        bump
+    } else {
+        let (address, bump) = claim_commitment_address(program_id, ctx.lock.key, ctx.committer.key);
+        if *ctx.commitment.key != address {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let space = ClaimCommitmentAccount::LEN;
+        invoke_signed(
+            &system_instruction::create_account(
+                ctx.committer.key,
+                ctx.commitment.key,
+                ClaimCommitmentAccount::rent_exempt_lamports(&Rent::get()?),
+                space as u64,
+                program_id,
+            ),
+            &[
+                ctx.committer.clone(),
+                ctx.commitment.clone(),
+                ctx.system_program.clone(),
+            ],
+            &[&[
+                CLAIM_COMMITMENT_SEED,
+                ctx.lock.key.as_ref(),
+                ctx.committer.key.as_ref(),
+                &[bump],
+            ]],
+        )?;
+        bump
+    };
+    let claim = ClaimCommitmentAccount {
+        lock: *ctx.lock.key,
+        committer: *ctx.committer.key,
+        commitment,
+        slot: Clock::get()?.slot,
+        bump,
+    };
+    claim.serialize(&mut &mut ctx.commitment.data.borrow_mut()[..])?;
+
+    msg!("Claim of the bounty committed by {}", ctx.committer.key);
+    emitter.emit(&TimeLockEvent::ClaimCommitted {
+        lock: *ctx.lock.key,
+        committer: *ctx.committer.key,
+    })
+}
+
+/// Accounts of `RevealClaim`
+struct RevealClaimAccounts<'a, 'info> {
+    lock: &'a AccountInfo<'info>,
+    commitment: &'a AccountInfo<'info>,
+    committer: &'a AccountInfo<'info>,
+    destination: &'a AccountInfo<'info>,
+    /// The accounts paying out the bounty, then the event accounts
+    remaining: &'a [AccountInfo<'info>],
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for RevealClaimAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            lock: iter.next(Constraints::ANY.writable().program_owned())?,
+            commitment: iter.next(Constraints::ANY.writable().program_owned())?,
+            committer: iter.next(Constraints::ANY.writable())?,
+            destination: iter.next(Constraints::ANY.writable())?,
+            remaining: iter.remaining(),
+        })
+    }
+}
+
+pub(crate) fn reveal_claim(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    salt: &[u8; 32],
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = RevealClaimAccounts::validate(program_id, accounts)?;
+
+    let mut timelock_data = TimeLockAccount::try_from_slice(&ctx.lock.data.borrow())?;
+    let claim = ClaimCommitmentAccount::try_from_slice(&ctx.commitment.data.borrow())?;
+    if claim.lock != *ctx.lock.key || claim.committer != *ctx.committer.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    check_pda(
+        program_id,
+        ctx.commitment,
+        &[
+            CLAIM_COMMITMENT_SEED,
+            ctx.lock.key.as_ref(),
+            ctx.committer.key.as_ref(),
+        ],
+        claim.bump,
+    )?;
+    // a claim copied in flight cannot have been committed before it
+    if Clock::get()?.slot <= claim.slot {
+        return Err(TimeLockError::ClaimCommitmentTooRecent.into());
+    }
+    if ClaimCommitmentAccount::commitment(ctx.destination.key, salt) != claim.commitment {
+        return Err(TimeLockError::ClaimCommitmentMismatch.into());
+    }
+    if timelock_data.has_unlockers() && !timelock_data.allows_unlocker(&claim.committer) {
+        return Err(TimeLockError::UnlockerNotAllowed.into());
+    }
+
+    // the commitment is spent either way, its rent back to the committer
+    let lamports = ctx.commitment.lamports();
+    **ctx.commitment.try_borrow_mut_lamports()? = 0;
+    **ctx.committer.try_borrow_mut_lamports()? += lamports;
+    ctx.commitment.data.borrow_mut().fill(0);
+
+    if timelock_data.bounty == 0 {
+        msg!("No bounty left to claim");
+        return Ok(());
+    }
+    if timelock_data.revealed_at == 0 {
+        return Err(TimeLockError::SecretNotRevealed.into());
+    }
+    pay_bounty(
+        program_id,
+        &emitter,
+        ctx.lock,
+        &mut timelock_data,
+        ctx.destination,
+        ctx.remaining,
+    )
+}
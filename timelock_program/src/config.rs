@@ -0,0 +1,910 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program::{invoke, invoke_signed, set_return_data};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::sysvar::Sysvar as _;
+use solana_program::{msg, system_instruction, system_program};
+use timelock_interface::error::TimeLockError;
+use timelock_interface::pda::{
+    config_address, config_update_address, fee_exemption_address, CONFIG_SEED, CONFIG_UPDATE_SEED,
+    FEE_EXEMPTION_SEED,
+};
+use timelock_interface::state::{
+    ConfigAccount, ConfigUpdateAccount, FeeExemptionAccount, FixedSizeAccount, ImmutabilityReport,
+    RevenueReport,
+};
+
+use crate::accounts::{check_pda, Accounts, AccountsIter, Constraints};
+use crate::event::EventEmitter;
+use crate::idl::{deploy_slot, upgrade_authority};
+use crate::TimeLockEvent;
+
+/// A lower bound above the upper one would reject every duration
+fn check_durations(min_duration: i64, max_duration: i64) -> ProgramResult {
+    if min_duration < 0 || (max_duration != 0 && max_duration < min_duration) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(())
+}
+
+fn emit_config(emitter: &EventEmitter, key: &Pubkey, config: &ConfigAccount) -> ProgramResult {
+    emitter.emit(&TimeLockEvent::ConfigUpdated {
+        config: *key,
+        admin: config.admin,
+        treasury: config.treasury,
+        creation_fee: config.creation_fee,
+        min_duration: config.min_duration,
+        max_duration: config.max_duration,
+        paused: config.paused,
+        guardian: config.guardian,
+        claim_fee_bps: config.claim_fee_bps,
+        update_delay: config.update_delay,
+        clock_drift_tolerance: config.clock_drift_tolerance,
+    })
+}
+
+/// Accounts of `InitializeConfig`
+struct InitializeConfigAccounts<'a, 'info> {
+    config: &'a AccountInfo<'info>,
+    /// Bump of the config address
+    bump: u8,
+    admin: &'a AccountInfo<'info>,
+    programdata: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for InitializeConfigAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        let (address, bump) = config_address(iter.program_id());
+        Ok(Self {
+            config: iter.next(Constraints::ANY.writable().pda(address))?,
+            bump,
+            admin: iter.next(Constraints::ANY.signer().writable())?,
+            programdata: iter.next(Constraints::ANY)?,
+            system_program: iter.next(Constraints::ANY.program(system_program::id()))?,
+        })
+    }
+}
+
+pub(crate) fn initialize_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    treasury: Pubkey,
+    creation_fee: u64,
+    min_duration: i64,
+    max_duration: i64,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = InitializeConfigAccounts::validate(program_id, accounts)?;
+
+    // whoever deploys the program sets it up, nobody can race them to it
+    if upgrade_authority(program_id, ctx.programdata)? != Some(*ctx.admin.key) {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_durations(min_duration, max_duration)?;
+
+    let space = ConfigAccount::LEN;
+    invoke_signed(
+        &system_instruction::create_account(
+            ctx.admin.key,
+            ctx.config.key,
+            ConfigAccount::rent_exempt_lamports(&Rent::get()?),
+            space as u64,
+            program_id,
+        ),
+        &[
+            ctx.admin.clone(),
+            ctx.config.clone(),
+            ctx.system_program.clone(),
+        ],
+        &[&[CONFIG_SEED, &[ctx.bump]]],
+    )?;
+    let config = ConfigAccount {
+        admin: *ctx.admin.key,
+        treasury,
+        creation_fee,
+        min_duration,
+        max_duration,
+        paused: false,
+        guardian: Pubkey::default(),
+        claim_fee_bps: 0,
+        pending_admin: Pubkey::default(),
+        pending_admin_expires_at: 0,
+        update_delay: 0,
+        total_creation_fees: 0,
+        total_claim_fees: 0,
+        total_penalties: 0,
+        total_withdrawn: 0,
+        clock_drift_tolerance: 0,
+        immutable_slot: 0,
+        immutable_deploy_slot: 0,
+        verified_build_hash: [0; 32],
+        verified_source_commit: [0; 20],
+        bump: ctx.bump,
+    };
+    config.serialize(&mut &mut ctx.config.data.borrow_mut()[..])?;
+
+    msg!("Config initialized, admin {}", ctx.admin.key);
+    emit_config(&emitter, ctx.config.key, &config)
+}
+
+/// Accounts of `UpdateConfig`
+struct UpdateConfigAccounts<'a, 'info> {
+    config: &'a AccountInfo<'info>,
+    admin: &'a AccountInfo<'info>,
+    guardian: &'a AccountInfo<'info>,
+    config_update: &'a AccountInfo<'info>,
+    /// Bump of the config update address
+    bump: u8,
+    system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for UpdateConfigAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        let (address, bump) = config_update_address(iter.program_id());
+        Ok(Self {
+            config: iter.next(Constraints::ANY)?,
+            admin: iter.next(Constraints::ANY.signer().writable())?,
+            guardian: iter.next(Constraints::ANY)?,
+            config_update: iter.next(Constraints::ANY.writable().pda(address))?,
+            bump,
+            system_program: iter.next(Constraints::ANY.program(system_program::id()))?,
+        })
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn update_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    treasury: Pubkey,
+    creation_fee: u64,
+    min_duration: i64,
+    max_duration: i64,
+    paused: bool,
+    claim_fee_bps: u16,
+    update_delay: i64,
+    clock_drift_tolerance: i64,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = UpdateConfigAccounts::validate(program_id, accounts)?;
+
+    let config = load_config(program_id, ctx.config)?;
+    if config.admin != *ctx.admin.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_durations(min_duration, max_duration)?;
+    if claim_fee_bps > ConfigAccount::MAX_CLAIM_FEE_BPS
+        || update_delay < 0
+        || !(0..=ConfigAccount::MAX_CLOCK_DRIFT_TOLERANCE).contains(&clock_drift_tolerance)
+    {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // the delay in force, not the queued one, holds the update back
+    let eta = Clock::get()?
+        .unix_timestamp
+        .checked_add(config.update_delay)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let space = ConfigUpdateAccount::LEN;
+    invoke_signed(
+        &system_instruction::create_account(
+            ctx.admin.key,
+            ctx.config_update.key,
+            ConfigUpdateAccount::rent_exempt_lamports(&Rent::get()?),
+            space as u64,
+            program_id,
+        ),
+        &[
+            ctx.admin.clone(),
+            ctx.config_update.clone(),
+            ctx.system_program.clone(),
+        ],
+        &[&[CONFIG_UPDATE_SEED, &[ctx.bump]]],
+    )?;
+    ConfigUpdateAccount {
+        treasury,
+        creation_fee,
+        min_duration,
+        max_duration,
+        paused,
+        guardian: *ctx.guardian.key,
+        claim_fee_bps,
+        update_delay,
+        clock_drift_tolerance,
+        eta,
+        payer: *ctx.admin.key,
+        bump: ctx.bump,
+    }
+    .serialize(&mut &mut ctx.config_update.data.borrow_mut()[..])?;
+
+    msg!("Config update queued until {}", eta);
+    emitter.emit(&TimeLockEvent::ConfigUpdateQueued {
+        config_update: *ctx.config_update.key,
+        eta,
+    })
+}
+
+/// Queued update held by the config update PDA `config_update_account`
+fn load_config_update(
+    program_id: &Pubkey,
+    config_update_account: &AccountInfo,
+) -> Result<ConfigUpdateAccount, ProgramError> {
+    if config_update_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let update = ConfigUpdateAccount::try_from_slice(&config_update_account.data.borrow())?;
+    check_pda(
+        program_id,
+        config_update_account,
+        &[CONFIG_UPDATE_SEED],
+        update.bump,
+    )?;
+    Ok(update)
+}
+
+/// Close the config update PDA, its rent going back to `payer_account`
+fn close_config_update(
+    config_update_account: &AccountInfo,
+    payer_account: &AccountInfo,
+) -> ProgramResult {
+    let rent = config_update_account.lamports();
+    **config_update_account.try_borrow_mut_lamports()? = 0;
+    **payer_account.try_borrow_mut_lamports()? += rent;
+    config_update_account.data.borrow_mut().fill(0);
+    Ok(())
+}
+
+/// Accounts of `ApplyConfigUpdate`
+struct ApplyConfigUpdateAccounts<'a, 'info> {
+    config: &'a AccountInfo<'info>,
+    config_update: &'a AccountInfo<'info>,
+    admin: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for ApplyConfigUpdateAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            config: iter.next(Constraints::ANY.writable())?,
+            config_update: iter.next(Constraints::ANY.writable())?,
+            admin: iter.next(Constraints::ANY.signer().writable())?,
+        })
+    }
+}
+
+pub(crate) fn apply_config_update(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = ApplyConfigUpdateAccounts::validate(program_id, accounts)?;
+
+    let config = load_config(program_id, ctx.config)?;
+    if config.admin != *ctx.admin.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let update = load_config_update(program_id, ctx.config_update)?;
+    // an update queued before an admin handover is the new admin's to cancel
+    if update.payer != *ctx.admin.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if Clock::get()?.unix_timestamp < update.eta {
+        return Err(TimeLockError::ConfigUpdateNotReady.into());
+    }
+    let config = ConfigAccount {
+        treasury: update.treasury,
+        creation_fee: update.creation_fee,
+        min_duration: update.min_duration,
+        max_duration: update.max_duration,
+        // the update was queued before any pause since, which stays set
+        paused: config.paused || update.paused,
+        guardian: update.guardian,
+        claim_fee_bps: update.claim_fee_bps,
+        update_delay: update.update_delay,
+        clock_drift_tolerance: update.clock_drift_tolerance,
+        ..config
+    };
+    config.serialize(&mut &mut ctx.config.data.borrow_mut()[..])?;
+    close_config_update(ctx.config_update, ctx.admin)?;
+
+    msg!("Config updated, paused: {}", config.paused);
+    emit_config(&emitter, ctx.config.key, &config)
+}
+
+/// Accounts of `CancelConfigUpdate`
+struct CancelConfigUpdateAccounts<'a, 'info> {
+    config: &'a AccountInfo<'info>,
+    config_update: &'a AccountInfo<'info>,
+    authority: &'a AccountInfo<'info>,
+    payer: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for CancelConfigUpdateAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            config: iter.next(Constraints::ANY)?,
+            config_update: iter.next(Constraints::ANY.writable())?,
+            authority: iter.next(Constraints::ANY.signer())?,
+            payer: iter.next(Constraints::ANY.writable())?,
+        })
+    }
+}
+
+pub(crate) fn cancel_config_update(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = CancelConfigUpdateAccounts::validate(program_id, accounts)?;
+
+    let config = load_config(program_id, ctx.config)?;
+    // the default guardian is nobody's key, it never signs
+    if config.admin != *ctx.authority.key && config.guardian != *ctx.authority.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let update = load_config_update(program_id, ctx.config_update)?;
+    if update.payer != *ctx.payer.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    close_config_update(ctx.config_update, ctx.payer)?;
+
+    msg!("Config update cancelled by {}", ctx.authority.key);
+    emitter.emit(&TimeLockEvent::ConfigUpdateCancelled {
+        config_update: *ctx.config_update.key,
+        authority: *ctx.authority.key,
+    })
+}
+
+/// Accounts of `ProposeAdmin`
+struct ProposeAdminAccounts<'a, 'info> {
+    config: &'a AccountInfo<'info>,
+    admin: &'a AccountInfo<'info>,
+    pending_admin: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for ProposeAdminAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            config: iter.next(Constraints::ANY.writable())?,
+            admin: iter.next(Constraints::ANY.signer())?,
+            pending_admin: iter.next(Constraints::ANY)?,
+        })
+    }
+}
+
+pub(crate) fn propose_admin(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    expires_at: i64,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = ProposeAdminAccounts::validate(program_id, accounts)?;
+
+    let mut config = load_config(program_id, ctx.config)?;
+    if config.admin != *ctx.admin.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let withdrawn = *ctx.pending_admin.key == Pubkey::default();
+    if !withdrawn && expires_at <= Clock::get()?.unix_timestamp {
+        return Err(ProgramError::InvalidArgument);
+    }
+    config.pending_admin = *ctx.pending_admin.key;
+    config.pending_admin_expires_at = if withdrawn { 0 } else { expires_at };
+    config.serialize(&mut &mut ctx.config.data.borrow_mut()[..])?;
+
+    msg!("Admin proposed: {}", ctx.pending_admin.key);
+    emitter.emit(&TimeLockEvent::AdminProposed {
+        config: *ctx.config.key,
+        pending_admin: config.pending_admin,
+        expires_at: config.pending_admin_expires_at,
+    })
+}
+
+/// Accounts of `AcceptAdmin`
+struct AcceptAdminAccounts<'a, 'info> {
+    config: &'a AccountInfo<'info>,
+    pending_admin: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for AcceptAdminAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            config: iter.next(Constraints::ANY.writable())?,
+            pending_admin: iter.next(Constraints::ANY.signer())?,
+        })
+    }
+}
+
+pub(crate) fn accept_admin(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = AcceptAdminAccounts::validate(program_id, accounts)?;
+
+    let mut config = load_config(program_id, ctx.config)?;
+    // the default pending admin is nobody's key, it never signs
+    if config.pending_admin != *ctx.pending_admin.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if Clock::get()?.unix_timestamp > config.pending_admin_expires_at {
+        return Err(TimeLockError::AdminProposalExpired.into());
+    }
+    config.admin = config.pending_admin;
+    config.pending_admin = Pubkey::default();
+    config.pending_admin_expires_at = 0;
+    config.serialize(&mut &mut ctx.config.data.borrow_mut()[..])?;
+
+    msg!("Admin accepted: {}", config.admin);
+    emit_config(&emitter, ctx.config.key, &config)
+}
+
+/// Accounts of `SetPaused` and `RecordVerifiedBuild`
+struct ConfigAuthorityAccounts<'a, 'info> {
+    config: &'a AccountInfo<'info>,
+    authority: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for ConfigAuthorityAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            config: iter.next(Constraints::ANY.writable())?,
+            authority: iter.next(Constraints::ANY.signer())?,
+        })
+    }
+}
+
+pub(crate) fn set_paused(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    paused: bool,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = ConfigAuthorityAccounts::validate(program_id, accounts)?;
+
+    let mut config = load_config(program_id, ctx.config)?;
+    // the default guardian is nobody's key, it never signs
+    if config.admin != *ctx.authority.key && config.guardian != *ctx.authority.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    config.paused = paused;
+    config.serialize(&mut &mut ctx.config.data.borrow_mut()[..])?;
+
+    msg!("Paused: {}", paused);
+    emit_config(&emitter, ctx.config.key, &config)
+}
+
+/// Accounts of `CollectFees`
+struct CollectFeesAccounts<'a, 'info> {
+    config: &'a AccountInfo<'info>,
+    treasury: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for CollectFeesAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            config: iter.next(Constraints::ANY.writable())?,
+            treasury: iter.next(Constraints::ANY.writable())?,
+        })
+    }
+}
+
+pub(crate) fn collect_fees(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = CollectFeesAccounts::validate(program_id, accounts)?;
+
+    let mut config = load_config(program_id, ctx.config)?;
+    if config.treasury != *ctx.treasury.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let amount = fees(ctx.config)?;
+    config.total_withdrawn = config.total_withdrawn.saturating_add(amount);
+    config.serialize(&mut &mut ctx.config.data.borrow_mut()[..])?;
+    **ctx.config.try_borrow_mut_lamports()? -= amount;
+    **ctx.treasury.try_borrow_mut_lamports()? += amount;
+
+    msg!("Collected {} lamports of fees", amount);
+    emitter.emit(&TimeLockEvent::FeesCollected {
+        treasury: *ctx.treasury.key,
+        amount,
+    })
+}
+
+/// Accounts of `WithdrawFees`
+struct WithdrawFeesAccounts<'a, 'info> {
+    config: &'a AccountInfo<'info>,
+    admin: &'a AccountInfo<'info>,
+    recipient: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for WithdrawFeesAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            config: iter.next(Constraints::ANY.writable())?,
+            admin: iter.next(Constraints::ANY.signer())?,
+            recipient: iter.next(Constraints::ANY.writable())?,
+        })
+    }
+}
+
+pub(crate) fn withdraw_fees(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = WithdrawFeesAccounts::validate(program_id, accounts)?;
+
+    let mut config = load_config(program_id, ctx.config)?;
+    if config.admin != *ctx.admin.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if amount > fees(ctx.config)? {
+        return Err(ProgramError::InsufficientFunds);
+    }
+    config.total_withdrawn = config.total_withdrawn.saturating_add(amount);
+    config.serialize(&mut &mut ctx.config.data.borrow_mut()[..])?;
+    **ctx.config.try_borrow_mut_lamports()? -= amount;
+    **ctx.recipient.try_borrow_mut_lamports()? += amount;
+
+    msg!("Withdrew {} lamports of fees", amount);
+    emitter.emit(&TimeLockEvent::FeesWithdrawn {
+        recipient: *ctx.recipient.key,
+        amount,
+    })
+}
+
+/// Accounts of `AddFeeExemption`
+struct AddFeeExemptionAccounts<'a, 'info> {
+    config: &'a AccountInfo<'info>,
+    admin: &'a AccountInfo<'info>,
+    fee_exemption: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for AddFeeExemptionAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            config: iter.next(Constraints::ANY)?,
+            admin: iter.next(Constraints::ANY.signer().writable())?,
+            fee_exemption: iter.next(Constraints::ANY.writable())?,
+            system_program: iter.next(Constraints::ANY.program(system_program::id()))?,
+        })
+    }
+}
+
+pub(crate) fn add_fee_exemption(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    key: Pubkey,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = AddFeeExemptionAccounts::validate(program_id, accounts)?;
+
+    let config = load_config(program_id, ctx.config)?;
+    if config.admin != *ctx.admin.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let (address, bump) = fee_exemption_address(program_id, &key);
+    if *ctx.fee_exemption.key != address {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let space = FeeExemptionAccount::LEN;
+    invoke_signed(
+        &system_instruction::create_account(
+            ctx.admin.key,
+            ctx.fee_exemption.key,
+            FeeExemptionAccount::rent_exempt_lamports(&Rent::get()?),
+            space as u64,
+            program_id,
+        ),
+        &[
+            ctx.admin.clone(),
+            ctx.fee_exemption.clone(),
+            ctx.system_program.clone(),
+        ],
+        &[&[FEE_EXEMPTION_SEED, key.as_ref(), &[bump]]],
+    )?;
+    FeeExemptionAccount { key, bump }
+        .serialize(&mut &mut ctx.fee_exemption.data.borrow_mut()[..])?;
+
+    msg!("Fee exemption added for {}", key);
+    emitter.emit(&TimeLockEvent::FeeExemptionAdded { key })
+}
+
+/// Accounts of `RemoveFeeExemption`
+struct RemoveFeeExemptionAccounts<'a, 'info> {
+    config: &'a AccountInfo<'info>,
+    admin: &'a AccountInfo<'info>,
+    fee_exemption: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for RemoveFeeExemptionAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            config: iter.next(Constraints::ANY)?,
+            admin: iter.next(Constraints::ANY.signer().writable())?,
+            fee_exemption: iter.next(Constraints::ANY.writable())?,
+        })
+    }
+}
+
+pub(crate) fn remove_fee_exemption(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    key: Pubkey,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = RemoveFeeExemptionAccounts::validate(program_id, accounts)?;
+
+    let config = load_config(program_id, ctx.config)?;
+    if config.admin != *ctx.admin.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if ctx.fee_exemption.owner != program_id {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let exemption = FeeExemptionAccount::try_from_slice(&ctx.fee_exemption.data.borrow())
+        .map_err(|_| ProgramError::InvalidSeeds)?;
+    check_pda(
+        program_id,
+        ctx.fee_exemption,
+        &[FEE_EXEMPTION_SEED, key.as_ref()],
+        exemption.bump,
+    )?;
+
+    let rent = ctx.fee_exemption.lamports();
+    **ctx.fee_exemption.try_borrow_mut_lamports()? = 0;
+    **ctx.admin.try_borrow_mut_lamports()? += rent;
+    ctx.fee_exemption.data.borrow_mut().fill(0);
+
+    msg!("Fee exemption removed for {}", key);
+    emitter.emit(&TimeLockEvent::FeeExemptionRemoved { key })
+}
+
+/// Whether the fee exemption PDA of `key` is among `accounts`, recognized by
+/// its stored bump rather than by searching for its address
+fn fee_exempt(program_id: &Pubkey, accounts: &[AccountInfo], key: &Pubkey) -> bool {
+    accounts.iter().any(|account| {
+        account.owner == program_id
+            && account.data_len() == FeeExemptionAccount::LEN
+            && FeeExemptionAccount::try_from_slice(&account.data.borrow()).is_ok_and(|exemption| {
+                exemption.key == *key
+                    && check_pda(
+                        program_id,
+                        account,
+                        &[FEE_EXEMPTION_SEED, key.as_ref()],
+                        exemption.bump,
+                    )
+                    .is_ok()
+            })
+    })
+}
+
+/// Accounts of `GetRevenue` and `GetImmutability`
+struct ConfigReportAccounts<'a, 'info> {
+    config: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for ConfigReportAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            config: iter.next(Constraints::ANY)?,
+        })
+    }
+}
+
+pub(crate) fn get_revenue(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let ctx = ConfigReportAccounts::validate(program_id, accounts)?;
+
+    let config = load_config(program_id, ctx.config)?;
+    let report = RevenueReport::new(&config, fees(ctx.config)?);
+    set_return_data(&borsh::to_vec(&report)?);
+    Ok(())
+}
+
+/// Accounts of `RecordImmutability`
+struct RecordImmutabilityAccounts<'a, 'info> {
+    config: &'a AccountInfo<'info>,
+    programdata: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for RecordImmutabilityAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            config: iter.next(Constraints::ANY.writable())?,
+            programdata: iter.next(Constraints::ANY)?,
+        })
+    }
+}
+
+pub(crate) fn record_immutability(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = RecordImmutabilityAccounts::validate(program_id, accounts)?;
+
+    let mut config = load_config(program_id, ctx.config)?;
+    if config.immutable_slot != 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    if upgrade_authority(program_id, ctx.programdata)?.is_some() {
+        return Err(TimeLockError::ProgramUpgradeable.into());
+    }
+    config.immutable_slot = Clock::get()?.slot;
+    config.immutable_deploy_slot = deploy_slot(ctx.programdata)?;
+    config.serialize(&mut &mut ctx.config.data.borrow_mut()[..])?;
+
+    msg!(
+        "Program immutable, last deployed at slot {}",
+        config.immutable_deploy_slot
+    );
+    emitter.emit(&TimeLockEvent::ImmutabilityRecorded {
+        config: *ctx.config.key,
+        slot: config.immutable_slot,
+        deploy_slot: config.immutable_deploy_slot,
+    })
+}
+
+pub(crate) fn record_verified_build(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    build_hash: [u8; 32],
+    source_commit: [u8; 20],
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = ConfigAuthorityAccounts::validate(program_id, accounts)?;
+
+    let mut config = load_config(program_id, ctx.config)?;
+    if config.admin != *ctx.authority.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    config.verified_build_hash = build_hash;
+    config.verified_source_commit = source_commit;
+    config.serialize(&mut &mut ctx.config.data.borrow_mut()[..])?;
+
+    msg!("Verified build recorded");
+    emitter.emit(&TimeLockEvent::VerifiedBuildRecorded {
+        config: *ctx.config.key,
+        build_hash,
+        source_commit,
+    })
+}
+
+pub(crate) fn get_immutability(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let ctx = ConfigReportAccounts::validate(program_id, accounts)?;
+
+    let config = load_config(program_id, ctx.config)?;
+    set_return_data(&borsh::to_vec(&ImmutabilityReport::new(&config))?);
+    Ok(())
+}
+
+/// Lamports held by the config PDA as fees, everything above its rent
+fn fees(config_account: &AccountInfo) -> Result<u64, ProgramError> {
+    Ok(config_account
+        .lamports()
+        .saturating_sub(ConfigAccount::rent_exempt_lamports(&Rent::get()?)))
+}
+
+/// Config stored in the config PDA `config_account`, `None` before it is
+/// initialized
+fn read_config(
+    program_id: &Pubkey,
+    config_account: &AccountInfo,
+) -> Result<Option<ConfigAccount>, ProgramError> {
+    if config_account.owner != program_id {
+        // before initialization only, the search rules out a stand-in config
+        if *config_account.key != config_address(program_id).0 {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        return Ok(None);
+    }
+    let config = ConfigAccount::try_from_slice(&config_account.data.borrow())?;
+    check_pda(program_id, config_account, &[CONFIG_SEED], config.bump)?;
+    Ok(Some(config))
+}
+
+/// Seconds a time lock may be initialized past its unlock timestamp, none
+/// until the config is initialized
+pub(crate) fn clock_drift_tolerance(
+    program_id: &Pubkey,
+    config_account: &AccountInfo,
+) -> Result<i64, ProgramError> {
+    Ok(read_config(program_id, config_account)?.map_or(0, |config| config.clock_drift_tolerance))
+}
+
+/// Config of the initialized config PDA `config_account`
+fn load_config(
+    program_id: &Pubkey,
+    config_account: &AccountInfo,
+) -> Result<ConfigAccount, ProgramError> {
+    read_config(program_id, config_account)?.ok_or(ProgramError::IncorrectProgramId)
+}
+
+/// Refuse a bounty while the program is paused
+pub(crate) fn check_not_paused(program_id: &Pubkey, config_account: &AccountInfo) -> ProgramResult {
+    match read_config(program_id, config_account)? {
+        Some(config) if config.paused => Err(TimeLockError::ProgramPaused.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Apply the config to the creation of a lock maturing `duration` seconds
+/// from now: refuse it when paused or out of bounds, else charge `payer` the
+/// creation fee unless its fee exemption is among `accounts`. Before the
+/// config is initialized, creation is free and unbounded.
+pub(crate) fn charge_creation<'info>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'info>],
+    config_account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    duration: i64,
+) -> ProgramResult {
+    let Some(mut config) = read_config(program_id, config_account)? else {
+        return Ok(());
+    };
+    if config.paused {
+        return Err(TimeLockError::ProgramPaused.into());
+    }
+    if !config.allows_duration(duration) {
+        return Err(TimeLockError::DurationOutOfBounds.into());
+    }
+    if config.creation_fee > 0 && !fee_exempt(program_id, accounts, payer.key) {
+        invoke(
+            &system_instruction::transfer(payer.key, config_account.key, config.creation_fee),
+            &[
+                payer.clone(),
+                config_account.clone(),
+                system_program.clone(),
+            ],
+        )?;
+        config.total_creation_fees = config
+            .total_creation_fees
+            .saturating_add(config.creation_fee);
+        config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    }
+    Ok(())
+}
+
+/// Apply the config to a claim of `amount` lamports held by the program owned
+/// `source`: refuse it when paused, else move the claim fee from `source` to
+/// the config unless the fee exemption of `claimant` is among `accounts`.
+/// Returns the fee, what the claimant gets is `amount` net of it.
+pub(crate) fn charge_claim(
+    program_id: &Pubkey,
+    emitter: &EventEmitter,
+    accounts: &[AccountInfo],
+    config_account: &AccountInfo,
+    source: &AccountInfo,
+    claimant: &Pubkey,
+    amount: u64,
+) -> Result<u64, ProgramError> {
+    let Some(mut config) = read_config(program_id, config_account)? else {
+        return Ok(0);
+    };
+    if config.paused {
+        return Err(TimeLockError::ProgramPaused.into());
+    }
+    let fee = config.claim_fee(amount);
+    if fee == 0 || fee_exempt(program_id, accounts, claimant) {
+        return Ok(0);
+    }
+    config.total_claim_fees = config.total_claim_fees.saturating_add(fee);
+    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    **source.try_borrow_mut_lamports()? -= fee;
+    **config_account.try_borrow_mut_lamports()? += fee;
+    emitter.emit(&TimeLockEvent::ClaimFeeCharged {
+        source: *source.key,
+        fee,
+    })?;
+    Ok(fee)
+}
+
+/// Add `penalty` lamports forfeited by an early claim to the totals of the
+/// config, if initialized
+pub(crate) fn record_penalty(
+    program_id: &Pubkey,
+    config_account: &AccountInfo,
+    penalty: u64,
+) -> ProgramResult {
+    if let Some(mut config) = read_config(program_id, config_account)? {
+        config.total_penalties = config.total_penalties.saturating_add(penalty);
+        config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    }
+    Ok(())
+}
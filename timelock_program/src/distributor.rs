@@ -0,0 +1,166 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program::invoke;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::sysvar::Sysvar as _;
+use solana_program::{msg, system_instruction, system_program};
+use timelock_interface::error::TimeLockError;
+use timelock_interface::merkle;
+use timelock_interface::state::DistributorAccount;
+
+use crate::accounts::{Accounts, AccountsIter, Constraints};
+use crate::config;
+use crate::event::EventEmitter;
+use crate::TimeLockEvent;
+
+/// Accounts of `CreateDistributor`
+struct CreateDistributorAccounts<'a, 'info> {
+    distributor: &'a AccountInfo<'info>,
+    authority: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for CreateDistributorAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            distributor: iter.next(Constraints::ANY.signer().writable())?,
+            authority: iter.next(Constraints::ANY.signer().writable())?,
+            system_program: iter.next(Constraints::ANY.program(system_program::id()))?,
+        })
+    }
+}
+
+pub(crate) fn create_distributor(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    root: [u8; 32],
+    num_leaves: u32,
+    amount: u64,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = CreateDistributorAccounts::validate(program_id, accounts)?;
+
+    if num_leaves == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let space = DistributorAccount::space(num_leaves);
+    let lamports = DistributorAccount::rent_exempt_lamports(&Rent::get()?, num_leaves)
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    invoke(
+        &system_instruction::create_account(
+            ctx.authority.key,
+            ctx.distributor.key,
+            lamports,
+            space as u64,
+            program_id,
+        ),
+        &[
+            ctx.authority.clone(),
+            ctx.distributor.clone(),
+            ctx.system_program.clone(),
+        ],
+    )?;
+    DistributorAccount {
+        authority: *ctx.authority.key,
+        root,
+        num_leaves,
+        amount,
+        claimed_amount: 0,
+        claimed: vec![0; (num_leaves as usize).div_ceil(8)],
+    }
+    .serialize(&mut &mut ctx.distributor.data.borrow_mut()[..])?;
+
+    msg!(
+        "Distributing {} lamports among {} leaves",
+        amount,
+        num_leaves
+    );
+    emitter.emit(&TimeLockEvent::DistributorCreated {
+        distributor: *ctx.distributor.key,
+        authority: *ctx.authority.key,
+        root,
+        num_leaves,
+        amount,
+    })
+}
+
+/// Accounts of `ClaimWithProof`
+struct ClaimWithProofAccounts<'a, 'info> {
+    distributor: &'a AccountInfo<'info>,
+    recipient: &'a AccountInfo<'info>,
+    config: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for ClaimWithProofAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            distributor: iter.next(Constraints::ANY.writable().program_owned())?,
+            recipient: iter.next(Constraints::ANY.writable())?,
+            config: iter.next(Constraints::ANY)?,
+        })
+    }
+}
+
+pub(crate) fn claim_with_proof(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    index: u32,
+    amount: u64,
+    unlock_time: i64,
+    proof: &[[u8; 32]],
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = ClaimWithProofAccounts::validate(program_id, accounts)?;
+
+    let mut distributor = DistributorAccount::try_from_slice(&ctx.distributor.data.borrow())?;
+    // the recipient is part of the leaf, so anyone may send the claim
+    let leaf = merkle::leaf(index, ctx.recipient.key, amount, unlock_time);
+    if index >= distributor.num_leaves || !merkle::verify(proof, &distributor.root, &leaf) {
+        return Err(TimeLockError::InvalidProof.into());
+    }
+    if distributor.is_claimed(index) {
+        return Err(TimeLockError::LeafClaimed.into());
+    }
+    if Clock::get()?.unix_timestamp < unlock_time {
+        return Err(TimeLockError::LeafLocked.into());
+    }
+    // a tree over more than was funded must not eat into the rent
+    distributor.claimed_amount = distributor
+        .claimed_amount
+        .checked_add(amount)
+        .filter(|claimed| *claimed <= distributor.amount)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    distributor.set_claimed(index);
+    distributor.serialize(&mut &mut ctx.distributor.data.borrow_mut()[..])?;
+
+    let fee = config::charge_claim(
+        program_id,
+        &emitter,
+        accounts,
+        ctx.config,
+        ctx.distributor,
+        ctx.recipient.key,
+        amount,
+    )?;
+    let amount = amount - fee;
+    **ctx.distributor.try_borrow_mut_lamports()? -= amount;
+    **ctx.recipient.try_borrow_mut_lamports()? += amount;
+
+    msg!(
+        "Leaf {} claimed, {} lamports to {}",
+        index,
+        amount,
+        ctx.recipient.key
+    );
+    emitter.emit(&TimeLockEvent::LeafClaimed {
+        distributor: *ctx.distributor.key,
+        recipient: *ctx.recipient.key,
+        index,
+        amount,
+    })
+}
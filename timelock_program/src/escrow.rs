@@ -0,0 +1,424 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program::invoke;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::sysvar::Sysvar as _;
+use solana_program::{msg, system_instruction, system_program};
+use timelock_interface::error::TimeLockError;
+use timelock_interface::state::{EscrowAccount, FixedSizeAccount};
+
+use crate::accounts::{Accounts, AccountsIter, Constraints};
+use crate::config;
+use crate::event::EventEmitter;
+use crate::TimeLockEvent;
+
+/// Accounts of `CreateEscrow`
+struct CreateEscrowAccounts<'a, 'info> {
+    escrow: &'a AccountInfo<'info>,
+    depositor: &'a AccountInfo<'info>,
+    beneficiary: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+    config: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for CreateEscrowAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            escrow: iter.next(Constraints::ANY.signer().writable())?,
+            depositor: iter.next(Constraints::ANY.signer().writable())?,
+            beneficiary: iter.next(Constraints::ANY)?,
+            system_program: iter.next(Constraints::ANY.program(system_program::id()))?,
+            config: iter.next(Constraints::ANY)?,
+        })
+    }
+}
+
+pub(crate) fn create_escrow(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    release_time: i64,
+    refund_time: i64,
+    penalty_bps: u16,
+    penalty_decays: bool,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = CreateEscrowAccounts::validate(program_id, accounts)?;
+
+    // the beneficiary must get a window to claim before the refund opens
+    if refund_time <= release_time {
+        return Err(TimeLockError::InvalidEscrowDeadlines.into());
+    }
+    if penalty_bps > EscrowAccount::MAX_PENALTY_BPS {
+        return Err(TimeLockError::InvalidEscrowPenalty.into());
+    }
+    let now = Clock::get()?.unix_timestamp;
+    config::charge_creation(
+        program_id,
+        accounts,
+        ctx.config,
+        ctx.depositor,
+        ctx.system_program,
+        release_time.saturating_sub(now),
+    )?;
+
+    let space = EscrowAccount::LEN;
+    let lamports = EscrowAccount::rent_exempt_lamports(&Rent::get()?)
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    invoke(
+        &system_instruction::create_account(
+            ctx.depositor.key,
+            ctx.escrow.key,
+            lamports,
+            space as u64,
+            program_id,
+        ),
+        &[
+            ctx.depositor.clone(),
+            ctx.escrow.clone(),
+            ctx.system_program.clone(),
+        ],
+    )?;
+    EscrowAccount {
+        depositor: *ctx.depositor.key,
+        beneficiary: *ctx.beneficiary.key,
+        amount,
+        release_time,
+        refund_time,
+        delegate: Pubkey::default(),
+        penalty_bps,
+        penalty_decays,
+        created_at: now,
+    }
+    .serialize(&mut &mut ctx.escrow.data.borrow_mut()[..])?;
+
+    msg!(
+        "Escrowed {} lamports for {}, released at {}, refundable at {}",
+        amount,
+        ctx.beneficiary.key,
+        release_time,
+        refund_time
+    );
+    emitter.emit(&TimeLockEvent::EscrowCreated {
+        escrow: *ctx.escrow.key,
+        depositor: *ctx.depositor.key,
+        beneficiary: *ctx.beneficiary.key,
+        amount,
+        release_time,
+        refund_time,
+    })
+}
+
+/// Escrow data of `escrow_account`, not paid out yet: paying it out empties
+/// the account, which lingers until the end of the transaction
+fn load_escrow(escrow_account: &AccountInfo) -> Result<EscrowAccount, ProgramError> {
+    if escrow_account.lamports() == 0 {
+        return Err(TimeLockError::AlreadyReleased.into());
+    }
+    EscrowAccount::try_from_slice(&escrow_account.data.borrow()).map_err(Into::into)
+}
+
+/// Accounts of `ClaimEscrow`
+struct ClaimEscrowAccounts<'a, 'info> {
+    escrow: &'a AccountInfo<'info>,
+    beneficiary: &'a AccountInfo<'info>,
+    depositor: &'a AccountInfo<'info>,
+    config: &'a AccountInfo<'info>,
+    /// The delegate signing for the beneficiary, then the event accounts
+    remaining: &'a [AccountInfo<'info>],
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for ClaimEscrowAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            escrow: iter.next(Constraints::ANY.writable().program_owned())?,
+            beneficiary: iter.next(Constraints::ANY.writable())?,
+            depositor: iter.next(Constraints::ANY.writable())?,
+            config: iter.next(Constraints::ANY)?,
+            remaining: iter.remaining(),
+        })
+    }
+}
+
+pub(crate) fn claim_escrow(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = ClaimEscrowAccounts::validate(program_id, accounts)?;
+
+    let escrow = load_escrow(ctx.escrow)?;
+    if escrow.depositor != *ctx.depositor.key || escrow.beneficiary != *ctx.beneficiary.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    // the beneficiary signs, or the delegate given after the config
+    let signer = match ctx
+        .remaining
+        .first()
+        .filter(|a| !emitter.is_event_authority(a.key) && a.owner != program_id)
+    {
+        Some(delegate_account) => {
+            if !delegate_account.is_signer
+                || escrow.delegate == Pubkey::default()
+                || escrow.delegate != *delegate_account.key
+            {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            delegate_account
+        }
+        None if ctx.beneficiary.is_signer => ctx.beneficiary,
+        None => return Err(ProgramError::MissingRequiredSignature),
+    };
+    if Clock::get()?.unix_timestamp < escrow.release_time {
+        return Err(TimeLockError::EscrowNotReleased.into());
+    }
+
+    // the amount net of the claim fee goes to the beneficiary, the rent back
+    // to the depositor
+    let lamports = ctx.escrow.lamports();
+    let amount = escrow.amount.min(lamports);
+    let fee = config::charge_claim(
+        program_id,
+        &emitter,
+        accounts,
+        ctx.config,
+        ctx.escrow,
+        ctx.beneficiary.key,
+        amount,
+    )?;
+    **ctx.escrow.try_borrow_mut_lamports()? = 0;
+    ctx.escrow.data.borrow_mut().fill(0);
+    **ctx.depositor.try_borrow_mut_lamports()? += lamports - amount;
+    let amount = amount - fee;
+    **ctx.beneficiary.try_borrow_mut_lamports()? += amount;
+
+    msg!(
+        "Escrow claimed, {} lamports to {}",
+        amount,
+        ctx.beneficiary.key
+    );
+    emitter.emit(&TimeLockEvent::EscrowClaimed {
+        escrow: *ctx.escrow.key,
+        beneficiary: *ctx.beneficiary.key,
+        amount,
+    })?;
+    emitter.emit(&TimeLockEvent::EscrowClaimedBy {
+        escrow: *ctx.escrow.key,
+        signer: *signer.key,
+    })
+}
+
+/// Accounts of `ClaimEscrowEarly`
+struct ClaimEscrowEarlyAccounts<'a, 'info> {
+    escrow: &'a AccountInfo<'info>,
+    beneficiary: &'a AccountInfo<'info>,
+    depositor: &'a AccountInfo<'info>,
+    config: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for ClaimEscrowEarlyAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            escrow: iter.next(Constraints::ANY.writable().program_owned())?,
+            beneficiary: iter.next(Constraints::ANY.signer().writable())?,
+            depositor: iter.next(Constraints::ANY.writable())?,
+            config: iter.next(Constraints::ANY)?,
+        })
+    }
+}
+
+pub(crate) fn claim_escrow_early(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = ClaimEscrowEarlyAccounts::validate(program_id, accounts)?;
+
+    let escrow = load_escrow(ctx.escrow)?;
+    if escrow.depositor != *ctx.depositor.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    // forfeiting part of the amount is the beneficiary's call alone
+    if escrow.beneficiary != *ctx.beneficiary.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let penalty = escrow
+        .early_claim_penalty(Clock::get()?.unix_timestamp)
+        .ok_or(TimeLockError::EarlyClaimDisabled)?;
+
+    // the amount net of the penalty and the claim fee goes to the beneficiary,
+    // the penalty and the rent back to the depositor
+    let lamports = ctx.escrow.lamports();
+    let amount = escrow.amount.saturating_sub(penalty).min(lamports);
+    config::record_penalty(program_id, ctx.config, penalty)?;
+    let fee = config::charge_claim(
+        program_id,
+        &emitter,
+        accounts,
+        ctx.config,
+        ctx.escrow,
+        ctx.beneficiary.key,
+        amount,
+    )?;
+    **ctx.escrow.try_borrow_mut_lamports()? = 0;
+    ctx.escrow.data.borrow_mut().fill(0);
+    **ctx.depositor.try_borrow_mut_lamports()? += lamports - amount;
+    let amount = amount - fee;
+    **ctx.beneficiary.try_borrow_mut_lamports()? += amount;
+
+    msg!(
+        "Escrow claimed early, {} lamports to {}, {} forfeited",
+        amount,
+        ctx.beneficiary.key,
+        penalty
+    );
+    emitter.emit(&TimeLockEvent::EscrowClaimedEarly {
+        escrow: *ctx.escrow.key,
+        beneficiary: *ctx.beneficiary.key,
+        amount,
+        penalty,
+    })
+}
+
+/// Accounts of `RefundEscrow`
+struct RefundEscrowAccounts<'a, 'info> {
+    escrow: &'a AccountInfo<'info>,
+    depositor: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for RefundEscrowAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            escrow: iter.next(Constraints::ANY.writable().program_owned())?,
+            depositor: iter.next(Constraints::ANY.signer().writable())?,
+        })
+    }
+}
+
+pub(crate) fn refund_escrow(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = RefundEscrowAccounts::validate(program_id, accounts)?;
+
+    let escrow = load_escrow(ctx.escrow)?;
+    if escrow.depositor != *ctx.depositor.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if Clock::get()?.unix_timestamp < escrow.refund_time {
+        return Err(TimeLockError::EscrowNotRefundable.into());
+    }
+
+    let lamports = ctx.escrow.lamports();
+    **ctx.escrow.try_borrow_mut_lamports()? = 0;
+    ctx.escrow.data.borrow_mut().fill(0);
+    **ctx.depositor.try_borrow_mut_lamports()? += lamports;
+
+    msg!(
+        "Escrow refunded, {} lamports to {}",
+        escrow.amount,
+        ctx.depositor.key
+    );
+    emitter.emit(&TimeLockEvent::EscrowRefunded {
+        escrow: *ctx.escrow.key,
+        depositor: *ctx.depositor.key,
+        amount: escrow.amount,
+    })
+}
+
+/// Accounts of the escrow settings of its depositor: `ChangeEscrowBeneficiary`,
+/// `SetEscrowDelegate` and `RevokeEscrowDelegate`
+struct DepositorAccounts<'a, 'info> {
+    escrow: &'a AccountInfo<'info>,
+    depositor: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for DepositorAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            escrow: iter.next(Constraints::ANY.writable().program_owned())?,
+            depositor: iter.next(Constraints::ANY.signer())?,
+        })
+    }
+}
+
+pub(crate) fn change_escrow_beneficiary(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    beneficiary: Pubkey,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = DepositorAccounts::validate(program_id, accounts)?;
+
+    let mut escrow = load_escrow(ctx.escrow)?;
+    if escrow.depositor != *ctx.depositor.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    // once released the beneficiary may have claimed, or be about to
+    if Clock::get()?.unix_timestamp >= escrow.release_time {
+        return Err(TimeLockError::EscrowReleased.into());
+    }
+
+    let previous = escrow.beneficiary;
+    escrow.beneficiary = beneficiary;
+    escrow.serialize(&mut &mut ctx.escrow.data.borrow_mut()[..])?;
+
+    msg!(
+        "Escrow beneficiary changed from {} to {}",
+        previous,
+        beneficiary
+    );
+    emitter.emit(&TimeLockEvent::EscrowBeneficiaryChanged {
+        escrow: *ctx.escrow.key,
+        previous,
+        beneficiary,
+    })
+}
+
+pub(crate) fn set_escrow_delegate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    delegate: Pubkey,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = DepositorAccounts::validate(program_id, accounts)?;
+
+    let mut escrow = load_escrow(ctx.escrow)?;
+    if escrow.depositor != *ctx.depositor.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if delegate == Pubkey::default() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    escrow.delegate = delegate;
+    escrow.serialize(&mut &mut ctx.escrow.data.borrow_mut()[..])?;
+
+    msg!("Escrow delegated to {}", delegate);
+    emitter.emit(&TimeLockEvent::EscrowDelegateSet {
+        escrow: *ctx.escrow.key,
+        delegate,
+    })
+}
+
+pub(crate) fn revoke_escrow_delegate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = DepositorAccounts::validate(program_id, accounts)?;
+
+    let mut escrow = load_escrow(ctx.escrow)?;
+    if escrow.depositor != *ctx.depositor.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let delegate = escrow.delegate;
+    if delegate == Pubkey::default() {
+        msg!("Escrow has no delegate");
+        return Ok(());
+    }
+    escrow.delegate = Pubkey::default();
+    escrow.serialize(&mut &mut ctx.escrow.data.borrow_mut()[..])?;
+
+    msg!("Escrow delegation of {} revoked", delegate);
+    emitter.emit(&TimeLockEvent::EscrowDelegateRevoked {
+        escrow: *ctx.escrow.key,
+        delegate,
+    })
+}
@@ -0,0 +1,375 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::log::sol_log_data;
+use solana_program::program::invoke_signed;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use timelock_interface::instruction::EMIT_EVENT_TAG;
+use timelock_interface::pda::{event_authority, EVENT_AUTHORITY_SEED};
+use timelock_interface::state::SECRET_LENGTH;
+
+/// Events logged by the program, one per instruction outcome.
+///
+/// Events are borsh serialized and logged with `sol_log_data`, showing up as
+/// `Program data: <base64>` lines indexers can decode reliably. Variants are
+/// append only: their order is the wire tag.
+#[allow(clippy::large_enum_variant)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(borsh::BorshSchema))]
+pub enum TimeLockEvent {
+    /// A time lock was initialized
+    LockCreated {
+        lock: Pubkey,
+        payer: Pubkey,
+        timestamp: i64,
+        bounty: u64,
+    },
+    /// `TryUnlock` was called before the lock matured
+    UnlockTooEarly {
+        lock: Pubkey,
+        timestamp: i64,
+        now: i64,
+    },
+    /// `TryUnlock` was called after the lock matured
+    SecretRevealed {
+        lock: Pubkey,
+        #[cfg_attr(feature = "serde", serde(with = "timelock_interface::serde_array"))]
+        secret: [u8; SECRET_LENGTH],
+    },
+    /// The bounty of a matured lock was paid to its cranker
+    BountyPaid {
+        lock: Pubkey,
+        cranker: Pubkey,
+        amount: u64,
+    },
+    /// A stake account was escrowed until `timestamp`
+    StakeLocked {
+        stake_lock: Pubkey,
+        stake: Pubkey,
+        owner: Pubkey,
+        beneficiary: Pubkey,
+        timestamp: i64,
+    },
+    /// The withdraw authority of an escrowed stake account went to its beneficiary
+    StakeReleased {
+        stake_lock: Pubkey,
+        stake: Pubkey,
+        beneficiary: Pubkey,
+    },
+    /// `amount` lamports were deposited in a lock, its installments now `funded`
+    ToppedUp {
+        lock: Pubkey,
+        depositor: Pubkey,
+        amount: u64,
+        funded: u64,
+    },
+    /// `amount` surplus lamports of a lock were sent to its authority
+    ExcessWithdrawn {
+        lock: Pubkey,
+        authority: Pubkey,
+        amount: u64,
+    },
+    /// `amount` lamports were escrowed for `beneficiary`
+    EscrowCreated {
+        escrow: Pubkey,
+        depositor: Pubkey,
+        beneficiary: Pubkey,
+        amount: u64,
+        release_time: i64,
+        refund_time: i64,
+    },
+    /// An escrow was paid to its beneficiary and closed
+    EscrowClaimed {
+        escrow: Pubkey,
+        beneficiary: Pubkey,
+        amount: u64,
+    },
+    /// An unclaimed escrow was returned to its depositor and closed
+    EscrowRefunded {
+        escrow: Pubkey,
+        depositor: Pubkey,
+        amount: u64,
+    },
+    /// The depositor of an escrow redirected it before its release time
+    EscrowBeneficiaryChanged {
+        escrow: Pubkey,
+        previous: Pubkey,
+        beneficiary: Pubkey,
+    },
+    /// The depositor of an escrow allowed `delegate` to claim it
+    EscrowDelegateSet { escrow: Pubkey, delegate: Pubkey },
+    /// The depositor of an escrow took the claim right of `delegate` back
+    EscrowDelegateRevoked { escrow: Pubkey, delegate: Pubkey },
+    /// Follows `EscrowClaimed`: the key that signed the claim, the
+    /// beneficiary or its delegate
+    EscrowClaimedBy { escrow: Pubkey, signer: Pubkey },
+    /// The authority of a lock allowed `unlocker` to call `TryUnlock`
+    UnlockerAdded { lock: Pubkey, unlocker: Pubkey },
+    /// The authority of a lock disallowed `unlocker` to call `TryUnlock`
+    UnlockerRemoved { lock: Pubkey, unlocker: Pubkey },
+    /// An escrow was paid to its beneficiary before its release time, `penalty`
+    /// lamports going back to its depositor
+    EscrowClaimedEarly {
+        escrow: Pubkey,
+        beneficiary: Pubkey,
+        amount: u64,
+        penalty: u64,
+    },
+    /// The authority set when a lock expires
+    LockExpirySet {
+        lock: Pubkey,
+        expires_at: i64,
+        recipient: Pubkey,
+    },
+    /// An expired lock was closed, its secret wiped
+    LockExpired {
+        lock: Pubkey,
+        recipient: Pubkey,
+        amount: u64,
+    },
+    /// The authority put a lock in dead man's switch mode, or ended it with a
+    /// zero interval
+    HeartbeatSet { lock: Pubkey, interval: i64 },
+    /// The authority deferred the maturity of a lock
+    Heartbeat { lock: Pubkey, timestamp: i64 },
+    /// The authority set the recurring windows a lock opens during
+    RecurrenceSet {
+        lock: Pubkey,
+        unit: u8,
+        every: u32,
+        window: u32,
+    },
+    /// The authority set the program a lock calls when revealed
+    CallbackSet { lock: Pubkey, program: Pubkey },
+    /// A lock called its callback program on its first reveal
+    CallbackInvoked { lock: Pubkey, program: Pubkey },
+    /// The unlock condition of a lock was set, or lifted with the default `account`
+    ConditionSet {
+        lock: Pubkey,
+        account: Pubkey,
+        offset: u32,
+    },
+    /// The predecessor of a lock was set, or removed with the default `predecessor`
+    PredecessorSet { lock: Pubkey, predecessor: Pubkey },
+    /// A distributor of timelocked claims was funded
+    DistributorCreated {
+        distributor: Pubkey,
+        authority: Pubkey,
+        root: [u8; 32],
+        num_leaves: u32,
+        amount: u64,
+    },
+    /// A leaf of a distributor was paid to its recipient
+    LeafClaimed {
+        distributor: Pubkey,
+        recipient: Pubkey,
+        index: u32,
+        amount: u64,
+    },
+    /// A vault was funded
+    VaultCreated {
+        vault: Pubkey,
+        authority: Pubkey,
+        amount: u64,
+    },
+    /// Lamports of a vault were allocated to a beneficiary
+    VaultLockCreated {
+        vault_lock: Pubkey,
+        vault: Pubkey,
+        beneficiary: Pubkey,
+        amount: u64,
+        release_time: i64,
+    },
+    /// A vault paid the amount of one of its vault locks
+    VaultLockClaimed {
+        vault_lock: Pubkey,
+        beneficiary: Pubkey,
+        amount: u64,
+    },
+    /// Unallocated lamports were withdrawn from a vault
+    VaultWithdrawn { vault: Pubkey, amount: u64 },
+    /// Part of the amount of a vault lock was moved to a new one
+    VaultLockSplit {
+        vault_lock: Pubkey,
+        new_vault_lock: Pubkey,
+        beneficiary: Pubkey,
+        amount: u64,
+    },
+    /// A vault lock was merged into another one, then closed
+    VaultLocksMerged {
+        vault_lock: Pubkey,
+        source: Pubkey,
+        amount: u64,
+    },
+    /// A vault lock was handed to another beneficiary
+    VaultLockTransferred {
+        vault_lock: Pubkey,
+        beneficiary: Pubkey,
+        new_beneficiary: Pubkey,
+    },
+    /// A vault lock was sold to a new beneficiary
+    VaultLockSold {
+        vault_lock: Pubkey,
+        seller: Pubkey,
+        buyer: Pubkey,
+        price: u64,
+    },
+    /// The program config was created or updated
+    ConfigUpdated {
+        config: Pubkey,
+        admin: Pubkey,
+        treasury: Pubkey,
+        creation_fee: u64,
+        min_duration: i64,
+        max_duration: i64,
+        paused: bool,
+        guardian: Pubkey,
+        claim_fee_bps: u16,
+        update_delay: i64,
+        clock_drift_tolerance: i64,
+    },
+    /// The fees held by the program config were moved to its treasury
+    FeesCollected { treasury: Pubkey, amount: u64 },
+    /// A claim from `source` left `fee` lamports to the program config
+    ClaimFeeCharged { source: Pubkey, fee: u64 },
+    /// The admin moved fees held by the program config to `recipient`
+    FeesWithdrawn { recipient: Pubkey, amount: u64 },
+    /// `key` was exempted from the fees of the program config
+    FeeExemptionAdded { key: Pubkey },
+    /// `key` is charged the fees of the program config again
+    FeeExemptionRemoved { key: Pubkey },
+    /// The admin proposed `pending_admin` to take over the program config until
+    /// `expires_at`
+    AdminProposed {
+        config: Pubkey,
+        pending_admin: Pubkey,
+        expires_at: i64,
+    },
+    /// The admin queued an update of the program config, applicable from `eta`
+    ConfigUpdateQueued { config_update: Pubkey, eta: i64 },
+    /// The admin or the guardian dropped the queued update of the program config
+    ConfigUpdateCancelled {
+        config_update: Pubkey,
+        authority: Pubkey,
+    },
+    /// The program was recorded without upgrade authority, last deployed at
+    /// `deploy_slot`
+    ImmutabilityRecorded {
+        config: Pubkey,
+        slot: u64,
+        deploy_slot: u64,
+    },
+    /// The admin recorded the hash of the verified build of the program and the
+    /// commit it was built from
+    VerifiedBuildRecorded {
+        config: Pubkey,
+        build_hash: [u8; 32],
+        source_commit: [u8; 20],
+    },
+    /// The authority of a lock started keeping its audit trail
+    AuditTrailEnabled { lock: Pubkey, authority: Pubkey },
+    /// The authority of a lock restricted its bounty to committed claims
+    ClaimCommitRequired { lock: Pubkey },
+    /// A committer committed to claiming the bounty of a lock
+    ClaimCommitted { lock: Pubkey, committer: Pubkey },
+    /// A payroll was funded
+    PayrollCreated {
+        payroll: Pubkey,
+        authority: Pubkey,
+        amount: u64,
+        num_entries: u32,
+    },
+    /// Entries were added to a payroll
+    PayrollEntriesAdded { payroll: Pubkey, added: u32 },
+    /// The vested share of an entry of a payroll was paid to its beneficiary
+    PayrollClaimed {
+        payroll: Pubkey,
+        beneficiary: Pubkey,
+        index: u32,
+        amount: u64,
+    },
+    /// A schedule template was created
+    ScheduleTemplateCreated { template: Pubkey, authority: Pubkey },
+    /// The authority of a matured lock dropped its callback
+    CallbackCleared { lock: Pubkey, program: Pubkey },
+    /// A settled payroll was closed by its authority
+    PayrollClosed { payroll: Pubkey, authority: Pubkey },
+    /// An account created before its layout grew was migrated to it
+    AccountMigrated { account: Pubkey, previous_len: u32 },
+    /// The installments of a lock go to `recipient` once revealed
+    GrantRecipientSet { lock: Pubkey, recipient: Pubkey },
+    /// `amount` lamports deposited in a lock were released to its grant `recipient`
+    GrantReleased {
+        lock: Pubkey,
+        recipient: Pubkey,
+        amount: u64,
+    },
+}
+
+impl TimeLockEvent {
+    /// Log the event as `Program data: <base64>`
+    pub fn emit(&self) {
+        sol_log_data(&[&borsh::to_vec(self).expect("serializing an event never fails")]);
+    }
+}
+
+/// Emits events to the logs and, when the instruction was given the event
+/// authority followed by the program account, as a self-CPI too.
+///
+/// Logs can be truncated when a transaction logs too much, while inner
+/// instructions are always kept in the transaction metadata.
+pub struct EventEmitter<'a, 'info> {
+    program_id: &'a Pubkey,
+    authority: Pubkey,
+    cpi: Option<(&'a AccountInfo<'info>, &'a AccountInfo<'info>, u8)>,
+}
+
+impl<'a, 'info> EventEmitter<'a, 'info> {
+    pub fn new(
+        program_id: &'a Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError> {
+        let (authority, bump) = event_authority(program_id);
+        let cpi = match accounts.iter().position(|a| *a.key == authority) {
+            Some(i) => {
+                let program = accounts
+                    .get(i + 1)
+                    .filter(|a| a.key == program_id)
+                    .ok_or(ProgramError::NotEnoughAccountKeys)?;
+                Some((&accounts[i], program, bump))
+            }
+            None => None,
+        };
+        Ok(Self {
+            program_id,
+            authority,
+            cpi,
+        })
+    }
+
+    /// Whether `key` is the event authority, so not a regular instruction account
+    pub fn is_event_authority(&self, key: &Pubkey) -> bool {
+        *key == self.authority
+    }
+
+    pub fn emit(&self, event: &TimeLockEvent) -> ProgramResult {
+        event.emit();
+        if let Some((authority, program, bump)) = self.cpi {
+            let mut data = vec![EMIT_EVENT_TAG];
+            event.serialize(&mut data)?;
+            invoke_signed(
+                &Instruction {
+                    program_id: *self.program_id,
+                    accounts: vec![AccountMeta::new_readonly(*authority.key, true)],
+                    data,
+                },
+                &[authority.clone(), program.clone()],
+                &[&[EVENT_AUTHORITY_SEED, &[bump]]],
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,228 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE};
+use solana_program::hash::hash;
+use solana_program::program::invoke_signed;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::sysvar::Sysvar as _;
+use solana_program::{bpf_loader_upgradeable, msg, system_instruction, system_program};
+
+use crate::accounts::{Accounts, AccountsIter, Constraints};
+
+/// Prefix of the instructions managing the IDL account, the Anchor `IDL_IX_TAG`
+pub const IDL_IX_TAG: [u8; 8] = 0x0a69e9a778bcf440u64.to_le_bytes();
+
+/// Seed of the IDL account, derived from the base PDA with `create_with_seed`
+pub const IDL_SEED: &str = "anchor:idl";
+
+/// Size of the IDL account header: discriminator + authority + u32 data length
+pub const IDL_HEADER_LEN: usize = 8 + 32 + 4;
+
+/// Instructions managing the IDL account, borsh compatible with Anchor's so
+/// explorers and tooling recognize them. Buffers are not supported.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub enum IdlInstruction {
+    /// Create the IDL account with room for `data_len` bytes of compressed IDL
+    Create { data_len: u64 },
+    /// Unsupported
+    CreateBuffer,
+    /// Append a chunk of the compressed IDL
+    Write { data: Vec<u8> },
+    /// Unsupported
+    SetBuffer,
+    /// Hand the IDL account over to another authority
+    SetAuthority { new_authority: Pubkey },
+}
+
+/// Address of the IDL account of `program_id`, where explorers look it up
+pub fn idl_address(program_id: &Pubkey) -> Pubkey {
+    let base = Pubkey::find_program_address(&[], program_id).0;
+    Pubkey::create_with_seed(&base, IDL_SEED, program_id).expect("seed is short enough")
+}
+
+/// Anchor discriminator of the `IdlAccount` layout
+pub fn idl_account_discriminator() -> [u8; 8] {
+    hash(b"account:IdlAccount").to_bytes()[..8]
+        .try_into()
+        .expect("8 bytes")
+}
+
+pub(crate) fn process_idl_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    match IdlInstruction::try_from_slice(data)? {
+        IdlInstruction::Create { data_len } => {
+            msg!("Instruction: IdlCreate");
+            create(program_id, accounts, data_len)
+        }
+        IdlInstruction::Write { data } => {
+            msg!("Instruction: IdlWrite");
+            write(program_id, accounts, &data)
+        }
+        IdlInstruction::SetAuthority { new_authority } => {
+            msg!("Instruction: IdlSetAuthority");
+            set_authority(program_id, accounts, new_authority)
+        }
+        IdlInstruction::CreateBuffer | IdlInstruction::SetBuffer => {
+            Err(ProgramError::InvalidInstructionData)
+        }
+    }
+}
+
+/// Accounts of `IdlCreate`
+struct CreateAccounts<'a, 'info> {
+    from: &'a AccountInfo<'info>,
+    idl_account: &'a AccountInfo<'info>,
+    base: &'a AccountInfo<'info>,
+    bump: u8,
+    system_program: &'a AccountInfo<'info>,
+    programdata: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for CreateAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        let program_id = iter.program_id();
+        let (base_key, bump) = Pubkey::find_program_address(&[], program_id);
+        let idl_key = Pubkey::create_with_seed(&base_key, IDL_SEED, program_id)?;
+        let from = iter.next(Constraints::ANY.signer().writable())?;
+        let idl_account = iter.next(Constraints::ANY.writable().pda(idl_key))?;
+        let base = iter.next(Constraints::ANY.pda(base_key))?;
+        let system_program = iter.next(Constraints::ANY.program(system_program::id()))?;
+        // the program itself, for the upgrade authority to be its own
+        iter.next(Constraints::ANY.program(*program_id))?;
+        Ok(Self {
+            from,
+            idl_account,
+            base,
+            bump,
+            system_program,
+            programdata: iter.next(Constraints::ANY)?,
+        })
+    }
+}
+
+fn create(program_id: &Pubkey, accounts: &[AccountInfo], data_len: u64) -> ProgramResult {
+    let ctx = CreateAccounts::validate(program_id, accounts)?;
+
+    // Only the upgrade authority may publish the IDL, otherwise anyone could front-run it
+    if upgrade_authority(program_id, ctx.programdata)? != Some(*ctx.from.key) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let space = IDL_HEADER_LEN
+        .checked_add(data_len as usize)
+        .filter(|space| *space <= MAX_PERMITTED_DATA_INCREASE)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    invoke_signed(
+        &system_instruction::create_account_with_seed(
+            ctx.from.key,
+            ctx.idl_account.key,
+            ctx.base.key,
+            IDL_SEED,
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            program_id,
+        ),
+        &[
+            ctx.from.clone(),
+            ctx.idl_account.clone(),
+            ctx.base.clone(),
+            ctx.system_program.clone(),
+        ],
+        &[&[&[ctx.bump]]],
+    )?;
+
+    let mut data = ctx.idl_account.data.borrow_mut();
+    data[..8].copy_from_slice(&idl_account_discriminator());
+    data[8..40].copy_from_slice(ctx.from.key.as_ref());
+    data[40..44].copy_from_slice(&0u32.to_le_bytes());
+    Ok(())
+}
+
+/// Accounts of `IdlWrite` and `IdlSetAuthority`
+struct IdlAuthorityAccounts<'a, 'info> {
+    idl_account: &'a AccountInfo<'info>,
+    authority: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for IdlAuthorityAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        let idl_key = idl_address(iter.program_id());
+        Ok(Self {
+            idl_account: iter.next(Constraints::ANY.writable().program_owned().pda(idl_key))?,
+            authority: iter.next(Constraints::ANY.signer())?,
+        })
+    }
+}
+
+fn write(program_id: &Pubkey, accounts: &[AccountInfo], chunk: &[u8]) -> ProgramResult {
+    let ctx = IdlAuthorityAccounts::validate(program_id, accounts)?;
+    check_authority(&ctx)?;
+
+    let mut data = ctx.idl_account.data.borrow_mut();
+    let len = u32::from_le_bytes(data[40..44].try_into().expect("4 bytes")) as usize;
+    let start = IDL_HEADER_LEN + len;
+    let end = start
+        .checked_add(chunk.len())
+        .filter(|end| *end <= data.len())
+        .ok_or(ProgramError::AccountDataTooSmall)?;
+    data[start..end].copy_from_slice(chunk);
+    data[40..44].copy_from_slice(&((len + chunk.len()) as u32).to_le_bytes());
+    Ok(())
+}
+
+fn set_authority(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_authority: Pubkey,
+) -> ProgramResult {
+    let ctx = IdlAuthorityAccounts::validate(program_id, accounts)?;
+    check_authority(&ctx)?;
+    ctx.idl_account.data.borrow_mut()[8..40].copy_from_slice(new_authority.as_ref());
+    Ok(())
+}
+
+fn check_authority(ctx: &IdlAuthorityAccounts) -> ProgramResult {
+    let data = ctx.idl_account.data.borrow();
+    if data.len() < IDL_HEADER_LEN || data[..8] != idl_account_discriminator() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if data[8..40] != ctx.authority.key.to_bytes() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    Ok(())
+}
+
+/// Upgrade authority of the program, read from its program data account
+pub(crate) fn upgrade_authority(
+    program_id: &Pubkey,
+    programdata: &AccountInfo,
+) -> Result<Option<Pubkey>, ProgramError> {
+    let (programdata_address, _) =
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+    if *programdata.key != programdata_address || *programdata.owner != bpf_loader_upgradeable::id()
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    // UpgradeableLoaderState::ProgramData: u32 tag 3, u64 slot, Option<Pubkey>
+    let data = programdata.data.borrow();
+    match data.get(..45) {
+        Some([3, 0, 0, 0, _, _, _, _, _, _, _, _, 1, authority @ ..]) => Ok(Some(
+            Pubkey::new_from_array(authority.try_into().expect("32 bytes")),
+        )),
+        Some([3, 0, 0, 0, _, _, _, _, _, _, _, _, 0, ..]) => Ok(None),
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+/// Slot the program was last deployed at, read from its program data account
+/// once checked by [`upgrade_authority`]
+pub(crate) fn deploy_slot(programdata: &AccountInfo) -> Result<u64, ProgramError> {
+    let data = programdata.data.borrow();
+    let slot = data.get(4..12).ok_or(ProgramError::InvalidAccountData)?;
+    Ok(u64::from_le_bytes(slot.try_into().expect("8 bytes")))
+}
@@ -1,257 +1,1475 @@
 use core::str;
 
+mod accounts;
+mod audit;
+mod callback;
+mod claim;
+mod config;
+mod distributor;
+mod escrow;
+mod event;
+mod idl;
+mod migrate;
+mod nonce;
+mod payroll;
+mod stake;
+mod stats;
+mod unlock;
+mod vault;
+pub use event::TimeLockEvent;
+pub use idl::{
+    idl_account_discriminator, idl_address, IdlInstruction, IDL_HEADER_LEN, IDL_IX_TAG, IDL_SEED,
+};
+pub use timelock_interface::error::TimeLockError;
+pub use timelock_interface::instruction::{
+    TimeLockInstruction, EMIT_EVENT_TAG, MAX_ACCOUNTS, MAX_INSTRUCTION_DATA, MEMO_PROGRAM_ID,
+    MEMO_V1_PROGRAM_ID,
+};
+pub use timelock_interface::pda::{
+    audit_address, callback_address, claim_commitment_address, config_address,
+    config_update_address, event_authority, fee_exemption_address, lock_address,
+    lock_nonce_address, stake_lock_address, stats_address, vault_stats_address, AUDIT_SEED,
+    CALLBACK_SEED, CLAIM_COMMITMENT_SEED, CONFIG_SEED, CONFIG_UPDATE_SEED, EVENT_AUTHORITY_SEED,
+    FEE_EXEMPTION_SEED, LOCK_NONCE_SEED, LOCK_SEED, STAKE_LOCK_SEED, STATS_SEED, VAULT_STATS_SEED,
+};
+#[cfg(feature = "schema")]
+pub use timelock_interface::schema;
+pub use timelock_interface::state::{
+    AuditEntry, AuditTrailAccount, CallbackAccount, ClaimCommitmentAccount, ConfigAccount,
+    ConfigUpdateAccount, DistributorAccount, EscrowAccount, FeeExemptionAccount, FixedSizeAccount,
+    ImmutabilityReport, LockNonceAccount, LockPosition, PayrollAccount, PayrollEntry, PayrollShare,
+    RevenueReport, ScheduleTemplateAccount, StakeLockAccount, TimeLockAccount, TimeLockStats,
+    VaultAccount, VaultLockAccount, VaultStatsAccount, IMMUTABILITY_VERSION, MAX_AUDIT_ENTRIES,
+    MAX_CALLBACK_ACCOUNTS, MAX_CALLBACK_DATA, MAX_CONDITION_BYTES, MAX_PAYROLL_ENTRIES,
+    MAX_UNLOCKERS, POSITION_VERSION, REVENUE_VERSION, SECRET_LENGTH, VAULT_SCHEDULE_WEEKS,
+};
+pub use timelock_interface::{builder, cpi, instruction, merkle};
+pub use unlock::{evaluate_unlock, UnlockDecision};
+
+use accounts::{check_not_reentered, Accounts, AccountsIter, Constraints};
+use event::EventEmitter;
+use stats::{create_stats_if_needed, update_stats};
+
 use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::account_info::next_account_info;
-use solana_program::program::invoke;
+use solana_program::hash::hash;
+use solana_program::program::{invoke, invoke_signed, set_return_data};
 use solana_program::rent::Rent;
-use solana_program::sysvar::Sysvar as _;
+use solana_program::sysvar::instructions::load_instruction_at_checked;
+use solana_program::sysvar::{self, Sysvar as _};
 use solana_program::{
     account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
     pubkey::Pubkey, sysvar::clock::Clock,
 };
-use solana_program::{entrypoint, system_instruction};
+use solana_program::{system_instruction, system_program};
 
-const SECRET_LENGTH: usize = 256;
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
 
-entrypoint!(process_instruction);
+// Disclosure channel read from the deployed binary by explorers and scanners
+#[cfg(not(feature = "no-entrypoint"))]
+solana_security_txt::security_txt! {
+    name: "TimeLock",
+    project_url: "https://github.com/kingwingfly/TimeLock",
+    contacts: "email:836250617@qq.com,link:https://github.com/kingwingfly/TimeLock/security/advisories/new",
+    policy: "https://github.com/kingwingfly/TimeLock/blob/master/SECURITY.md",
+    preferred_languages: "en",
+    source_code: "https://github.com/kingwingfly/TimeLock"
+}
 
-fn process_instruction(
+pub fn process_instruction(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    match TimeLockInstruction::unpack(instruction_data)? {
-        TimeLockInstruction::InitializeTimeLock { timestamp, secret } => {
+    if accounts.len() > MAX_ACCOUNTS {
+        return Err(TimeLockError::TooManyAccounts.into());
+    }
+    if let Some(data) = instruction_data.strip_prefix(&IDL_IX_TAG) {
+        return idl::process_idl_instruction(program_id, accounts, data);
+    }
+    let instruction = TimeLockInstruction::unpack(instruction_data)?;
+    // counted off the logs to tell when the legacy layout can be retired
+    if let Some(tag) = TimeLockInstruction::legacy_tag(instruction_data) {
+        msg!("Legacy instruction tag {}", tag);
+    }
+    let audited = audit::audited(program_id, accounts, &instruction);
+    match instruction {
+        TimeLockInstruction::InitializeTimeLock {
+            timestamp,
+            secret,
+            bounty,
+            require_memo,
+            top_up_authority_only,
+        } => {
             msg!("Instruction: InitializeTimeLock");
-            initialize_time_lock(program_id, accounts, timestamp, secret)?;
+            initialize_time_lock(
+                program_id,
+                accounts,
+                timestamp,
+                secret,
+                bounty,
+                require_memo,
+                top_up_authority_only,
+                &[],
+            )?;
         }
         TimeLockInstruction::TryUnlock => try_unlock(program_id, accounts)?,
+        TimeLockInstruction::EmitEvent => emit_event(program_id, accounts)?,
+        TimeLockInstruction::GetPosition => get_position(program_id, accounts)?,
+        TimeLockInstruction::LockStake {
+            timestamp,
+            beneficiary,
+        } => {
+            msg!("Instruction: LockStake");
+            stake::lock_stake(program_id, accounts, timestamp, beneficiary)?;
+        }
+        TimeLockInstruction::ReleaseStake => {
+            msg!("Instruction: ReleaseStake");
+            stake::release_stake(program_id, accounts)?;
+        }
+        TimeLockInstruction::TopUp { amount } => {
+            msg!("Instruction: TopUp");
+            top_up(program_id, accounts, amount)?;
+        }
+        TimeLockInstruction::WithdrawExcess => {
+            msg!("Instruction: WithdrawExcess");
+            withdraw_excess(program_id, accounts)?;
+        }
+        TimeLockInstruction::CreateEscrow {
+            amount,
+            release_time,
+            refund_time,
+            penalty_bps,
+            penalty_decays,
+        } => {
+            msg!("Instruction: CreateEscrow");
+            escrow::create_escrow(
+                program_id,
+                accounts,
+                amount,
+                release_time,
+                refund_time,
+                penalty_bps,
+                penalty_decays,
+            )?;
+        }
+        TimeLockInstruction::ClaimEscrow => {
+            msg!("Instruction: ClaimEscrow");
+            escrow::claim_escrow(program_id, accounts)?;
+        }
+        TimeLockInstruction::RefundEscrow => {
+            msg!("Instruction: RefundEscrow");
+            escrow::refund_escrow(program_id, accounts)?;
+        }
+        TimeLockInstruction::ChangeEscrowBeneficiary { beneficiary } => {
+            msg!("Instruction: ChangeEscrowBeneficiary");
+            escrow::change_escrow_beneficiary(program_id, accounts, beneficiary)?;
+        }
+        TimeLockInstruction::SetEscrowDelegate { delegate } => {
+            msg!("Instruction: SetEscrowDelegate");
+            escrow::set_escrow_delegate(program_id, accounts, delegate)?;
+        }
+        TimeLockInstruction::RevokeEscrowDelegate => {
+            msg!("Instruction: RevokeEscrowDelegate");
+            escrow::revoke_escrow_delegate(program_id, accounts)?;
+        }
+        TimeLockInstruction::AddUnlocker { unlocker } => {
+            msg!("Instruction: AddUnlocker");
+            add_unlocker(program_id, accounts, unlocker)?;
+        }
+        TimeLockInstruction::RemoveUnlocker { unlocker } => {
+            msg!("Instruction: RemoveUnlocker");
+            remove_unlocker(program_id, accounts, unlocker)?;
+        }
+        TimeLockInstruction::ClaimEscrowEarly => {
+            msg!("Instruction: ClaimEscrowEarly");
+            escrow::claim_escrow_early(program_id, accounts)?;
+        }
+        TimeLockInstruction::SetExpiry {
+            expires_at,
+            recipient,
+        } => {
+            msg!("Instruction: SetExpiry");
+            set_expiry(program_id, accounts, expires_at, recipient)?;
+        }
+        TimeLockInstruction::Expire => {
+            msg!("Instruction: Expire");
+            expire(program_id, accounts)?;
+        }
+        TimeLockInstruction::SetHeartbeat { interval } => {
+            msg!("Instruction: SetHeartbeat");
+            set_heartbeat(program_id, accounts, interval)?;
+        }
+        TimeLockInstruction::Heartbeat => {
+            msg!("Instruction: Heartbeat");
+            heartbeat(program_id, accounts)?;
+        }
+        TimeLockInstruction::SetRecurrence {
+            unit,
+            every,
+            window,
+        } => {
+            msg!("Instruction: SetRecurrence");
+            set_recurrence(program_id, accounts, unit, every, window)?;
+        }
+        TimeLockInstruction::SetCallback { data } => {
+            msg!("Instruction: SetCallback");
+            callback::set_callback(program_id, accounts, &data, false)?;
+        }
+        TimeLockInstruction::SetUnlockInstruction { data } => {
+            msg!("Instruction: SetUnlockInstruction");
+            callback::set_callback(program_id, accounts, &data, true)?;
+        }
+        TimeLockInstruction::SetCondition {
+            account,
+            offset,
+            expected,
+        } => {
+            msg!("Instruction: SetCondition");
+            set_condition(program_id, accounts, account, offset, &expected)?;
+        }
+        TimeLockInstruction::SetPredecessor { predecessor } => {
+            msg!("Instruction: SetPredecessor");
+            set_predecessor(program_id, accounts, predecessor)?;
+        }
+        TimeLockInstruction::CreateDistributor {
+            root,
+            num_leaves,
+            amount,
+        } => {
+            msg!("Instruction: CreateDistributor");
+            distributor::create_distributor(program_id, accounts, root, num_leaves, amount)?;
+        }
+        TimeLockInstruction::ClaimWithProof {
+            index,
+            amount,
+            unlock_time,
+            proof,
+        } => {
+            msg!("Instruction: ClaimWithProof");
+            distributor::claim_with_proof(
+                program_id,
+                accounts,
+                index,
+                amount,
+                unlock_time,
+                &proof,
+            )?;
+        }
+        TimeLockInstruction::CreateVault { amount } => {
+            msg!("Instruction: CreateVault");
+            vault::create_vault(program_id, accounts, amount)?;
+        }
+        TimeLockInstruction::CreateVaultLock {
+            amount,
+            release_time,
+            transferable,
+        } => {
+            msg!("Instruction: CreateVaultLock");
+            vault::create_vault_lock(program_id, accounts, amount, release_time, transferable)?;
+        }
+        TimeLockInstruction::ClaimVaultLock => {
+            msg!("Instruction: ClaimVaultLock");
+            vault::claim_vault_lock(program_id, accounts)?;
+        }
+        TimeLockInstruction::WithdrawUnallocated { amount } => {
+            msg!("Instruction: WithdrawUnallocated");
+            vault::withdraw_unallocated(program_id, accounts, amount)?;
+        }
+        TimeLockInstruction::SplitLock { amount } => {
+            msg!("Instruction: SplitLock");
+            vault::split_lock(program_id, accounts, amount)?;
+        }
+        TimeLockInstruction::MergeLocks => {
+            msg!("Instruction: MergeLocks");
+            vault::merge_locks(program_id, accounts)?;
+        }
+        TimeLockInstruction::TransferVaultLock => {
+            msg!("Instruction: TransferVaultLock");
+            vault::transfer_vault_lock(program_id, accounts)?;
+        }
+        TimeLockInstruction::TransferWithPayment { price } => {
+            msg!("Instruction: TransferWithPayment");
+            vault::transfer_with_payment(program_id, accounts, price)?;
+        }
+        TimeLockInstruction::InitializeConfig {
+            treasury,
+            creation_fee,
+            min_duration,
+            max_duration,
+        } => {
+            msg!("Instruction: InitializeConfig");
+            config::initialize_config(
+                program_id,
+                accounts,
+                treasury,
+                creation_fee,
+                min_duration,
+                max_duration,
+            )?;
+        }
+        TimeLockInstruction::UpdateConfig {
+            treasury,
+            creation_fee,
+            min_duration,
+            max_duration,
+            paused,
+            claim_fee_bps,
+            update_delay,
+            clock_drift_tolerance,
+        } => {
+            msg!("Instruction: UpdateConfig");
+            config::update_config(
+                program_id,
+                accounts,
+                treasury,
+                creation_fee,
+                min_duration,
+                max_duration,
+                paused,
+                claim_fee_bps,
+                update_delay,
+                clock_drift_tolerance,
+            )?;
+        }
+        TimeLockInstruction::CollectFees => {
+            msg!("Instruction: CollectFees");
+            config::collect_fees(program_id, accounts)?;
+        }
+        TimeLockInstruction::SetPaused { paused } => {
+            msg!("Instruction: SetPaused");
+            config::set_paused(program_id, accounts, paused)?;
+        }
+        TimeLockInstruction::WithdrawFees { amount } => {
+            msg!("Instruction: WithdrawFees");
+            config::withdraw_fees(program_id, accounts, amount)?;
+        }
+        TimeLockInstruction::AddFeeExemption { key } => {
+            msg!("Instruction: AddFeeExemption");
+            config::add_fee_exemption(program_id, accounts, key)?;
+        }
+        TimeLockInstruction::RemoveFeeExemption { key } => {
+            msg!("Instruction: RemoveFeeExemption");
+            config::remove_fee_exemption(program_id, accounts, key)?;
+        }
+        TimeLockInstruction::ProposeAdmin { expires_at } => {
+            msg!("Instruction: ProposeAdmin");
+            config::propose_admin(program_id, accounts, expires_at)?;
+        }
+        TimeLockInstruction::AcceptAdmin => {
+            msg!("Instruction: AcceptAdmin");
+            config::accept_admin(program_id, accounts)?;
+        }
+        TimeLockInstruction::ApplyConfigUpdate => {
+            msg!("Instruction: ApplyConfigUpdate");
+            config::apply_config_update(program_id, accounts)?;
+        }
+        TimeLockInstruction::CancelConfigUpdate => {
+            msg!("Instruction: CancelConfigUpdate");
+            config::cancel_config_update(program_id, accounts)?;
+        }
+        TimeLockInstruction::GetRevenue => {
+            msg!("Instruction: GetRevenue");
+            config::get_revenue(program_id, accounts)?;
+        }
+        TimeLockInstruction::RecordImmutability => {
+            msg!("Instruction: RecordImmutability");
+            config::record_immutability(program_id, accounts)?;
+        }
+        TimeLockInstruction::GetImmutability => {
+            msg!("Instruction: GetImmutability");
+            config::get_immutability(program_id, accounts)?;
+        }
+        TimeLockInstruction::LockStakeIdempotent {
+            timestamp,
+            beneficiary,
+        } => {
+            msg!("Instruction: LockStakeIdempotent");
+            stake::lock_stake_idempotent(program_id, accounts, timestamp, beneficiary)?;
+        }
+        TimeLockInstruction::RecordVerifiedBuild {
+            build_hash,
+            source_commit,
+        } => {
+            msg!("Instruction: RecordVerifiedBuild");
+            config::record_verified_build(program_id, accounts, build_hash, source_commit)?;
+        }
+        TimeLockInstruction::EnableAuditTrail => {
+            msg!("Instruction: EnableAuditTrail");
+            audit::enable_audit_trail(program_id, accounts)?;
+        }
+        TimeLockInstruction::RequireClaimCommit => {
+            msg!("Instruction: RequireClaimCommit");
+            claim::require_claim_commit(program_id, accounts)?;
+        }
+        TimeLockInstruction::CommitClaim { commitment } => {
+            msg!("Instruction: CommitClaim");
+            claim::commit_claim(program_id, accounts, commitment)?;
+        }
+        TimeLockInstruction::RevealClaim { salt } => {
+            msg!("Instruction: RevealClaim");
+            claim::reveal_claim(program_id, accounts, &salt)?;
+        }
+        TimeLockInstruction::InitializeNoncedTimeLock {
+            nonce,
+            timestamp,
+            secret,
+            bounty,
+            require_memo,
+            top_up_authority_only,
+        } => {
+            msg!("Instruction: InitializeNoncedTimeLock");
+            nonce::initialize_nonced_time_lock(
+                program_id,
+                accounts,
+                nonce,
+                timestamp,
+                secret,
+                bounty,
+                require_memo,
+                top_up_authority_only,
+            )?;
+        }
+        TimeLockInstruction::CreatePayroll {
+            amount,
+            start_time,
+            end_time,
+            num_entries,
+        } => {
+            msg!("Instruction: CreatePayroll");
+            payroll::create_payroll(
+                program_id,
+                accounts,
+                amount,
+                start_time,
+                end_time,
+                num_entries,
+            )?;
+        }
+        TimeLockInstruction::AddPayrollEntries { entries } => {
+            msg!("Instruction: AddPayrollEntries");
+            payroll::add_payroll_entries(program_id, accounts, &entries)?;
+        }
+        TimeLockInstruction::ClaimPayroll { index } => {
+            msg!("Instruction: ClaimPayroll");
+            payroll::claim_payroll(program_id, accounts, index)?;
+        }
+        TimeLockInstruction::CreateScheduleTemplate {
+            tge_bps,
+            cliff_months,
+            period_months,
+            periods,
+        } => {
+            msg!("Instruction: CreateScheduleTemplate");
+            payroll::create_schedule_template(
+                program_id,
+                accounts,
+                tge_bps,
+                cliff_months,
+                period_months,
+                periods,
+            )?;
+        }
+        TimeLockInstruction::CreateTemplatedPayroll {
+            amount,
+            start_time,
+            num_entries,
+        } => {
+            msg!("Instruction: CreateTemplatedPayroll");
+            payroll::create_templated_payroll(
+                program_id,
+                accounts,
+                amount,
+                start_time,
+                num_entries,
+            )?;
+        }
+        TimeLockInstruction::ClearCallback => {
+            msg!("Instruction: ClearCallback");
+            callback::clear_callback(program_id, accounts)?;
+        }
+        TimeLockInstruction::ClosePayroll => {
+            msg!("Instruction: ClosePayroll");
+            payroll::close_payroll(program_id, accounts)?;
+        }
+        TimeLockInstruction::MigrateAccount => {
+            msg!("Instruction: MigrateAccount");
+            migrate::migrate_account(program_id, accounts)?;
+        }
+        TimeLockInstruction::SetGrantRecipient { recipient } => {
+            msg!("Instruction: SetGrantRecipient");
+            set_grant_recipient(program_id, accounts, recipient)?;
+        }
+        TimeLockInstruction::ReleaseGrant => {
+            msg!("Instruction: ReleaseGrant");
+            release_grant(program_id, accounts)?;
+        }
+    }
+    if let Some(audited) = audited {
+        audited.record(program_id, accounts)?;
     }
     Ok(())
 }
 
-// Define struct representing our time lock account's data
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
-pub struct TimeLockAccount {
-    timestamp: i64,
-    secret: [u8; SECRET_LENGTH],
+/// Accounts of `InitializeTimeLock` and `InitializeNoncedTimeLock`
+struct InitializeTimeLockAccounts<'a, 'info> {
+    lock: &'a AccountInfo<'info>,
+    payer: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+    /// The stats and the config came after the first three accounts: a lock
+    /// created without them, as the first clients do, is neither counted nor
+    /// charged a fee
+    stats: Option<&'a AccountInfo<'info>>,
+    config: Option<&'a AccountInfo<'info>>,
 }
 
-#[allow(clippy::large_enum_variant)]
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
-pub enum TimeLockInstruction {
-    InitializeTimeLock {
-        timestamp: i64,
-        /// encrypted encoded secret
-        secret: [u8; SECRET_LENGTH],
-    },
-    TryUnlock,
-}
-
-impl TimeLockInstruction {
-    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
-        let (tag, rest) = input
-            .split_first()
-            .ok_or(ProgramError::InvalidInstructionData)?;
-        match tag {
-            0 => {
-                let (timestamp, rest) = rest.split_at(8);
-                let timestamp = i64::from_le_bytes(
-                    timestamp
-                        .try_into()
-                        .map_err(|_| ProgramError::InvalidInstructionData)?,
-                );
-                let secret: [u8; SECRET_LENGTH] = rest
-                    .try_into()
-                    .map_err(|_| ProgramError::InvalidInstructionData)?;
-                // check that the secret is valid utf8
-                str::from_utf8(&secret).map_err(|_| ProgramError::InvalidAccountData)?;
-                Ok(Self::InitializeTimeLock { timestamp, secret })
-            }
-            1 => Ok(Self::TryUnlock),
-            _ => Err(ProgramError::InvalidInstructionData),
-        }
+impl<'a, 'info> Accounts<'a, 'info> for InitializeTimeLockAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        let program_id = iter.program_id();
+        let lock = iter.next(Constraints::ANY.writable())?;
+        let payer = iter.next(Constraints::ANY.signer().writable())?;
+        let system_program = iter.next(Constraints::ANY.program(system_program::id()))?;
+        let stats = iter.next_if(&stats_address(program_id).0, Constraints::ANY.writable())?;
+        let config = match stats {
+            Some(_) => iter.next_if(&config_address(program_id).0, Constraints::ANY)?,
+            None => None,
+        };
+        Ok(Self {
+            lock,
+            payer,
+            system_program,
+            stats,
+            config,
+        })
     }
 }
 
+/// Create the time lock, the first account. A PDA lock is created with its
+/// `signer_seeds`, a keypair lock signs itself and passes none.
+#[allow(clippy::too_many_arguments)]
 fn initialize_time_lock(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     timestamp: i64,
     secret: [u8; SECRET_LENGTH],
+    bounty: u64,
+    require_memo: bool,
+    top_up_authority_only: bool,
+    signer_seeds: &[&[&[u8]]],
 ) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = InitializeTimeLockAccounts::validate(program_id, accounts)?;
+
+    // a timestamp passed while the transaction was in flight is let through
+    // by the configured tolerance, the lock then being unlockable at once
     let now = Clock::get()?.unix_timestamp;
-    if now >= timestamp {
+    let tolerance = match ctx.config {
+        Some(config_account) => config::clock_drift_tolerance(program_id, config_account)?,
+        None => 0,
+    };
+    if now >= timestamp.saturating_add(tolerance) {
         return Err(ProgramError::InvalidInstructionData);
     }
 
-    let accounts_iter = &mut accounts.iter();
-
-    let timelock_data_account = next_account_info(accounts_iter)?;
-    let payer_account = next_account_info(accounts_iter)?;
-    let system_program = next_account_info(accounts_iter)?;
+    if let Some(config_account) = ctx.config {
+        config::charge_creation(
+            program_id,
+            accounts,
+            config_account,
+            ctx.payer,
+            ctx.system_program,
+            timestamp.saturating_sub(now).max(0),
+        )?;
+    }
 
     // Size of our timelock data
-    let account_space = 8 + SECRET_LENGTH; // i64 timestamp + u64 secret length + SECRET_LENGTH byte secret
+    let account_space = TimeLockAccount::LEN;
 
-    // Calculate minimum balance for rent exemption
+    // Calculate minimum balance for rent exemption, the bounty is escrowed on top of it
     let rent = Rent::get()?;
-    let required_lamports = rent.minimum_balance(account_space);
+    let required_lamports = TimeLockAccount::rent_exempt_lamports(&rent)
+        .checked_add(bounty)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
 
     // Create the timelock account
-    invoke(
+    invoke_signed(
         &system_instruction::create_account(
-            payer_account.key,         // Account paying for the new account
-            timelock_data_account.key, // Account to be created
-            required_lamports,         // Amount of lamports to transfer to the new account
-            account_space as u64,      // Size in bytes to allocate for the data field
-            program_id,                // Set program owner to our program
+            ctx.payer.key,        // Account paying for the new account
+            ctx.lock.key,         // Account to be created
+            required_lamports,    // Amount of lamports to transfer to the new account
+            account_space as u64, // Size in bytes to allocate for the data field
+            program_id,           // Set program owner to our program
         ),
         &[
-            payer_account.clone(),
-            timelock_data_account.clone(),
-            system_program.clone(),
+            ctx.payer.clone(),
+            ctx.lock.clone(),
+            ctx.system_program.clone(),
         ],
+        signer_seeds,
     )?;
 
     // Create a new TimeLockAccount struct with the initial value
-    let timelock_data = TimeLockAccount { timestamp, secret };
+    let timelock_data = TimeLockAccount {
+        timestamp,
+        secret,
+        bounty,
+        attempts: 0,
+        premature_attempts: 0,
+        last_attempt: 0,
+        revealed_at: 0,
+        require_memo,
+        claim_memo_hash: [0; 32],
+        authority: *ctx.payer.key,
+        top_up_authority_only,
+        unlockers: [Pubkey::default(); MAX_UNLOCKERS],
+        expires_at: 0,
+        expiry_recipient: Pubkey::default(),
+        heartbeat_interval: 0,
+        recurrence_unit: TimeLockAccount::RECUR_NONE,
+        recurrence_every: 0,
+        recurrence_window: 0,
+        has_callback: false,
+        condition_account: Pubkey::default(),
+        condition_offset: 0,
+        condition_len: 0,
+        condition_bytes: [0; MAX_CONDITION_BYTES],
+        predecessor: Pubkey::default(),
+        has_audit_trail: false,
+        claim_commit_required: false,
+        in_progress: false,
+        funded: 0,
+        grant_recipient: Pubkey::default(),
+    };
 
     // Get a mutable reference to the timelock account's data
-    let mut account_data = &mut timelock_data_account.data.borrow_mut()[..];
+    let mut account_data = &mut ctx.lock.data.borrow_mut()[..];
 
     // Serialize the TimeLockAccount struct into the account's data
     timelock_data.serialize(&mut account_data)?;
 
-    msg!("TimeLock set to unix timestamp: {}", timestamp);
+    if let Some(stats_account) = ctx.stats {
+        create_stats_if_needed(program_id, stats_account, ctx.payer, ctx.system_program)?;
+        update_stats(program_id, stats_account, |stats| {
+            stats.active_locks += 1;
+            stats.total_locks += 1;
+            stats.locked_lamports = stats
+                .locked_lamports
+                .checked_add(required_lamports)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            Ok(())
+        })?;
+    }
+
+    msg!(
+        "TimeLock set to unix timestamp: {} with bounty: {}",
+        timestamp,
+        bounty
+    );
+    emitter.emit(&TimeLockEvent::LockCreated {
+        lock: *ctx.lock.key,
+        payer: *ctx.payer.key,
+        timestamp,
+        bounty,
+    })?;
     Ok(())
 }
 
-fn try_unlock(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
-    let accounts_iter = &mut accounts.iter();
-    let timelock_data_account = next_account_info(accounts_iter)?;
+/// Accounts of `TryUnlock`
+struct TryUnlockAccounts<'a, 'info> {
+    lock: &'a AccountInfo<'info>,
+    /// The cranker and the accounts paying it out, or the callback and
+    /// condition accounts, then the event accounts
+    remaining: &'a [AccountInfo<'info>],
+}
 
-    // Verify account ownership
-    if timelock_data_account.owner != program_id {
-        return Err(ProgramError::IncorrectProgramId);
+impl<'a, 'info> Accounts<'a, 'info> for TryUnlockAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            lock: iter.next(Constraints::ANY.writable().program_owned())?,
+            remaining: iter.remaining(),
+        })
     }
+}
+
+fn try_unlock(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = TryUnlockAccounts::validate(program_id, accounts)?;
 
     // Deserialize the account data
-    let timelock_data = TimeLockAccount::try_from_slice(&timelock_data_account.data.borrow())?;
+    let mut timelock_data = TimeLockAccount::try_from_slice(&ctx.lock.data.borrow())?;
+    // called back by its own callback
+    if timelock_data.in_progress {
+        return Err(TimeLockError::ReentrantCall.into());
+    }
+    // the cranker, when given, comes right after the lock; the callback PDA,
+    // owned by this program, or the condition account do when it is not
+    let cranker_account = ctx.remaining.first().filter(|a| {
+        !emitter.is_event_authority(a.key)
+            && a.owner != program_id
+            && *a.key != timelock_data.condition_account
+    });
+    if timelock_data.has_unlockers() {
+        match cranker_account {
+            Some(a) if a.is_signer && timelock_data.allows_unlocker(a.key) => {}
+            _ => return Err(TimeLockError::UnlockerNotAllowed.into()),
+        }
+    }
     let now = Clock::get()?.unix_timestamp;
+    timelock_data.attempts = timelock_data.attempts.saturating_add(1);
+    timelock_data.last_attempt = now;
 
-    match now >= timelock_data.timestamp {
-        true => msg!(
-            "TimeLock unlocked! Encryped secret: {}",
-            str::from_utf8(&timelock_data.secret).map_err(|_| ProgramError::InvalidAccountData)?
-        ),
-        false => msg!("TimeLock will lock until {}", timelock_data.timestamp),
+    let bounty = match evaluate_unlock(now, &timelock_data) {
+        UnlockDecision::Reveal {
+            revealed_at,
+            bounty,
+        } => {
+            if timelock_data.has_condition() {
+                let condition_account = accounts
+                    .iter()
+                    .find(|a| *a.key == timelock_data.condition_account)
+                    .ok_or(ProgramError::NotEnoughAccountKeys)?;
+                if !timelock_data.condition_met(&condition_account.data.borrow()) {
+                    return Err(TimeLockError::ConditionNotMet.into());
+                }
+            }
+            if timelock_data.has_predecessor() {
+                let predecessor_account = accounts
+                    .iter()
+                    .find(|a| *a.key == timelock_data.predecessor)
+                    .ok_or(ProgramError::NotEnoughAccountKeys)?;
+                if predecessor_account.owner != program_id {
+                    return Err(ProgramError::IncorrectProgramId);
+                }
+                let predecessor =
+                    TimeLockAccount::try_from_slice(&predecessor_account.data.borrow())?;
+                if predecessor.revealed_at == 0 {
+                    return Err(TimeLockError::PredecessorLocked.into());
+                }
+            }
+            let first_reveal = timelock_data.revealed_at == 0;
+            timelock_data.revealed_at = revealed_at;
+            timelock_data.serialize(&mut &mut ctx.lock.data.borrow_mut()[..])?;
+            msg!(
+                "TimeLock unlocked! Encryped secret: {}",
+                str::from_utf8(&timelock_data.secret)
+                    .map_err(|_| ProgramError::InvalidAccountData)?
+            );
+            emitter.emit(&TimeLockEvent::SecretRevealed {
+                lock: *ctx.lock.key,
+                secret: timelock_data.secret,
+            })?;
+            if first_reveal && timelock_data.has_callback {
+                // marked in progress for the call: the lock must come back
+                // from it as it went, a call back to mutate it meanwhile
+                // fails the reveal
+                timelock_data.in_progress = true;
+                timelock_data.serialize(&mut &mut ctx.lock.data.borrow_mut()[..])?;
+                let lamports = ctx.lock.lamports();
+                let program = callback::invoke_callback(
+                    program_id,
+                    accounts,
+                    ctx.lock.key,
+                    &timelock_data.secret,
+                )?;
+                check_not_reentered(ctx.lock, &timelock_data, lamports)?;
+                timelock_data.in_progress = false;
+                timelock_data.serialize(&mut &mut ctx.lock.data.borrow_mut()[..])?;
+                emitter.emit(&TimeLockEvent::CallbackInvoked {
+                    lock: *ctx.lock.key,
+                    program,
+                })?;
+            }
+            bounty
+        }
+        UnlockDecision::TooEarly => {
+            timelock_data.premature_attempts = timelock_data.premature_attempts.saturating_add(1);
+            timelock_data.serialize(&mut &mut ctx.lock.data.borrow_mut()[..])?;
+            msg!("TimeLock will lock until {}", timelock_data.timestamp);
+            emitter.emit(&TimeLockEvent::UnlockTooEarly {
+                lock: *ctx.lock.key,
+                timestamp: timelock_data.timestamp,
+                now,
+            })?;
+            return Ok(());
+        }
+    };
+
+    // Pay the bounty to the cranker, if one is set aside and a cranker signed,
+    // unless it is kept for committed claims
+    if bounty > 0 {
+        if let Some(cranker_account) = cranker_account {
+            if !cranker_account.is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if timelock_data.claim_commit_required {
+                msg!("Bounty only paid to committed claims");
+                return Ok(());
+            }
+            pay_bounty(
+                program_id,
+                &emitter,
+                ctx.lock,
+                &mut timelock_data,
+                cranker_account,
+                &ctx.remaining[1..],
+            )?;
+        }
     }
 
     Ok(())
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use solana_program_test::*;
-    use solana_sdk::{
-        instruction::{AccountMeta, Instruction},
-        signature::{Keypair, Signer},
-        system_program,
-        transaction::Transaction,
+/// Accounts paying out a bounty, after the lock and its payee
+struct BountyAccounts<'a, 'info> {
+    stats: &'a AccountInfo<'info>,
+    config: &'a AccountInfo<'info>,
+    /// Read for locks requiring a memo only
+    instructions_sysvar: Option<&'a AccountInfo<'info>>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for BountyAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            stats: iter.next(Constraints::ANY.writable())?,
+            config: iter.next(Constraints::ANY)?,
+            instructions_sysvar: iter.next_if(&sysvar::instructions::id(), Constraints::ANY)?,
+        })
+    }
+}
+
+/// Pay the bounty of a revealed lock to `payee`, the stats, the config and,
+/// for locks requiring a memo, the instructions sysvar coming next in `accounts`
+fn pay_bounty(
+    program_id: &Pubkey,
+    emitter: &EventEmitter,
+    timelock_data_account: &AccountInfo,
+    timelock_data: &mut TimeLockAccount,
+    payee: &AccountInfo,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let bounty = timelock_data.bounty;
+    let ctx = BountyAccounts::validate(program_id, accounts)?;
+    config::check_not_paused(program_id, ctx.config)?;
+    if timelock_data.require_memo {
+        let instructions_sysvar = ctx
+            .instructions_sysvar
+            .ok_or(ProgramError::UnsupportedSysvar)?;
+        timelock_data.claim_memo_hash = memo_hash(instructions_sysvar)?;
+    }
+    timelock_data.bounty = 0;
+    timelock_data.serialize(&mut &mut timelock_data_account.data.borrow_mut()[..])?;
+
+    **timelock_data_account.try_borrow_mut_lamports()? -= bounty;
+    **payee.try_borrow_mut_lamports()? += bounty;
+    // locks older than the stats account were never added to it
+    update_stats(program_id, ctx.stats, |stats| {
+        stats.locked_lamports = stats.locked_lamports.saturating_sub(bounty);
+        Ok(())
+    })?;
+    msg!("Bounty of {} lamports paid to {}", bounty, payee.key);
+    emitter.emit(&TimeLockEvent::BountyPaid {
+        lock: *timelock_data_account.key,
+        cranker: *payee.key,
+        amount: bounty,
+    })
+}
+
+/// Accounts of `TopUp`
+struct TopUpAccounts<'a, 'info> {
+    lock: &'a AccountInfo<'info>,
+    depositor: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+    stats: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for TopUpAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            lock: iter.next(Constraints::ANY.writable().program_owned())?,
+            depositor: iter.next(Constraints::ANY.signer().writable())?,
+            system_program: iter.next(Constraints::ANY.program(system_program::id()))?,
+            stats: iter.next(Constraints::ANY.writable())?,
+        })
+    }
+}
+
+fn top_up(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = TopUpAccounts::validate(program_id, accounts)?;
+
+    let mut timelock_data = TimeLockAccount::try_from_slice(&ctx.lock.data.borrow())?;
+    if timelock_data.top_up_authority_only && *ctx.depositor.key != timelock_data.authority {
+        return Err(TimeLockError::AuthorityRequired.into());
+    }
+    if amount == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    // once matured the lock may reveal at any time, deposits would race it
+    if Clock::get()?.unix_timestamp >= timelock_data.timestamp {
+        return Err(TimeLockError::LockMatured.into());
+    }
+
+    // the installments are kept apart from the bounty, which goes to the
+    // cranker, for the grant recipient to receive them
+    timelock_data.funded = timelock_data
+        .funded
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    invoke(
+        &system_instruction::transfer(ctx.depositor.key, ctx.lock.key, amount),
+        &[
+            ctx.depositor.clone(),
+            ctx.lock.clone(),
+            ctx.system_program.clone(),
+        ],
+    )?;
+    timelock_data.serialize(&mut &mut ctx.lock.data.borrow_mut()[..])?;
+    // locks older than the stats account were never added to it
+    update_stats(program_id, ctx.stats, |stats| {
+        stats.locked_lamports = stats.locked_lamports.saturating_add(amount);
+        Ok(())
+    })?;
+
+    msg!(
+        "TimeLock topped up by {} lamports, funded now {}",
+        amount,
+        timelock_data.funded
+    );
+    emitter.emit(&TimeLockEvent::ToppedUp {
+        lock: *ctx.lock.key,
+        depositor: *ctx.depositor.key,
+        amount,
+        funded: timelock_data.funded,
+    })?;
+    Ok(())
+}
+
+/// Accounts of `WithdrawExcess`
+struct WithdrawExcessAccounts<'a, 'info> {
+    lock: &'a AccountInfo<'info>,
+    authority: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for WithdrawExcessAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            lock: iter.next(Constraints::ANY.writable().program_owned())?,
+            authority: iter.next(Constraints::ANY.signer().writable())?,
+        })
+    }
+}
+
+fn withdraw_excess(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = WithdrawExcessAccounts::validate(program_id, accounts)?;
+
+    let timelock_data = TimeLockAccount::try_from_slice(&ctx.lock.data.borrow())?;
+    if *ctx.authority.key != timelock_data.authority {
+        return Err(TimeLockError::AuthorityRequired.into());
+    }
+
+    // everything the lock accounts for stays: its rent exemption, bounty and
+    // installments
+    let kept = Rent::get()?
+        .minimum_balance(ctx.lock.data_len())
+        .checked_add(timelock_data.bounty)
+        .and_then(|kept| kept.checked_add(timelock_data.funded))
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let excess = ctx.lock.lamports().saturating_sub(kept);
+    if excess == 0 {
+        msg!("No excess lamports to withdraw");
+        return Ok(());
+    }
+    **ctx.lock.try_borrow_mut_lamports()? -= excess;
+    **ctx.authority.try_borrow_mut_lamports()? += excess;
+
+    msg!(
+        "Withdrew {} excess lamports to {}",
+        excess,
+        ctx.authority.key
+    );
+    emitter.emit(&TimeLockEvent::ExcessWithdrawn {
+        lock: *ctx.lock.key,
+        authority: *ctx.authority.key,
+        amount: excess,
+    })?;
+    Ok(())
+}
+
+/// Accounts of the lock settings of its authority: `SetGrantRecipient`,
+/// `AddUnlocker`, `RemoveUnlocker`, `SetExpiry`, `SetHeartbeat`, `Heartbeat`,
+/// `SetRecurrence`, `SetCondition`, `SetPredecessor` and `RequireClaimCommit`
+struct LockAuthorityAccounts<'a, 'info> {
+    lock: &'a AccountInfo<'info>,
+    authority: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for LockAuthorityAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            lock: iter.next(Constraints::ANY.writable().program_owned())?,
+            authority: iter.next(Constraints::ANY.signer())?,
+        })
+    }
+}
+
+fn set_grant_recipient(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    recipient: Pubkey,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = LockAuthorityAccounts::validate(program_id, accounts)?;
+
+    let mut timelock_data = TimeLockAccount::try_from_slice(&ctx.lock.data.borrow())?;
+    if *ctx.authority.key != timelock_data.authority {
+        return Err(TimeLockError::AuthorityRequired.into());
+    }
+    // the installments are owed to the recipient named while they were deposited
+    if Clock::get()?.unix_timestamp >= timelock_data.timestamp {
+        return Err(TimeLockError::LockMatured.into());
+    }
+    timelock_data.grant_recipient = recipient;
+    timelock_data.serialize(&mut &mut ctx.lock.data.borrow_mut()[..])?;
+
+    msg!(
+        "TimeLock installments go to {}",
+        timelock_data.grant_payee()
+    );
+    emitter.emit(&TimeLockEvent::GrantRecipientSet {
+        lock: *ctx.lock.key,
+        recipient,
+    })?;
+    Ok(())
+}
+
+/// Accounts of the payouts of a lock to its recipient: `ReleaseGrant` and
+/// `Expire`
+struct LockRecipientAccounts<'a, 'info> {
+    lock: &'a AccountInfo<'info>,
+    recipient: &'a AccountInfo<'info>,
+    stats: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for LockRecipientAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            lock: iter.next(Constraints::ANY.writable().program_owned())?,
+            recipient: iter.next(Constraints::ANY.writable())?,
+            stats: iter.next(Constraints::ANY.writable())?,
+        })
+    }
+}
+
+fn release_grant(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = LockRecipientAccounts::validate(program_id, accounts)?;
+
+    let mut timelock_data = TimeLockAccount::try_from_slice(&ctx.lock.data.borrow())?;
+    if timelock_data.revealed_at == 0 {
+        return Err(TimeLockError::SecretNotRevealed.into());
+    }
+    if *ctx.recipient.key != timelock_data.grant_payee() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let amount = timelock_data.funded;
+    if amount == 0 {
+        msg!("No installments to release");
+        return Ok(());
+    }
+    timelock_data.funded = 0;
+    timelock_data.serialize(&mut &mut ctx.lock.data.borrow_mut()[..])?;
+
+    **ctx.lock.try_borrow_mut_lamports()? -= amount;
+    **ctx.recipient.try_borrow_mut_lamports()? += amount;
+    // locks older than the stats account were never added to it
+    update_stats(program_id, ctx.stats, |stats| {
+        stats.locked_lamports = stats.locked_lamports.saturating_sub(amount);
+        Ok(())
+    })?;
+
+    msg!(
+        "Grant of {} lamports released to {}",
+        amount,
+        ctx.recipient.key
+    );
+    emitter.emit(&TimeLockEvent::GrantReleased {
+        lock: *ctx.lock.key,
+        recipient: *ctx.recipient.key,
+        amount,
+    })?;
+    Ok(())
+}
+
+fn add_unlocker(program_id: &Pubkey, accounts: &[AccountInfo], unlocker: Pubkey) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = LockAuthorityAccounts::validate(program_id, accounts)?;
+
+    let mut timelock_data = TimeLockAccount::try_from_slice(&ctx.lock.data.borrow())?;
+    if *ctx.authority.key != timelock_data.authority {
+        return Err(TimeLockError::AuthorityRequired.into());
+    }
+    // the default pubkey marks a free slot
+    if unlocker == Pubkey::default() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if timelock_data.unlockers.contains(&unlocker) {
+        msg!("{} already allowed to unlock", unlocker);
+        return Ok(());
+    }
+    let slot = timelock_data
+        .unlockers
+        .iter_mut()
+        .find(|k| **k == Pubkey::default())
+        .ok_or(TimeLockError::UnlockersFull)?;
+    *slot = unlocker;
+    timelock_data.serialize(&mut &mut ctx.lock.data.borrow_mut()[..])?;
+
+    msg!("{} allowed to unlock", unlocker);
+    emitter.emit(&TimeLockEvent::UnlockerAdded {
+        lock: *ctx.lock.key,
+        unlocker,
+    })?;
+    Ok(())
+}
+
+fn remove_unlocker(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    unlocker: Pubkey,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = LockAuthorityAccounts::validate(program_id, accounts)?;
+
+    let mut timelock_data = TimeLockAccount::try_from_slice(&ctx.lock.data.borrow())?;
+    if *ctx.authority.key != timelock_data.authority {
+        return Err(TimeLockError::AuthorityRequired.into());
+    }
+    let Some(slot) = timelock_data
+        .unlockers
+        .iter_mut()
+        .find(|k| **k == unlocker && unlocker != Pubkey::default())
+    else {
+        msg!("{} was not allowed to unlock", unlocker);
+        return Ok(());
     };
+    *slot = Pubkey::default();
+    timelock_data.serialize(&mut &mut ctx.lock.data.borrow_mut()[..])?;
 
-    #[tokio::test]
-    async fn test_timelock_program() {
-        let program_id = Pubkey::new_unique();
-        let (banks_client, payer, recent_blockhash) = ProgramTest::new(
-            "timelock_program",
-            program_id,
-            processor!(process_instruction),
-        )
-        .start()
-        .await;
-
-        // Create a new keypair to use as the address for our timelock account
-        let timelock_keypair = Keypair::new();
-        let timestamp: i64 = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64
-            + 5;
-        let secret = [65; SECRET_LENGTH];
-
-        // Step 1: Initialize the timelock
-        println!("Testing timelock initialization...");
-
-        // Create initialization instruction
-        let mut init_instruction_data = vec![0]; // 0 = initialize instruction
-        init_instruction_data.extend_from_slice(&timestamp.to_le_bytes());
-        init_instruction_data.extend_from_slice(&secret);
-
-        let initialize_instruction = Instruction::new_with_bytes(
-            program_id,
-            &init_instruction_data,
-            vec![
-                AccountMeta::new(timelock_keypair.pubkey(), true),
-                AccountMeta::new(payer.pubkey(), true),
-                AccountMeta::new_readonly(system_program::id(), false),
-            ],
-        );
-
-        // Send transaction with initialize instruction
-        let mut transaction =
-            Transaction::new_with_payer(&[initialize_instruction], Some(&payer.pubkey()));
-        transaction.sign(&[&payer, &timelock_keypair], recent_blockhash);
-        banks_client.process_transaction(transaction).await.unwrap();
-
-        // Check account data
-        let account = banks_client
-            .get_account(timelock_keypair.pubkey())
-            .await
-            .expect("Failed to get timelock account");
-
-        if let Some(account_data) = account {
-            let timelock = TimeLockAccount::try_from_slice(&account_data.data)
-                .expect("Failed to deserialize timelock data");
-            assert_eq!(timelock.timestamp, timestamp);
-            println!(
-                "✅ TimeLock initialized successfully with value: {}",
-                timelock.timestamp
-            );
+    msg!("{} no longer allowed to unlock", unlocker);
+    emitter.emit(&TimeLockEvent::UnlockerRemoved {
+        lock: *ctx.lock.key,
+        unlocker,
+    })?;
+    Ok(())
+}
+
+fn set_expiry(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    expires_at: i64,
+    recipient: Pubkey,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = LockAuthorityAccounts::validate(program_id, accounts)?;
+
+    let mut timelock_data = TimeLockAccount::try_from_slice(&ctx.lock.data.borrow())?;
+    if *ctx.authority.key != timelock_data.authority {
+        return Err(TimeLockError::AuthorityRequired.into());
+    }
+    // unlockers count on the window they were given
+    if Clock::get()?.unix_timestamp >= timelock_data.timestamp {
+        return Err(TimeLockError::LockMatured.into());
+    }
+    if expires_at != 0 && expires_at <= timelock_data.timestamp {
+        return Err(TimeLockError::InvalidExpiry.into());
+    }
+    timelock_data.expires_at = expires_at;
+    timelock_data.expiry_recipient = recipient;
+    timelock_data.serialize(&mut &mut ctx.lock.data.borrow_mut()[..])?;
+
+    msg!(
+        "TimeLock expires at {}, closed to {}",
+        expires_at,
+        recipient
+    );
+    emitter.emit(&TimeLockEvent::LockExpirySet {
+        lock: *ctx.lock.key,
+        expires_at,
+        recipient,
+    })?;
+    Ok(())
+}
+
+fn expire(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = LockRecipientAccounts::validate(program_id, accounts)?;
+
+    let timelock_data = TimeLockAccount::try_from_slice(&ctx.lock.data.borrow())?;
+    if timelock_data.expires_at == 0 || Clock::get()?.unix_timestamp < timelock_data.expires_at {
+        return Err(TimeLockError::LockNotExpired.into());
+    }
+    if *ctx.recipient.key != timelock_data.expiry_recipient {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // the rent and any unclaimed bounty go to the recipient, the secret is wiped
+    let lamports = ctx.lock.lamports();
+    **ctx.lock.try_borrow_mut_lamports()? = 0;
+    **ctx.recipient.try_borrow_mut_lamports()? += lamports;
+    ctx.lock.data.borrow_mut().fill(0);
+    // locks older than the stats account were never added to it
+    update_stats(program_id, ctx.stats, |stats| {
+        stats.active_locks = stats.active_locks.saturating_sub(1);
+        stats.locked_lamports = stats.locked_lamports.saturating_sub(lamports);
+        Ok(())
+    })?;
+
+    msg!(
+        "TimeLock expired, {} lamports to {}",
+        lamports,
+        ctx.recipient.key
+    );
+    emitter.emit(&TimeLockEvent::LockExpired {
+        lock: *ctx.lock.key,
+        recipient: *ctx.recipient.key,
+        amount: lamports,
+    })?;
+    Ok(())
+}
+
+fn set_heartbeat(program_id: &Pubkey, accounts: &[AccountInfo], interval: i64) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = LockAuthorityAccounts::validate(program_id, accounts)?;
+
+    let mut timelock_data = TimeLockAccount::try_from_slice(&ctx.lock.data.borrow())?;
+    if *ctx.authority.key != timelock_data.authority {
+        return Err(TimeLockError::AuthorityRequired.into());
+    }
+    if Clock::get()?.unix_timestamp >= timelock_data.timestamp {
+        return Err(TimeLockError::LockMatured.into());
+    }
+    if interval < 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    timelock_data.heartbeat_interval = interval;
+    timelock_data.serialize(&mut &mut ctx.lock.data.borrow_mut()[..])?;
+
+    msg!("TimeLock heartbeat interval set to {}", interval);
+    emitter.emit(&TimeLockEvent::HeartbeatSet {
+        lock: *ctx.lock.key,
+        interval,
+    })?;
+    Ok(())
+}
+
+fn heartbeat(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = LockAuthorityAccounts::validate(program_id, accounts)?;
+
+    let mut timelock_data = TimeLockAccount::try_from_slice(&ctx.lock.data.borrow())?;
+    if *ctx.authority.key != timelock_data.authority {
+        return Err(TimeLockError::AuthorityRequired.into());
+    }
+    if timelock_data.heartbeat_interval == 0 {
+        return Err(TimeLockError::HeartbeatDisabled.into());
+    }
+    // a missed heartbeat is final, the secret may already be revealed
+    let now = Clock::get()?.unix_timestamp;
+    if now >= timelock_data.timestamp {
+        return Err(TimeLockError::LockMatured.into());
+    }
+
+    let timestamp = now
+        .checked_add(timelock_data.heartbeat_interval)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    if timestamp > timelock_data.timestamp {
+        // the expiry window keeps its length
+        if timelock_data.expires_at != 0 {
+            timelock_data.expires_at = timelock_data
+                .expires_at
+                .checked_add(timestamp - timelock_data.timestamp)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
         }
+        timelock_data.timestamp = timestamp;
+        timelock_data.serialize(&mut &mut ctx.lock.data.borrow_mut()[..])?;
+    }
 
-        // Step 2: Increment the timelock
-        println!("Testing timelock unlock...");
+    msg!(
+        "TimeLock heartbeat, locked until {}",
+        timelock_data.timestamp
+    );
+    emitter.emit(&TimeLockEvent::Heartbeat {
+        lock: *ctx.lock.key,
+        timestamp: timelock_data.timestamp,
+    })?;
+    Ok(())
+}
 
-        // Create increment instruction
-        let increment_instruction = Instruction::new_with_bytes(
-            program_id,
-            &[1], // 1 = try unlock instruction
-            vec![AccountMeta::new(timelock_keypair.pubkey(), true)],
-        );
-
-        // Send transaction with increment instruction
-        let mut transaction =
-            Transaction::new_with_payer(&[increment_instruction], Some(&payer.pubkey()));
-        transaction.sign(&[&payer, &timelock_keypair], recent_blockhash);
-        banks_client.process_transaction(transaction).await.unwrap();
-
-        // Check account data
-        let account = banks_client
-            .get_account(timelock_keypair.pubkey())
-            .await
-            .expect("Failed to get timelock account");
-
-        if let Some(account_data) = account {
-            let timelock = TimeLockAccount::try_from_slice(&account_data.data)
-                .expect("Failed to deserialize timelock data");
-            assert_eq!(timelock.secret, secret);
-            println!(
-                "✅ TimeLock unlock successfully: {}",
-                str::from_utf8(&timelock.secret).unwrap()
-            );
+fn set_recurrence(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    unit: u8,
+    every: u32,
+    window: u32,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = LockAuthorityAccounts::validate(program_id, accounts)?;
+
+    let mut timelock_data = TimeLockAccount::try_from_slice(&ctx.lock.data.borrow())?;
+    if *ctx.authority.key != timelock_data.authority {
+        return Err(TimeLockError::AuthorityRequired.into());
+    }
+    if Clock::get()?.unix_timestamp >= timelock_data.timestamp {
+        return Err(TimeLockError::LockMatured.into());
+    }
+    let (every, window) = match unit {
+        TimeLockAccount::RECUR_NONE => (0, 0),
+        TimeLockAccount::RECUR_SECONDS | TimeLockAccount::RECUR_MONTHS
+            if every > 0 && window > 0 =>
+        {
+            (every, window)
         }
+        _ => return Err(TimeLockError::InvalidRecurrence.into()),
+    };
+    timelock_data.recurrence_unit = unit;
+    timelock_data.recurrence_every = every;
+    timelock_data.recurrence_window = window;
+    timelock_data.serialize(&mut &mut ctx.lock.data.borrow_mut()[..])?;
+
+    msg!(
+        "TimeLock opens {} seconds every {} in unit {}",
+        window,
+        every,
+        unit
+    );
+    emitter.emit(&TimeLockEvent::RecurrenceSet {
+        lock: *ctx.lock.key,
+        unit,
+        every,
+        window,
+    })?;
+    Ok(())
+}
+
+fn set_condition(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    account: Pubkey,
+    offset: u32,
+    expected: &[u8],
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = LockAuthorityAccounts::validate(program_id, accounts)?;
+
+    let mut timelock_data = TimeLockAccount::try_from_slice(&ctx.lock.data.borrow())?;
+    if *ctx.authority.key != timelock_data.authority {
+        return Err(TimeLockError::AuthorityRequired.into());
+    }
+    if Clock::get()?.unix_timestamp >= timelock_data.timestamp {
+        return Err(TimeLockError::LockMatured.into());
+    }
+    let mut condition_bytes = [0; MAX_CONDITION_BYTES];
+    condition_bytes[..expected.len()].copy_from_slice(expected);
+    timelock_data.condition_account = account;
+    timelock_data.condition_offset = offset;
+    timelock_data.condition_len = expected.len() as u8;
+    timelock_data.condition_bytes = condition_bytes;
+    timelock_data.serialize(&mut &mut ctx.lock.data.borrow_mut()[..])?;
+
+    msg!(
+        "TimeLock reveals once {} holds {} bytes at {}",
+        account,
+        expected.len(),
+        offset
+    );
+    emitter.emit(&TimeLockEvent::ConditionSet {
+        lock: *ctx.lock.key,
+        account,
+        offset,
+    })?;
+    Ok(())
+}
+
+fn set_predecessor(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    predecessor: Pubkey,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = LockAuthorityAccounts::validate(program_id, accounts)?;
+
+    let mut timelock_data = TimeLockAccount::try_from_slice(&ctx.lock.data.borrow())?;
+    if *ctx.authority.key != timelock_data.authority {
+        return Err(TimeLockError::AuthorityRequired.into());
+    }
+    if Clock::get()?.unix_timestamp >= timelock_data.timestamp {
+        return Err(TimeLockError::LockMatured.into());
     }
+    // a lock waiting on itself would never reveal
+    if predecessor == *ctx.lock.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    timelock_data.predecessor = predecessor;
+    timelock_data.serialize(&mut &mut ctx.lock.data.borrow_mut()[..])?;
+
+    msg!("TimeLock reveals after {}", predecessor);
+    emitter.emit(&TimeLockEvent::PredecessorSet {
+        lock: *ctx.lock.key,
+        predecessor,
+    })?;
+    Ok(())
+}
+
+/// Hash of the first SPL memo of the transaction
+fn memo_hash(instructions_sysvar: &AccountInfo) -> Result<[u8; 32], ProgramError> {
+    let mut index = 0;
+    while let Ok(ix) = load_instruction_at_checked(index, instructions_sysvar) {
+        if ix.program_id == MEMO_PROGRAM_ID || ix.program_id == MEMO_V1_PROGRAM_ID {
+            return Ok(hash(&ix.data).to_bytes());
+        }
+        index += 1;
+    }
+    Err(TimeLockError::MemoRequired.into())
+}
+
+/// Accounts of `GetPosition`
+struct GetPositionAccounts<'a, 'info> {
+    lock: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for GetPositionAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            lock: iter.next(Constraints::ANY.program_owned())?,
+        })
+    }
+}
+
+fn get_position(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let ctx = GetPositionAccounts::validate(program_id, accounts)?;
+    let timelock_data = TimeLockAccount::try_from_slice(&ctx.lock.data.borrow())?;
+    let position = LockPosition::new(*ctx.lock.key, &timelock_data, Clock::get()?.unix_timestamp);
+    set_return_data(&borsh::to_vec(&position)?);
+    Ok(())
+}
+
+/// Accounts of `EmitEvent`
+struct EmitEventAccounts<'a, 'info> {
+    _event_authority: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for EmitEventAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        let address = event_authority(iter.program_id()).0;
+        Ok(Self {
+            // Only the program can sign for its event authority, so only it can emit events
+            _event_authority: iter.next(Constraints::ANY.signer().pda(address))?,
+        })
+    }
+}
+
+fn emit_event(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    EmitEventAccounts::validate(program_id, accounts)?;
+    Ok(())
 }
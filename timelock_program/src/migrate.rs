@@ -0,0 +1,143 @@
+use borsh::BorshDeserialize;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program::invoke;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::sysvar::Sysvar as _;
+use solana_program::{msg, system_instruction, system_program};
+use timelock_interface::error::TimeLockError;
+use timelock_interface::pda::{
+    callback_address, config_address, config_update_address, fee_exemption_address,
+    stake_lock_address, stats_address,
+};
+use timelock_interface::state::{
+    CallbackAccount, ConfigAccount, ConfigUpdateAccount, DistributorAccount, FeeExemptionAccount,
+    StakeLockAccount, TimeLockAccount, TimeLockStats,
+};
+
+use crate::accounts::{Accounts, AccountsIter, Constraints};
+use crate::event::EventEmitter;
+use crate::idl::idl_address;
+use crate::TimeLockEvent;
+
+/// Size of the statistics created before they stored their bump
+const LEGACY_STATS_LEN: usize = 24;
+/// Size of the config created before it stored its bump and verified build
+const LEGACY_CONFIG_LEN: usize = 227;
+/// Size of the config updates queued before they stored their bump
+const LEGACY_CONFIG_UPDATE_LEN: usize = 147;
+/// Size of the fee exemptions created before they stored their bump
+const LEGACY_FEE_EXEMPTION_LEN: usize = 32;
+/// Size of the callbacks set before they stored their bump
+const LEGACY_CALLBACK_LEN: usize = 259;
+/// Size of the stake locks created before they stored their bump
+const LEGACY_STAKE_LOCK_LEN: usize = 104;
+/// Sizes of the time locks created before their layout grew, the fields
+/// added since all defaulting to zero: 264 for the first locks, a timestamp
+/// and a secret, 272 once they escrowed a bounty, 649 before the audit trail
+/// flag, 650 before the claim commit flag, 651 before the in progress flag,
+/// 652 before the grant installments
+const LEGACY_LOCK_LENS: &[usize] = &[264, 272, 649, 650, 651, 652];
+
+/// Accounts of `MigrateAccount`
+struct MigrateAccountAccounts<'a, 'info> {
+    account: &'a AccountInfo<'info>,
+    payer: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for MigrateAccountAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            account: iter.next(Constraints::ANY.writable().program_owned())?,
+            payer: iter.next(Constraints::ANY.signer().writable())?,
+            system_program: iter.next(Constraints::ANY.program(system_program::id()))?,
+        })
+    }
+}
+
+/// Whether `data`, of a legacy size, is a time lock. Time locks are keypair
+/// accounts, with no address to tell them by: the distributors and the IDL
+/// account are the other accounts of the program that may be that size.
+fn is_legacy_lock(program_id: &Pubkey, account: &AccountInfo, data: &[u8]) -> bool {
+    let is_distributor = DistributorAccount::try_from_slice(data)
+        .is_ok_and(|distributor| DistributorAccount::space(distributor.num_leaves) == data.len());
+    let mut grown = data.to_vec();
+    grown.resize(TimeLockAccount::LEN, 0);
+    !is_distributor
+        && *account.key != idl_address(program_id)
+        && TimeLockAccount::try_from_slice(&grown).is_ok()
+}
+
+/// Bytes to append to `account` for it to be in its current layout. The
+/// kind of a PDA is told by its legacy size and proven by its address, so
+/// that no other account of that size is ever grown as one.
+fn legacy_tail(program_id: &Pubkey, account: &AccountInfo) -> Result<Vec<u8>, ProgramError> {
+    let data = account.data.borrow();
+    // every PDA the program creates is keyed by the pubkey its data starts with
+    let seed_key = || {
+        data.get(..32)
+            .and_then(|key| Pubkey::try_from(key).ok())
+            .ok_or(ProgramError::InvalidAccountData)
+    };
+    let (len, (address, bump)) = match data.len() {
+        LEGACY_STATS_LEN => (TimeLockStats::LEN, stats_address(program_id)),
+        LEGACY_CONFIG_LEN => (ConfigAccount::LEN, config_address(program_id)),
+        LEGACY_CONFIG_UPDATE_LEN => (ConfigUpdateAccount::LEN, config_update_address(program_id)),
+        LEGACY_FEE_EXEMPTION_LEN => (
+            FeeExemptionAccount::LEN,
+            fee_exemption_address(program_id, &seed_key()?),
+        ),
+        LEGACY_CALLBACK_LEN => (
+            CallbackAccount::LEN,
+            callback_address(program_id, &seed_key()?),
+        ),
+        LEGACY_STAKE_LOCK_LEN => (
+            StakeLockAccount::LEN,
+            stake_lock_address(program_id, &seed_key()?),
+        ),
+        len if LEGACY_LOCK_LENS.contains(&len) && is_legacy_lock(program_id, account, &data) => {
+            return Ok(vec![0; TimeLockAccount::LEN - len]);
+        }
+        _ => return Err(TimeLockError::NotMigratable.into()),
+    };
+    if address != *account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    // the fields added since default to zero, then comes the bump
+    let mut tail = vec![0; len - data.len()];
+    tail[len - data.len() - 1] = bump;
+    Ok(tail)
+}
+
+pub(crate) fn migrate_account(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = MigrateAccountAccounts::validate(program_id, accounts)?;
+
+    let tail = legacy_tail(program_id, ctx.account)?;
+    let previous_len = ctx.account.data_len();
+    let len = previous_len + tail.len();
+    let missing = Rent::get()?
+        .minimum_balance(len)
+        .saturating_sub(ctx.account.lamports());
+    if missing > 0 {
+        invoke(
+            &system_instruction::transfer(ctx.payer.key, ctx.account.key, missing),
+            &[
+                ctx.payer.clone(),
+                ctx.account.clone(),
+                ctx.system_program.clone(),
+            ],
+        )?;
+    }
+    ctx.account.realloc(len, false)?;
+    ctx.account.data.borrow_mut()[previous_len..].copy_from_slice(&tail);
+
+    msg!("Account migrated from {} to {} bytes", previous_len, len);
+    emitter.emit(&TimeLockEvent::AccountMigrated {
+        account: *ctx.account.key,
+        previous_len: previous_len as u32,
+    })
+}
@@ -0,0 +1,119 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program::invoke_signed;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::sysvar::Sysvar as _;
+use solana_program::{system_instruction, system_program};
+use timelock_interface::error::TimeLockError;
+use timelock_interface::pda::{lock_address, lock_nonce_address, LOCK_NONCE_SEED, LOCK_SEED};
+use timelock_interface::state::{FixedSizeAccount, LockNonceAccount, SECRET_LENGTH};
+
+use crate::accounts::{check_pda, Accounts, AccountsIter, Constraints};
+use crate::initialize_time_lock;
+
+/// Accounts of `InitializeNoncedTimeLock`
+struct InitializeNoncedTimeLockAccounts<'a, 'info> {
+    lock: &'a AccountInfo<'info>,
+    payer: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+    nonce: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for InitializeNoncedTimeLockAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        let lock = iter.next(Constraints::ANY.writable())?;
+        let payer = iter.next(Constraints::ANY.signer().writable())?;
+        let system_program = iter.next(Constraints::ANY.program(system_program::id()))?;
+        // the stats and the config, taken by `initialize_time_lock`
+        iter.next(Constraints::ANY)?;
+        iter.next(Constraints::ANY)?;
+        Ok(Self {
+            lock,
+            payer,
+            system_program,
+            nonce: iter.next(Constraints::ANY.writable())?,
+        })
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn initialize_nonced_time_lock(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    nonce: u64,
+    timestamp: i64,
+    secret: [u8; SECRET_LENGTH],
+    bounty: u64,
+    require_memo: bool,
+    top_up_authority_only: bool,
+) -> ProgramResult {
+    let ctx = InitializeNoncedTimeLockAccounts::validate(program_id, accounts)?;
+
+    let mut counter = if ctx.nonce.owner == program_id {
+        let counter = LockNonceAccount::try_from_slice(&ctx.nonce.data.borrow())?;
+        check_pda(
+            program_id,
+            ctx.nonce,
+            &[LOCK_NONCE_SEED, ctx.payer.key.as_ref()],
+            counter.bump,
+        )?;
+        counter
+    } else {
+        // the first lock of the payer creates its nonce PDA
+        let (address, bump) = lock_nonce_address(program_id, ctx.payer.key);
+        if *ctx.nonce.key != address {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        invoke_signed(
+            &system_instruction::create_account(
+                ctx.payer.key,
+                ctx.nonce.key,
+                LockNonceAccount::rent_exempt_lamports(&Rent::get()?),
+                LockNonceAccount::LEN as u64,
+                program_id,
+            ),
+            &[
+                ctx.payer.clone(),
+                ctx.nonce.clone(),
+                ctx.system_program.clone(),
+            ],
+            &[&[LOCK_NONCE_SEED, ctx.payer.key.as_ref(), &[bump]]],
+        )?;
+        LockNonceAccount {
+            payer: *ctx.payer.key,
+            next: 0,
+            bump,
+        }
+    };
+    // a replayed creation carries a spent nonce
+    if nonce != counter.next {
+        return Err(TimeLockError::NonceMismatch.into());
+    }
+    let (address, bump) = lock_address(program_id, ctx.payer.key, nonce);
+    if *ctx.lock.key != address {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    counter.next = nonce
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    counter.serialize(&mut &mut ctx.nonce.data.borrow_mut()[..])?;
+
+    initialize_time_lock(
+        program_id,
+        accounts,
+        timestamp,
+        secret,
+        bounty,
+        require_memo,
+        top_up_authority_only,
+        &[&[
+            LOCK_SEED,
+            ctx.payer.key.as_ref(),
+            &nonce.to_le_bytes(),
+            &[bump],
+        ]],
+    )
+}
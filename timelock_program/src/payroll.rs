@@ -0,0 +1,432 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program::invoke;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::sysvar::Sysvar as _;
+use solana_program::{msg, system_instruction, system_program};
+use timelock_interface::error::TimeLockError;
+use timelock_interface::state::{
+    FixedSizeAccount, PayrollAccount, PayrollEntry, PayrollShare, ScheduleTemplateAccount,
+    MAX_PAYROLL_ENTRIES,
+};
+
+use crate::accounts::{Accounts, AccountsIter, Constraints};
+use crate::config;
+use crate::event::EventEmitter;
+use crate::TimeLockEvent;
+
+/// Accounts of `CreatePayroll`
+struct CreatePayrollAccounts<'a, 'info> {
+    payroll: &'a AccountInfo<'info>,
+    authority: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for CreatePayrollAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            payroll: iter.next(Constraints::ANY.signer().writable())?,
+            authority: iter.next(Constraints::ANY.signer().writable())?,
+            system_program: iter.next(Constraints::ANY.program(system_program::id()))?,
+        })
+    }
+}
+
+pub(crate) fn create_payroll(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    start_time: i64,
+    end_time: i64,
+    num_entries: u32,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = CreatePayrollAccounts::validate(program_id, accounts)?;
+
+    if end_time < start_time {
+        return Err(ProgramError::InvalidArgument);
+    }
+    fund_payroll(
+        program_id,
+        &emitter,
+        &ctx,
+        amount,
+        start_time,
+        end_time,
+        Pubkey::default(),
+        num_entries,
+    )
+}
+
+/// Accounts of `CreateTemplatedPayroll`
+struct CreateTemplatedPayrollAccounts<'a, 'info> {
+    payroll: CreatePayrollAccounts<'a, 'info>,
+    template: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for CreateTemplatedPayrollAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            payroll: CreatePayrollAccounts::take(iter)?,
+            template: iter.next(Constraints::ANY.program_owned())?,
+        })
+    }
+}
+
+pub(crate) fn create_templated_payroll(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    start_time: i64,
+    num_entries: u32,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = CreateTemplatedPayrollAccounts::validate(program_id, accounts)?;
+
+    let template = load_template(program_id, ctx.template)?;
+    fund_payroll(
+        program_id,
+        &emitter,
+        &ctx.payroll,
+        amount,
+        start_time,
+        template
+            .end_time(start_time)
+            .ok_or(ProgramError::ArithmeticOverflow)?,
+        *ctx.template.key,
+        num_entries,
+    )
+}
+
+/// The schedule template held by `account`, checked to be one created by
+/// `CreateScheduleTemplate`: the vesting of a payroll is only as sound as it.
+fn load_template(
+    program_id: &Pubkey,
+    account: &AccountInfo,
+) -> Result<ScheduleTemplateAccount, ProgramError> {
+    if account.owner != program_id || account.data_len() != ScheduleTemplateAccount::LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let template = ScheduleTemplateAccount::try_from_slice(&account.data.borrow())?;
+    if !template.is_valid() {
+        return Err(TimeLockError::InvalidScheduleTemplate.into());
+    }
+    Ok(template)
+}
+
+/// Create the payroll of `ctx` funded with `amount`, its entries to be added
+#[allow(clippy::too_many_arguments)]
+fn fund_payroll(
+    program_id: &Pubkey,
+    emitter: &EventEmitter,
+    ctx: &CreatePayrollAccounts,
+    amount: u64,
+    start_time: i64,
+    end_time: i64,
+    template: Pubkey,
+    num_entries: u32,
+) -> ProgramResult {
+    if num_entries == 0 || num_entries > MAX_PAYROLL_ENTRIES {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let space = PayrollAccount::space(num_entries);
+    let lamports = PayrollAccount::rent_exempt_lamports(&Rent::get()?, num_entries)
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    invoke(
+        &system_instruction::create_account(
+            ctx.authority.key,
+            ctx.payroll.key,
+            lamports,
+            space as u64,
+            program_id,
+        ),
+        &[
+            ctx.authority.clone(),
+            ctx.payroll.clone(),
+            ctx.system_program.clone(),
+        ],
+    )?;
+    PayrollAccount {
+        authority: *ctx.authority.key,
+        amount,
+        start_time,
+        end_time,
+        template,
+        total_weight: 0,
+        added: 0,
+        entries: vec![PayrollEntry::default(); num_entries as usize],
+    }
+    .serialize(&mut &mut ctx.payroll.data.borrow_mut()[..])?;
+
+    msg!(
+        "Payroll of {} lamports among {} entries",
+        amount,
+        num_entries
+    );
+    emitter.emit(&TimeLockEvent::PayrollCreated {
+        payroll: *ctx.payroll.key,
+        authority: *ctx.authority.key,
+        amount,
+        num_entries,
+    })
+}
+
+/// Accounts of `AddPayrollEntries`
+struct AddPayrollEntriesAccounts<'a, 'info> {
+    payroll: &'a AccountInfo<'info>,
+    authority: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for AddPayrollEntriesAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            payroll: iter.next(Constraints::ANY.writable().program_owned())?,
+            authority: iter.next(Constraints::ANY.signer())?,
+        })
+    }
+}
+
+pub(crate) fn add_payroll_entries(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    entries: &[PayrollShare],
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = AddPayrollEntriesAccounts::validate(program_id, accounts)?;
+
+    let mut payroll = PayrollAccount::try_from_slice(&ctx.payroll.data.borrow())?;
+    if payroll.authority != *ctx.authority.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    // a share of no weight would never be paid
+    if entries.is_empty() || entries.iter().any(|entry| entry.weight == 0) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let from = payroll.added as usize;
+    let slots = payroll
+        .entries
+        .get_mut(from..from + entries.len())
+        .ok_or(TimeLockError::PayrollFull)?;
+    for (slot, share) in slots.iter_mut().zip(entries) {
+        *slot = PayrollEntry {
+            beneficiary: share.beneficiary,
+            weight: share.weight,
+            claimed: 0,
+        };
+        payroll.total_weight += share.weight as u64;
+    }
+    payroll.added += entries.len() as u32;
+    payroll.serialize(&mut &mut ctx.payroll.data.borrow_mut()[..])?;
+
+    msg!(
+        "Added {} payroll entries, {} of {}",
+        entries.len(),
+        payroll.added,
+        payroll.entries.len()
+    );
+    emitter.emit(&TimeLockEvent::PayrollEntriesAdded {
+        payroll: *ctx.payroll.key,
+        added: entries.len() as u32,
+    })
+}
+
+/// Accounts of `ClaimPayroll`
+struct ClaimPayrollAccounts<'a, 'info> {
+    payroll: &'a AccountInfo<'info>,
+    beneficiary: &'a AccountInfo<'info>,
+    config: &'a AccountInfo<'info>,
+    /// The schedule template of the payroll if it has one, then the event
+    /// accounts
+    remaining: &'a [AccountInfo<'info>],
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for ClaimPayrollAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            payroll: iter.next(Constraints::ANY.writable().program_owned())?,
+            beneficiary: iter.next(Constraints::ANY.writable())?,
+            config: iter.next(Constraints::ANY)?,
+            remaining: iter.remaining(),
+        })
+    }
+}
+
+pub(crate) fn claim_payroll(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    index: u32,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = ClaimPayrollAccounts::validate(program_id, accounts)?;
+
+    let mut payroll = PayrollAccount::try_from_slice(&ctx.payroll.data.borrow())?;
+    // the weights of the shares are final once every entry is added
+    if !payroll.is_complete() {
+        return Err(TimeLockError::PayrollIncomplete.into());
+    }
+    let template = if payroll.has_template() {
+        let template_account = ctx
+            .remaining
+            .first()
+            .filter(|a| *a.key == payroll.template)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        Some(load_template(program_id, template_account)?)
+    } else {
+        None
+    };
+    // the beneficiary is part of the entry, so anyone may send the claim
+    let now = Clock::get()?.unix_timestamp;
+    let claimable = payroll.claimable(template.as_ref(), index, now);
+    let entry = payroll
+        .entries
+        .get_mut(index as usize)
+        .ok_or(ProgramError::InvalidArgument)?;
+    if entry.beneficiary != *ctx.beneficiary.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if claimable == 0 {
+        return Err(TimeLockError::PayrollNothingVested.into());
+    }
+    entry.claimed += claimable;
+    payroll.serialize(&mut &mut ctx.payroll.data.borrow_mut()[..])?;
+
+    let fee = config::charge_claim(
+        program_id,
+        &emitter,
+        accounts,
+        ctx.config,
+        ctx.payroll,
+        ctx.beneficiary.key,
+        claimable,
+    )?;
+    let amount = claimable - fee;
+    **ctx.payroll.try_borrow_mut_lamports()? -= amount;
+    **ctx.beneficiary.try_borrow_mut_lamports()? += amount;
+
+    msg!(
+        "Payroll entry {} claimed, {} lamports to {}",
+        index,
+        amount,
+        ctx.beneficiary.key
+    );
+    emitter.emit(&TimeLockEvent::PayrollClaimed {
+        payroll: *ctx.payroll.key,
+        beneficiary: *ctx.beneficiary.key,
+        index,
+        amount,
+    })
+}
+
+/// Accounts of `ClosePayroll`
+struct ClosePayrollAccounts<'a, 'info> {
+    payroll: &'a AccountInfo<'info>,
+    authority: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for ClosePayrollAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            payroll: iter.next(Constraints::ANY.writable().program_owned())?,
+            authority: iter.next(Constraints::ANY.signer().writable())?,
+        })
+    }
+}
+
+pub(crate) fn close_payroll(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = ClosePayrollAccounts::validate(program_id, accounts)?;
+
+    let payroll = PayrollAccount::try_from_slice(&ctx.payroll.data.borrow())?;
+    if payroll.authority != *ctx.authority.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    // what is left belongs to the beneficiaries until they claimed it all
+    if !payroll.is_settled() {
+        return Err(TimeLockError::PayrollNotSettled.into());
+    }
+
+    let lamports = ctx.payroll.lamports();
+    **ctx.payroll.try_borrow_mut_lamports()? = 0;
+    **ctx.authority.try_borrow_mut_lamports()? += lamports;
+    ctx.payroll.data.borrow_mut().fill(0);
+
+    msg!("Payroll closed, {} lamports returned", lamports);
+    emitter.emit(&TimeLockEvent::PayrollClosed {
+        payroll: *ctx.payroll.key,
+        authority: *ctx.authority.key,
+    })
+}
+
+/// Accounts of `CreateScheduleTemplate`
+struct CreateScheduleTemplateAccounts<'a, 'info> {
+    template: &'a AccountInfo<'info>,
+    authority: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for CreateScheduleTemplateAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            template: iter.next(Constraints::ANY.signer().writable())?,
+            authority: iter.next(Constraints::ANY.signer().writable())?,
+            system_program: iter.next(Constraints::ANY.program(system_program::id()))?,
+        })
+    }
+}
+
+pub(crate) fn create_schedule_template(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    tge_bps: u16,
+    cliff_months: u32,
+    period_months: u32,
+    periods: u32,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = CreateScheduleTemplateAccounts::validate(program_id, accounts)?;
+
+    let template = ScheduleTemplateAccount {
+        authority: *ctx.authority.key,
+        tge_bps,
+        cliff_months,
+        period_months,
+        periods,
+    };
+    if !template.is_valid() {
+        return Err(TimeLockError::InvalidScheduleTemplate.into());
+    }
+    let space = ScheduleTemplateAccount::LEN;
+    invoke(
+        &system_instruction::create_account(
+            ctx.authority.key,
+            ctx.template.key,
+            ScheduleTemplateAccount::rent_exempt_lamports(&Rent::get()?),
+            space as u64,
+            program_id,
+        ),
+        &[
+            ctx.authority.clone(),
+            ctx.template.clone(),
+            ctx.system_program.clone(),
+        ],
+    )?;
+    // no instruction writes the template again
+    template.serialize(&mut &mut ctx.template.data.borrow_mut()[..])?;
+
+    msg!(
+        "Schedule template of {} bps at TGE, {} periods of {} months after {} months",
+        tge_bps,
+        periods,
+        period_months,
+        cliff_months
+    );
+    emitter.emit(&TimeLockEvent::ScheduleTemplateCreated {
+        template: *ctx.template.key,
+        authority: *ctx.authority.key,
+    })
+}
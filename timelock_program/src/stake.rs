@@ -0,0 +1,249 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program::{invoke, invoke_signed};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::stake::instruction as stake_instruction;
+use solana_program::stake::state::{Meta, StakeAuthorize, StakeStateV2};
+use solana_program::sysvar::Sysvar as _;
+use solana_program::{msg, stake, system_instruction, system_program};
+use timelock_interface::error::TimeLockError;
+use timelock_interface::pda::{stake_lock_address, STAKE_LOCK_SEED};
+use timelock_interface::state::{FixedSizeAccount, StakeLockAccount};
+
+use crate::accounts::{check_pda, Accounts, AccountsIter, Constraints};
+use crate::event::EventEmitter;
+use crate::TimeLockEvent;
+
+/// Authorities and lockup of a stake account, whether delegated or not
+fn stake_meta(stake_account: &AccountInfo) -> Result<Meta, ProgramError> {
+    if *stake_account.owner != stake::program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    // the account is padded, don't require the whole of it to be consumed
+    match StakeStateV2::deserialize(&mut &stake_account.data.borrow()[..])? {
+        StakeStateV2::Initialized(meta) | StakeStateV2::Stake(meta, _, _) => Ok(meta),
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+/// Accounts of `LockStake` and `LockStakeIdempotent`
+struct LockStakeAccounts<'a, 'info> {
+    stake_lock: &'a AccountInfo<'info>,
+    /// Bump of the stake lock address
+    bump: u8,
+    stake: &'a AccountInfo<'info>,
+    owner: &'a AccountInfo<'info>,
+    clock_sysvar: &'a AccountInfo<'info>,
+    stake_program: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for LockStakeAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        let (address, bump) = stake_lock_address(iter.program_id(), iter.peek_key(1)?);
+        Ok(Self {
+            stake_lock: iter.next(Constraints::ANY.writable().pda(address))?,
+            bump,
+            stake: iter.next(Constraints::ANY.writable())?,
+            owner: iter.next(Constraints::ANY.signer().writable())?,
+            clock_sysvar: iter.next(Constraints::ANY)?,
+            stake_program: iter.next(Constraints::ANY.program(stake::program::id()))?,
+            system_program: iter.next(Constraints::ANY.program(system_program::id()))?,
+        })
+    }
+}
+
+pub(crate) fn lock_stake(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    timestamp: i64,
+    beneficiary: Pubkey,
+) -> ProgramResult {
+    let ctx = LockStakeAccounts::validate(program_id, accounts)?;
+    create_stake_lock(program_id, accounts, &ctx, timestamp, beneficiary)
+}
+
+/// [`lock_stake`], without effect when the stake lock already exists with
+/// the same owner, release time and beneficiary
+pub(crate) fn lock_stake_idempotent(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    timestamp: i64,
+    beneficiary: Pubkey,
+) -> ProgramResult {
+    let ctx = LockStakeAccounts::validate(program_id, accounts)?;
+    if ctx.stake_lock.owner != program_id {
+        return create_stake_lock(program_id, accounts, &ctx, timestamp, beneficiary);
+    }
+    let existing = StakeLockAccount::try_from_slice(&ctx.stake_lock.data.borrow())?;
+    let requested = StakeLockAccount {
+        stake: *ctx.stake.key,
+        owner: *ctx.owner.key,
+        beneficiary,
+        timestamp,
+        bump: ctx.bump,
+    };
+    if existing != requested {
+        return Err(TimeLockError::StakeLockMismatch.into());
+    }
+    msg!("Stake {} already locked until {}", ctx.stake.key, timestamp);
+    Ok(())
+}
+
+/// Create the stake lock of `ctx` and hand it the withdraw authority
+fn create_stake_lock(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    ctx: &LockStakeAccounts,
+    timestamp: i64,
+    beneficiary: Pubkey,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let clock = Clock::get()?;
+    if clock.unix_timestamp >= timestamp {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let meta = stake_meta(ctx.stake)?;
+    if meta.authorized.withdrawer != *ctx.owner.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    // a native lockup still in force at release would need its custodian to
+    // hand over the withdraw authority, so it must end before the stake lock
+    if meta.lockup.unix_timestamp > timestamp || meta.lockup.epoch > clock.epoch {
+        return Err(TimeLockError::StakeLockupMisaligned.into());
+    }
+
+    let space = StakeLockAccount::LEN;
+    invoke_signed(
+        &system_instruction::create_account(
+            ctx.owner.key,
+            ctx.stake_lock.key,
+            StakeLockAccount::rent_exempt_lamports(&Rent::get()?),
+            space as u64,
+            program_id,
+        ),
+        &[
+            ctx.owner.clone(),
+            ctx.stake_lock.clone(),
+            ctx.system_program.clone(),
+        ],
+        &[&[STAKE_LOCK_SEED, ctx.stake.key.as_ref(), &[ctx.bump]]],
+    )?;
+    StakeLockAccount {
+        stake: *ctx.stake.key,
+        owner: *ctx.owner.key,
+        beneficiary,
+        timestamp,
+        bump: ctx.bump,
+    }
+    .serialize(&mut &mut ctx.stake_lock.data.borrow_mut()[..])?;
+
+    // the staker keeps managing the delegation, only withdrawals are locked
+    invoke(
+        &stake_instruction::authorize(
+            ctx.stake.key,
+            ctx.owner.key,
+            ctx.stake_lock.key,
+            StakeAuthorize::Withdrawer,
+            None,
+        ),
+        &[
+            ctx.stake.clone(),
+            ctx.clock_sysvar.clone(),
+            ctx.owner.clone(),
+            ctx.stake_program.clone(),
+        ],
+    )?;
+
+    msg!("Stake {} locked until {}", ctx.stake.key, timestamp);
+    emitter.emit(&TimeLockEvent::StakeLocked {
+        stake_lock: *ctx.stake_lock.key,
+        stake: *ctx.stake.key,
+        owner: *ctx.owner.key,
+        beneficiary,
+        timestamp,
+    })
+}
+
+/// Accounts of `ReleaseStake`
+struct ReleaseStakeAccounts<'a, 'info> {
+    stake_lock: &'a AccountInfo<'info>,
+    stake: &'a AccountInfo<'info>,
+    owner: &'a AccountInfo<'info>,
+    clock_sysvar: &'a AccountInfo<'info>,
+    stake_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for ReleaseStakeAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            stake_lock: iter.next(Constraints::ANY.writable().program_owned())?,
+            stake: iter.next(Constraints::ANY.writable())?,
+            owner: iter.next(Constraints::ANY.writable())?,
+            clock_sysvar: iter.next(Constraints::ANY)?,
+            stake_program: iter.next(Constraints::ANY.program(stake::program::id()))?,
+        })
+    }
+}
+
+pub(crate) fn release_stake(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = ReleaseStakeAccounts::validate(program_id, accounts)?;
+
+    // releasing empties the stake lock, the closed account lingers until the
+    // end of the transaction
+    if ctx.stake_lock.lamports() == 0 {
+        return Err(TimeLockError::AlreadyReleased.into());
+    }
+    let stake_lock = StakeLockAccount::try_from_slice(&ctx.stake_lock.data.borrow())?;
+    if stake_lock.stake != *ctx.stake.key || stake_lock.owner != *ctx.owner.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    check_pda(
+        program_id,
+        ctx.stake_lock,
+        &[STAKE_LOCK_SEED, ctx.stake.key.as_ref()],
+        stake_lock.bump,
+    )?;
+    if Clock::get()?.unix_timestamp < stake_lock.timestamp {
+        return Err(TimeLockError::StakeStillLocked.into());
+    }
+
+    invoke_signed(
+        &stake_instruction::authorize(
+            ctx.stake.key,
+            ctx.stake_lock.key,
+            &stake_lock.beneficiary,
+            StakeAuthorize::Withdrawer,
+            None,
+        ),
+        &[
+            ctx.stake.clone(),
+            ctx.clock_sysvar.clone(),
+            ctx.stake_lock.clone(),
+            ctx.stake_program.clone(),
+        ],
+        &[&[STAKE_LOCK_SEED, ctx.stake.key.as_ref(), &[stake_lock.bump]]],
+    )?;
+
+    // close the stake lock, refunding its rent to the owner
+    let lamports = ctx.stake_lock.lamports();
+    **ctx.stake_lock.try_borrow_mut_lamports()? = 0;
+    ctx.stake_lock.data.borrow_mut().fill(0);
+    **ctx.owner.try_borrow_mut_lamports()? += lamports;
+
+    msg!(
+        "Stake {} released to {}",
+        ctx.stake.key,
+        stake_lock.beneficiary
+    );
+    emitter.emit(&TimeLockEvent::StakeReleased {
+        stake_lock: *ctx.stake_lock.key,
+        stake: *ctx.stake.key,
+        beneficiary: stake_lock.beneficiary,
+    })
+}
@@ -0,0 +1,64 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program::invoke_signed;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_instruction;
+use solana_program::sysvar::Sysvar as _;
+use timelock_interface::pda::{stats_address, STATS_SEED};
+use timelock_interface::state::{FixedSizeAccount, TimeLockStats};
+
+use crate::accounts::check_pda;
+
+/// Create the statistics account on first use, paid by `payer`
+pub(crate) fn create_stats_if_needed<'info>(
+    program_id: &Pubkey,
+    stats_account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> ProgramResult {
+    if stats_account.owner == program_id {
+        let stats = TimeLockStats::try_from_slice(&stats_account.data.borrow())?;
+        return check_pda(program_id, stats_account, &[STATS_SEED], stats.bump);
+    }
+    let (address, bump) = stats_address(program_id);
+    if *stats_account.key != address {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let space = TimeLockStats::LEN;
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            stats_account.key,
+            TimeLockStats::rent_exempt_lamports(&Rent::get()?),
+            space as u64,
+            program_id,
+        ),
+        &[payer.clone(), stats_account.clone(), system_program.clone()],
+        &[&[STATS_SEED, &[bump]]],
+    )?;
+    TimeLockStats {
+        bump,
+        ..Default::default()
+    }
+    .serialize(&mut &mut stats_account.data.borrow_mut()[..])
+    .map_err(Into::into)
+}
+
+/// Apply `f` to the statistics stored in `stats_account`
+pub(crate) fn update_stats(
+    program_id: &Pubkey,
+    stats_account: &AccountInfo,
+    f: impl FnOnce(&mut TimeLockStats) -> ProgramResult,
+) -> ProgramResult {
+    if stats_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let mut stats = TimeLockStats::try_from_slice(&stats_account.data.borrow())?;
+    check_pda(program_id, stats_account, &[STATS_SEED], stats.bump)?;
+    f(&mut stats)?;
+    stats.serialize(&mut &mut stats_account.data.borrow_mut()[..])?;
+    Ok(())
+}
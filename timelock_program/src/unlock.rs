@@ -0,0 +1,140 @@
+use timelock_interface::state::TimeLockAccount;
+
+/// What a `TryUnlock` at `now` does to a time lock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnlockDecision {
+    /// The lock has not matured, or is between two windows of a recurring
+    /// lock, only the premature attempt is recorded
+    TooEarly,
+    /// The lock matured, the secret is revealed
+    Reveal {
+        /// Reveal time to record, kept from the first successful unlock
+        revealed_at: i64,
+        /// Lamports payable to a signing cranker, 0 once claimed
+        bounty: u64,
+    },
+}
+
+/// Decide the outcome of unlocking `lock` at `now`, without touching any account
+pub fn evaluate_unlock(now: i64, lock: &TimeLockAccount) -> UnlockDecision {
+    if !lock.is_open(now) {
+        return UnlockDecision::TooEarly;
+    }
+    UnlockDecision::Reveal {
+        revealed_at: match lock.revealed_at {
+            0 => now,
+            revealed_at => revealed_at,
+        },
+        bounty: lock.bounty,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::pubkey::Pubkey;
+    use timelock_interface::state::{MAX_UNLOCKERS, SECRET_LENGTH};
+
+    fn lock(timestamp: i64, bounty: u64, revealed_at: i64) -> TimeLockAccount {
+        TimeLockAccount {
+            timestamp,
+            secret: [b'a'; SECRET_LENGTH],
+            bounty,
+            attempts: 0,
+            premature_attempts: 0,
+            last_attempt: 0,
+            revealed_at,
+            require_memo: false,
+            claim_memo_hash: [0; 32],
+            authority: Pubkey::default(),
+            top_up_authority_only: false,
+            unlockers: [Pubkey::default(); MAX_UNLOCKERS],
+            expires_at: 0,
+            expiry_recipient: Pubkey::default(),
+            heartbeat_interval: 0,
+            recurrence_unit: TimeLockAccount::RECUR_NONE,
+            recurrence_every: 0,
+            recurrence_window: 0,
+            has_callback: false,
+            condition_account: Pubkey::default(),
+            condition_offset: 0,
+            condition_len: 0,
+            condition_bytes: [0; MAX_CONDITION_BYTES],
+            predecessor: Pubkey::default(),
+            has_audit_trail: false,
+            claim_commit_required: false,
+            in_progress: false,
+            funded: 0,
+            grant_recipient: Pubkey::default(),
+        }
+    }
+
+    #[test]
+    fn test_evaluate_unlock() {
+        assert_eq!(
+            evaluate_unlock(99, &lock(100, 5, 0)),
+            UnlockDecision::TooEarly
+        );
+        // maturity is inclusive
+        assert_eq!(
+            evaluate_unlock(100, &lock(100, 5, 0)),
+            UnlockDecision::Reveal {
+                revealed_at: 100,
+                bounty: 5
+            }
+        );
+        // the first reveal time is kept, a claimed bounty is not paid twice
+        assert_eq!(
+            evaluate_unlock(200, &lock(100, 0, 150)),
+            UnlockDecision::Reveal {
+                revealed_at: 150,
+                bounty: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_evaluate_unlock_recurring() {
+        let mut recurring = lock(100, 5, 0);
+        recurring.recurrence_unit = TimeLockAccount::RECUR_SECONDS;
+        recurring.recurrence_every = 60;
+        recurring.recurrence_window = 10;
+        assert_eq!(evaluate_unlock(130, &recurring), UnlockDecision::TooEarly);
+        assert_eq!(
+            evaluate_unlock(165, &recurring),
+            UnlockDecision::Reveal {
+                revealed_at: 165,
+                bounty: 5
+            }
+        );
+    }
+
+    #[test]
+    fn test_evaluate_unlock_negative_time() {
+        assert_eq!(
+            evaluate_unlock(-10, &lock(-5, 1, 0)),
+            UnlockDecision::TooEarly
+        );
+        assert_eq!(
+            evaluate_unlock(-5, &lock(-5, 1, 0)),
+            UnlockDecision::Reveal {
+                revealed_at: -5,
+                bounty: 1
+            }
+        );
+        assert_eq!(
+            evaluate_unlock(i64::MIN, &lock(i64::MIN, 1, 0)),
+            UnlockDecision::Reveal {
+                revealed_at: i64::MIN,
+                bounty: 1
+            }
+        );
+        assert_eq!(
+            evaluate_unlock(i64::MAX, &lock(i64::MIN, 1, 0)),
+            UnlockDecision::Reveal {
+                revealed_at: i64::MAX,
+                bounty: 1
+            }
+        );
+    }
+}
@@ -0,0 +1,596 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program::{invoke, invoke_signed};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::sysvar::Sysvar as _;
+use solana_program::{msg, system_instruction, system_program};
+use timelock_interface::error::TimeLockError;
+use timelock_interface::pda::{vault_stats_address, VAULT_STATS_SEED};
+use timelock_interface::state::{
+    FixedSizeAccount, VaultAccount, VaultLockAccount, VaultStatsAccount,
+};
+
+use crate::accounts::{check_pda, Accounts, AccountsIter, Constraints};
+use crate::config;
+use crate::event::EventEmitter;
+use crate::TimeLockEvent;
+
+/// Accounts of `CreateVault`
+struct CreateVaultAccounts<'a, 'info> {
+    vault: &'a AccountInfo<'info>,
+    authority: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for CreateVaultAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            vault: iter.next(Constraints::ANY.signer().writable())?,
+            authority: iter.next(Constraints::ANY.signer().writable())?,
+            system_program: iter.next(Constraints::ANY.program(system_program::id()))?,
+        })
+    }
+}
+
+pub(crate) fn create_vault(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = CreateVaultAccounts::validate(program_id, accounts)?;
+
+    let space = VaultAccount::LEN;
+    let lamports = VaultAccount::rent_exempt_lamports(&Rent::get()?)
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    invoke(
+        &system_instruction::create_account(
+            ctx.authority.key,
+            ctx.vault.key,
+            lamports,
+            space as u64,
+            program_id,
+        ),
+        &[
+            ctx.authority.clone(),
+            ctx.vault.clone(),
+            ctx.system_program.clone(),
+        ],
+    )?;
+    VaultAccount {
+        authority: *ctx.authority.key,
+        amount,
+        allocated: 0,
+    }
+    .serialize(&mut &mut ctx.vault.data.borrow_mut()[..])?;
+
+    msg!("Vault funded with {} lamports", amount);
+    emitter.emit(&TimeLockEvent::VaultCreated {
+        vault: *ctx.vault.key,
+        authority: *ctx.authority.key,
+        amount,
+    })
+}
+
+/// Data of an account of the program
+fn load<T: BorshDeserialize>(account: &AccountInfo) -> Result<T, ProgramError> {
+    T::try_from_slice(&account.data.borrow()).map_err(Into::into)
+}
+
+/// Vault lock data of `vault_lock_account`, not paid out yet: the claim or
+/// merge closing it empties the account, which lingers until the end of the
+/// transaction
+fn load_vault_lock(vault_lock_account: &AccountInfo) -> Result<VaultLockAccount, ProgramError> {
+    if vault_lock_account.lamports() == 0 {
+        return Err(TimeLockError::AlreadyReleased.into());
+    }
+    load(vault_lock_account)
+}
+
+/// Vault data of `vault_account`, signed for by its authority
+fn load_vault(
+    vault_account: &AccountInfo,
+    authority_account: &AccountInfo,
+) -> Result<VaultAccount, ProgramError> {
+    let vault: VaultAccount = load(vault_account)?;
+    if vault.authority != *authority_account.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    Ok(vault)
+}
+
+/// Stats of `vault`, if created, checked to be its PDA either way
+fn load_vault_stats(
+    program_id: &Pubkey,
+    vault_stats_account: &AccountInfo,
+    vault: &Pubkey,
+) -> Result<Option<VaultStatsAccount>, ProgramError> {
+    if vault_stats_account.owner != program_id {
+        if *vault_stats_account.key != vault_stats_address(program_id, vault).0 {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        return Ok(None);
+    }
+    let stats: VaultStatsAccount = load(vault_stats_account)?;
+    if stats.vault != *vault {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    check_pda(
+        program_id,
+        vault_stats_account,
+        &[VAULT_STATS_SEED, vault.as_ref()],
+        stats.bump,
+    )?;
+    Ok(Some(stats))
+}
+
+/// Accounts of `CreateVaultLock`
+struct CreateVaultLockAccounts<'a, 'info> {
+    vault_lock: &'a AccountInfo<'info>,
+    vault: &'a AccountInfo<'info>,
+    authority: &'a AccountInfo<'info>,
+    beneficiary: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+    vault_stats: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for CreateVaultLockAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            vault_lock: iter.next(Constraints::ANY.signer().writable())?,
+            vault: iter.next(Constraints::ANY.writable().program_owned())?,
+            authority: iter.next(Constraints::ANY.signer().writable())?,
+            beneficiary: iter.next(Constraints::ANY)?,
+            system_program: iter.next(Constraints::ANY.program(system_program::id()))?,
+            vault_stats: iter.next(Constraints::ANY.writable())?,
+        })
+    }
+}
+
+pub(crate) fn create_vault_lock(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    release_time: i64,
+    transferable: bool,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = CreateVaultLockAccounts::validate(program_id, accounts)?;
+
+    let mut vault = load_vault(ctx.vault, ctx.authority)?;
+    if amount > vault.unallocated() {
+        return Err(TimeLockError::VaultUnderfunded.into());
+    }
+    vault.allocated += amount;
+    vault.serialize(&mut &mut ctx.vault.data.borrow_mut()[..])?;
+
+    // the vault lock only holds its rent, the amount stays in the vault
+    let space = VaultLockAccount::LEN;
+    invoke(
+        &system_instruction::create_account(
+            ctx.authority.key,
+            ctx.vault_lock.key,
+            VaultLockAccount::rent_exempt_lamports(&Rent::get()?),
+            space as u64,
+            program_id,
+        ),
+        &[
+            ctx.authority.clone(),
+            ctx.vault_lock.clone(),
+            ctx.system_program.clone(),
+        ],
+    )?;
+    VaultLockAccount {
+        vault: *ctx.vault.key,
+        beneficiary: *ctx.beneficiary.key,
+        amount,
+        release_time,
+        payer: *ctx.authority.key,
+        transferable,
+    }
+    .serialize(&mut &mut ctx.vault_lock.data.borrow_mut()[..])?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let mut stats = match load_vault_stats(program_id, ctx.vault_stats, ctx.vault.key)? {
+        Some(stats) => stats,
+        None => {
+            // the first vault lock of the vault creates its stats
+            let (_, bump) = vault_stats_address(program_id, ctx.vault.key);
+            let space = VaultStatsAccount::LEN;
+            invoke_signed(
+                &system_instruction::create_account(
+                    ctx.authority.key,
+                    ctx.vault_stats.key,
+                    VaultStatsAccount::rent_exempt_lamports(&Rent::get()?),
+                    space as u64,
+                    program_id,
+                ),
+                &[
+                    ctx.authority.clone(),
+                    ctx.vault_stats.clone(),
+                    ctx.system_program.clone(),
+                ],
+                &[&[VAULT_STATS_SEED, ctx.vault.key.as_ref(), &[bump]]],
+            )?;
+            VaultStatsAccount::new(*ctx.vault.key, now, bump)
+        }
+    };
+    stats.add_grant(amount, release_time, now);
+    stats.serialize(&mut &mut ctx.vault_stats.data.borrow_mut()[..])?;
+
+    msg!(
+        "Allocated {} vault lamports to {}, released at {}",
+        amount,
+        ctx.beneficiary.key,
+        release_time
+    );
+    emitter.emit(&TimeLockEvent::VaultLockCreated {
+        vault_lock: *ctx.vault_lock.key,
+        vault: *ctx.vault.key,
+        beneficiary: *ctx.beneficiary.key,
+        amount,
+        release_time,
+    })
+}
+
+/// Accounts of `ClaimVaultLock`
+struct ClaimVaultLockAccounts<'a, 'info> {
+    vault_lock: &'a AccountInfo<'info>,
+    vault: &'a AccountInfo<'info>,
+    beneficiary: &'a AccountInfo<'info>,
+    payer: &'a AccountInfo<'info>,
+    config: &'a AccountInfo<'info>,
+    vault_stats: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for ClaimVaultLockAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            vault_lock: iter.next(Constraints::ANY.writable().program_owned())?,
+            vault: iter.next(Constraints::ANY.writable().program_owned())?,
+            beneficiary: iter.next(Constraints::ANY.signer().writable())?,
+            payer: iter.next(Constraints::ANY.writable())?,
+            config: iter.next(Constraints::ANY)?,
+            vault_stats: iter.next(Constraints::ANY.writable())?,
+        })
+    }
+}
+
+pub(crate) fn claim_vault_lock(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = ClaimVaultLockAccounts::validate(program_id, accounts)?;
+
+    let vault_lock = load_vault_lock(ctx.vault_lock)?;
+    if vault_lock.vault != *ctx.vault.key || vault_lock.payer != *ctx.payer.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let mut vault: VaultAccount = load(ctx.vault)?;
+    if vault_lock.beneficiary != *ctx.beneficiary.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let now = Clock::get()?.unix_timestamp;
+    if now < vault_lock.release_time {
+        return Err(TimeLockError::VaultLockNotReleased.into());
+    }
+    // vaults only locked before the stats existed have none
+    if let Some(mut stats) = load_vault_stats(program_id, ctx.vault_stats, ctx.vault.key)? {
+        stats.add_claim(vault_lock.amount, now);
+        stats.serialize(&mut &mut ctx.vault_stats.data.borrow_mut()[..])?;
+    }
+
+    // the allocation guarantees the vault holds the amount
+    let amount = vault_lock.amount;
+    vault.amount -= amount;
+    vault.allocated -= amount;
+    vault.serialize(&mut &mut ctx.vault.data.borrow_mut()[..])?;
+    let fee = config::charge_claim(
+        program_id,
+        &emitter,
+        accounts,
+        ctx.config,
+        ctx.vault,
+        ctx.beneficiary.key,
+        amount,
+    )?;
+    let amount = amount - fee;
+    **ctx.vault.try_borrow_mut_lamports()? -= amount;
+    **ctx.beneficiary.try_borrow_mut_lamports()? += amount;
+
+    // the rent of the vault lock goes back to whoever paid it
+    let rent = ctx.vault_lock.lamports();
+    **ctx.vault_lock.try_borrow_mut_lamports()? = 0;
+    ctx.vault_lock.data.borrow_mut().fill(0);
+    **ctx.payer.try_borrow_mut_lamports()? += rent;
+
+    msg!(
+        "Vault lock claimed, {} lamports to {}",
+        amount,
+        ctx.beneficiary.key
+    );
+    emitter.emit(&TimeLockEvent::VaultLockClaimed {
+        vault_lock: *ctx.vault_lock.key,
+        beneficiary: *ctx.beneficiary.key,
+        amount,
+    })
+}
+
+/// Accounts of `SplitLock`
+struct SplitLockAccounts<'a, 'info> {
+    vault_lock: &'a AccountInfo<'info>,
+    new_vault_lock: &'a AccountInfo<'info>,
+    beneficiary: &'a AccountInfo<'info>,
+    new_beneficiary: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for SplitLockAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            vault_lock: iter.next(Constraints::ANY.writable().program_owned())?,
+            new_vault_lock: iter.next(Constraints::ANY.signer().writable())?,
+            beneficiary: iter.next(Constraints::ANY.signer().writable())?,
+            new_beneficiary: iter.next(Constraints::ANY)?,
+            system_program: iter.next(Constraints::ANY.program(system_program::id()))?,
+        })
+    }
+}
+
+pub(crate) fn split_lock(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = SplitLockAccounts::validate(program_id, accounts)?;
+
+    let mut vault_lock = load_vault_lock(ctx.vault_lock)?;
+    if vault_lock.beneficiary != *ctx.beneficiary.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    // both parts must keep something to claim
+    if amount == 0 || amount >= vault_lock.amount {
+        return Err(ProgramError::InvalidArgument);
+    }
+    vault_lock.amount -= amount;
+    vault_lock.serialize(&mut &mut ctx.vault_lock.data.borrow_mut()[..])?;
+
+    // the part keeps the vault and the release time, the allocation is unchanged
+    let space = VaultLockAccount::LEN;
+    invoke(
+        &system_instruction::create_account(
+            ctx.beneficiary.key,
+            ctx.new_vault_lock.key,
+            VaultLockAccount::rent_exempt_lamports(&Rent::get()?),
+            space as u64,
+            program_id,
+        ),
+        &[
+            ctx.beneficiary.clone(),
+            ctx.new_vault_lock.clone(),
+            ctx.system_program.clone(),
+        ],
+    )?;
+    VaultLockAccount {
+        vault: vault_lock.vault,
+        beneficiary: *ctx.new_beneficiary.key,
+        amount,
+        release_time: vault_lock.release_time,
+        payer: *ctx.beneficiary.key,
+        transferable: vault_lock.transferable,
+    }
+    .serialize(&mut &mut ctx.new_vault_lock.data.borrow_mut()[..])?;
+
+    msg!(
+        "Split {} lamports off the vault lock to {}",
+        amount,
+        ctx.new_beneficiary.key
+    );
+    emitter.emit(&TimeLockEvent::VaultLockSplit {
+        vault_lock: *ctx.vault_lock.key,
+        new_vault_lock: *ctx.new_vault_lock.key,
+        beneficiary: *ctx.new_beneficiary.key,
+        amount,
+    })
+}
+
+/// Accounts of `MergeLocks`
+struct MergeLocksAccounts<'a, 'info> {
+    vault_lock: &'a AccountInfo<'info>,
+    source: &'a AccountInfo<'info>,
+    beneficiary: &'a AccountInfo<'info>,
+    payer: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for MergeLocksAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            vault_lock: iter.next(Constraints::ANY.writable().program_owned())?,
+            source: iter.next(Constraints::ANY.writable().program_owned())?,
+            beneficiary: iter.next(Constraints::ANY.signer())?,
+            payer: iter.next(Constraints::ANY.writable())?,
+        })
+    }
+}
+
+pub(crate) fn merge_locks(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = MergeLocksAccounts::validate(program_id, accounts)?;
+
+    if ctx.vault_lock.key == ctx.source.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let mut vault_lock = load_vault_lock(ctx.vault_lock)?;
+    let source = load_vault_lock(ctx.source)?;
+    if vault_lock.beneficiary != *ctx.beneficiary.key || source.beneficiary != *ctx.beneficiary.key
+    {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    // only locks claimable together merge, the allocation is unchanged
+    if source.vault != vault_lock.vault
+        || source.release_time != vault_lock.release_time
+        || source.transferable != vault_lock.transferable
+        || source.payer != *ctx.payer.key
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let amount = source.amount;
+    vault_lock.amount = vault_lock
+        .amount
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    vault_lock.serialize(&mut &mut ctx.vault_lock.data.borrow_mut()[..])?;
+
+    let rent = ctx.source.lamports();
+    **ctx.source.try_borrow_mut_lamports()? = 0;
+    ctx.source.data.borrow_mut().fill(0);
+    **ctx.payer.try_borrow_mut_lamports()? += rent;
+
+    msg!("Merged {} lamports into the vault lock", amount);
+    emitter.emit(&TimeLockEvent::VaultLocksMerged {
+        vault_lock: *ctx.vault_lock.key,
+        source: *ctx.source.key,
+        amount,
+    })
+}
+
+/// Accounts of `TransferVaultLock`
+struct TransferVaultLockAccounts<'a, 'info> {
+    vault_lock: &'a AccountInfo<'info>,
+    beneficiary: &'a AccountInfo<'info>,
+    new_beneficiary: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for TransferVaultLockAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            vault_lock: iter.next(Constraints::ANY.writable().program_owned())?,
+            beneficiary: iter.next(Constraints::ANY.signer())?,
+            new_beneficiary: iter.next(Constraints::ANY)?,
+        })
+    }
+}
+
+pub(crate) fn transfer_vault_lock(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = TransferVaultLockAccounts::validate(program_id, accounts)?;
+
+    let mut vault_lock = load_vault_lock(ctx.vault_lock)?;
+    if vault_lock.beneficiary != *ctx.beneficiary.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !vault_lock.transferable {
+        return Err(TimeLockError::VaultLockNotTransferable.into());
+    }
+    vault_lock.beneficiary = *ctx.new_beneficiary.key;
+    vault_lock.serialize(&mut &mut ctx.vault_lock.data.borrow_mut()[..])?;
+
+    msg!("Vault lock transferred to {}", ctx.new_beneficiary.key);
+    emitter.emit(&TimeLockEvent::VaultLockTransferred {
+        vault_lock: *ctx.vault_lock.key,
+        beneficiary: *ctx.beneficiary.key,
+        new_beneficiary: *ctx.new_beneficiary.key,
+    })
+}
+
+/// Accounts of `TransferWithPayment`
+struct TransferWithPaymentAccounts<'a, 'info> {
+    vault_lock: &'a AccountInfo<'info>,
+    beneficiary: &'a AccountInfo<'info>,
+    buyer: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for TransferWithPaymentAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            vault_lock: iter.next(Constraints::ANY.writable().program_owned())?,
+            beneficiary: iter.next(Constraints::ANY.signer().writable())?,
+            buyer: iter.next(Constraints::ANY.signer().writable())?,
+            system_program: iter.next(Constraints::ANY.program(system_program::id()))?,
+        })
+    }
+}
+
+pub(crate) fn transfer_with_payment(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    price: u64,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = TransferWithPaymentAccounts::validate(program_id, accounts)?;
+
+    let mut vault_lock = load_vault_lock(ctx.vault_lock)?;
+    if vault_lock.beneficiary != *ctx.beneficiary.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !vault_lock.transferable {
+        return Err(TimeLockError::VaultLockNotTransferable.into());
+    }
+    // the payment fails the whole instruction, the vault lock with it
+    invoke(
+        &system_instruction::transfer(ctx.buyer.key, ctx.beneficiary.key, price),
+        &[
+            ctx.buyer.clone(),
+            ctx.beneficiary.clone(),
+            ctx.system_program.clone(),
+        ],
+    )?;
+    vault_lock.beneficiary = *ctx.buyer.key;
+    vault_lock.serialize(&mut &mut ctx.vault_lock.data.borrow_mut()[..])?;
+
+    msg!(
+        "Vault lock sold to {} for {} lamports",
+        ctx.buyer.key,
+        price
+    );
+    emitter.emit(&TimeLockEvent::VaultLockSold {
+        vault_lock: *ctx.vault_lock.key,
+        seller: *ctx.beneficiary.key,
+        buyer: *ctx.buyer.key,
+        price,
+    })
+}
+
+/// Accounts of `WithdrawUnallocated`
+struct WithdrawUnallocatedAccounts<'a, 'info> {
+    vault: &'a AccountInfo<'info>,
+    authority: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Accounts<'a, 'info> for WithdrawUnallocatedAccounts<'a, 'info> {
+    fn take(iter: &mut AccountsIter<'a, 'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            vault: iter.next(Constraints::ANY.writable().program_owned())?,
+            authority: iter.next(Constraints::ANY.signer().writable())?,
+        })
+    }
+}
+
+pub(crate) fn withdraw_unallocated(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let emitter = EventEmitter::new(program_id, accounts)?;
+    let ctx = WithdrawUnallocatedAccounts::validate(program_id, accounts)?;
+
+    let mut vault = load_vault(ctx.vault, ctx.authority)?;
+    if amount > vault.unallocated() {
+        return Err(TimeLockError::VaultUnderfunded.into());
+    }
+    vault.amount -= amount;
+    vault.serialize(&mut &mut ctx.vault.data.borrow_mut()[..])?;
+    **ctx.vault.try_borrow_mut_lamports()? -= amount;
+    **ctx.authority.try_borrow_mut_lamports()? += amount;
+
+    msg!("Withdrew {} unallocated vault lamports", amount);
+    emitter.emit(&TimeLockEvent::VaultWithdrawn {
+        vault: *ctx.vault.key,
+        amount,
+    })
+}
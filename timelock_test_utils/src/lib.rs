@@ -0,0 +1,1480 @@
+//! `solana-program-test` fixtures for the time lock program.
+//!
+//! ```ignore
+//! let mut test = setup_program().await;
+//! let timestamp = test.now().await + 60;
+//! let lock = test.create_lock(timestamp, [b'a'; SECRET_LENGTH], 5000, false, false).await?;
+//! test.warp_past(timestamp).await;
+//! test.try_unlock(&lock.pubkey(), None).await?;
+//! test.assert_lock_state(&lock.pubkey(), &ExpectedLock { revealed: Some(true), ..Default::default() }).await;
+//! ```
+//!
+//! Programs calling the time lock through CPI can add their own program to
+//! [`program_test`] before starting it with [`TimeLockTest::start`].
+//!
+//! With the `litesvm` feature, [`lite::LiteTimeLockTest`] offers the same
+//! helpers on an in-process LiteSVM, without a bank to boot per test. It runs
+//! the SBF build of the program, so `cargo build-sbf` it first.
+//!
+//! With the `mollusk` feature, `tests/mollusk.rs` snapshots the accounts and
+//! compute units of each instruction on the same SBF build.
+
+use std::path::PathBuf;
+
+use borsh::BorshDeserialize as _;
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::account::{Account, AccountSharedData};
+use solana_sdk::bpf_loader_upgradeable;
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer as _};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+use timelock_program::instruction::{
+    accept_admin, add_fee_exemption, add_payroll_entries, add_unlocker, apply_config_update,
+    cancel_config_update, change_escrow_beneficiary, claim_escrow, claim_escrow_early,
+    claim_payroll, claim_vault_lock, claim_with_proof, clear_callback, close_payroll, collect_fees,
+    commit_claim, create_distributor, create_escrow, create_payroll, create_schedule_template,
+    create_templated_payroll, create_vault, create_vault_lock, enable_audit_trail, expire,
+    get_immutability, get_revenue, heartbeat, initialize_config, initialize_nonced_time_lock,
+    initialize_time_lock, merge_locks, migrate_account, propose_admin, record_immutability,
+    refund_escrow, release_grant, remove_fee_exemption, remove_unlocker, require_claim_commit,
+    reveal_claim, revoke_escrow_delegate, set_callback, set_condition, set_escrow_delegate,
+    set_expiry, set_grant_recipient, set_heartbeat, set_paused, set_predecessor, set_recurrence,
+    set_unlock_instruction, split_lock, top_up, transfer_vault_lock, transfer_with_payment,
+    try_unlock, try_unlock_with_callback, try_unlock_with_condition, try_unlock_with_predecessor,
+    update_config, withdraw_excess, withdraw_fees, withdraw_unallocated,
+};
+use timelock_program::{
+    audit_address, callback_address, config_address, lock_nonce_address, stats_address,
+    vault_stats_address, AuditTrailAccount, CallbackAccount, ConfigAccount, DistributorAccount,
+    EscrowAccount, ImmutabilityReport, LockNonceAccount, PayrollAccount, PayrollShare,
+    RevenueReport, ScheduleTemplateAccount, TimeLockAccount, TimeLockStats, VaultAccount,
+    VaultLockAccount, VaultStatsAccount, SECRET_LENGTH,
+};
+
+#[cfg(feature = "litesvm")]
+pub mod lite;
+pub mod malformed;
+
+/// Where `cargo build-sbf` puts the program.
+pub const DEFAULT_PROGRAM_SO: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../target/deploy/timelock_program.so"
+);
+
+/// SBF build of the program loaded by the SVM harnesses,
+/// `TIMELOCK_PROGRAM_SO` or else [`DEFAULT_PROGRAM_SO`].
+pub fn program_so() -> PathBuf {
+    std::env::var_os("TIMELOCK_PROGRAM_SO")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| DEFAULT_PROGRAM_SO.into())
+}
+
+/// Lamports [`TimeLockTest::cranker`] funds a cranker with, so paid bounties
+/// don't leave it below rent exemption.
+pub const CRANKER_LAMPORTS: u64 = LAMPORTS_PER_SOL;
+
+/// A running test validator with the time lock program deployed.
+pub struct TimeLockTest {
+    /// The underlying program-test context.
+    pub context: ProgramTestContext,
+    /// Address the time lock program is deployed at.
+    pub program_id: Pubkey,
+}
+
+/// Fields to check on a lock, `None` ones are not checked.
+#[derive(Debug, Default, Clone)]
+pub struct ExpectedLock {
+    /// Unlock timestamp.
+    pub timestamp: Option<i64>,
+    /// Bounty still escrowed.
+    pub bounty: Option<u64>,
+    /// Installments still to release.
+    pub funded: Option<u64>,
+    /// Number of `TryUnlock` calls.
+    pub attempts: Option<u32>,
+    /// Number of premature `TryUnlock` calls.
+    pub premature_attempts: Option<u32>,
+    /// Whether the secret was revealed.
+    pub revealed: Option<bool>,
+}
+
+impl ExpectedLock {
+    /// Assert `data`, the time lock at `lock`, matches.
+    pub fn check(&self, lock: &Pubkey, data: &TimeLockAccount) {
+        if let Some(timestamp) = self.timestamp {
+            assert_eq!(data.timestamp, timestamp, "timestamp of {lock}");
+        }
+        if let Some(bounty) = self.bounty {
+            assert_eq!(data.bounty, bounty, "bounty of {lock}");
+        }
+        if let Some(funded) = self.funded {
+            assert_eq!(data.funded, funded, "funded total of {lock}");
+        }
+        if let Some(attempts) = self.attempts {
+            assert_eq!(data.attempts, attempts, "attempts of {lock}");
+        }
+        if let Some(premature_attempts) = self.premature_attempts {
+            assert_eq!(
+                data.premature_attempts, premature_attempts,
+                "premature attempts of {lock}"
+            );
+        }
+        if let Some(revealed) = self.revealed {
+            assert_eq!(data.revealed_at != 0, revealed, "revealed state of {lock}");
+        }
+    }
+}
+
+/// The time lock program registered at a fresh address, not started yet.
+pub fn program_test() -> (ProgramTest, Pubkey) {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new(
+        "timelock_program",
+        program_id,
+        processor!(timelock_program::process_instruction),
+    );
+    (program_test, program_id)
+}
+
+/// Start a validator with only the time lock program.
+pub async fn setup_program() -> TimeLockTest {
+    let (program_test, program_id) = program_test();
+    TimeLockTest::start(program_test, program_id).await
+}
+
+impl TimeLockTest {
+    /// Start `program_test`, which has the time lock program at `program_id`.
+    pub async fn start(program_test: ProgramTest, program_id: Pubkey) -> Self {
+        Self {
+            context: program_test.start_with_context().await,
+            program_id,
+        }
+    }
+
+    /// Current unix timestamp of the validator.
+    pub async fn now(&mut self) -> i64 {
+        self.clock().await.unix_timestamp
+    }
+
+    async fn clock(&mut self) -> Clock {
+        self.context
+            .banks_client
+            .get_sysvar::<Clock>()
+            .await
+            .expect("clock sysvar")
+    }
+
+    /// Set the validator clock to `timestamp`, backwards if need be.
+    pub async fn warp_to(&mut self, timestamp: i64) {
+        let mut clock = self.clock().await;
+        clock.unix_timestamp = timestamp;
+        self.context.set_sysvar(&clock);
+    }
+
+    /// Move the validator clock one second past `timestamp`.
+    pub async fn warp_past(&mut self, timestamp: i64) {
+        let mut clock = self.clock().await;
+        clock.unix_timestamp = clock.unix_timestamp.max(timestamp + 1);
+        self.context.set_sysvar(&clock);
+    }
+
+    /// Move the validator to its next slot, keeping its clock.
+    pub async fn next_slot(&mut self) {
+        let clock = self.clock().await;
+        self.context
+            .warp_to_slot(clock.slot + 1)
+            .expect("warp to the next slot");
+        self.warp_to(clock.unix_timestamp).await;
+    }
+
+    /// Send `ixs` paid by the context payer and signed by `signers` as well.
+    pub async fn process(
+        &mut self,
+        ixs: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        let blockhash = self
+            .context
+            .get_new_latest_blockhash()
+            .await
+            .expect("latest blockhash");
+        let payer = self.context.payer.insecure_clone();
+        let mut all_signers = vec![&payer];
+        all_signers.extend_from_slice(signers);
+        let tx =
+            Transaction::new_signed_with_payer(ixs, Some(&payer.pubkey()), &all_signers, blockhash);
+        self.context.banks_client.process_transaction(tx).await
+    }
+
+    /// Create a time lock paid by the context payer, returning its keypair.
+    pub async fn create_lock(
+        &mut self,
+        timestamp: i64,
+        secret: [u8; SECRET_LENGTH],
+        bounty: u64,
+        require_memo: bool,
+        top_up_authority_only: bool,
+    ) -> Result<Keypair, BanksClientError> {
+        let lock = Keypair::new();
+        let ix = initialize_time_lock(
+            &self.program_id,
+            &lock.pubkey(),
+            &self.context.payer.pubkey(),
+            timestamp,
+            secret,
+            bounty,
+            require_memo,
+            top_up_authority_only,
+        );
+        self.process(&[ix], &[&lock]).await?;
+        Ok(lock)
+    }
+
+    /// Create the time lock of the context payer at the PDA of `nonce`.
+    pub async fn create_nonced_lock(
+        &mut self,
+        nonce: u64,
+        timestamp: i64,
+        secret: [u8; SECRET_LENGTH],
+        bounty: u64,
+    ) -> Result<(), BanksClientError> {
+        let ix = initialize_nonced_time_lock(
+            &self.program_id,
+            &self.context.payer.pubkey(),
+            nonce,
+            timestamp,
+            secret,
+            bounty,
+            false,
+            false,
+        );
+        self.process(&[ix], &[]).await
+    }
+
+    /// A new keypair holding [`CRANKER_LAMPORTS`].
+    pub async fn cranker(&mut self) -> Result<Keypair, BanksClientError> {
+        let cranker = Keypair::new();
+        let ix = system_instruction::transfer(
+            &self.context.payer.pubkey(),
+            &cranker.pubkey(),
+            CRANKER_LAMPORTS,
+        );
+        self.process(&[ix], &[]).await?;
+        Ok(cranker)
+    }
+
+    /// Deposit `amount` lamports in `lock`, paid by `depositor` or else by
+    /// the payer, the lock authority.
+    pub async fn top_up(
+        &mut self,
+        lock: &Pubkey,
+        depositor: Option<&Keypair>,
+        amount: u64,
+    ) -> Result<(), BanksClientError> {
+        let depositor_key = depositor.map_or(self.context.payer.pubkey(), |d| d.pubkey());
+        let ix = top_up(&self.program_id, lock, &depositor_key, amount);
+        let signers: Vec<&Keypair> = depositor.into_iter().collect();
+        self.process(&[ix], &signers).await
+    }
+
+    /// Release the installments of `lock` to `recipient`, signed by the
+    /// payer, the lock authority.
+    pub async fn set_grant_recipient(
+        &mut self,
+        lock: &Pubkey,
+        recipient: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let ix = set_grant_recipient(
+            &self.program_id,
+            lock,
+            &self.context.payer.pubkey(),
+            recipient,
+        );
+        self.process(&[ix], &[]).await
+    }
+
+    /// Pay the installments of the revealed `lock` to its grant `recipient`.
+    pub async fn release_grant(
+        &mut self,
+        lock: &Pubkey,
+        recipient: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let ix = release_grant(&self.program_id, lock, recipient);
+        self.process(&[ix], &[]).await
+    }
+
+    /// Withdraw the surplus of `lock`, signed by `authority` or else by the
+    /// payer, the lock authority.
+    pub async fn withdraw_excess(
+        &mut self,
+        lock: &Pubkey,
+        authority: Option<&Keypair>,
+    ) -> Result<(), BanksClientError> {
+        let authority_key = authority.map_or(self.context.payer.pubkey(), |a| a.pubkey());
+        let ix = withdraw_excess(&self.program_id, lock, &authority_key);
+        let signers: Vec<&Keypair> = authority.into_iter().collect();
+        self.process(&[ix], &signers).await
+    }
+
+    /// Allow `unlocker` to call `TryUnlock` on `lock`, signed by `authority`
+    /// or else by the payer, the lock authority.
+    pub async fn add_unlocker(
+        &mut self,
+        lock: &Pubkey,
+        authority: Option<&Keypair>,
+        unlocker: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let authority_key = authority.map_or(self.context.payer.pubkey(), |a| a.pubkey());
+        let ix = add_unlocker(&self.program_id, lock, &authority_key, unlocker);
+        let signers: Vec<&Keypair> = authority.into_iter().collect();
+        self.process(&[ix], &signers).await
+    }
+
+    /// Disallow `unlocker` to call `TryUnlock` on `lock`, signed by the
+    /// payer, the lock authority.
+    pub async fn remove_unlocker(
+        &mut self,
+        lock: &Pubkey,
+        unlocker: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let ix = remove_unlocker(
+            &self.program_id,
+            lock,
+            &self.context.payer.pubkey(),
+            unlocker,
+        );
+        self.process(&[ix], &[]).await
+    }
+
+    /// Let anyone close `lock` once `expires_at` passes, paying `recipient`,
+    /// signed by the payer, the lock authority.
+    pub async fn set_expiry(
+        &mut self,
+        lock: &Pubkey,
+        expires_at: i64,
+        recipient: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let ix = set_expiry(
+            &self.program_id,
+            lock,
+            &self.context.payer.pubkey(),
+            expires_at,
+            recipient,
+        );
+        self.process(&[ix], &[]).await
+    }
+
+    /// Close the expired `lock`, paying its expiry `recipient`.
+    pub async fn expire(
+        &mut self,
+        lock: &Pubkey,
+        recipient: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let ix = expire(&self.program_id, lock, recipient);
+        self.process(&[ix], &[]).await
+    }
+
+    /// Put `lock` in dead man's switch mode with `interval`, signed by the
+    /// payer, the lock authority.
+    pub async fn set_heartbeat(
+        &mut self,
+        lock: &Pubkey,
+        interval: i64,
+    ) -> Result<(), BanksClientError> {
+        let ix = set_heartbeat(
+            &self.program_id,
+            lock,
+            &self.context.payer.pubkey(),
+            interval,
+        );
+        self.process(&[ix], &[]).await
+    }
+
+    /// Defer the maturity of `lock`, signed by the payer, the lock authority.
+    pub async fn heartbeat(&mut self, lock: &Pubkey) -> Result<(), BanksClientError> {
+        let ix = heartbeat(&self.program_id, lock, &self.context.payer.pubkey());
+        self.process(&[ix], &[]).await
+    }
+
+    /// Keep an audit trail of `lock`, signed by the payer, the lock authority.
+    pub async fn enable_audit_trail(&mut self, lock: &Pubkey) -> Result<(), BanksClientError> {
+        let ix = enable_audit_trail(&self.program_id, lock, &self.context.payer.pubkey());
+        self.process(&[ix], &[]).await
+    }
+
+    /// Pay the bounty of `lock` only to committed claims, signed by the payer,
+    /// the lock authority.
+    pub async fn require_claim_commit(&mut self, lock: &Pubkey) -> Result<(), BanksClientError> {
+        let ix = require_claim_commit(&self.program_id, lock, &self.context.payer.pubkey());
+        self.process(&[ix], &[]).await
+    }
+
+    /// Commit `committer` to claiming the bounty of `lock` for `destination`.
+    pub async fn commit_claim(
+        &mut self,
+        lock: &Pubkey,
+        committer: &Keypair,
+        destination: &Pubkey,
+        salt: &[u8; 32],
+    ) -> Result<(), BanksClientError> {
+        let ix = commit_claim(
+            &self.program_id,
+            lock,
+            &committer.pubkey(),
+            destination,
+            salt,
+        );
+        self.process(&[ix], &[committer]).await
+    }
+
+    /// Reveal the claim of the bounty of `lock` committed by `committer`.
+    pub async fn reveal_claim(
+        &mut self,
+        lock: &Pubkey,
+        committer: &Pubkey,
+        destination: &Pubkey,
+        salt: [u8; 32],
+    ) -> Result<(), BanksClientError> {
+        let ix = reveal_claim(&self.program_id, lock, committer, destination, salt);
+        self.process(&[ix], &[]).await
+    }
+
+    /// Make `lock` open `window` seconds every `every` `unit`, signed by the
+    /// payer, the lock authority.
+    pub async fn set_recurrence(
+        &mut self,
+        lock: &Pubkey,
+        unit: u8,
+        every: u32,
+        window: u32,
+    ) -> Result<(), BanksClientError> {
+        let ix = set_recurrence(
+            &self.program_id,
+            lock,
+            &self.context.payer.pubkey(),
+            unit,
+            every,
+            window,
+        );
+        self.process(&[ix], &[]).await
+    }
+
+    /// Make revealing `lock` call `target` with `accounts` and `data`, signed
+    /// by the payer, the lock authority.
+    pub async fn set_callback(
+        &mut self,
+        lock: &Pubkey,
+        target: &Pubkey,
+        accounts: &[AccountMeta],
+        data: &[u8],
+    ) -> Result<(), BanksClientError> {
+        let ix = set_callback(
+            &self.program_id,
+            lock,
+            &self.context.payer.pubkey(),
+            target,
+            accounts,
+            data,
+        );
+        self.process(&[ix], &[]).await
+    }
+
+    /// Drop the callback of the matured `lock`, signed by the payer, the lock
+    /// authority.
+    pub async fn clear_callback(&mut self, lock: &Pubkey) -> Result<(), BanksClientError> {
+        let ix = clear_callback(&self.program_id, lock, &self.context.payer.pubkey());
+        self.process(&[ix], &[]).await
+    }
+
+    /// Make revealing `lock` execute `ix` as is, signed by the payer, the lock
+    /// authority.
+    pub async fn set_unlock_instruction(
+        &mut self,
+        lock: &Pubkey,
+        ix: &Instruction,
+    ) -> Result<(), BanksClientError> {
+        let ix = set_unlock_instruction(&self.program_id, lock, &self.context.payer.pubkey(), ix);
+        self.process(&[ix], &[]).await
+    }
+
+    /// Make `lock` reveal only while `account` holds `expected` at `offset`,
+    /// signed by the payer, the lock authority.
+    pub async fn set_condition(
+        &mut self,
+        lock: &Pubkey,
+        account: &Pubkey,
+        offset: u32,
+        expected: &[u8],
+    ) -> Result<(), BanksClientError> {
+        let ix = set_condition(
+            &self.program_id,
+            lock,
+            &self.context.payer.pubkey(),
+            account,
+            offset,
+            expected,
+        );
+        self.process(&[ix], &[]).await
+    }
+
+    /// Make `lock` reveal only after `predecessor` did, signed by the payer,
+    /// the lock authority.
+    pub async fn set_predecessor(
+        &mut self,
+        lock: &Pubkey,
+        predecessor: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let ix = set_predecessor(
+            &self.program_id,
+            lock,
+            &self.context.payer.pubkey(),
+            predecessor,
+        );
+        self.process(&[ix], &[]).await
+    }
+
+    /// Escrow `amount` lamports of `depositor`, or else the payer, for
+    /// `beneficiary`, returning the escrow keypair.
+    pub async fn create_escrow(
+        &mut self,
+        depositor: Option<&Keypair>,
+        beneficiary: &Pubkey,
+        amount: u64,
+        release_time: i64,
+        refund_time: i64,
+    ) -> Result<Keypair, BanksClientError> {
+        self.create_escrow_with_penalty(
+            depositor,
+            beneficiary,
+            amount,
+            release_time,
+            refund_time,
+            0,
+            false,
+        )
+        .await
+    }
+
+    /// [`Self::create_escrow`], the beneficiary being allowed to claim early
+    /// forfeiting `penalty_bps` of the amount.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_escrow_with_penalty(
+        &mut self,
+        depositor: Option<&Keypair>,
+        beneficiary: &Pubkey,
+        amount: u64,
+        release_time: i64,
+        refund_time: i64,
+        penalty_bps: u16,
+        penalty_decays: bool,
+    ) -> Result<Keypair, BanksClientError> {
+        let escrow = Keypair::new();
+        let depositor_key = depositor.map_or(self.context.payer.pubkey(), |d| d.pubkey());
+        let ix = create_escrow(
+            &self.program_id,
+            &escrow.pubkey(),
+            &depositor_key,
+            beneficiary,
+            amount,
+            release_time,
+            refund_time,
+            penalty_bps,
+            penalty_decays,
+        );
+        let mut signers = vec![&escrow];
+        signers.extend(depositor);
+        self.process(&[ix], &signers).await?;
+        Ok(escrow)
+    }
+
+    /// Fund a distributor with `amount` lamports of the payer for the
+    /// `num_leaves` claims of the tree of `root`, returning its keypair.
+    pub async fn create_distributor(
+        &mut self,
+        root: [u8; 32],
+        num_leaves: u32,
+        amount: u64,
+    ) -> Result<Keypair, BanksClientError> {
+        let distributor = Keypair::new();
+        let ix = create_distributor(
+            &self.program_id,
+            &distributor.pubkey(),
+            &self.context.payer.pubkey(),
+            root,
+            num_leaves,
+            amount,
+        );
+        self.process(&[ix], &[&distributor]).await?;
+        Ok(distributor)
+    }
+
+    /// Claim the `index`th leaf of `distributor` for `recipient`.
+    pub async fn claim_with_proof(
+        &mut self,
+        distributor: &Pubkey,
+        recipient: &Pubkey,
+        index: u32,
+        amount: u64,
+        unlock_time: i64,
+        proof: &[[u8; 32]],
+    ) -> Result<(), BanksClientError> {
+        let ix = claim_with_proof(
+            &self.program_id,
+            distributor,
+            recipient,
+            index,
+            amount,
+            unlock_time,
+            proof,
+        );
+        self.process(&[ix], &[]).await
+    }
+
+    /// Fund a payroll with `amount` lamports of the payer for `num_entries`
+    /// entries vesting from `start_time` to `end_time`, returning its keypair.
+    pub async fn create_payroll(
+        &mut self,
+        amount: u64,
+        start_time: i64,
+        end_time: i64,
+        num_entries: u32,
+    ) -> Result<Keypair, BanksClientError> {
+        let payroll = Keypair::new();
+        let ix = create_payroll(
+            &self.program_id,
+            &payroll.pubkey(),
+            &self.context.payer.pubkey(),
+            amount,
+            start_time,
+            end_time,
+            num_entries,
+        );
+        self.process(&[ix], &[&payroll]).await?;
+        Ok(payroll)
+    }
+
+    /// Add `entries` to `payroll`, signed by the payer, its authority.
+    pub async fn add_payroll_entries(
+        &mut self,
+        payroll: &Pubkey,
+        entries: &[PayrollShare],
+    ) -> Result<(), BanksClientError> {
+        let ix = add_payroll_entries(
+            &self.program_id,
+            payroll,
+            &self.context.payer.pubkey(),
+            entries,
+        );
+        self.process(&[ix], &[]).await
+    }
+
+    /// Migrate `account` to its current layout, the payer topping up the
+    /// rent.
+    pub async fn migrate_account(&mut self, account: &Pubkey) -> Result<(), BanksClientError> {
+        let ix = migrate_account(&self.program_id, account, &self.context.payer.pubkey());
+        self.process(&[ix], &[]).await
+    }
+
+    /// Close the settled `payroll`, signed by the payer, its authority.
+    pub async fn close_payroll(&mut self, payroll: &Pubkey) -> Result<(), BanksClientError> {
+        let ix = close_payroll(&self.program_id, payroll, &self.context.payer.pubkey());
+        self.process(&[ix], &[]).await
+    }
+
+    /// Claim the share of the `index`th entry of `payroll` for `beneficiary`,
+    /// passing the schedule template of the payroll if it has one.
+    pub async fn claim_payroll(
+        &mut self,
+        payroll: &Pubkey,
+        beneficiary: &Pubkey,
+        index: u32,
+    ) -> Result<(), BanksClientError> {
+        let template = self
+            .context
+            .banks_client
+            .get_account(*payroll)
+            .await
+            .expect("get account")
+            .and_then(|account| PayrollAccount::try_from_slice(&account.data).ok())
+            .filter(PayrollAccount::has_template)
+            .map(|payroll| payroll.template);
+        let ix = claim_payroll(
+            &self.program_id,
+            payroll,
+            beneficiary,
+            index,
+            template.as_ref(),
+        );
+        self.process(&[ix], &[]).await
+    }
+
+    /// Create a schedule template paid by the payer, returning its keypair.
+    pub async fn create_schedule_template(
+        &mut self,
+        tge_bps: u16,
+        cliff_months: u32,
+        period_months: u32,
+        periods: u32,
+    ) -> Result<Keypair, BanksClientError> {
+        let template = Keypair::new();
+        let ix = create_schedule_template(
+            &self.program_id,
+            &template.pubkey(),
+            &self.context.payer.pubkey(),
+            tge_bps,
+            cliff_months,
+            period_months,
+            periods,
+        );
+        self.process(&[ix], &[&template]).await?;
+        Ok(template)
+    }
+
+    /// Fund a payroll with `amount` lamports of the payer for `num_entries`
+    /// entries vesting on `template` from `start_time`, returning its keypair.
+    pub async fn create_templated_payroll(
+        &mut self,
+        template: &Pubkey,
+        amount: u64,
+        start_time: i64,
+        num_entries: u32,
+    ) -> Result<Keypair, BanksClientError> {
+        let payroll = Keypair::new();
+        let ix = create_templated_payroll(
+            &self.program_id,
+            &payroll.pubkey(),
+            &self.context.payer.pubkey(),
+            template,
+            amount,
+            start_time,
+            num_entries,
+        );
+        self.process(&[ix], &[&payroll]).await?;
+        Ok(payroll)
+    }
+
+    /// Fund a vault with `amount` lamports of the payer, returning its keypair.
+    pub async fn create_vault(&mut self, amount: u64) -> Result<Keypair, BanksClientError> {
+        let vault = Keypair::new();
+        let ix = create_vault(
+            &self.program_id,
+            &vault.pubkey(),
+            &self.context.payer.pubkey(),
+            amount,
+        );
+        self.process(&[ix], &[&vault]).await?;
+        Ok(vault)
+    }
+
+    /// Allocate `amount` lamports of `vault` to `beneficiary`, signed by the
+    /// payer, the vault authority, returning the vault lock keypair.
+    pub async fn create_vault_lock(
+        &mut self,
+        vault: &Pubkey,
+        beneficiary: &Pubkey,
+        amount: u64,
+        release_time: i64,
+        transferable: bool,
+    ) -> Result<Keypair, BanksClientError> {
+        let vault_lock = Keypair::new();
+        let ix = create_vault_lock(
+            &self.program_id,
+            &vault_lock.pubkey(),
+            vault,
+            &self.context.payer.pubkey(),
+            beneficiary,
+            amount,
+            release_time,
+            transferable,
+        );
+        self.process(&[ix], &[&vault_lock]).await?;
+        Ok(vault_lock)
+    }
+
+    /// Claim `vault_lock`, signed by `beneficiary`.
+    pub async fn claim_vault_lock(
+        &mut self,
+        vault_lock: &Pubkey,
+        beneficiary: &Keypair,
+    ) -> Result<(), BanksClientError> {
+        let data = self.vault_lock(vault_lock).await;
+        let ix = claim_vault_lock(
+            &self.program_id,
+            vault_lock,
+            &data.vault,
+            &beneficiary.pubkey(),
+            &data.payer,
+        );
+        self.process(&[ix], &[beneficiary]).await
+    }
+
+    /// Split `amount` lamports off `vault_lock` for `new_beneficiary`, signed
+    /// by `beneficiary`, returning the new vault lock keypair.
+    pub async fn split_lock(
+        &mut self,
+        vault_lock: &Pubkey,
+        beneficiary: &Keypair,
+        new_beneficiary: &Pubkey,
+        amount: u64,
+    ) -> Result<Keypair, BanksClientError> {
+        let new_vault_lock = Keypair::new();
+        let ix = split_lock(
+            &self.program_id,
+            vault_lock,
+            &new_vault_lock.pubkey(),
+            &beneficiary.pubkey(),
+            new_beneficiary,
+            amount,
+        );
+        self.process(&[ix], &[&new_vault_lock, beneficiary]).await?;
+        Ok(new_vault_lock)
+    }
+
+    /// Merge `source` into `vault_lock`, signed by `beneficiary`.
+    pub async fn merge_locks(
+        &mut self,
+        vault_lock: &Pubkey,
+        source: &Pubkey,
+        beneficiary: &Keypair,
+    ) -> Result<(), BanksClientError> {
+        let payer = self.vault_lock(source).await.payer;
+        let ix = merge_locks(
+            &self.program_id,
+            vault_lock,
+            source,
+            &beneficiary.pubkey(),
+            &payer,
+        );
+        self.process(&[ix], &[beneficiary]).await
+    }
+
+    /// Hand `vault_lock` to `new_beneficiary`, signed by `beneficiary`.
+    pub async fn transfer_vault_lock(
+        &mut self,
+        vault_lock: &Pubkey,
+        beneficiary: &Keypair,
+        new_beneficiary: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let ix = transfer_vault_lock(
+            &self.program_id,
+            vault_lock,
+            &beneficiary.pubkey(),
+            new_beneficiary,
+        );
+        self.process(&[ix], &[beneficiary]).await
+    }
+
+    /// Sell `vault_lock` of `beneficiary` to `buyer` for `price` lamports,
+    /// signed by both.
+    pub async fn transfer_with_payment(
+        &mut self,
+        vault_lock: &Pubkey,
+        beneficiary: &Keypair,
+        buyer: &Keypair,
+        price: u64,
+    ) -> Result<(), BanksClientError> {
+        let ix = transfer_with_payment(
+            &self.program_id,
+            vault_lock,
+            &beneficiary.pubkey(),
+            &buyer.pubkey(),
+            price,
+        );
+        self.process(&[ix], &[beneficiary, buyer]).await
+    }
+
+    /// Initialize the program config with the payer as admin. The harness
+    /// program has no program data account, one naming the payer as upgrade
+    /// authority is set first.
+    pub async fn initialize_config(
+        &mut self,
+        treasury: &Pubkey,
+        creation_fee: u64,
+        min_duration: i64,
+        max_duration: i64,
+    ) -> Result<(), BanksClientError> {
+        let admin = self.context.payer.pubkey();
+        // UpgradeableLoaderState::ProgramData: u32 tag 3, u64 slot, Option<Pubkey>
+        let mut data = vec![3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+        data.extend_from_slice(admin.as_ref());
+        let programdata = Account {
+            lamports: LAMPORTS_PER_SOL,
+            data,
+            owner: bpf_loader_upgradeable::id(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.context.set_account(
+            &bpf_loader_upgradeable::get_program_data_address(&self.program_id),
+            &AccountSharedData::from(programdata),
+        );
+        let ix = initialize_config(
+            &self.program_id,
+            &admin,
+            treasury,
+            creation_fee,
+            min_duration,
+            max_duration,
+        );
+        self.process(&[ix], &[]).await
+    }
+
+    /// Replace the program config with `config`, signed by `admin`, queueing
+    /// the update and applying it in the same transaction: only works while
+    /// the update delay is zero.
+    pub async fn update_config(
+        &mut self,
+        admin: &Keypair,
+        config: &ConfigAccount,
+    ) -> Result<(), BanksClientError> {
+        let ixs = [
+            self.update_config_ix(admin, config),
+            apply_config_update(&self.program_id, &admin.pubkey()),
+        ];
+        self.process(&ixs, &[admin]).await
+    }
+
+    /// Queue the update of the program config to `config`, signed by `admin`.
+    pub async fn queue_config_update(
+        &mut self,
+        admin: &Keypair,
+        config: &ConfigAccount,
+    ) -> Result<(), BanksClientError> {
+        let ix = self.update_config_ix(admin, config);
+        self.process(&[ix], &[admin]).await
+    }
+
+    fn update_config_ix(&self, admin: &Keypair, config: &ConfigAccount) -> Instruction {
+        update_config(
+            &self.program_id,
+            &admin.pubkey(),
+            &config.guardian,
+            &config.treasury,
+            config.creation_fee,
+            config.min_duration,
+            config.max_duration,
+            config.paused,
+            config.claim_fee_bps,
+            config.update_delay,
+            config.clock_drift_tolerance,
+        )
+    }
+
+    /// Apply the queued update of the program config, signed by the payer as
+    /// its admin.
+    pub async fn apply_config_update(&mut self) -> Result<(), BanksClientError> {
+        let ix = apply_config_update(&self.program_id, &self.context.payer.pubkey());
+        self.process(&[ix], &[]).await
+    }
+
+    /// Drop the queued update of the program config, signed by the admin or
+    /// the guardian of the config, refunding its rent to the payer.
+    pub async fn cancel_config_update(
+        &mut self,
+        authority: &Keypair,
+    ) -> Result<(), BanksClientError> {
+        let payer = self.context.payer.pubkey();
+        let ix = cancel_config_update(&self.program_id, &authority.pubkey(), &payer);
+        self.process(&[ix], &[authority]).await
+    }
+
+    /// Move `amount` lamports of the fees held by the program config to
+    /// `recipient`, signed by its admin.
+    pub async fn withdraw_fees(
+        &mut self,
+        admin: &Keypair,
+        recipient: &Pubkey,
+        amount: u64,
+    ) -> Result<(), BanksClientError> {
+        let ix = withdraw_fees(&self.program_id, &admin.pubkey(), recipient, amount);
+        self.process(&[ix], &[admin]).await
+    }
+
+    /// Exempt `key` from the fees of the program config, signed by its
+    /// `admin`.
+    pub async fn add_fee_exemption(
+        &mut self,
+        admin: &Keypair,
+        key: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let ix = add_fee_exemption(&self.program_id, &admin.pubkey(), key);
+        self.process(&[ix], &[admin]).await
+    }
+
+    /// Charge `key` the fees of the program config again, signed by its
+    /// `admin`.
+    pub async fn remove_fee_exemption(
+        &mut self,
+        admin: &Keypair,
+        key: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let ix = remove_fee_exemption(&self.program_id, &admin.pubkey(), key);
+        self.process(&[ix], &[admin]).await
+    }
+
+    /// Pause or unpause the program, signed by the admin or the guardian of
+    /// its config.
+    pub async fn set_paused(
+        &mut self,
+        authority: &Keypair,
+        paused: bool,
+    ) -> Result<(), BanksClientError> {
+        let ix = set_paused(&self.program_id, &authority.pubkey(), paused);
+        self.process(&[ix], &[authority]).await
+    }
+
+    /// Propose `pending_admin` to take over the program config until
+    /// `expires_at`, signed by its `admin`.
+    pub async fn propose_admin(
+        &mut self,
+        admin: &Keypair,
+        pending_admin: &Pubkey,
+        expires_at: i64,
+    ) -> Result<(), BanksClientError> {
+        let ix = propose_admin(&self.program_id, &admin.pubkey(), pending_admin, expires_at);
+        self.process(&[ix], &[admin]).await
+    }
+
+    /// Take over the program config as its `pending_admin`.
+    pub async fn accept_admin(&mut self, pending_admin: &Keypair) -> Result<(), BanksClientError> {
+        let ix = accept_admin(&self.program_id, &pending_admin.pubkey());
+        self.process(&[ix], &[pending_admin]).await
+    }
+
+    /// Move the fees held by the program config to its treasury.
+    pub async fn collect_fees(&mut self) -> Result<(), BanksClientError> {
+        let treasury = self.config().await.treasury;
+        let ix = collect_fees(&self.program_id, &treasury);
+        self.process(&[ix], &[]).await
+    }
+
+    /// Withdraw `amount` unallocated lamports of `vault`, signed by the payer,
+    /// its authority.
+    pub async fn withdraw_unallocated(
+        &mut self,
+        vault: &Pubkey,
+        amount: u64,
+    ) -> Result<(), BanksClientError> {
+        let ix = withdraw_unallocated(
+            &self.program_id,
+            vault,
+            &self.context.payer.pubkey(),
+            amount,
+        );
+        self.process(&[ix], &[]).await
+    }
+
+    /// Claim `escrow`, signed by `beneficiary`.
+    pub async fn claim_escrow(
+        &mut self,
+        escrow: &Pubkey,
+        beneficiary: &Keypair,
+    ) -> Result<(), BanksClientError> {
+        let depositor = self.escrow(escrow).await.depositor;
+        let ix = claim_escrow(
+            &self.program_id,
+            escrow,
+            &beneficiary.pubkey(),
+            &depositor,
+            None,
+        );
+        self.process(&[ix], &[beneficiary]).await
+    }
+
+    /// Claim `escrow` for its beneficiary, signed by `delegate`.
+    pub async fn claim_escrow_as_delegate(
+        &mut self,
+        escrow: &Pubkey,
+        delegate: &Keypair,
+    ) -> Result<(), BanksClientError> {
+        let data = self.escrow(escrow).await;
+        let ix = claim_escrow(
+            &self.program_id,
+            escrow,
+            &data.beneficiary,
+            &data.depositor,
+            Some(&delegate.pubkey()),
+        );
+        self.process(&[ix], &[delegate]).await
+    }
+
+    /// Let `delegate` claim `escrow`, signed by the payer, its depositor.
+    pub async fn set_escrow_delegate(
+        &mut self,
+        escrow: &Pubkey,
+        delegate: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let ix = set_escrow_delegate(
+            &self.program_id,
+            escrow,
+            &self.context.payer.pubkey(),
+            delegate,
+        );
+        self.process(&[ix], &[]).await
+    }
+
+    /// Remove the delegate of `escrow`, signed by the payer, its depositor.
+    pub async fn revoke_escrow_delegate(
+        &mut self,
+        escrow: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let ix = revoke_escrow_delegate(&self.program_id, escrow, &self.context.payer.pubkey());
+        self.process(&[ix], &[]).await
+    }
+
+    /// Claim `escrow` before its release time, signed by `beneficiary`.
+    pub async fn claim_escrow_early(
+        &mut self,
+        escrow: &Pubkey,
+        beneficiary: &Keypair,
+    ) -> Result<(), BanksClientError> {
+        let depositor = self.escrow(escrow).await.depositor;
+        let ix = claim_escrow_early(&self.program_id, escrow, &beneficiary.pubkey(), &depositor);
+        self.process(&[ix], &[beneficiary]).await
+    }
+
+    /// Refund `escrow`, signed by `depositor` or else by the payer.
+    pub async fn refund_escrow(
+        &mut self,
+        escrow: &Pubkey,
+        depositor: Option<&Keypair>,
+    ) -> Result<(), BanksClientError> {
+        let depositor_key = depositor.map_or(self.context.payer.pubkey(), |d| d.pubkey());
+        let ix = refund_escrow(&self.program_id, escrow, &depositor_key);
+        let signers: Vec<&Keypair> = depositor.into_iter().collect();
+        self.process(&[ix], &signers).await
+    }
+
+    /// Redirect `escrow` to `beneficiary`, signed by `depositor` or else by
+    /// the payer.
+    pub async fn change_escrow_beneficiary(
+        &mut self,
+        escrow: &Pubkey,
+        depositor: Option<&Keypair>,
+        beneficiary: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let depositor_key = depositor.map_or(self.context.payer.pubkey(), |d| d.pubkey());
+        let ix = change_escrow_beneficiary(&self.program_id, escrow, &depositor_key, beneficiary);
+        let signers: Vec<&Keypair> = depositor.into_iter().collect();
+        self.process(&[ix], &signers).await
+    }
+
+    /// Call `TryUnlock` on `lock`, claiming the bounty with `cranker` if given.
+    pub async fn try_unlock(
+        &mut self,
+        lock: &Pubkey,
+        cranker: Option<&Keypair>,
+    ) -> Result<(), BanksClientError> {
+        let ix = try_unlock(&self.program_id, lock, cranker.map(|c| c.pubkey()).as_ref());
+        let signers: Vec<&Keypair> = cranker.into_iter().collect();
+        self.process(&[ix], &signers).await
+    }
+
+    /// [`Self::try_unlock`] passing the accounts of the callback of `lock`.
+    pub async fn try_unlock_with_callback(
+        &mut self,
+        lock: &Pubkey,
+        cranker: Option<&Keypair>,
+    ) -> Result<(), BanksClientError> {
+        let callback = self.callback(lock).await;
+        let ix = try_unlock_with_callback(
+            &self.program_id,
+            lock,
+            cranker.map(|c| c.pubkey()).as_ref(),
+            &callback,
+        );
+        let signers: Vec<&Keypair> = cranker.into_iter().collect();
+        self.process(&[ix], &signers).await
+    }
+
+    /// [`Self::try_unlock`] passing the `condition` account of `lock`.
+    pub async fn try_unlock_with_condition(
+        &mut self,
+        lock: &Pubkey,
+        cranker: Option<&Keypair>,
+        condition: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let ix = try_unlock_with_condition(
+            &self.program_id,
+            lock,
+            cranker.map(|c| c.pubkey()).as_ref(),
+            condition,
+        );
+        let signers: Vec<&Keypair> = cranker.into_iter().collect();
+        self.process(&[ix], &signers).await
+    }
+
+    /// [`Self::try_unlock`] passing the `predecessor` of `lock`.
+    pub async fn try_unlock_with_predecessor(
+        &mut self,
+        lock: &Pubkey,
+        cranker: Option<&Keypair>,
+        predecessor: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let ix = try_unlock_with_predecessor(
+            &self.program_id,
+            lock,
+            cranker.map(|c| c.pubkey()).as_ref(),
+            predecessor,
+        );
+        let signers: Vec<&Keypair> = cranker.into_iter().collect();
+        self.process(&[ix], &signers).await
+    }
+
+    /// Lamports held by `address`, 0 if the account does not exist.
+    pub async fn balance(&mut self, address: &Pubkey) -> u64 {
+        self.context
+            .banks_client
+            .get_balance(*address)
+            .await
+            .expect("balance")
+    }
+
+    /// Decoded time lock at `lock`, panicking if there is none.
+    pub async fn lock(&mut self, lock: &Pubkey) -> TimeLockAccount {
+        let account = self
+            .context
+            .banks_client
+            .get_account(*lock)
+            .await
+            .expect("get account")
+            .expect("time lock exists");
+        assert_eq!(account.owner, self.program_id, "time lock owner");
+        TimeLockAccount::try_from_slice(&account.data).expect("time lock data")
+    }
+
+    /// Decoded escrow at `escrow`, panicking if there is none.
+    pub async fn escrow(&mut self, escrow: &Pubkey) -> EscrowAccount {
+        let account = self
+            .context
+            .banks_client
+            .get_account(*escrow)
+            .await
+            .expect("get account")
+            .expect("escrow exists");
+        assert_eq!(account.owner, self.program_id, "escrow owner");
+        EscrowAccount::try_from_slice(&account.data).expect("escrow data")
+    }
+
+    /// Decoded distributor at `distributor`, panicking if there is none.
+    pub async fn distributor(&mut self, distributor: &Pubkey) -> DistributorAccount {
+        let account = self
+            .context
+            .banks_client
+            .get_account(*distributor)
+            .await
+            .expect("get account")
+            .expect("distributor exists");
+        assert_eq!(account.owner, self.program_id, "distributor owner");
+        DistributorAccount::try_from_slice(&account.data).expect("distributor data")
+    }
+
+    /// Decoded payroll at `payroll`, panicking if there is none.
+    pub async fn payroll(&mut self, payroll: &Pubkey) -> PayrollAccount {
+        let account = self
+            .context
+            .banks_client
+            .get_account(*payroll)
+            .await
+            .expect("get account")
+            .expect("payroll exists");
+        assert_eq!(account.owner, self.program_id, "payroll owner");
+        PayrollAccount::try_from_slice(&account.data).expect("payroll data")
+    }
+
+    /// Decoded schedule template at `template`, panicking if there is none.
+    pub async fn schedule_template(&mut self, template: &Pubkey) -> ScheduleTemplateAccount {
+        let account = self
+            .context
+            .banks_client
+            .get_account(*template)
+            .await
+            .expect("get account")
+            .expect("schedule template exists");
+        assert_eq!(account.owner, self.program_id, "schedule template owner");
+        ScheduleTemplateAccount::try_from_slice(&account.data).expect("schedule template data")
+    }
+
+    /// Decoded program config, panicking if there is none.
+    pub async fn config(&mut self) -> ConfigAccount {
+        let account = self
+            .context
+            .banks_client
+            .get_account(config_address(&self.program_id).0)
+            .await
+            .expect("get account")
+            .expect("config exists");
+        assert_eq!(account.owner, self.program_id, "config owner");
+        ConfigAccount::try_from_slice(&account.data).expect("config data")
+    }
+
+    /// Lifetime revenue of the program config, simulating `GetRevenue`.
+    pub async fn revenue(&mut self) -> RevenueReport {
+        let payer = self.context.payer.insecure_clone();
+        let tx = Transaction::new_signed_with_payer(
+            &[get_revenue(&self.program_id)],
+            Some(&payer.pubkey()),
+            &[&payer],
+            self.context.last_blockhash,
+        );
+        let simulated = self
+            .context
+            .banks_client
+            .simulate_transaction(tx)
+            .await
+            .expect("simulate get revenue");
+        let return_data = simulated
+            .simulation_details
+            .and_then(|details| details.return_data)
+            .expect("return data");
+        RevenueReport::try_from_slice(&return_data.data).expect("revenue data")
+    }
+
+    /// Drop the upgrade authority of the program, last deployed at
+    /// `deploy_slot`, by rewriting its program data account.
+    pub fn burn_upgrade_authority(&mut self, deploy_slot: u64) {
+        // UpgradeableLoaderState::ProgramData: u32 tag 3, u64 slot, Option<Pubkey>
+        let mut data = vec![3, 0, 0, 0];
+        data.extend_from_slice(&deploy_slot.to_le_bytes());
+        data.push(0);
+        let programdata = Account {
+            lamports: LAMPORTS_PER_SOL,
+            data,
+            owner: bpf_loader_upgradeable::id(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.context.set_account(
+            &bpf_loader_upgradeable::get_program_data_address(&self.program_id),
+            &AccountSharedData::from(programdata),
+        );
+    }
+
+    /// Record in the program config that the program has no upgrade authority.
+    pub async fn record_immutability(&mut self) -> Result<(), BanksClientError> {
+        let ix = record_immutability(&self.program_id);
+        self.process(&[ix], &[]).await
+    }
+
+    /// Whether the program was recorded immutable, simulating
+    /// `GetImmutability`.
+    pub async fn immutability(&mut self) -> ImmutabilityReport {
+        let payer = self.context.payer.insecure_clone();
+        let tx = Transaction::new_signed_with_payer(
+            &[get_immutability(&self.program_id)],
+            Some(&payer.pubkey()),
+            &[&payer],
+            self.context.last_blockhash,
+        );
+        let simulated = self
+            .context
+            .banks_client
+            .simulate_transaction(tx)
+            .await
+            .expect("simulate get immutability");
+        let return_data = simulated
+            .simulation_details
+            .and_then(|details| details.return_data)
+            .expect("return data");
+        ImmutabilityReport::try_from_slice(&return_data.data).expect("immutability data")
+    }
+
+    /// Decoded vault at `vault`, panicking if there is none.
+    pub async fn vault(&mut self, vault: &Pubkey) -> VaultAccount {
+        let account = self
+            .context
+            .banks_client
+            .get_account(*vault)
+            .await
+            .expect("get account")
+            .expect("vault exists");
+        assert_eq!(account.owner, self.program_id, "vault owner");
+        VaultAccount::try_from_slice(&account.data).expect("vault data")
+    }
+
+    /// Decoded vault lock at `vault_lock`, panicking if there is none.
+    pub async fn vault_lock(&mut self, vault_lock: &Pubkey) -> VaultLockAccount {
+        let account = self
+            .context
+            .banks_client
+            .get_account(*vault_lock)
+            .await
+            .expect("get account")
+            .expect("vault lock exists");
+        assert_eq!(account.owner, self.program_id, "vault lock owner");
+        VaultLockAccount::try_from_slice(&account.data).expect("vault lock data")
+    }
+
+    /// Decoded stats of `vault`, panicking if it has no vault lock yet.
+    pub async fn vault_stats(&mut self, vault: &Pubkey) -> VaultStatsAccount {
+        let account = self
+            .context
+            .banks_client
+            .get_account(vault_stats_address(&self.program_id, vault).0)
+            .await
+            .expect("get account")
+            .expect("vault stats exist");
+        assert_eq!(account.owner, self.program_id, "vault stats owner");
+        VaultStatsAccount::try_from_slice(&account.data).expect("vault stats data")
+    }
+
+    /// Decoded callback of `lock`, panicking if there is none.
+    pub async fn callback(&mut self, lock: &Pubkey) -> CallbackAccount {
+        let account = self
+            .context
+            .banks_client
+            .get_account(callback_address(&self.program_id, lock).0)
+            .await
+            .expect("get account")
+            .expect("callback exists");
+        assert_eq!(account.owner, self.program_id, "callback owner");
+        CallbackAccount::try_from_slice(&account.data).expect("callback data")
+    }
+
+    /// Decoded audit trail of `lock`, panicking if it keeps none.
+    pub async fn audit_trail(&mut self, lock: &Pubkey) -> AuditTrailAccount {
+        let account = self
+            .context
+            .banks_client
+            .get_account(audit_address(&self.program_id, lock).0)
+            .await
+            .expect("get account")
+            .expect("audit trail exists");
+        assert_eq!(account.owner, self.program_id, "audit trail owner");
+        AuditTrailAccount::try_from_slice(&account.data).expect("audit trail data")
+    }
+
+    /// Decoded lock nonce PDA of `payer`, panicking if there is none.
+    pub async fn lock_nonce(&mut self, payer: &Pubkey) -> LockNonceAccount {
+        let account = self
+            .context
+            .banks_client
+            .get_account(lock_nonce_address(&self.program_id, payer).0)
+            .await
+            .expect("get account")
+            .expect("lock nonce exists");
+        assert_eq!(account.owner, self.program_id, "lock nonce owner");
+        LockNonceAccount::try_from_slice(&account.data).expect("lock nonce data")
+    }
+
+    /// Decoded global statistics, panicking if the account was not created.
+    pub async fn stats(&mut self) -> TimeLockStats {
+        let account = self
+            .context
+            .banks_client
+            .get_account(stats_address(&self.program_id).0)
+            .await
+            .expect("get account")
+            .expect("stats account exists");
+        TimeLockStats::try_from_slice(&account.data).expect("stats data")
+    }
+
+    /// Assert the time lock at `lock` matches `expected`.
+    pub async fn assert_lock_state(&mut self, lock: &Pubkey, expected: &ExpectedLock) {
+        expected.check(lock, &self.lock(lock).await);
+    }
+}
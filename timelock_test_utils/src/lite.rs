@@ -0,0 +1,1032 @@
+//! LiteSVM flavour of [`TimeLockTest`](crate::TimeLockTest).
+//!
+//! Every helper has the same name and arguments, only synchronous.
+
+use std::path::Path;
+
+use borsh::BorshDeserialize as _;
+use litesvm::types::{FailedTransactionMetadata, TransactionMetadata};
+use litesvm::LiteSVM;
+use solana_sdk::account::Account;
+use solana_sdk::bpf_loader_upgradeable;
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer as _};
+use solana_sdk::transaction::Transaction;
+use timelock_program::instruction::{
+    accept_admin, add_fee_exemption, add_unlocker, apply_config_update, cancel_config_update,
+    change_escrow_beneficiary, claim_escrow, claim_escrow_early, claim_vault_lock,
+    claim_with_proof, clear_callback, collect_fees, create_distributor, create_escrow,
+    create_vault, create_vault_lock, expire, get_immutability, get_revenue, heartbeat,
+    initialize_config, initialize_time_lock, merge_locks, propose_admin, record_immutability,
+    refund_escrow, remove_fee_exemption, remove_unlocker, revoke_escrow_delegate, set_callback,
+    set_condition, set_escrow_delegate, set_expiry, set_heartbeat, set_paused, set_predecessor,
+    set_recurrence, set_unlock_instruction, split_lock, top_up, transfer_vault_lock,
+    transfer_with_payment, try_unlock, try_unlock_with_callback, try_unlock_with_condition,
+    try_unlock_with_predecessor, update_config, withdraw_excess, withdraw_fees,
+    withdraw_unallocated,
+};
+use timelock_program::{
+    callback_address, config_address, stats_address, CallbackAccount, ConfigAccount,
+    DistributorAccount, EscrowAccount, ImmutabilityReport, RevenueReport, TimeLockAccount,
+    TimeLockStats, VaultAccount, VaultLockAccount, SECRET_LENGTH,
+};
+
+use crate::{program_so, ExpectedLock, CRANKER_LAMPORTS};
+
+/// An in-process LiteSVM with the time lock program deployed.
+pub struct LiteTimeLockTest {
+    /// The underlying virtual machine.
+    pub svm: LiteSVM,
+    /// Funded payer of every transaction.
+    pub payer: Keypair,
+    /// Address the time lock program is deployed at.
+    pub program_id: Pubkey,
+}
+
+/// Start a LiteSVM with only the time lock program.
+pub fn setup_program() -> LiteTimeLockTest {
+    LiteTimeLockTest::start(LiteSVM::new(), Pubkey::new_unique(), program_so())
+}
+
+impl LiteTimeLockTest {
+    /// Deploy the program at `path` to `program_id` on `svm` and fund a payer.
+    pub fn start(mut svm: LiteSVM, program_id: Pubkey, path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        svm.add_program_from_file(program_id, path)
+            .unwrap_or_else(|e| panic!("loading {}: {e}", path.display()));
+        let payer = Keypair::new();
+        svm.airdrop(&payer.pubkey(), 100 * LAMPORTS_PER_SOL)
+            .expect("airdrop to payer");
+        Self {
+            svm,
+            payer,
+            program_id,
+        }
+    }
+
+    /// Current unix timestamp of the virtual machine.
+    pub fn now(&self) -> i64 {
+        self.svm.get_sysvar::<Clock>().unix_timestamp
+    }
+
+    /// Set the clock to `timestamp`, backwards if need be.
+    pub fn warp_to(&mut self, timestamp: i64) {
+        let mut clock = self.svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = timestamp;
+        self.svm.set_sysvar(&clock);
+    }
+
+    /// Move the clock one second past `timestamp`.
+    pub fn warp_past(&mut self, timestamp: i64) {
+        let mut clock = self.svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = clock.unix_timestamp.max(timestamp + 1);
+        self.svm.set_sysvar(&clock);
+    }
+
+    /// Send `ixs` paid by the payer and signed by `signers` as well.
+    pub fn process(
+        &mut self,
+        ixs: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        // identical transactions in a row would be rejected as already processed
+        self.svm.expire_blockhash();
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+        let tx = Transaction::new_signed_with_payer(
+            ixs,
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            self.svm.latest_blockhash(),
+        );
+        self.svm.send_transaction(tx)
+    }
+
+    /// Create a time lock paid by the payer, returning its keypair.
+    pub fn create_lock(
+        &mut self,
+        timestamp: i64,
+        secret: [u8; SECRET_LENGTH],
+        bounty: u64,
+        require_memo: bool,
+        top_up_authority_only: bool,
+    ) -> Result<Keypair, FailedTransactionMetadata> {
+        let lock = Keypair::new();
+        let ix = initialize_time_lock(
+            &self.program_id,
+            &lock.pubkey(),
+            &self.payer.pubkey(),
+            timestamp,
+            secret,
+            bounty,
+            require_memo,
+            top_up_authority_only,
+        );
+        self.process(&[ix], &[&lock])?;
+        Ok(lock)
+    }
+
+    /// A new keypair holding [`CRANKER_LAMPORTS`].
+    pub fn cranker(&mut self) -> Keypair {
+        let cranker = Keypair::new();
+        self.svm
+            .airdrop(&cranker.pubkey(), CRANKER_LAMPORTS)
+            .expect("airdrop to cranker");
+        cranker
+    }
+
+    /// Deposit `amount` lamports in `lock`, paid by `depositor` or else by
+    /// the payer, the lock authority.
+    pub fn top_up(
+        &mut self,
+        lock: &Pubkey,
+        depositor: Option<&Keypair>,
+        amount: u64,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let depositor_key = depositor.map_or(self.payer.pubkey(), |d| d.pubkey());
+        let ix = top_up(&self.program_id, lock, &depositor_key, amount);
+        let signers: Vec<&Keypair> = depositor.into_iter().collect();
+        self.process(&[ix], &signers)
+    }
+
+    /// Withdraw the surplus of `lock`, signed by `authority` or else by the
+    /// payer, the lock authority.
+    pub fn withdraw_excess(
+        &mut self,
+        lock: &Pubkey,
+        authority: Option<&Keypair>,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let authority_key = authority.map_or(self.payer.pubkey(), |a| a.pubkey());
+        let ix = withdraw_excess(&self.program_id, lock, &authority_key);
+        let signers: Vec<&Keypair> = authority.into_iter().collect();
+        self.process(&[ix], &signers)
+    }
+
+    /// Allow `unlocker` to call `TryUnlock` on `lock`, signed by `authority`
+    /// or else by the payer, the lock authority.
+    pub fn add_unlocker(
+        &mut self,
+        lock: &Pubkey,
+        authority: Option<&Keypair>,
+        unlocker: &Pubkey,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let authority_key = authority.map_or(self.payer.pubkey(), |a| a.pubkey());
+        let ix = add_unlocker(&self.program_id, lock, &authority_key, unlocker);
+        let signers: Vec<&Keypair> = authority.into_iter().collect();
+        self.process(&[ix], &signers)
+    }
+
+    /// Disallow `unlocker` to call `TryUnlock` on `lock`, signed by the
+    /// payer, the lock authority.
+    pub fn remove_unlocker(
+        &mut self,
+        lock: &Pubkey,
+        unlocker: &Pubkey,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = remove_unlocker(&self.program_id, lock, &self.payer.pubkey(), unlocker);
+        self.process(&[ix], &[])
+    }
+
+    /// Let anyone close `lock` once `expires_at` passes, paying `recipient`,
+    /// signed by the payer, the lock authority.
+    pub fn set_expiry(
+        &mut self,
+        lock: &Pubkey,
+        expires_at: i64,
+        recipient: &Pubkey,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = set_expiry(
+            &self.program_id,
+            lock,
+            &self.payer.pubkey(),
+            expires_at,
+            recipient,
+        );
+        self.process(&[ix], &[])
+    }
+
+    /// Close the expired `lock`, paying its expiry `recipient`.
+    pub fn expire(
+        &mut self,
+        lock: &Pubkey,
+        recipient: &Pubkey,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = expire(&self.program_id, lock, recipient);
+        self.process(&[ix], &[])
+    }
+
+    /// Put `lock` in dead man's switch mode with `interval`, signed by the
+    /// payer, the lock authority.
+    pub fn set_heartbeat(
+        &mut self,
+        lock: &Pubkey,
+        interval: i64,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = set_heartbeat(&self.program_id, lock, &self.payer.pubkey(), interval);
+        self.process(&[ix], &[])
+    }
+
+    /// Defer the maturity of `lock`, signed by the payer, the lock authority.
+    pub fn heartbeat(
+        &mut self,
+        lock: &Pubkey,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = heartbeat(&self.program_id, lock, &self.payer.pubkey());
+        self.process(&[ix], &[])
+    }
+
+    /// Make `lock` open `window` seconds every `every` `unit`, signed by the
+    /// payer, the lock authority.
+    pub fn set_recurrence(
+        &mut self,
+        lock: &Pubkey,
+        unit: u8,
+        every: u32,
+        window: u32,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = set_recurrence(
+            &self.program_id,
+            lock,
+            &self.payer.pubkey(),
+            unit,
+            every,
+            window,
+        );
+        self.process(&[ix], &[])
+    }
+
+    /// Make revealing `lock` call `target` with `accounts` and `data`, signed
+    /// by the payer, the lock authority.
+    pub fn set_callback(
+        &mut self,
+        lock: &Pubkey,
+        target: &Pubkey,
+        accounts: &[AccountMeta],
+        data: &[u8],
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = set_callback(
+            &self.program_id,
+            lock,
+            &self.payer.pubkey(),
+            target,
+            accounts,
+            data,
+        );
+        self.process(&[ix], &[])
+    }
+
+    /// Drop the callback of the matured `lock`, signed by the payer, the lock
+    /// authority.
+    pub fn clear_callback(
+        &mut self,
+        lock: &Pubkey,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = clear_callback(&self.program_id, lock, &self.payer.pubkey());
+        self.process(&[ix], &[])
+    }
+
+    /// Make revealing `lock` execute `ix` as is, signed by the payer, the lock
+    /// authority.
+    pub fn set_unlock_instruction(
+        &mut self,
+        lock: &Pubkey,
+        ix: &Instruction,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = set_unlock_instruction(&self.program_id, lock, &self.payer.pubkey(), ix);
+        self.process(&[ix], &[])
+    }
+
+    /// Make `lock` reveal only while `account` holds `expected` at `offset`,
+    /// signed by the payer, the lock authority.
+    pub fn set_condition(
+        &mut self,
+        lock: &Pubkey,
+        account: &Pubkey,
+        offset: u32,
+        expected: &[u8],
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = set_condition(
+            &self.program_id,
+            lock,
+            &self.payer.pubkey(),
+            account,
+            offset,
+            expected,
+        );
+        self.process(&[ix], &[])
+    }
+
+    /// Make `lock` reveal only after `predecessor` did, signed by the payer,
+    /// the lock authority.
+    pub fn set_predecessor(
+        &mut self,
+        lock: &Pubkey,
+        predecessor: &Pubkey,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = set_predecessor(&self.program_id, lock, &self.payer.pubkey(), predecessor);
+        self.process(&[ix], &[])
+    }
+
+    /// Escrow `amount` lamports of `depositor`, or else the payer, for
+    /// `beneficiary`, returning the escrow keypair.
+    pub fn create_escrow(
+        &mut self,
+        depositor: Option<&Keypair>,
+        beneficiary: &Pubkey,
+        amount: u64,
+        release_time: i64,
+        refund_time: i64,
+    ) -> Result<Keypair, FailedTransactionMetadata> {
+        self.create_escrow_with_penalty(
+            depositor,
+            beneficiary,
+            amount,
+            release_time,
+            refund_time,
+            0,
+            false,
+        )
+    }
+
+    /// [`Self::create_escrow`], the beneficiary being allowed to claim early
+    /// forfeiting `penalty_bps` of the amount.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_escrow_with_penalty(
+        &mut self,
+        depositor: Option<&Keypair>,
+        beneficiary: &Pubkey,
+        amount: u64,
+        release_time: i64,
+        refund_time: i64,
+        penalty_bps: u16,
+        penalty_decays: bool,
+    ) -> Result<Keypair, FailedTransactionMetadata> {
+        let escrow = Keypair::new();
+        let depositor_key = depositor.map_or(self.payer.pubkey(), |d| d.pubkey());
+        let ix = create_escrow(
+            &self.program_id,
+            &escrow.pubkey(),
+            &depositor_key,
+            beneficiary,
+            amount,
+            release_time,
+            refund_time,
+            penalty_bps,
+            penalty_decays,
+        );
+        let mut signers = vec![&escrow];
+        signers.extend(depositor);
+        self.process(&[ix], &signers)?;
+        Ok(escrow)
+    }
+
+    /// Fund a distributor with `amount` lamports of the payer for the
+    /// `num_leaves` claims of the tree of `root`, returning its keypair.
+    pub fn create_distributor(
+        &mut self,
+        root: [u8; 32],
+        num_leaves: u32,
+        amount: u64,
+    ) -> Result<Keypair, FailedTransactionMetadata> {
+        let distributor = Keypair::new();
+        let ix = create_distributor(
+            &self.program_id,
+            &distributor.pubkey(),
+            &self.payer.pubkey(),
+            root,
+            num_leaves,
+            amount,
+        );
+        self.process(&[ix], &[&distributor])?;
+        Ok(distributor)
+    }
+
+    /// Claim the `index`th leaf of `distributor` for `recipient`.
+    pub fn claim_with_proof(
+        &mut self,
+        distributor: &Pubkey,
+        recipient: &Pubkey,
+        index: u32,
+        amount: u64,
+        unlock_time: i64,
+        proof: &[[u8; 32]],
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = claim_with_proof(
+            &self.program_id,
+            distributor,
+            recipient,
+            index,
+            amount,
+            unlock_time,
+            proof,
+        );
+        self.process(&[ix], &[])
+    }
+
+    /// Fund a vault with `amount` lamports of the payer, returning its keypair.
+    pub fn create_vault(&mut self, amount: u64) -> Result<Keypair, FailedTransactionMetadata> {
+        let vault = Keypair::new();
+        let ix = create_vault(
+            &self.program_id,
+            &vault.pubkey(),
+            &self.payer.pubkey(),
+            amount,
+        );
+        self.process(&[ix], &[&vault])?;
+        Ok(vault)
+    }
+
+    /// Allocate `amount` lamports of `vault` to `beneficiary`, signed by the
+    /// payer, the vault authority, returning the vault lock keypair.
+    pub fn create_vault_lock(
+        &mut self,
+        vault: &Pubkey,
+        beneficiary: &Pubkey,
+        amount: u64,
+        release_time: i64,
+        transferable: bool,
+    ) -> Result<Keypair, FailedTransactionMetadata> {
+        let vault_lock = Keypair::new();
+        let ix = create_vault_lock(
+            &self.program_id,
+            &vault_lock.pubkey(),
+            vault,
+            &self.payer.pubkey(),
+            beneficiary,
+            amount,
+            release_time,
+            transferable,
+        );
+        self.process(&[ix], &[&vault_lock])?;
+        Ok(vault_lock)
+    }
+
+    /// Claim `vault_lock`, signed by `beneficiary`.
+    pub fn claim_vault_lock(
+        &mut self,
+        vault_lock: &Pubkey,
+        beneficiary: &Keypair,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let data = self.vault_lock(vault_lock);
+        let ix = claim_vault_lock(
+            &self.program_id,
+            vault_lock,
+            &data.vault,
+            &beneficiary.pubkey(),
+            &data.payer,
+        );
+        self.process(&[ix], &[beneficiary])
+    }
+
+    /// Split `amount` lamports off `vault_lock` for `new_beneficiary`, signed
+    /// by `beneficiary`, returning the new vault lock keypair.
+    pub fn split_lock(
+        &mut self,
+        vault_lock: &Pubkey,
+        beneficiary: &Keypair,
+        new_beneficiary: &Pubkey,
+        amount: u64,
+    ) -> Result<Keypair, FailedTransactionMetadata> {
+        let new_vault_lock = Keypair::new();
+        let ix = split_lock(
+            &self.program_id,
+            vault_lock,
+            &new_vault_lock.pubkey(),
+            &beneficiary.pubkey(),
+            new_beneficiary,
+            amount,
+        );
+        self.process(&[ix], &[&new_vault_lock, beneficiary])?;
+        Ok(new_vault_lock)
+    }
+
+    /// Merge `source` into `vault_lock`, signed by `beneficiary`.
+    pub fn merge_locks(
+        &mut self,
+        vault_lock: &Pubkey,
+        source: &Pubkey,
+        beneficiary: &Keypair,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let payer = self.vault_lock(source).payer;
+        let ix = merge_locks(
+            &self.program_id,
+            vault_lock,
+            source,
+            &beneficiary.pubkey(),
+            &payer,
+        );
+        self.process(&[ix], &[beneficiary])
+    }
+
+    /// Hand `vault_lock` to `new_beneficiary`, signed by `beneficiary`.
+    pub fn transfer_vault_lock(
+        &mut self,
+        vault_lock: &Pubkey,
+        beneficiary: &Keypair,
+        new_beneficiary: &Pubkey,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = transfer_vault_lock(
+            &self.program_id,
+            vault_lock,
+            &beneficiary.pubkey(),
+            new_beneficiary,
+        );
+        self.process(&[ix], &[beneficiary])
+    }
+
+    /// Sell `vault_lock` of `beneficiary` to `buyer` for `price` lamports,
+    /// signed by both.
+    pub fn transfer_with_payment(
+        &mut self,
+        vault_lock: &Pubkey,
+        beneficiary: &Keypair,
+        buyer: &Keypair,
+        price: u64,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = transfer_with_payment(
+            &self.program_id,
+            vault_lock,
+            &beneficiary.pubkey(),
+            &buyer.pubkey(),
+            price,
+        );
+        self.process(&[ix], &[beneficiary, buyer])
+    }
+
+    /// Initialize the program config with the payer as admin. The harness
+    /// program has no program data account, one naming the payer as upgrade
+    /// authority is set first.
+    pub fn initialize_config(
+        &mut self,
+        treasury: &Pubkey,
+        creation_fee: u64,
+        min_duration: i64,
+        max_duration: i64,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let admin = self.payer.pubkey();
+        // UpgradeableLoaderState::ProgramData: u32 tag 3, u64 slot, Option<Pubkey>
+        let mut data = vec![3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+        data.extend_from_slice(admin.as_ref());
+        let programdata = Account {
+            lamports: LAMPORTS_PER_SOL,
+            data,
+            owner: bpf_loader_upgradeable::id(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.svm
+            .set_account(
+                bpf_loader_upgradeable::get_program_data_address(&self.program_id),
+                programdata,
+            )
+            .expect("set program data");
+        let ix = initialize_config(
+            &self.program_id,
+            &admin,
+            treasury,
+            creation_fee,
+            min_duration,
+            max_duration,
+        );
+        self.process(&[ix], &[])
+    }
+
+    /// Replace the program config with `config`, signed by `admin`, queueing
+    /// the update and applying it in the same transaction: only works while
+    /// the update delay is zero.
+    pub fn update_config(
+        &mut self,
+        admin: &Keypair,
+        config: &ConfigAccount,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ixs = [
+            self.update_config_ix(admin, config),
+            apply_config_update(&self.program_id, &admin.pubkey()),
+        ];
+        self.process(&ixs, &[admin])
+    }
+
+    /// Queue the update of the program config to `config`, signed by `admin`.
+    pub fn queue_config_update(
+        &mut self,
+        admin: &Keypair,
+        config: &ConfigAccount,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = self.update_config_ix(admin, config);
+        self.process(&[ix], &[admin])
+    }
+
+    fn update_config_ix(&self, admin: &Keypair, config: &ConfigAccount) -> Instruction {
+        update_config(
+            &self.program_id,
+            &admin.pubkey(),
+            &config.guardian,
+            &config.treasury,
+            config.creation_fee,
+            config.min_duration,
+            config.max_duration,
+            config.paused,
+            config.claim_fee_bps,
+            config.update_delay,
+            config.clock_drift_tolerance,
+        )
+    }
+
+    /// Apply the queued update of the program config, signed by the payer as
+    /// its admin.
+    pub fn apply_config_update(
+        &mut self,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = apply_config_update(&self.program_id, &self.payer.pubkey());
+        self.process(&[ix], &[])
+    }
+
+    /// Drop the queued update of the program config, signed by the admin or
+    /// the guardian of the config, refunding its rent to the payer.
+    pub fn cancel_config_update(
+        &mut self,
+        authority: &Keypair,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let payer = self.payer.pubkey();
+        let ix = cancel_config_update(&self.program_id, &authority.pubkey(), &payer);
+        self.process(&[ix], &[authority])
+    }
+
+    /// Move `amount` lamports of the fees held by the program config to
+    /// `recipient`, signed by its admin.
+    pub fn withdraw_fees(
+        &mut self,
+        admin: &Keypair,
+        recipient: &Pubkey,
+        amount: u64,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = withdraw_fees(&self.program_id, &admin.pubkey(), recipient, amount);
+        self.process(&[ix], &[admin])
+    }
+
+    /// Exempt `key` from the fees of the program config, signed by its
+    /// `admin`.
+    pub fn add_fee_exemption(
+        &mut self,
+        admin: &Keypair,
+        key: &Pubkey,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = add_fee_exemption(&self.program_id, &admin.pubkey(), key);
+        self.process(&[ix], &[admin])
+    }
+
+    /// Charge `key` the fees of the program config again, signed by its
+    /// `admin`.
+    pub fn remove_fee_exemption(
+        &mut self,
+        admin: &Keypair,
+        key: &Pubkey,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = remove_fee_exemption(&self.program_id, &admin.pubkey(), key);
+        self.process(&[ix], &[admin])
+    }
+
+    /// Pause or unpause the program, signed by the admin or the guardian of
+    /// its config.
+    pub fn set_paused(
+        &mut self,
+        authority: &Keypair,
+        paused: bool,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = set_paused(&self.program_id, &authority.pubkey(), paused);
+        self.process(&[ix], &[authority])
+    }
+
+    /// Propose `pending_admin` to take over the program config until
+    /// `expires_at`, signed by its `admin`.
+    pub fn propose_admin(
+        &mut self,
+        admin: &Keypair,
+        pending_admin: &Pubkey,
+        expires_at: i64,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = propose_admin(&self.program_id, &admin.pubkey(), pending_admin, expires_at);
+        self.process(&[ix], &[admin])
+    }
+
+    /// Take over the program config as its `pending_admin`.
+    pub fn accept_admin(
+        &mut self,
+        pending_admin: &Keypair,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = accept_admin(&self.program_id, &pending_admin.pubkey());
+        self.process(&[ix], &[pending_admin])
+    }
+
+    /// Move the fees held by the program config to its treasury.
+    pub fn collect_fees(&mut self) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let treasury = self.config().treasury;
+        let ix = collect_fees(&self.program_id, &treasury);
+        self.process(&[ix], &[])
+    }
+
+    /// Withdraw `amount` unallocated lamports of `vault`, signed by the payer,
+    /// its authority.
+    pub fn withdraw_unallocated(
+        &mut self,
+        vault: &Pubkey,
+        amount: u64,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = withdraw_unallocated(&self.program_id, vault, &self.payer.pubkey(), amount);
+        self.process(&[ix], &[])
+    }
+
+    /// Claim `escrow`, signed by `beneficiary`.
+    pub fn claim_escrow(
+        &mut self,
+        escrow: &Pubkey,
+        beneficiary: &Keypair,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let depositor = self.escrow(escrow).depositor;
+        let ix = claim_escrow(
+            &self.program_id,
+            escrow,
+            &beneficiary.pubkey(),
+            &depositor,
+            None,
+        );
+        self.process(&[ix], &[beneficiary])
+    }
+
+    /// Claim `escrow` for its beneficiary, signed by `delegate`.
+    pub fn claim_escrow_as_delegate(
+        &mut self,
+        escrow: &Pubkey,
+        delegate: &Keypair,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let data = self.escrow(escrow);
+        let ix = claim_escrow(
+            &self.program_id,
+            escrow,
+            &data.beneficiary,
+            &data.depositor,
+            Some(&delegate.pubkey()),
+        );
+        self.process(&[ix], &[delegate])
+    }
+
+    /// Let `delegate` claim `escrow`, signed by the payer, its depositor.
+    pub fn set_escrow_delegate(
+        &mut self,
+        escrow: &Pubkey,
+        delegate: &Pubkey,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = set_escrow_delegate(&self.program_id, escrow, &self.payer.pubkey(), delegate);
+        self.process(&[ix], &[])
+    }
+
+    /// Remove the delegate of `escrow`, signed by the payer, its depositor.
+    pub fn revoke_escrow_delegate(
+        &mut self,
+        escrow: &Pubkey,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = revoke_escrow_delegate(&self.program_id, escrow, &self.payer.pubkey());
+        self.process(&[ix], &[])
+    }
+
+    /// Claim `escrow` before its release time, signed by `beneficiary`.
+    pub fn claim_escrow_early(
+        &mut self,
+        escrow: &Pubkey,
+        beneficiary: &Keypair,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let depositor = self.escrow(escrow).depositor;
+        let ix = claim_escrow_early(&self.program_id, escrow, &beneficiary.pubkey(), &depositor);
+        self.process(&[ix], &[beneficiary])
+    }
+
+    /// Refund `escrow`, signed by `depositor` or else by the payer.
+    pub fn refund_escrow(
+        &mut self,
+        escrow: &Pubkey,
+        depositor: Option<&Keypair>,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let depositor_key = depositor.map_or(self.payer.pubkey(), |d| d.pubkey());
+        let ix = refund_escrow(&self.program_id, escrow, &depositor_key);
+        let signers: Vec<&Keypair> = depositor.into_iter().collect();
+        self.process(&[ix], &signers)
+    }
+
+    /// Redirect `escrow` to `beneficiary`, signed by `depositor` or else by
+    /// the payer.
+    pub fn change_escrow_beneficiary(
+        &mut self,
+        escrow: &Pubkey,
+        depositor: Option<&Keypair>,
+        beneficiary: &Pubkey,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let depositor_key = depositor.map_or(self.payer.pubkey(), |d| d.pubkey());
+        let ix = change_escrow_beneficiary(&self.program_id, escrow, &depositor_key, beneficiary);
+        let signers: Vec<&Keypair> = depositor.into_iter().collect();
+        self.process(&[ix], &signers)
+    }
+
+    /// Call `TryUnlock` on `lock`, claiming the bounty with `cranker` if given.
+    pub fn try_unlock(
+        &mut self,
+        lock: &Pubkey,
+        cranker: Option<&Keypair>,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = try_unlock(&self.program_id, lock, cranker.map(|c| c.pubkey()).as_ref());
+        let signers: Vec<&Keypair> = cranker.into_iter().collect();
+        self.process(&[ix], &signers)
+    }
+
+    /// [`Self::try_unlock`] passing the accounts of the callback of `lock`.
+    pub fn try_unlock_with_callback(
+        &mut self,
+        lock: &Pubkey,
+        cranker: Option<&Keypair>,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let callback = self.callback(lock);
+        let ix = try_unlock_with_callback(
+            &self.program_id,
+            lock,
+            cranker.map(|c| c.pubkey()).as_ref(),
+            &callback,
+        );
+        let signers: Vec<&Keypair> = cranker.into_iter().collect();
+        self.process(&[ix], &signers)
+    }
+
+    /// [`Self::try_unlock`] passing the `condition` account of `lock`.
+    pub fn try_unlock_with_condition(
+        &mut self,
+        lock: &Pubkey,
+        cranker: Option<&Keypair>,
+        condition: &Pubkey,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = try_unlock_with_condition(
+            &self.program_id,
+            lock,
+            cranker.map(|c| c.pubkey()).as_ref(),
+            condition,
+        );
+        let signers: Vec<&Keypair> = cranker.into_iter().collect();
+        self.process(&[ix], &signers)
+    }
+
+    /// [`Self::try_unlock`] passing the `predecessor` of `lock`.
+    pub fn try_unlock_with_predecessor(
+        &mut self,
+        lock: &Pubkey,
+        cranker: Option<&Keypair>,
+        predecessor: &Pubkey,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = try_unlock_with_predecessor(
+            &self.program_id,
+            lock,
+            cranker.map(|c| c.pubkey()).as_ref(),
+            predecessor,
+        );
+        let signers: Vec<&Keypair> = cranker.into_iter().collect();
+        self.process(&[ix], &signers)
+    }
+
+    /// Lamports held by `address`, 0 if the account does not exist.
+    pub fn balance(&self, address: &Pubkey) -> u64 {
+        self.svm.get_balance(address).unwrap_or_default()
+    }
+
+    /// Decoded time lock at `lock`, panicking if there is none.
+    pub fn lock(&self, lock: &Pubkey) -> TimeLockAccount {
+        let account = self.svm.get_account(lock).expect("time lock exists");
+        assert_eq!(account.owner, self.program_id, "time lock owner");
+        TimeLockAccount::try_from_slice(&account.data).expect("time lock data")
+    }
+
+    /// Decoded escrow at `escrow`, panicking if there is none.
+    pub fn escrow(&self, escrow: &Pubkey) -> EscrowAccount {
+        let account = self.svm.get_account(escrow).expect("escrow exists");
+        assert_eq!(account.owner, self.program_id, "escrow owner");
+        EscrowAccount::try_from_slice(&account.data).expect("escrow data")
+    }
+
+    /// Decoded distributor at `distributor`, panicking if there is none.
+    pub fn distributor(&self, distributor: &Pubkey) -> DistributorAccount {
+        let account = self
+            .svm
+            .get_account(distributor)
+            .expect("distributor exists");
+        assert_eq!(account.owner, self.program_id, "distributor owner");
+        DistributorAccount::try_from_slice(&account.data).expect("distributor data")
+    }
+
+    /// Decoded program config, panicking if there is none.
+    pub fn config(&self) -> ConfigAccount {
+        let account = self
+            .svm
+            .get_account(&config_address(&self.program_id).0)
+            .expect("config exists");
+        assert_eq!(account.owner, self.program_id, "config owner");
+        ConfigAccount::try_from_slice(&account.data).expect("config data")
+    }
+
+    /// Lifetime revenue of the program config, simulating `GetRevenue`.
+    pub fn revenue(&self) -> RevenueReport {
+        let tx = Transaction::new_signed_with_payer(
+            &[get_revenue(&self.program_id)],
+            Some(&self.payer.pubkey()),
+            &[&self.payer],
+            self.svm.latest_blockhash(),
+        );
+        let simulated = self
+            .svm
+            .simulate_transaction(tx)
+            .expect("simulate get revenue");
+        RevenueReport::try_from_slice(&simulated.meta.return_data.data).expect("revenue data")
+    }
+
+    /// Drop the upgrade authority of the program, last deployed at
+    /// `deploy_slot`, by rewriting its program data account.
+    pub fn burn_upgrade_authority(&mut self, deploy_slot: u64) {
+        // UpgradeableLoaderState::ProgramData: u32 tag 3, u64 slot, Option<Pubkey>
+        let mut data = vec![3, 0, 0, 0];
+        data.extend_from_slice(&deploy_slot.to_le_bytes());
+        data.push(0);
+        let programdata = Account {
+            lamports: LAMPORTS_PER_SOL,
+            data,
+            owner: bpf_loader_upgradeable::id(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.svm
+            .set_account(
+                bpf_loader_upgradeable::get_program_data_address(&self.program_id),
+                programdata,
+            )
+            .expect("set program data");
+    }
+
+    /// Record in the program config that the program has no upgrade authority.
+    pub fn record_immutability(
+        &mut self,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let ix = record_immutability(&self.program_id);
+        self.process(&[ix], &[])
+    }
+
+    /// Whether the program was recorded immutable, simulating
+    /// `GetImmutability`.
+    pub fn immutability(&self) -> ImmutabilityReport {
+        let tx = Transaction::new_signed_with_payer(
+            &[get_immutability(&self.program_id)],
+            Some(&self.payer.pubkey()),
+            &[&self.payer],
+            self.svm.latest_blockhash(),
+        );
+        let simulated = self
+            .svm
+            .simulate_transaction(tx)
+            .expect("simulate get immutability");
+        ImmutabilityReport::try_from_slice(&simulated.meta.return_data.data)
+            .expect("immutability data")
+    }
+
+    /// Decoded vault at `vault`, panicking if there is none.
+    pub fn vault(&self, vault: &Pubkey) -> VaultAccount {
+        let account = self.svm.get_account(vault).expect("vault exists");
+        assert_eq!(account.owner, self.program_id, "vault owner");
+        VaultAccount::try_from_slice(&account.data).expect("vault data")
+    }
+
+    /// Decoded vault lock at `vault_lock`, panicking if there is none.
+    pub fn vault_lock(&self, vault_lock: &Pubkey) -> VaultLockAccount {
+        let account = self.svm.get_account(vault_lock).expect("vault lock exists");
+        assert_eq!(account.owner, self.program_id, "vault lock owner");
+        VaultLockAccount::try_from_slice(&account.data).expect("vault lock data")
+    }
+
+    /// Decoded callback of `lock`, panicking if there is none.
+    pub fn callback(&self, lock: &Pubkey) -> CallbackAccount {
+        let account = self
+            .svm
+            .get_account(&callback_address(&self.program_id, lock).0)
+            .expect("callback exists");
+        assert_eq!(account.owner, self.program_id, "callback owner");
+        CallbackAccount::try_from_slice(&account.data).expect("callback data")
+    }
+
+    /// Decoded global statistics, panicking if the account was not created.
+    pub fn stats(&self) -> TimeLockStats {
+        let account = self
+            .svm
+            .get_account(&stats_address(&self.program_id).0)
+            .expect("stats account exists");
+        TimeLockStats::try_from_slice(&account.data).expect("stats data")
+    }
+
+    /// Assert the time lock at `lock` matches `expected`.
+    pub fn assert_lock_state(&self, lock: &Pubkey, expected: &ExpectedLock) {
+        expected.check(lock, &self.lock(lock));
+    }
+}
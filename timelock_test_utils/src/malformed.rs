@@ -0,0 +1,583 @@
+//! Malformed instruction data, each with the error it must be rejected with.
+//!
+//! The corpus is generated from well formed payloads of every instruction, in
+//! both the discriminated and the legacy encoding: every truncation, trailing
+//! bytes, wrong tags and discriminators, invalid flags, secrets that are
+//! not UTF-8 and data over the size limit.
+
+use solana_sdk::pubkey::Pubkey;
+use timelock_program::merkle::MAX_PROOF_LEN;
+use timelock_program::{
+    PayrollShare, TimeLockError, TimeLockInstruction, EMIT_EVENT_TAG, MAX_CALLBACK_DATA,
+    MAX_CONDITION_BYTES, MAX_INSTRUCTION_DATA, SECRET_LENGTH,
+};
+
+/// Instruction data the program must reject with `error`.
+#[derive(Debug, Clone)]
+pub struct Malformed {
+    /// What is wrong with `data`
+    pub name: String,
+    /// The instruction data
+    pub data: Vec<u8>,
+    /// Error `TimeLockInstruction::unpack` must return
+    pub error: TimeLockError,
+}
+
+fn initialize() -> TimeLockInstruction {
+    TimeLockInstruction::InitializeTimeLock {
+        timestamp: 1_700_000_000,
+        secret: [b's'; SECRET_LENGTH],
+        bounty: 5000,
+        require_memo: true,
+        top_up_authority_only: true,
+    }
+}
+
+/// Well formed payloads: name, data and the length of the shortest prefix
+/// that still decodes, shorter ones are truncated.
+fn well_formed() -> Vec<(&'static str, Vec<u8>, usize)> {
+    let initialize = initialize().pack();
+    // the legacy layout has a one byte tag and no flags
+    let mut legacy_initialize = vec![0];
+    legacy_initialize.extend_from_slice(&initialize[8..initialize.len() - 2]);
+    let lock_stake = TimeLockInstruction::LockStake {
+        timestamp: 1_700_000_000,
+        beneficiary: Pubkey::new_from_array([7; 32]),
+    }
+    .pack();
+    let top_up = TimeLockInstruction::TopUp { amount: 5000 }.pack();
+    let create_escrow = TimeLockInstruction::CreateEscrow {
+        amount: 5000,
+        release_time: 1_700_000_000,
+        refund_time: 1_700_000_001,
+        penalty_bps: 500,
+        penalty_decays: true,
+    }
+    .pack();
+    let change_beneficiary = TimeLockInstruction::ChangeEscrowBeneficiary {
+        beneficiary: Pubkey::new_from_array([7; 32]),
+    }
+    .pack();
+    let set_delegate = TimeLockInstruction::SetEscrowDelegate {
+        delegate: Pubkey::new_from_array([7; 32]),
+    }
+    .pack();
+    let add_unlocker = TimeLockInstruction::AddUnlocker {
+        unlocker: Pubkey::new_from_array([7; 32]),
+    }
+    .pack();
+    let remove_unlocker = TimeLockInstruction::RemoveUnlocker {
+        unlocker: Pubkey::new_from_array([7; 32]),
+    }
+    .pack();
+    let set_expiry = TimeLockInstruction::SetExpiry {
+        expires_at: 1_700_000_000,
+        recipient: Pubkey::new_from_array([7; 32]),
+    }
+    .pack();
+    let set_heartbeat = TimeLockInstruction::SetHeartbeat {
+        interval: 1_700_000_000,
+    }
+    .pack();
+    let set_recurrence = TimeLockInstruction::SetRecurrence {
+        unit: 2,
+        every: 1,
+        window: 86_400,
+    }
+    .pack();
+    let set_callback = TimeLockInstruction::SetCallback {
+        data: vec![7; MAX_CALLBACK_DATA],
+    }
+    .pack();
+    let set_unlock_instruction = TimeLockInstruction::SetUnlockInstruction {
+        data: vec![7; MAX_CALLBACK_DATA],
+    }
+    .pack();
+    let set_condition = TimeLockInstruction::SetCondition {
+        account: Pubkey::new_from_array([7; 32]),
+        offset: 7,
+        expected: vec![7; MAX_CONDITION_BYTES],
+    }
+    .pack();
+    let set_predecessor = TimeLockInstruction::SetPredecessor {
+        predecessor: Pubkey::new_from_array([7; 32]),
+    }
+    .pack();
+    let create_distributor = TimeLockInstruction::CreateDistributor {
+        root: [7; 32],
+        num_leaves: 7,
+        amount: 5000,
+    }
+    .pack();
+    let claim_with_proof = TimeLockInstruction::ClaimWithProof {
+        index: 7,
+        amount: 5000,
+        unlock_time: 1_700_000_000,
+        proof: vec![[7; 32]; MAX_PROOF_LEN],
+    }
+    .pack();
+    let create_vault = TimeLockInstruction::CreateVault { amount: 5000 }.pack();
+    let create_vault_lock = TimeLockInstruction::CreateVaultLock {
+        amount: 5000,
+        release_time: 1_700_000_000,
+        transferable: true,
+    }
+    .pack();
+    let withdraw_unallocated = TimeLockInstruction::WithdrawUnallocated { amount: 5000 }.pack();
+    let split_lock = TimeLockInstruction::SplitLock { amount: 5000 }.pack();
+    let transfer_with_payment = TimeLockInstruction::TransferWithPayment { price: 5000 }.pack();
+    let initialize_config = TimeLockInstruction::InitializeConfig {
+        treasury: Pubkey::new_from_array([7; 32]),
+        creation_fee: 5000,
+        min_duration: 1_700_000_000,
+        max_duration: 1_700_000_000,
+    }
+    .pack();
+    let update_config = TimeLockInstruction::UpdateConfig {
+        treasury: Pubkey::new_from_array([7; 32]),
+        creation_fee: 5000,
+        min_duration: 1_700_000_000,
+        max_duration: 1_700_000_000,
+        paused: true,
+        claim_fee_bps: 7,
+        update_delay: 1_700_000_000,
+        clock_drift_tolerance: 30,
+    }
+    .pack();
+    let set_paused = TimeLockInstruction::SetPaused { paused: true }.pack();
+    let withdraw_fees = TimeLockInstruction::WithdrawFees { amount: 5000 }.pack();
+    let add_fee_exemption = TimeLockInstruction::AddFeeExemption {
+        key: Pubkey::new_from_array([7; 32]),
+    }
+    .pack();
+    let remove_fee_exemption = TimeLockInstruction::RemoveFeeExemption {
+        key: Pubkey::new_from_array([7; 32]),
+    }
+    .pack();
+    let propose_admin = TimeLockInstruction::ProposeAdmin {
+        expires_at: 1_700_000_000,
+    }
+    .pack();
+    let lock_stake_idempotent = TimeLockInstruction::LockStakeIdempotent {
+        timestamp: 1_700_000_000,
+        beneficiary: Pubkey::new_from_array([7; 32]),
+    }
+    .pack();
+    let record_verified_build = TimeLockInstruction::RecordVerifiedBuild {
+        build_hash: [7; 32],
+        source_commit: [7; 20],
+    }
+    .pack();
+    let commit_claim = TimeLockInstruction::CommitClaim {
+        commitment: [7; 32],
+    }
+    .pack();
+    let reveal_claim = TimeLockInstruction::RevealClaim { salt: [7; 32] }.pack();
+    let initialize_nonced_time_lock = TimeLockInstruction::InitializeNoncedTimeLock {
+        nonce: 5000,
+        timestamp: 1_700_000_000,
+        secret: [b's'; SECRET_LENGTH],
+        bounty: 5000,
+        require_memo: true,
+        top_up_authority_only: true,
+    }
+    .pack();
+    let create_payroll = TimeLockInstruction::CreatePayroll {
+        amount: 5000,
+        start_time: 1_700_000_000,
+        end_time: 1_700_000_000,
+        num_entries: 7,
+    }
+    .pack();
+    let add_payroll_entries = TimeLockInstruction::AddPayrollEntries {
+        entries: vec![
+            PayrollShare {
+                beneficiary: Pubkey::new_from_array([7; 32]),
+                weight: 7,
+            };
+            7
+        ],
+    }
+    .pack();
+    let claim_payroll = TimeLockInstruction::ClaimPayroll { index: 7 }.pack();
+    let create_schedule_template = TimeLockInstruction::CreateScheduleTemplate {
+        tge_bps: 7,
+        cliff_months: 7,
+        period_months: 7,
+        periods: 7,
+    }
+    .pack();
+    let create_templated_payroll = TimeLockInstruction::CreateTemplatedPayroll {
+        amount: 5000,
+        start_time: 1_700_000_000,
+        num_entries: 7,
+    }
+    .pack();
+    let set_grant_recipient = TimeLockInstruction::SetGrantRecipient {
+        recipient: Pubkey::new_from_array([7; 32]),
+    }
+    .pack();
+    vec![
+        // without its flags, the lock is a valid one without restrictions
+        (
+            "initialize_time_lock",
+            initialize.clone(),
+            initialize.len() - 2,
+        ),
+        (
+            "legacy initialize_time_lock",
+            legacy_initialize.clone(),
+            legacy_initialize.len(),
+        ),
+        ("try_unlock", TimeLockInstruction::TryUnlock.pack(), 8),
+        ("legacy try_unlock", vec![1], 1),
+        ("get_position", TimeLockInstruction::GetPosition.pack(), 8),
+        ("lock_stake", lock_stake.clone(), lock_stake.len()),
+        ("release_stake", TimeLockInstruction::ReleaseStake.pack(), 8),
+        ("top_up", top_up.clone(), top_up.len()),
+        (
+            "withdraw_excess",
+            TimeLockInstruction::WithdrawExcess.pack(),
+            8,
+        ),
+        ("create_escrow", create_escrow.clone(), create_escrow.len()),
+        ("claim_escrow", TimeLockInstruction::ClaimEscrow.pack(), 8),
+        ("refund_escrow", TimeLockInstruction::RefundEscrow.pack(), 8),
+        (
+            "change_escrow_beneficiary",
+            change_beneficiary.clone(),
+            change_beneficiary.len(),
+        ),
+        (
+            "set_escrow_delegate",
+            set_delegate.clone(),
+            set_delegate.len(),
+        ),
+        (
+            "revoke_escrow_delegate",
+            TimeLockInstruction::RevokeEscrowDelegate.pack(),
+            8,
+        ),
+        ("add_unlocker", add_unlocker.clone(), add_unlocker.len()),
+        (
+            "remove_unlocker",
+            remove_unlocker.clone(),
+            remove_unlocker.len(),
+        ),
+        (
+            "claim_escrow_early",
+            TimeLockInstruction::ClaimEscrowEarly.pack(),
+            8,
+        ),
+        ("set_expiry", set_expiry.clone(), set_expiry.len()),
+        ("expire", TimeLockInstruction::Expire.pack(), 8),
+        ("set_heartbeat", set_heartbeat.clone(), set_heartbeat.len()),
+        ("heartbeat", TimeLockInstruction::Heartbeat.pack(), 8),
+        (
+            "set_recurrence",
+            set_recurrence.clone(),
+            set_recurrence.len(),
+        ),
+        // the data of the call is optional, but bounded
+        ("set_callback", set_callback, 8),
+        ("set_unlock_instruction", set_unlock_instruction, 8),
+        // so are the expected bytes of a condition
+        ("set_condition", set_condition, 8 + 32 + 4),
+        (
+            "set_predecessor",
+            set_predecessor.clone(),
+            set_predecessor.len(),
+        ),
+        (
+            "create_distributor",
+            create_distributor.clone(),
+            create_distributor.len(),
+        ),
+        // the proof is whole hashes, at most MAX_PROOF_LEN of them
+        ("claim_with_proof", claim_with_proof, 8 + 4 + 8 + 8),
+        ("create_vault", create_vault.clone(), create_vault.len()),
+        (
+            "create_vault_lock",
+            create_vault_lock.clone(),
+            create_vault_lock.len(),
+        ),
+        (
+            "claim_vault_lock",
+            TimeLockInstruction::ClaimVaultLock.pack(),
+            8,
+        ),
+        (
+            "withdraw_unallocated",
+            withdraw_unallocated.clone(),
+            withdraw_unallocated.len(),
+        ),
+        ("split_lock", split_lock.clone(), split_lock.len()),
+        ("merge_locks", TimeLockInstruction::MergeLocks.pack(), 8),
+        (
+            "transfer_vault_lock",
+            TimeLockInstruction::TransferVaultLock.pack(),
+            8,
+        ),
+        (
+            "transfer_with_payment",
+            transfer_with_payment.clone(),
+            transfer_with_payment.len(),
+        ),
+        (
+            "initialize_config",
+            initialize_config.clone(),
+            initialize_config.len(),
+        ),
+        ("update_config", update_config.clone(), update_config.len()),
+        ("collect_fees", TimeLockInstruction::CollectFees.pack(), 8),
+        ("set_paused", set_paused.clone(), set_paused.len()),
+        ("withdraw_fees", withdraw_fees.clone(), withdraw_fees.len()),
+        (
+            "add_fee_exemption",
+            add_fee_exemption.clone(),
+            add_fee_exemption.len(),
+        ),
+        (
+            "remove_fee_exemption",
+            remove_fee_exemption.clone(),
+            remove_fee_exemption.len(),
+        ),
+        ("propose_admin", propose_admin.clone(), propose_admin.len()),
+        ("accept_admin", TimeLockInstruction::AcceptAdmin.pack(), 8),
+        (
+            "apply_config_update",
+            TimeLockInstruction::ApplyConfigUpdate.pack(),
+            8,
+        ),
+        (
+            "cancel_config_update",
+            TimeLockInstruction::CancelConfigUpdate.pack(),
+            8,
+        ),
+        ("get_revenue", TimeLockInstruction::GetRevenue.pack(), 8),
+        (
+            "record_immutability",
+            TimeLockInstruction::RecordImmutability.pack(),
+            8,
+        ),
+        (
+            "get_immutability",
+            TimeLockInstruction::GetImmutability.pack(),
+            8,
+        ),
+        (
+            "lock_stake_idempotent",
+            lock_stake_idempotent.clone(),
+            lock_stake_idempotent.len(),
+        ),
+        (
+            "record_verified_build",
+            record_verified_build.clone(),
+            record_verified_build.len(),
+        ),
+        (
+            "enable_audit_trail",
+            TimeLockInstruction::EnableAuditTrail.pack(),
+            8,
+        ),
+        (
+            "require_claim_commit",
+            TimeLockInstruction::RequireClaimCommit.pack(),
+            8,
+        ),
+        ("commit_claim", commit_claim.clone(), commit_claim.len()),
+        ("reveal_claim", reveal_claim.clone(), reveal_claim.len()),
+        (
+            "initialize_nonced_time_lock",
+            initialize_nonced_time_lock.clone(),
+            initialize_nonced_time_lock.len(),
+        ),
+        (
+            "create_payroll",
+            create_payroll.clone(),
+            create_payroll.len(),
+        ),
+        // so are the entries of a payroll, whole shares
+        ("add_payroll_entries", add_payroll_entries, 8),
+        ("claim_payroll", claim_payroll.clone(), claim_payroll.len()),
+        (
+            "create_schedule_template",
+            create_schedule_template.clone(),
+            create_schedule_template.len(),
+        ),
+        (
+            "create_templated_payroll",
+            create_templated_payroll.clone(),
+            create_templated_payroll.len(),
+        ),
+        (
+            "clear_callback",
+            TimeLockInstruction::ClearCallback.pack(),
+            8,
+        ),
+        ("close_payroll", TimeLockInstruction::ClosePayroll.pack(), 8),
+        (
+            "migrate_account",
+            TimeLockInstruction::MigrateAccount.pack(),
+            8,
+        ),
+        (
+            "set_grant_recipient",
+            set_grant_recipient.clone(),
+            set_grant_recipient.len(),
+        ),
+        ("release_grant", TimeLockInstruction::ReleaseGrant.pack(), 8),
+    ]
+}
+
+/// Every malformed payload of the corpus.
+pub fn corpus() -> Vec<Malformed> {
+    let mut corpus = vec![Malformed {
+        name: "empty data".into(),
+        data: vec![],
+        error: TimeLockError::InstructionTooShort,
+    }];
+    let mut push =
+        |name: String, data: Vec<u8>, error| corpus.push(Malformed { name, data, error });
+
+    for tag in (2..=u8::MAX).filter(|tag| *tag != EMIT_EVENT_TAG) {
+        push(
+            format!("unknown tag {tag}"),
+            vec![tag],
+            TimeLockError::UnknownInstruction,
+        );
+    }
+    push(
+        "unknown discriminator".into(),
+        vec![0x42; 8],
+        TimeLockError::UnknownInstruction,
+    );
+    // the size is checked before the instruction is known
+    for (name, mut data) in [
+        ("unknown discriminator", vec![0x42; 8]),
+        ("event", vec![EMIT_EVENT_TAG]),
+    ] {
+        data.resize(MAX_INSTRUCTION_DATA + 1, 0);
+        push(
+            format!("{name} over MAX_INSTRUCTION_DATA"),
+            data,
+            TimeLockError::InstructionDataTooLarge,
+        );
+    }
+
+    for (name, data, complete) in well_formed() {
+        let discriminated = data.len() >= 8 && data[0] > 1;
+        for len in 1..complete {
+            // the first layout had no bounty, a lock without one then
+            if name.ends_with("initialize_time_lock") && len == complete - 8 {
+                continue;
+            }
+            // a partial discriminator is read as a legacy tag
+            let error = match discriminated && len < 8 {
+                true => TimeLockError::UnknownInstruction,
+                false => TimeLockError::InstructionTooShort,
+            };
+            push(
+                format!("{name} truncated to {len} bytes"),
+                data[..len].to_vec(),
+                error,
+            );
+        }
+        for extra in [1, 2, 32] {
+            // zeros after the legacy fields are valid flags
+            if name == "legacy initialize_time_lock" && extra <= 2 {
+                continue;
+            }
+            let mut oversized = data.clone();
+            oversized.resize(data.len() + extra, 0);
+            // the variable length fields have their own bounds
+            let error = match name {
+                "set_condition" => TimeLockError::ConditionTooLong,
+                "claim_with_proof" if extra == 32 => TimeLockError::ProofTooLong,
+                _ => TimeLockError::InstructionTooLong,
+            };
+            push(
+                format!("{name} with {extra} trailing bytes"),
+                oversized,
+                error,
+            );
+        }
+    }
+
+    let initialize = initialize().pack();
+    let flags = initialize.len() - 2;
+    for value in 2..=u8::MAX {
+        let mut data = initialize.clone();
+        data[flags] = value;
+        push(
+            format!("memo flag {value}"),
+            data,
+            TimeLockError::InvalidMemoFlag,
+        );
+        let mut data = initialize.clone();
+        data[flags + 1] = value;
+        push(
+            format!("top up flag {value}"),
+            data,
+            TimeLockError::InvalidTopUpFlag,
+        );
+    }
+    let create_escrow = TimeLockInstruction::CreateEscrow {
+        amount: 5000,
+        release_time: 1_700_000_000,
+        refund_time: 1_700_000_001,
+        penalty_bps: 500,
+        penalty_decays: true,
+    }
+    .pack();
+    for value in 2..=u8::MAX {
+        let mut data = create_escrow.clone();
+        *data.last_mut().unwrap() = value;
+        push(
+            format!("penalty decay flag {value}"),
+            data,
+            TimeLockError::InvalidPenaltyFlag,
+        );
+    }
+    let create_vault_lock = TimeLockInstruction::CreateVaultLock {
+        amount: 5000,
+        release_time: 1_700_000_000,
+        transferable: true,
+    }
+    .pack();
+    for value in 2..=u8::MAX {
+        let mut data = create_vault_lock.clone();
+        *data.last_mut().unwrap() = value;
+        push(
+            format!("transferable flag {value}"),
+            data,
+            TimeLockError::InvalidTransferableFlag,
+        );
+    }
+    let mut oversized_secret = initialize[..8 + 8].to_vec();
+    oversized_secret.extend_from_slice(&[b's'; SECRET_LENGTH + 1]);
+    oversized_secret.extend_from_slice(&initialize[8 + 8 + SECRET_LENGTH..]);
+    push(
+        "secret one byte too long".into(),
+        oversized_secret,
+        TimeLockError::InstructionTooLong,
+    );
+    for at in [0, SECRET_LENGTH / 2, SECRET_LENGTH - 1] {
+        let mut data = initialize.clone();
+        data[8 + 8 + at] = 0xff;
+        push(
+            format!("secret not utf8 at byte {at}"),
+            data,
+            TimeLockError::SecretNotUtf8,
+        );
+        // the legacy layout shares the check
+        let mut legacy = vec![0];
+        legacy.extend_from_slice(&data[8..flags]);
+        push(
+            format!("legacy secret not utf8 at byte {at}"),
+            legacy,
+            TimeLockError::SecretNotUtf8,
+        );
+    }
+    corpus
+}
@@ -0,0 +1,43 @@
+#![cfg(feature = "litesvm")]
+
+use solana_sdk::signature::Signer as _;
+use timelock_program::SECRET_LENGTH;
+use timelock_test_utils::lite::setup_program;
+use timelock_test_utils::{ExpectedLock, CRANKER_LAMPORTS};
+
+#[test]
+fn test_unlock_matrix() {
+    let mut test = setup_program();
+    let now = test.now();
+    for bounty in [0, 1, 5000, 1_000_000] {
+        for delay in [-1, 0, 1, 60] {
+            let timestamp = now + delay;
+            let lock = test
+                .create_lock(timestamp, [7; SECRET_LENGTH], bounty, false, false)
+                .unwrap()
+                .pubkey();
+            let cranker = test.cranker();
+            let claim = (bounty > 0).then_some(&cranker);
+            let matured = timestamp <= test.now();
+            test.try_unlock(&lock, claim).unwrap();
+            test.assert_lock_state(
+                &lock,
+                &ExpectedLock {
+                    timestamp: Some(timestamp),
+                    bounty: Some(if matured && claim.is_some() {
+                        0
+                    } else {
+                        bounty
+                    }),
+                    attempts: Some(1),
+                    premature_attempts: Some(u32::from(!matured)),
+                    revealed: Some(matured),
+                },
+            );
+            if matured && claim.is_some() {
+                assert_eq!(test.balance(&cranker.pubkey()), CRANKER_LAMPORTS + bounty);
+            }
+        }
+    }
+    assert_eq!(test.stats().total_locks, 16);
+}
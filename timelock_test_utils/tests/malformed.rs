@@ -0,0 +1,25 @@
+use solana_sdk::program_error::ProgramError;
+use solana_sdk::pubkey::Pubkey;
+use timelock_program::TimeLockInstruction;
+use timelock_test_utils::malformed::corpus;
+
+#[test]
+fn test_malformed_corpus() {
+    let program_id = Pubkey::new_unique();
+    for case in corpus() {
+        let expected = Err(ProgramError::from(case.error));
+        assert_eq!(
+            TimeLockInstruction::unpack(&case.data),
+            expected,
+            "{}",
+            case.name
+        );
+        // the program rejects it before looking at any account
+        assert_eq!(
+            timelock_program::process_instruction(&program_id, &[], &case.data),
+            expected.map(|_| ()),
+            "{}",
+            case.name
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! Account bytes and compute units of each instruction, compared against the
+//! snapshots in `tests/snapshots/mollusk`.
+//!
+//! A missing snapshot is recorded, a differing one fails the test unless
+//! `UPDATE_SNAPSHOTS=1` is set, in which case it is rewritten. Either way the
+//! diff shows up in review.
+#![cfg(feature = "mollusk")]
+
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use mollusk_svm::program::keyed_account_for_system_program;
+use mollusk_svm::result::{InstructionResult, ProgramResult};
+use mollusk_svm::Mollusk;
+use solana_sdk::account::Account;
+use solana_sdk::bpf_loader_upgradeable;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::{system_program, sysvar};
+use timelock_program::instruction::{get_position, initialize_time_lock, try_unlock};
+use timelock_program::{config_address, stats_address, SECRET_LENGTH};
+use timelock_test_utils::program_so;
+
+// fixed addresses and times, so the snapshots are stable
+const PROGRAM_ID: Pubkey = Pubkey::new_from_array([1; 32]);
+const LOCK: Pubkey = Pubkey::new_from_array([2; 32]);
+const PAYER: Pubkey = Pubkey::new_from_array([3; 32]);
+const CRANKER: Pubkey = Pubkey::new_from_array([4; 32]);
+const TIMESTAMP: i64 = 1_700_000_000;
+const BOUNTY: u64 = 5000;
+
+fn mollusk() -> Mollusk {
+    let path = program_so();
+    let elf = std::fs::read(&path).unwrap_or_else(|e| panic!("reading {}: {e}", path.display()));
+    let mut mollusk = Mollusk::default();
+    mollusk.add_program_with_elf_and_loader(&PROGRAM_ID, &elf, &bpf_loader_upgradeable::id());
+    mollusk
+}
+
+fn process(
+    mollusk: &mut Mollusk,
+    now: i64,
+    ix: &Instruction,
+    accounts: &[(Pubkey, Account)],
+) -> InstructionResult {
+    mollusk.sysvars.clock.unix_timestamp = now;
+    let result = mollusk.process_instruction(ix, accounts);
+    assert_eq!(result.program_result, ProgramResult::Success, "{ix:?}");
+    result
+}
+
+fn account(result: &InstructionResult, key: &Pubkey) -> (Pubkey, Account) {
+    result
+        .resulting_accounts
+        .iter()
+        .find(|(k, _)| k == key)
+        .cloned()
+        .expect("account of the instruction")
+}
+
+fn snapshot(name: &str, result: &InstructionResult) {
+    let mut rendered = format!("compute_units: {}\n", result.compute_units_consumed);
+    if !result.return_data.is_empty() {
+        let hex: String = result
+            .return_data
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        writeln!(rendered, "return_data: {hex}").unwrap();
+    }
+    for (key, account) in &result.resulting_accounts {
+        writeln!(
+            rendered,
+            "\n{key}\nowner: {}\nlamports: {}",
+            account.owner, account.lamports
+        )
+        .unwrap();
+        for chunk in account.data.chunks(32) {
+            let line: String = chunk.iter().map(|b| format!("{b:02x}")).collect();
+            writeln!(rendered, "{line}").unwrap();
+        }
+    }
+
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots/mollusk")
+        .join(format!("{name}.snap"));
+    match std::fs::read_to_string(&path) {
+        Ok(expected) if expected == rendered => {}
+        Ok(_) if std::env::var_os("UPDATE_SNAPSHOTS").is_none() => {
+            panic!(
+                "{name} differs from {}, rerun with UPDATE_SNAPSHOTS=1 to accept:\n{rendered}",
+                path.display()
+            )
+        }
+        _ => {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, rendered).unwrap();
+        }
+    }
+}
+
+#[test]
+fn test_snapshots() {
+    let mut mollusk = mollusk();
+    let stats = stats_address(&PROGRAM_ID).0;
+
+    let ix = initialize_time_lock(
+        &PROGRAM_ID,
+        &LOCK,
+        &PAYER,
+        TIMESTAMP,
+        [b's'; SECRET_LENGTH],
+        BOUNTY,
+        false,
+        false,
+    );
+    let initialized = process(
+        &mut mollusk,
+        TIMESTAMP - 60,
+        &ix,
+        &[
+            (LOCK, Account::default()),
+            (
+                PAYER,
+                Account::new(10 * LAMPORTS_PER_SOL, 0, &system_program::id()),
+            ),
+            keyed_account_for_system_program(),
+            (stats, Account::default()),
+            (config_address(&PROGRAM_ID).0, Account::default()),
+        ],
+    );
+    snapshot("initialize_time_lock", &initialized);
+    let lock = account(&initialized, &LOCK);
+
+    let ix = try_unlock(&PROGRAM_ID, &LOCK, None);
+    let premature = process(&mut mollusk, TIMESTAMP - 1, &ix, &[lock.clone()]);
+    snapshot("try_unlock_premature", &premature);
+
+    let revealed = process(&mut mollusk, TIMESTAMP, &ix, &[lock.clone()]);
+    snapshot("try_unlock_revealed", &revealed);
+
+    let ix = try_unlock(&PROGRAM_ID, &LOCK, Some(&CRANKER));
+    let claimed = process(
+        &mut mollusk,
+        TIMESTAMP,
+        &ix,
+        &[
+            lock.clone(),
+            (
+                CRANKER,
+                Account::new(LAMPORTS_PER_SOL, 0, &system_program::id()),
+            ),
+            account(&initialized, &stats),
+            (config_address(&PROGRAM_ID).0, Account::default()),
+            (sysvar::instructions::id(), Account::default()),
+        ],
+    );
+    snapshot("try_unlock_claimed", &claimed);
+
+    let ix = get_position(&PROGRAM_ID, &LOCK);
+    let position = process(&mut mollusk, TIMESTAMP, &ix, &[lock]);
+    snapshot("get_position", &position);
+}
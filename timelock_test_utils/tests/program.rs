@@ -0,0 +1,2448 @@
+use borsh::BorshSerialize;
+use solana_program_test::processor;
+use solana_sdk::account::{Account, AccountSharedData};
+use solana_sdk::account_info::AccountInfo;
+use solana_sdk::entrypoint::ProgramResult;
+use solana_sdk::instruction::{AccountMeta, Instruction, InstructionError};
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::program_error::ProgramError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer as _};
+use solana_sdk::transaction::TransactionError;
+use solana_sdk::{system_instruction, system_program};
+use timelock_program::builder::TimeLockBuilder;
+use timelock_program::instruction::{
+    apply_config_update, claim_escrow, claim_payroll, claim_vault_lock, clear_callback,
+    close_payroll, create_escrow, enable_audit_trail, record_verified_build, refund_escrow,
+    set_heartbeat, try_unlock, with_audit_trail, with_fee_exemption,
+};
+use timelock_program::{
+    audit_address, callback_address, claim_commitment_address, config_address, cpi, lock_address,
+    merkle, stats_address, ConfigAccount, FixedSizeAccount as _, PayrollShare,
+    ScheduleTemplateAccount, TimeLockAccount, TimeLockError, TimeLockStats, MAX_ACCOUNTS,
+    MEMO_PROGRAM_ID, SECRET_LENGTH,
+};
+use timelock_test_utils::{
+    program_test, setup_program, ExpectedLock, TimeLockTest, CRANKER_LAMPORTS,
+};
+
+#[tokio::test]
+async fn test_timelock_program() {
+    let mut test = setup_program().await;
+    let timestamp = test.now().await + 5;
+    let secret = [65; SECRET_LENGTH];
+    let bounty: u64 = 5000;
+
+    // Step 1: Initialize the timelock
+    let lock = test
+        .create_lock(timestamp, secret, bounty, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+    test.assert_lock_state(
+        &lock,
+        &ExpectedLock {
+            timestamp: Some(timestamp),
+            bounty: Some(bounty),
+            attempts: Some(0),
+            revealed: Some(false),
+            ..Default::default()
+        },
+    )
+    .await;
+    let rent = test.context.banks_client.get_rent().await.unwrap();
+    let lock_lamports = test.balance(&lock).await;
+    assert_eq!(
+        lock_lamports,
+        TimeLockAccount::rent_exempt_lamports(&rent) + bounty
+    );
+    let stats = test.stats().await;
+    assert_eq!(stats.active_locks, 1);
+    assert_eq!(stats.total_locks, 1);
+    assert_eq!(stats.locked_lamports, lock_lamports);
+    assert_eq!(stats.bump, stats_address(&test.program_id).1);
+
+    // Step 2: Unlocking too early only counts the attempt
+    test.try_unlock(&lock, None).await.unwrap();
+    test.assert_lock_state(
+        &lock,
+        &ExpectedLock {
+            attempts: Some(1),
+            premature_attempts: Some(1),
+            revealed: Some(false),
+            ..Default::default()
+        },
+    )
+    .await;
+
+    // Step 3: Once matured, the cranker reveals the secret and earns the bounty
+    test.warp_past(timestamp).await;
+    let cranker = test.cranker().await.unwrap();
+    test.try_unlock(&lock, Some(&cranker)).await.unwrap();
+    test.assert_lock_state(
+        &lock,
+        &ExpectedLock {
+            bounty: Some(0),
+            attempts: Some(2),
+            premature_attempts: Some(1),
+            revealed: Some(true),
+            ..Default::default()
+        },
+    )
+    .await;
+    assert_eq!(test.lock(&lock).await.secret, secret);
+    assert_eq!(
+        test.balance(&cranker.pubkey()).await,
+        CRANKER_LAMPORTS + bounty
+    );
+    assert_eq!(test.stats().await.locked_lamports, lock_lamports - bounty);
+}
+
+#[tokio::test]
+async fn test_legacy_initialize() {
+    let mut test = setup_program().await;
+    let timestamp = test.now().await + 5;
+    let secret = [65; SECRET_LENGTH];
+    let lock = Keypair::new();
+
+    // the first clients send a one byte tag, the timestamp and the secret,
+    // with the lock, the payer and the system program only
+    let mut data = vec![0];
+    data.extend_from_slice(&timestamp.to_le_bytes());
+    data.extend_from_slice(&secret);
+    let ix = Instruction::new_with_bytes(
+        test.program_id,
+        &data,
+        vec![
+            AccountMeta::new(lock.pubkey(), true),
+            AccountMeta::new(test.context.payer.pubkey(), true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    test.process(&[ix], &[&lock]).await.unwrap();
+    test.assert_lock_state(
+        &lock.pubkey(),
+        &ExpectedLock {
+            timestamp: Some(timestamp),
+            bounty: Some(0),
+            revealed: Some(false),
+            ..Default::default()
+        },
+    )
+    .await;
+    // without the stats, the lock is not counted
+    let stats = test
+        .context
+        .banks_client
+        .get_account(stats_address(&test.program_id).0)
+        .await
+        .unwrap();
+    assert!(stats.is_none());
+
+    test.warp_past(timestamp).await;
+    let ix = Instruction::new_with_bytes(
+        test.program_id,
+        &[1],
+        vec![AccountMeta::new(lock.pubkey(), false)],
+    );
+    test.process(&[ix], &[]).await.unwrap();
+    test.assert_lock_state(
+        &lock.pubkey(),
+        &ExpectedLock {
+            revealed: Some(true),
+            ..Default::default()
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_unlock_around_timestamp() {
+    let mut test = setup_program().await;
+    let timestamp = test.now().await + 100;
+    let early = test
+        .create_lock(timestamp, [b'e'; SECRET_LENGTH], 0, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+    let late = test
+        .create_lock(timestamp, [b'l'; SECRET_LENGTH], 0, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+
+    // strictly before: locked
+    test.warp_to(timestamp - 1).await;
+    test.try_unlock(&early, None).await.unwrap();
+    test.assert_lock_state(
+        &early,
+        &ExpectedLock {
+            attempts: Some(1),
+            premature_attempts: Some(1),
+            revealed: Some(false),
+            ..Default::default()
+        },
+    )
+    .await;
+
+    // the boundary second: unlocked
+    test.warp_to(timestamp).await;
+    test.try_unlock(&early, None).await.unwrap();
+    test.assert_lock_state(
+        &early,
+        &ExpectedLock {
+            attempts: Some(2),
+            premature_attempts: Some(1),
+            revealed: Some(true),
+            ..Default::default()
+        },
+    )
+    .await;
+    assert_eq!(test.lock(&early).await.revealed_at, timestamp);
+
+    // strictly after: unlocked, the first reveal time is kept
+    test.warp_to(timestamp + 1).await;
+    test.try_unlock(&late, None).await.unwrap();
+    test.try_unlock(&early, None).await.unwrap();
+    test.assert_lock_state(
+        &late,
+        &ExpectedLock {
+            attempts: Some(1),
+            premature_attempts: Some(0),
+            revealed: Some(true),
+            ..Default::default()
+        },
+    )
+    .await;
+    assert_eq!(test.lock(&late).await.revealed_at, timestamp + 1);
+    assert_eq!(test.lock(&early).await.revealed_at, timestamp);
+    assert_eq!(test.lock(&early).await.last_attempt, timestamp + 1);
+}
+
+#[tokio::test]
+async fn test_top_up() {
+    let mut test = setup_program().await;
+    let timestamp = test.now().await + 100;
+    let open = test
+        .create_lock(timestamp, [b'o'; SECRET_LENGTH], 1000, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+    let restricted = test
+        .create_lock(timestamp, [b'r'; SECRET_LENGTH], 1000, false, true)
+        .await
+        .unwrap()
+        .pubkey();
+    let locked_lamports = test.stats().await.locked_lamports;
+    let stranger = test.cranker().await.unwrap();
+
+    // anyone tops up an open lock, only the authority a restricted one
+    test.top_up(&open, Some(&stranger), 500).await.unwrap();
+    assert!(test
+        .top_up(&restricted, Some(&stranger), 500)
+        .await
+        .is_err());
+    test.top_up(&restricted, None, 250).await.unwrap();
+    // an empty installment is refused
+    assert!(test.top_up(&open, None, 0).await.is_err());
+    for (lock, funded) in [(open, 500), (restricted, 250)] {
+        test.assert_lock_state(
+            &lock,
+            &ExpectedLock {
+                bounty: Some(1000),
+                funded: Some(funded),
+                ..Default::default()
+            },
+        )
+        .await;
+    }
+    assert_eq!(test.stats().await.locked_lamports, locked_lamports + 750);
+    let grantee = test.cranker().await.unwrap().pubkey();
+    // the authority names the grantee, and may name another until maturity
+    test.set_grant_recipient(&open, &stranger.pubkey())
+        .await
+        .unwrap();
+    test.set_grant_recipient(&open, &grantee).await.unwrap();
+
+    // a matured lock may reveal any time, deposits are refused
+    test.warp_to(timestamp).await;
+    assert!(test.top_up(&open, None, 1).await.is_err());
+    assert!(test
+        .set_grant_recipient(&open, &stranger.pubkey())
+        .await
+        .is_err());
+    assert!(test.release_grant(&open, &grantee).await.is_err());
+    let cranker = test.cranker().await.unwrap();
+    test.try_unlock(&open, Some(&cranker)).await.unwrap();
+    // the cranker is paid the bounty alone, the installments go to the grantee
+    assert_eq!(
+        test.balance(&cranker.pubkey()).await,
+        CRANKER_LAMPORTS + 1000
+    );
+    assert!(test.release_grant(&open, &cranker.pubkey()).await.is_err());
+    test.release_grant(&open, &grantee).await.unwrap();
+    assert_eq!(test.balance(&grantee).await, CRANKER_LAMPORTS + 500);
+    assert_eq!(test.lock(&open).await.funded, 0);
+
+    // without a grant recipient the authority receives them
+    test.try_unlock(&restricted, None).await.unwrap();
+    let authority = test.context.payer.pubkey();
+    test.release_grant(&restricted, &authority).await.unwrap();
+    assert_eq!(test.lock(&restricted).await.funded, 0);
+    assert_eq!(test.stats().await.locked_lamports, locked_lamports - 1000);
+}
+
+#[tokio::test]
+async fn test_withdraw_excess() {
+    let mut test = setup_program().await;
+    let timestamp = test.now().await + 100;
+    let lock = test
+        .create_lock(timestamp, [b'x'; SECRET_LENGTH], 1000, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+    let accounted = test.balance(&lock).await;
+
+    // lamports sent to the lock by mistake
+    let payer = test.context.payer.pubkey();
+    let transfer = system_instruction::transfer(&payer, &lock, 7000);
+    test.process(&[transfer], &[]).await.unwrap();
+    assert_eq!(test.balance(&lock).await, accounted + 7000);
+
+    let stranger = test.cranker().await.unwrap();
+    assert!(test.withdraw_excess(&lock, Some(&stranger)).await.is_err());
+    test.withdraw_excess(&lock, None).await.unwrap();
+    assert_eq!(test.balance(&lock).await, accounted);
+    test.assert_lock_state(
+        &lock,
+        &ExpectedLock {
+            bounty: Some(1000),
+            ..Default::default()
+        },
+    )
+    .await;
+    // nothing left to withdraw
+    test.withdraw_excess(&lock, None).await.unwrap();
+    assert_eq!(test.balance(&lock).await, accounted);
+}
+
+#[tokio::test]
+async fn test_escrow() {
+    let mut test = setup_program().await;
+    let release_time = test.now().await + 100;
+    let refund_time = release_time + 100;
+    let beneficiary = test.cranker().await.unwrap();
+    let stranger = test.cranker().await.unwrap();
+
+    // the beneficiary must get a window to claim in
+    assert!(test
+        .create_escrow(
+            None,
+            &beneficiary.pubkey(),
+            1000,
+            release_time,
+            release_time
+        )
+        .await
+        .is_err());
+    let claimed = test
+        .create_escrow(None, &beneficiary.pubkey(), 1000, release_time, refund_time)
+        .await
+        .unwrap()
+        .pubkey();
+    let refunded = test
+        .create_escrow(None, &beneficiary.pubkey(), 2000, release_time, refund_time)
+        .await
+        .unwrap()
+        .pubkey();
+    assert_eq!(test.escrow(&claimed).await.amount, 1000);
+
+    assert!(test.claim_escrow(&claimed, &beneficiary).await.is_err());
+    assert!(test.refund_escrow(&refunded, None).await.is_err());
+
+    test.warp_to(release_time).await;
+    assert!(test.claim_escrow(&claimed, &stranger).await.is_err());
+    assert!(test.refund_escrow(&refunded, None).await.is_err());
+    test.claim_escrow(&claimed, &beneficiary).await.unwrap();
+    assert_eq!(
+        test.balance(&beneficiary.pubkey()).await,
+        CRANKER_LAMPORTS + 1000
+    );
+    assert_eq!(test.balance(&claimed).await, 0);
+
+    test.warp_to(refund_time).await;
+    assert!(test
+        .refund_escrow(&refunded, Some(&stranger))
+        .await
+        .is_err());
+    test.refund_escrow(&refunded, None).await.unwrap();
+    assert_eq!(test.balance(&refunded).await, 0);
+    assert_eq!(
+        test.balance(&beneficiary.pubkey()).await,
+        CRANKER_LAMPORTS + 1000
+    );
+}
+
+#[tokio::test]
+async fn test_change_escrow_beneficiary() {
+    let mut test = setup_program().await;
+    let release_time = test.now().await + 100;
+    let wrong_wallet = test.cranker().await.unwrap();
+    let beneficiary = test.cranker().await.unwrap();
+    let escrow = test
+        .create_escrow(
+            None,
+            &wrong_wallet.pubkey(),
+            1000,
+            release_time,
+            release_time + 100,
+        )
+        .await
+        .unwrap()
+        .pubkey();
+
+    // only the depositor redirects the escrow
+    assert!(test
+        .change_escrow_beneficiary(&escrow, Some(&wrong_wallet), &wrong_wallet.pubkey())
+        .await
+        .is_err());
+    test.change_escrow_beneficiary(&escrow, None, &beneficiary.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(test.escrow(&escrow).await.beneficiary, beneficiary.pubkey());
+
+    test.warp_to(release_time).await;
+    assert!(test
+        .change_escrow_beneficiary(&escrow, None, &wrong_wallet.pubkey())
+        .await
+        .is_err());
+    assert!(test.claim_escrow(&escrow, &wrong_wallet).await.is_err());
+    test.claim_escrow(&escrow, &beneficiary).await.unwrap();
+    assert_eq!(
+        test.balance(&beneficiary.pubkey()).await,
+        CRANKER_LAMPORTS + 1000
+    );
+}
+
+#[tokio::test]
+async fn test_escrow_delegate() {
+    let mut test = setup_program().await;
+    let release_time = test.now().await + 100;
+    let beneficiary = test.cranker().await.unwrap();
+    let bot = test.cranker().await.unwrap();
+    let escrow = test
+        .create_escrow(
+            None,
+            &beneficiary.pubkey(),
+            1000,
+            release_time,
+            release_time + 100,
+        )
+        .await
+        .unwrap()
+        .pubkey();
+    test.warp_to(release_time).await;
+
+    assert!(test.claim_escrow_as_delegate(&escrow, &bot).await.is_err());
+    test.set_escrow_delegate(&escrow, &bot.pubkey())
+        .await
+        .unwrap();
+    test.revoke_escrow_delegate(&escrow).await.unwrap();
+    assert!(test.claim_escrow_as_delegate(&escrow, &bot).await.is_err());
+
+    test.set_escrow_delegate(&escrow, &bot.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(test.escrow(&escrow).await.delegate, bot.pubkey());
+    test.claim_escrow_as_delegate(&escrow, &bot).await.unwrap();
+    // the delegate triggers the claim, the beneficiary gets the amount
+    assert_eq!(
+        test.balance(&beneficiary.pubkey()).await,
+        CRANKER_LAMPORTS + 1000
+    );
+    assert_eq!(test.balance(&bot.pubkey()).await, CRANKER_LAMPORTS);
+}
+
+#[tokio::test]
+async fn test_unlockers() {
+    let mut test = setup_program().await;
+    let timestamp = test.now().await + 100;
+    let lock = test
+        .create_lock(timestamp, [b'x'; SECRET_LENGTH], 1000, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+    let ops = test.cranker().await.unwrap();
+    let stranger = test.cranker().await.unwrap();
+
+    assert!(test
+        .add_unlocker(&lock, Some(&stranger), &stranger.pubkey())
+        .await
+        .is_err());
+    test.add_unlocker(&lock, None, &ops.pubkey()).await.unwrap();
+    test.add_unlocker(&lock, None, &stranger.pubkey())
+        .await
+        .unwrap();
+    test.remove_unlocker(&lock, &stranger.pubkey())
+        .await
+        .unwrap();
+    let unlockers = test.lock(&lock).await.unlockers;
+    assert!(unlockers.contains(&ops.pubkey()));
+    assert!(!unlockers.contains(&stranger.pubkey()));
+
+    // premature attempts are restricted too
+    assert!(test.try_unlock(&lock, None).await.is_err());
+    test.try_unlock(&lock, Some(&ops)).await.unwrap();
+
+    test.warp_to(timestamp).await;
+    assert!(test.try_unlock(&lock, None).await.is_err());
+    assert!(test.try_unlock(&lock, Some(&stranger)).await.is_err());
+    test.try_unlock(&lock, Some(&ops)).await.unwrap();
+    test.assert_lock_state(
+        &lock,
+        &ExpectedLock {
+            bounty: Some(0),
+            premature_attempts: Some(1),
+            revealed: Some(true),
+            ..Default::default()
+        },
+    )
+    .await;
+    assert_eq!(test.balance(&ops.pubkey()).await, CRANKER_LAMPORTS + 1000);
+}
+
+#[tokio::test]
+async fn test_claim_escrow_early() {
+    let mut test = setup_program().await;
+    let now = test.now().await;
+    let release_time = now + 100;
+    let beneficiary = test.cranker().await.unwrap();
+    let locked = test
+        .create_escrow(None, &beneficiary.pubkey(), 10_000, release_time, now + 200)
+        .await
+        .unwrap()
+        .pubkey();
+    let flat = test
+        .create_escrow_with_penalty(
+            None,
+            &beneficiary.pubkey(),
+            10_000,
+            release_time,
+            now + 200,
+            2_500,
+            false,
+        )
+        .await
+        .unwrap()
+        .pubkey();
+    assert!(test
+        .create_escrow_with_penalty(
+            None,
+            &beneficiary.pubkey(),
+            10_000,
+            release_time,
+            now + 200,
+            10_001,
+            false,
+        )
+        .await
+        .is_err());
+
+    // escrows without a penalty wait for their release time
+    assert!(test
+        .claim_escrow_early(&locked, &beneficiary)
+        .await
+        .is_err());
+    test.claim_escrow_early(&flat, &beneficiary).await.unwrap();
+    assert_eq!(test.balance(&flat).await, 0);
+    assert_eq!(
+        test.balance(&beneficiary.pubkey()).await,
+        CRANKER_LAMPORTS + 7_500
+    );
+}
+
+#[tokio::test]
+async fn test_expire() {
+    let mut test = setup_program().await;
+    let timestamp = test.now().await + 100;
+    let lock = test
+        .create_lock(timestamp, [b'x'; SECRET_LENGTH], 1000, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+    let recipient = Pubkey::new_unique();
+    let active_locks = test.stats().await.active_locks;
+
+    // no expiry yet, then one inside the lock window
+    assert!(test.expire(&lock, &recipient).await.is_err());
+    assert!(test.set_expiry(&lock, timestamp, &recipient).await.is_err());
+    test.set_expiry(&lock, timestamp + 50, &recipient)
+        .await
+        .unwrap();
+    assert_eq!(test.lock(&lock).await.expires_at, timestamp + 50);
+
+    test.warp_to(timestamp).await;
+    assert!(test.expire(&lock, &recipient).await.is_err());
+    // the expiry is fixed once the lock matured
+    assert!(test.set_expiry(&lock, 0, &recipient).await.is_err());
+
+    test.warp_to(timestamp + 50).await;
+    assert!(test.expire(&lock, &Pubkey::new_unique()).await.is_err());
+    let lamports = test.balance(&lock).await;
+    test.expire(&lock, &recipient).await.unwrap();
+    assert_eq!(test.balance(&lock).await, 0);
+    assert_eq!(test.balance(&recipient).await, lamports);
+    assert_eq!(test.stats().await.active_locks, active_locks - 1);
+}
+
+#[tokio::test]
+async fn test_heartbeat() {
+    let mut test = setup_program().await;
+    let now = test.now().await;
+    let lock = test
+        .create_lock(now + 100, [b'x'; SECRET_LENGTH], 1000, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+    let recipient = Pubkey::new_unique();
+    test.set_expiry(&lock, now + 150, &recipient).await.unwrap();
+
+    assert!(test.heartbeat(&lock).await.is_err());
+    assert!(test.set_heartbeat(&lock, -1).await.is_err());
+    test.set_heartbeat(&lock, 200).await.unwrap();
+
+    // each heartbeat defers maturity, the expiry window keeping its length
+    test.warp_to(now + 50).await;
+    test.heartbeat(&lock).await.unwrap();
+    let data = test.lock(&lock).await;
+    assert_eq!(data.timestamp, now + 250);
+    assert_eq!(data.expires_at, now + 300);
+    test.warp_to(now + 240).await;
+    test.try_unlock(&lock, None).await.unwrap();
+    test.assert_lock_state(
+        &lock,
+        &ExpectedLock {
+            revealed: Some(false),
+            ..Default::default()
+        },
+    )
+    .await;
+
+    // once a heartbeat is missed the lock unlocks as usual
+    test.warp_to(now + 250).await;
+    assert!(test.heartbeat(&lock).await.is_err());
+    test.try_unlock(&lock, None).await.unwrap();
+    test.assert_lock_state(
+        &lock,
+        &ExpectedLock {
+            revealed: Some(true),
+            ..Default::default()
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_recurrence() {
+    let mut test = setup_program().await;
+    let timestamp = test.now().await + 100;
+    let lock = test
+        .create_lock(timestamp, [b'x'; SECRET_LENGTH], 1000, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+    assert!(test
+        .set_recurrence(&lock, TimeLockAccount::RECUR_SECONDS, 0, 10)
+        .await
+        .is_err());
+    assert!(test.set_recurrence(&lock, 3, 60, 10).await.is_err());
+    test.set_recurrence(&lock, TimeLockAccount::RECUR_SECONDS, 60, 10)
+        .await
+        .unwrap();
+
+    // between two windows the attempt is premature
+    test.warp_to(timestamp + 30).await;
+    test.try_unlock(&lock, None).await.unwrap();
+    test.assert_lock_state(
+        &lock,
+        &ExpectedLock {
+            premature_attempts: Some(1),
+            revealed: Some(false),
+            ..Default::default()
+        },
+    )
+    .await;
+    test.warp_to(timestamp + 60).await;
+    test.try_unlock(&lock, None).await.unwrap();
+    test.assert_lock_state(
+        &lock,
+        &ExpectedLock {
+            revealed: Some(true),
+            ..Default::default()
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_callback() {
+    let mut test = setup_program().await;
+    let timestamp = test.now().await + 100;
+    let lock = test
+        .create_lock(timestamp, [b'x'; SECRET_LENGTH], 1000, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+
+    // only programs can be called
+    assert!(test
+        .set_callback(&lock, &Pubkey::new_unique(), &[], b"revealed ")
+        .await
+        .is_err());
+    // the memo program logs the secret, the callback PDA signing
+    test.set_callback(&lock, &MEMO_PROGRAM_ID, &[], b"revealed ")
+        .await
+        .unwrap();
+    assert!(test.lock(&lock).await.has_callback);
+    assert_eq!(test.callback(&lock).await.data(), b"revealed ");
+    assert_eq!(
+        test.callback(&lock).await.bump,
+        callback_address(&test.program_id, &lock).1
+    );
+
+    test.warp_to(timestamp).await;
+    // the call needs its accounts
+    assert!(test.try_unlock(&lock, None).await.is_err());
+    test.try_unlock_with_callback(&lock, None).await.unwrap();
+    test.assert_lock_state(
+        &lock,
+        &ExpectedLock {
+            revealed: Some(true),
+            ..Default::default()
+        },
+    )
+    .await;
+    // no longer in progress once the call returned
+    assert!(!test.lock(&lock).await.in_progress);
+}
+
+#[tokio::test]
+async fn test_unlock_instruction() {
+    let mut test = setup_program().await;
+    let timestamp = test.now().await + 100;
+    let lock = test
+        .create_lock(timestamp, [b'x'; SECRET_LENGTH], 1000, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+
+    // a memo the callback PDA signs, executed as is
+    let signer = callback_address(&test.program_id, &lock).0;
+    let memo = Instruction::new_with_bytes(
+        MEMO_PROGRAM_ID,
+        b"released",
+        vec![AccountMeta::new_readonly(signer, true)],
+    );
+    test.set_unlock_instruction(&lock, &memo).await.unwrap();
+    let callback = test.callback(&lock).await;
+    assert!(callback.verbatim);
+    assert_eq!(callback.data(), b"released");
+
+    test.warp_to(timestamp).await;
+    test.try_unlock_with_callback(&lock, None).await.unwrap();
+    test.assert_lock_state(
+        &lock,
+        &ExpectedLock {
+            revealed: Some(true),
+            ..Default::default()
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_clear_callback() {
+    let mut test = setup_program().await;
+    let timestamp = test.now().await + 100;
+    let lock = test
+        .create_lock(timestamp, [b'x'; SECRET_LENGTH], 1000, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+    // a call that always fails, the system program cannot debit the program
+    // owned callback PDA
+    let callback = callback_address(&test.program_id, &lock).0;
+    let transfer = system_instruction::transfer(&callback, &Pubkey::new_unique(), 1);
+    test.set_unlock_instruction(&lock, &transfer).await.unwrap();
+
+    // kept until the lock matures
+    let err = test.clear_callback(&lock).await.unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TimeLockError::LockNotMatured as u32)
+        )
+    );
+    test.warp_to(timestamp).await;
+    assert!(test.try_unlock_with_callback(&lock, None).await.is_err());
+
+    // only by the authority
+    let intruder = Keypair::new();
+    let ix = clear_callback(&test.program_id, &lock, &intruder.pubkey());
+    let err = test.process(&[ix], &[&intruder]).await.unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TimeLockError::AuthorityRequired as u32)
+        )
+    );
+    test.clear_callback(&lock).await.unwrap();
+    assert!(!test.lock(&lock).await.has_callback);
+    assert_eq!(test.balance(&callback).await, 0);
+
+    // the secret reveals without the call
+    test.try_unlock(&lock, None).await.unwrap();
+    test.assert_lock_state(
+        &lock,
+        &ExpectedLock {
+            revealed: Some(true),
+            ..Default::default()
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_condition() {
+    let mut test = setup_program().await;
+    let timestamp = test.now().await + 100;
+    let lock = test
+        .create_lock(timestamp, [b'x'; SECRET_LENGTH], 0, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+    // the condition is the attempt counter of another lock reaching 1
+    let other = test
+        .create_lock(timestamp, [b'y'; SECRET_LENGTH], 0, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+    let attempts = (8 + SECRET_LENGTH + 8) as u32;
+    test.set_condition(&lock, &other, attempts, &1u32.to_le_bytes())
+        .await
+        .unwrap();
+    assert!(test.lock(&lock).await.has_condition());
+
+    test.warp_to(timestamp).await;
+    // mature, but the condition does not hold yet
+    assert!(test
+        .try_unlock_with_condition(&lock, None, &other)
+        .await
+        .is_err());
+    // nor can it be skipped
+    assert!(test.try_unlock(&lock, None).await.is_err());
+
+    test.try_unlock(&other, None).await.unwrap();
+    test.try_unlock_with_condition(&lock, None, &other)
+        .await
+        .unwrap();
+    test.assert_lock_state(
+        &lock,
+        &ExpectedLock {
+            revealed: Some(true),
+            ..Default::default()
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_predecessor() {
+    let mut test = setup_program().await;
+    let timestamp = test.now().await + 100;
+    let first = test
+        .create_lock(timestamp, [b'x'; SECRET_LENGTH], 0, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+    let second = test
+        .create_lock(timestamp, [b'y'; SECRET_LENGTH], 0, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+    assert!(test.set_predecessor(&second, &second).await.is_err());
+    test.set_predecessor(&second, &first).await.unwrap();
+    assert_eq!(test.lock(&second).await.predecessor, first);
+
+    test.warp_to(timestamp).await;
+    // both matured, the first has not revealed yet
+    assert!(test
+        .try_unlock_with_predecessor(&second, None, &first)
+        .await
+        .is_err());
+    assert!(test.try_unlock(&second, None).await.is_err());
+
+    test.try_unlock(&first, None).await.unwrap();
+    test.try_unlock_with_predecessor(&second, None, &first)
+        .await
+        .unwrap();
+    test.assert_lock_state(
+        &second,
+        &ExpectedLock {
+            revealed: Some(true),
+            ..Default::default()
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_audit_trail() {
+    let mut test = setup_program().await;
+    let timestamp = test.now().await + 100;
+    let lock = test
+        .create_lock(timestamp, [b'x'; SECRET_LENGTH], 1000, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+    let program_id = test.program_id;
+    let intruder = test.cranker().await.unwrap();
+    let ix = enable_audit_trail(&program_id, &lock, &intruder.pubkey());
+    assert!(test.process(&[ix], &[&intruder]).await.is_err());
+
+    test.enable_audit_trail(&lock).await.unwrap();
+    assert!(test.lock(&lock).await.has_audit_trail);
+    let trail = test.audit_trail(&lock).await;
+    assert_eq!((trail.lock, trail.total), (lock, 0));
+    assert_eq!(trail.bump, audit_address(&program_id, &lock).1);
+
+    // mutations must now carry the audit trail
+    let err = test.set_heartbeat(&lock, 200).await.unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TimeLockError::AuditTrailMissing as u32)
+        )
+    );
+    let authority = test.context.payer.pubkey();
+    let ix = set_heartbeat(&program_id, &lock, &authority, 200);
+    let data = ix.data.clone();
+    test.process(&[with_audit_trail(&program_id, ix, &lock)], &[])
+        .await
+        .unwrap();
+    // premature attempts are not recorded, nor the deposits of others, with
+    // which anyone could fill the trail
+    test.try_unlock(&lock, None).await.unwrap();
+    let stranger = test.cranker().await.unwrap();
+    for _ in 0..3 {
+        test.top_up(&lock, Some(&stranger), 1).await.unwrap();
+    }
+    assert_eq!(test.audit_trail(&lock).await.total, 1);
+
+    test.warp_to(timestamp).await;
+    let cranker = test.cranker().await.unwrap();
+    let ix = try_unlock(&program_id, &lock, Some(&cranker.pubkey()));
+    test.process(&[with_audit_trail(&program_id, ix, &lock)], &[&cranker])
+        .await
+        .unwrap();
+    let trail = test.audit_trail(&lock).await;
+    assert_eq!(trail.total, 2);
+    let [heartbeat, reveal] = trail.stored() else {
+        panic!("two entries stored");
+    };
+    assert_eq!(heartbeat.instruction, data[..8]);
+    assert_eq!(heartbeat.actor, authority);
+    assert_eq!(reveal.actor, cranker.pubkey());
+    assert!(reveal.slot >= heartbeat.slot);
+    assert!(reveal.unix_timestamp >= timestamp);
+}
+
+#[tokio::test]
+async fn test_claim_commit() {
+    let mut test = setup_program().await;
+    let timestamp = test.now().await + 100;
+    let bounty = 5000;
+    let lock = test
+        .create_lock(timestamp, [b'x'; SECRET_LENGTH], bounty, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+    test.require_claim_commit(&lock).await.unwrap();
+    assert!(test.lock(&lock).await.claim_commit_required);
+
+    let committer = test.cranker().await.unwrap();
+    let destination = test.cranker().await.unwrap().pubkey();
+    let salt = [3; 32];
+    test.commit_claim(&lock, &committer, &destination, &salt)
+        .await
+        .unwrap();
+    // revealed in the slot of the commitment, a copied claim would go through
+    let err = test
+        .reveal_claim(&lock, &committer.pubkey(), &destination, salt)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TimeLockError::ClaimCommitmentTooRecent as u32)
+        )
+    );
+
+    test.next_slot().await;
+    test.warp_to(timestamp).await;
+    // a cranker reveals the secret but is not paid
+    let cranker = test.cranker().await.unwrap();
+    test.try_unlock(&lock, Some(&cranker)).await.unwrap();
+    let data = test.lock(&lock).await;
+    assert_ne!(data.revealed_at, 0);
+    assert_eq!(data.bounty, bounty);
+
+    // another destination does not match the commitment
+    let err = test
+        .reveal_claim(&lock, &committer.pubkey(), &cranker.pubkey(), salt)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TimeLockError::ClaimCommitmentMismatch as u32)
+        )
+    );
+    let committer_balance = test.balance(&committer.pubkey()).await;
+    test.reveal_claim(&lock, &committer.pubkey(), &destination, salt)
+        .await
+        .unwrap();
+    assert_eq!(test.balance(&destination).await, CRANKER_LAMPORTS + bounty);
+    assert_eq!(test.lock(&lock).await.bounty, 0);
+    // the commitment is closed, its rent refunded
+    let commitment = claim_commitment_address(&test.program_id, &lock, &committer.pubkey()).0;
+    assert_eq!(test.balance(&commitment).await, 0);
+    assert!(test.balance(&committer.pubkey()).await > committer_balance);
+}
+
+#[tokio::test]
+async fn test_nonced_lock() {
+    let mut test = setup_program().await;
+    let payer = test.context.payer.pubkey();
+    let timestamp = test.now().await + 100;
+    let secret = [b'n'; SECRET_LENGTH];
+    test.create_nonced_lock(0, timestamp, secret, 5000)
+        .await
+        .unwrap();
+    let first = test
+        .lock(&lock_address(&test.program_id, &payer, 0).0)
+        .await;
+    assert_eq!(first.authority, payer);
+    assert_eq!(first.bounty, 5000);
+    assert_eq!(test.lock_nonce(&payer).await.next, 1);
+
+    // the captured creation is replayed with a spent nonce
+    let err = test
+        .create_nonced_lock(0, timestamp, secret, 5000)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TimeLockError::NonceMismatch as u32)
+        )
+    );
+    // nor can a nonce be skipped
+    assert!(test
+        .create_nonced_lock(2, timestamp, secret, 0)
+        .await
+        .is_err());
+
+    test.create_nonced_lock(1, timestamp, secret, 0)
+        .await
+        .unwrap();
+    assert_eq!(
+        test.lock(&lock_address(&test.program_id, &payer, 1).0)
+            .await
+            .timestamp,
+        timestamp
+    );
+    assert_eq!(test.lock_nonce(&payer).await.next, 2);
+    assert_eq!(test.stats().await.total_locks, 2);
+}
+
+#[tokio::test]
+async fn test_too_many_accounts() {
+    let mut test = setup_program().await;
+    let timestamp = test.now().await + 100;
+    let lock = test
+        .create_lock(timestamp, [b'x'; SECRET_LENGTH], 0, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+    let mut ix = try_unlock(&test.program_id, &lock, None);
+    // repeated, the accounts fit the transaction
+    ix.accounts.resize(
+        MAX_ACCOUNTS + 1,
+        AccountMeta::new_readonly(system_program::id(), false),
+    );
+    let err = test.process(&[ix], &[]).await.unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TimeLockError::TooManyAccounts as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_distributor() {
+    let mut test = setup_program().await;
+    let unlock_time = test.now().await + 100;
+    let early = test.cranker().await.unwrap().pubkey();
+    let late = test.cranker().await.unwrap().pubkey();
+    let claims = [(early, 1000, unlock_time), (late, 2000, unlock_time + 100)];
+    let leaves: Vec<_> = claims
+        .iter()
+        .enumerate()
+        .map(|(i, (recipient, amount, time))| merkle::leaf(i as u32, recipient, *amount, *time))
+        .collect();
+    let distributor = test
+        .create_distributor(merkle::root(&leaves), 2, 3000)
+        .await
+        .unwrap()
+        .pubkey();
+    let proof = |i| merkle::proof(&leaves, i).unwrap();
+
+    // locked until its unlock time
+    assert!(test
+        .claim_with_proof(&distributor, &early, 0, 1000, unlock_time, &proof(0))
+        .await
+        .is_err());
+    test.warp_to(unlock_time).await;
+    // the leaf fixes the recipient and the amount
+    assert!(test
+        .claim_with_proof(&distributor, &late, 0, 1000, unlock_time, &proof(0))
+        .await
+        .is_err());
+    assert!(test
+        .claim_with_proof(&distributor, &early, 0, 2000, unlock_time, &proof(0))
+        .await
+        .is_err());
+    test.claim_with_proof(&distributor, &early, 0, 1000, unlock_time, &proof(0))
+        .await
+        .unwrap();
+    assert_eq!(test.balance(&early).await, CRANKER_LAMPORTS + 1000);
+    assert!(test.distributor(&distributor).await.is_claimed(0));
+    // once
+    assert!(test
+        .claim_with_proof(&distributor, &early, 0, 1000, unlock_time, &proof(0))
+        .await
+        .is_err());
+    assert!(test
+        .claim_with_proof(&distributor, &late, 1, 2000, unlock_time + 100, &proof(1))
+        .await
+        .is_err());
+
+    test.warp_to(unlock_time + 100).await;
+    test.claim_with_proof(&distributor, &late, 1, 2000, unlock_time + 100, &proof(1))
+        .await
+        .unwrap();
+    assert_eq!(test.balance(&late).await, CRANKER_LAMPORTS + 2000);
+    assert_eq!(test.distributor(&distributor).await.claimed_amount, 3000);
+}
+
+#[tokio::test]
+async fn test_payroll() {
+    let mut test = setup_program().await;
+    let start_time = test.now().await + 100;
+    let end_time = start_time + 1000;
+    let mut team = vec![];
+    for weight in [1, 1, 2] {
+        team.push(PayrollShare {
+            beneficiary: test.cranker().await.unwrap().pubkey(),
+            weight,
+        });
+    }
+    let payroll = test
+        .create_payroll(4000, start_time, end_time, 3)
+        .await
+        .unwrap()
+        .pubkey();
+    let paid = |team: &[PayrollShare], i: usize| team[i].beneficiary;
+
+    // the shares are only final once every entry is added
+    test.add_payroll_entries(&payroll, &team[..2])
+        .await
+        .unwrap();
+    test.warp_to(end_time).await;
+    let err = test
+        .claim_payroll(&payroll, &paid(&team, 0), 0)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TimeLockError::PayrollIncomplete as u32)
+        )
+    );
+    let err = test
+        .add_payroll_entries(&payroll, &team[1..])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TimeLockError::PayrollFull as u32)
+        )
+    );
+    test.add_payroll_entries(&payroll, &team[2..])
+        .await
+        .unwrap();
+    let data = test.payroll(&payroll).await;
+    assert_eq!((data.added, data.total_weight), (3, 4));
+
+    // halfway, each claims half its share, independently
+    test.warp_to(start_time + 500).await;
+    test.claim_payroll(&payroll, &paid(&team, 2), 2)
+        .await
+        .unwrap();
+    assert_eq!(test.balance(&paid(&team, 2)).await, CRANKER_LAMPORTS + 1000);
+    // the entry decides who is paid
+    assert!(test
+        .claim_payroll(&payroll, &paid(&team, 0), 2)
+        .await
+        .is_err());
+    let err = test
+        .claim_payroll(&payroll, &paid(&team, 2), 2)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TimeLockError::PayrollNothingVested as u32)
+        )
+    );
+
+    // not closed while shares are left to claim
+    let err = test.close_payroll(&payroll).await.unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TimeLockError::PayrollNotSettled as u32)
+        )
+    );
+
+    test.warp_to(end_time).await;
+    for i in 0..3 {
+        test.claim_payroll(&payroll, &paid(&team, i), i as u32)
+            .await
+            .unwrap();
+    }
+    assert_eq!(test.balance(&paid(&team, 0)).await, CRANKER_LAMPORTS + 1000);
+    assert_eq!(test.balance(&paid(&team, 2)).await, CRANKER_LAMPORTS + 2000);
+    let data = test.payroll(&payroll).await;
+    assert_eq!(data.entries.iter().map(|e| e.claimed).sum::<u64>(), 4000);
+
+    // only the authority closes it, once settled
+    let intruder = Keypair::new();
+    let ix = close_payroll(&test.program_id, &payroll, &intruder.pubkey());
+    assert!(test.process(&[ix], &[&intruder]).await.is_err());
+    test.close_payroll(&payroll).await.unwrap();
+    assert_eq!(test.balance(&payroll).await, 0);
+}
+
+#[tokio::test]
+async fn test_schedule_template_payroll() {
+    let mut test = setup_program().await;
+    let err = test
+        .create_schedule_template(2500, 0, 0, 3)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TimeLockError::InvalidScheduleTemplate as u32)
+        )
+    );
+    // a quarter at TGE, the rest over three monthly releases
+    let template = test
+        .create_schedule_template(2500, 0, 1, 3)
+        .await
+        .unwrap()
+        .pubkey();
+    assert_eq!(test.schedule_template(&template).await.periods, 3);
+
+    let start_time = test.now().await + 100;
+    let beneficiary = test.cranker().await.unwrap().pubkey();
+    let payroll = test
+        .create_templated_payroll(&template, 4000, start_time, 1)
+        .await
+        .unwrap()
+        .pubkey();
+    test.add_payroll_entries(
+        &payroll,
+        &[PayrollShare {
+            beneficiary,
+            weight: 1,
+        }],
+    )
+    .await
+    .unwrap();
+    let data = test.payroll(&payroll).await;
+    assert_eq!(data.template, template);
+
+    test.warp_to(start_time).await;
+    // the template decides the vesting, so it must be passed
+    let program_id = test.program_id;
+    let ix = claim_payroll(&program_id, &payroll, &beneficiary, 0, None);
+    assert!(test.process(&[ix], &[]).await.is_err());
+    test.claim_payroll(&payroll, &beneficiary, 0).await.unwrap();
+    assert_eq!(test.balance(&beneficiary).await, CRANKER_LAMPORTS + 1000);
+
+    test.warp_to(data.end_time).await;
+    test.claim_payroll(&payroll, &beneficiary, 0).await.unwrap();
+    assert_eq!(test.balance(&beneficiary).await, CRANKER_LAMPORTS + 4000);
+}
+
+#[tokio::test]
+async fn test_forged_schedule_template() {
+    let mut test = setup_program().await;
+    let start_time = test.now().await + 100;
+    let program_id = test.program_id;
+    let mut forge = |owner, tge_bps| {
+        let template = ScheduleTemplateAccount {
+            authority: Pubkey::new_unique(),
+            tge_bps,
+            cliff_months: 0,
+            period_months: 0,
+            periods: 0,
+        };
+        let address = Pubkey::new_unique();
+        let account = Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: borsh::to_vec(&template).unwrap(),
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        };
+        test.context
+            .set_account(&address, &AccountSharedData::from(account));
+        address
+    };
+    // a template the program did not create
+    let foreign = forge(system_program::id(), 10_000);
+    // nor does it hold template terms past the whole amount
+    let invalid = forge(program_id, 20_000);
+
+    let err = test
+        .create_templated_payroll(&foreign, 4000, start_time, 1)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(0, InstructionError::IncorrectProgramId)
+    );
+    let err = test
+        .create_templated_payroll(&invalid, 4000, start_time, 1)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TimeLockError::InvalidScheduleTemplate as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_vault() {
+    let mut test = setup_program().await;
+    let release_time = test.now().await + 100;
+    let first = test.cranker().await.unwrap();
+    let second = test.cranker().await.unwrap();
+    let vault = test.create_vault(3000).await.unwrap().pubkey();
+
+    let claimed = test
+        .create_vault_lock(&vault, &first.pubkey(), 1000, release_time, false)
+        .await
+        .unwrap()
+        .pubkey();
+    test.create_vault_lock(&vault, &second.pubkey(), 1500, release_time, false)
+        .await
+        .unwrap();
+    let vault_data = test.vault(&vault).await;
+    assert_eq!((vault_data.amount, vault_data.allocated), (3000, 2500));
+    let stats = test.vault_stats(&vault).await;
+    assert_eq!((stats.grants, stats.granted), (2, 2500));
+    // the release may fall in the next week
+    assert_eq!(stats.upcoming(2), 2500);
+    // only the unallocated lamports back new locks or withdrawals
+    assert!(test
+        .create_vault_lock(&vault, &second.pubkey(), 501, release_time, false)
+        .await
+        .is_err());
+    assert!(test.withdraw_unallocated(&vault, 501).await.is_err());
+
+    assert!(test.claim_vault_lock(&claimed, &first).await.is_err());
+    test.warp_to(release_time).await;
+    assert!(test.claim_vault_lock(&claimed, &second).await.is_err());
+    test.claim_vault_lock(&claimed, &first).await.unwrap();
+    assert_eq!(test.balance(&first.pubkey()).await, CRANKER_LAMPORTS + 1000);
+    assert_eq!(test.balance(&claimed).await, 0);
+    let vault_data = test.vault(&vault).await;
+    assert_eq!((vault_data.amount, vault_data.allocated), (2000, 1500));
+    let stats = test.vault_stats(&vault).await;
+    // the release week is the current one, not yet vested
+    assert_eq!((stats.vested, stats.claimed), (0, 1000));
+    assert_eq!(stats.upcoming(1), 2500);
+
+    test.withdraw_unallocated(&vault, 500).await.unwrap();
+    assert_eq!(test.vault(&vault).await.unallocated(), 0);
+}
+
+#[tokio::test]
+async fn test_split_lock() {
+    let mut test = setup_program().await;
+    let release_time = test.now().await + 100;
+    let seller = test.cranker().await.unwrap();
+    let buyer = test.cranker().await.unwrap();
+    let vault = test.create_vault(3000).await.unwrap().pubkey();
+    let grant = test
+        .create_vault_lock(&vault, &seller.pubkey(), 3000, release_time, false)
+        .await
+        .unwrap()
+        .pubkey();
+
+    // only the beneficiary splits, and both parts keep an amount
+    assert!(test
+        .split_lock(&grant, &buyer, &buyer.pubkey(), 1000)
+        .await
+        .is_err());
+    assert!(test
+        .split_lock(&grant, &seller, &buyer.pubkey(), 3000)
+        .await
+        .is_err());
+    let part = test
+        .split_lock(&grant, &seller, &buyer.pubkey(), 1000)
+        .await
+        .unwrap()
+        .pubkey();
+    assert_eq!(test.vault_lock(&grant).await.amount, 2000);
+    let split = test.vault_lock(&part).await;
+    assert_eq!(split.beneficiary, buyer.pubkey());
+    assert_eq!((split.amount, split.release_time), (1000, release_time));
+    // the vault still owes the same
+    assert_eq!(test.vault(&vault).await.allocated, 3000);
+
+    test.warp_to(release_time).await;
+    let seller_balance = test.balance(&seller.pubkey()).await;
+    test.claim_vault_lock(&part, &buyer).await.unwrap();
+    // the buyer gets the part, the seller the rent they paid for it
+    assert_eq!(test.balance(&buyer.pubkey()).await, CRANKER_LAMPORTS + 1000);
+    assert!(test.balance(&seller.pubkey()).await > seller_balance);
+    test.claim_vault_lock(&grant, &seller).await.unwrap();
+    assert_eq!(test.vault(&vault).await.amount, 0);
+}
+
+#[tokio::test]
+async fn test_merge_locks() {
+    let mut test = setup_program().await;
+    let release_time = test.now().await + 100;
+    let beneficiary = test.cranker().await.unwrap();
+    let vault = test.create_vault(4000).await.unwrap().pubkey();
+    let first = test
+        .create_vault_lock(&vault, &beneficiary.pubkey(), 1000, release_time, false)
+        .await
+        .unwrap()
+        .pubkey();
+    let second = test
+        .create_vault_lock(&vault, &beneficiary.pubkey(), 2000, release_time, false)
+        .await
+        .unwrap()
+        .pubkey();
+    let later = test
+        .create_vault_lock(&vault, &beneficiary.pubkey(), 1000, release_time + 1, false)
+        .await
+        .unwrap()
+        .pubkey();
+
+    // a lock with another schedule, or itself, does not merge
+    assert!(test
+        .merge_locks(&first, &later, &beneficiary)
+        .await
+        .is_err());
+    assert!(test
+        .merge_locks(&first, &first, &beneficiary)
+        .await
+        .is_err());
+    let payer = test.context.payer.pubkey();
+    let payer_balance = test.balance(&payer).await;
+    test.merge_locks(&first, &second, &beneficiary)
+        .await
+        .unwrap();
+    assert_eq!(test.vault_lock(&first).await.amount, 3000);
+    // the rent of the closed lock outweighs the fee
+    assert!(test.balance(&payer).await > payer_balance);
+    assert_eq!(test.vault(&vault).await.allocated, 4000);
+
+    test.warp_to(release_time).await;
+    test.claim_vault_lock(&first, &beneficiary).await.unwrap();
+    assert_eq!(
+        test.balance(&beneficiary.pubkey()).await,
+        CRANKER_LAMPORTS + 3000
+    );
+}
+
+#[tokio::test]
+async fn test_transfer_vault_lock() {
+    let mut test = setup_program().await;
+    let release_time = test.now().await + 100;
+    let seller = test.cranker().await.unwrap();
+    let buyer = test.cranker().await.unwrap();
+    let vault = test.create_vault(2000).await.unwrap().pubkey();
+    let locked = test
+        .create_vault_lock(&vault, &seller.pubkey(), 1000, release_time, false)
+        .await
+        .unwrap()
+        .pubkey();
+    let transferable = test
+        .create_vault_lock(&vault, &seller.pubkey(), 1000, release_time, true)
+        .await
+        .unwrap()
+        .pubkey();
+
+    assert!(test
+        .transfer_vault_lock(&locked, &seller, &buyer.pubkey())
+        .await
+        .is_err());
+    // only the beneficiary hands it over
+    assert!(test
+        .transfer_vault_lock(&transferable, &buyer, &buyer.pubkey())
+        .await
+        .is_err());
+    test.transfer_vault_lock(&transferable, &seller, &buyer.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(
+        test.vault_lock(&transferable).await.beneficiary,
+        buyer.pubkey()
+    );
+
+    test.warp_to(release_time).await;
+    assert!(test.claim_vault_lock(&transferable, &seller).await.is_err());
+    test.claim_vault_lock(&transferable, &buyer).await.unwrap();
+    assert_eq!(test.balance(&buyer.pubkey()).await, CRANKER_LAMPORTS + 1000);
+}
+
+#[tokio::test]
+async fn test_transfer_with_payment() {
+    let mut test = setup_program().await;
+    let release_time = test.now().await + 100;
+    let seller = test.cranker().await.unwrap();
+    let buyer = test.cranker().await.unwrap();
+    let vault = test.create_vault(2000).await.unwrap().pubkey();
+    let locked = test
+        .create_vault_lock(&vault, &seller.pubkey(), 1000, release_time, false)
+        .await
+        .unwrap()
+        .pubkey();
+    let transferable = test
+        .create_vault_lock(&vault, &seller.pubkey(), 1000, release_time, true)
+        .await
+        .unwrap()
+        .pubkey();
+
+    assert!(test
+        .transfer_with_payment(&locked, &seller, &buyer, 700)
+        .await
+        .is_err());
+    // a buyer who cannot pay gets nothing
+    assert!(test
+        .transfer_with_payment(&transferable, &seller, &buyer, CRANKER_LAMPORTS + 1)
+        .await
+        .is_err());
+    assert_eq!(
+        test.vault_lock(&transferable).await.beneficiary,
+        seller.pubkey()
+    );
+    test.transfer_with_payment(&transferable, &seller, &buyer, 700)
+        .await
+        .unwrap();
+    assert_eq!(
+        test.vault_lock(&transferable).await.beneficiary,
+        buyer.pubkey()
+    );
+    assert_eq!(test.balance(&seller.pubkey()).await, CRANKER_LAMPORTS + 700);
+    assert_eq!(test.balance(&buyer.pubkey()).await, CRANKER_LAMPORTS - 700);
+}
+
+#[tokio::test]
+async fn test_config() {
+    let mut test = setup_program().await;
+    let now = test.now().await;
+    let treasury = test.cranker().await.unwrap().pubkey();
+    // locks are created freely until the config exists
+    test.create_lock(now + 10, [b's'; SECRET_LENGTH], 0, false, false)
+        .await
+        .unwrap();
+
+    test.initialize_config(&treasury, 1000, 60, 3600)
+        .await
+        .unwrap();
+    assert!(test
+        .initialize_config(&treasury, 1000, 60, 3600)
+        .await
+        .is_err());
+    assert!(test
+        .create_lock(now + 59, [b's'; SECRET_LENGTH], 0, false, false)
+        .await
+        .is_err());
+    assert!(test
+        .create_lock(now + 3601, [b's'; SECRET_LENGTH], 0, false, false)
+        .await
+        .is_err());
+    let lock = test
+        .create_lock(now + 60, [b's'; SECRET_LENGTH], 0, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+    test.create_escrow(None, &Pubkey::new_unique(), 1000, now + 600, now + 1200)
+        .await
+        .unwrap();
+
+    // only the admin updates it
+    let admin = test.context.payer.insecure_clone();
+    let intruder = test.cranker().await.unwrap();
+    let mut config = test.config().await;
+    assert_eq!(config.bump, config_address(&test.program_id).1);
+    config.paused = true;
+    assert!(test.update_config(&intruder, &config).await.is_err());
+    test.update_config(&admin, &config).await.unwrap();
+    assert!(test
+        .create_lock(now + 60, [b's'; SECRET_LENGTH], 0, false, false)
+        .await
+        .is_err());
+    // a paused program still reveals the secret of a lock without bounty
+    test.warp_to(now + 60).await;
+    test.try_unlock(&lock, None).await.unwrap();
+
+    test.collect_fees().await.unwrap();
+    assert_eq!(test.balance(&treasury).await, CRANKER_LAMPORTS + 2000);
+}
+
+#[tokio::test]
+async fn test_pause() {
+    let mut test = setup_program().await;
+    let now = test.now().await;
+    let treasury = test.cranker().await.unwrap().pubkey();
+    let beneficiary = test.cranker().await.unwrap();
+    let guardian = test.cranker().await.unwrap();
+    let intruder = test.cranker().await.unwrap();
+    test.initialize_config(&treasury, 0, 0, 0).await.unwrap();
+    let admin = test.context.payer.insecure_clone();
+    let mut config = test.config().await;
+    config.guardian = guardian.pubkey();
+    test.update_config(&admin, &config).await.unwrap();
+
+    let lock = test
+        .create_lock(now + 60, [b's'; SECRET_LENGTH], 500, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+    let claimed = test
+        .create_escrow(None, &beneficiary.pubkey(), 1000, now + 60, now + 120)
+        .await
+        .unwrap()
+        .pubkey();
+    let refunded = test
+        .create_escrow(None, &beneficiary.pubkey(), 2000, now + 60, now + 120)
+        .await
+        .unwrap()
+        .pubkey();
+
+    // the admin or the guardian pauses, nobody else
+    assert!(test.set_paused(&intruder, true).await.is_err());
+    test.set_paused(&guardian, true).await.unwrap();
+    assert!(test.config().await.paused);
+    assert!(test
+        .create_lock(now + 60, [b's'; SECRET_LENGTH], 0, false, false)
+        .await
+        .is_err());
+    assert!(test
+        .create_escrow(None, &beneficiary.pubkey(), 1000, now + 60, now + 120)
+        .await
+        .is_err());
+
+    test.warp_to(now + 60).await;
+    assert!(test.claim_escrow(&claimed, &beneficiary).await.is_err());
+    assert!(test.try_unlock(&lock, Some(&intruder)).await.is_err());
+    // refunds still go through
+    test.warp_to(now + 120).await;
+    test.refund_escrow(&refunded, None).await.unwrap();
+
+    test.set_paused(&admin, false).await.unwrap();
+    test.try_unlock(&lock, Some(&intruder)).await.unwrap();
+    assert_eq!(
+        test.balance(&intruder.pubkey()).await,
+        CRANKER_LAMPORTS + 500
+    );
+}
+
+#[tokio::test]
+async fn test_claim_fee() {
+    let mut test = setup_program().await;
+    let now = test.now().await;
+    let treasury = test.cranker().await.unwrap().pubkey();
+    let beneficiary = test.cranker().await.unwrap();
+    let recipient = test.cranker().await.unwrap().pubkey();
+    test.initialize_config(&treasury, 0, 0, 0).await.unwrap();
+    let admin = test.context.payer.insecure_clone();
+    let mut config = test.config().await;
+    config.claim_fee_bps = 10_001;
+    assert!(test.update_config(&admin, &config).await.is_err());
+    config.claim_fee_bps = 250;
+    test.update_config(&admin, &config).await.unwrap();
+
+    let escrow = test
+        .create_escrow(None, &beneficiary.pubkey(), 100_000, now + 60, now + 120)
+        .await
+        .unwrap()
+        .pubkey();
+    test.warp_to(now + 60).await;
+    test.claim_escrow(&escrow, &beneficiary).await.unwrap();
+    assert_eq!(
+        test.balance(&beneficiary.pubkey()).await,
+        CRANKER_LAMPORTS + 97_500
+    );
+
+    // only the admin withdraws, no more than the fees
+    let intruder = test.cranker().await.unwrap();
+    assert!(test
+        .withdraw_fees(&intruder, &recipient, 2_500)
+        .await
+        .is_err());
+    assert!(test.withdraw_fees(&admin, &recipient, 2_501).await.is_err());
+    test.withdraw_fees(&admin, &recipient, 2_500).await.unwrap();
+    assert_eq!(test.balance(&recipient).await, CRANKER_LAMPORTS + 2_500);
+}
+
+#[tokio::test]
+async fn test_fee_exemption() {
+    let mut test = setup_program().await;
+    let now = test.now().await;
+    let treasury = test.cranker().await.unwrap().pubkey();
+    let beneficiary = test.cranker().await.unwrap();
+    let intruder = test.cranker().await.unwrap();
+    test.initialize_config(&treasury, 1000, 0, 0).await.unwrap();
+    let admin = test.context.payer.insecure_clone();
+    let mut config = test.config().await;
+    config.claim_fee_bps = 250;
+    test.update_config(&admin, &config).await.unwrap();
+
+    // only the admin exempts
+    assert!(test
+        .add_fee_exemption(&intruder, &admin.pubkey())
+        .await
+        .is_err());
+    test.add_fee_exemption(&admin, &admin.pubkey())
+        .await
+        .unwrap();
+    test.add_fee_exemption(&admin, &beneficiary.pubkey())
+        .await
+        .unwrap();
+
+    let config_lamports = test.balance(&config_address(&test.program_id).0).await;
+    let escrow = Keypair::new();
+    let ix = create_escrow(
+        &test.program_id,
+        &escrow.pubkey(),
+        &admin.pubkey(),
+        &beneficiary.pubkey(),
+        100_000,
+        now + 60,
+        now + 120,
+        0,
+        false,
+    );
+    let ix = with_fee_exemption(&test.program_id, ix, &admin.pubkey());
+    test.process(&[ix], &[&escrow]).await.unwrap();
+    assert_eq!(
+        test.balance(&config_address(&test.program_id).0).await,
+        config_lamports
+    );
+
+    test.warp_to(now + 60).await;
+    let ix = claim_escrow(
+        &test.program_id,
+        &escrow.pubkey(),
+        &beneficiary.pubkey(),
+        &admin.pubkey(),
+        None,
+    );
+    let ix = with_fee_exemption(&test.program_id, ix, &beneficiary.pubkey());
+    test.process(&[ix], &[&beneficiary]).await.unwrap();
+    assert_eq!(
+        test.balance(&beneficiary.pubkey()).await,
+        CRANKER_LAMPORTS + 100_000
+    );
+
+    // without its exemption, the beneficiary pays the claim fee again
+    assert!(test
+        .remove_fee_exemption(&intruder, &beneficiary.pubkey())
+        .await
+        .is_err());
+    test.remove_fee_exemption(&admin, &beneficiary.pubkey())
+        .await
+        .unwrap();
+    let escrow = test
+        .create_escrow(None, &beneficiary.pubkey(), 100_000, now + 90, now + 120)
+        .await
+        .unwrap()
+        .pubkey();
+    test.warp_to(now + 90).await;
+    test.claim_escrow(&escrow, &beneficiary).await.unwrap();
+    assert_eq!(
+        test.balance(&beneficiary.pubkey()).await,
+        CRANKER_LAMPORTS + 100_000 + 97_500
+    );
+}
+
+#[tokio::test]
+async fn test_admin_transfer() {
+    let mut test = setup_program().await;
+    let now = test.now().await;
+    let treasury = test.cranker().await.unwrap().pubkey();
+    let new_admin = test.cranker().await.unwrap();
+    let intruder = test.cranker().await.unwrap();
+    test.initialize_config(&treasury, 0, 0, 0).await.unwrap();
+    let admin = test.context.payer.insecure_clone();
+
+    // only the admin proposes, an expired proposal is not accepted
+    assert!(test
+        .propose_admin(&intruder, &intruder.pubkey(), now + 100)
+        .await
+        .is_err());
+    assert!(test
+        .propose_admin(&admin, &new_admin.pubkey(), now)
+        .await
+        .is_err());
+    test.propose_admin(&admin, &new_admin.pubkey(), now + 100)
+        .await
+        .unwrap();
+    assert_eq!(test.config().await.pending_admin, new_admin.pubkey());
+    assert!(test.accept_admin(&intruder).await.is_err());
+    test.warp_to(now + 101).await;
+    assert!(test.accept_admin(&new_admin).await.is_err());
+
+    // a withdrawn proposal is not accepted either
+    test.propose_admin(&admin, &new_admin.pubkey(), now + 1000)
+        .await
+        .unwrap();
+    test.propose_admin(&admin, &Pubkey::default(), 0)
+        .await
+        .unwrap();
+    assert!(test.accept_admin(&new_admin).await.is_err());
+
+    test.propose_admin(&admin, &new_admin.pubkey(), now + 1000)
+        .await
+        .unwrap();
+    test.accept_admin(&new_admin).await.unwrap();
+    let config = test.config().await;
+    assert_eq!(config.admin, new_admin.pubkey());
+    assert_eq!(config.pending_admin, Pubkey::default());
+    assert!(test.update_config(&admin, &config).await.is_err());
+    test.update_config(&new_admin, &config).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_config_update_delay() {
+    let mut test = setup_program().await;
+    let now = test.now().await;
+    let treasury = test.cranker().await.unwrap().pubkey();
+    let guardian = test.cranker().await.unwrap();
+    let intruder = test.cranker().await.unwrap();
+    test.initialize_config(&treasury, 0, 0, 0).await.unwrap();
+    let admin = test.context.payer.insecure_clone();
+    let mut config = test.config().await;
+    config.guardian = guardian.pubkey();
+    config.update_delay = 100;
+    test.update_config(&admin, &config).await.unwrap();
+
+    // updates now wait for the delay, one at a time
+    config.creation_fee = 5000;
+    assert!(test.update_config(&admin, &config).await.is_err());
+    test.queue_config_update(&admin, &config).await.unwrap();
+    assert!(test.apply_config_update().await.is_err());
+    assert!(test.queue_config_update(&admin, &config).await.is_err());
+
+    // the guardian cancels it during the window, nobody else
+    assert!(test.cancel_config_update(&intruder).await.is_err());
+    test.cancel_config_update(&guardian).await.unwrap();
+    test.warp_to(now + 100).await;
+    assert!(test.apply_config_update().await.is_err());
+    assert_eq!(test.config().await.creation_fee, 0);
+
+    let queued_at = test.now().await;
+    test.queue_config_update(&admin, &config).await.unwrap();
+    // a pause during the window outlives the update queued before it
+    test.set_paused(&guardian, true).await.unwrap();
+    test.warp_to(queued_at + 100).await;
+    // only the admin applies it
+    let program_id = test.program_id;
+    let ix = apply_config_update(&program_id, &intruder.pubkey());
+    assert!(test.process(&[ix], &[&intruder]).await.is_err());
+    test.apply_config_update().await.unwrap();
+    let config = test.config().await;
+    assert_eq!(config.creation_fee, 5000);
+    assert!(config.paused);
+}
+
+#[tokio::test]
+async fn test_clock_drift_tolerance() {
+    let mut test = setup_program().await;
+    let treasury = test.cranker().await.unwrap().pubkey();
+    test.initialize_config(&treasury, 0, 0, 0).await.unwrap();
+    let admin = test.context.payer.insecure_clone();
+    let now = test.now().await;
+    assert!(test
+        .create_lock(now, [b'n'; SECRET_LENGTH], 0, false, false)
+        .await
+        .is_err());
+
+    let mut config = test.config().await;
+    config.clock_drift_tolerance = ConfigAccount::MAX_CLOCK_DRIFT_TOLERANCE + 1;
+    assert!(test.update_config(&admin, &config).await.is_err());
+    config.clock_drift_tolerance = 30;
+    test.update_config(&admin, &config).await.unwrap();
+
+    // within the tolerance a lock landing late is created, unlockable at once
+    let now = test.now().await;
+    let late = test
+        .create_lock(now - 10, [b'l'; SECRET_LENGTH], 0, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+    assert!(test
+        .create_lock(now - 30, [b'o'; SECRET_LENGTH], 0, false, false)
+        .await
+        .is_err());
+    test.try_unlock(&late, None).await.unwrap();
+    test.assert_lock_state(
+        &late,
+        &ExpectedLock {
+            revealed: Some(true),
+            ..Default::default()
+        },
+    )
+    .await;
+
+    // unlocking stays strict
+    let timestamp = now + 100;
+    let early = test
+        .create_lock(timestamp, [b'e'; SECRET_LENGTH], 0, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+    test.warp_to(timestamp - 1).await;
+    test.try_unlock(&early, None).await.unwrap();
+    test.assert_lock_state(
+        &early,
+        &ExpectedLock {
+            revealed: Some(false),
+            ..Default::default()
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_revenue() {
+    let mut test = setup_program().await;
+    let now = test.now().await;
+    let treasury = test.cranker().await.unwrap().pubkey();
+    let beneficiary = test.cranker().await.unwrap();
+    test.initialize_config(&treasury, 1000, 0, 0).await.unwrap();
+    let admin = test.context.payer.insecure_clone();
+    let mut config = test.config().await;
+    config.claim_fee_bps = 250;
+    test.update_config(&admin, &config).await.unwrap();
+
+    test.create_lock(now + 60, [b's'; SECRET_LENGTH], 0, false, false)
+        .await
+        .unwrap();
+    let escrow = test
+        .create_escrow_with_penalty(
+            None,
+            &beneficiary.pubkey(),
+            100_000,
+            now + 60,
+            now + 120,
+            1_000,
+            false,
+        )
+        .await
+        .unwrap()
+        .pubkey();
+    // 10_000 forfeited, then 2.5% of the remaining 90_000 kept as a fee
+    test.claim_escrow_early(&escrow, &beneficiary)
+        .await
+        .unwrap();
+    test.collect_fees().await.unwrap();
+
+    let report = test.revenue().await;
+    assert_eq!(report.version, timelock_program::REVENUE_VERSION);
+    assert_eq!(report.fees_held, 0);
+    assert_eq!(report.total_creation_fees, 2000);
+    assert_eq!(report.total_claim_fees, 2250);
+    assert_eq!(report.total_withdrawn, 4250);
+    // penalties go back to the depositor, they are no revenue
+    assert_eq!(test.config().await.total_penalties, 10_000);
+}
+
+#[tokio::test]
+async fn test_immutability() {
+    let mut test = setup_program().await;
+    let treasury = test.cranker().await.unwrap().pubkey();
+    test.initialize_config(&treasury, 0, 0, 0).await.unwrap();
+    assert!(!test.immutability().await.immutable);
+    // the payer is still the upgrade authority
+    assert!(test.record_immutability().await.is_err());
+
+    test.burn_upgrade_authority(42);
+    test.record_immutability().await.unwrap();
+    let report = test.immutability().await;
+    assert_eq!(report.version, timelock_program::IMMUTABILITY_VERSION);
+    assert!(report.immutable);
+    assert_eq!(report.deploy_slot, 42);
+    assert_eq!(report.recorded_slot, test.config().await.immutable_slot);
+    // recorded once
+    assert!(test.record_immutability().await.is_err());
+}
+
+#[tokio::test]
+async fn test_verified_build() {
+    let mut test = setup_program().await;
+    let treasury = test.cranker().await.unwrap().pubkey();
+    test.initialize_config(&treasury, 0, 0, 0).await.unwrap();
+    assert_eq!(test.config().await.verified_build_hash, [0; 32]);
+
+    let (build_hash, source_commit) = ([7; 32], [9; 20]);
+    let intruder = test.cranker().await.unwrap();
+    let ix = record_verified_build(
+        &test.program_id,
+        &intruder.pubkey(),
+        build_hash,
+        source_commit,
+    );
+    assert!(test.process(&[ix], &[&intruder]).await.is_err());
+
+    // the context payer is the admin
+    let admin = test.context.payer.pubkey();
+    let ix = record_verified_build(&test.program_id, &admin, build_hash, source_commit);
+    test.process(&[ix], &[]).await.unwrap();
+    let config = test.config().await;
+    assert_eq!(config.verified_build_hash, build_hash);
+    assert_eq!(config.verified_source_commit, source_commit);
+}
+
+#[tokio::test]
+async fn test_builder() {
+    let mut test = setup_program().await;
+    let now = test.now().await;
+    let lock = Keypair::new();
+    let payer = test.context.payer.pubkey();
+    let unlocker = Pubkey::new_unique();
+    let builder = TimeLockBuilder::new(&test.program_id, &lock.pubkey(), &payer)
+        .unlock_at(now + 60)
+        .secret([b's'; SECRET_LENGTH])
+        .bounty(5000)
+        .unlocker(&unlocker)
+        .expires_at(now + 120, &payer);
+    test.process(&builder.build_instructions().unwrap(), &[&lock])
+        .await
+        .unwrap();
+
+    let state = test.lock(&lock.pubkey()).await;
+    assert_eq!(state.timestamp, now + 60);
+    assert_eq!(state.secret, [b's'; SECRET_LENGTH]);
+    assert_eq!(state.unlockers[0], unlocker);
+    assert_eq!(state.expires_at, now + 120);
+    assert_eq!(state.expiry_recipient, payer);
+    let rent = test.context.banks_client.get_rent().await.unwrap();
+    assert_eq!(test.balance(&lock.pubkey()).await, builder.lamports(&rent));
+
+    // rejected before reaching the program
+    let mut secret = [b's'; SECRET_LENGTH];
+    secret[0] = 0xff;
+    assert_eq!(
+        builder.secret(secret).build_instructions(),
+        Err(TimeLockError::SecretNotUtf8)
+    );
+}
+
+fn already_released(index: u8) -> TransactionError {
+    TransactionError::InstructionError(
+        index,
+        InstructionError::Custom(TimeLockError::AlreadyReleased as u32),
+    )
+}
+
+#[tokio::test]
+async fn test_double_release_in_one_transaction() {
+    let mut test = setup_program().await;
+    let release_time = test.now().await + 100;
+    let beneficiary = test.cranker().await.unwrap();
+    let escrow = test
+        .create_escrow(
+            None,
+            &beneficiary.pubkey(),
+            1000,
+            release_time,
+            release_time + 100,
+        )
+        .await
+        .unwrap()
+        .pubkey();
+    let vault = test.create_vault(1000).await.unwrap().pubkey();
+    let vault_lock = test
+        .create_vault_lock(&vault, &beneficiary.pubkey(), 1000, release_time, false)
+        .await
+        .unwrap()
+        .pubkey();
+    test.warp_to(release_time + 100).await;
+
+    // the first claim flags the escrow, the second in the same slot is refused
+    let payer = test.context.payer.pubkey();
+    let claim = claim_escrow(
+        &test.program_id,
+        &escrow,
+        &beneficiary.pubkey(),
+        &payer,
+        None,
+    );
+    let err = test
+        .process(&[claim.clone(), claim.clone()], &[&beneficiary])
+        .await
+        .unwrap_err();
+    assert_eq!(err.unwrap(), already_released(1));
+    let refund = refund_escrow(&test.program_id, &escrow, &payer);
+    let err = test
+        .process(&[claim, refund], &[&beneficiary])
+        .await
+        .unwrap_err();
+    assert_eq!(err.unwrap(), already_released(1));
+    assert_eq!(test.escrow(&escrow).await.amount, 1000);
+
+    let claim = claim_vault_lock(
+        &test.program_id,
+        &vault_lock,
+        &vault,
+        &beneficiary.pubkey(),
+        &payer,
+    );
+    let err = test
+        .process(&[claim.clone(), claim], &[&beneficiary])
+        .await
+        .unwrap_err();
+    assert_eq!(err.unwrap(), already_released(1));
+    assert_eq!(test.vault(&vault).await.amount, 1000);
+
+    test.claim_vault_lock(&vault_lock, &beneficiary)
+        .await
+        .unwrap();
+    test.claim_escrow(&escrow, &beneficiary).await.unwrap();
+    assert_eq!(
+        test.balance(&beneficiary.pubkey()).await,
+        CRANKER_LAMPORTS + 2000
+    );
+}
+
+/// Claims the escrow of its accounts through as many CPIs as the first byte
+/// of its data says.
+fn claim_escrow_repeatedly(_: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [program, escrow, beneficiary, depositor, config] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    for _ in 0..data[0] {
+        cpi::claim_escrow(program, escrow, beneficiary, depositor, config, None, &[])?;
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_double_release_across_cpis() {
+    let (mut program_test, program_id) = program_test();
+    let caller = Pubkey::new_unique();
+    program_test.add_program(
+        "claim_escrow_repeatedly",
+        caller,
+        processor!(claim_escrow_repeatedly),
+    );
+    let mut test = TimeLockTest::start(program_test, program_id).await;
+    let release_time = test.now().await + 100;
+    let beneficiary = test.cranker().await.unwrap();
+    let escrow = test
+        .create_escrow(
+            None,
+            &beneficiary.pubkey(),
+            1000,
+            release_time,
+            release_time + 100,
+        )
+        .await
+        .unwrap()
+        .pubkey();
+    test.warp_to(release_time).await;
+
+    let claims = |times: u8| {
+        Instruction::new_with_bytes(
+            caller,
+            &[times],
+            vec![
+                AccountMeta::new_readonly(program_id, false),
+                AccountMeta::new(escrow, false),
+                AccountMeta::new(beneficiary.pubkey(), true),
+                AccountMeta::new(test.context.payer.pubkey(), false),
+                AccountMeta::new(config_address(&program_id).0, false),
+            ],
+        )
+    };
+    let (twice, once) = (claims(2), claims(1));
+    let err = test.process(&[twice], &[&beneficiary]).await.unwrap_err();
+    assert_eq!(err.unwrap(), already_released(0));
+    assert_eq!(test.escrow(&escrow).await.amount, 1000);
+
+    test.process(&[once], &[&beneficiary]).await.unwrap();
+    assert_eq!(
+        test.balance(&beneficiary.pubkey()).await,
+        CRANKER_LAMPORTS + 1000
+    );
+    assert_eq!(test.balance(&escrow).await, 0);
+}
+
+/// Rewind the account at `address` to the first `len` bytes of its data,
+/// less the rent of the others, as it was created before its layout grew.
+async fn rewind(test: &mut TimeLockTest, address: &Pubkey, len: usize) {
+    let rent = test.context.banks_client.get_rent().await.unwrap();
+    let mut account = test
+        .context
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .unwrap();
+    account.lamports -= rent.minimum_balance(account.data.len()) - rent.minimum_balance(len);
+    account.data.truncate(len);
+    test.context
+        .set_account(address, &AccountSharedData::from(account));
+}
+
+#[tokio::test]
+async fn test_migrate_account() {
+    let mut test = setup_program().await;
+    let program_id = test.program_id;
+    test.initialize_config(&Pubkey::new_unique(), 0, 0, 0)
+        .await
+        .unwrap();
+    let timestamp = test.now().await + 60;
+    test.create_lock(timestamp, [1; SECRET_LENGTH], 5000, false, false)
+        .await
+        .unwrap();
+    let config = test.config().await;
+    let stats = test.stats().await;
+    let config_key = config_address(&program_id).0;
+    let stats_key = stats_address(&program_id).0;
+    rewind(&mut test, &config_key, 227).await;
+    rewind(&mut test, &stats_key, 24).await;
+
+    // the program no longer reads them until they are migrated
+    assert!(test
+        .create_lock(timestamp, [2; SECRET_LENGTH], 5000, false, false)
+        .await
+        .is_err());
+
+    test.migrate_account(&config_key).await.unwrap();
+    test.migrate_account(&stats_key).await.unwrap();
+    assert_eq!(test.config().await, config);
+    assert_eq!(test.stats().await, stats);
+    let rent = test.context.banks_client.get_rent().await.unwrap();
+    assert_eq!(
+        test.balance(&stats_key).await,
+        rent.minimum_balance(TimeLockStats::LEN)
+    );
+    test.create_lock(timestamp, [2; SECRET_LENGTH], 5000, false, false)
+        .await
+        .unwrap();
+    assert_eq!(test.stats().await.total_locks, stats.total_locks + 1);
+
+    // a migrated account has nothing left to migrate
+    let err = test.migrate_account(&stats_key).await.unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TimeLockError::NotMigratable as u32)
+        )
+    );
+    // nor is an account of a legacy size at another address grown as one
+    let forged = Pubkey::new_unique();
+    let account = Account {
+        lamports: LAMPORTS_PER_SOL,
+        data: vec![0; 24],
+        owner: program_id,
+        executable: false,
+        rent_epoch: 0,
+    };
+    test.context
+        .set_account(&forged, &AccountSharedData::from(account));
+    let err = test.migrate_account(&forged).await.unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
+    );
+
+    // time locks are keypair accounts, told apart by their data
+    let lock = test
+        .create_lock(timestamp, [3; SECRET_LENGTH], 5000, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+    let expected = test.lock(&lock).await;
+    let balance = test.balance(&lock).await;
+    rewind(&mut test, &lock, 649).await;
+    test.migrate_account(&lock).await.unwrap();
+    assert_eq!(test.lock(&lock).await, expected);
+    assert_eq!(test.balance(&lock).await, balance);
+    test.enable_audit_trail(&lock).await.unwrap();
+    assert!(test.lock(&lock).await.has_audit_trail);
+
+    let lock = test
+        .create_lock(timestamp, [4; SECRET_LENGTH], 5000, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+    let expected = test.lock(&lock).await;
+    rewind(&mut test, &lock, 650).await;
+    test.migrate_account(&lock).await.unwrap();
+    assert_eq!(test.lock(&lock).await, expected);
+    test.require_claim_commit(&lock).await.unwrap();
+    assert!(test.lock(&lock).await.claim_commit_required);
+
+    let expected = test.lock(&lock).await;
+    rewind(&mut test, &lock, 651).await;
+    test.migrate_account(&lock).await.unwrap();
+    assert_eq!(test.lock(&lock).await, expected);
+    assert!(!expected.in_progress);
+
+    rewind(&mut test, &lock, 652).await;
+    test.migrate_account(&lock).await.unwrap();
+    assert_eq!(test.lock(&lock).await, expected);
+    assert_eq!(expected.funded, 0);
+
+    // the first locks held only their timestamp and secret
+    let lock = test
+        .create_lock(timestamp, [5; SECRET_LENGTH], 0, false, false)
+        .await
+        .unwrap()
+        .pubkey();
+    rewind(&mut test, &lock, 8 + SECRET_LENGTH).await;
+    test.migrate_account(&lock).await.unwrap();
+    let migrated = test.lock(&lock).await;
+    assert_eq!(
+        (migrated.timestamp, migrated.secret, migrated.bounty),
+        (timestamp, [5; SECRET_LENGTH], 0)
+    );
+    test.warp_past(timestamp).await;
+    test.try_unlock(&lock, None).await.unwrap();
+    assert_ne!(test.lock(&lock).await.revealed_at, 0);
+}
+
+#[tokio::test]
+async fn test_migrate_first_locks() {
+    /// A time lock as the first deployment encoded it
+    #[derive(BorshSerialize)]
+    struct FirstTimeLockAccount {
+        timestamp: i64,
+        secret: [u8; SECRET_LENGTH],
+    }
+
+    /// A time lock once it escrowed a bounty, before it counted its attempts
+    #[derive(BorshSerialize)]
+    struct BountyTimeLockAccount {
+        timestamp: i64,
+        secret: [u8; SECRET_LENGTH],
+        bounty: u64,
+    }
+
+    let mut test = setup_program().await;
+    let rent = test.context.banks_client.get_rent().await.unwrap();
+    let timestamp = test.now().await + 100;
+    let first = borsh::to_vec(&FirstTimeLockAccount {
+        timestamp,
+        secret: [6; SECRET_LENGTH],
+    })
+    .unwrap();
+    let bountied = borsh::to_vec(&BountyTimeLockAccount {
+        timestamp,
+        secret: [7; SECRET_LENGTH],
+        bounty: 5000,
+    })
+    .unwrap();
+    assert_eq!((first.len(), bountied.len()), (264, 272));
+
+    let mut locks = vec![];
+    for (data, bounty) in [(first, 0), (bountied, 5000)] {
+        let lock = Pubkey::new_unique();
+        let account = Account {
+            lamports: rent.minimum_balance(data.len()) + bounty,
+            data,
+            owner: test.program_id,
+            executable: false,
+            rent_epoch: 0,
+        };
+        test.context
+            .set_account(&lock, &AccountSharedData::from(account));
+        // the layout they were written in no longer decodes
+        assert!(test.try_unlock(&lock, None).await.is_err());
+        test.migrate_account(&lock).await.unwrap();
+        test.assert_lock_state(
+            &lock,
+            &ExpectedLock {
+                timestamp: Some(timestamp),
+                bounty: Some(bounty),
+                attempts: Some(0),
+                revealed: Some(false),
+                ..Default::default()
+            },
+        )
+        .await;
+        assert_eq!(
+            test.balance(&lock).await,
+            rent.minimum_balance(TimeLockAccount::LEN) + bounty
+        );
+        locks.push(lock);
+    }
+
+    test.warp_past(timestamp).await;
+    let cranker = test.cranker().await.unwrap();
+    for (lock, secret) in locks.iter().zip([[6; SECRET_LENGTH], [7; SECRET_LENGTH]]) {
+        test.try_unlock(lock, Some(&cranker)).await.unwrap();
+        let migrated = test.lock(lock).await;
+        assert_eq!(migrated.secret, secret);
+        assert_ne!(migrated.revealed_at, 0);
+    }
+    // the escrowed bounty survived the migration
+    assert_eq!(
+        test.balance(&cranker.pubkey()).await,
+        CRANKER_LAMPORTS + 5000
+    );
+}
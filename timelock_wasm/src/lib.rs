@@ -0,0 +1,159 @@
+//! Browser bindings of the time lock interface: PDAs, instruction builders
+//! and account decoders, without any RPC transport. Build them with
+//! `wasm-pack build timelock_wasm --target web`.
+//!
+//! Pubkeys are passed as base58 strings and `i64`/`u64` as `BigInt`.
+//! Instructions are returned as
+//! `{ programId, accounts: [{ pubkey, isSigner, isWritable }], data }`, ready
+//! for `new TransactionInstruction(...)` once the keys are wrapped. Decoded
+//! accounts are plain objects whose pubkeys are arrays of 32 bytes.
+
+use std::str::FromStr as _;
+
+use borsh::BorshDeserialize;
+use serde::Serialize;
+use solana_program::instruction::Instruction;
+use solana_program::pubkey::Pubkey;
+use timelock_interface::builder::TimeLockBuilder;
+use timelock_interface::{instruction, pda};
+use timelock_interface::{
+    ConfigAccount, EscrowAccount, TimeLockAccount, VaultLockAccount, SECRET_LENGTH,
+};
+use wasm_bindgen::prelude::*;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsInstruction {
+    program_id: String,
+    accounts: Vec<JsAccountMeta>,
+    data: Vec<u8>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsAccountMeta {
+    pubkey: String,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+impl From<Instruction> for JsInstruction {
+    fn from(ix: Instruction) -> Self {
+        Self {
+            program_id: ix.program_id.to_string(),
+            accounts: ix
+                .accounts
+                .into_iter()
+                .map(|meta| JsAccountMeta {
+                    pubkey: meta.pubkey.to_string(),
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: ix.data,
+        }
+    }
+}
+
+fn pubkey(key: &str) -> Result<Pubkey, JsError> {
+    Pubkey::from_str(key).map_err(|e| JsError::new(&format!("invalid pubkey {key}: {e}")))
+}
+
+fn to_js(value: &impl Serialize) -> Result<JsValue, JsError> {
+    serde_wasm_bindgen::to_value(value).map_err(|e| JsError::new(&e.to_string()))
+}
+
+fn decode<T: BorshDeserialize + Serialize>(data: &[u8]) -> Result<JsValue, JsError> {
+    let account = T::try_from_slice(data).map_err(|e| JsError::new(&e.to_string()))?;
+    to_js(&account)
+}
+
+/// Address of the program config PDA
+#[wasm_bindgen(js_name = configAddress)]
+pub fn config_address(program_id: &str) -> Result<String, JsError> {
+    Ok(pda::config_address(&pubkey(program_id)?).0.to_string())
+}
+
+/// Address of the global statistics PDA
+#[wasm_bindgen(js_name = statsAddress)]
+pub fn stats_address(program_id: &str) -> Result<String, JsError> {
+    Ok(pda::stats_address(&pubkey(program_id)?).0.to_string())
+}
+
+/// Address of the PDA holding the callback of `lock`
+#[wasm_bindgen(js_name = callbackAddress)]
+pub fn callback_address(program_id: &str, lock: &str) -> Result<String, JsError> {
+    Ok(pda::callback_address(&pubkey(program_id)?, &pubkey(lock)?)
+        .0
+        .to_string())
+}
+
+/// Address of the fee exemption PDA of `key`
+#[wasm_bindgen(js_name = feeExemptionAddress)]
+pub fn fee_exemption_address(program_id: &str, key: &str) -> Result<String, JsError> {
+    Ok(
+        pda::fee_exemption_address(&pubkey(program_id)?, &pubkey(key)?)
+            .0
+            .to_string(),
+    )
+}
+
+/// Instructions creating a time lock at `lock` paid by `payer`, maturing at
+/// the unix timestamp `timestamp` in seconds, see `TimeLockBuilder`
+#[wasm_bindgen(js_name = createTimeLock)]
+pub fn create_time_lock(
+    program_id: &str,
+    lock: &str,
+    payer: &str,
+    timestamp: i64,
+    secret: &[u8],
+    bounty: u64,
+) -> Result<JsValue, JsError> {
+    let secret: [u8; SECRET_LENGTH] = secret
+        .try_into()
+        .map_err(|_| JsError::new(&format!("the secret must be {SECRET_LENGTH} bytes")))?;
+    let ixs = TimeLockBuilder::new(&pubkey(program_id)?, &pubkey(lock)?, &pubkey(payer)?)
+        .unlock_at(timestamp)
+        .secret(secret)
+        .bounty(bounty)
+        .build_instructions()
+        .map_err(|e| JsError::new(&format!("{e:?}")))?;
+    to_js(&ixs.into_iter().map(JsInstruction::from).collect::<Vec<_>>())
+}
+
+/// Instruction revealing the secret of `lock`, paying its bounty to
+/// `cranker` when given
+#[wasm_bindgen(js_name = tryUnlock)]
+pub fn try_unlock(
+    program_id: &str,
+    lock: &str,
+    cranker: Option<String>,
+) -> Result<JsValue, JsError> {
+    let cranker = cranker.as_deref().map(pubkey).transpose()?;
+    let ix = instruction::try_unlock(&pubkey(program_id)?, &pubkey(lock)?, cranker.as_ref());
+    to_js(&JsInstruction::from(ix))
+}
+
+/// Decode the data of a time lock account
+#[wasm_bindgen(js_name = decodeTimeLock)]
+pub fn decode_time_lock(data: &[u8]) -> Result<JsValue, JsError> {
+    decode::<TimeLockAccount>(data)
+}
+
+/// Decode the data of an escrow account
+#[wasm_bindgen(js_name = decodeEscrow)]
+pub fn decode_escrow(data: &[u8]) -> Result<JsValue, JsError> {
+    decode::<EscrowAccount>(data)
+}
+
+/// Decode the data of a vault lock account
+#[wasm_bindgen(js_name = decodeVaultLock)]
+pub fn decode_vault_lock(data: &[u8]) -> Result<JsValue, JsError> {
+    decode::<VaultLockAccount>(data)
+}
+
+/// Decode the data of the program config
+#[wasm_bindgen(js_name = decodeConfig)]
+pub fn decode_config(data: &[u8]) -> Result<JsValue, JsError> {
+    decode::<ConfigAccount>(data)
+}